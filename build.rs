@@ -0,0 +1,21 @@
+//! Передаёт хэш коммита исходников в `RUA_GIT_COMMIT` для манифеста сборки `build.json`.
+//! Переменная, заданная снаружи (например, в CI без `.git`), имеет приоритет.
+
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=RUA_GIT_COMMIT");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    if std::env::var_os("RUA_GIT_COMMIT").is_some() {
+        return;
+    }
+    let Ok(output) = Command::new("git").args(["rev-parse", "HEAD"]).output() else {
+        return;
+    };
+    let commit = String::from_utf8_lossy(&output.stdout);
+    let commit = commit.trim();
+    if output.status.success() && !commit.is_empty() {
+        println!("cargo:rustc-env=RUA_GIT_COMMIT={commit}");
+    }
+}
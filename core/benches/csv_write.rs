@@ -0,0 +1,116 @@
+//! Скорость записи истории: `cargo bench -p rua-core --bench csv_write`.
+//!
+//! Сравнивает [`append_csv`] с прежней записью (`csv::Writer` поверх `BufWriter`, каждое поле
+//! через `to_string`) на синтетической истории из `RUA_BENCH_ROWS` строк (по умолчанию
+//! 2 000 000) и проверяет, что оба файла совпадают байт в байт.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::{TimeDelta, TimeZone, Utc};
+use rua_core::data::{Area, CsvFormat, append_csv};
+
+const DEFAULT_ROWS: usize = 2_000_000;
+const RUNS: usize = 3;
+const LAYERS: [(&str, &str); 3] = [
+    ("#a52714", "occupied_after_24_02_2022"),
+    ("#0f9d58", "liberated"),
+    ("#880e4f", "contested"),
+];
+
+fn main() {
+    let rows = std::env::var("RUA_BENCH_ROWS")
+        .ok()
+        .and_then(|rows| rows.parse().ok())
+        .unwrap_or(DEFAULT_ROWS);
+    let areas = history(rows);
+    let temp = tempfile::tempdir().expect("temp dir");
+    let dir = temp.path();
+    let baseline_path = dir.join("baseline.csv");
+    let current_path = dir.join("current.csv");
+
+    let baseline = best_of(|| write_baseline(&areas, &baseline_path));
+    let current = best_of(|| {
+        let _ = std::fs::remove_file(&current_path);
+        append_csv(&areas, &current_path, CsvFormat::default()).expect("append_csv");
+    });
+    let same = std::fs::read(&baseline_path).expect("baseline csv")
+        == std::fs::read(&current_path).expect("current csv");
+
+    assert!(same, "append_csv output differs from the baseline writer");
+    println!("rows: {rows}");
+    println!("baseline:   {}", throughput(rows, baseline));
+    println!("append_csv: {}", throughput(rows, current));
+    println!(
+        "speedup: {:.2}x",
+        baseline.as_secs_f64() / current.as_secs_f64()
+    );
+}
+
+/// Несколько слоёв на срез и срез каждые шесть часов, как в выгрузке API.
+fn history(rows: usize) -> Vec<Area> {
+    let start = Utc
+        .with_ymd_and_hms(2022, 2, 24, 0, 0, 0)
+        .single()
+        .expect("valid timestamp");
+    (0..rows)
+        .map(|row| {
+            let (hash, area_type) = LAYERS[row % LAYERS.len()];
+            let snapshot = i64::try_from(row / LAYERS.len()).expect("row fits i64");
+            #[allow(clippy::cast_precision_loss)]
+            let area = (row as f64).mul_add(0.173_205, 40_000.0);
+            let mut area = Area::new(
+                start + TimeDelta::hours(6 * snapshot),
+                hash,
+                area,
+                area_type,
+            );
+            area.percent = area.area / 6_035.5;
+            area
+        })
+        .collect()
+}
+
+/// Прежняя запись: новый `String` на каждое поле каждой строки.
+fn write_baseline(areas: &[Area], path: &Path) {
+    let file = File::create(path).expect("baseline csv");
+    let mut writer = csv::Writer::from_writer(BufWriter::new(file));
+    writer
+        .write_record(["time_index", "hash", "area", "percent", "area_type"])
+        .expect("header");
+    for area in areas {
+        writer
+            .write_record([
+                area.time_index.to_string(),
+                area.hash.clone(),
+                area.area.to_string(),
+                area.percent.to_string(),
+                area.area_type.clone(),
+            ])
+            .expect("row");
+    }
+    writer.flush().expect("flush");
+}
+
+fn best_of(mut run: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let started = Instant::now();
+            run();
+            started.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn throughput(rows: usize, elapsed: Duration) -> String {
+    #[allow(clippy::cast_precision_loss)]
+    let per_second = rows as f64 / elapsed.as_secs_f64();
+    format!(
+        "{:.3} s, {:.2} M rows/s",
+        elapsed.as_secs_f64(),
+        per_second / 1e6
+    )
+}
@@ -0,0 +1,115 @@
+//! Общие константы форматирования, чтобы не дублировать строки и единицы измерения.
+
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+pub const AREA_THOUSANDS_DIVISOR: f64 = 1000.0;
+/// Площадь Украины в км² (в границах 1991 года).
+pub const UKRAINE_AREA_SQ_KM: f64 = 603_550.0;
+
+/// Доля площади в км² от площади Украины, в процентах.
+pub fn ukraine_percent(area_sq_km: f64) -> f64 {
+    area_sq_km / UKRAINE_AREA_SQ_KM * 100.0
+}
+/// Число знаков после запятой для параметров моделей в CSV (`tune.csv`).
+pub const CSV_FLOAT_PRECISION: usize = 6;
+/// Больше знаков `f64` всё равно не различает на площадях в сотни тысяч км².
+pub const MAX_PRECISION_DECIMALS: usize = 12;
+
+/// Сколько знаков после запятой публикуется у каждого вида величин (`[precision]`): одни и те
+/// же правила для CSV, JSON и данных графика на странице. Опубликованная копия `history.csv`
+/// округляется по `area_km2` и `percent`; хранилище истории (`store_csv`) лежит вне сайта и
+/// хранит значения источника как есть.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Precision {
+    /// Площади и границы их интервалов, км².
+    pub area_km2: usize,
+    /// Доли от площади Украины, %.
+    pub percent: usize,
+    /// Темпы и изменения за сутки, км²/сутки.
+    pub rate_km2_per_day: usize,
+    /// Дисперсии состояний и прогноза модели.
+    pub variance: usize,
+    /// Соотношения площади с территориями (`ratios.json`).
+    pub ratio: usize,
+    /// Значения в данных графика, в единицах его осей (страница и `figure.json`).
+    pub chart: usize,
+}
+
+impl Default for Precision {
+    fn default() -> Self {
+        Self {
+            area_km2: 2,
+            percent: 4,
+            rate_km2_per_day: 2,
+            variance: 2,
+            ratio: 3,
+            chart: 4,
+        }
+    }
+}
+
+impl Precision {
+    pub fn area(self, value: f64) -> String {
+        format_fixed(value, self.area_km2)
+    }
+
+    pub fn percent(self, value: f64) -> String {
+        format_fixed(value, self.percent)
+    }
+
+    pub fn rate(self, value: f64) -> String {
+        format_fixed(value, self.rate_km2_per_day)
+    }
+
+    pub fn variance(self, value: f64) -> String {
+        format_fixed(value, self.variance)
+    }
+}
+
+/// Округляет до `decimals` знаков для JSON: `serde_json` пишет кратчайшую запись числа,
+/// поэтому округлённое значение так и выходит в файл.
+pub fn round_to(value: f64, decimals: usize) -> f64 {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    let scale = 10f64.powi(decimals.min(MAX_PRECISION_DECIMALS) as i32);
+    // `+ 0.0` превращает `-0.0` в `0.0`.
+    (value * scale).round() / scale + 0.0
+}
+
+/// Форматирует число для CSV с фиксированной точностью [`CSV_FLOAT_PRECISION`].
+pub fn format_csv_float(value: f64) -> String {
+    format_fixed(value, CSV_FLOAT_PRECISION)
+}
+
+/// Число с `decimals` знаками после запятой: одинаковые данные дают побайтно одинаковые
+/// файлы, а округлённый до нуля результат не превращается в `-0.00`.
+pub fn format_fixed(value: f64, decimals: usize) -> String {
+    let formatted = format!("{value:.decimals$}");
+    if formatted.starts_with('-')
+        && formatted[1..]
+            .bytes()
+            .all(|byte| matches!(byte, b'0' | b'.'))
+    {
+        formatted[1..].to_string()
+    } else {
+        formatted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Precision, format_csv_float, round_to};
+
+    #[test]
+    fn csv_floats_have_fixed_precision_and_no_negative_zero() {
+        assert_eq!(format_csv_float(1.5), "1.500000");
+        assert_eq!(format_csv_float(-2.25), "-2.250000");
+        assert_eq!(format_csv_float(-1e-9), "0.000000");
+        assert_eq!(format_csv_float(-0.0), "0.000000");
+
+        let precision = Precision::default();
+        assert_eq!(precision.area(116_320.567_891), "116320.57");
+        assert_eq!(precision.percent(19.272_727_3), "19.2727");
+        assert_eq!(precision.rate(-0.001), "0.00");
+        assert_eq!(round_to(116_320.567_891, 2).to_string(), "116320.57");
+        assert_eq!(round_to(-0.000_04, 4).to_string(), "0");
+    }
+}
@@ -0,0 +1,493 @@
+//! Общая схема среза площадей и запись истории в CSV.
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Timelike, Utc};
+use serde::Deserialize;
+
+use crate::constants::Precision;
+use crate::series::{self, DateRange, MANUAL_HASH};
+
+const CSV_HEADER: &str = "time_index,hash,area,percent,area_type\n";
+const SPOOL_SUFFIX: &str = ".partial";
+/// Буфер записи: строки копятся в памяти и уходят в файл пачками по мегабайту вместо
+/// восьми килобайт по умолчанию.
+const WRITE_BUFFER_BYTES: usize = 1 << 20;
+
+/// Запись чисел в CSV истории.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CsvFormat {
+    /// Знаков после запятой в `area`; `None` — кратчайшая запись, которая читается обратно
+    /// в то же `f64`.
+    pub area_precision: Option<usize>,
+    /// То же для `percent`.
+    pub percent_precision: Option<usize>,
+}
+
+impl CsvFormat {
+    /// Опубликованная копия истории: площади и доли с точностью `[precision]`.
+    pub const fn published(precision: Precision) -> Self {
+        Self {
+            area_precision: Some(precision.area_km2),
+            percent_precision: Some(precision.percent),
+        }
+    }
+}
+
+/// Строка истории в формате экспорта API; к ней приводят свои данные все источники карт.
+#[allow(clippy::struct_field_names)]
+#[derive(Debug)]
+pub struct Area {
+    pub time_index: DateTime<Utc>,
+    /// Цвет слоя на карте (или `manual` для ручного ввода).
+    pub hash: String,
+    /// Площадь, км².
+    pub area: f64,
+    pub percent: f64,
+    pub area_type: String,
+}
+
+/// Строка уже сохранённого CSV.
+#[derive(Deserialize)]
+struct StoredArea {
+    time_index: String,
+    hash: String,
+    area: f64,
+    percent: f64,
+    #[serde(alias = "type")]
+    area_type: String,
+}
+
+impl Area {
+    /// Строка ручного ввода за день (`time_index` — полночь UTC).
+    pub fn manual(date: NaiveDate, area: f64, area_type: &str) -> Self {
+        Self {
+            time_index: date.and_time(NaiveTime::MIN).and_utc(),
+            hash: MANUAL_HASH.to_string(),
+            area,
+            percent: 0.0,
+            area_type: area_type.to_string(),
+        }
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.time_index.date_naive()
+    }
+
+    /// Срез без процента от площади страны — для тестов и синтетических рядов.
+    pub fn new(time_index: DateTime<Utc>, hash: &str, area: f64, area_type: &str) -> Self {
+        Self {
+            time_index,
+            hash: hash.to_string(),
+            area,
+            percent: 0.0,
+            area_type: area_type.to_string(),
+        }
+    }
+}
+
+/// Черновик полной выгрузки: срезы дописываются в CSV рядом с историей по мере загрузки,
+/// а в памяти остаются только дни, за которые они пришли. Незавершённый черновик удаляется
+/// при drop, так что прерванная выгрузка не оставляет мусора и не трогает историю.
+pub struct AreaSpool {
+    path: PathBuf,
+    writer: Option<AreaWriter>,
+    format: CsvFormat,
+    days: BTreeSet<NaiveDate>,
+    rows: usize,
+}
+
+impl AreaSpool {
+    /// Черновик для `file_path`: `history.csv` → `history.csv.partial`.
+    pub fn create(file_path: &Path, format: CsvFormat) -> Result<Self, String> {
+        let mut name = file_path.as_os_str().to_owned();
+        name.push(SPOOL_SUFFIX);
+        let path = PathBuf::from(name);
+        create_parent_dir(&path)?;
+        let file = File::create(&path)
+            .map_err(|err| format!("Failed to create CSV {}: {err}", path.display()))?;
+        let mut writer = AreaWriter::new(file, &path, format);
+        writer.write_header()?;
+        Ok(Self {
+            path,
+            writer: Some(writer),
+            format,
+            days: BTreeSet::new(),
+            rows: 0,
+        })
+    }
+
+    /// Дописывает срезы в черновик.
+    pub fn push(&mut self, areas: impl IntoIterator<Item = Area>) -> Result<(), String> {
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or_else(|| format!("CSV {} is already closed", self.path.display()))?;
+        for area in areas {
+            self.days.insert(area.date());
+            writer.write(&area)?;
+            self.rows += 1;
+        }
+        Ok(())
+    }
+
+    /// Число записанных строк.
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn close(&mut self) -> Result<(), String> {
+        self.writer.take().map_or(Ok(()), AreaWriter::finish)
+    }
+
+    /// Забирает записанные строки и начинает черновик заново; дни остаются.
+    fn take_rows(&mut self) -> Result<Vec<Area>, String> {
+        self.close()?;
+        let mut areas = Vec::with_capacity(self.rows);
+        for_each_stored_area(&self.path, |area| {
+            areas.push(area);
+            Ok(())
+        })?;
+        let file = File::create(&self.path)
+            .map_err(|err| format!("Failed to create CSV {}: {err}", self.path.display()))?;
+        let mut writer = AreaWriter::new(file, &self.path, self.format);
+        writer.write_header()?;
+        self.writer = Some(writer);
+        self.rows = 0;
+        Ok(areas)
+    }
+}
+
+impl Drop for AreaSpool {
+    fn drop(&mut self) {
+        self.writer = None;
+        // После `replace_with_spool` файла уже нет: он переименован в историю.
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Заменяет `file_path` черновиком; ручные строки за дни, которых в черновике нет, переносятся.
+/// Замена атомарна: до переименования старая история остаётся целой.
+pub fn replace_with_spool(mut spool: AreaSpool, file_path: &Path) -> Result<(), String> {
+    let manual = read_manual_areas(file_path)?
+        .into_iter()
+        .filter(|area| !spool.days.contains(&area.date()))
+        .collect::<Vec<_>>();
+    spool.push(manual)?;
+    spool.close()?;
+    crate::platform::replace_file(&spool.path, file_path).map_err(|err| {
+        format!(
+            "Failed to move {} to {}: {err}",
+            spool.path.display(),
+            file_path.display()
+        )
+    })
+}
+
+/// Заменяет в `file_path` дни из `range` черновиком: строки за другие дни сохраняются, как и
+/// ручные точки за дни диапазона, которых в черновике нет. Свежие строки вливаются в
+/// сохранённые по `time_index`, так что упорядоченная история остаётся упорядоченной, а
+/// результат не зависит от того, в каком порядке пришли срезы. Черновик за диапазон на время
+/// слияния читается в память: он не длиннее диапазона.
+pub fn merge_with_spool(
+    mut spool: AreaSpool,
+    file_path: &Path,
+    range: DateRange,
+) -> Result<(), String> {
+    let fresh_days = spool.days.clone();
+    let mut fresh = spool.take_rows()?;
+    fresh.sort_by_key(|area| area.time_index);
+    let mut fresh = fresh.into_iter().peekable();
+    if file_path.exists() {
+        for_each_stored_area(file_path, |area| {
+            let date = area.date();
+            let kept =
+                !range.contains(date) || (area.hash == MANUAL_HASH && !fresh_days.contains(&date));
+            if kept {
+                let time = area.time_index;
+                let earlier = std::iter::from_fn(|| fresh.next_if(|next| next.time_index < time));
+                spool.push(earlier.chain(std::iter::once(area)))?;
+            }
+            Ok(())
+        })?;
+    }
+    spool.push(fresh)?;
+    spool.close()?;
+    crate::platform::replace_file(&spool.path, file_path).map_err(|err| {
+        format!(
+            "Failed to move {} to {}: {err}",
+            spool.path.display(),
+            file_path.display()
+        )
+    })
+}
+
+/// Дописывает в `file_path` строки черновика новее `after` и возвращает их число;
+/// без таких строк файл не создаётся.
+pub fn append_spooled(
+    mut spool: AreaSpool,
+    file_path: &Path,
+    after: Option<DateTime<Utc>>,
+) -> Result<usize, String> {
+    spool.close()?;
+    let mut writer = None;
+    let mut saved = 0;
+    for_each_stored_area(&spool.path, |area| {
+        if after.is_some_and(|after| area.time_index <= after) {
+            return Ok(());
+        }
+        let writer = match &mut writer {
+            Some(writer) => writer,
+            None => writer.insert(open_append(file_path, spool.format)?),
+        };
+        writer.write(&area)?;
+        saved += 1;
+        Ok(())
+    })?;
+    writer.map_or(Ok(()), AreaWriter::finish)?;
+    Ok(saved)
+}
+
+/// Дописывает точки площадей в конец CSV; заголовок пишется только в новый файл.
+pub fn append_csv(areas: &[Area], file_path: &Path, format: CsvFormat) -> Result<(), String> {
+    let mut writer = open_append(file_path, format)?;
+    for area in areas {
+        writer.write(area)?;
+    }
+    writer.finish()
+}
+
+/// Переписывает все строки `source` в `target` с записью чисел `format`. `target` заменяется
+/// атомарно, как история черновиком; строки идут через черновик, а не через память.
+pub fn export_csv(source: &Path, target: &Path, format: CsvFormat) -> Result<(), String> {
+    let mut spool = AreaSpool::create(target, format)?;
+    for_each_stored_area(source, |area| spool.push(std::iter::once(area)))?;
+    spool.close()?;
+    crate::platform::replace_file(&spool.path, target).map_err(|err| {
+        format!(
+            "Failed to move {} to {}: {err}",
+            spool.path.display(),
+            target.display()
+        )
+    })
+}
+
+/// Читает из CSV строки ручного ввода (пустой список, если файла нет).
+pub fn read_manual_areas(file_path: &Path) -> Result<Vec<Area>, String> {
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut areas = Vec::new();
+    for_each_stored_area(file_path, |area| {
+        if area.hash == MANUAL_HASH {
+            areas.push(area);
+        }
+        Ok(())
+    })?;
+    Ok(areas)
+}
+
+/// Читает из CSV все строки истории.
+pub fn read_areas(file_path: &Path) -> Result<Vec<Area>, String> {
+    let mut areas = Vec::new();
+    for_each_stored_area(file_path, |area| {
+        areas.push(area);
+        Ok(())
+    })?;
+    Ok(areas)
+}
+
+/// Разбирает строки истории из CSV в памяти, например скачанного с зеркала.
+pub fn parse_areas(csv: &[u8], source: &str) -> Result<Vec<Area>, String> {
+    let mut areas = Vec::new();
+    visit_areas(csv::Reader::from_reader(csv), source, |area| {
+        areas.push(area);
+        Ok(())
+    })?;
+    Ok(areas)
+}
+
+/// Построчно читает сохранённый CSV, не загружая его целиком.
+fn for_each_stored_area(
+    file_path: &Path,
+    visit: impl FnMut(Area) -> Result<(), String>,
+) -> Result<(), String> {
+    let reader = csv::Reader::from_path(file_path)
+        .map_err(|err| format!("Failed to open CSV {}: {err}", file_path.display()))?;
+    visit_areas(reader, &file_path.display().to_string(), visit)
+}
+
+fn visit_areas<R: std::io::Read>(
+    mut reader: csv::Reader<R>,
+    source: &str,
+    mut visit: impl FnMut(Area) -> Result<(), String>,
+) -> Result<(), String> {
+    for row in reader.deserialize::<StoredArea>() {
+        let row = row.map_err(|err| format!("Failed to read CSV {source}: {err}"))?;
+        let time_index = series::parse_time_index(&row.time_index)
+            .map_err(|err| format!("Failed to parse time_index '{}': {err}", row.time_index))?;
+        visit(Area {
+            time_index,
+            hash: row.hash,
+            area: row.area,
+            percent: row.percent,
+            area_type: row.area_type,
+        })?;
+    }
+    Ok(())
+}
+
+fn create_parent_dir(file_path: &Path) -> Result<(), String> {
+    if let Some(parent) = file_path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    Ok(())
+}
+
+/// Открывает CSV на дописывание; в новый или пустой файл сначала пишется заголовок.
+fn open_append(file_path: &Path, format: CsvFormat) -> Result<AreaWriter, String> {
+    create_parent_dir(file_path)?;
+    let is_empty = std::fs::metadata(file_path).map_or(true, |meta| meta.len() == 0);
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)
+        .map_err(|err| format!("Failed to open CSV {}: {err}", file_path.display()))?;
+    let mut writer = AreaWriter::new(file, file_path, format);
+    if is_empty {
+        writer.write_header()?;
+    }
+    Ok(writer)
+}
+
+/// Писатель строк истории. Поля форматируются в буферы, которые живут всё время записи, так
+/// что строка CSV не выделяет память; в файл данные уходят пачками по [`WRITE_BUFFER_BYTES`].
+struct AreaWriter {
+    writer: csv::Writer<File>,
+    path: PathBuf,
+    area_precision: Option<usize>,
+    percent_precision: Option<usize>,
+    time: String,
+    area: String,
+    percent: String,
+}
+
+impl AreaWriter {
+    fn new(file: File, path: &Path, format: CsvFormat) -> Self {
+        Self {
+            writer: csv::WriterBuilder::new()
+                .buffer_capacity(WRITE_BUFFER_BYTES)
+                .from_writer(file),
+            path: path.to_path_buf(),
+            area_precision: format.area_precision,
+            percent_precision: format.percent_precision,
+            time: String::new(),
+            area: String::new(),
+            percent: String::new(),
+        }
+    }
+
+    fn write_header(&mut self) -> Result<(), String> {
+        self.writer
+            .write_record(CSV_HEADER.trim_end().split(','))
+            .map_err(|err| {
+                format!(
+                    "Failed to write CSV header to {}: {err}",
+                    self.path.display()
+                )
+            })
+    }
+
+    fn write(&mut self, area: &Area) -> Result<(), String> {
+        write_time_index(&mut self.time, area.time_index);
+        write_float(&mut self.area, area.area, self.area_precision);
+        write_float(&mut self.percent, area.percent, self.percent_precision);
+        self.writer
+            .write_record([
+                self.time.as_bytes(),
+                area.hash.as_bytes(),
+                self.area.as_bytes(),
+                self.percent.as_bytes(),
+                area.area_type.as_bytes(),
+            ])
+            .map_err(|err| format!("Failed to write CSV row to {}: {err}", self.path.display()))
+    }
+
+    fn finish(mut self) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|err| format!("Failed to flush CSV {}: {err}", self.path.display()))
+    }
+}
+
+/// `time_index` в том же виде, что и `Display` у `DateTime<Utc>` (`2024-05-01 00:00:00 UTC`),
+/// но без разбора шаблона: на многомиллионной истории это заметная часть записи.
+fn write_time_index(buffer: &mut String, time: DateTime<Utc>) {
+    buffer.clear();
+    if time.nanosecond() != 0 || !(0..=9999).contains(&time.year()) {
+        let _ = write!(buffer, "{time}");
+        return;
+    }
+    #[allow(clippy::cast_sign_loss)]
+    let year = time.year() as u32;
+    for (value, width, separator) in [
+        (year, 4, '-'),
+        (time.month(), 2, '-'),
+        (time.day(), 2, ' '),
+        (time.hour(), 2, ':'),
+        (time.minute(), 2, ':'),
+        (time.second(), 2, ' '),
+    ] {
+        push_padded(buffer, value, width);
+        buffer.push(separator);
+    }
+    buffer.push_str("UTC");
+}
+
+/// Дописывает `value` цифрами с ведущими нулями до `width` знаков.
+fn push_padded(buffer: &mut String, value: u32, width: u32) {
+    for power in (0..width).rev() {
+        let digit = (value / 10u32.pow(power)) % 10;
+        buffer.push(char::from_digit(digit, 10).unwrap_or('0'));
+    }
+}
+
+fn write_float(buffer: &mut String, value: f64, precision: Option<usize>) {
+    buffer.clear();
+    let _ = match precision {
+        Some(precision) => write!(buffer, "{value:.precision$}"),
+        None => write!(buffer, "{value}"),
+    };
+}
+
+/// Общие строки для тестов: срезы мая 2024 в полночь со слоем оккупации после 24.02.2022.
+/// Доступны и тестам бинарника через фичу `fixtures`.
+#[cfg(any(test, feature = "fixtures"))]
+pub mod test_support {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use super::Area;
+    use crate::series::AREA_TYPE_OCCUPIED;
+
+    /// Время среза за `day` мая 2024.
+    ///
+    /// # Panics
+    ///
+    /// Если в мае нет такого дня.
+    pub fn day(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, day, 0, 0, 0)
+            .single()
+            .expect("valid timestamp")
+    }
+
+    /// Строка среза за `day` мая 2024 с площадью `area`.
+    pub fn area_row(day: u32, area: f64) -> Area {
+        Area::new(self::day(day), "#a52714", area, AREA_TYPE_OCCUPIED)
+    }
+}
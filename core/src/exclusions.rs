@@ -0,0 +1,83 @@
+//! Список дней, исключённых из обучения модели (`exclusions.toml`). Каждое исключение —
+//! редакционное решение с причиной: файл хранится в репозитории данных и проходит ревью,
+//! а исключённые точки остаются на графике полыми маркерами с той же причиной.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExclusionsFile {
+    #[serde(default)]
+    exclude: Vec<ExclusionEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExclusionEntry {
+    date: NaiveDate,
+    reason: String,
+    /// Кто проверил решение.
+    #[serde(default)]
+    reviewer: Option<String>,
+}
+
+/// Исключённые дни и подписи к ним (причина и, если указан, проверивший).
+pub fn load(path: &Path) -> Result<BTreeMap<NaiveDate, String>, String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    parse(&raw).map_err(|err| format!("Invalid exclusions file {}: {err}", path.display()))
+}
+
+fn parse(raw: &str) -> Result<BTreeMap<NaiveDate, String>, String> {
+    let file = toml::from_str::<ExclusionsFile>(raw).map_err(|err| err.to_string())?;
+    let mut days = BTreeMap::new();
+    for entry in file.exclude {
+        let reason = entry.reason.trim();
+        if reason.is_empty() {
+            return Err(format!("exclusion {} has an empty reason", entry.date));
+        }
+        let note = match entry.reviewer.as_deref().map(str::trim) {
+            Some(reviewer) if !reviewer.is_empty() => format!("{reason} (проверил: {reviewer})"),
+            _ => reason.to_string(),
+        };
+        if days.insert(entry.date, note).is_some() {
+            return Err(format!("date {} is excluded twice", entry.date));
+        }
+    }
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::parse;
+
+    #[test]
+    fn exclusions_require_reasons_and_unique_dates() {
+        let days = parse(
+            "[[exclude]]\ndate = \"2024-03-02\"\nreason = \"Откат слоя\"\nreviewer = \"ed\"\n\n\
+             [[exclude]]\ndate = \"2024-03-05\"\nreason = \"Сбой API\"\n",
+        )
+        .expect("exclusions should parse");
+        assert_eq!(
+            days.get(&NaiveDate::from_ymd_opt(2024, 3, 2).expect("valid date")),
+            Some(&"Откат слоя (проверил: ed)".to_string())
+        );
+        assert_eq!(days.len(), 2);
+
+        assert!(parse("[[exclude]]\ndate = \"2024-03-02\"\nreason = \" \"\n").is_err());
+        assert!(
+            parse(
+                "[[exclude]]\ndate = \"2024-03-02\"\nreason = \"a\"\n\
+                 [[exclude]]\ndate = \"2024-03-02\"\nreason = \"b\"\n"
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,431 @@
+//! Смещение линии фронта по направлениям между двумя картами источника в `GeoJSON`.
+//!
+//! Каждое направление — отрезок из глубины занятой территории к подконтрольной Украине.
+//! Положение фронта на нём — расстояние от начала отрезка до первого выхода из занятых
+//! полигонов; разница положений на двух картах и есть продвижение (плюс) или отход (минус).
+//! Точки пересечения ищутся прямо в градусах: доля длины отрезка не меняется при
+//! аффинном переходе к километрам, который нужен только для длины.
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use tracing::debug;
+
+/// Цвета заливки занятых полигонов на карте `deepstatemap.live`: после и до 24.02.2022.
+pub const DEFAULT_OCCUPIED_FILLS: [&str; 2] = ["#a52714", "#880e4f"];
+pub const DEFAULT_FRONT_DAYS: u32 = 7;
+const KM_PER_DEGREE_LAT: f64 = 110.574;
+const KM_PER_DEGREE_LON_AT_EQUATOR: f64 = 111.32;
+/// Средний радиус Земли (IUGG), км.
+const EARTH_RADIUS_KM: f64 = 6_371.008_8;
+
+/// Направление фронта: отрезок `from` → `to` в координатах (долгота, широта).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sector {
+    pub name: &'static str,
+    pub from: (f64, f64),
+    pub to: (f64, f64),
+}
+
+/// Встроенные направления; концы выбраны с запасом, чтобы фронт оставался внутри отрезков.
+pub const SECTORS: &[Sector] = &[
+    Sector {
+        name: "Купянское",
+        from: (38.16, 49.41),
+        to: (37.20, 49.75),
+    },
+    Sector {
+        name: "Лиманское",
+        from: (38.22, 49.05),
+        to: (37.55, 49.05),
+    },
+    Sector {
+        name: "Славянское",
+        from: (38.00, 48.59),
+        to: (37.56, 48.75),
+    },
+    Sector {
+        name: "Константиновское",
+        from: (38.05, 48.30),
+        to: (37.55, 48.55),
+    },
+    Sector {
+        name: "Покровское",
+        from: (37.75, 48.14),
+        to: (36.90, 48.38),
+    },
+    Sector {
+        name: "Новопавловское",
+        from: (37.28, 47.98),
+        to: (36.55, 48.12),
+    },
+    Sector {
+        name: "Гуляйпольское",
+        from: (36.84, 47.60),
+        to: (35.95, 47.72),
+    },
+    Sector {
+        name: "Ореховское",
+        from: (35.71, 47.25),
+        to: (35.80, 47.72),
+    },
+    Sector {
+        name: "Херсонское",
+        from: (33.00, 46.40),
+        to: (32.60, 46.70),
+    },
+];
+
+/// Параметры раздела (`[front]`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrontConfig {
+    /// Насколько раньше последней карты брать вторую.
+    pub days: u32,
+    /// Заливки полигонов, которые считаются занятой территорией.
+    pub occupied_fills: Vec<String>,
+}
+
+impl Default for FrontConfig {
+    fn default() -> Self {
+        Self {
+            days: DEFAULT_FRONT_DAYS,
+            occupied_fills: DEFAULT_OCCUPIED_FILLS.map(str::to_string).to_vec(),
+        }
+    }
+}
+
+/// Положение фронта на направлении на обеих картах, км от начала отрезка.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SectorShift {
+    pub name: String,
+    pub before_km: f64,
+    pub after_km: f64,
+}
+
+impl SectorShift {
+    /// Продвижение вдоль направления: плюс — занятая территория выросла.
+    pub fn advance_km(&self) -> f64 {
+        self.after_km - self.before_km
+    }
+}
+
+/// Смещения по направлениям между картами `before` и `after`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrontShifts {
+    pub before: DateTime<Utc>,
+    pub after: DateTime<Utc>,
+    pub sectors: Vec<SectorShift>,
+}
+
+/// Кольца одного полигона: внешнее и дыры, в координатах (долгота, широта).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polygon {
+    rings: Vec<Vec<(f64, f64)>>,
+}
+
+impl Polygon {
+    /// Правило чётности по всем кольцам: точка в дыре лежит вне полигона.
+    fn contains(&self, (x, y): (f64, f64)) -> bool {
+        let mut inside = false;
+        for ring in &self.rings {
+            for (a, b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+                if (a.1 > y) != (b.1 > y) && x < (b.0 - a.0) * (y - a.1) / (b.1 - a.1) + a.0 {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    /// Пересекаются ли полигоны: рамки перекрываются и вершина внешнего кольца одного лежит
+    /// внутри другого. Пересечения «крестом», где ни одна вершина не попадает внутрь, не
+    /// видны, но для проверки выгрузок на дубли этого хватает.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let (Some(own), Some(theirs)) = (self.bounds(), other.bounds()) else {
+            return false;
+        };
+        let disjoint = own.1.0 < theirs.0.0
+            || theirs.1.0 < own.0.0
+            || own.1.1 < theirs.0.1
+            || theirs.1.1 < own.0.1;
+        if disjoint {
+            return false;
+        }
+        let outer = |polygon: &Self| polygon.rings.first().cloned().unwrap_or_default();
+        outer(other).into_iter().any(|point| self.contains(point))
+            || outer(self).into_iter().any(|point| other.contains(point))
+    }
+
+    /// Углы рамки внешнего кольца: (мин. долгота, мин. широта), (макс. долгота, макс. широта).
+    fn bounds(&self) -> Option<((f64, f64), (f64, f64))> {
+        let outer = self.rings.first().filter(|ring| !ring.is_empty())?;
+        Some(outer.iter().fold(
+            (
+                (f64::INFINITY, f64::INFINITY),
+                (f64::NEG_INFINITY, f64::NEG_INFINITY),
+            ),
+            |(min, max), &(x, y)| ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y))),
+        ))
+    }
+
+    /// Площадь на сфере, км²: внешнее кольцо за вычетом дыр. Направление обхода колец
+    /// не важно — берётся модуль площади каждого.
+    pub fn area_km2(&self) -> f64 {
+        let mut rings = self.rings.iter().map(|ring| ring_area_km2(ring));
+        let outer = rings.next().unwrap_or_default();
+        (outer - rings.sum::<f64>()).max(0.0)
+    }
+}
+
+/// Площадь кольца на сфере по сумме трапеций в синусах широт (Chamberlain, Duquette, 2007).
+fn ring_area_km2(ring: &[(f64, f64)]) -> f64 {
+    let twice_area = ring
+        .iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(a, b)| {
+            (b.0 - a.0).to_radians() * (2.0 + a.1.to_radians().sin() + b.1.to_radians().sin())
+        })
+        .sum::<f64>();
+    (twice_area * EARTH_RADIUS_KM * EARTH_RADIUS_KM / 2.0).abs()
+}
+
+/// Смещения по встроенным направлениям; направления, где фронт не найден на одной из карт,
+/// пропускаются.
+pub fn front_shifts(
+    before: (DateTime<Utc>, &[u8]),
+    after: (DateTime<Utc>, &[u8]),
+    config: &FrontConfig,
+) -> Result<FrontShifts, String> {
+    let before_polygons = occupied_polygons(before.1, &config.occupied_fills)?;
+    let after_polygons = occupied_polygons(after.1, &config.occupied_fills)?;
+    let sectors = SECTORS
+        .iter()
+        .filter_map(|sector| {
+            let before_km = front_position(sector, &before_polygons);
+            let after_km = front_position(sector, &after_polygons);
+            if before_km.is_none() || after_km.is_none() {
+                debug!(
+                    sector = sector.name,
+                    "Front line not found on the sector, skipping"
+                );
+            }
+            Some(SectorShift {
+                name: sector.name.to_string(),
+                before_km: before_km?,
+                after_km: after_km?,
+            })
+        })
+        .collect::<Vec<_>>();
+    if sectors.is_empty() {
+        return Err("front line was not found on any sector".to_string());
+    }
+    Ok(FrontShifts {
+        before: before.0,
+        after: after.0,
+        sectors,
+    })
+}
+
+/// Полигоны и мультиполигоны из `FeatureCollection`, залитые одним из цветов `fills`.
+pub fn occupied_polygons(geojson: &[u8], fills: &[String]) -> Result<Vec<Polygon>, String> {
+    collect_polygons(geojson, |feature| {
+        let fill = feature.pointer("/properties/fill").and_then(Value::as_str);
+        fill.is_some_and(|fill| {
+            fills
+                .iter()
+                .any(|occupied| occupied.eq_ignore_ascii_case(fill))
+        })
+    })
+}
+
+/// Все полигоны и мультиполигоны из `FeatureCollection`, без отбора по заливке.
+pub fn all_polygons(geojson: &[u8]) -> Result<Vec<Polygon>, String> {
+    collect_polygons(geojson, |_| true)
+}
+
+/// Полигоны и мультиполигоны объектов, у которых строковое свойство равно `value` (без учёта
+/// регистра): так из выгрузки с несколькими слоями берётся один.
+pub fn polygons_with_property(geojson: &[u8], value: &str) -> Result<Vec<Polygon>, String> {
+    collect_polygons(geojson, |feature| {
+        feature
+            .get("properties")
+            .and_then(Value::as_object)
+            .is_some_and(|properties| {
+                properties
+                    .values()
+                    .filter_map(Value::as_str)
+                    .any(|property| property.eq_ignore_ascii_case(value))
+            })
+    })
+}
+
+fn collect_polygons(geojson: &[u8], keep: impl Fn(&Value) -> bool) -> Result<Vec<Polygon>, String> {
+    let collection: Value = serde_json::from_slice(geojson)
+        .map_err(|err| format!("Failed to deserialize GeoJSON: {err}"))?;
+    let features = collection
+        .get("features")
+        .and_then(Value::as_array)
+        .ok_or("GeoJSON has no features array")?;
+    let mut polygons = Vec::new();
+    for feature in features {
+        if !keep(feature) {
+            continue;
+        }
+        let Some(geometry) = feature.get("geometry") else {
+            continue;
+        };
+        let coordinates = geometry.get("coordinates");
+        match geometry.get("type").and_then(Value::as_str) {
+            Some("Polygon") => polygons.extend(coordinates.and_then(parse_polygon)),
+            Some("MultiPolygon") => polygons.extend(
+                coordinates
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(parse_polygon),
+            ),
+            _ => {}
+        }
+    }
+    Ok(polygons)
+}
+
+fn parse_polygon(rings: &Value) -> Option<Polygon> {
+    let rings = rings
+        .as_array()?
+        .iter()
+        .map(|ring| {
+            ring.as_array()?
+                .iter()
+                .map(|point| Some((point.get(0)?.as_f64()?, point.get(1)?.as_f64()?)))
+                .collect::<Option<Vec<_>>>()
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!rings.is_empty()).then_some(Polygon { rings })
+}
+
+/// Расстояние от начала направления до первого выхода из занятой территории, км.
+/// `None`, если начало не занято или отрезок занят целиком.
+fn front_position(sector: &Sector, polygons: &[Polygon]) -> Option<f64> {
+    let (from, to) = (sector.from, sector.to);
+    let at = |t: f64| {
+        (
+            t.mul_add(to.0 - from.0, from.0),
+            t.mul_add(to.1 - from.1, from.1),
+        )
+    };
+    let occupied = |point| polygons.iter().any(|polygon| polygon.contains(point));
+    if !occupied(from) {
+        return None;
+    }
+    let mut cuts = vec![0.0, 1.0];
+    for ring in polygons.iter().flat_map(|polygon| &polygon.rings) {
+        for (a, b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+            cuts.extend(crossing(from, to, *a, *b));
+        }
+    }
+    cuts.sort_by(f64::total_cmp);
+    cuts.dedup();
+    let exit = cuts
+        .windows(2)
+        .find(|pair| !occupied(at(f64::midpoint(pair[0], pair[1]))))
+        .map(|pair| pair[0])?;
+    Some(exit * length_km(from, to))
+}
+
+/// Доля отрезка `from → to`, на которой он пересекает ребро `edge_start → edge_end`.
+fn crossing(
+    from: (f64, f64),
+    to: (f64, f64),
+    edge_start: (f64, f64),
+    edge_end: (f64, f64),
+) -> Option<f64> {
+    let cross = |lhs: (f64, f64), rhs: (f64, f64)| lhs.0.mul_add(rhs.1, -(lhs.1 * rhs.0));
+    let along = (to.0 - from.0, to.1 - from.1);
+    let edge = (edge_end.0 - edge_start.0, edge_end.1 - edge_start.1);
+    let denom = cross(along, edge);
+    if denom.abs() < f64::EPSILON {
+        return None;
+    }
+    let offset = (edge_start.0 - from.0, edge_start.1 - from.1);
+    let share = cross(offset, edge) / denom;
+    let on_edge = cross(offset, along) / denom;
+    ((0.0..=1.0).contains(&share) && (0.0..=1.0).contains(&on_edge)).then_some(share)
+}
+
+/// Длина отрезка в км в равнопромежуточной проекции у его средней широты.
+fn length_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let latitude = f64::midpoint(from.1, to.1).to_radians();
+    let dx = (to.0 - from.0) * KM_PER_DEGREE_LON_AT_EQUATOR * latitude.cos();
+    let dy = (to.1 - from.1) * KM_PER_DEGREE_LAT;
+    dx.hypot(dy)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::test_support::day;
+
+    use super::{FrontConfig, SECTORS, front_shifts, length_km};
+
+    /// Занятый параллелограмм вдоль Покровского направления до доли `share` его длины,
+    /// с дырой в стороне от отрезка и полигоном другого цвета, который не учитывается.
+    fn map(share: f64) -> String {
+        let sector = SECTORS
+            .iter()
+            .find(|sector| sector.name == "Покровское")
+            .expect("sector exists");
+        let (from, to) = (sector.from, sector.to);
+        let at = |t: f64| {
+            (
+                t.mul_add(to.0 - from.0, from.0),
+                t.mul_add(to.1 - from.1, from.1),
+            )
+        };
+        let (end, start) = (at(share), at(-0.1));
+        let ring = [
+            (start.0, start.1 - 0.2),
+            (end.0, end.1 - 0.2),
+            (end.0, end.1 + 0.2),
+            (start.0, start.1 + 0.2),
+        ];
+        let hole = [
+            (from.0, from.1 + 0.1),
+            (from.0, from.1 + 0.15),
+            (from.0 - 0.05, from.1 + 0.15),
+        ];
+        let coords = |points: &[(f64, f64)]| {
+            points
+                .iter()
+                .map(|(x, y)| format!("[{x},{y}]"))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        format!(
+            r##"{{"type":"FeatureCollection","features":[
+                {{"type":"Feature","properties":{{"fill":"#A52714"}},
+                  "geometry":{{"type":"Polygon","coordinates":[[{}],[{}]]}}}},
+                {{"type":"Feature","properties":{{"fill":"#01579b"}},
+                  "geometry":{{"type":"Polygon","coordinates":[[[30,40],[50,40],[50,60]]]}}}}
+            ]}}"##,
+            coords(&ring),
+            coords(&hole)
+        )
+    }
+
+    #[test]
+    fn front_advance_is_the_change_of_the_first_exit_along_the_sector() {
+        let (before, after) = (map(0.25), map(0.35));
+        let shifts = front_shifts(
+            (day(1), before.as_bytes()),
+            (day(8), after.as_bytes()),
+            &FrontConfig::default(),
+        )
+        .expect("front should be found");
+
+        assert_eq!(shifts.sectors.len(), 1);
+        let sector = &shifts.sectors[0];
+        assert_eq!(sector.name, "Покровское");
+        let length = length_km(SECTORS[4].from, SECTORS[4].to);
+        assert!((sector.before_km / length - 0.25).abs() < 1e-9);
+        assert!((sector.advance_km() / length - 0.1).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,141 @@
+//! Контрольные суммы SHA-256 и подписи minisign для публикуемых CSV.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use sha2::{Digest, Sha256};
+
+const CHECKSUM_EXTENSION: &str = "sha256";
+const SIGNATURE_EXTENSION: &str = "minisig";
+const MINISIGN_BIN: &str = "minisign";
+const SHA256_HEX_LEN: usize = 64;
+
+/// Что публиковать рядом с артефактом.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntegrityConfig {
+    /// Писать `<файл>.sha256` в формате `sha256sum`.
+    pub checksums: bool,
+    /// Секретный ключ minisign без пароля; `None` — не подписывать.
+    pub minisign_secret_key: Option<PathBuf>,
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .fold(String::with_capacity(SHA256_HEX_LEN), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+/// Путь к файлу-спутнику: `<файл>.<extension>`.
+fn sidecar_path(path: &Path, extension: &str) -> Result<PathBuf, String> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Path {} has no file name", path.display()))?;
+    let mut sidecar = file_name.to_os_string();
+    sidecar.push(format!(".{extension}"));
+    Ok(path.with_file_name(sidecar))
+}
+
+/// Сохраняет контрольные суммы и подпись для готового артефакта.
+pub fn publish_artifact(path: &Path, config: &IntegrityConfig) -> Result<Vec<PathBuf>, String> {
+    let mut written = Vec::new();
+    if config.checksums {
+        written.push(write_checksum(path)?);
+    }
+    if let Some(secret_key) = &config.minisign_secret_key {
+        written.push(sign_minisign(path, secret_key)?);
+    }
+    Ok(written)
+}
+
+/// Пишет `<файл>.sha256`, совместимый с `sha256sum -c`.
+pub fn write_checksum(path: &Path) -> Result<PathBuf, String> {
+    let bytes =
+        fs::read(path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Path {} has no file name", path.display()))?;
+    let checksum_path = sidecar_path(path, CHECKSUM_EXTENSION)?;
+    fs::write(
+        &checksum_path,
+        format!("{}  {file_name}\n", sha256_hex(&bytes)),
+    )
+    .map_err(|err| format!("Failed to write {}: {err}", checksum_path.display()))?;
+    Ok(checksum_path)
+}
+
+/// Достаёт хэш из содержимого `.sha256` (первое поле первой строки).
+pub fn parse_checksum(content: &str) -> Result<String, String> {
+    let digest = content
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| "checksum file is empty".to_string())?;
+    if digest.len() != SHA256_HEX_LEN || !digest.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return Err(format!("`{digest}` is not a SHA-256 hex digest"));
+    }
+    Ok(digest.to_ascii_lowercase())
+}
+
+pub fn verify_checksum(bytes: &[u8], expected: &str) -> Result<(), String> {
+    let actual = sha256_hex(bytes);
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "checksum mismatch: expected {expected}, got {actual}"
+        ))
+    }
+}
+
+/// Подписывает файл утилитой `minisign` и возвращает путь к `<файл>.minisig`.
+fn sign_minisign(path: &Path, secret_key: &Path) -> Result<PathBuf, String> {
+    let signature_path = sidecar_path(path, SIGNATURE_EXTENSION)?;
+    let output = Command::new(MINISIGN_BIN)
+        .arg("-S")
+        .arg("-s")
+        .arg(secret_key)
+        .arg("-m")
+        .arg(path)
+        .arg("-x")
+        .arg(&signature_path)
+        .output()
+        .map_err(|err| format!("Failed to run {MINISIGN_BIN}: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{MINISIGN_BIN} failed to sign {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(signature_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_checksum, sha256_hex, verify_checksum, write_checksum};
+
+    #[test]
+    fn checksum_roundtrips_through_sha256sum_format() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("integrity.csv");
+        std::fs::write(&path, "time_index,hash\n").expect("artifact should be written");
+        let checksum_path = write_checksum(&path).expect("checksum should be written");
+        let content = std::fs::read_to_string(&checksum_path).expect("checksum readable");
+
+        assert!(content.ends_with("  integrity.csv\n"));
+        let expected = parse_checksum(&content).expect("checksum should parse");
+        assert!(verify_checksum(b"time_index,hash\n", &expected).is_ok());
+        assert!(verify_checksum(b"tampered\n", &expected).is_err());
+        assert!(parse_checksum("not-a-digest  file.csv").is_err());
+    }
+}
@@ -0,0 +1,43 @@
+//! Прерывание по Ctrl-C. Долгая загрузка подписывается на сигнал: по первому нажатию она
+//! отменяет запросы в полёте и сохраняет собранное. Если подписчиков нет или нажатие
+//! повторное, процесс завершается сразу.
+
+use std::sync::Arc;
+
+use tokio::sync::watch;
+use tracing::warn;
+
+/// Код выхода по SIGINT, как у оболочки.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+#[derive(Clone, Debug)]
+pub struct Interrupt {
+    requested: Arc<watch::Sender<bool>>,
+}
+
+impl Interrupt {
+    /// Перехватывает Ctrl-C до конца процесса; нужен запущенный рантайм tokio.
+    pub fn listen() -> Self {
+        let requested = Arc::new(watch::channel(false).0);
+        let handler = Arc::clone(&requested);
+        tokio::spawn(async move {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                if handler.receiver_count() == 0 || *handler.borrow() {
+                    std::process::exit(INTERRUPTED_EXIT_CODE);
+                }
+                warn!(
+                    "Interrupt requested: stopping the download and saving collected snapshots; \
+                     press Ctrl-C again to exit immediately"
+                );
+                handler.send_replace(true);
+            }
+        });
+        Self { requested }
+    }
+
+    /// Подписка загрузки: пока она жива, первый Ctrl-C не завершает процесс, а меняет
+    /// значение на `true`.
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.requested.subscribe()
+    }
+}
@@ -0,0 +1,18 @@
+//! Данные и расчёты `rua` без отрисовки: загрузка срезов `DeepStateMap`, дневной ряд площадей и
+//! модели прогноза. Веб-сервер или бот подключают этот крейт без plotly, maud и minify-html.
+
+pub mod constants;
+pub mod data;
+pub mod exclusions;
+pub mod fetch;
+pub mod front;
+pub mod integrity;
+pub mod interrupt;
+pub mod methodology;
+pub mod model;
+pub mod platform;
+pub mod progress;
+pub mod series;
+pub mod source;
+pub mod store;
+pub mod updates;
@@ -0,0 +1,107 @@
+//! Приложение «Методология»: как считаются ряд, модель и график в этой сборке.
+//!
+//! Разделы собирают сами модули (`series`, `model`, `report`) из своих констант и текущего
+//! конфига, поэтому описание не расходится с кодом: новая константа попадает в приложение
+//! вместе с изменением расчёта. Приложение пишется страницей HTML (для печати в PDF) и CSV.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Один параметр расчёта: имя, значение в этой сборке и что он делает.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Parameter {
+    pub name: String,
+    pub value: String,
+    pub description: &'static str,
+}
+
+impl Parameter {
+    // Значение принимается по значению, чтобы в разделах писать числа и `format!` без `&`.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(name: impl Into<String>, value: impl ToString, description: &'static str) -> Self {
+        Self {
+            name: name.into(),
+            value: value.to_string(),
+            description,
+        }
+    }
+}
+
+/// Раздел приложения: этап конвейера и его параметры.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Section {
+    pub title: &'static str,
+    /// Краткое описание этапа.
+    pub summary: &'static str,
+    pub parameters: Vec<Parameter>,
+}
+
+/// CSV приложения рядом со страницей: `methodology.html` → `methodology.csv`.
+pub fn csv_path_for(output_html: &Path) -> PathBuf {
+    output_html.with_extension("csv")
+}
+
+/// Пишет все параметры одной таблицей: раздел, параметр, значение, описание.
+pub fn write_csv(sections: &[Section], output_csv: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = output_csv.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let mut writer = csv::Writer::from_path(output_csv)?;
+    writer.write_record(["section", "parameter", "value", "description"])?;
+    for section in sections {
+        for parameter in &section.parameters {
+            writer.write_record([
+                section.title,
+                &parameter.name,
+                &parameter.value,
+                parameter.description,
+            ])?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv_path_for, write_csv};
+    use crate::model::{self, ModelConfig};
+
+    #[test]
+    fn appendix_follows_the_config_and_round_trips_through_csv() {
+        let section = model::methodology_llt(&ModelConfig {
+            restarts: 7,
+            ..ModelConfig::default()
+        });
+        let value = |name: &str| {
+            section
+                .parameters
+                .iter()
+                .find(|parameter| parameter.name == name)
+                .map(|parameter| parameter.value.clone())
+        };
+        assert_eq!(value("restarts").as_deref(), Some("7"));
+        assert_eq!(
+            value("training_window").as_deref(),
+            Some("since 2022-11-22")
+        );
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let html = temp.path().join("methodology.html");
+        let path = csv_path_for(&html);
+        write_csv(std::slice::from_ref(&section), &path).expect("appendix CSV should be written");
+        let mut reader = csv::Reader::from_path(&path).expect("appendix CSV should open");
+        let rows = reader
+            .records()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("appendix CSV should parse");
+        assert_eq!(rows.len(), section.parameters.len());
+        assert!(
+            rows.iter()
+                .any(|row| &row[1] == "restarts" && &row[2] == "7")
+        );
+    }
+}
@@ -0,0 +1,1524 @@
+// Без `forecast` задачи подбора остаются без решателя.
+#![cfg_attr(not(feature = "forecast"), allow(dead_code))]
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+#[cfg(feature = "forecast")]
+use argmin::core::{CostFunction, Error as ArgminError, Gradient};
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+mod calibration;
+mod diagnostics;
+mod methodology;
+mod optimizer;
+mod priors;
+mod regressors;
+mod seasonality;
+pub mod split;
+mod window;
+
+pub use calibration::{Calibration, CalibrationConfig, DEFAULT_CALIBRATION_FOLDS};
+pub use diagnostics::Diagnostics;
+pub use methodology::{methodology_intervals, methodology_llt, methodology_trend_filter};
+pub use optimizer::OptimizationSummary;
+pub use priors::SigmaPrior;
+
+pub use regressors::{RegressionFit, Regressors};
+pub use seasonality::{DEFAULT_SEASONAL_PENALTY, SeasonalFit, SeasonalPeriod, SeasonalityConfig};
+pub use split::{TimeSeriesSplit, mean_abs_error};
+pub use window::{TrainingSpan, TrainingWindow};
+
+use crate::constants::{DATE_FORMAT, Precision};
+use crate::series::{AreaBuckets, Granularity, InterpolationStrategy, build_occupied_series_with};
+
+const DEFAULT_MAX_ITERS: u64 = 400;
+const DEFAULT_HISTORY: usize = 10;
+/// Стартов подбора LLT по умолчанию: эвристика и три её возмущения.
+pub const DEFAULT_RESTARTS: usize = 4;
+const DEFAULT_SCALE: f64 = 1000.0;
+const DEFAULT_TOL_GRAD: f64 = 1e-8;
+const DEFAULT_TOL_COST: f64 = 1e-10;
+const DEFAULT_TF_LAMBDA: f64 = 5.0;
+const DEFAULT_TF_EPS: f64 = 1e-3;
+const DEFAULT_TF_HUBER_DELTA: f64 = 0.0;
+const DEFAULT_TF_DAMPING: f64 = 1.0;
+const HETERO_WINDOW: usize = 21;
+const HETERO_MIN_SCALE: f64 = 0.6;
+const HETERO_MAX_SCALE: f64 = 2.5;
+const MIN_SIGMA: f64 = 1e-6;
+const LARGE_COST: f64 = 1e30;
+const CONFIDENCE_Z: f64 = 1.96;
+const MIN_BACKTEST_TRAIN_DAYS: usize = 30;
+
+fn usize_to_i64(value: usize, context: &str) -> Option<i64> {
+    i64::try_from(value).map_or_else(
+        |_| {
+            tracing::warn!(
+                value,
+                context,
+                "usize value exceeds i64::MAX; truncating forecast horizon",
+            );
+            None
+        },
+        Some,
+    )
+}
+
+fn usize_to_f64(value: usize, context: &str) -> f64 {
+    u32::try_from(value).map_or_else(
+        |_| {
+            tracing::warn!(
+                value,
+                context,
+                "usize value exceeds u32::MAX; clamping conversion to f64",
+            );
+            f64::from(u32::MAX)
+        },
+        f64::from,
+    )
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct ModelConfig {
+    pub max_iters: u64,
+    pub history: usize,
+    pub scale: f64,
+    pub interpolation: InterpolationStrategy,
+    /// Сезонные эффекты темпа; `None` — чистый локальный тренд.
+    pub seasonality: Option<SeasonalityConfig>,
+    /// Сколько начальных точек подбора пробовать; берётся наименьшая NLL.
+    pub restarts: usize,
+    /// Веса мягких штрафов на сигмы, чтобы дисперсия тренда не падала до пола.
+    pub sigma_prior: SigmaPrior,
+    /// Какую часть истории брать для обучения.
+    pub training_window: TrainingWindow,
+}
+
+impl Default for ModelConfig {
+    fn default() -> Self {
+        Self {
+            max_iters: DEFAULT_MAX_ITERS,
+            history: DEFAULT_HISTORY,
+            scale: DEFAULT_SCALE,
+            interpolation: InterpolationStrategy::default(),
+            seasonality: None,
+            restarts: DEFAULT_RESTARTS,
+            sigma_prior: SigmaPrior::default(),
+            training_window: TrainingWindow::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FittedModel {
+    pub sigma_level: f64,
+    pub sigma_trend: f64,
+    pub sigma_obs: f64,
+    pub state: [f64; 2],
+    pub cov: [[f64; 2]; 2],
+    pub last_date: NaiveDate,
+    /// Шаг обучающего ряда; прогноз идёт с тем же шагом.
+    pub granularity: Granularity,
+    pub scale: f64,
+    pub nll: f64,
+    pub last_weight: f64,
+    /// Влияние экзогенных регрессоров, если модель обучалась с ними.
+    pub regression: Option<RegressionFit>,
+    /// Сезонные эффекты темпа, если они включены.
+    pub seasonality: Option<SeasonalFit>,
+    pub optimization: OptimizationSummary,
+    /// Первый и последний день обучающего ряда.
+    pub training: TrainingSpan,
+}
+
+#[derive(Clone, Debug)]
+pub struct Forecast {
+    pub dates: Vec<NaiveDate>,
+    pub mean: Vec<f64>,
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+    pub variance: Vec<f64>,
+    pub granularity: Granularity,
+    /// Границы ряда, на котором обучена модель; нет в старых CSV.
+    pub training: Option<TrainingSpan>,
+}
+
+/// Скользящий бэктест: `folds` отсечений истории с шагом в горизонт прогноза.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BacktestConfig {
+    pub horizon_days: usize,
+    pub folds: usize,
+    /// Дни между концом обучения и началом проверки, которые не участвуют ни в чём.
+    pub gap_days: usize,
+    /// Длина скользящего окна обучения; `None` — вся история до отсечения.
+    pub train_days: Option<usize>,
+}
+
+impl BacktestConfig {
+    /// Разбиение ряда, на котором считается бэктест.
+    pub const fn split(self) -> TimeSeriesSplit {
+        let horizon = if self.horizon_days > 0 {
+            self.horizon_days
+        } else {
+            1
+        };
+        let folds = if self.folds > 0 { self.folds } else { 1 };
+        let split = match self.train_days {
+            Some(train_days) => TimeSeriesSplit::sliding(folds, horizon, train_days),
+            None => TimeSeriesSplit::expanding(folds, horizon),
+        };
+        split
+            .with_gap(self.gap_days)
+            .with_min_train(MIN_BACKTEST_TRAIN_DAYS)
+    }
+}
+
+/// Сглаженные (RTS) оценки уровня и тренда LLT-модели по всей обучающей истории.
+#[derive(Clone, Debug)]
+pub struct SmoothedStates {
+    pub dates: Vec<NaiveDate>,
+    /// Уровень, км².
+    pub level: Vec<f64>,
+    /// Дисперсия уровня, км²².
+    pub level_variance: Vec<f64>,
+    /// Тренд (изменение за сутки), км²/сутки.
+    pub trend: Vec<f64>,
+    /// Дисперсия тренда.
+    pub trend_variance: Vec<f64>,
+    /// Проверка остатков модели; `None`, если ряд слишком короткий.
+    pub diagnostics: Option<Diagnostics>,
+}
+
+impl SmoothedStates {
+    /// Нижняя и верхняя границы 95% интервала уровня.
+    pub fn level_band(&self) -> (Vec<f64>, Vec<f64>) {
+        confidence_band(&self.level, &self.level_variance)
+    }
+
+    /// Нижняя и верхняя границы 95% интервала тренда (км²/сутки).
+    pub fn trend_band(&self) -> (Vec<f64>, Vec<f64>) {
+        confidence_band(&self.trend, &self.trend_variance)
+    }
+}
+
+fn confidence_band(mean: &[f64], variance: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    mean.iter()
+        .zip(variance)
+        .map(|(mean, variance)| {
+            let std = variance.max(0.0).sqrt();
+            (
+                CONFIDENCE_Z.mul_add(-std, *mean),
+                CONFIDENCE_Z.mul_add(std, *mean),
+            )
+        })
+        .unzip()
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct TrendFilterConfig {
+    pub lambda: f64,
+    pub epsilon: f64,
+    pub huber_delta: f64,
+    pub damping: f64,
+    pub max_iters: u64,
+    pub history: usize,
+    pub scale: f64,
+    pub interpolation: InterpolationStrategy,
+    pub training_window: TrainingWindow,
+}
+
+impl Default for TrendFilterConfig {
+    fn default() -> Self {
+        Self {
+            lambda: DEFAULT_TF_LAMBDA,
+            epsilon: DEFAULT_TF_EPS,
+            huber_delta: DEFAULT_TF_HUBER_DELTA,
+            damping: DEFAULT_TF_DAMPING,
+            max_iters: DEFAULT_MAX_ITERS,
+            history: DEFAULT_HISTORY,
+            scale: DEFAULT_SCALE,
+            interpolation: InterpolationStrategy::default(),
+            training_window: TrainingWindow::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TrendFilterModel {
+    trend: Vec<f64>,
+    last_date: NaiveDate,
+    granularity: Granularity,
+    scale: f64,
+    resid_std: f64,
+    slope_std: f64,
+    damping: f64,
+    pub optimization: OptimizationSummary,
+    pub training: TrainingSpan,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastRow {
+    date: String,
+    mean: f64,
+    lower: f64,
+    upper: f64,
+    variance: f64,
+    /// Нет в CSV, записанных до появления недельного прогноза.
+    #[serde(default)]
+    granularity: Granularity,
+    /// Нет в CSV, записанных до настройки окна обучения.
+    #[serde(default)]
+    train_from: Option<String>,
+    #[serde(default)]
+    train_to: Option<String>,
+}
+
+pub fn write_forecast_csv(
+    forecast: &Forecast,
+    output_path: &Path,
+    precision: Precision,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = output_path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut writer = csv::Writer::from_path(output_path)?;
+    writer.write_record([
+        "date",
+        "mean",
+        "lower",
+        "upper",
+        "variance",
+        "granularity",
+        "train_from",
+        "train_to",
+    ])?;
+    let (train_from, train_to) = forecast.training.map_or_else(Default::default, |span| {
+        (
+            span.from.format(DATE_FORMAT).to_string(),
+            span.to.format(DATE_FORMAT).to_string(),
+        )
+    });
+
+    for idx in 0..forecast.dates.len() {
+        let date = forecast.dates[idx].format(DATE_FORMAT).to_string();
+        writer.write_record([
+            date,
+            precision.area(forecast.mean[idx]),
+            precision.area(forecast.lower[idx]),
+            precision.area(forecast.upper[idx]),
+            precision.variance(forecast.variance[idx]),
+            forecast.granularity.as_str().to_string(),
+            train_from.clone(),
+            train_to.clone(),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn write_smoothed_csv(
+    smoothed: &SmoothedStates,
+    output_path: &Path,
+    precision: Precision,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = output_path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut writer = csv::Writer::from_path(output_path)?;
+    writer.write_record(["date", "level", "level_variance", "trend", "trend_variance"])?;
+
+    for idx in 0..smoothed.dates.len() {
+        writer.write_record([
+            smoothed.dates[idx].format(DATE_FORMAT).to_string(),
+            precision.area(smoothed.level[idx]),
+            precision.variance(smoothed.level_variance[idx]),
+            precision.rate(smoothed.trend[idx]),
+            precision.variance(smoothed.trend_variance[idx]),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Сохраняет темп изменения (сглаженный тренд) с 95% интервалом, км²/сутки.
+pub fn write_trend_csv(
+    smoothed: &SmoothedStates,
+    output_path: &Path,
+    precision: Precision,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = output_path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let (lower, upper) = smoothed.trend_band();
+    let mut writer = csv::Writer::from_path(output_path)?;
+    writer.write_record(["date", "slope", "lower", "upper"])?;
+
+    for idx in 0..smoothed.dates.len() {
+        writer.write_record([
+            smoothed.dates[idx].format(DATE_FORMAT).to_string(),
+            precision.rate(smoothed.trend[idx]),
+            precision.rate(lower[idx]),
+            precision.rate(upper[idx]),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn read_forecast_csv(input_path: &Path) -> Result<Forecast, Box<dyn Error>> {
+    read_forecast_from_reader(csv::Reader::from_path(input_path)?)
+}
+
+/// Читает прогноз из любого источника CSV, например из распакованного `.csv.gz`.
+pub fn read_forecast_from_reader<R: std::io::Read>(
+    mut reader: csv::Reader<R>,
+) -> Result<Forecast, Box<dyn Error>> {
+    let mut dates = Vec::new();
+    let mut mean = Vec::new();
+    let mut lower = Vec::new();
+    let mut upper = Vec::new();
+    let mut variance = Vec::new();
+    let mut granularity = Granularity::default();
+    let mut training = None;
+
+    for row in reader.deserialize::<ForecastRow>() {
+        let row = row?;
+        granularity = row.granularity;
+        if let (Some(from), Some(to)) = (&row.train_from, &row.train_to) {
+            training = Some(TrainingSpan {
+                from: NaiveDate::parse_from_str(from, DATE_FORMAT)?,
+                to: NaiveDate::parse_from_str(to, DATE_FORMAT)?,
+            });
+        }
+        let date = NaiveDate::parse_from_str(&row.date, DATE_FORMAT)?;
+        dates.push(date);
+        mean.push(row.mean);
+        lower.push(row.lower);
+        upper.push(row.upper);
+        variance.push(row.variance);
+    }
+
+    if dates.is_empty() {
+        return Err("forecast CSV is empty".into());
+    }
+
+    Ok(Forecast {
+        dates,
+        mean,
+        lower,
+        upper,
+        variance,
+        granularity,
+        training,
+    })
+}
+
+pub fn train_from_buckets(
+    buckets: &AreaBuckets,
+    config: ModelConfig,
+    regressors: Option<&Regressors>,
+    granularity: Granularity,
+) -> Result<FittedModel, Box<dyn Error>> {
+    let (dates, values) =
+        load_target_series_from_buckets(buckets, config.interpolation, config.training_window)?;
+    let (dates, values) = granularity.aggregate(dates, values);
+    train_from_series_with_regressors(&dates, &values, config, regressors)
+}
+
+/// Обучает LLT-модель и сглаживает её состояния по всей истории.
+pub fn smooth_from_buckets(
+    buckets: &AreaBuckets,
+    config: ModelConfig,
+    regressors: Option<&Regressors>,
+) -> Result<SmoothedStates, Box<dyn Error>> {
+    let (dates, values) =
+        load_target_series_from_buckets(buckets, config.interpolation, config.training_window)?;
+    let fitted = train_from_series_with_regressors(&dates, &values, config, regressors)?;
+    fitted.smooth(&dates, &values)
+}
+
+/// Средняя абсолютная ошибка прогноза trend filter (км²) по скользящим отсечениям ряда.
+pub fn backtest_trend_filter_from_series(
+    dates: &[NaiveDate],
+    values: &[f64],
+    config: TrendFilterConfig,
+    backtest: BacktestConfig,
+) -> Result<f64, Box<dyn Error>> {
+    let mae = mean_abs_error(
+        dates,
+        values,
+        &backtest.split(),
+        |dates, values, horizon| {
+            train_trend_filter_from_series(dates, values, config)
+                .map(|model| model.forecast(horizon).mean)
+                .map_err(|err| err.to_string())
+        },
+    )?;
+    Ok(mae)
+}
+
+/// Конформно калибрует интервалы: `train` обучает модель на начале ряда и прогнозирует
+/// `steps` шагов, остатки на `config.folds` отсечениях задают множитель ширины.
+pub fn calibrate_from_buckets<F>(
+    buckets: &AreaBuckets,
+    interpolation: InterpolationStrategy,
+    window: TrainingWindow,
+    granularity: Granularity,
+    steps: usize,
+    config: CalibrationConfig,
+    train: F,
+) -> Result<Calibration, Box<dyn Error>>
+where
+    F: FnMut(&[NaiveDate], &[f64], usize) -> Result<Forecast, String>,
+{
+    let (dates, values) = load_target_series_from_buckets(buckets, interpolation, window)?;
+    let (dates, values) = granularity.aggregate(dates, values);
+    let split = BacktestConfig {
+        horizon_days: steps,
+        folds: config.folds,
+        gap_days: config.gap_days,
+        train_days: None,
+    }
+    .split();
+    Ok(calibration::conformal_calibration(
+        &dates, &values, &split, train,
+    )?)
+}
+
+pub fn train_trend_filter_from_buckets(
+    buckets: &AreaBuckets,
+    config: TrendFilterConfig,
+    granularity: Granularity,
+) -> Result<TrendFilterModel, Box<dyn Error>> {
+    let (dates, values) =
+        load_target_series_from_buckets(buckets, config.interpolation, config.training_window)?;
+    let (dates, values) = granularity.aggregate(dates, values);
+    train_trend_filter_from_series(&dates, &values, config)
+}
+
+/// Обучает LLT-модель; коэффициенты при `regressors` оцениваются вместе с дисперсиями шумов.
+pub fn train_from_series_with_regressors(
+    dates: &[NaiveDate],
+    values: &[f64],
+    config: ModelConfig,
+    regressors: Option<&Regressors>,
+) -> Result<FittedModel, Box<dyn Error>> {
+    if dates.is_empty() || values.is_empty() || dates.len() != values.len() {
+        return Err("dates/values must be non-empty and the same length".into());
+    }
+    if values.len() < 2 {
+        return Err("need at least 2 observations".into());
+    }
+
+    let scale = if config.scale > 0.0 {
+        config.scale
+    } else {
+        DEFAULT_SCALE
+    };
+    let design = regressors
+        .map(|regressors| regressors.standardized(dates))
+        .transpose()?;
+    let mut exog = design
+        .as_ref()
+        .map_or_else(Vec::new, |design| design.z.clone());
+    let regressor_count = exog.len();
+    if let Some(seasonality) = config.seasonality {
+        exog.extend(seasonality::design(seasonality.period, dates));
+    }
+    let series: Vec<f64> = values.iter().map(|v| v / scale).collect();
+    let mut init = initial_params(&series);
+    init.resize(init.len() + exog.len(), 0.0);
+    let weights = volatility_weights(&series);
+    let last_weight = weights.last().copied().unwrap_or(1.0);
+    let problem = LocalLinearTrendProblem {
+        y: series,
+        weights,
+        exog: exog.clone(),
+        seasonal_start: regressor_count,
+        seasonal_penalty: config
+            .seasonality
+            .map_or(0.0, |seasonality| seasonality.penalty * scale * scale),
+        sigma_prior: config.sigma_prior,
+        prior_center: [init[0], init[1], init[2]],
+    };
+    let starts = optimizer::perturbed_starts(&init, 3, config.restarts.max(1));
+    let (best, optimization) = optimizer::lbfgs_restarts(
+        &problem,
+        lbfgs(config.history),
+        starts,
+        config.max_iters,
+        "llt",
+    )?;
+    let (sigma_level, sigma_trend, sigma_obs) = unpack_params(&best);
+    let betas = best.get(3..).unwrap_or_default();
+    let filter_series: Vec<f64> = values.iter().map(|v| v / scale).collect();
+    let filter_weights = volatility_weights(&filter_series);
+    let filter = kalman_filter(
+        &remove_regressors(&filter_series, &exog, betas),
+        sigma_level,
+        sigma_trend,
+        sigma_obs,
+        Some(&filter_weights),
+    );
+    let last_date = *dates.last().ok_or("dates are empty after validation")?;
+
+    Ok(FittedModel {
+        granularity: series_granularity(dates),
+        sigma_level,
+        sigma_trend,
+        sigma_obs,
+        state: filter.state,
+        cov: filter.cov,
+        last_date,
+        scale,
+        nll: filter.nll,
+        last_weight,
+        regression: regressors.zip(design.as_ref()).map(|(regressors, design)| {
+            RegressionFit::new(regressors, design, &betas[..regressor_count], scale)
+        }),
+        seasonality: config.seasonality.map(|seasonality| {
+            SeasonalFit::new(seasonality.period, &betas[regressor_count..], scale, dates)
+        }),
+        optimization,
+        training: TrainingSpan::of(dates).ok_or("dates are empty after validation")?,
+    })
+}
+
+pub fn train_trend_filter_from_series(
+    dates: &[NaiveDate],
+    values: &[f64],
+    config: TrendFilterConfig,
+) -> Result<TrendFilterModel, Box<dyn Error>> {
+    if dates.is_empty() || values.is_empty() || dates.len() != values.len() {
+        return Err("dates/values must be non-empty and the same length".into());
+    }
+    if values.len() < 3 {
+        return Err("need at least 3 observations".into());
+    }
+
+    let scale = if config.scale > 0.0 {
+        config.scale
+    } else {
+        DEFAULT_SCALE
+    };
+    let series: Vec<f64> = values.iter().map(|v| v / scale).collect();
+
+    let lambda = config.lambda.max(0.0);
+    let epsilon = config.epsilon.max(1e-9);
+    let diffs: Vec<f64> = series.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    let diff_std = stddev(&diffs);
+    let huber_delta = if config.huber_delta > 0.0 {
+        config.huber_delta
+    } else {
+        (1.5 * diff_std).max(1e-3)
+    };
+
+    let problem = TrendFilterProblem::new(series.clone(), lambda, epsilon, huber_delta);
+    let init = series.clone();
+    let (trend, optimization) = optimizer::lbfgs(
+        problem,
+        lbfgs(config.history),
+        init,
+        config.max_iters,
+        "trend_filter",
+    )?;
+
+    let residuals: Vec<f64> = series
+        .iter()
+        .zip(trend.iter())
+        .map(|(y, f)| y - f)
+        .collect();
+    let mut resid_std = stddev(&residuals);
+    let floor_resid = (0.25 * diff_std).max(1e-3);
+    if !resid_std.is_finite() || resid_std < floor_resid {
+        resid_std = floor_resid;
+    }
+
+    let slopes: Vec<f64> = trend.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    let mut slope_std = stddev(&slopes);
+    let floor_slope = (0.10 * diff_std).max(1e-4);
+    if !slope_std.is_finite() || slope_std < floor_slope {
+        slope_std = floor_slope;
+    }
+    let last_date = *dates.last().ok_or("dates are empty after validation")?;
+
+    Ok(TrendFilterModel {
+        trend,
+        last_date,
+        granularity: series_granularity(dates),
+        scale,
+        resid_std,
+        slope_std,
+        damping: config.damping,
+        optimization,
+        training: TrainingSpan::of(dates).ok_or("dates are empty after validation")?,
+    })
+}
+
+impl FittedModel {
+    /// Вклад регрессоров и накопленных сезонных надбавок на обучающих датах, км².
+    fn exogenous_effect(&self, dates: &[NaiveDate]) -> Result<Vec<f64>, String> {
+        let seasonal = self
+            .seasonality
+            .as_ref()
+            .map_or_else(|| vec![0.0; dates.len()], |fit| fit.cumulative(dates));
+        dates
+            .iter()
+            .zip(seasonal)
+            .map(|(date, seasonal)| {
+                self.regression.as_ref().map_or(Ok(seasonal), |regression| {
+                    regression
+                        .effect(*date)
+                        .map(|effect| effect + seasonal)
+                        .ok_or_else(|| format!("regressors have no value on {date}"))
+                })
+            })
+            .collect()
+    }
+
+    /// Прогоняет фильтр с найденными параметрами и сглаживает состояния обратным проходом.
+    pub fn smooth(
+        &self,
+        dates: &[NaiveDate],
+        values: &[f64],
+    ) -> Result<SmoothedStates, Box<dyn Error>> {
+        if dates.is_empty() || dates.len() != values.len() {
+            return Err("dates/values must be non-empty and the same length".into());
+        }
+        let series: Vec<f64> = values.iter().map(|v| v / self.scale).collect();
+        let weights = volatility_weights(&series);
+        let series = self
+            .exogenous_effect(dates)?
+            .iter()
+            .zip(&series)
+            .map(|(effect, value)| value - effect / self.scale)
+            .collect::<Vec<_>>();
+        let mut steps = Vec::with_capacity(series.len());
+        let filter = kalman_filter_with(
+            &series,
+            self.sigma_level,
+            self.sigma_trend,
+            self.sigma_obs,
+            Some(&weights),
+            |step| steps.push(step),
+        );
+        if steps.len() != series.len() || filter.nll >= LARGE_COST {
+            return Err("kalman filter diverged before smoothing".into());
+        }
+
+        let scale_sq = self.scale * self.scale;
+        let innovations = steps
+            .iter()
+            .map(|step| step.innovation / step.innovation_var.sqrt())
+            .collect::<Vec<_>>();
+        let diagnostics = diagnostics::diagnose(
+            &innovations,
+            filter.nll,
+            (
+                self.sigma_level * self.scale,
+                self.sigma_trend * self.scale,
+                self.sigma_obs * self.scale,
+            ),
+        );
+        let smoothed = rts_smooth(&steps);
+        Ok(SmoothedStates {
+            dates: dates.to_vec(),
+            level: smoothed
+                .iter()
+                .map(|(state, _)| state[0] * self.scale)
+                .collect(),
+            level_variance: smoothed
+                .iter()
+                .map(|(_, cov)| cov[0][0].max(0.0) * scale_sq)
+                .collect(),
+            trend: smoothed
+                .iter()
+                .map(|(state, _)| state[1] * self.scale)
+                .collect(),
+            trend_variance: smoothed
+                .iter()
+                .map(|(_, cov)| cov[1][1].max(0.0) * scale_sq)
+                .collect(),
+            diagnostics,
+        })
+    }
+
+    /// Прогноз на `horizon` шагов ряда (дней или недель, см. [`Granularity`]).
+    pub fn forecast(&self, horizon: usize) -> Forecast {
+        let mut dates = Vec::with_capacity(horizon);
+        let mut mean = Vec::with_capacity(horizon);
+        let mut lower = Vec::with_capacity(horizon);
+        let mut upper = Vec::with_capacity(horizon);
+        let mut variance = Vec::with_capacity(horizon);
+
+        let weight = self.last_weight.clamp(HETERO_MIN_SCALE, HETERO_MAX_SCALE);
+        let q_level = self.sigma_level * self.sigma_level * weight * weight;
+        let q_trend = self.sigma_trend * self.sigma_trend * weight * weight;
+        let r = self.sigma_obs * self.sigma_obs * weight * weight;
+
+        let mut state = self.state;
+        let mut cov = self.cov;
+        let mut seasonal = None;
+        let mut previous_day = self.last_date;
+
+        for step in 1..=horizon {
+            predict_state(&mut state, &mut cov, q_level, q_trend);
+            let y_mean = state[0];
+            let y_var = (cov[0][0] + r).max(0.0);
+            let std = y_var.sqrt();
+
+            let Some(step_days) =
+                usize_to_i64(step * self.granularity.step_days(), "fitted_model_forecast")
+            else {
+                break;
+            };
+            let day = self.last_date + Duration::days(step_days);
+            // Неопределённость коэффициентов в интервал не входит.
+            seasonal = self
+                .seasonality
+                .as_ref()
+                .map(|fit| fit.step(seasonal, previous_day, day));
+            previous_day = day;
+            let effect = self
+                .regression
+                .as_ref()
+                .and_then(|regression| regression.effect(day))
+                .unwrap_or(0.0)
+                + seasonal.unwrap_or(0.0);
+            dates.push(day);
+            mean.push(y_mean.mul_add(self.scale, effect));
+            variance.push(y_var * self.scale * self.scale);
+            lower.push(
+                CONFIDENCE_Z
+                    .mul_add(-std, y_mean)
+                    .mul_add(self.scale, effect),
+            );
+            upper.push(
+                CONFIDENCE_Z
+                    .mul_add(std, y_mean)
+                    .mul_add(self.scale, effect),
+            );
+        }
+
+        Forecast {
+            dates,
+            mean,
+            lower,
+            upper,
+            variance,
+            granularity: self.granularity,
+            training: Some(self.training),
+        }
+    }
+}
+
+impl TrendFilterModel {
+    /// Прогноз на `horizon` шагов ряда (дней или недель, см. [`Granularity`]).
+    pub fn forecast(&self, horizon: usize) -> Forecast {
+        let mut dates = Vec::with_capacity(horizon);
+        let mut mean = Vec::with_capacity(horizon);
+        let mut lower = Vec::with_capacity(horizon);
+        let mut upper = Vec::with_capacity(horizon);
+        let mut variance = Vec::with_capacity(horizon);
+
+        let last = self.trend.last().copied().unwrap_or(0.0);
+        let slope = if self.trend.len() > 1 {
+            last - self.trend[self.trend.len() - 2]
+        } else {
+            0.0
+        };
+        let resid_var = self.resid_std * self.resid_std;
+        let slope_var = self.slope_std * self.slope_std;
+        let phi = self.damping.clamp(0.0, 1.0);
+
+        for step in 1..=horizon {
+            let step_f = usize_to_f64(step, "trend_filter_forecast");
+            let sum_phi = if (phi - 1.0).abs() < 1e-12 {
+                step_f
+            } else {
+                (1.0 - phi.powf(step_f)) / (1.0 - phi)
+            };
+            let mean_scaled = last + slope * sum_phi;
+            let Some(step_days) =
+                usize_to_i64(step * self.granularity.step_days(), "trend_filter_forecast")
+            else {
+                break;
+            };
+            let day = self.last_date + Duration::days(step_days);
+            let var = (sum_phi * sum_phi).mul_add(slope_var, resid_var).max(0.0);
+            let std = var.sqrt();
+
+            dates.push(day);
+            mean.push(mean_scaled * self.scale);
+            variance.push(var * self.scale * self.scale);
+            lower.push(CONFIDENCE_Z.mul_add(-std, mean_scaled) * self.scale);
+            upper.push(CONFIDENCE_Z.mul_add(std, mean_scaled) * self.scale);
+        }
+
+        Forecast {
+            dates,
+            mean,
+            lower,
+            upper,
+            variance,
+            granularity: self.granularity,
+            training: Some(self.training),
+        }
+    }
+}
+
+const fn lbfgs(history: usize) -> optimizer::Lbfgs {
+    optimizer::Lbfgs {
+        history,
+        tol_grad: DEFAULT_TOL_GRAD,
+        tol_cost: DEFAULT_TOL_COST,
+    }
+}
+
+#[derive(Clone)]
+struct TrendFilterProblem {
+    y: Vec<f64>,
+    lambda: f64,
+    epsilon: f64,
+    huber_delta: f64,
+}
+
+impl TrendFilterProblem {
+    const fn new(y: Vec<f64>, lambda: f64, epsilon: f64, huber_delta: f64) -> Self {
+        Self {
+            y,
+            lambda,
+            epsilon,
+            huber_delta,
+        }
+    }
+}
+
+#[cfg(feature = "forecast")]
+impl CostFunction for TrendFilterProblem {
+    type Param = Vec<f64>;
+    type Output = f64;
+
+    fn cost(&self, param: &Self::Param) -> Result<Self::Output, ArgminError> {
+        if param.len() != self.y.len() {
+            return Ok(LARGE_COST);
+        }
+        let mut cost = 0.0;
+        let delta = self.huber_delta.max(1e-6);
+        for (value, target) in param.iter().zip(self.y.iter()) {
+            let diff = value - target;
+            cost += huber_loss(diff, delta);
+        }
+        if param.len() >= 3 && self.lambda > 0.0 {
+            let eps2 = self.epsilon * self.epsilon;
+            for idx in 2..param.len() {
+                let d2 = 2.0f64.mul_add(-param[idx - 1], param[idx]) + param[idx - 2];
+                cost += self.lambda * d2.mul_add(d2, eps2).sqrt();
+            }
+        }
+        Ok(cost)
+    }
+}
+
+#[cfg(feature = "forecast")]
+impl Gradient for TrendFilterProblem {
+    type Param = Vec<f64>;
+    type Gradient = Vec<f64>;
+
+    fn gradient(&self, param: &Self::Param) -> Result<Self::Gradient, ArgminError> {
+        if param.len() != self.y.len() {
+            return Ok(vec![0.0; param.len()]);
+        }
+        let mut grad = vec![0.0; param.len()];
+        let delta = self.huber_delta.max(1e-6);
+        for (idx, (value, target)) in param.iter().zip(self.y.iter()).enumerate() {
+            grad[idx] += huber_grad(value - target, delta);
+        }
+        if param.len() >= 3 && self.lambda > 0.0 {
+            let eps2 = self.epsilon * self.epsilon;
+            for idx in 2..param.len() {
+                let d2 = 2.0f64.mul_add(-param[idx - 1], param[idx]) + param[idx - 2];
+                let denom = d2.mul_add(d2, eps2).sqrt();
+                let g = if denom > 0.0 {
+                    self.lambda * d2 / denom
+                } else {
+                    0.0
+                };
+                grad[idx] += g;
+                grad[idx - 1] += -2.0 * g;
+                grad[idx - 2] += g;
+            }
+        }
+        Ok(grad)
+    }
+}
+
+#[derive(Clone)]
+struct LocalLinearTrendProblem {
+    y: Vec<f64>,
+    weights: Vec<f64>,
+    /// Стандартизованные регрессоры и сезонные контрасты `[регрессор][день]`;
+    /// их коэффициенты идут после трёх сигм.
+    exog: Vec<Vec<f64>>,
+    /// С этого регрессора начинаются сезонные контрасты.
+    seasonal_start: usize,
+    /// Вес гребневого штрафа на сезонные эффекты в масштабе модели.
+    seasonal_penalty: f64,
+    sigma_prior: SigmaPrior,
+    /// Логарифмы эвристических сигм, вокруг которых действует `sigma_prior`.
+    prior_center: [f64; 3],
+}
+
+impl LocalLinearTrendProblem {
+    fn nll(&self, param: &[f64]) -> f64 {
+        if param.len() != 3 + self.exog.len() {
+            return LARGE_COST;
+        }
+        let (sigma_level, sigma_trend, sigma_obs) = unpack_params(param);
+        if !sigma_level.is_finite() || !sigma_trend.is_finite() || !sigma_obs.is_finite() {
+            return LARGE_COST;
+        }
+        let result = kalman_filter(
+            &remove_regressors(&self.y, &self.exog, &param[3..]),
+            sigma_level,
+            sigma_trend,
+            sigma_obs,
+            Some(&self.weights),
+        );
+        let seasonal = param.get(3 + self.seasonal_start..).unwrap_or_default();
+        let penalty = if seasonal.is_empty() {
+            0.0
+        } else {
+            0.5 * self.seasonal_penalty
+                * seasonality::effects(seasonal)
+                    .iter()
+                    .map(|effect| effect * effect)
+                    .sum::<f64>()
+        };
+        let prior = self.sigma_prior.penalty(&param[..3], &self.prior_center);
+        if result.nll.is_finite() {
+            result.nll + penalty + prior
+        } else {
+            LARGE_COST
+        }
+    }
+}
+
+#[cfg(feature = "forecast")]
+impl CostFunction for LocalLinearTrendProblem {
+    type Param = Vec<f64>;
+    type Output = f64;
+
+    fn cost(&self, param: &Self::Param) -> Result<Self::Output, ArgminError> {
+        Ok(self.nll(param))
+    }
+}
+
+#[cfg(feature = "forecast")]
+impl Gradient for LocalLinearTrendProblem {
+    type Param = Vec<f64>;
+    type Gradient = Vec<f64>;
+
+    fn gradient(&self, param: &Self::Param) -> Result<Self::Gradient, ArgminError> {
+        let mut grad = vec![0.0; param.len()];
+        for i in 0..param.len() {
+            let step = 1e-4 * (1.0 + param[i].abs());
+            let mut plus = param.clone();
+            let mut minus = param.clone();
+            plus[i] += step;
+            minus[i] -= step;
+            let f_plus = self.nll(&plus);
+            let f_minus = self.nll(&minus);
+            grad[i] = (f_plus - f_minus) / (2.0 * step);
+        }
+        Ok(grad)
+    }
+}
+
+/// Шаг ряда по двум последним датам: недельный ряд получается из [`Granularity::aggregate`].
+fn series_granularity(dates: &[NaiveDate]) -> Granularity {
+    let step = match dates {
+        [.., previous, last] => (*last - *previous).num_days(),
+        _ => 1,
+    };
+    if usize_to_i64(Granularity::Week.step_days(), "series_granularity") == Some(step) {
+        Granularity::Week
+    } else {
+        Granularity::Day
+    }
+}
+
+/// Ряд за вычетом вклада регрессоров `Σ β_j · z_j,t`.
+fn remove_regressors(y: &[f64], exog: &[Vec<f64>], betas: &[f64]) -> Vec<f64> {
+    y.iter()
+        .enumerate()
+        .map(|(idx, value)| {
+            exog.iter()
+                .zip(betas)
+                .fold(*value, |acc, (z, beta)| (-beta).mul_add(z[idx], acc))
+        })
+        .collect()
+}
+
+struct FilterResult {
+    nll: f64,
+    state: [f64; 2],
+    cov: [[f64; 2]; 2],
+}
+
+/// Состояние фильтра на одном шаге: прогноз до наблюдения и оценка после него.
+#[derive(Clone, Copy, Debug)]
+struct FilterStep {
+    predicted_state: [f64; 2],
+    predicted_cov: [[f64; 2]; 2],
+    state: [f64; 2],
+    cov: [[f64; 2]; 2],
+    /// Ошибка прогноза наблюдения и её дисперсия.
+    innovation: f64,
+    innovation_var: f64,
+}
+
+fn kalman_filter(
+    y: &[f64],
+    sigma_level: f64,
+    sigma_trend: f64,
+    sigma_obs: f64,
+    weights: Option<&[f64]>,
+) -> FilterResult {
+    kalman_filter_with(y, sigma_level, sigma_trend, sigma_obs, weights, |_| {})
+}
+
+fn kalman_filter_with(
+    y: &[f64],
+    sigma_level: f64,
+    sigma_trend: f64,
+    sigma_obs: f64,
+    weights: Option<&[f64]>,
+    mut on_step: impl FnMut(FilterStep),
+) -> FilterResult {
+    if y.is_empty() {
+        return FilterResult {
+            nll: LARGE_COST,
+            state: [0.0, 0.0],
+            cov: [[0.0, 0.0], [0.0, 0.0]],
+        };
+    }
+
+    let sigma_level = sigma_level.max(MIN_SIGMA);
+    let sigma_trend = sigma_trend.max(MIN_SIGMA);
+    let sigma_obs = sigma_obs.max(MIN_SIGMA);
+
+    let q_level = sigma_level * sigma_level;
+    let q_trend = sigma_trend * sigma_trend;
+    let r = sigma_obs * sigma_obs;
+
+    let mut state = [y[0], 0.0];
+    let mut cov = [[1.0e4, 0.0], [0.0, 1.0e2]];
+    let mut nll = 0.0;
+
+    for (idx, &obs) in y.iter().enumerate() {
+        let weight = weights
+            .and_then(|series| series.get(idx).copied())
+            .unwrap_or(1.0)
+            .clamp(HETERO_MIN_SCALE, HETERO_MAX_SCALE);
+        let q_level_t = q_level * weight * weight;
+        let q_trend_t = q_trend * weight * weight;
+        let r_t = r * weight * weight;
+
+        predict_state(&mut state, &mut cov, q_level_t, q_trend_t);
+        let (predicted_state, predicted_cov) = (state, cov);
+
+        let y_pred = state[0];
+        let innovation = obs - y_pred;
+        let s = cov[0][0] + r_t;
+        if s <= 0.0 || !s.is_finite() {
+            return FilterResult {
+                nll: LARGE_COST,
+                state,
+                cov,
+            };
+        }
+
+        let k0 = cov[0][0] / s;
+        let k1 = cov[1][0] / s;
+
+        state[0] += k0 * innovation;
+        state[1] += k1 * innovation;
+
+        let p00 = cov[0][0];
+        let p01 = cov[0][1];
+        let p11 = cov[1][1];
+
+        cov[0][0] = (1.0 - k0) * p00;
+        cov[0][1] = (1.0 - k0) * p01;
+        cov[1][0] = cov[0][1];
+        cov[1][1] = p11 - k1 * p01;
+        on_step(FilterStep {
+            predicted_state,
+            predicted_cov,
+            state,
+            cov,
+            innovation,
+            innovation_var: s,
+        });
+
+        nll += 0.5 * ((2.0 * std::f64::consts::PI * s).ln() + (innovation * innovation) / s);
+    }
+
+    FilterResult { nll, state, cov }
+}
+
+fn predict_state(state: &mut [f64; 2], cov: &mut [[f64; 2]; 2], q_level: f64, q_trend: f64) {
+    state[0] += state[1];
+
+    let p00 = cov[0][0];
+    let p01 = cov[0][1];
+    let p11 = cov[1][1];
+
+    let p00_pred = 2.0f64.mul_add(p01, p00) + p11 + q_level;
+    let p01_pred = p01 + p11;
+    let p11_pred = p11 + q_trend;
+
+    cov[0][0] = p00_pred;
+    cov[0][1] = p01_pred;
+    cov[1][0] = p01_pred;
+    cov[1][1] = p11_pred;
+}
+
+/// Сглаживатель Рауха–Тунга–Штрибеля: обратный проход по шагам фильтра.
+fn rts_smooth(steps: &[FilterStep]) -> Vec<([f64; 2], [[f64; 2]; 2])> {
+    let mut smoothed: Vec<_> = steps.iter().map(|step| (step.state, step.cov)).collect();
+    for idx in (0..steps.len().saturating_sub(1)).rev() {
+        let current = &steps[idx];
+        let next = &steps[idx + 1];
+        let Some(pred_inv) = invert_2x2(next.predicted_cov) else {
+            continue;
+        };
+        // J = P_t · Fᵀ · P_{t+1|t}⁻¹, где F = [[1, 1], [0, 1]].
+        let cov_ft = [
+            [current.cov[0][0] + current.cov[0][1], current.cov[0][1]],
+            [current.cov[1][0] + current.cov[1][1], current.cov[1][1]],
+        ];
+        let gain = mat_mul_2x2(cov_ft, pred_inv);
+        let (next_state, next_cov) = smoothed[idx + 1];
+        let state_diff = [
+            next_state[0] - next.predicted_state[0],
+            next_state[1] - next.predicted_state[1],
+        ];
+        let state = [
+            gain[0][1].mul_add(
+                state_diff[1],
+                gain[0][0].mul_add(state_diff[0], current.state[0]),
+            ),
+            gain[1][1].mul_add(
+                state_diff[1],
+                gain[1][0].mul_add(state_diff[0], current.state[1]),
+            ),
+        ];
+        let mut cov_diff = [[0.0; 2]; 2];
+        for row in 0..2 {
+            for col in 0..2 {
+                cov_diff[row][col] = next_cov[row][col] - next.predicted_cov[row][col];
+            }
+        }
+        let correction = mat_mul_2x2(mat_mul_2x2(gain, cov_diff), transpose_2x2(gain));
+        let mut cov = current.cov;
+        for row in 0..2 {
+            for col in 0..2 {
+                cov[row][col] += correction[row][col];
+            }
+        }
+        smoothed[idx] = (state, cov);
+    }
+    smoothed
+}
+
+fn mat_mul_2x2(lhs: [[f64; 2]; 2], rhs: [[f64; 2]; 2]) -> [[f64; 2]; 2] {
+    let mut out = [[0.0; 2]; 2];
+    for row in 0..2 {
+        for col in 0..2 {
+            out[row][col] = lhs[row][0].mul_add(rhs[0][col], lhs[row][1] * rhs[1][col]);
+        }
+    }
+    out
+}
+
+const fn transpose_2x2(matrix: [[f64; 2]; 2]) -> [[f64; 2]; 2] {
+    [[matrix[0][0], matrix[1][0]], [matrix[0][1], matrix[1][1]]]
+}
+
+fn invert_2x2(matrix: [[f64; 2]; 2]) -> Option<[[f64; 2]; 2]> {
+    let det = matrix[0][0].mul_add(matrix[1][1], -(matrix[0][1] * matrix[1][0]));
+    if det.abs() <= f64::EPSILON || !det.is_finite() {
+        return None;
+    }
+    Some([
+        [matrix[1][1] / det, -matrix[0][1] / det],
+        [-matrix[1][0] / det, matrix[0][0] / det],
+    ])
+}
+
+fn unpack_params(param: &[f64]) -> (f64, f64, f64) {
+    if param.len() < 3 {
+        return (MIN_SIGMA, MIN_SIGMA, MIN_SIGMA);
+    }
+    let sigma_level = param[0].exp().max(MIN_SIGMA);
+    let sigma_trend = param[1].exp().max(MIN_SIGMA);
+    let sigma_obs = param[2].exp().max(MIN_SIGMA);
+    (sigma_level, sigma_trend, sigma_obs)
+}
+
+fn initial_params(series: &[f64]) -> Vec<f64> {
+    let diffs: Vec<f64> = series.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    let diff_std = stddev(&diffs).max(1e-3);
+    let sigma_obs = diff_std;
+    let sigma_level = diff_std * 0.5;
+    let sigma_trend = diff_std * 0.1;
+    vec![sigma_level.ln(), sigma_trend.ln(), sigma_obs.ln()]
+}
+
+fn stddev(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 1.0;
+    }
+    let mean = values.iter().sum::<f64>() / usize_to_f64(values.len(), "stddev");
+    let var = values
+        .iter()
+        .map(|value| {
+            let diff = value - mean;
+            diff * diff
+        })
+        .sum::<f64>()
+        / usize_to_f64(values.len(), "stddev");
+    var.sqrt().max(1e-6)
+}
+
+fn huber_loss(diff: f64, delta: f64) -> f64 {
+    let abs = diff.abs();
+    if abs <= delta {
+        0.5 * diff * diff
+    } else {
+        delta * 0.5f64.mul_add(-delta, abs)
+    }
+}
+
+fn huber_grad(diff: f64, delta: f64) -> f64 {
+    let abs = diff.abs();
+    if abs <= delta {
+        diff
+    } else if diff > 0.0 {
+        delta
+    } else {
+        -delta
+    }
+}
+
+fn volatility_weights(series: &[f64]) -> Vec<f64> {
+    let n = series.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    if n == 1 {
+        return vec![1.0];
+    }
+
+    let mut diffs = Vec::with_capacity(n);
+    diffs.push(0.0);
+    for pair in series.windows(2) {
+        diffs.push((pair[1] - pair[0]).abs());
+    }
+
+    let mut vol = vec![0.0; n];
+    for (idx, slot) in vol.iter_mut().enumerate().skip(1) {
+        let start = idx.saturating_sub(HETERO_WINDOW - 1).max(1);
+        let window = &diffs[start..=idx];
+        let sum: f64 = window.iter().sum();
+        let count = window.len();
+        *slot = if count > 0 {
+            sum / usize_to_f64(count, "volatility_weights")
+        } else {
+            0.0
+        };
+    }
+    vol[0] = vol[1];
+
+    let mut sample = vol[1..].to_vec();
+    let median = if sample.is_empty() {
+        1.0
+    } else {
+        let mid = sample.len() / 2;
+        sample.select_nth_unstable_by(mid, |a, b| {
+            a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        sample[mid]
+    };
+
+    if !median.is_finite() || median <= 1e-9 {
+        return vec![1.0; n];
+    }
+
+    vol.into_iter()
+        .map(|v| {
+            let ratio = v / median;
+            ratio.clamp(HETERO_MIN_SCALE, HETERO_MAX_SCALE)
+        })
+        .collect()
+}
+
+/// Целевой ряд для обучения: занятая площадь по дням окна `window`;
+/// исключённые дни ([`AreaBuckets::exclude_from_training`]) заполняются интерполяцией.
+pub fn load_target_series_from_buckets(
+    buckets: &AreaBuckets,
+    interpolation: InterpolationStrategy,
+    window: TrainingWindow,
+) -> Result<(Vec<NaiveDate>, Vec<f64>), Box<dyn Error>> {
+    let (dates, values) = build_occupied_series_with(&buckets.for_training(), interpolation)?;
+    let (dates, values) = window.apply(dates, values);
+    if dates.is_empty() {
+        return Err(format!("no training data in window ({window})").into());
+    }
+    Ok((dates, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{SmoothedStates, kalman_filter_with, rts_smooth, write_smoothed_csv};
+    use crate::constants::Precision;
+
+    /// Шаги фильтра по ряду без шума: уровень `100 + 2t`, тренд ровно 2 в сутки.
+    fn linear_steps() -> Vec<super::FilterStep> {
+        let series = (0..30)
+            .map(|t| 2.0f64.mul_add(f64::from(t), 100.0))
+            .collect::<Vec<_>>();
+        let mut steps = Vec::new();
+        kalman_filter_with(&series, 0.1, 0.01, 0.5, None, |step| steps.push(step));
+        assert_eq!(steps.len(), series.len());
+        steps
+    }
+
+    #[test]
+    fn rts_smoother_matches_filter_at_the_end_and_narrows_variances() {
+        let steps = linear_steps();
+        let smoothed = rts_smooth(&steps);
+        let last = steps.last().expect("steps are not empty");
+        assert_eq!(smoothed.last(), Some(&(last.state, last.cov)));
+
+        for (step, (_, cov)) in steps.iter().zip(&smoothed) {
+            assert!(cov[0][0] <= step.cov[0][0] + 1e-12);
+            assert!(cov[1][1] <= step.cov[1][1] + 1e-12);
+        }
+        // Фильтр в начале ряда ещё не знает тренда, а сглаживатель видит весь ряд: у прямой
+        // без шума сглаженный тренд — её наклон, а уровень — сами наблюдения.
+        assert!(steps[0].state[1].abs() < 0.5);
+        for (t, (state, _)) in (0..).zip(&smoothed) {
+            assert!((state[1] - 2.0).abs() < 0.05, "trend at {t}: {}", state[1]);
+            assert!((state[0] - 2.0f64.mul_add(f64::from(t), 100.0)).abs() < 0.5);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "forecast")]
+    fn fitted_model_smooths_back_to_its_last_filtered_state() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date");
+        let dates = (0..120)
+            .map(|day| start + chrono::Days::new(day))
+            .collect::<Vec<_>>();
+        let values = (0..120u32)
+            .map(|day| f64::from(day).mul_add(15.0, 100_000.0) + f64::from((day * 7) % 5))
+            .collect::<Vec<_>>();
+        let fitted = super::train_from_series_with_regressors(
+            &dates,
+            &values,
+            super::ModelConfig::default(),
+            None,
+        )
+        .expect("model should train");
+        let smoothed = fitted
+            .smooth(&dates, &values)
+            .expect("states should smooth");
+
+        assert_eq!(smoothed.dates, dates);
+        let last = smoothed.level.len() - 1;
+        assert!(
+            fitted.state[0]
+                .mul_add(-fitted.scale, smoothed.level[last])
+                .abs()
+                < 1e-6
+        );
+        assert!(
+            fitted.state[1]
+                .mul_add(-fitted.scale, smoothed.trend[last])
+                .abs()
+                < 1e-6
+        );
+        // Внутри ряда сглаживатель видит и прошлое, и будущее: тренд — наклон ряда.
+        assert!((smoothed.trend[last / 2] - 15.0).abs() < 1.0);
+        let scale_sq = fitted.scale * fitted.scale;
+        assert!(
+            fitted.cov[0][0]
+                .mul_add(-scale_sq, smoothed.level_variance[last])
+                .abs()
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn smoothed_states_round_trip_through_csv() {
+        let day = |day| NaiveDate::from_ymd_opt(2024, 5, day).expect("valid date");
+        let states = SmoothedStates {
+            dates: vec![day(1), day(2)],
+            level: vec![100_000.123_4, 100_012.5],
+            level_variance: vec![4.0, 2.25],
+            trend: vec![12.345, 12.5],
+            trend_variance: vec![0.25, 0.5],
+            diagnostics: None,
+        };
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("smoothed.csv");
+        write_smoothed_csv(&states, &path, Precision::default()).expect("CSV should be written");
+
+        let mut reader = csv::Reader::from_path(&path).expect("CSV should open");
+        assert_eq!(
+            reader.headers().expect("header"),
+            vec!["date", "level", "level_variance", "trend", "trend_variance"]
+        );
+        let rows = reader
+            .records()
+            .map(|record| {
+                let record = record.expect("row");
+                let number = |idx: usize| record[idx].parse::<f64>().expect("number");
+                (
+                    record[0].to_string(),
+                    number(1),
+                    number(2),
+                    number(3),
+                    number(4),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                ("2024-05-01".to_string(), 100_000.12, 4.0, 12.35, 0.25),
+                ("2024-05-02".to_string(), 100_012.5, 2.25, 12.5, 0.5),
+            ]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Конформная калибровка интервалов прогноза по остаткам скользящего бэктеста.
+//!
+//! На каждом фолде модель прогнозирует проверочный отрезок, и для каждого дня считается
+//! нормированная ошибка `|y - mean| / std`. Её эмпирический квантиль уровня `⌈(n + 1)·0.95⌉ / n`
+//! делится на `z = 1.96`: получается множитель ширины гауссовского интервала, при котором
+//! доля попаданий на бэктесте совпадает с номинальными 95%.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use super::{CONFIDENCE_Z, Forecast, MIN_SIGMA, TimeSeriesSplit};
+
+/// Номинальное покрытие интервала `mean ± 1.96·std`.
+pub const NOMINAL_COVERAGE: f64 = 0.95;
+pub const DEFAULT_CALIBRATION_FOLDS: usize = 5;
+
+/// Параметры бэктеста для калибровки; горизонт берётся из прогноза.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CalibrationConfig {
+    pub folds: usize,
+    /// Дни между концом обучения и началом проверки.
+    pub gap_days: usize,
+}
+
+impl Default for CalibrationConfig {
+    fn default() -> Self {
+        Self {
+            folds: DEFAULT_CALIBRATION_FOLDS,
+            gap_days: 0,
+        }
+    }
+}
+
+/// Результат калибровки; попадает в манифест сборки.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Calibration {
+    /// Множитель ширины интервала: больше 1 — модель была слишком уверена.
+    pub factor: f64,
+    pub nominal_coverage: f64,
+    /// Доля проверочных дней внутри исходного (некалиброванного) интервала.
+    pub empirical_coverage: f64,
+    /// Число проверочных дней во всех фолдах.
+    pub residuals: usize,
+}
+
+impl Forecast {
+    /// Растягивает (или сужает) интервал вокруг среднего в `factor` раз.
+    pub fn calibrate(&mut self, factor: f64) {
+        for ((mean, lower), upper) in self
+            .mean
+            .iter()
+            .zip(self.lower.iter_mut())
+            .zip(self.upper.iter_mut())
+        {
+            *lower = factor.mul_add(*lower - mean, *mean);
+            *upper = factor.mul_add(*upper - mean, *mean);
+        }
+        for variance in &mut self.variance {
+            *variance *= factor * factor;
+        }
+    }
+}
+
+/// Множитель интервала по прогнозам `forecast` на фолдах `split`.
+pub fn conformal_calibration<F>(
+    dates: &[NaiveDate],
+    values: &[f64],
+    split: &TimeSeriesSplit,
+    mut forecast: F,
+) -> Result<Calibration, String>
+where
+    F: FnMut(&[NaiveDate], &[f64], usize) -> Result<Forecast, String>,
+{
+    if dates.len() != values.len() {
+        return Err("dates/values must be the same length".to_string());
+    }
+    let mut scores = Vec::new();
+    for fold in split.split(values.len())? {
+        let predicted = forecast(
+            &dates[fold.train.clone()],
+            &values[fold.train],
+            split.horizon,
+        )?;
+        if predicted.mean.len() < split.horizon {
+            return Err(format!(
+                "forecast returned {} values for a horizon of {}",
+                predicted.mean.len(),
+                split.horizon
+            ));
+        }
+        scores.extend(
+            predicted
+                .mean
+                .iter()
+                .zip(&predicted.variance)
+                .zip(&values[fold.test])
+                .map(|((mean, variance), actual)| {
+                    (actual - mean).abs() / variance.sqrt().max(MIN_SIGMA)
+                }),
+        );
+    }
+    calibration_from_scores(scores)
+}
+
+fn calibration_from_scores(mut scores: Vec<f64>) -> Result<Calibration, String> {
+    scores.retain(|score| score.is_finite());
+    if scores.is_empty() {
+        return Err("backtest produced no finite residuals".to_string());
+    }
+    scores.sort_by(f64::total_cmp);
+    let count = scores.len();
+    let covered = scores
+        .iter()
+        .filter(|score| **score <= CONFIDENCE_Z)
+        .count();
+    // Поправка на конечную выборку: ⌈(n + 1)·0.95⌉-я по порядку ошибка (19/20 = 0.95).
+    let rank = ((count + 1) * 19).div_ceil(20).min(count);
+    let factor = (scores[rank - 1] / CONFIDENCE_Z).max(MIN_SIGMA);
+    Ok(Calibration {
+        factor,
+        nominal_coverage: NOMINAL_COVERAGE,
+        empirical_coverage: super::usize_to_f64(covered, "conformal_calibration")
+            / super::usize_to_f64(count, "conformal_calibration"),
+        residuals: count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate};
+
+    use super::{Calibration, calibration_from_scores, conformal_calibration};
+    use crate::model::{Forecast, TimeSeriesSplit};
+    use crate::series::Granularity;
+
+    fn forecast(mean: f64, variance: f64, horizon: usize) -> Forecast {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date");
+        let std = variance.sqrt();
+        Forecast {
+            dates: (0..horizon)
+                .map(|day| start + Duration::days(i64::try_from(day).expect("small day")))
+                .collect(),
+            mean: vec![mean; horizon],
+            lower: vec![1.96f64.mul_add(-std, mean); horizon],
+            upper: vec![1.96f64.mul_add(std, mean); horizon],
+            variance: vec![variance; horizon],
+            granularity: Granularity::Day,
+            training: None,
+        }
+    }
+
+    #[test]
+    fn factor_is_the_finite_sample_quantile_over_z() {
+        // Нормированные ошибки 0.1, 0.2, …, 3.9: ⌈40·0.95⌉ = 38-я по порядку — 3.8.
+        let scores = (1..=39).map(|step| f64::from(step) / 10.0).collect();
+        let calibration = calibration_from_scores(scores).expect("calibration");
+        assert!((calibration.factor - 3.8 / 1.96).abs() < 1e-9);
+        assert_eq!(calibration.residuals, 39);
+        // В исходный интервал попадают ошибки до 1.96, то есть 19 из 39.
+        assert!((calibration.empirical_coverage - 19.0 / 39.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn overconfident_model_gets_a_wider_band() {
+        let dates: Vec<NaiveDate> = (0..60)
+            .map(|day| {
+                NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date") + Duration::days(day)
+            })
+            .collect();
+        // Ряд колеблется на ±10 км², а модель уверена в среднем с σ = 1 км².
+        let values: Vec<f64> = (0..60)
+            .map(|day| if day % 2 == 0 { 110.0 } else { 90.0 })
+            .collect();
+        let split = TimeSeriesSplit::expanding(4, 5).with_min_train(10);
+        let Calibration { factor, .. } =
+            conformal_calibration(&dates, &values, &split, |_, _, horizon| {
+                Ok(forecast(100.0, 1.0, horizon))
+            })
+            .expect("calibration");
+        assert!((factor - 10.0 / 1.96).abs() < 1e-9);
+
+        let mut calibrated = forecast(100.0, 1.0, 3);
+        calibrated.calibrate(factor);
+        assert!((calibrated.upper[0] - 110.0).abs() < 1e-9);
+        assert!((calibrated.lower[0] - 90.0).abs() < 1e-9);
+        assert!((calibrated.variance[0].sqrt() - factor).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,275 @@
+//! Диагностика LLT-модели по стандартизованным инновациям фильтра Калмана.
+//!
+//! Если модель верна, инновации `(y − ŷ) / √S` — независимый стандартный нормальный шум.
+//! Автокорреляции и тест Льюнга — Бокса показывают недоучтённую динамику, а квантили и
+//! эксцесс остатков — тяжёлые хвосты, при которых 95% интервал прогноза оптимистичен.
+
+use serde::Serialize;
+
+/// Сколько лагов автокорреляции считать и проверять тестом Льюнга — Бокса.
+pub const DIAGNOSTIC_LAGS: usize = 10;
+/// Первые шаги фильтра стартуют с диффузного состояния и в диагностику не входят.
+const BURN_IN: usize = 2;
+/// Уровни квантилей для сравнения с нормальным распределением и их нормальные значения.
+const QQ_LEVELS: [(f64, f64); 5] = [
+    (0.025, -1.959_964),
+    (0.25, -0.674_490),
+    (0.5, 0.0),
+    (0.75, 0.674_490),
+    (0.975, 1.959_964),
+];
+const NORMAL_95: f64 = 1.959_964;
+
+/// Сводка о подобранной модели и её остатках.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Diagnostics {
+    /// Сколько инноваций вошло в статистики.
+    pub observations: usize,
+    pub nll: f64,
+    /// Сигмы шумов уровня, тренда и наблюдений, км².
+    pub sigma_level: f64,
+    pub sigma_trend: f64,
+    pub sigma_obs: f64,
+    /// Автокорреляции инноваций на лагах `1..=DIAGNOSTIC_LAGS`.
+    pub acf: Vec<f64>,
+    pub ljung_box: LjungBox,
+    pub qq: QqSummary,
+}
+
+/// Тест Льюнга — Бокса на отсутствие автокорреляции до `lags` включительно.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct LjungBox {
+    pub lags: usize,
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+/// Остатки против стандартного нормального распределения.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct QqSummary {
+    /// (уровень, нормальный квантиль, выборочный квантиль).
+    pub quantiles: Vec<(f64, f64, f64)>,
+    pub skewness: f64,
+    pub excess_kurtosis: f64,
+    /// Доля инноваций за пределами ±1.96 (у нормального шума — 5%).
+    pub outside_95: f64,
+}
+
+/// Диагностика по стандартизованным инновациям; `None`, если их слишком мало для лагов.
+pub fn diagnose(innovations: &[f64], nll: f64, sigmas: (f64, f64, f64)) -> Option<Diagnostics> {
+    let z = innovations
+        .get(BURN_IN..)?
+        .iter()
+        .copied()
+        .filter(|value| value.is_finite())
+        .collect::<Vec<_>>();
+    if z.len() <= 2 * DIAGNOSTIC_LAGS {
+        return None;
+    }
+    let acf = autocorrelations(&z, DIAGNOSTIC_LAGS);
+    let n = count(z.len());
+    let statistic = n
+        * (n + 2.0)
+        * acf
+            .iter()
+            .enumerate()
+            .map(|(lag, rho)| rho * rho / (n - count(lag + 1)))
+            .sum::<f64>();
+    let ljung_box = LjungBox {
+        lags: DIAGNOSTIC_LAGS,
+        statistic,
+        p_value: chi_squared_survival(statistic, count(DIAGNOSTIC_LAGS)),
+    };
+    Some(Diagnostics {
+        observations: z.len(),
+        nll,
+        sigma_level: sigmas.0,
+        sigma_trend: sigmas.1,
+        sigma_obs: sigmas.2,
+        acf,
+        ljung_box,
+        qq: qq_summary(&z),
+    })
+}
+
+fn count(value: usize) -> f64 {
+    f64::from(u32::try_from(value).unwrap_or(u32::MAX))
+}
+
+fn autocorrelations(values: &[f64], lags: usize) -> Vec<f64> {
+    let mean = values.iter().sum::<f64>() / count(values.len());
+    let centered = values.iter().map(|value| value - mean).collect::<Vec<_>>();
+    let denom = centered.iter().map(|value| value * value).sum::<f64>();
+    (1..=lags)
+        .map(|lag| {
+            if denom <= 0.0 {
+                return 0.0;
+            }
+            centered
+                .iter()
+                .zip(&centered[lag..])
+                .map(|(a, b)| a * b)
+                .sum::<f64>()
+                / denom
+        })
+        .collect()
+}
+
+fn qq_summary(values: &[f64]) -> QqSummary {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = count(sorted.len());
+    let mean = sorted.iter().sum::<f64>() / n;
+    let moment = |power| {
+        sorted
+            .iter()
+            .map(|value| (value - mean).powi(power))
+            .sum::<f64>()
+            / n
+    };
+    let variance = moment(2);
+    let (skewness, excess_kurtosis) = if variance > 0.0 {
+        (
+            moment(3) / variance.powf(1.5),
+            moment(4) / (variance * variance) - 3.0,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+    let outside = sorted
+        .iter()
+        .filter(|value| value.abs() > NORMAL_95)
+        .count();
+    QqSummary {
+        quantiles: QQ_LEVELS
+            .iter()
+            .map(|&(level, normal)| (level, normal, quantile(&sorted, level)))
+            .collect(),
+        skewness,
+        excess_kurtosis,
+        outside_95: count(outside) / n,
+    }
+}
+
+/// Квантиль отсортированной выборки с линейной интерполяцией между порядковыми статистиками.
+fn quantile(sorted: &[f64], level: f64) -> f64 {
+    let position = level * count(sorted.len() - 1);
+    let lower = position.floor();
+    // `position` лежит в [0, n − 1], поэтому приведение без потерь.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let index = lower as usize;
+    let next = sorted.get(index + 1).copied().unwrap_or(sorted[index]);
+    (position - lower).mul_add(next - sorted[index], sorted[index])
+}
+
+/// `P(χ²_k > x)` — регуляризованная верхняя неполная гамма-функция `Q(k/2, x/2)`.
+fn chi_squared_survival(statistic: f64, dof: f64) -> f64 {
+    if statistic <= 0.0 {
+        return 1.0;
+    }
+    let (shape, half) = (dof / 2.0, statistic / 2.0);
+    let prefix = shape.mul_add(half.ln(), -half) - ln_gamma(shape);
+    if half < shape + 1.0 {
+        // Ряд для нижней функции P(a, x).
+        let (mut term, mut sum, mut denom) = (1.0 / shape, 1.0 / shape, shape);
+        for _ in 0..500 {
+            denom += 1.0;
+            term *= half / denom;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        sum.mul_add(-prefix.exp(), 1.0).clamp(0.0, 1.0)
+    } else {
+        // Цепная дробь для Q(a, x) по модифицированному методу Лентца.
+        let tiny = 1e-300;
+        let mut offset = half + 1.0 - shape;
+        let mut numerator_ratio = 1.0 / tiny;
+        let mut denominator_ratio = 1.0 / offset;
+        let mut fraction = denominator_ratio;
+        for step in 1..500 {
+            let step = f64::from(step);
+            let coefficient = -step * (step - shape);
+            offset += 2.0;
+            denominator_ratio = coefficient.mul_add(denominator_ratio, offset);
+            if denominator_ratio.abs() < tiny {
+                denominator_ratio = tiny;
+            }
+            numerator_ratio = offset + coefficient / numerator_ratio;
+            if numerator_ratio.abs() < tiny {
+                numerator_ratio = tiny;
+            }
+            denominator_ratio = 1.0 / denominator_ratio;
+            let delta = denominator_ratio * numerator_ratio;
+            fraction *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        (prefix.exp() * fraction).clamp(0.0, 1.0)
+    }
+}
+
+/// `ln Γ(x)` по Ланцошу (g = 7, 9 коэффициентов), точность ~1e-15 при x > 0.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    let x = x - 1.0;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (idx, coefficient)| {
+            sum + coefficient / (x + count(idx + 1))
+        });
+    let t = x + 7.5;
+    0.5f64.mul_add((2.0 * std::f64::consts::PI).ln(), (x + 0.5) * t.ln()) - t + sum.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chi_squared_survival, diagnose};
+
+    #[test]
+    fn white_noise_passes_and_autocorrelated_noise_fails_ljung_box() {
+        // χ²₁₀: 95-й процентиль ≈ 18.307.
+        assert!((chi_squared_survival(18.307, 10.0) - 0.05).abs() < 1e-4);
+        assert!((chi_squared_survival(2.0, 2.0) - (-1.0f64).exp()).abs() < 1e-12);
+
+        // Детерминированный «белый» шум: линейный конгруэнтный генератор.
+        let mut seed = 12_345u64;
+        let noise = (0..400)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                let unit =
+                    f64::from(u32::try_from(seed >> 40).expect("24 bits")) / f64::from(1u32 << 24);
+                unit.mul_add(3.4, -1.7)
+            })
+            .collect::<Vec<_>>();
+        let white = diagnose(&noise, 1.0, (1.0, 1.0, 1.0)).expect("enough data");
+        assert_eq!(white.observations, 398);
+        assert!(white.ljung_box.p_value > 0.01, "{:?}", white.ljung_box);
+        assert!(white.acf.iter().all(|rho| rho.abs() < 0.15));
+        assert!(white.qq.quantiles[2].2.abs() < 0.3);
+
+        let smoothed = noise
+            .windows(3)
+            .map(|window| window.iter().sum::<f64>())
+            .collect::<Vec<_>>();
+        let correlated = diagnose(&smoothed, 1.0, (1.0, 1.0, 1.0)).expect("enough data");
+        assert!(correlated.acf[0] > 0.5);
+        assert!(correlated.ljung_box.p_value < 1e-6);
+        assert!(diagnose(&noise[..20], 1.0, (1.0, 1.0, 1.0)).is_none());
+    }
+}
@@ -0,0 +1,174 @@
+//! Разделы приложения «Методология» о моделях: уравнения, константы подбора и параметры
+//! текущего конфига.
+
+use super::{
+    CONFIDENCE_Z, CalibrationConfig, DEFAULT_TOL_COST, DEFAULT_TOL_GRAD, HETERO_MAX_SCALE,
+    HETERO_MIN_SCALE, HETERO_WINDOW, MIN_SIGMA, ModelConfig, TrendFilterConfig, calibration,
+    optimizer,
+};
+use crate::methodology::{Parameter, Section};
+
+/// LLT-модель: прогноз при `model = "llt"` и сглаживание истории при любой модели.
+pub fn methodology_llt(config: &ModelConfig) -> Section {
+    let mut parameters = vec![
+        Parameter::new(
+            "equations",
+            "y = μ + ε; μ' = μ + ν + η; ν' = ν + ζ",
+            "Наблюдение y — уровень μ с шумом ε; уровень растёт на темп ν, оба блуждают шумами η и ζ",
+        ),
+        Parameter::new(
+            "training_window",
+            config.training_window,
+            "Часть истории, на которой подбираются дисперсии шумов",
+        ),
+        Parameter::new(
+            "interpolation",
+            config.interpolation.as_str(),
+            "Заполнение дней без срезов в обучающем ряду",
+        ),
+        Parameter::new(
+            "scale",
+            config.scale,
+            "Ряд делится на масштаб (км²), чтобы сигмы были порядка единицы",
+        ),
+        Parameter::new(
+            "sigma_floor",
+            MIN_SIGMA,
+            "Нижняя граница сигм (в единицах масштаба)",
+        ),
+        Parameter::new(
+            "volatility_window",
+            format!("{HETERO_WINDOW} дн., множитель {HETERO_MIN_SCALE}–{HETERO_MAX_SCALE}"),
+            "Шумы дня масштабируются по скользящему разбросу суточных изменений",
+        ),
+        Parameter::new(
+            "sigma_prior",
+            format!(
+                "level {}, trend {}, obs {}",
+                config.sigma_prior.level, config.sigma_prior.trend, config.sigma_prior.obs
+            ),
+            "Веса логнормальных штрафов на сигмы вокруг эвристической оценки; 0 — без штрафа",
+        ),
+        Parameter::new(
+            "seasonality",
+            config.seasonality.map_or_else(
+                || "нет".to_string(),
+                |seasonality| {
+                    format!(
+                        "{}, penalty {}",
+                        seasonality.period.key(),
+                        seasonality.penalty
+                    )
+                },
+            ),
+            "Сезонные надбавки к темпу с гребневым штрафом",
+        ),
+        Parameter::new(
+            "optimizer",
+            format!("L-BFGS, память {}", config.history),
+            "Минимизация NLL фильтра Калмана по логарифмам сигм",
+        ),
+        Parameter::new(
+            "max_iters",
+            config.max_iters,
+            "Предел итераций на один старт",
+        ),
+        Parameter::new(
+            "tolerance",
+            format!("градиент {DEFAULT_TOL_GRAD:e}, стоимость {DEFAULT_TOL_COST:e}"),
+            "Критерии сходимости L-BFGS",
+        ),
+        Parameter::new(
+            "restarts",
+            config.restarts,
+            "Стартов подбора; берётся наименьшая NLL",
+        ),
+    ];
+    parameters.extend(optimizer::methodology());
+    Section {
+        title: "Модель LLT",
+        summary: "Локальный линейный тренд: уровень и темп оцениваются фильтром Калмана, \
+                  дисперсии шумов подбираются по правдоподобию.",
+        parameters,
+    }
+}
+
+/// Trend filter: прогноз при `model = "trend-filter"`.
+pub fn methodology_trend_filter(config: &TrendFilterConfig) -> Section {
+    Section {
+        title: "Модель trend filter",
+        summary: "Сглаженная траектория минимизирует штраф Хьюбера за отклонения от ряда плюс \
+                  λ · Σ √(Δ²x² + ε²); прогноз продолжает последний наклон с затуханием.",
+        parameters: vec![
+            Parameter::new(
+                "training_window",
+                config.training_window,
+                "Часть истории, на которой строится траектория",
+            ),
+            Parameter::new(
+                "interpolation",
+                config.interpolation.as_str(),
+                "Заполнение дней без срезов в обучающем ряду",
+            ),
+            Parameter::new("lambda", config.lambda, "Вес штрафа на изломы траектории"),
+            Parameter::new(
+                "epsilon",
+                config.epsilon,
+                "Сглаживание модуля второй разности",
+            ),
+            Parameter::new(
+                "huber_delta",
+                config.huber_delta,
+                "Порог штрафа Хьюбера; 0 — 1.5 σ суточных изменений",
+            ),
+            Parameter::new(
+                "damping",
+                config.damping,
+                "Затухание наклона на каждом шаге прогноза; 1 — без затухания",
+            ),
+            Parameter::new("scale", config.scale, "Масштаб ряда, км²"),
+            Parameter::new(
+                "optimizer",
+                format!("L-BFGS, память {}", config.history),
+                "Минимизация стоимости траектории",
+            ),
+            Parameter::new("max_iters", config.max_iters, "Предел итераций"),
+        ],
+    }
+}
+
+/// Интервалы прогноза и их калибровка.
+pub fn methodology_intervals(calibration: Option<CalibrationConfig>) -> Section {
+    let mut parameters = vec![Parameter::new(
+        "z",
+        CONFIDENCE_Z,
+        "Множитель стандартного отклонения для 95% интервала",
+    )];
+    parameters.push(calibration.map_or_else(
+        || {
+            Parameter::new(
+                "calibration",
+                "нет",
+                "Интервал модели используется как есть",
+            )
+        },
+        |config| {
+            Parameter::new(
+                "calibration",
+                format!(
+                    "{} отсечений, зазор {} дн., цель {}%",
+                    config.folds,
+                    config.gap_days,
+                    calibration::NOMINAL_COVERAGE * 100.0
+                ),
+                "Конформная калибровка: ширина масштабируется по остаткам скользящего бэктеста",
+            )
+        },
+    ));
+    Section {
+        title: "Интервалы",
+        summary: "Интервал прогноза — гауссовский по дисперсии модели, при калибровке — \
+                  подогнанный под фактическое покрытие на истории.",
+        parameters,
+    }
+}
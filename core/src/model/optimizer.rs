@@ -0,0 +1,636 @@
+//! Подбор параметров через `argmin` под наблюдением: стоимость по итерациям пишется в
+//! отладочный лог, подбор останавливается, когда стоимость [`PATIENCE`] итераций подряд не
+//! улучшается, а расходимость (NaN, бесконечность, рост далеко выше начальной стоимости)
+//! прерывает его ошибкой [`OptimizerError`] вместо молчаливого прогона всех итераций.
+//!
+//! Наблюдатели `argmin` видят состояние только для чтения и остановить подбор без ошибки не
+//! могут, поэтому раннюю остановку делает обёртка решателя [`EarlyStopping`].
+//!
+//! [`minimize_restarts`] запускает тот же подбор из нескольких начальных точек параллельно и
+//! берёт результат с наименьшей стоимостью: функция правдоподобия LLT-модели не выпукла, и
+//! единственный старт из эвристики может застрять в локальном минимуме. Точки выводятся из
+//! фиксированного зерна, поэтому прогноз воспроизводим от запуска к запуску.
+//!
+//! Без фичи `forecast` argmin не собирается, а [`lbfgs`] и [`lbfgs_restarts`] возвращают ошибку.
+
+use std::error::Error;
+use std::fmt;
+
+#[cfg(feature = "forecast")]
+use argmin::core::observers::{Observe, ObserverMode};
+#[cfg(feature = "forecast")]
+use argmin::core::{
+    CostFunction, Error as ArgminError, Executor, Gradient, IterState, KV, Problem, Solver, State,
+    TerminationReason, TerminationStatus,
+};
+#[cfg(feature = "forecast")]
+use argmin::solver::linesearch::MoreThuenteLineSearch;
+#[cfg(feature = "forecast")]
+use argmin::solver::quasinewton::LBFGS;
+#[cfg(feature = "forecast")]
+use tracing::debug;
+
+use crate::methodology::Parameter;
+
+/// Сколько итераций без улучшения стоимости допускается до остановки.
+pub const PATIENCE: u64 = 20;
+/// Улучшение меньше этой доли от лучшей стоимости не считается улучшением.
+const MIN_RELATIVE_IMPROVEMENT: f64 = 1e-9;
+/// Во сколько раз стоимость может уйти выше начальной, прежде чем подбор считается расходящимся.
+const DIVERGENCE_FACTOR: f64 = 1e6;
+/// Как часто писать итерации в отладочный лог.
+const LOG_EVERY: u64 = 25;
+/// Зерно возмущений начальных точек. Зашито в код: это единственный псевдослучайный шаг rua,
+/// и политика воспроизводимости (Readme, «Сборка воспроизводима») держится на нём вместе с
+/// `SOURCE_DATE_EPOCH`.
+const RESTART_SEED: u64 = 0x5EED_2022_0224;
+/// Половина ширины равномерного возмущения начальной точки по каждой координате.
+const RESTART_SPREAD: f64 = 1.5;
+
+#[cfg(feature = "forecast")]
+type ParamState = IterState<Vec<f64>, Vec<f64>, (), (), (), f64>;
+#[cfg(feature = "forecast")]
+type LbfgsSolver = LBFGS<MoreThuenteLineSearch<Vec<f64>, Vec<f64>, f64>, Vec<f64>, Vec<f64>, f64>;
+type Fit = (Vec<f64>, OptimizationSummary);
+/// Ошибка подбора, которую можно вернуть из потока.
+#[cfg(feature = "forecast")]
+type SendError = Box<dyn Error + Send + Sync>;
+#[cfg(not(feature = "forecast"))]
+const FORECAST_DISABLED: &str = "rua was built without the `forecast` feature";
+
+/// Настройки L-BFGS с линейным поиском Море — Туенте.
+#[derive(Clone, Copy, Debug)]
+pub struct Lbfgs {
+    /// Сколько последних шагов хранит приближение гессиана.
+    pub history: usize,
+    pub tol_grad: f64,
+    pub tol_cost: f64,
+}
+
+#[cfg(feature = "forecast")]
+impl Lbfgs {
+    fn solver(self) -> Result<LbfgsSolver, ArgminError> {
+        let linesearch = MoreThuenteLineSearch::new().with_c(1e-4, 0.9)?;
+        LBFGS::new(linesearch, self.history)
+            .with_tolerance_grad(self.tol_grad)?
+            .with_tolerance_cost(self.tol_cost)
+    }
+}
+
+/// Подбор параметров сорвался.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptimizerError {
+    /// Стоимость стала NaN или бесконечной.
+    NonFinite { model: &'static str, iter: u64 },
+    /// Стоимость ушла на порядки выше начальной.
+    Diverged {
+        model: &'static str,
+        iter: u64,
+        cost: f64,
+        initial: f64,
+    },
+}
+
+impl fmt::Display for OptimizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonFinite { model, iter } => {
+                write!(
+                    f,
+                    "{model} optimizer produced a non-finite cost at iteration {iter}"
+                )
+            }
+            Self::Diverged {
+                model,
+                iter,
+                cost,
+                initial,
+            } => write!(
+                f,
+                "{model} optimizer diverged at iteration {iter}: cost {cost:.4e}, initial {initial:.4e}"
+            ),
+        }
+    }
+}
+
+impl Error for OptimizerError {}
+
+/// Итог подбора параметров: сколько итераций понадобилось и чем закончилось.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptimizationSummary {
+    pub iterations: u64,
+    pub max_iters: u64,
+    pub best_cost: f64,
+    /// Причина остановки (сходимость, застой, предел итераций); `None`, если не записана.
+    pub termination: Option<String>,
+}
+
+impl OptimizationSummary {
+    #[cfg(feature = "forecast")]
+    fn from_state<I: State<Float = f64>>(state: &I) -> Self {
+        Self {
+            iterations: state.get_iter(),
+            max_iters: state.get_max_iters(),
+            best_cost: state.get_best_cost(),
+            termination: match state.get_termination_status() {
+                TerminationStatus::Terminated(reason) => Some(reason.to_string()),
+                TerminationStatus::NotTerminated => None,
+            },
+        }
+    }
+
+    /// Строка для лога: итерации из предела, лучшая стоимость и причина остановки.
+    pub fn describe(&self) -> String {
+        let reason = self
+            .termination
+            .as_ref()
+            .map_or_else(String::new, |reason| format!(" ({reason})"));
+        format!(
+            "{} of {} iterations, cost {:.4}{reason}",
+            self.iterations, self.max_iters, self.best_cost
+        )
+    }
+}
+
+/// [`minimize`] решателем L-BFGS с настройками `settings`.
+#[cfg(feature = "forecast")]
+pub fn lbfgs<O>(
+    problem: O,
+    settings: Lbfgs,
+    init: Vec<f64>,
+    max_iters: u64,
+    model: &'static str,
+) -> Result<Fit, Box<dyn Error>>
+where
+    O: CostFunction<Param = Vec<f64>, Output = f64>
+        + Gradient<Param = Vec<f64>, Gradient = Vec<f64>>,
+{
+    minimize(problem, settings.solver()?, init, max_iters, model)
+}
+
+/// Заглушка для сборки без фичи `forecast`.
+#[cfg(not(feature = "forecast"))]
+pub fn lbfgs<O>(
+    _problem: O,
+    _settings: Lbfgs,
+    _init: Vec<f64>,
+    _max_iters: u64,
+    _model: &'static str,
+) -> Result<Fit, Box<dyn Error>> {
+    Err(FORECAST_DISABLED.into())
+}
+
+/// [`minimize_restarts`] решателем L-BFGS с настройками `settings`.
+#[cfg(feature = "forecast")]
+pub fn lbfgs_restarts<O>(
+    problem: &O,
+    settings: Lbfgs,
+    starts: Vec<Vec<f64>>,
+    max_iters: u64,
+    model: &'static str,
+) -> Result<Fit, Box<dyn Error>>
+where
+    O: CostFunction<Param = Vec<f64>, Output = f64>
+        + Gradient<Param = Vec<f64>, Gradient = Vec<f64>>
+        + Clone
+        + Send
+        + Sync,
+{
+    minimize_restarts(problem, &settings.solver()?, starts, max_iters, model)
+}
+
+/// Заглушка для сборки без фичи `forecast`.
+#[cfg(not(feature = "forecast"))]
+pub fn lbfgs_restarts<O>(
+    _problem: &O,
+    _settings: Lbfgs,
+    _starts: Vec<Vec<f64>>,
+    _max_iters: u64,
+    _model: &'static str,
+) -> Result<Fit, Box<dyn Error>> {
+    Err(FORECAST_DISABLED.into())
+}
+
+/// Минимизирует `problem` решателем `solver` из `init` не дольше `max_iters` итераций.
+/// Возвращает лучшие параметры и итог подбора; расходимость — [`OptimizerError`].
+#[cfg(feature = "forecast")]
+pub fn minimize<O, S>(
+    problem: O,
+    solver: S,
+    init: Vec<f64>,
+    max_iters: u64,
+    model: &'static str,
+) -> Result<Fit, Box<dyn Error>>
+where
+    S: Solver<O, ParamState>,
+{
+    run(problem, solver, init, max_iters, model).map_err(|err| err as Box<dyn Error>)
+}
+
+/// [`minimize`] из каждой точки `starts` в своём потоке; побеждает наименьшая стоимость, при
+/// равенстве — более ранняя точка. Ошибка возвращается, только если сорвались все старты.
+#[cfg(feature = "forecast")]
+pub fn minimize_restarts<O, S>(
+    problem: &O,
+    solver: &S,
+    starts: Vec<Vec<f64>>,
+    max_iters: u64,
+    model: &'static str,
+) -> Result<(Vec<f64>, OptimizationSummary), Box<dyn Error>>
+where
+    O: Clone + Send + Sync,
+    S: Solver<O, ParamState> + Clone + Send + Sync,
+{
+    let results = std::thread::scope(|scope| {
+        // Все потоки запускаются до первого `join`, иначе старты шли бы по очереди.
+        #[allow(clippy::needless_collect)]
+        let handles = starts
+            .into_iter()
+            .map(|init| {
+                scope.spawn(move || run(problem.clone(), solver.clone(), init, max_iters, model))
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err("optimizer thread panicked".into()))
+            })
+            .collect::<Vec<_>>()
+    });
+    let starts = results.len();
+    let mut first_error = None;
+    let mut best: Option<(usize, Fit)> = None;
+    for (start, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(fit) => {
+                if best
+                    .as_ref()
+                    .is_none_or(|(_, (_, summary))| fit.1.best_cost < summary.best_cost)
+                {
+                    best = Some((start, fit));
+                }
+            }
+            Err(err) => {
+                debug!(model, start, error = %err, "Optimizer restart failed");
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    match best {
+        Some((start, fit)) => {
+            debug!(
+                model,
+                start,
+                starts,
+                cost = fit.1.best_cost,
+                "Selected optimizer restart"
+            );
+            Ok(fit)
+        }
+        None => Err(first_error.map_or_else(
+            || "no optimizer starts were given".into(),
+            |err| err as Box<dyn Error>,
+        )),
+    }
+}
+
+/// `count` начальных точек: сама `init` и её детерминированные возмущения первых `perturbed`
+/// координат (остальные, например коэффициенты регрессоров, не трогаются).
+pub fn perturbed_starts(init: &[f64], perturbed: usize, count: usize) -> Vec<Vec<f64>> {
+    let mut state = RESTART_SEED;
+    let mut starts = vec![init.to_vec()];
+    for _ in 1..count {
+        let mut start = init.to_vec();
+        for value in start.iter_mut().take(perturbed) {
+            *value += RESTART_SPREAD * 2.0f64.mul_add(unit(&mut state), -1.0);
+        }
+        starts.push(start);
+    }
+    starts
+}
+
+/// Равномерное число из `[0, 1)` генератора `SplitMix64`.
+fn unit(state: &mut u64) -> f64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    // Старшие 53 бита точно представимы в f64.
+    #[allow(clippy::cast_precision_loss)]
+    let mantissa = (z >> 11) as f64;
+    mantissa / 9_007_199_254_740_992.0
+}
+
+#[cfg(feature = "forecast")]
+fn run<O, S>(
+    problem: O,
+    solver: S,
+    init: Vec<f64>,
+    max_iters: u64,
+    model: &'static str,
+) -> Result<Fit, SendError>
+where
+    S: Solver<O, ParamState>,
+{
+    let result = Executor::new(problem, EarlyStopping::new(solver, PATIENCE))
+        .configure(|state| state.param(init).max_iters(max_iters))
+        .add_observer(OptimizerLog::new(model), ObserverMode::Always)
+        .run()
+        .map_err(|err| match err.downcast::<OptimizerError>() {
+            Ok(err) => Box::new(err) as SendError,
+            Err(err) => err.into(),
+        })?;
+    let best = result
+        .state
+        .get_best_param()
+        .or_else(|| result.state.get_param())
+        .ok_or("no parameters returned from optimizer")?
+        .clone();
+    Ok((best, OptimizationSummary::from_state(&result.state)))
+}
+
+/// Обёртка решателя: останавливает подбор, когда стоимость перестала улучшаться.
+#[cfg(feature = "forecast")]
+struct EarlyStopping<S> {
+    inner: S,
+    stall: Stall,
+}
+
+#[cfg(feature = "forecast")]
+impl<S> EarlyStopping<S> {
+    const fn new(inner: S, patience: u64) -> Self {
+        Self {
+            inner,
+            stall: Stall::new(patience),
+        }
+    }
+}
+
+#[cfg(feature = "forecast")]
+impl<O, S, I> Solver<O, I> for EarlyStopping<S>
+where
+    S: Solver<O, I>,
+    I: State<Float = f64>,
+{
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn init(&mut self, problem: &mut Problem<O>, state: I) -> Result<(I, Option<KV>), ArgminError> {
+        self.inner.init(problem, state)
+    }
+
+    fn next_iter(
+        &mut self,
+        problem: &mut Problem<O>,
+        state: I,
+    ) -> Result<(I, Option<KV>), ArgminError> {
+        self.inner.next_iter(problem, state)
+    }
+
+    fn terminate(&mut self, state: &I) -> TerminationStatus {
+        let status = self.inner.terminate(state);
+        if status.terminated() || !self.stall.stalled(state.get_best_cost()) {
+            return status;
+        }
+        TerminationStatus::Terminated(TerminationReason::SolverExit(format!(
+            "no improvement in {} iterations",
+            self.stall.patience
+        )))
+    }
+}
+
+/// Счётчик итераций без улучшения лучшей стоимости.
+struct Stall {
+    patience: u64,
+    best: f64,
+    since: u64,
+}
+
+impl Stall {
+    const fn new(patience: u64) -> Self {
+        Self {
+            patience,
+            best: f64::INFINITY,
+            since: 0,
+        }
+    }
+
+    /// Учитывает лучшую стоимость очередной итерации; `true` — пора останавливаться.
+    fn stalled(&mut self, best: f64) -> bool {
+        let threshold = MIN_RELATIVE_IMPROVEMENT * self.best.abs().max(1.0);
+        if best < self.best - threshold || self.best.is_infinite() {
+            self.best = best;
+            self.since = 0;
+        } else {
+            self.since += 1;
+        }
+        self.since >= self.patience
+    }
+}
+
+/// Наблюдатель: стоимость в отладочный лог и проверка на расходимость на каждой итерации.
+struct OptimizerLog {
+    model: &'static str,
+    initial: Option<f64>,
+}
+
+impl OptimizerLog {
+    const fn new(model: &'static str) -> Self {
+        Self {
+            model,
+            initial: None,
+        }
+    }
+}
+
+#[cfg(feature = "forecast")]
+impl<I: State<Float = f64>> Observe<I> for OptimizerLog {
+    fn observe_init(&mut self, _name: &str, state: &I, _kv: &KV) -> Result<(), ArgminError> {
+        let cost = state.get_cost();
+        self.initial = cost.is_finite().then_some(cost);
+        Ok(())
+    }
+
+    fn observe_iter(&mut self, state: &I, _kv: &KV) -> Result<(), ArgminError> {
+        let iter = state.get_iter();
+        let cost = state.get_cost();
+        check_cost(self.model, iter, cost, self.initial)?;
+        if iter.is_multiple_of(LOG_EVERY) {
+            debug!(
+                model = self.model,
+                iter,
+                max_iters = state.get_max_iters(),
+                cost,
+                best_cost = state.get_best_cost(),
+                "Optimizer iteration"
+            );
+        }
+        Ok(())
+    }
+
+    fn observe_final(&mut self, state: &I) -> Result<(), ArgminError> {
+        debug!(
+            model = self.model,
+            summary = OptimizationSummary::from_state(state).describe(),
+            "Optimizer finished"
+        );
+        Ok(())
+    }
+}
+
+fn check_cost(
+    model: &'static str,
+    iter: u64,
+    cost: f64,
+    initial: Option<f64>,
+) -> Result<(), OptimizerError> {
+    if !cost.is_finite() {
+        return Err(OptimizerError::NonFinite { model, iter });
+    }
+    match initial {
+        Some(initial) if cost > DIVERGENCE_FACTOR.mul_add(initial.abs().max(1.0), initial) => {
+            Err(OptimizerError::Diverged {
+                model,
+                iter,
+                cost,
+                initial,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Константы ранней остановки и перезапусков для приложения «Методология».
+pub fn methodology() -> Vec<Parameter> {
+    vec![
+        Parameter::new(
+            "patience",
+            PATIENCE,
+            "Итераций без улучшения стоимости до ранней остановки",
+        ),
+        Parameter::new(
+            "min_relative_improvement",
+            MIN_RELATIVE_IMPROVEMENT,
+            "Меньшее относительное улучшение не считается улучшением",
+        ),
+        Parameter::new(
+            "divergence_factor",
+            DIVERGENCE_FACTOR,
+            "Во сколько раз стоимость может превысить начальную, прежде чем подбор прерывается",
+        ),
+        Parameter::new(
+            "restart_spread",
+            format!("±{RESTART_SPREAD}, зерно {RESTART_SEED:#x}"),
+            "Равномерное возмущение логарифмов сигм для дополнительных стартов",
+        ),
+    ]
+}
+
+#[cfg(all(test, feature = "forecast"))]
+mod tests {
+    use argmin::core::{CostFunction, Error as ArgminError, Gradient};
+    use argmin::solver::linesearch::MoreThuenteLineSearch;
+    use argmin::solver::quasinewton::LBFGS;
+
+    use super::{OptimizerError, Stall, check_cost, minimize, minimize_restarts, perturbed_starts};
+
+    /// `(x - 3)²`.
+    struct Parabola;
+
+    impl CostFunction for Parabola {
+        type Param = Vec<f64>;
+        type Output = f64;
+
+        fn cost(&self, param: &Self::Param) -> Result<Self::Output, ArgminError> {
+            Ok((param[0] - 3.0).powi(2))
+        }
+    }
+
+    impl Gradient for Parabola {
+        type Param = Vec<f64>;
+        type Gradient = Vec<f64>;
+
+        fn gradient(&self, param: &Self::Param) -> Result<Self::Gradient, ArgminError> {
+            Ok(vec![2.0 * (param[0] - 3.0)])
+        }
+    }
+
+    /// `(x² - 1)² + 0.3·x`: глобальный минимум около −1, локальный около +1.
+    #[derive(Clone)]
+    struct DoubleWell;
+
+    impl CostFunction for DoubleWell {
+        type Param = Vec<f64>;
+        type Output = f64;
+
+        fn cost(&self, param: &Self::Param) -> Result<Self::Output, ArgminError> {
+            let x = param[0];
+            Ok(0.3f64.mul_add(x, x.mul_add(x, -1.0).powi(2)))
+        }
+    }
+
+    impl Gradient for DoubleWell {
+        type Param = Vec<f64>;
+        type Gradient = Vec<f64>;
+
+        fn gradient(&self, param: &Self::Param) -> Result<Self::Gradient, ArgminError> {
+            let x = param[0];
+            Ok(vec![(4.0 * x).mul_add(x.mul_add(x, -1.0), 0.3)])
+        }
+    }
+
+    #[test]
+    fn optimizer_converges_stops_on_stalls_and_rejects_divergence() {
+        let solver = LBFGS::new(MoreThuenteLineSearch::new(), 5);
+        let (best, summary) =
+            minimize(Parabola, solver, vec![0.0], 400, "test").expect("quadratic converges");
+        assert!((best[0] - 3.0).abs() < 1e-6);
+        assert!(summary.iterations < 400);
+
+        let mut stall = Stall::new(3);
+        let stalled = [10.0, 5.0, 5.0, 5.0, 5.0]
+            .into_iter()
+            .map(|cost| stall.stalled(cost))
+            .collect::<Vec<_>>();
+        assert_eq!(stalled, [false, false, false, false, true]);
+
+        assert_eq!(
+            check_cost("test", 4, f64::NAN, Some(1.0)),
+            Err(OptimizerError::NonFinite {
+                model: "test",
+                iter: 4
+            })
+        );
+        assert!(matches!(
+            check_cost("test", 7, 1e9, Some(1.0)),
+            Err(OptimizerError::Diverged { iter: 7, .. })
+        ));
+        assert_eq!(check_cost("test", 7, 0.5, Some(1.0)), Ok(()));
+    }
+
+    #[test]
+    fn restarts_escape_local_minima_deterministically() {
+        let starts = perturbed_starts(&[0.8, 5.0], 1, 8);
+        assert_eq!(starts, perturbed_starts(&[0.8, 5.0], 1, 8));
+        assert_eq!(starts[0], [0.8, 5.0]);
+        assert!(
+            starts
+                .iter()
+                .all(|start| start[1].to_bits() == 5.0f64.to_bits())
+        );
+
+        let solver = LBFGS::new(MoreThuenteLineSearch::new(), 5);
+        let (single, _) =
+            minimize(DoubleWell, solver.clone(), vec![0.8], 400, "test").expect("single start");
+        let starts = perturbed_starts(&[0.8], 1, 8);
+        let (restarted, summary) =
+            minimize_restarts(&DoubleWell, &solver, starts, 400, "test").expect("restarts");
+        assert!(single[0] > 0.0);
+        assert!(restarted[0] < 0.0);
+        assert!(summary.best_cost < 0.0);
+    }
+}
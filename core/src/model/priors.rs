@@ -0,0 +1,96 @@
+//! Мягкие априорные штрафы на сигмы LLT-модели.
+//!
+//! На коротком или ровном окне обучения правдоподобие почти не зависит от `sigma_trend`, и
+//! оптимизатор сводит её к полу `MIN_SIGMA`: тренд застывает, а интервалы прогноза схлопываются.
+//! Штраф `½·w·(ln σ − ln σ₀)²` — логнормальное априорное распределение вокруг эвристической
+//! оценки `σ₀` из разброса суточных изменений — удерживает сигму рядом с правдоподобным
+//! масштабом, пока данных мало, и почти не влияет на длинных рядах. Вес `w = 1/τ²`, где `τ` —
+//! допустимое отклонение логарифма сигмы; `0` выключает штраф.
+
+use serde::Serialize;
+
+/// Вес штрафа на `sigma_trend` по умолчанию: `τ ≈ 1.4`, сигма свободно меняется в разы.
+pub const DEFAULT_TREND_PRIOR_WEIGHT: f64 = 0.5;
+
+/// Веса штрафов на логарифмы сигм уровня, тренда и наблюдений.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct SigmaPrior {
+    pub level: f64,
+    pub trend: f64,
+    pub obs: f64,
+}
+
+impl Default for SigmaPrior {
+    fn default() -> Self {
+        Self {
+            level: 0.0,
+            trend: DEFAULT_TREND_PRIOR_WEIGHT,
+            obs: 0.0,
+        }
+    }
+}
+
+impl SigmaPrior {
+    /// Штраф за отклонение `log_sigmas` (уровень, тренд, наблюдения) от центров `center`.
+    pub fn penalty(&self, log_sigmas: &[f64], center: &[f64; 3]) -> f64 {
+        [self.level, self.trend, self.obs]
+            .iter()
+            .zip(center)
+            .zip(log_sigmas)
+            .map(|((weight, center), value)| 0.5 * weight * (value - center).powi(2))
+            .sum()
+    }
+}
+
+#[cfg(all(test, feature = "forecast"))]
+mod tests {
+    use chrono::{Days, NaiveDate};
+
+    use super::SigmaPrior;
+    use crate::model::{ModelConfig, train_from_series_with_regressors};
+
+    #[test]
+    fn trend_prior_keeps_sigma_trend_off_the_floor() {
+        let prior = SigmaPrior {
+            level: 0.0,
+            trend: 2.0,
+            obs: 1.0,
+        };
+        assert!((prior.penalty(&[5.0, 1.0, -1.0], &[0.0, 0.0, 0.0]) - 1.5).abs() < 1e-12);
+
+        // Короткий ровный ряд: темп постоянен, шум мал.
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).expect("valid date");
+        let dates = (0..30)
+            .map(|day| start.checked_add_days(Days::new(day)).expect("valid date"))
+            .collect::<Vec<_>>();
+        let values = (0..30)
+            .map(|day| {
+                10.0f64.mul_add(f64::from(day), 100_000.0) + if day % 2 == 0 { 3.0 } else { -3.0 }
+            })
+            .collect::<Vec<_>>();
+        let fit = |sigma_prior| {
+            train_from_series_with_regressors(
+                &dates,
+                &values,
+                ModelConfig {
+                    sigma_prior,
+                    ..ModelConfig::default()
+                },
+                None,
+            )
+            .expect("model should train")
+        };
+        let free = fit(SigmaPrior {
+            level: 0.0,
+            trend: 0.0,
+            obs: 0.0,
+        });
+        let anchored = fit(SigmaPrior::default());
+        assert!(
+            anchored.sigma_trend > 10.0 * free.sigma_trend,
+            "free {} anchored {}",
+            free.sigma_trend,
+            anchored.sigma_trend
+        );
+    }
+}
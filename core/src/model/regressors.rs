@@ -0,0 +1,283 @@
+//! Экзогенные дневные ряды (регрессоры) для уравнения наблюдения LLT-модели:
+//! `y_t = level_t + Σ β_j · x_j,t + ε_t`.
+//!
+//! CSV: первая колонка `date` (`YYYY-MM-DD`), остальные — показатели. Пустая ячейка означает
+//! пропуск: берётся последнее известное значение, так же продлеваются ряды за концом файла.
+
+use std::error::Error;
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+use crate::constants::DATE_FORMAT;
+
+const DATE_COLUMN: &str = "date";
+/// Регрессор с меньшим разбросом на обучающем окне не отличить от константы уровня.
+const MIN_REGRESSOR_STD: f64 = 1e-9;
+
+/// Набор регрессоров, загруженный из CSV.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Regressors {
+    columns: Vec<Regressor>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Regressor {
+    name: String,
+    /// Непустые значения по возрастанию дат.
+    observations: Vec<(NaiveDate, f64)>,
+}
+
+/// Регрессоры на обучающем окне, приведённые к нулевому среднему и единичному разбросу.
+pub(super) struct StandardizedDesign {
+    /// `[регрессор][день]`.
+    pub z: Vec<Vec<f64>>,
+    pub means: Vec<f64>,
+    pub stds: Vec<f64>,
+}
+
+/// Оценённое влияние регрессоров.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegressionFit {
+    pub names: Vec<String>,
+    /// Коэффициенты, км² на единицу показателя.
+    pub coefficients: Vec<f64>,
+    /// Средние на обучающем окне: эффект отсчитывается от них, постоянная часть уходит в уровень.
+    means: Vec<f64>,
+    regressors: Regressors,
+}
+
+impl Regressors {
+    /// Читает `path`; `columns` выбирает показатели по имени (пустой список — все колонки).
+    pub fn from_csv(path: &Path, columns: &[String]) -> Result<Self, Box<dyn Error>> {
+        let mut reader = csv::Reader::from_path(path)?;
+        let headers = reader.headers()?.clone();
+        if headers.get(0) != Some(DATE_COLUMN) {
+            return Err(
+                format!("first column of {} must be `{DATE_COLUMN}`", path.display()).into(),
+            );
+        }
+        let selected = if columns.is_empty() {
+            (1..headers.len()).collect::<Vec<_>>()
+        } else {
+            columns
+                .iter()
+                .map(|name| {
+                    headers
+                        .iter()
+                        .skip(1)
+                        .position(|header| header == name)
+                        .map(|idx| idx + 1)
+                        .ok_or_else(|| format!("column `{name}` not found in {}", path.display()))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        if selected.is_empty() {
+            return Err(format!("{} has no regressor columns", path.display()).into());
+        }
+
+        let mut regressors = selected
+            .iter()
+            .map(|&idx| Regressor {
+                name: headers[idx].to_string(),
+                observations: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        for record in reader.records() {
+            let record = record?;
+            let date = NaiveDate::parse_from_str(record.get(0).unwrap_or_default(), DATE_FORMAT)?;
+            for (regressor, &idx) in regressors.iter_mut().zip(&selected) {
+                let raw = record.get(idx).unwrap_or_default().trim();
+                if raw.is_empty() {
+                    continue;
+                }
+                let value = raw.parse::<f64>().map_err(|err| {
+                    format!("invalid {} value `{raw}` on {date}: {err}", regressor.name)
+                })?;
+                regressor.observations.push((date, value));
+            }
+        }
+        for regressor in &mut regressors {
+            regressor.observations.sort_by_key(|(date, _)| *date);
+            if regressor
+                .observations
+                .windows(2)
+                .any(|pair| pair[0].0 == pair[1].0)
+            {
+                return Err(format!("duplicate dates in regressor `{}`", regressor.name).into());
+            }
+        }
+        Ok(Self {
+            columns: regressors,
+        })
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.columns
+            .iter()
+            .map(|regressor| regressor.name.clone())
+            .collect()
+    }
+
+    /// Значения на обучающих датах; до первого значения показателя обучаться нельзя.
+    pub(super) fn standardized(&self, dates: &[NaiveDate]) -> Result<StandardizedDesign, String> {
+        let mut design = StandardizedDesign {
+            z: Vec::with_capacity(self.columns.len()),
+            means: Vec::with_capacity(self.columns.len()),
+            stds: Vec::with_capacity(self.columns.len()),
+        };
+        for regressor in &self.columns {
+            let values = dates
+                .iter()
+                .map(|date| {
+                    regressor.value_at(*date).ok_or_else(|| {
+                        format!(
+                            "regressor `{}` has no value on or before {}",
+                            regressor.name,
+                            date.format(DATE_FORMAT)
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let len = super::usize_to_f64(values.len(), "regressor_mean");
+            let mean = values.iter().sum::<f64>() / len;
+            let std = super::stddev(&values);
+            if std < MIN_REGRESSOR_STD {
+                return Err(format!(
+                    "regressor `{}` is constant over the training window",
+                    regressor.name
+                ));
+            }
+            design
+                .z
+                .push(values.iter().map(|value| (value - mean) / std).collect());
+            design.means.push(mean);
+            design.stds.push(std);
+        }
+        Ok(design)
+    }
+}
+
+impl Regressor {
+    fn value_at(&self, date: NaiveDate) -> Option<f64> {
+        let known = self
+            .observations
+            .partition_point(|(observed, _)| *observed <= date);
+        known.checked_sub(1).map(|idx| self.observations[idx].1)
+    }
+}
+
+impl RegressionFit {
+    /// `betas` — коэффициенты при стандартизованных регрессорах в масштабе модели `scale`.
+    pub(super) fn new(
+        regressors: &Regressors,
+        design: &StandardizedDesign,
+        betas: &[f64],
+        scale: f64,
+    ) -> Self {
+        Self {
+            names: regressors.names(),
+            coefficients: betas
+                .iter()
+                .zip(&design.stds)
+                .map(|(beta, std)| beta * scale / std)
+                .collect(),
+            means: design.means.clone(),
+            regressors: regressors.clone(),
+        }
+    }
+
+    /// Вклад регрессоров в наблюдение за `date`, км². Прогноз за концом ряда
+    /// использует последние известные значения показателей.
+    pub fn effect(&self, date: NaiveDate) -> Option<f64> {
+        self.regressors
+            .columns
+            .iter()
+            .zip(self.coefficients.iter().zip(&self.means))
+            .map(|(regressor, (coefficient, mean))| {
+                regressor
+                    .value_at(date)
+                    .map(|value| coefficient * (value - mean))
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "forecast")]
+    use chrono::Days;
+    use chrono::NaiveDate;
+
+    use super::Regressors;
+    #[cfg(feature = "forecast")]
+    use crate::model::{ModelConfig, train_from_series_with_regressors};
+
+    /// CSV во временном каталоге; каталог удаляется вместе с возвращённым `TempDir`.
+    fn write_csv(name: &str, raw: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join(format!("{name}.csv"));
+        std::fs::write(&path, raw).expect("regressors should be written");
+        (temp, path)
+    }
+
+    #[test]
+    fn regressors_fill_gaps_and_reject_days_before_first_value() {
+        let (_temp, path) = write_csv(
+            "regressors_fill",
+            "date,aid,rain\n2024-01-02,10,1\n2024-01-03,,2\n2024-01-05,30,\n",
+        );
+        let regressors =
+            Regressors::from_csv(&path, &["aid".to_string()]).expect("regressors should load");
+
+        assert_eq!(regressors.names(), vec!["aid".to_string()]);
+        let day = |day| NaiveDate::from_ymd_opt(2024, 1, day).expect("valid date");
+        let design = regressors
+            .standardized(&[day(2), day(3), day(4), day(5)])
+            .expect("days are covered");
+        assert!((design.means[0] - 15.0).abs() < 1e-9);
+        assert!(regressors.standardized(&[day(1), day(2)]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "forecast")]
+    fn joint_fit_recovers_regressor_coefficient() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date");
+        let dates = (0..240)
+            .map(|day| start.checked_add_days(Days::new(day)).expect("valid date"))
+            .collect::<Vec<_>>();
+        let indicator = (0..240u32)
+            .map(|day| f64::from((day * 37 + 11) % 17) / 4.0)
+            .collect::<Vec<_>>();
+        let raw = std::iter::once("date,aid".to_string())
+            .chain(
+                dates
+                    .iter()
+                    .zip(&indicator)
+                    .map(|(date, value)| format!("{date},{value}")),
+            )
+            .collect::<Vec<_>>()
+            .join("\n");
+        let (_temp, path) = write_csv("regressors_fit", &raw);
+        let regressors = Regressors::from_csv(&path, &[]).expect("regressors should load");
+        let values = (0..240u32)
+            .zip(&indicator)
+            .map(|(day, x)| 50.0f64.mul_add(*x, f64::from(day).mul_add(12.0, 100_000.0)))
+            .collect::<Vec<_>>();
+
+        let fitted = train_from_series_with_regressors(
+            &dates,
+            &values,
+            ModelConfig::default(),
+            Some(&regressors),
+        )
+        .expect("model should train");
+
+        let regression = fitted.regression.expect("regression should be fitted");
+        assert!(
+            (regression.coefficients[0] - 50.0).abs() < 5.0,
+            "coefficient {}",
+            regression.coefficients[0]
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! Сезонные эффекты LLT-модели: надбавка к дневному темпу для каждого месяца или сезона.
+//!
+//! Переход уровня становится `level_t = level_{t-1} + trend_{t-1} + γ_{g(t)} + w_t`, где `g(t)` —
+//! месяц или сезон дня. Сумма эффектов по группам равна нулю, так что средний темп остаётся
+//! в тренде, а эффекты — отклонения от него. Накопленная надбавка `Σ γ` вычитается из ряда
+//! так же, как вклад регрессоров, а на коэффициенты действует гребневый штраф.
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_SEASONAL_PENALTY: f64 = 1.0;
+
+const MONTH_LABELS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const SEASON_LABELS: [&str; 4] = ["winter", "spring", "summer", "autumn"];
+
+/// Группировка дней для сезонных эффектов.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeasonalPeriod {
+    /// Двенадцать эффектов, по одному на месяц.
+    #[default]
+    Month,
+    /// Четыре эффекта: зима (декабрь–февраль), весна, лето, осень.
+    Season,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct SeasonalityConfig {
+    pub period: SeasonalPeriod,
+    /// Вес гребневого штрафа `½ · penalty · Σ γ²` (γ в км²/сутки).
+    pub penalty: f64,
+}
+
+/// Оценённые сезонные эффекты.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeasonalFit {
+    pub period: SeasonalPeriod,
+    /// Надбавка к дневному темпу по группам, км²/сутки.
+    pub effects: Vec<f64>,
+    /// Накопленная надбавка на последний день обучения, км².
+    drift_at_last: f64,
+}
+
+impl SeasonalPeriod {
+    /// Имя периода в конфигурации.
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Month => "month",
+            Self::Season => "season",
+        }
+    }
+
+    pub const fn groups(self) -> usize {
+        match self {
+            Self::Month => MONTH_LABELS.len(),
+            Self::Season => SEASON_LABELS.len(),
+        }
+    }
+
+    pub fn group(self, date: NaiveDate) -> usize {
+        let month = date.month0() as usize;
+        match self {
+            Self::Month => month,
+            Self::Season => (month + 1) % 12 / 3,
+        }
+    }
+
+    pub const fn label(self, group: usize) -> &'static str {
+        match self {
+            Self::Month => MONTH_LABELS[group],
+            Self::Season => SEASON_LABELS[group],
+        }
+    }
+}
+
+/// Регрессоры накопленной надбавки для свободных коэффициентов `[группа][день]`: число дней
+/// группы `j` минус число дней последней группы (её эффект равен минус сумме остальных).
+pub(super) fn design(period: SeasonalPeriod, dates: &[NaiveDate]) -> Vec<Vec<f64>> {
+    let free = period.groups() - 1;
+    let mut rows = vec![Vec::with_capacity(dates.len()); free];
+    let mut counts = vec![0.0; period.groups()];
+    for (idx, date) in dates.iter().enumerate() {
+        // Надбавка входит в переход к дню, поэтому первый день её не получает; в недельном
+        // ряду переход накапливает надбавки всех дней между точками.
+        if let Some(previous) = idx.checked_sub(1).map(|previous| dates[previous]) {
+            for day in days_between(previous, *date) {
+                counts[period.group(day)] += 1.0;
+            }
+        }
+        for (row, count) in rows.iter_mut().zip(&counts) {
+            row.push(count - counts[free]);
+        }
+    }
+    rows
+}
+
+/// Эффекты всех групп по свободным коэффициентам.
+pub(super) fn effects(free: &[f64]) -> Vec<f64> {
+    let mut effects = free.to_vec();
+    effects.push(-free.iter().sum::<f64>());
+    effects
+}
+
+impl SeasonalFit {
+    /// `free` — свободные коэффициенты в масштабе модели `scale`.
+    pub(super) fn new(
+        period: SeasonalPeriod,
+        free: &[f64],
+        scale: f64,
+        dates: &[NaiveDate],
+    ) -> Self {
+        let mut fit = Self {
+            period,
+            effects: effects(free).iter().map(|effect| effect * scale).collect(),
+            drift_at_last: 0.0,
+        };
+        fit.drift_at_last = fit.cumulative(dates).last().copied().unwrap_or(0.0);
+        fit
+    }
+
+    /// Накопленная надбавка на каждую из `dates` от первой даты, км².
+    pub fn cumulative(&self, dates: &[NaiveDate]) -> Vec<f64> {
+        let mut drift = 0.0;
+        dates
+            .iter()
+            .enumerate()
+            .map(|(idx, date)| {
+                if let Some(previous) = idx.checked_sub(1).map(|previous| dates[previous]) {
+                    drift += self.drift(previous, *date);
+                }
+                drift
+            })
+            .collect()
+    }
+
+    /// Накопленная надбавка на точку прогноза `day`, если `so_far` — её значение
+    /// в предыдущей точке `previous` (`None` — конец обучения).
+    pub fn step(&self, so_far: Option<f64>, previous: NaiveDate, day: NaiveDate) -> f64 {
+        so_far.unwrap_or(self.drift_at_last) + self.drift(previous, day)
+    }
+
+    fn drift(&self, previous: NaiveDate, day: NaiveDate) -> f64 {
+        days_between(previous, day)
+            .map(|day| self.effects[self.period.group(day)])
+            .sum()
+    }
+}
+
+/// Дни после `previous` по `day` включительно.
+fn days_between(previous: NaiveDate, day: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    previous
+        .iter_days()
+        .skip(1)
+        .take_while(move |current| *current <= day)
+}
+
+#[cfg(all(test, feature = "forecast"))]
+mod tests {
+    use chrono::{Days, NaiveDate};
+
+    use super::{SeasonalPeriod, SeasonalityConfig};
+    use crate::model::{ModelConfig, train_from_series_with_regressors};
+
+    #[test]
+    fn seasonal_fit_recovers_faster_summer_pace() {
+        let start = NaiveDate::from_ymd_opt(2022, 12, 1).expect("valid date");
+        let dates = (0..730)
+            .map(|day| start.checked_add_days(Days::new(day)).expect("valid date"))
+            .collect::<Vec<_>>();
+        assert_eq!(SeasonalPeriod::Season.group(dates[0]), 0);
+        let mut level = 100_000.0;
+        let values = dates
+            .iter()
+            .map(|date| {
+                level += match SeasonalPeriod::Season.group(*date) {
+                    2 => 20.0,
+                    0 => 2.0,
+                    _ => 10.0,
+                };
+                level
+            })
+            .collect::<Vec<_>>();
+
+        let fitted = train_from_series_with_regressors(
+            &dates,
+            &values,
+            ModelConfig {
+                seasonality: Some(SeasonalityConfig {
+                    period: SeasonalPeriod::Season,
+                    penalty: 0.0,
+                }),
+                ..ModelConfig::default()
+            },
+            None,
+        )
+        .expect("model should train");
+
+        let seasonal = fitted
+            .seasonality
+            .expect("seasonal effects should be fitted");
+        let [winter, _, summer, _] = seasonal.effects[..] else {
+            panic!("expected four seasons");
+        };
+        assert!(summer > 5.0, "summer {summer}");
+        assert!(winter < -3.0, "winter {winter}");
+        assert!(seasonal.effects.iter().sum::<f64>().abs() < 1e-6);
+    }
+}
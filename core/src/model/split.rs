@@ -0,0 +1,198 @@
+//! Разбиения временного ряда для кросс-валидации: обучение всегда раньше проверки.
+//!
+//! Окно обучения растёт от начала ряда ([`Window::Expanding`]) или скользит с постоянной длиной
+//! ([`Window::Sliding`]); `gap` дней между обучением и проверкой отбрасываются, чтобы
+//! автокорреляция соседних дней не завышала качество.
+
+use std::ops::Range;
+
+use chrono::NaiveDate;
+
+/// Как выбирается обучающая часть каждого фолда.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Window {
+    /// Вся история до начала проверки (за вычетом `gap`).
+    Expanding,
+    /// Последние `train_days` наблюдений перед `gap`.
+    Sliding { train_days: usize },
+}
+
+/// Индексы обучающей и проверочной частей одного фолда.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fold {
+    pub train: Range<usize>,
+    pub test: Range<usize>,
+}
+
+/// Параметры разбиения: `folds` проверочных отрезков по `horizon` наблюдений в конце ряда.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeSeriesSplit {
+    pub folds: usize,
+    pub horizon: usize,
+    pub gap: usize,
+    pub window: Window,
+    /// Минимальная длина обучающей части.
+    pub min_train: usize,
+}
+
+impl TimeSeriesSplit {
+    pub const fn expanding(folds: usize, horizon: usize) -> Self {
+        Self {
+            folds,
+            horizon,
+            gap: 0,
+            window: Window::Expanding,
+            min_train: 1,
+        }
+    }
+
+    pub const fn sliding(folds: usize, horizon: usize, train_days: usize) -> Self {
+        Self {
+            window: Window::Sliding { train_days },
+            ..Self::expanding(folds, horizon)
+        }
+    }
+
+    #[must_use]
+    pub const fn with_gap(mut self, gap: usize) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_min_train(mut self, min_train: usize) -> Self {
+        self.min_train = min_train;
+        self
+    }
+
+    /// Минимальная длина ряда, на которой помещаются все фолды.
+    pub const fn required_len(&self) -> usize {
+        let train = match self.window {
+            Window::Expanding => self.min_train,
+            Window::Sliding { train_days } => {
+                if train_days > self.min_train {
+                    train_days
+                } else {
+                    self.min_train
+                }
+            }
+        };
+        train + self.gap + self.folds * self.horizon
+    }
+
+    /// Фолды ряда длины `len` в хронологическом порядке; проверочные отрезки не пересекаются.
+    pub fn split(&self, len: usize) -> Result<Vec<Fold>, String> {
+        if self.folds == 0 || self.horizon == 0 {
+            return Err("folds and horizon must be positive".to_string());
+        }
+        if let Window::Sliding { train_days } = self.window
+            && train_days < self.min_train
+        {
+            return Err(format!(
+                "sliding window of {train_days} days is shorter than the minimum of {}",
+                self.min_train
+            ));
+        }
+        if len < self.required_len() {
+            return Err(format!(
+                "need at least {} observations for {} folds of {} days",
+                self.required_len(),
+                self.folds,
+                self.horizon
+            ));
+        }
+        Ok((1..=self.folds)
+            .rev()
+            .map(|fold| {
+                let test_start = len - fold * self.horizon;
+                let train_end = test_start - self.gap;
+                let train_start = match self.window {
+                    Window::Expanding => 0,
+                    Window::Sliding { train_days } => train_end - train_days,
+                };
+                Fold {
+                    train: train_start..train_end,
+                    test: test_start..test_start + self.horizon,
+                }
+            })
+            .collect())
+    }
+}
+
+/// Средняя абсолютная ошибка прогноза по фолдам `split`.
+///
+/// `forecast` получает обучающие даты и значения и возвращает прогноз на `horizon` шагов;
+/// так свою модель можно сравнить со встроенными на тех же отсечениях.
+pub fn mean_abs_error<F>(
+    dates: &[NaiveDate],
+    values: &[f64],
+    split: &TimeSeriesSplit,
+    mut forecast: F,
+) -> Result<f64, String>
+where
+    F: FnMut(&[NaiveDate], &[f64], usize) -> Result<Vec<f64>, String>,
+{
+    if dates.len() != values.len() {
+        return Err("dates/values must be the same length".to_string());
+    }
+    let mut abs_error = 0.0;
+    let mut count = 0u32;
+    for fold in split.split(values.len())? {
+        let predicted = forecast(
+            &dates[fold.train.clone()],
+            &values[fold.train],
+            split.horizon,
+        )?;
+        if predicted.len() < split.horizon {
+            return Err(format!(
+                "forecast returned {} values for a horizon of {}",
+                predicted.len(),
+                split.horizon
+            ));
+        }
+        for (predicted, actual) in predicted.iter().zip(&values[fold.test]) {
+            abs_error += (predicted - actual).abs();
+            count += 1;
+        }
+    }
+    Ok(abs_error / f64::from(count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fold, TimeSeriesSplit};
+
+    #[test]
+    fn splits_keep_training_before_the_gap_and_test() {
+        let expanding = TimeSeriesSplit::expanding(2, 3)
+            .with_gap(1)
+            .split(10)
+            .expect("series is long enough");
+        assert_eq!(
+            expanding,
+            vec![
+                Fold {
+                    train: 0..3,
+                    test: 4..7,
+                },
+                Fold {
+                    train: 0..6,
+                    test: 7..10,
+                },
+            ]
+        );
+
+        let sliding = TimeSeriesSplit::sliding(2, 3, 2)
+            .split(10)
+            .expect("series is long enough");
+        assert_eq!(sliding[0].train, 2..4);
+        assert_eq!(sliding[1].train, 5..7);
+
+        assert!(
+            TimeSeriesSplit::expanding(3, 3)
+                .with_min_train(2)
+                .split(10)
+                .is_err()
+        );
+    }
+}
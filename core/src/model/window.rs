@@ -0,0 +1,155 @@
+//! Окно обучения модели: с фиксированной даты, последние N дней или вся история.
+//!
+//! По умолчанию обучение идёт с 22.11.2022 — после отхода из Херсона фронт стабилизировался,
+//! и ранние скачки первых месяцев войны искажали бы темп. Фактические границы окна
+//! ([`TrainingSpan`]) сохраняются в CSV прогноза, манифесте сборки и на странице.
+
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+
+use chrono::{Days, NaiveDate};
+use serde::Serialize;
+
+const DEFAULT_TRAINING_START: (i32, u32, u32) = (2022, 11, 22);
+
+/// Какую часть ряда брать для обучения.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrainingWindow {
+    /// Дни начиная с даты включительно.
+    Since(NaiveDate),
+    /// Последние N дней ряда.
+    LastDays(NonZeroUsize),
+    /// Весь ряд.
+    All,
+}
+
+impl Default for TrainingWindow {
+    fn default() -> Self {
+        let (year, month, day) = DEFAULT_TRAINING_START;
+        Self::Since(NaiveDate::from_ymd_opt(year, month, day).unwrap_or(NaiveDate::MIN))
+    }
+}
+
+impl fmt::Display for TrainingWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Since(date) => write!(f, "since {date}"),
+            Self::LastDays(days) => write!(f, "last {days} days"),
+            Self::All => f.write_str("all data"),
+        }
+    }
+}
+
+/// `all`, дата начала `YYYY-MM-DD` или число дней `365d` — так окно задаётся в TOML и CLI.
+impl FromStr for TrainingWindow {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let raw = raw.trim();
+        if raw == "all" {
+            return Ok(Self::All);
+        }
+        if let Some(days) = raw.strip_suffix('d') {
+            return days
+                .parse::<NonZeroUsize>()
+                .map(Self::LastDays)
+                .map_err(|_| {
+                    format!(
+                        "invalid training window {raw:?}: expected a positive day count like 365d"
+                    )
+                });
+        }
+        NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+            .map(Self::Since)
+            .map_err(|_| format!("invalid training window {raw:?}: expected all, YYYY-MM-DD or Nd"))
+    }
+}
+
+impl TrainingWindow {
+    /// Первый день окна для ряда, который кончается `last`; `None` — без ограничения.
+    pub fn start(self, last: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::Since(date) => Some(date),
+            Self::LastDays(days) => {
+                let back = u64::try_from(days.get() - 1).unwrap_or(u64::MAX);
+                Some(
+                    last.checked_sub_days(Days::new(back))
+                        .unwrap_or(NaiveDate::MIN),
+                )
+            }
+            Self::All => None,
+        }
+    }
+
+    /// Оставляет из дневного ряда дни окна.
+    pub fn apply(self, dates: Vec<NaiveDate>, values: Vec<f64>) -> (Vec<NaiveDate>, Vec<f64>) {
+        let Some(start) = dates.last().and_then(|last| self.start(*last)) else {
+            return (dates, values);
+        };
+        dates
+            .into_iter()
+            .zip(values)
+            .filter(|(date, _)| *date >= start)
+            .unzip()
+    }
+}
+
+/// Фактические границы обучающего ряда (включительно).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct TrainingSpan {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl TrainingSpan {
+    pub fn of(dates: &[NaiveDate]) -> Option<Self> {
+        Some(Self {
+            from: *dates.first()?,
+            to: *dates.last()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use chrono::NaiveDate;
+
+    use super::{TrainingSpan, TrainingWindow};
+
+    #[test]
+    fn windows_cut_the_series_from_the_right_day() {
+        let start = NaiveDate::from_ymd_opt(2022, 11, 20).expect("valid date");
+        let dates = start.iter_days().take(10).collect::<Vec<_>>();
+        let values = (0..10).map(f64::from).collect::<Vec<_>>();
+        let window = |window: TrainingWindow| window.apply(dates.clone(), values.clone());
+
+        let (since, _) = window(TrainingWindow::default());
+        assert_eq!(since.first(), Some(&dates[2]));
+        let (last, last_values) = window(TrainingWindow::LastDays(
+            NonZeroUsize::new(3).expect("non-zero"),
+        ));
+        assert_eq!(last, dates[7..]);
+        assert_eq!(last_values, [7.0, 8.0, 9.0]);
+        assert_eq!(window(TrainingWindow::All).0.len(), 10);
+        assert_eq!("all".parse(), Ok(TrainingWindow::All));
+        assert_eq!("2022-11-22".parse(), Ok(TrainingWindow::default()));
+        assert_eq!(
+            "3d".parse(),
+            Ok(TrainingWindow::LastDays(
+                NonZeroUsize::new(3).expect("non-zero")
+            ))
+        );
+        assert!("0d".parse::<TrainingWindow>().is_err());
+        assert_eq!(
+            TrainingSpan::of(&last),
+            Some(TrainingSpan {
+                from: dates[7],
+                to: dates[9]
+            })
+        );
+    }
+}
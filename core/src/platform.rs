@@ -0,0 +1,96 @@
+//! Различия Windows, macOS и Linux, которые видны пользователю: ANSI-последовательности в
+//! консоли и замена файла, открытого другим процессом.
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::time::Duration;
+
+/// Сколько раз повторять замену файла, которую Windows временно отклонила.
+const REPLACE_RETRIES: u32 = 5;
+/// Пауза перед первым повтором; каждая следующая длиннее на столько же.
+const REPLACE_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Можно ли писать в `stream` ANSI-последовательности: цвета лога и индикатор загрузки.
+///
+/// Нужен терминал без `NO_COLOR` и `TERM=dumb`. В консоли Windows последовательности сначала
+/// включаются (`ENABLE_VIRTUAL_TERMINAL_PROCESSING`); старая консоль без их поддержки получает
+/// простой текст вместо мусора из escape-кодов.
+pub fn ansi_enabled(stream: &impl IsTerminal) -> bool {
+    ansi_allowed(
+        stream.is_terminal(),
+        std::env::var_os("NO_COLOR").as_deref(),
+        std::env::var_os("TERM").as_deref(),
+    ) && virtual_terminal()
+}
+
+fn ansi_allowed(is_terminal: bool, no_color: Option<&OsStr>, term: Option<&OsStr>) -> bool {
+    is_terminal && no_color.is_none_or(OsStr::is_empty) && term != Some(OsStr::new("dumb"))
+}
+
+#[cfg(windows)]
+fn virtual_terminal() -> bool {
+    static ENABLED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *ENABLED.get_or_init(|| nu_ansi_term::enable_ansi_support().is_ok())
+}
+
+#[cfg(not(windows))]
+const fn virtual_terminal() -> bool {
+    true
+}
+
+/// Атомарно заменяет `to` файлом `from`.
+///
+/// В Windows переименование поверх файла, который держит другой процесс (антивирус, Excel,
+/// параллельный запуск `rua`), на время отклоняется с `PermissionDenied`; такой отказ
+/// повторяется с нарастающей паузой, а на остальных системах ошибка возвращается сразу.
+pub fn replace_file(from: &Path, to: &Path) -> io::Result<()> {
+    let mut attempt = 0;
+    loop {
+        match fs::rename(from, to) {
+            Err(err)
+                if cfg!(windows)
+                    && err.kind() == io::ErrorKind::PermissionDenied
+                    && attempt < REPLACE_RETRIES =>
+            {
+                attempt += 1;
+                std::thread::sleep(REPLACE_BACKOFF * attempt);
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::{ansi_allowed, replace_file};
+
+    #[test]
+    fn ansi_detection_and_replacing_an_open_file() {
+        let xterm = Some(OsStr::new("xterm-256color"));
+        assert!(ansi_allowed(true, None, xterm));
+        // В консоли Windows `TERM` обычно не задан.
+        assert!(ansi_allowed(true, None, None));
+        assert!(ansi_allowed(true, Some(OsStr::new("")), xterm));
+        assert!(!ansi_allowed(false, None, xterm));
+        assert!(!ansi_allowed(true, Some(OsStr::new("1")), xterm));
+        assert!(!ansi_allowed(true, None, Some(OsStr::new("dumb"))));
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let dir = temp.path();
+        let (from, to) = (dir.join("new.csv"), dir.join("history.csv"));
+        std::fs::write(&from, "new").expect("new file");
+        std::fs::write(&to, "old").expect("old file");
+        // Читатель держит файл открытым, как `rua serve`: замена всё равно проходит.
+        let reader = std::fs::File::open(&to).expect("open reader");
+        let replaced = replace_file(&from, &to);
+        drop(reader);
+        let content = std::fs::read_to_string(&to);
+        replaced.expect("file is replaced");
+        assert_eq!(content.expect("replaced file"), "new");
+        assert!(!from.exists());
+    }
+}
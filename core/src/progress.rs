@@ -0,0 +1,145 @@
+//! Ход загрузки срезов: скорость, оставшееся время и повторы запросов. Ход подбора
+//! параметров модели пишет наблюдатель из `model::optimizer`.
+//!
+//! Индикатор в терминале показывает то же, что строки лога; лог пишется раз в
+//! [`LOG_INTERVAL`], поэтому ход загрузки виден и в CI, где индикатор не рисуется. Индикатор
+//! перерисовывается ANSI-последовательностями и выключается там, где их нет (см.
+//! [`crate::platform::ansi_enabled`]): в перенаправленном stderr и старой консоли Windows.
+
+use std::time::{Duration, Instant};
+
+use tqdm::{Tqdm, pbar};
+use tracing::{info, warn};
+
+/// Как часто писать ход загрузки в лог.
+const LOG_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Индикатор загрузки срезов с оценкой скорости и оставшегося времени.
+pub struct Progress {
+    bar: Option<Tqdm<()>>,
+    total: usize,
+    done: usize,
+    retries: u32,
+    started: Instant,
+    logged: Instant,
+}
+
+impl Progress {
+    pub fn new(total: usize) -> Self {
+        let now = Instant::now();
+        Self {
+            bar: crate::platform::ansi_enabled(&std::io::stderr()).then(|| pbar(Some(total))),
+            total,
+            done: 0,
+            retries: 0,
+            started: now,
+            logged: now,
+        }
+    }
+
+    /// Добавляет `more` ожидаемых срезов: так один индикатор ведёт несколько загрузок сразу.
+    pub fn grow(&mut self, more: usize) {
+        self.total += more;
+        let total = self.total;
+        self.bar = self.bar.take().map(|bar| bar.total(Some(total)));
+    }
+
+    /// Отмечает ещё один обработанный срез; `retries` — повторов запросов с начала загрузки.
+    pub fn advance(&mut self, retries: u32) {
+        self.done += 1;
+        self.retries = retries;
+        let elapsed = self.started.elapsed();
+        let status = self.status(elapsed);
+        if let Some(bar) = &mut self.bar {
+            bar.set_desc(Some(status));
+            if let Err(err) = bar.update(1) {
+                warn!(error = %err, "Failed to update progress bar");
+            }
+        }
+        if self.logged.elapsed() >= LOG_INTERVAL {
+            self.logged = Instant::now();
+            info!(
+                done = self.done,
+                total = self.total,
+                rate = format!("{:.1}/s", rate(self.done, elapsed)),
+                eta = eta(self.done, self.total, elapsed).map_or_else(|| "?".to_string(), clock),
+                retries = self.retries,
+                "Fetching snapshots"
+            );
+        }
+    }
+
+    /// Закрывает индикатор и пишет итог загрузки.
+    pub fn finish(mut self) {
+        if let Some(Err(err)) = self.bar.as_mut().map(Tqdm::close) {
+            warn!(error = %err, "Failed to close progress bar");
+        }
+        let elapsed = self.started.elapsed();
+        info!(
+            done = self.done,
+            total = self.total,
+            elapsed = clock(elapsed),
+            rate = format!("{:.1}/s", rate(self.done, elapsed)),
+            retries = self.retries,
+            "Finished fetching snapshots"
+        );
+    }
+
+    fn status(&self, elapsed: Duration) -> String {
+        let eta = eta(self.done, self.total, elapsed).map_or_else(|| "?".to_string(), clock);
+        format!(
+            "{:.1} срез/с, осталось {eta}, повторов {}",
+            rate(self.done, elapsed),
+            self.retries
+        )
+    }
+}
+
+/// Обработано в секунду.
+fn rate(done: usize, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds > 0.0 {
+        f64::from(u32::try_from(done).unwrap_or(u32::MAX)) / seconds
+    } else {
+        0.0
+    }
+}
+
+/// Оставшееся время при текущей средней скорости; `None`, пока скорость неизвестна.
+fn eta(done: usize, total: usize, elapsed: Duration) -> Option<Duration> {
+    if done == 0 {
+        return None;
+    }
+    let remaining = u32::try_from(total.saturating_sub(done)).unwrap_or(u32::MAX);
+    let done = u32::try_from(done).unwrap_or(u32::MAX);
+    Some(elapsed / done * remaining)
+}
+
+/// `m:ss` или `h:mm:ss`.
+fn clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{clock, eta, rate};
+
+    #[test]
+    fn rate_and_eta_follow_the_average_pace() {
+        let elapsed = Duration::from_secs(40);
+        assert!((rate(100, elapsed) - 2.5).abs() < 1e-9);
+        assert_eq!(eta(0, 500, elapsed), None);
+        assert_eq!(eta(100, 500, elapsed), Some(Duration::from_secs(160)));
+        assert_eq!(eta(500, 500, elapsed), Some(Duration::ZERO));
+        assert_eq!(clock(Duration::from_secs(160)), "2:40");
+        assert_eq!(clock(Duration::from_secs(3_725)), "1:02:05");
+    }
+}
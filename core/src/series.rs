@@ -0,0 +1,1465 @@
+//! Общие функции для загрузки CSV и построения временного ряда занятых территорий.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::constants::{DATE_FORMAT, Precision, ukraine_percent};
+use crate::data::Area;
+use crate::integrity::sha256_hex;
+use crate::methodology::{Parameter, Section};
+
+mod cache;
+
+pub use cache::CACHE_SUFFIX as SERIES_CACHE_SUFFIX;
+pub use cache::cache_path as series_cache_path;
+
+pub const AREA_TYPE_OCCUPIED: &str = "occupied_after_24_02_2022";
+const AREA_TYPE_OCCUPIED_PRE_WAR: &str = "occupied_to_24_02_2022";
+pub const AREA_TYPE_OTHER: &str = "other_territories";
+const AREA_TYPE_UNSPECIFIED: &str = "unspecified";
+const UA_HASH: &str = "#01579b";
+/// Значение `hash` у строк, внесённых вручную (`rua append`); слой UA у них тоже учитывается.
+pub const MANUAL_HASH: &str = "manual";
+
+const TIME_FORMAT_TZ: &str = "%Y-%m-%d %H:%M:%S %Z";
+const TIME_FORMAT_OFFSET: &str = "%Y-%m-%d %H:%M:%S %z";
+const TIME_FORMAT_NAIVE: &str = "%Y-%m-%d %H:%M:%S";
+const UTC_SUFFIX: &str = " UTC";
+
+const ERROR_EMPTY_TIME_INDEX: &str = "empty time index";
+const ERROR_UNRECOGNIZED_TIME: &str = "unrecognized time format";
+const ERROR_NO_DATA: &str = "No data available";
+
+const FULL_RESOLUTION_HEADER: [&str; 4] = ["time", "resolution", "occupied_km2", "unspecified_km2"];
+const RESOLUTION_DAILY: &str = "daily";
+const RESOLUTION_RAW: &str = "raw";
+const GAP_REPORT_HEADER: [&str; 3] = ["start", "end", "days"];
+const PERCENT_HISTORY_HEADER: [&str; 3] = ["date", "occupied_km2", "ukraine_percent"];
+
+type DailyBuckets = BTreeMap<NaiveDate, DailyAccumulator>;
+
+#[derive(Clone, Copy, Debug)]
+enum TimeFormatHint {
+    Tz,
+    Offset,
+    Rfc3339,
+    NaiveUtcSuffix,
+    Naive,
+}
+
+impl TimeFormatHint {
+    fn parse(self, raw: &str) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Tz => DateTime::parse_from_str(raw, TIME_FORMAT_TZ)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc)),
+            Self::Offset => DateTime::parse_from_str(raw, TIME_FORMAT_OFFSET)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc)),
+            Self::Rfc3339 => DateTime::parse_from_rfc3339(raw)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc)),
+            Self::NaiveUtcSuffix => raw
+                .strip_suffix(UTC_SUFFIX)
+                .and_then(|stripped| {
+                    NaiveDateTime::parse_from_str(stripped.trim_end(), TIME_FORMAT_NAIVE).ok()
+                })
+                .map(|naive| Utc.from_utc_datetime(&naive)),
+            Self::Naive => NaiveDateTime::parse_from_str(raw, TIME_FORMAT_NAIVE)
+                .ok()
+                .map(|naive| Utc.from_utc_datetime(&naive)),
+        }
+    }
+}
+
+/// Строка из CSV-экспорта API (поля используются выборочно).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AreaKind {
+    RuOccupied,
+    RuPreWar,
+    UaOtherTerritories,
+    Unspecified,
+    Other,
+}
+
+fn deserialize_area_kind<'de, D>(deserializer: D) -> Result<AreaKind, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = String::deserialize(deserializer)?;
+    Ok(area_kind(&raw))
+}
+
+fn area_kind(raw: &str) -> AreaKind {
+    match raw {
+        AREA_TYPE_OCCUPIED => AreaKind::RuOccupied,
+        AREA_TYPE_OCCUPIED_PRE_WAR => AreaKind::RuPreWar,
+        AREA_TYPE_OTHER => AreaKind::UaOtherTerritories,
+        AREA_TYPE_UNSPECIFIED => AreaKind::Unspecified,
+        _ => AreaKind::Other,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HashKind {
+    Ua,
+    Manual,
+    Other,
+}
+
+fn deserialize_hash_kind<'de, D>(deserializer: D) -> Result<HashKind, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = String::deserialize(deserializer)?;
+    Ok(hash_kind(&raw))
+}
+
+fn hash_kind(raw: &str) -> HashKind {
+    match raw {
+        UA_HASH => HashKind::Ua,
+        MANUAL_HASH => HashKind::Manual,
+        _ => HashKind::Other,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CsvRow {
+    time_index: String,
+    #[serde(rename = "hash", deserialize_with = "deserialize_hash_kind")]
+    hash_kind: HashKind,
+    area: f64,
+    #[allow(dead_code)]
+    percent: f64,
+    #[serde(
+        alias = "type",
+        alias = "area_type",
+        deserialize_with = "deserialize_area_kind"
+    )]
+    area_kind: AreaKind,
+}
+
+/// Аккумулятор для усреднения значений внутри одного дня.
+#[derive(Default, Clone, Copy)]
+struct DailyAccumulator {
+    sum: f64,
+    count: u32,
+}
+
+impl DailyAccumulator {
+    fn add(&mut self, value: f64) {
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn mean(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.sum / f64::from(self.count))
+    }
+}
+
+/// Диапазон дат `[from, to]` (границы включительно, `None` снимает ограничение с края).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Только срезы не позже этого момента (`--as-of`): история такой, какой она была тогда.
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+impl DateRange {
+    pub fn contains(self, date: NaiveDate) -> bool {
+        self.from.is_none_or(|from| date >= from)
+            && self.to.is_none_or(|to| date <= to)
+            && self.as_of.is_none_or(|as_of| date <= as_of.date_naive())
+    }
+
+    pub const fn is_unbounded(self) -> bool {
+        self.from.is_none() && self.to.is_none() && self.as_of.is_none()
+    }
+}
+
+impl fmt::Display for DateRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(from) = self.from {
+            write!(f, "{from}")?;
+        }
+        f.write_str("..")?;
+        if let Some(to) = self.to {
+            write!(f, "{to}")?;
+        }
+        if let Some(as_of) = self.as_of {
+            write!(f, " as of {}", as_of.to_rfc3339())?;
+        }
+        Ok(())
+    }
+}
+
+/// Значения одного среза API по категориям площадей.
+#[derive(Default, Clone, Copy)]
+struct SnapshotAccumulator {
+    ru: DailyAccumulator,
+    ua: DailyAccumulator,
+    unspecified: DailyAccumulator,
+}
+
+/// Отдельный срез без дневного усреднения.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Snapshot {
+    pub time: DateTime<Utc>,
+    pub occupied: f64,
+    pub unspecified: f64,
+}
+
+/// Дневные бакеты, сгруппированные по категориям площадей.
+#[derive(Default, Clone)]
+pub struct AreaBuckets {
+    ru: DailyBuckets,
+    ua: DailyBuckets,
+    unspecified: DailyBuckets,
+    /// Территории, занятые до 24.02.2022; в основной ряд не входят.
+    pre_war: DailyBuckets,
+    snapshots: BTreeMap<DateTime<Utc>, SnapshotAccumulator>,
+    /// Дни, в которые есть строки ручного ввода.
+    manual: BTreeSet<NaiveDate>,
+    /// Дни, исключённые из обучения модели, с причинами; на графике они остаются.
+    excluded: BTreeMap<NaiveDate, String>,
+}
+
+impl AreaBuckets {
+    /// Добавляет строку CSV в соответствующий дневной бакет и в срез по её времени.
+    fn ingest(&mut self, row: &CsvRow, datetime: DateTime<Utc>) {
+        self.ingest_value(row.area_kind, row.hash_kind, row.area, datetime);
+    }
+
+    fn ingest_value(
+        &mut self,
+        area_kind: AreaKind,
+        hash_kind: HashKind,
+        area: f64,
+        datetime: DateTime<Utc>,
+    ) {
+        let date = datetime.date_naive();
+        if hash_kind == HashKind::Manual {
+            self.manual.insert(date);
+        }
+        match (area_kind, hash_kind) {
+            (AreaKind::RuOccupied, _) => {
+                self.ru.entry(date).or_default().add(area);
+                self.snapshots.entry(datetime).or_default().ru.add(area);
+            }
+            (AreaKind::UaOtherTerritories, HashKind::Ua | HashKind::Manual) => {
+                self.ua.entry(date).or_default().add(area);
+                self.snapshots.entry(datetime).or_default().ua.add(area);
+            }
+            (AreaKind::RuPreWar, _) => {
+                self.pre_war.entry(date).or_default().add(area);
+            }
+            (AreaKind::Unspecified, _) => {
+                self.unspecified.entry(date).or_default().add(area);
+                self.snapshots
+                    .entry(datetime)
+                    .or_default()
+                    .unspecified
+                    .add(area);
+            }
+            _ => {} // Игнорируем прочие категории.
+        }
+    }
+
+    /// Время последнего среза со слоем RU.
+    pub fn latest_snapshot_time(&self) -> Option<DateTime<Utc>> {
+        self.snapshots
+            .iter()
+            .rev()
+            .find(|(_, acc)| acc.ru.mean().is_some())
+            .map(|(time, _)| *time)
+    }
+
+    /// Срезы в исходном временном разрешении (только те, где есть слой RU).
+    pub fn snapshots(&self) -> Vec<Snapshot> {
+        self.snapshots
+            .iter()
+            .filter_map(|(time, acc)| {
+                Some(Snapshot {
+                    time: *time,
+                    occupied: acc.ru.mean()? - acc.ua.mean().unwrap_or_default(),
+                    unspecified: acc.unspecified.mean().unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    /// Оставляет только дни из диапазона дат.
+    pub fn retain_range(&mut self, range: DateRange) {
+        if range.is_unbounded() {
+            return;
+        }
+        for buckets in [
+            &mut self.ru,
+            &mut self.ua,
+            &mut self.unspecified,
+            &mut self.pre_war,
+        ] {
+            buckets.retain(|date, _| range.contains(*date));
+        }
+        self.snapshots
+            .retain(|time, _| range.contains(time.date_naive()));
+        self.manual.retain(|date| range.contains(*date));
+        self.excluded.retain(|date, _| range.contains(*date));
+    }
+
+    /// Есть ли за день срезы основного слоя.
+    pub fn has_day(&self, date: NaiveDate) -> bool {
+        self.ru.contains_key(&date)
+    }
+
+    /// Число дней, за которые есть срезы занятой территории.
+    pub fn day_count(&self) -> usize {
+        self.ru.len()
+    }
+
+    /// Дни с точками ручного ввода.
+    pub const fn manual_days(&self) -> &BTreeSet<NaiveDate> {
+        &self.manual
+    }
+
+    /// Помечает дни, которые не должны попадать в обучающий ряд; дни без срезов пропускаются.
+    pub fn exclude_from_training(&mut self, days: BTreeMap<NaiveDate, String>) {
+        self.excluded = days
+            .into_iter()
+            .filter(|(date, _)| self.has_day(*date))
+            .collect();
+    }
+
+    /// Исключённые из обучения дни с причинами.
+    pub const fn excluded_days(&self) -> &BTreeMap<NaiveDate, String> {
+        &self.excluded
+    }
+
+    /// Бакеты без исключённых дней: при построении ряда они заполняются интерполяцией.
+    pub fn for_training(&self) -> Cow<'_, Self> {
+        if self.excluded.is_empty() {
+            return Cow::Borrowed(self);
+        }
+        let mut training = self.clone();
+        for buckets in [
+            &mut training.ru,
+            &mut training.ua,
+            &mut training.unspecified,
+            &mut training.pre_war,
+        ] {
+            buckets.retain(|date, _| !self.excluded.contains_key(date));
+        }
+        training
+            .snapshots
+            .retain(|time, _| !self.excluded.contains_key(&time.date_naive()));
+        training
+            .manual
+            .retain(|date| !self.excluded.contains_key(date));
+        training.excluded.clear();
+        Cow::Owned(training)
+    }
+}
+
+/// Способ заполнения дней без срезов.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InterpolationStrategy {
+    /// Линейная интерполяция между соседними срезами.
+    #[default]
+    Linear,
+    /// Удержание последнего известного значения.
+    Previous,
+    /// Натуральный кубический сплайн.
+    Spline,
+    /// Без заполнения: значения как у `Linear` (ряд должен оставаться непрерывным),
+    /// но потребители скрывают дни с `measured == false` и рвут линию.
+    None,
+}
+
+impl InterpolationStrategy {
+    /// Имя стратегии в конфигурации.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Previous => "previous",
+            Self::Spline => "spline",
+            Self::None => "none",
+        }
+    }
+}
+
+/// Правила построения дневного ряда для приложения «Методология».
+pub fn methodology(model: InterpolationStrategy, chart: InterpolationStrategy) -> Section {
+    Section {
+        title: "Данные и дневной ряд",
+        summary: "Срезы площадей из API DeepState раскладываются по суткам UTC; занятая \
+                  территория — слой занятого за вычетом освобождённых участков.",
+        parameters: vec![
+            Parameter::new(
+                "occupied_layer",
+                AREA_TYPE_OCCUPIED,
+                "Слой территории, занятой после 24.02.2022",
+            ),
+            Parameter::new(
+                "subtracted_layer",
+                format!("{AREA_TYPE_OTHER} цвета {UA_HASH} и ручные точки"),
+                "Освобождённые участки внутри занятого слоя; вычитаются из него",
+            ),
+            Parameter::new(
+                "pre_war_layer",
+                AREA_TYPE_OCCUPIED_PRE_WAR,
+                "Занятое до 24.02.2022; в ряд не входит",
+            ),
+            Parameter::new(
+                "unspecified_layer",
+                AREA_TYPE_UNSPECIFIED,
+                "Серая зона; отдельный ряд на графике",
+            ),
+            Parameter::new(
+                "daily_bucket",
+                "сутки UTC, среднее",
+                "Срезы одних суток усредняются в одну точку слоя",
+            ),
+            Parameter::new(
+                "manual_points",
+                MANUAL_HASH,
+                "Значение hash у точек, сверенных вручную (rua append)",
+            ),
+            Parameter::new(
+                "interpolation_model",
+                model.as_str(),
+                "Заполнение дней без срезов в ряду для модели",
+            ),
+            Parameter::new(
+                "interpolation_chart",
+                chart.as_str(),
+                "Заполнение дней без срезов на графике; none рвёт линию",
+            ),
+        ],
+    }
+}
+
+/// Шаг ряда, на котором обучается модель и строится прогноз.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    #[default]
+    Day,
+    /// Средние за 7-дневные блоки: на длинных горизонтах дневные точки — шум.
+    Week,
+}
+
+impl Granularity {
+    pub const fn step_days(self) -> usize {
+        match self {
+            Self::Day => 1,
+            Self::Week => 7,
+        }
+    }
+
+    /// Число шагов, покрывающее `horizon_days` дней.
+    pub const fn steps(self, horizon_days: usize) -> usize {
+        horizon_days.div_ceil(self.step_days())
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+        }
+    }
+
+    /// Переводит непрерывный дневной ряд в ряд этого шага. Недельные блоки выровнены
+    /// по последнему дню, чтобы свежие данные не отбрасывались; неполный первый блок
+    /// пропускается. Точка блока — его средний день.
+    pub fn aggregate(self, dates: Vec<NaiveDate>, values: Vec<f64>) -> (Vec<NaiveDate>, Vec<f64>) {
+        let step = self.step_days();
+        if step == 1 {
+            return (dates, values);
+        }
+        let skip = dates.len() % step;
+        dates[skip..]
+            .chunks(step)
+            .zip(values[skip..].chunks(step))
+            .map(|(days, block)| {
+                let mean = block.iter().sum::<f64>() / index_to_f64(step);
+                (days[step / 2], mean)
+            })
+            .unzip()
+    }
+}
+
+/// Непрерывные дневные ряды для занятых территорий и слоя `unspecified`.
+#[derive(Clone, Debug)]
+pub struct OccupiedUnspecifiedSeries {
+    pub dates: Vec<NaiveDate>,
+    pub occupied: Vec<f64>,
+    pub unspecified: Vec<f64>,
+    /// `true` для дней с исходным срезом, `false` для интерполированных.
+    pub measured: Vec<bool>,
+}
+
+/// Дневные ряды по категориям контроля на общей шкале дат (км²).
+#[derive(Clone, Debug)]
+pub struct CategorySeries {
+    pub dates: Vec<NaiveDate>,
+    /// Занято до 24.02.2022.
+    pub occupied_pre_war: Vec<f64>,
+    /// Занято после 24.02.2022 (основной ряд).
+    pub occupied: Vec<f64>,
+    /// Освобождено: слой `other_territories`, вычитаемый из занятого.
+    pub liberated: Vec<f64>,
+    /// Серая зона.
+    pub unspecified: Vec<f64>,
+}
+
+/// Понедельные приобретения и потери площади (км²); неделя задаётся своим понедельником.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeeklyFlows {
+    pub weeks: Vec<NaiveDate>,
+    /// Сумма приростов: рост слоя занятого и сокращение слоя освобождённого (≥ 0).
+    pub gained: Vec<f64>,
+    /// Сумма потерь: сокращение слоя занятого и рост слоя освобождённого (≤ 0).
+    pub lost: Vec<f64>,
+}
+
+/// Непрерывный отрезок календарных дней без срезов в источнике.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gap {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Gap {
+    pub fn days(self) -> i64 {
+        (self.end - self.start).num_days() + 1
+    }
+}
+
+/// Читает CSV и раскладывает значения по дневным бакетам.
+pub fn load_area_buckets(csv_path: &Path) -> Result<AreaBuckets, Box<dyn Error>> {
+    buckets_from_reader(csv::Reader::from_path(csv_path)?, None)
+}
+
+/// То же, что [`load_area_buckets`], но без срезов новее `as_of`. Строки отбрасываются до
+/// дневного усреднения, поэтому день `as_of` усредняется только по уже известным срезам.
+pub fn load_area_buckets_as_of(
+    csv_path: &Path,
+    as_of: DateTime<Utc>,
+) -> Result<AreaBuckets, Box<dyn Error>> {
+    buckets_from_reader(csv::Reader::from_path(csv_path)?, Some(as_of))
+}
+
+/// То же, что [`load_area_buckets`], но через двоичный кэш рядом с CSV (см. `series/cache.rs`):
+/// если хэш CSV совпадает с записанным в кэше, разбор пропускается, иначе кэш перезаписывается.
+pub fn load_area_buckets_cached(csv_path: &Path) -> Result<AreaBuckets, Box<dyn Error>> {
+    let bytes = std::fs::read(csv_path)?;
+    let digest = sha256_hex(&bytes);
+    let cache_path = cache::cache_path(csv_path);
+    if let Some(buckets) = cache::read(&cache_path, &digest) {
+        tracing::debug!(cache = %cache_path.display(), "Loaded daily buckets from the series cache");
+        return Ok(buckets);
+    }
+    let buckets = buckets_from_reader(csv::Reader::from_reader(bytes.as_slice()), None)?;
+    if let Err(err) = cache::write(&cache_path, &digest, &buckets) {
+        tracing::warn!(error = %err, "Failed to write the series cache");
+    }
+    Ok(buckets)
+}
+
+fn buckets_from_reader<R: std::io::Read>(
+    mut reader: csv::Reader<R>,
+    as_of: Option<DateTime<Utc>>,
+) -> Result<AreaBuckets, Box<dyn Error>> {
+    let mut hint = None;
+    reader
+        .deserialize::<CsvRow>()
+        .try_fold(AreaBuckets::default(), |mut acc, row| {
+            let row = row?;
+            let datetime = parse_time_index_with_hint(&row.time_index, &mut hint)
+                .map_err(|err| format!("failed to parse time_index '{}': {err}", row.time_index))?;
+            if as_of.is_some_and(|as_of| datetime > as_of) {
+                return Ok(acc);
+            }
+            acc.ingest(&row, datetime);
+            Ok::<_, Box<dyn Error>>(acc)
+        })
+}
+
+/// Последний срез из строк источника, ещё не сохранённых в CSV (по тем же правилам, что и история).
+pub fn snapshot_from_areas(areas: &[Area]) -> Option<Snapshot> {
+    let mut buckets = AreaBuckets::default();
+    for area in areas {
+        buckets.ingest_value(
+            area_kind(&area.area_type),
+            hash_kind(&area.hash),
+            area.area,
+            area.time_index,
+        );
+    }
+    buckets.snapshots().pop()
+}
+
+/// Разбирает `time_index` в любом из поддерживаемых форматов.
+pub fn parse_time_index(raw: &str) -> Result<DateTime<Utc>, String> {
+    parse_time_index_with_hint(raw, &mut None)
+}
+
+/// Момент `--as-of`: время в любом формате `time_index` или дата `YYYY-MM-DD` — тогда конец
+/// этих суток UTC, то есть все срезы дня.
+pub fn parse_as_of(raw: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(raw.trim(), DATE_FORMAT) {
+        return date
+            .and_hms_micro_opt(23, 59, 59, 999_999)
+            .map(|end| end.and_utc())
+            .ok_or_else(|| format!("invalid date '{raw}'"));
+    }
+    parse_time_index(raw)
+        .map_err(|err| format!("'{raw}': {err}; expected YYYY-MM-DD or a timestamp"))
+}
+
+/// Возвращает самый поздний `time_index` из CSV (`None`, если строк нет).
+pub fn latest_time_index(csv_path: &Path) -> Result<Option<DateTime<Utc>>, Box<dyn Error>> {
+    let mut reader = csv::Reader::from_path(csv_path)?;
+    let mut hint = None;
+    reader
+        .deserialize::<CsvRow>()
+        .try_fold(None, |latest: Option<DateTime<Utc>>, row| {
+            let row = row?;
+            let datetime = parse_time_index_with_hint(&row.time_index, &mut hint)
+                .map_err(|err| format!("failed to parse time_index '{}': {err}", row.time_index))?;
+            Ok::<_, Box<dyn Error>>(Some(latest.map_or(datetime, |latest| latest.max(datetime))))
+        })
+}
+
+/// Строит ряд занятых территорий в разрешении исходных срезов (без дневного усреднения).
+pub fn build_snapshot_series(buckets: &AreaBuckets) -> (Vec<DateTime<Utc>>, Vec<f64>) {
+    buckets
+        .snapshots()
+        .into_iter()
+        .map(|snapshot| (snapshot.time, snapshot.occupied))
+        .unzip()
+}
+
+/// Строит непрерывный ряд занятых территорий, вычитая и интерполируя RU/UA.
+pub fn build_occupied_series(
+    buckets: &AreaBuckets,
+) -> Result<(Vec<NaiveDate>, Vec<f64>), Box<dyn Error>> {
+    build_occupied_series_with(buckets, InterpolationStrategy::default())
+}
+
+/// То же, что [`build_occupied_series`], с выбранной стратегией заполнения пропусков.
+pub fn build_occupied_series_with(
+    buckets: &AreaBuckets,
+    strategy: InterpolationStrategy,
+) -> Result<(Vec<NaiveDate>, Vec<f64>), Box<dyn Error>> {
+    let series = build_occupied_and_unspecified_series_with(buckets, strategy)?;
+    Ok((series.dates, series.occupied))
+}
+
+/// Строит непрерывные ряды `occupied` и `unspecified` на общей шкале дат.
+pub fn build_occupied_and_unspecified_series(
+    buckets: &AreaBuckets,
+) -> Result<OccupiedUnspecifiedSeries, Box<dyn Error>> {
+    build_occupied_and_unspecified_series_with(buckets, InterpolationStrategy::default())
+}
+
+/// То же, что [`build_occupied_and_unspecified_series`], с выбранной стратегией.
+pub fn build_occupied_and_unspecified_series_with(
+    buckets: &AreaBuckets,
+    strategy: InterpolationStrategy,
+) -> Result<OccupiedUnspecifiedSeries, Box<dyn Error>> {
+    let first_date = buckets
+        .ru
+        .keys()
+        .chain(buckets.ua.keys())
+        .chain(buckets.unspecified.keys())
+        .min()
+        .copied()
+        .ok_or_else(|| ERROR_NO_DATA.to_string())?;
+    let last_date = buckets
+        .ru
+        .keys()
+        .chain(buckets.ua.keys())
+        .chain(buckets.unspecified.keys())
+        .max()
+        .copied()
+        .ok_or_else(|| ERROR_NO_DATA.to_string())?;
+
+    let span_days = (last_date - first_date).num_days();
+    if span_days < 0 {
+        tracing::warn!(
+            first_date = %first_date,
+            last_date = %last_date,
+            span_days,
+            "Negative date span while building series; returning error",
+        );
+        return Err("negative date span".into());
+    }
+    let dates: Vec<_> = (0..=span_days)
+        .map(|offset| first_date + Duration::days(offset))
+        .collect();
+
+    let ru_values = interpolate_series(&dates, &buckets.ru, strategy);
+    let ua_values = interpolate_series(&dates, &buckets.ua, strategy);
+    let unspecified_values = interpolate_series(&dates, &buckets.unspecified, strategy);
+    let occupied_area = ru_values
+        .iter()
+        .zip(ua_values)
+        .map(|(ru, ua)| ru - ua)
+        .collect();
+    let measured = dates
+        .iter()
+        .map(|date| {
+            [&buckets.ru, &buckets.ua, &buckets.unspecified]
+                .iter()
+                .any(|source| source.contains_key(date))
+        })
+        .collect();
+
+    Ok(OccupiedUnspecifiedSeries {
+        dates,
+        occupied: occupied_area,
+        unspecified: unspecified_values,
+        measured,
+    })
+}
+
+/// Строит ряды всех категорий на шкале дат основного ряда.
+pub fn build_category_series_with(
+    buckets: &AreaBuckets,
+    strategy: InterpolationStrategy,
+) -> Result<CategorySeries, Box<dyn Error>> {
+    let series = build_occupied_and_unspecified_series_with(buckets, strategy)?;
+    let occupied_pre_war = if buckets.pre_war.is_empty() {
+        vec![0.0; series.dates.len()]
+    } else {
+        interpolate_series(&series.dates, &buckets.pre_war, strategy)
+    };
+    let liberated = interpolate_series(&series.dates, &buckets.ua, strategy);
+    Ok(CategorySeries {
+        occupied_pre_war,
+        liberated,
+        occupied: series.occupied,
+        unspecified: series.unspecified,
+        dates: series.dates,
+    })
+}
+
+/// Раскладывает суточное изменение занятой площади на приобретения и потери по знаку
+/// изменения каждого слоя источника и суммирует их по ISO-неделям.
+///
+/// Занятая площадь — это слой занятого за вычетом слоя освобождённого, поэтому изменения слоёв
+/// разделяются независимо. Встречные изменения внутри одного слоя за сутки (для этого нужно
+/// сравнение полигонов) взаимно гасятся и в разложение не попадают.
+pub fn build_weekly_flows(series: &CategorySeries) -> WeeklyFlows {
+    let mut flows = WeeklyFlows {
+        weeks: Vec::new(),
+        gained: Vec::new(),
+        lost: Vec::new(),
+    };
+    for idx in 1..series.dates.len() {
+        let liberated_change = series.liberated[idx] - series.liberated[idx - 1];
+        let layer_change = series.occupied[idx] - series.occupied[idx - 1] + liberated_change;
+        let gained = layer_change.max(0.0) + (-liberated_change).max(0.0);
+        let lost = layer_change.min(0.0) + (-liberated_change).min(0.0);
+
+        let date = series.dates[idx];
+        let week = date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+        if flows.weeks.last() != Some(&week) {
+            flows.weeks.push(week);
+            flows.gained.push(0.0);
+            flows.lost.push(0.0);
+        }
+        if let (Some(total_gained), Some(total_lost)) =
+            (flows.gained.last_mut(), flows.lost.last_mut())
+        {
+            *total_gained += gained;
+            *total_lost += lost;
+        }
+    }
+    flows
+}
+
+/// Находит отрезки дней без срезов внутри ряда (значения на них интерполированы).
+pub fn find_gaps(series: &OccupiedUnspecifiedSeries) -> Vec<Gap> {
+    series
+        .dates
+        .iter()
+        .zip(&series.measured)
+        .chunk_by(|(_, measured)| **measured)
+        .into_iter()
+        .filter(|(measured, _)| !measured)
+        .filter_map(|(_, mut days)| {
+            let (start, _) = days.next()?;
+            let end = days.last().map_or(*start, |(end, _)| *end);
+            Some(Gap { start: *start, end })
+        })
+        .collect()
+}
+
+/// Пишет отчёт о пропусках: начало, конец и длину каждого отрезка без срезов.
+pub fn write_gap_report_csv(gaps: &[Gap], output_csv: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = output_csv.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut writer = csv::Writer::from_path(output_csv)?;
+    writer.write_record(GAP_REPORT_HEADER)?;
+    for gap in gaps {
+        writer.write_record([
+            gap.start.to_string(),
+            gap.end.to_string(),
+            gap.days().to_string(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Пишет CSV полного разрешения: интерполированные дневные точки и все исходные срезы.
+pub fn write_full_resolution_csv(
+    buckets: &AreaBuckets,
+    output_csv: &Path,
+    precision: Precision,
+) -> Result<(), Box<dyn Error>> {
+    let series = build_occupied_and_unspecified_series(buckets)?;
+    if let Some(parent) = output_csv.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut writer = csv::Writer::from_path(output_csv)?;
+    writer.write_record(FULL_RESOLUTION_HEADER)?;
+    let daily = series
+        .dates
+        .iter()
+        .zip(series.occupied.iter().zip(&series.unspecified))
+        .map(|(date, (occupied, unspecified))| {
+            (
+                Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN)),
+                RESOLUTION_DAILY,
+                *occupied,
+                *unspecified,
+            )
+        });
+    let raw = buckets.snapshots().into_iter().map(|snapshot| {
+        (
+            snapshot.time,
+            RESOLUTION_RAW,
+            snapshot.occupied,
+            snapshot.unspecified,
+        )
+    });
+    for (time, resolution, occupied, unspecified) in daily
+        .chain(raw)
+        .sorted_by(|left, right| left.0.cmp(&right.0).then(left.1.cmp(right.1)))
+    {
+        writer.write_record([
+            time.to_string(),
+            resolution.to_string(),
+            precision.area(occupied),
+            precision.area(unspecified),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Пишет дневной ряд доли занятой территории от площади Украины.
+pub fn write_percent_history_csv(
+    buckets: &AreaBuckets,
+    output_csv: &Path,
+    precision: Precision,
+) -> Result<(), Box<dyn Error>> {
+    let series = build_occupied_and_unspecified_series(buckets)?;
+    if let Some(parent) = output_csv.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut writer = csv::Writer::from_path(output_csv)?;
+    writer.write_record(PERCENT_HISTORY_HEADER)?;
+    for (date, occupied) in series.dates.iter().zip(&series.occupied) {
+        writer.write_record([
+            date.format(DATE_FORMAT).to_string(),
+            precision.area(*occupied),
+            precision.percent(ukraine_percent(*occupied)),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Парсит разные форматы времени из API и приводит их к UTC.
+fn parse_time_index_with_hint(
+    raw: &str,
+    hint: &mut Option<TimeFormatHint>,
+) -> Result<DateTime<Utc>, String> {
+    let trimmed = raw.trim();
+
+    if trimmed.is_empty() {
+        return Err(ERROR_EMPTY_TIME_INDEX.into());
+    }
+
+    if let Some(hint) = *hint
+        && let Some(parsed) = hint.parse(trimmed)
+    {
+        return Ok(parsed);
+    }
+
+    if let Ok(dt) = DateTime::parse_from_str(trimmed, TIME_FORMAT_TZ) {
+        *hint = Some(TimeFormatHint::Tz);
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    if let Ok(dt) = DateTime::parse_from_str(trimmed, TIME_FORMAT_OFFSET) {
+        *hint = Some(TimeFormatHint::Offset);
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(trimmed) {
+        *hint = Some(TimeFormatHint::Rfc3339);
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    if let Some(stripped) = trimmed.strip_suffix(UTC_SUFFIX)
+        && let Ok(naive) = NaiveDateTime::parse_from_str(stripped.trim_end(), TIME_FORMAT_NAIVE)
+    {
+        *hint = Some(TimeFormatHint::NaiveUtcSuffix);
+        return Ok(Utc.from_utc_datetime(&naive));
+    }
+
+    if let Ok(naive) = NaiveDateTime::parse_from_str(trimmed, TIME_FORMAT_NAIVE) {
+        *hint = Some(TimeFormatHint::Naive);
+        return Ok(Utc.from_utc_datetime(&naive));
+    }
+
+    Err(ERROR_UNRECOGNIZED_TIME.into())
+}
+
+/// Заполняет пропуски по дням выбранной стратегией и делает ряд непрерывным.
+fn interpolate_series(
+    dates: &[NaiveDate],
+    source: &DailyBuckets,
+    strategy: InterpolationStrategy,
+) -> Vec<f64> {
+    match strategy {
+        InterpolationStrategy::Linear | InterpolationStrategy::None => {
+            interpolate_linear(dates, source)
+        }
+        InterpolationStrategy::Previous => interpolate_previous(dates, source),
+        InterpolationStrategy::Spline => interpolate_spline(dates, source),
+    }
+}
+
+/// Удерживает последнее известное значение до следующего среза.
+fn interpolate_previous(dates: &[NaiveDate], source: &DailyBuckets) -> Vec<f64> {
+    let first_value = dates
+        .iter()
+        .find_map(|date| source.get(date).and_then(DailyAccumulator::mean))
+        .unwrap_or_default();
+    let mut held = first_value;
+    dates
+        .iter()
+        .map(|date| {
+            if let Some(value) = source.get(date).and_then(DailyAccumulator::mean) {
+                held = value;
+            }
+            held
+        })
+        .collect()
+}
+
+/// Натуральный кубический сплайн по известным точкам; края удерживаются как в линейном режиме.
+fn interpolate_spline(dates: &[NaiveDate], source: &DailyBuckets) -> Vec<f64> {
+    let knots = dates
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, date)| {
+            source
+                .get(date)
+                .and_then(DailyAccumulator::mean)
+                .map(|value| (idx, value))
+        })
+        .collect::<Vec<_>>();
+    let mut values = interpolate_linear(dates, source);
+    if knots.len() < 3 {
+        return values;
+    }
+
+    let knot_x = knots
+        .iter()
+        .map(|(idx, _)| index_to_f64(*idx))
+        .collect::<Vec<_>>();
+    let knot_y = knots.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+    let second = natural_spline_second_derivatives(&knot_x, &knot_y);
+    for (segment, pair) in knots.windows(2).enumerate() {
+        let (start, end) = (pair[0].0, pair[1].0);
+        let width = knot_x[segment + 1] - knot_x[segment];
+        let (m_left, m_right) = (second[segment], second[segment + 1]);
+        let (y_left, y_right) = (knot_y[segment], knot_y[segment + 1]);
+        for (idx, slot) in values.iter_mut().enumerate().take(end).skip(start + 1) {
+            let position = index_to_f64(idx);
+            let left = knot_x[segment + 1] - position;
+            let right = position - knot_x[segment];
+            let cubic = m_left.mul_add(left.powi(3), m_right * right.powi(3)) / (6.0 * width);
+            let linear = (y_left / width - m_left * width / 6.0)
+                .mul_add(left, (y_right / width - m_right * width / 6.0) * right);
+            *slot = cubic + linear;
+        }
+    }
+    values
+}
+
+/// Решает трёхдиагональную систему для вторых производных натурального сплайна.
+fn natural_spline_second_derivatives(knot_x: &[f64], knot_y: &[f64]) -> Vec<f64> {
+    let count = knot_x.len();
+    let mut second = vec![0.0; count];
+    let mut diag = vec![0.0; count];
+    let mut rhs = vec![0.0; count];
+    for idx in 1..count - 1 {
+        let h_prev = knot_x[idx] - knot_x[idx - 1];
+        let h_next = knot_x[idx + 1] - knot_x[idx];
+        let mut pivot = 2.0 * (h_prev + h_next);
+        let mut right = 6.0
+            * ((knot_y[idx + 1] - knot_y[idx]) / h_next - (knot_y[idx] - knot_y[idx - 1]) / h_prev);
+        if idx > 1 {
+            let factor = h_prev / diag[idx - 1];
+            pivot -= factor * h_prev;
+            right -= factor * rhs[idx - 1];
+        }
+        diag[idx] = pivot;
+        rhs[idx] = right;
+    }
+    for idx in (1..count - 1).rev() {
+        let h_next = knot_x[idx + 1] - knot_x[idx];
+        second[idx] = h_next.mul_add(-second[idx + 1], rhs[idx]) / diag[idx];
+    }
+    second
+}
+
+fn index_to_f64(idx: usize) -> f64 {
+    f64::from(u32::try_from(idx).unwrap_or(u32::MAX))
+}
+
+/// Линейно интерполирует пропуски по дням и делает ряд непрерывным.
+fn interpolate_linear(dates: &[NaiveDate], source: &DailyBuckets) -> Vec<f64> {
+    let mut values: Vec<Option<f64>> = dates
+        .iter()
+        .map(|date| source.get(date).and_then(DailyAccumulator::mean))
+        .collect();
+
+    // Заполняем пропуски интерполяцией между известными точками.
+    let mut last_known = None;
+    for idx in 0..values.len() {
+        if values[idx].is_some() {
+            if let Some(start) = last_known {
+                if let (Some(start_val), Some(end_val)) = (values[start], values[idx]) {
+                    let gap = idx - start;
+                    if gap > 1 {
+                        for (offset, slot) in ((start + 1)..idx).enumerate() {
+                            let numerator: f64 = u32::try_from(offset + 1).map_or_else(
+                                |_| {
+                                    tracing::warn!(
+                                        offset = offset + 1,
+                                        "Interpolation offset exceeds u32::MAX; clamping ratio",
+                                    );
+                                    f64::from(u32::MAX)
+                                },
+                                f64::from,
+                            );
+                            let denominator: f64 = u32::try_from(gap).map_or_else(
+                                |_| {
+                                    tracing::warn!(
+                                        gap,
+                                        "Interpolation gap exceeds u32::MAX; clamping ratio",
+                                    );
+                                    f64::from(u32::MAX)
+                                },
+                                f64::from,
+                            );
+                            let ratio = numerator / denominator;
+                            values[slot] =
+                                Some(f64::mul_add(end_val - start_val, ratio, start_val));
+                        }
+                    }
+                }
+            } else {
+                // В начале ряда тянем первое известное значение назад.
+                let first_value = values[idx];
+                for placeholder in values.iter_mut().take(idx) {
+                    *placeholder = first_value;
+                }
+            }
+            last_known = Some(idx);
+        }
+    }
+
+    if let Some(last) = last_known {
+        let tail_value = values[last];
+        for value in values.iter_mut().skip(last + 1) {
+            *value = tail_value;
+        }
+    }
+
+    let fallback = values.iter().flatten().copied().next().unwrap_or_default();
+    values
+        .into_iter()
+        .map(|value| value.unwrap_or(fallback))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CategorySeries, DateRange, Gap, Granularity, InterpolationStrategy,
+        build_occupied_and_unspecified_series, build_occupied_series, build_occupied_series_with,
+        build_weekly_flows, find_gaps, load_area_buckets, load_area_buckets_as_of, parse_as_of,
+        write_percent_history_csv,
+    };
+    use chrono::{Duration, NaiveDate};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+    const EPS: f64 = 1e-9;
+
+    fn write_temp_csv(contents: &str) -> PathBuf {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before UNIX_EPOCH")
+            .as_nanos();
+        let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let mut path = std::env::temp_dir();
+        path.push(format!("rua_series_test_{timestamp}_{counter}.csv"));
+        std::fs::write(&path, contents).expect("failed to write test csv");
+        path
+    }
+
+    fn remove_temp_csv(path: &Path) {
+        let _ = std::fs::remove_file(path);
+    }
+
+    fn assert_vec_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(
+            actual.len(),
+            expected.len(),
+            "series length mismatch: actual={} expected={}",
+            actual.len(),
+            expected.len()
+        );
+        for (idx, (actual_value, expected_value)) in actual.iter().zip(expected).enumerate() {
+            assert!(
+                (actual_value - expected_value).abs() < EPS,
+                "mismatch at index {idx}: actual={actual_value}, expected={expected_value}"
+            );
+        }
+    }
+
+    #[test]
+    fn load_area_buckets_parses_unspecified_independent_of_hash() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-02-01 00:00:00 UTC,#01579b,12.5,0.0,unspecified\n\
+2024-02-01 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-02-01 00:00:00 UTC,#01579b,20.0,0.0,other_territories\n";
+        let path = write_temp_csv(csv);
+        let buckets = load_area_buckets(&path).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+
+        let date = NaiveDate::from_ymd_opt(2024, 2, 1).expect("valid date");
+        let unspecified = buckets
+            .unspecified
+            .get(&date)
+            .and_then(super::DailyAccumulator::mean)
+            .expect("missing unspecified bucket");
+        assert!(
+            (unspecified - 12.5).abs() < EPS,
+            "unexpected unspecified value: {unspecified}"
+        );
+    }
+
+    #[test]
+    fn build_occupied_series_ignores_unspecified_values() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-03-01 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-03-01 00:00:00 UTC,#01579b,20.0,0.0,other_territories\n\
+2024-03-01 00:00:00 UTC,#bcaaa4,30.0,0.0,unspecified\n\
+2024-03-02 00:00:00 UTC,#a52714,110.0,0.0,occupied_after_24_02_2022\n\
+2024-03-02 00:00:00 UTC,#01579b,25.0,0.0,other_territories\n\
+2024-03-02 00:00:00 UTC,#bcaaa4,40.0,0.0,unspecified\n";
+        let path = write_temp_csv(csv);
+        let buckets = load_area_buckets(&path).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+
+        let (dates, occupied) = build_occupied_series(&buckets).expect("failed to build series");
+        assert_eq!(
+            dates,
+            vec![
+                NaiveDate::from_ymd_opt(2024, 3, 1).expect("valid date"),
+                NaiveDate::from_ymd_opt(2024, 3, 2).expect("valid date"),
+            ]
+        );
+        assert_vec_close(&occupied, &[80.0, 85.0]);
+    }
+
+    #[test]
+    fn build_occupied_and_unspecified_series_uses_common_interpolated_dates() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-04-01 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-04-01 00:00:00 UTC,#01579b,20.0,0.0,other_territories\n\
+2024-04-01 00:00:00 UTC,#bcaaa4,10.0,0.0,unspecified\n\
+2024-04-03 00:00:00 UTC,#a52714,160.0,0.0,occupied_after_24_02_2022\n\
+2024-04-03 00:00:00 UTC,#01579b,50.0,0.0,other_territories\n\
+2024-04-03 00:00:00 UTC,#bcaaa4,40.0,0.0,unspecified\n";
+        let path = write_temp_csv(csv);
+        let buckets = load_area_buckets(&path).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+
+        let series = build_occupied_and_unspecified_series(&buckets)
+            .expect("failed to build occupied/unspecified series");
+        assert_eq!(
+            series.dates,
+            vec![
+                NaiveDate::from_ymd_opt(2024, 4, 1).expect("valid date"),
+                NaiveDate::from_ymd_opt(2024, 4, 2).expect("valid date"),
+                NaiveDate::from_ymd_opt(2024, 4, 3).expect("valid date"),
+            ]
+        );
+        assert_vec_close(&series.occupied, &[80.0, 95.0, 110.0]);
+        assert_vec_close(&series.unspecified, &[10.0, 25.0, 40.0]);
+
+        let upper = series
+            .occupied
+            .iter()
+            .zip(&series.unspecified)
+            .map(|(occupied, unspecified)| occupied + unspecified)
+            .collect::<Vec<_>>();
+        assert_vec_close(&upper, &[90.0, 120.0, 150.0]);
+    }
+
+    #[test]
+    fn percent_history_csv_divides_the_daily_series_by_ukraine_area() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-04-01 00:00:00 UTC,#a52714,60355.0,0.0,occupied_after_24_02_2022\n\
+2024-04-03 00:00:00 UTC,#a52714,120710.0,0.0,occupied_after_24_02_2022\n";
+        let path = write_temp_csv(csv);
+        let buckets = load_area_buckets(&path).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+
+        let output = path.with_extension("percent.csv");
+        write_percent_history_csv(&buckets, &output, crate::constants::Precision::default())
+            .expect("failed to write percent history");
+        let written = std::fs::read_to_string(&output).expect("failed to read percent history");
+        remove_temp_csv(&output);
+        assert_eq!(
+            written,
+            "date,occupied_km2,ukraine_percent\n\
+2024-04-01,60355.00,10.0000\n\
+2024-04-02,90532.50,15.0000\n\
+2024-04-03,120710.00,20.0000\n"
+        );
+    }
+
+    #[test]
+    fn retain_range_trims_series_to_requested_dates() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-06-01 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-06-02 00:00:00 UTC,#a52714,110.0,0.0,occupied_after_24_02_2022\n\
+2024-06-03 00:00:00 UTC,#a52714,120.0,0.0,occupied_after_24_02_2022\n\
+2024-06-04 00:00:00 UTC,#a52714,130.0,0.0,occupied_after_24_02_2022\n";
+        let path = write_temp_csv(csv);
+        let mut buckets = load_area_buckets(&path).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+
+        let range = DateRange {
+            from: NaiveDate::from_ymd_opt(2024, 6, 2),
+            to: NaiveDate::from_ymd_opt(2024, 6, 3),
+            as_of: None,
+        };
+        assert_eq!(range.to_string(), "2024-06-02..2024-06-03");
+        buckets.retain_range(range);
+        let (dates, occupied) = build_occupied_series(&buckets).expect("failed to build series");
+        assert_eq!(dates.first().copied(), range.from);
+        assert_eq!(dates.last().copied(), range.to);
+        assert_vec_close(&occupied, &[110.0, 120.0]);
+    }
+
+    #[test]
+    fn as_of_drops_later_snapshots_before_daily_averaging() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-06-01 12:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-06-02 06:00:00 UTC,#a52714,110.0,0.0,occupied_after_24_02_2022\n\
+2024-06-02 18:00:00 UTC,#a52714,130.0,0.0,occupied_after_24_02_2022\n\
+2024-06-03 06:00:00 UTC,#a52714,140.0,0.0,occupied_after_24_02_2022\n";
+        let path = write_temp_csv(csv);
+        let as_of = parse_as_of("2024-06-02 12:00:00").expect("timestamp should parse");
+        let mut buckets =
+            load_area_buckets_as_of(&path, as_of).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+
+        let range = DateRange {
+            as_of: Some(as_of),
+            ..DateRange::default()
+        };
+        buckets.retain_range(range);
+        let (dates, occupied) = build_occupied_series(&buckets).expect("failed to build series");
+        assert_eq!(dates.last().copied(), NaiveDate::from_ymd_opt(2024, 6, 2));
+        // Вечерний срез 2 июня ещё не был известен.
+        assert_vec_close(&occupied, &[100.0, 110.0]);
+        assert_eq!(
+            parse_as_of("2024-06-02")
+                .expect("date should parse")
+                .to_rfc3339(),
+            "2024-06-02T23:59:59.999999+00:00"
+        );
+        assert!(!range.contains(NaiveDate::from_ymd_opt(2024, 6, 3).expect("valid date")));
+        assert!(parse_as_of("yesterday").is_err());
+    }
+
+    #[test]
+    fn snapshots_keep_intra_day_observations() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-07-01 06:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-07-01 06:00:00 UTC,#01579b,20.0,0.0,other_territories\n\
+2024-07-01 18:00:00 UTC,#a52714,110.0,0.0,occupied_after_24_02_2022\n\
+2024-07-01 18:00:00 UTC,#01579b,20.0,0.0,other_territories\n";
+        let path = write_temp_csv(csv);
+        let buckets = load_area_buckets(&path).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+
+        let snapshots = buckets.snapshots();
+        assert_eq!(snapshots.len(), 2);
+        assert_vec_close(
+            &snapshots.iter().map(|s| s.occupied).collect::<Vec<_>>(),
+            &[80.0, 90.0],
+        );
+        let (_, daily) = build_occupied_series(&buckets).expect("failed to build series");
+        assert_vec_close(&daily, &[85.0]);
+    }
+
+    #[test]
+    fn find_gaps_reports_missing_calendar_days() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-08-01 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-08-02 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-08-05 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-08-07 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n";
+        let path = write_temp_csv(csv);
+        let buckets = load_area_buckets(&path).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+
+        let series = build_occupied_and_unspecified_series(&buckets).expect("series");
+        let date = |day| NaiveDate::from_ymd_opt(2024, 8, day).expect("valid date");
+        let gaps = find_gaps(&series);
+        assert_eq!(
+            gaps,
+            vec![
+                Gap {
+                    start: date(3),
+                    end: date(4)
+                },
+                Gap {
+                    start: date(6),
+                    end: date(6)
+                },
+            ]
+        );
+        assert_eq!(gaps[0].days(), 2);
+        assert_eq!(gaps[1].days(), 1);
+    }
+
+    #[test]
+    fn weekly_flows_split_layer_changes_by_sign() {
+        let start = NaiveDate::from_ymd_opt(2024, 9, 1).expect("valid date");
+        let dates = (0..9)
+            .map(|day| start + Duration::days(day))
+            .collect::<Vec<_>>();
+        // Слой занятого: 100, 110, 105, ...; слой освобождённого растёт на 3 км² в среду.
+        let layer = [
+            100.0, 110.0, 105.0, 105.0, 120.0, 120.0, 120.0, 120.0, 118.0,
+        ];
+        let liberated = [0.0, 0.0, 0.0, 3.0, 3.0, 3.0, 3.0, 3.0, 1.0];
+        let series = CategorySeries {
+            dates,
+            occupied_pre_war: vec![0.0; 9],
+            occupied: layer
+                .iter()
+                .zip(&liberated)
+                .map(|(layer, liberated)| layer - liberated)
+                .collect(),
+            liberated: liberated.to_vec(),
+            unspecified: vec![0.0; 9],
+        };
+
+        let flows = build_weekly_flows(&series);
+
+        // 2024-09-01 — воскресенье: оно лишь задаёт базу для изменения понедельника.
+        assert_eq!(
+            flows.weeks,
+            vec![
+                NaiveDate::from_ymd_opt(2024, 9, 2).expect("valid date"),
+                NaiveDate::from_ymd_opt(2024, 9, 9).expect("valid date"),
+            ]
+        );
+        assert_vec_close(&flows.gained, &[25.0, 2.0]);
+        assert_vec_close(&flows.lost, &[-8.0, -2.0]);
+    }
+
+    #[test]
+    fn interpolation_strategies_fill_gaps_differently() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-09-01 00:00:00 UTC,#a52714,0.0,0.0,occupied_after_24_02_2022\n\
+2024-09-02 00:00:00 UTC,#a52714,10.0,0.0,occupied_after_24_02_2022\n\
+2024-09-04 00:00:00 UTC,#a52714,30.0,0.0,occupied_after_24_02_2022\n\
+2024-09-05 00:00:00 UTC,#a52714,40.0,0.0,occupied_after_24_02_2022\n";
+        let path = write_temp_csv(csv);
+        let buckets = load_area_buckets(&path).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+
+        let build = |strategy| {
+            build_occupied_series_with(&buckets, strategy)
+                .expect("failed to build series")
+                .1
+        };
+        assert_vec_close(
+            &build(InterpolationStrategy::Linear),
+            &[0.0, 10.0, 20.0, 30.0, 40.0],
+        );
+        assert_vec_close(
+            &build(InterpolationStrategy::Previous),
+            &[0.0, 10.0, 10.0, 30.0, 40.0],
+        );
+        // Точки лежат на прямой, поэтому сплайн совпадает с линейной интерполяцией.
+        assert_vec_close(
+            &build(InterpolationStrategy::Spline),
+            &[0.0, 10.0, 20.0, 30.0, 40.0],
+        );
+    }
+
+    #[test]
+    fn weekly_granularity_averages_blocks_ending_on_latest_day() {
+        let start = NaiveDate::from_ymd_opt(2024, 3, 1).expect("valid date");
+        let dates = (0..16)
+            .map(|day| start + Duration::days(day))
+            .collect::<Vec<_>>();
+        let values = (0..16).map(f64::from).collect::<Vec<_>>();
+
+        let (weeks, means) = Granularity::Week.aggregate(dates.clone(), values);
+
+        // Первые 2 дня не образуют полного блока; блоки — дни 2..9 и 9..16.
+        assert_eq!(weeks, vec![dates[5], dates[12]]);
+        assert_eq!(means, vec![5.0, 12.0]);
+        assert_eq!(Granularity::Week.steps(90), 13);
+    }
+}
@@ -0,0 +1,258 @@
+//! Двоичный кэш дневных бакетов рядом с CSV истории: `history.csv` → `.history.csv.rua-series`.
+//!
+//! Разбор CSV с временными метками — самая медленная часть сборки отчёта, а между запусками
+//! история обычно не меняется. Кэш хранит дневные суммы и счётчики по каждой категории,
+//! срезы и дни ручного ввода по столбцам (сначала все даты, потом все значения) и действителен,
+//! пока совпадают SHA-256 исходного CSV, версия rua и версия формата.
+//!
+//! Формат (little-endian): `MAGIC`, хэш CSV (64 hex-символа), длина и текст версии rua (u16),
+//! затем для `ru`, `ua`, `unspecified`, `pre_war` — число дней (u64), даты (i32, дни от н. э.),
+//! суммы (f64) и счётчики (u32); срезы — число (u64), время (i64, мкс Unix), суммы и счётчики
+//! для `ru`, `ua`, `unspecified`; дни ручного ввода — число (u64) и даты.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use super::{AreaBuckets, DailyAccumulator, DailyBuckets, SnapshotAccumulator};
+
+/// Окончание имени кэша; такие файлы не публикуются вместе с сайтом.
+pub const CACHE_SUFFIX: &str = ".rua-series";
+const MAGIC: &[u8; 8] = b"RUASER01";
+const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Путь кэша для CSV: скрытый файл в том же каталоге.
+pub fn cache_path(csv_path: &Path) -> PathBuf {
+    let name = csv_path
+        .file_name()
+        .map_or_else(|| "history.csv".into(), |name| name.to_string_lossy());
+    csv_path.with_file_name(format!(".{name}{CACHE_SUFFIX}"))
+}
+
+/// Бакеты из кэша, если он записан для CSV с хэшем `csv_sha256`.
+pub fn read(path: &Path, csv_sha256: &str) -> Option<AreaBuckets> {
+    let bytes = fs::read(path).ok()?;
+    decode(&bytes, csv_sha256)
+}
+
+/// Сохраняет бакеты в кэш; файл пишется целиком через временный и переименовывается.
+pub fn write(path: &Path, csv_sha256: &str, buckets: &AreaBuckets) -> Result<(), String> {
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, encode(csv_sha256, buckets))
+        .map_err(|err| format!("Failed to write {}: {err}", temporary.display()))?;
+    crate::platform::replace_file(&temporary, path).map_err(|err| {
+        format!(
+            "Failed to move {} to {}: {err}",
+            temporary.display(),
+            path.display()
+        )
+    })
+}
+
+fn encode(csv_sha256: &str, buckets: &AreaBuckets) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(csv_sha256.as_bytes());
+    let version = APP_VERSION.as_bytes();
+    out.extend_from_slice(&u16::try_from(version.len()).unwrap_or(0).to_le_bytes());
+    out.extend_from_slice(version);
+    for daily in [
+        &buckets.ru,
+        &buckets.ua,
+        &buckets.unspecified,
+        &buckets.pre_war,
+    ] {
+        put_len(&mut out, daily.len());
+        for date in daily.keys() {
+            put_date(&mut out, *date);
+        }
+        put_accumulators(&mut out, daily.values());
+    }
+    put_len(&mut out, buckets.snapshots.len());
+    for time in buckets.snapshots.keys() {
+        out.extend_from_slice(&time.timestamp_micros().to_le_bytes());
+    }
+    put_accumulators(
+        &mut out,
+        buckets.snapshots.values().map(|snapshot| &snapshot.ru),
+    );
+    put_accumulators(
+        &mut out,
+        buckets.snapshots.values().map(|snapshot| &snapshot.ua),
+    );
+    put_accumulators(
+        &mut out,
+        buckets
+            .snapshots
+            .values()
+            .map(|snapshot| &snapshot.unspecified),
+    );
+    put_len(&mut out, buckets.manual.len());
+    for date in &buckets.manual {
+        put_date(&mut out, *date);
+    }
+    out
+}
+
+fn decode(bytes: &[u8], csv_sha256: &str) -> Option<AreaBuckets> {
+    let mut reader = Reader { bytes };
+    if reader.take(MAGIC.len())? != MAGIC || reader.take(csv_sha256.len())? != csv_sha256.as_bytes()
+    {
+        return None;
+    }
+    let version_len = usize::from(u16::from_le_bytes(reader.array()?));
+    if reader.take(version_len)? != APP_VERSION.as_bytes() {
+        return None;
+    }
+    let mut daily = [
+        DailyBuckets::new(),
+        DailyBuckets::new(),
+        DailyBuckets::new(),
+        DailyBuckets::new(),
+    ];
+    for buckets in &mut daily {
+        let len = reader.len()?;
+        let dates = (0..len)
+            .map(|_| reader.date())
+            .collect::<Option<Vec<_>>>()?;
+        let accumulators = reader.accumulators(len)?;
+        *buckets = dates.into_iter().zip(accumulators).collect();
+    }
+    let len = reader.len()?;
+    let times = (0..len)
+        .map(|_| DateTime::<Utc>::from_timestamp_micros(i64::from_le_bytes(reader.array()?)))
+        .collect::<Option<Vec<_>>>()?;
+    let ru = reader.accumulators(len)?;
+    let ua = reader.accumulators(len)?;
+    let unspecified = reader.accumulators(len)?;
+    let snapshots = times
+        .into_iter()
+        .zip(ru.into_iter().zip(ua).zip(unspecified))
+        .map(|(time, ((ru, ua), unspecified))| {
+            (
+                time,
+                SnapshotAccumulator {
+                    ru,
+                    ua,
+                    unspecified,
+                },
+            )
+        })
+        .collect();
+    let len = reader.len()?;
+    let manual = (0..len).map(|_| reader.date()).collect::<Option<_>>()?;
+    if !reader.bytes.is_empty() {
+        return None;
+    }
+    let [ru, ua, unspecified, pre_war] = daily;
+    Some(AreaBuckets {
+        ru,
+        ua,
+        unspecified,
+        pre_war,
+        snapshots,
+        manual,
+        excluded: std::collections::BTreeMap::new(),
+    })
+}
+
+fn put_len(out: &mut Vec<u8>, len: usize) {
+    out.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+fn put_date(out: &mut Vec<u8>, date: NaiveDate) {
+    out.extend_from_slice(&chrono::Datelike::num_days_from_ce(&date).to_le_bytes());
+}
+
+fn put_accumulators<'a>(
+    out: &mut Vec<u8>,
+    accumulators: impl Iterator<Item = &'a DailyAccumulator> + Clone,
+) {
+    for accumulator in accumulators.clone() {
+        out.extend_from_slice(&accumulator.sum.to_le_bytes());
+    }
+    for accumulator in accumulators {
+        out.extend_from_slice(&accumulator.count.to_le_bytes());
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    const fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < len {
+            return None;
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Some(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.take(N)?.try_into().ok()
+    }
+
+    fn len(&mut self) -> Option<usize> {
+        usize::try_from(u64::from_le_bytes(self.array()?)).ok()
+    }
+
+    fn date(&mut self) -> Option<NaiveDate> {
+        NaiveDate::from_num_days_from_ce_opt(i32::from_le_bytes(self.array()?))
+    }
+
+    fn accumulators(&mut self, len: usize) -> Option<Vec<DailyAccumulator>> {
+        let sums = (0..len)
+            .map(|_| self.array().map(f64::from_le_bytes))
+            .collect::<Option<Vec<_>>>()?;
+        sums.into_iter()
+            .map(|sum| {
+                Some(DailyAccumulator {
+                    sum,
+                    count: u32::from_le_bytes(self.array()?),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cache_path, decode, encode};
+    use crate::series::{build_occupied_series, load_area_buckets};
+    use std::path::Path;
+
+    #[test]
+    fn cache_round_trips_buckets_and_rejects_other_csv() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("series_cache.csv");
+        std::fs::write(
+            &path,
+            "time_index,hash,area,percent,area_type\n\
+             2024-05-01 10:00:00 UTC,#a52714,100,0,occupied_after_24_02_2022\n\
+             2024-05-01 10:00:00 UTC,#01579b,5,0,other_territories\n\
+             2024-05-01 10:00:00 UTC,#bcaaa4,7,0,unspecified\n\
+             2024-05-03 00:00:00 UTC,manual,120,0,occupied_after_24_02_2022\n",
+        )
+        .expect("csv should be written");
+        let buckets = load_area_buckets(&path).expect("csv should load");
+
+        let encoded = encode("ab", &buckets);
+        let decoded = decode(&encoded, "ab").expect("cache should decode");
+        assert_eq!(
+            build_occupied_series(&decoded).expect("series"),
+            build_occupied_series(&buckets).expect("series")
+        );
+        assert_eq!(decoded.snapshots(), buckets.snapshots());
+        assert_eq!(decoded.manual_days(), buckets.manual_days());
+        assert!(decode(&encoded, "cd").is_none());
+        assert!(decode(&encoded[..encoded.len() - 1], "ab").is_none());
+
+        assert_eq!(
+            cache_path(Path::new("dist/history.csv")),
+            Path::new("dist/.history.csv.rua-series")
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Источники истории площадей: общий интерфейс для провайдеров карт.
+//! Каждый источник приводит свои данные к схеме `Area`, дальше они идут в `DataStore`.
+
+mod deepstate;
+mod isw;
+#[cfg(test)]
+mod mock;
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use bytes::Bytes;
+use chrono::{DateTime, TimeDelta, Utc};
+use reqwest::Client;
+use tokio::sync::{Semaphore, mpsc};
+use tokio::time::Instant;
+
+pub use deepstate::{DeepStateSource, replay_raw_archive};
+pub use isw::{ISW_HASH, IswSource};
+
+use crate::data::Area;
+use crate::fetch::{self, FetchConfig, FetchError};
+use crate::interrupt::Interrupt;
+use crate::progress::Progress;
+use crate::series::DateRange;
+
+/// Сколько разобранных срезов может ждать записи; вместе с параллельными запросами
+/// источника это и есть предел памяти загрузки, независимо от длины истории.
+const SNAPSHOT_BUFFER: usize = 8;
+
+/// Канал, в который источник отдаёт строки по одному срезу.
+pub type AreaSink = mpsc::Sender<Vec<Area>>;
+
+/// Итог загрузки срезов.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FetchSummary {
+    /// Скачанные и отданные в канал срезы.
+    pub fetched: usize,
+    /// Срезы, которые требовалось скачать.
+    pub total: usize,
+    /// Загрузку прервали по Ctrl-C.
+    pub interrupted: bool,
+}
+
+/// Последний опубликованный срез карты; в истории его может ещё не быть.
+#[derive(Debug)]
+pub struct LatestSnapshot {
+    pub time: DateTime<Utc>,
+    pub areas: Vec<Area>,
+}
+
+/// Карта одного среза в `GeoJSON`, как её отдаёт источник.
+#[derive(Debug)]
+pub struct MapGeoJson {
+    pub time: DateTime<Utc>,
+    pub body: Bytes,
+}
+
+/// Общая загрузка запуска: клиент, сетевые параметры, один предел одновременных запросов
+/// из [`FetchConfig::max_concurrent_requests`] и один индикатор хода. Все методы
+/// [`MapSource`] получают её, поэтому история и карты фронта, скачиваемые одновременно,
+/// делят и предел, и индикатор, а не заводят каждая свои.
+pub struct Fetcher {
+    client: Client,
+    config: FetchConfig,
+    limiter: Semaphore,
+    retries: AtomicU32,
+    tracking: Mutex<Tracking>,
+}
+
+/// Общий индикатор и число загрузок, которые его сейчас ведут.
+#[derive(Default)]
+struct Tracking {
+    progress: Option<Progress>,
+    active: usize,
+}
+
+impl Fetcher {
+    pub fn new(client: Client, config: FetchConfig) -> Self {
+        Self {
+            limiter: Semaphore::new(config.max_concurrent_requests.max(1)),
+            client,
+            config,
+            retries: AtomicU32::new(0),
+            tracking: Mutex::default(),
+        }
+    }
+
+    pub const fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub const fn config(&self) -> &FetchConfig {
+        &self.config
+    }
+
+    /// Срок одной загрузки по `deadline` из конфига, считая от текущего момента.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.config.deadline.map(|budget| Instant::now() + budget)
+    }
+
+    /// Скачивает `url` в пределах общего лимита; повторы идут в общий счётчик индикатора.
+    pub async fn fetch_bytes(
+        &self,
+        url: &str,
+        deadline: Option<Instant>,
+    ) -> Result<Bytes, FetchError> {
+        fetch::fetch_bytes_shared(
+            &self.client,
+            url,
+            &self.config,
+            deadline,
+            &self.retries,
+            &self.limiter,
+        )
+        .await
+    }
+
+    /// Добавляет `total` срезов на общий индикатор. Индикатор закрывается, когда завершится
+    /// последняя из одновременных загрузок.
+    pub fn track(&self, total: usize) -> Tracked<'_> {
+        let mut tracking = self.lock();
+        tracking.active += 1;
+        match &mut tracking.progress {
+            Some(progress) => progress.grow(total),
+            None => tracking.progress = Some(Progress::new(total)),
+        }
+        drop(tracking);
+        Tracked { fetcher: self }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Tracking> {
+        self.tracking.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Доля загрузки на общем индикаторе [`Fetcher`].
+pub struct Tracked<'a> {
+    fetcher: &'a Fetcher,
+}
+
+impl Tracked<'_> {
+    /// Отмечает ещё один обработанный срез.
+    pub fn advance(&self) {
+        let retries = self.fetcher.retries.load(Ordering::Relaxed);
+        if let Some(progress) = &mut self.fetcher.lock().progress {
+            progress.advance(retries);
+        }
+    }
+}
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        let mut tracking = self.fetcher.lock();
+        tracking.active -= 1;
+        if tracking.active == 0
+            && let Some(progress) = tracking.progress.take()
+        {
+            progress.finish();
+        }
+    }
+}
+
+/// Провайдер карты, из которого скачивается история площадей.
+pub trait MapSource {
+    /// Название источника для логов.
+    fn name(&self) -> &'static str;
+
+    /// Адрес, по которому `rua doctor` проверяет доступность источника.
+    fn probe_url(&self) -> &'static str;
+
+    /// Загружает срезы за диапазон дат (границы включительно) и отдаёт каждый в `sink`,
+    /// как только он разобран; по `interrupt` останавливается. Если получатель закрыл канал,
+    /// загрузка завершается ошибкой.
+    fn fetch_areas(
+        &self,
+        fetcher: &Fetcher,
+        range: DateRange,
+        interrupt: &Interrupt,
+        sink: AreaSink,
+    ) -> impl Future<Output = Result<FetchSummary, String>> + Send;
+
+    /// Загружает текущий срез карты, не дожидаясь его появления в списке истории.
+    fn fetch_latest(
+        &self,
+        fetcher: &Fetcher,
+    ) -> impl Future<Output = Result<LatestSnapshot, String>> + Send;
+
+    /// Загружает карты последнего среза и самого позднего среза не позже `ago` до него.
+    fn fetch_map_pair(
+        &self,
+        fetcher: &Fetcher,
+        ago: TimeDelta,
+    ) -> impl Future<Output = Result<(MapGeoJson, MapGeoJson), String>> + Send;
+}
+
+/// Загрузка и запись идут одновременно: `produce` пишет срезы в ограниченный канал,
+/// `consume` сохраняет их по одному. Полный канал приостанавливает загрузку, ошибка записи
+/// закрывает канал и останавливает её; задачи не переживают вызов.
+pub async fn stream_snapshots<P, F, C>(produce: P, mut consume: C) -> Result<FetchSummary, String>
+where
+    P: FnOnce(AreaSink) -> F,
+    F: Future<Output = Result<FetchSummary, String>>,
+    C: FnMut(Vec<Area>) -> Result<(), String>,
+{
+    let (sink, mut snapshots) = mpsc::channel(SNAPSHOT_BUFFER);
+    let persist = async move {
+        while let Some(areas) = snapshots.recv().await {
+            consume(areas)?;
+        }
+        Ok::<_, String>(())
+    };
+    let (fetched, persisted) = tokio::join!(produce(sink), persist);
+    persisted?;
+    fetched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FetchSummary, stream_snapshots};
+
+    #[tokio::test]
+    async fn failed_write_stops_the_download() {
+        let err = stream_snapshots(
+            |sink| async move {
+                for _ in 0..100 {
+                    if sink.send(Vec::new()).await.is_err() {
+                        return Err("sink closed".to_string());
+                    }
+                }
+                Ok(FetchSummary::default())
+            },
+            |_| Err("disk full".to_string()),
+        )
+        .await
+        .expect_err("write error should fail the download");
+        assert_eq!(err, "disk full");
+    }
+}
@@ -0,0 +1,1058 @@
+//! `DeepStateMap`: список срезов из публичной истории и площади по каждому срезу.
+//!
+//! С `raw_archive` каждый скачанный ответ со срезом сохраняется как есть в `<id>.json.gz`:
+//! `rua rebuild-history` прогоняет архив через текущий разбор и пересобирает историю.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use futures::{StreamExt, stream};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use super::{AreaSink, FetchSummary, Fetcher, LatestSnapshot, MapGeoJson, MapSource};
+use crate::data::Area;
+use crate::fetch::FetchError;
+use crate::interrupt::Interrupt;
+use crate::series::DateRange;
+
+const HISTORY_API_BASE: &str = "https://deepstatemap.live/api/history";
+const HISTORY_PUBLIC_URL: &str = "https://deepstatemap.live/api/history/public";
+const HISTORY_LAST_URL: &str = "https://deepstatemap.live/api/history/last";
+/// Допустимое расхождение `datetime` с `id`: подпись бывает в киевском времени без зоны.
+const DATETIME_TOLERANCE_HOURS: i64 = 12;
+const DATETIME_LABEL_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+const RAW_ARCHIVE_SUFFIX: &str = ".json.gz";
+
+/// Источник по умолчанию: API deepstatemap.live.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeepStateSource {
+    /// Каталог, куда сохраняются сырые ответы со срезами; `None` — не сохранять.
+    pub raw_archive: Option<PathBuf>,
+}
+
+impl MapSource for DeepStateSource {
+    fn name(&self) -> &'static str {
+        "DeepStateMap"
+    }
+
+    fn probe_url(&self) -> &'static str {
+        HISTORY_PUBLIC_URL
+    }
+
+    async fn fetch_areas(
+        &self,
+        fetcher: &Fetcher,
+        range: DateRange,
+        interrupt: &Interrupt,
+        sink: AreaSink,
+    ) -> Result<FetchSummary, String> {
+        let archive = self.raw_archive.as_deref();
+        fetch_history(fetcher, range, interrupt, Endpoints::LIVE, archive, sink).await
+    }
+
+    async fn fetch_latest(&self, fetcher: &Fetcher) -> Result<LatestSnapshot, String> {
+        fetch_last(fetcher, Endpoints::LIVE, self.raw_archive.as_deref()).await
+    }
+
+    async fn fetch_map_pair(
+        &self,
+        fetcher: &Fetcher,
+        ago: TimeDelta,
+    ) -> Result<(MapGeoJson, MapGeoJson), String> {
+        fetch_map_pair(fetcher, ago, Endpoints::LIVE).await
+    }
+}
+
+/// Список срезов без повторов, упорядоченный по времени.
+async fn fetch_timestamps(
+    fetcher: &Fetcher,
+    url: &str,
+    deadline: Option<Instant>,
+) -> Result<Vec<(SnapshotId, DateTime<Utc>)>, String> {
+    info!("Fetching timestamps...");
+    let json_data = fetcher
+        .fetch_bytes(url, deadline)
+        .await
+        .map_err(|err| format!("Failed to fetch timestamps: {err}"))?;
+    let items: Vec<AreaItem> = serde_json::from_slice(&json_data)
+        .map_err(|err| format!("Failed to deserialize JSON: {err}"))?;
+    let listed = items.len();
+    let result = collapse_duplicates(items);
+    if result.len() < listed {
+        info!(
+            listed,
+            selected = result.len(),
+            "Collapsed duplicate snapshots of the same instant"
+        );
+    }
+    Ok(result)
+}
+
+/// Карты последнего среза и самого позднего среза не позже `ago` до него (`<id>/geojson`).
+async fn fetch_map_pair(
+    fetcher: &Fetcher,
+    ago: TimeDelta,
+    endpoints: Endpoints<'_>,
+) -> Result<(MapGeoJson, MapGeoJson), String> {
+    let deadline = fetcher.deadline();
+    let timestamps = fetch_timestamps(fetcher, endpoints.timestamps, deadline).await?;
+    let &(after_id, after_time) = timestamps.last().ok_or("history list is empty")?;
+    let &(before_id, before_time) = timestamps
+        .iter()
+        .rev()
+        .find(|(_, time)| *time <= after_time - ago)
+        .ok_or_else(|| format!("history has no snapshot {ago} before {after_time}"))?;
+    let tracked = fetcher.track(2);
+    let tracked = &tracked;
+    let geojson = |id: SnapshotId, time| async move {
+        let url = format!("{}/{id}/geojson", endpoints.areas_base);
+        let map = fetcher
+            .fetch_bytes(&url, deadline)
+            .await
+            .map(|body| MapGeoJson { time, body })
+            .map_err(|err| format!("Failed to fetch map of snapshot {id}: {err}"));
+        tracked.advance();
+        map
+    };
+    tokio::try_join!(
+        geojson(before_id, before_time),
+        geojson(after_id, after_time)
+    )
+}
+
+/// Адреса API: список срезов, последний срез и префикс `<areas_base>/<timestamp>/areas`.
+#[derive(Clone, Copy)]
+struct Endpoints<'a> {
+    timestamps: &'a str,
+    last: &'a str,
+    areas_base: &'a str,
+}
+
+impl Endpoints<'static> {
+    const LIVE: Self = Self {
+        timestamps: HISTORY_PUBLIC_URL,
+        last: HISTORY_LAST_URL,
+        areas_base: HISTORY_API_BASE,
+    };
+}
+
+/// Ответ `/history/last`: из него нужен только `id`, геометрия карты пропускается.
+#[derive(Deserialize)]
+struct LastItem {
+    id: SnapshotId,
+}
+
+/// Загружает последний срез: `id` из `/history/last`, площади — как у среза истории.
+async fn fetch_last(
+    fetcher: &Fetcher,
+    endpoints: Endpoints<'_>,
+    archive: Option<&Path>,
+) -> Result<LatestSnapshot, String> {
+    let deadline = fetcher.deadline();
+    let body = fetcher
+        .fetch_bytes(endpoints.last, deadline)
+        .await
+        .map_err(|err| format!("Failed to fetch the latest snapshot: {err}"))?;
+    let item: LastItem = serde_json::from_slice(&body)
+        .map_err(|err| format!("Failed to deserialize JSON: {err}"))?;
+    let time = item.id.time()?;
+    let content = fetch_snapshot(fetcher, endpoints.areas_base, item.id, deadline)
+        .await
+        .map_err(|err| format!("Failed to fetch snapshot {}: {err}", item.id))?;
+    let (areas, report) = parse_areas(&content, time)?;
+    archive_snapshot(archive, item.id, &content);
+    report.log_summary();
+    Ok(LatestSnapshot { time, areas })
+}
+
+/// Загружает доступные срезы из диапазона дат, проставляет `time_index` из timestamp
+/// и отдаёт каждый срез в `sink`; в памяти одновременно только срезы в полёте.
+async fn fetch_history(
+    fetcher: &Fetcher,
+    range: DateRange,
+    interrupt: &Interrupt,
+    endpoints: Endpoints<'_>,
+    archive: Option<&Path>,
+    sink: AreaSink,
+) -> Result<FetchSummary, String> {
+    let deadline = fetcher.deadline();
+    // Сначала получаем список временных отметок, по которым запрашиваем площади.
+    let mut result = fetch_timestamps(fetcher, endpoints.timestamps, deadline).await?;
+    if !range.is_unbounded() {
+        result.retain(|(_, time)| range.contains(time.date_naive()));
+        info!(range = %range, timestamps = result.len(), "Restricted fetch to date range");
+    }
+
+    // Затем скачиваем площади по каждой отметке.
+    let total = result.len();
+    let progress = fetcher.track(total);
+    let stream = stream::iter(result).map(|(id, time_index)| async move {
+        let content = fetch_snapshot(fetcher, endpoints.areas_base, id, deadline)
+            .await
+            .map_err(|err| format!("Failed to fetch snapshot {id}: {err}"))?;
+        let parsed = parse_areas(&content, time_index)?;
+        archive_snapshot(archive, id, &content);
+        Ok::<_, String>(parsed)
+    });
+    // Запросы ограничивает общий предел `fetcher`; здесь — лишь число срезов в памяти.
+    let mut buffered = stream.buffer_unordered(fetcher.config().max_concurrent_requests.max(1));
+
+    let mut report = SchemaReport::default();
+    let mut done = 0usize;
+    let mut interrupt = interrupt.subscribe();
+    let mut interrupted = false;
+    let mut sink_closed = false;
+    loop {
+        let next = tokio::select! {
+            next = buffered.next() => next,
+            Ok(()) = interrupt.changed() => {
+                interrupted = true;
+                None
+            }
+        };
+        let Some(result) = next else {
+            break;
+        };
+        match result {
+            Ok((areas, area_report)) => {
+                report.merge(area_report);
+                // Ждём, пока запись освободит место в канале: так загрузка не обгоняет диск.
+                if sink.send(areas).await.is_err() {
+                    sink_closed = true;
+                    break;
+                }
+                done += 1;
+            }
+            Err(err) => warn!(error = %err, "Failed to fetch the URL"),
+        }
+        progress.advance();
+    }
+    // Отменяем запросы в полёте и отпускаем индикатор до следующих строк лога: если карты
+    // фронта уже скачаны, он закрывается здесь.
+    drop(buffered);
+    drop(progress);
+    report.log_summary();
+    if sink_closed {
+        return Err(format!(
+            "Snapshot writer stopped after {done} of {total} snapshots"
+        ));
+    }
+    if interrupted {
+        return Ok(FetchSummary {
+            fetched: done,
+            total,
+            interrupted: true,
+        });
+    }
+    if let (Some(deadline), Some(budget)) = (deadline, fetcher.config().deadline)
+        && Instant::now() >= deadline
+        && done < total
+    {
+        return Err(format!(
+            "Download deadline of {}s exceeded: fetched {done} of {total} snapshots",
+            budget.as_secs()
+        ));
+    }
+
+    Ok(FetchSummary {
+        fetched: done,
+        total,
+        interrupted: false,
+    })
+}
+
+/// Идентификатор среза в API: он же время среза в секундах Unix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+struct SnapshotId(i64);
+
+impl SnapshotId {
+    fn time(self) -> Result<DateTime<Utc>, String> {
+        DateTime::from_timestamp(self.0, 0)
+            .ok_or_else(|| format!("snapshot id {self} is not a valid Unix timestamp"))
+    }
+}
+
+impl fmt::Display for SnapshotId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct AreaItem {
+    id: SnapshotId,
+    #[serde(rename = "updatedAt")]
+    updated_at: DateTime<Utc>,
+    datetime: String,
+    status: bool,
+    #[serde(rename = "createdAt")]
+    created_at: DateTime<Utc>,
+}
+
+impl AreaItem {
+    /// Время среза по `id`. Если `datetime` разбирается как дата со временем, оно должно
+    /// совпадать с `id`: расхождение значит, что API прислал несогласованный срез.
+    fn time(&self) -> Result<DateTime<Utc>, String> {
+        let time = self.id.time()?;
+        let Some(labelled) = parse_datetime_label(&self.datetime) else {
+            return Ok(time);
+        };
+        if (labelled - time).abs() > TimeDelta::hours(DATETIME_TOLERANCE_HOURS) {
+            return Err(format!(
+                "snapshot {} is dated {time} by id but {} by datetime",
+                self.id, self.datetime
+            ));
+        }
+        Ok(time)
+    }
+}
+
+/// Оставляет по одному срезу на момент времени: API иногда перечисляет несколько `id`
+/// с одной подписью `datetime`, и без отбора они дважды попадают в среднее за день.
+/// Момент — подпись `datetime`, а если она не разбирается, время по `id`. Из повторов
+/// побеждает позже обновлённый (`updated_at`), при равенстве — больший `id`. Срезы
+/// с невалидным временем пропускаются с предупреждением; результат упорядочен по времени.
+fn collapse_duplicates(items: Vec<AreaItem>) -> Vec<(SnapshotId, DateTime<Utc>)> {
+    let mut latest: BTreeMap<DateTime<Utc>, (AreaItem, DateTime<Utc>)> = BTreeMap::new();
+    for item in items {
+        let time = match item.time() {
+            Ok(time) => time,
+            Err(err) => {
+                warn!(error = %err, "Skipping snapshot with an invalid timestamp");
+                continue;
+            }
+        };
+        let instant = parse_datetime_label(&item.datetime).unwrap_or(time);
+        match latest.get(&instant) {
+            Some((kept, _)) if (kept.updated_at, kept.id.0) >= (item.updated_at, item.id.0) => {}
+            _ => {
+                latest.insert(instant, (item, time));
+            }
+        }
+    }
+    let mut selected = latest
+        .into_values()
+        .map(|(item, time)| (item.id, time))
+        .collect::<Vec<_>>();
+    selected.sort_by_key(|&(id, time)| (time, id.0));
+    selected
+}
+
+/// Подпись `datetime` в одном из встречающихся форматов; нераспознанная подпись не проверяется.
+fn parse_datetime_label(raw: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    DateTime::parse_from_rfc3339(raw)
+        .map(|time| time.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            DATETIME_LABEL_FORMATS
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(raw, format).ok())
+                .map(|time| time.and_utc())
+        })
+}
+
+/// Ответ API со срезом: обычный массив или массив, завёрнутый в объект.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AreasPayload {
+    List(Vec<Value>),
+    Wrapped {
+        #[serde(alias = "data", alias = "items")]
+        areas: Vec<Value>,
+    },
+}
+
+/// Число, которое API может прислать как JSON-число или как строку.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LooseNumber {
+    Number(f64),
+    Text(String),
+}
+
+impl LooseNumber {
+    fn value(&self) -> Option<f64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            Self::Text(text) => text.trim().parse().ok(),
+        }
+    }
+}
+
+/// Терпимое представление элемента среза: все поля необязательны, неизвестные попадают в `raw`.
+#[derive(Deserialize)]
+struct RawArea {
+    #[serde(default, alias = "color")]
+    hash: Option<String>,
+    #[serde(default, alias = "area_km2", alias = "areaKm2")]
+    area: Option<LooseNumber>,
+    #[serde(default)]
+    percent: Option<LooseNumber>,
+    #[serde(default, rename = "type", alias = "area_type", alias = "areaType")]
+    area_type: Option<String>,
+    #[serde(flatten)]
+    raw: Map<String, Value>,
+}
+
+/// Сводка отклонений ответа API от ожидаемой схемы.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct SchemaReport {
+    /// Пропущенные элементы по причине.
+    skipped: BTreeMap<&'static str, usize>,
+    /// Поля, для которых подставлено значение по умолчанию.
+    defaulted: BTreeMap<&'static str, usize>,
+    /// Незнакомые поля и число их появлений.
+    unknown_fields: BTreeMap<String, usize>,
+}
+
+impl SchemaReport {
+    fn merge(&mut self, other: Self) {
+        for (reason, count) in other.skipped {
+            *self.skipped.entry(reason).or_default() += count;
+        }
+        for (field, count) in other.defaulted {
+            *self.defaulted.entry(field).or_default() += count;
+        }
+        for (field, count) in other.unknown_fields {
+            *self.unknown_fields.entry(field).or_default() += count;
+        }
+    }
+
+    fn is_clean(&self) -> bool {
+        self.skipped.is_empty() && self.defaulted.is_empty() && self.unknown_fields.is_empty()
+    }
+
+    fn log_summary(&self) {
+        if self.is_clean() {
+            return;
+        }
+        let describe = |counts: Vec<(String, usize)>| {
+            counts
+                .into_iter()
+                .map(|(key, count)| format!("{key}×{count}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        warn!(
+            skipped = %describe(self.skipped.iter().map(|(k, v)| ((*k).to_string(), *v)).collect()),
+            defaulted = %describe(self.defaulted.iter().map(|(k, v)| ((*k).to_string(), *v)).collect()),
+            unknown_fields = %describe(self.unknown_fields.iter().map(|(k, v)| (k.clone(), *v)).collect()),
+            "API response deviates from the expected schema"
+        );
+    }
+}
+
+/// Разбирает ответ API со срезом, пропуская элементы без обязательных полей.
+fn parse_areas(
+    content: &[u8],
+    time_index: DateTime<Utc>,
+) -> Result<(Vec<Area>, SchemaReport), String> {
+    let payload: AreasPayload = serde_json::from_slice(content)
+        .map_err(|err| format!("Failed to deserialize JSON: {err}"))?;
+    let items = match payload {
+        AreasPayload::List(items) | AreasPayload::Wrapped { areas: items } => items,
+    };
+
+    let mut report = SchemaReport::default();
+    let mut areas = Vec::with_capacity(items.len());
+    for item in items {
+        let is_object = item.is_object();
+        let raw = match serde_json::from_value::<RawArea>(item) {
+            Ok(raw) => raw,
+            Err(err) => {
+                // Поле вместе со своим псевдонимом (`hash` и `color`) serde считает повтором.
+                let reason = if !is_object {
+                    "not an object"
+                } else if err.to_string().starts_with("duplicate field") {
+                    "duplicate field"
+                } else {
+                    "invalid field"
+                };
+                *report.skipped.entry(reason).or_default() += 1;
+                continue;
+            }
+        };
+        for key in raw.raw.keys() {
+            *report.unknown_fields.entry(key.clone()).or_default() += 1;
+        }
+        let Some(area) = raw.area.as_ref().and_then(LooseNumber::value) else {
+            *report.skipped.entry("area").or_default() += 1;
+            continue;
+        };
+        let Some(area_type) = raw.area_type else {
+            *report.skipped.entry("type").or_default() += 1;
+            continue;
+        };
+        let percent = raw
+            .percent
+            .as_ref()
+            .and_then(LooseNumber::value)
+            .unwrap_or_else(|| {
+                *report.defaulted.entry("percent").or_default() += 1;
+                0.0
+            });
+        // Без цвета слоя строку не отнести ни к одному ряду: пустой `hash` лишь прятал её.
+        let Some(hash) = raw.hash else {
+            *report.skipped.entry("missing hash").or_default() += 1;
+            continue;
+        };
+        areas.push(Area {
+            time_index,
+            hash,
+            area,
+            percent,
+            area_type,
+        });
+    }
+    Ok((areas, report))
+}
+
+/// Сохраняет разобранный ответ со срезом в архив; ошибка записи не мешает загрузке и только пишется в лог.
+fn archive_snapshot(archive: Option<&Path>, id: SnapshotId, content: &[u8]) {
+    let Some(dir) = archive else {
+        return;
+    };
+    let path = dir.join(format!("{id}{RAW_ARCHIVE_SUFFIX}"));
+    let written = fs::create_dir_all(dir).and_then(|()| {
+        let mut encoder = GzEncoder::new(File::create(&path)?, Compression::default());
+        encoder.write_all(content)?;
+        encoder.finish().map(drop)
+    });
+    if let Err(err) = written {
+        warn!(path = %path.display(), error = %err, "Failed to archive raw snapshot");
+    }
+}
+
+/// Срезы, заново разобранные из архива сырых ответов.
+#[derive(Debug)]
+pub struct ReplayedArchive {
+    /// Строки всех срезов по возрастанию времени.
+    pub areas: Vec<Area>,
+    pub snapshots: usize,
+}
+
+/// Разбирает все `<id>.json.gz` из `dir` текущим разбором в порядке `id`; время среза —
+/// из `id`, как при загрузке. Битый файл прерывает пересборку: молча потерянный срез
+/// сделал бы историю зависимой от состояния архива.
+pub fn replay_raw_archive(dir: &Path) -> Result<ReplayedArchive, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|err| format!("Failed to read {}: {err}", dir.display()))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|err| format!("Failed to read {}: {err}", dir.display()))?
+            .path();
+        let Some(id) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(RAW_ARCHIVE_SUFFIX))
+            .and_then(|stem| stem.parse::<i64>().ok())
+        else {
+            continue;
+        };
+        files.push((SnapshotId(id), path));
+    }
+    files.sort_by_key(|(id, _)| id.0);
+
+    let mut report = SchemaReport::default();
+    let mut areas = Vec::new();
+    for (id, path) in &files {
+        let mut content = Vec::new();
+        File::open(path)
+            .and_then(|file| GzDecoder::new(file).read_to_end(&mut content))
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        let (snapshot, snapshot_report) = parse_areas(&content, id.time()?)
+            .map_err(|err| format!("Failed to parse {}: {err}", path.display()))?;
+        report.merge(snapshot_report);
+        areas.extend(snapshot);
+    }
+    report.log_summary();
+    Ok(ReplayedArchive {
+        areas,
+        snapshots: files.len(),
+    })
+}
+
+/// Запрашивает историю площадей по timestamp и повторяет попытки при сетевых/HTTP ошибках.
+/// После `deadline` новые попытки не начинаются, а текущая прерывается.
+async fn fetch_snapshot(
+    fetcher: &Fetcher,
+    areas_base: &str,
+    timestamp: SnapshotId,
+    deadline: Option<Instant>,
+) -> Result<Bytes, FetchError> {
+    let url = format!("{areas_base}/{timestamp}/areas");
+    fetcher.fetch_bytes(&url, deadline).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{
+        AreaItem, Endpoints, SnapshotId, collapse_duplicates, fetch_history, fetch_last,
+        fetch_map_pair, parse_areas, replay_raw_archive,
+    };
+    use crate::data::test_support;
+    use crate::fetch::FetchConfig;
+    use crate::interrupt::Interrupt;
+    use crate::series::DateRange;
+    use crate::source::mock::{MockServer, Reply};
+    use crate::source::{Fetcher, stream_snapshots};
+
+    const TIMESTAMPS_PATH: &str = "/history/public";
+    const LAST_PATH: &str = "/history/last";
+
+    fn areas_path(timestamp: i64) -> String {
+        format!("/history/{timestamp}/areas")
+    }
+
+    fn timestamps(ids: &[i64]) -> Reply {
+        let items = ids
+            .iter()
+            .map(|id| {
+                format!(
+                    r#"{{"id":{id},"updatedAt":"2024-05-01T00:00:00Z","datetime":"","status":true,"createdAt":"2024-05-01T00:00:00Z"}}"#
+                )
+            })
+            .collect::<Vec<_>>();
+        Reply::Body(format!("[{}]", items.join(",")))
+    }
+
+    fn snapshot(area: f64) -> Reply {
+        Reply::Body(format!(
+            r##"[{{"hash":"#a52714","area":{area},"percent":1.0,"type":"occupied_after_24_02_2022"}}]"##
+        ))
+    }
+
+    async fn fetch(server: &MockServer) -> Result<Vec<f64>, String> {
+        let config = FetchConfig {
+            max_retries: 3,
+            retry_delay: Duration::from_millis(10),
+            ..FetchConfig::default()
+        };
+        let timestamps = server.url(TIMESTAMPS_PATH);
+        let areas_base = server.url("/history");
+        let fetcher = Fetcher::new(reqwest::Client::new(), config);
+        let interrupt = Interrupt::listen();
+        let mut areas = Vec::new();
+        let summary = stream_snapshots(
+            |sink| {
+                fetch_history(
+                    &fetcher,
+                    DateRange::default(),
+                    &interrupt,
+                    Endpoints {
+                        timestamps: &timestamps,
+                        last: "",
+                        areas_base: &areas_base,
+                    },
+                    None,
+                    sink,
+                )
+            },
+            |snapshot| {
+                areas.extend(snapshot.into_iter().map(|area| area.area));
+                Ok(())
+            },
+        )
+        .await?;
+        assert!(!summary.interrupted);
+        assert_eq!(summary.fetched, areas.len());
+        areas.sort_by(f64::total_cmp);
+        Ok(areas)
+    }
+
+    #[test]
+    fn snapshot_time_comes_from_id_and_must_agree_with_datetime() {
+        let item = |id: i64, datetime: &str| {
+            serde_json::from_str::<AreaItem>(&format!(
+                r#"{{"id":{id},"updatedAt":"2024-05-01T00:00:00Z","datetime":"{datetime}","status":true,"createdAt":"2024-05-01T00:00:00Z"}}"#
+            ))
+            .expect("item should parse")
+        };
+        let expected = test_support::day(1);
+
+        assert_eq!(item(1_714_521_600, "").time(), Ok(expected));
+        assert_eq!(
+            item(1_714_521_600, "2024-05-01 03:00:00").time(),
+            Ok(expected)
+        );
+        assert_eq!(item(1_714_521_600, "вчера").time(), Ok(expected));
+        let err = item(1_714_521_600, "2024-06-01T00:00:00Z")
+            .time()
+            .expect_err("dates disagree");
+        assert!(err.contains("snapshot 1714521600"), "{err}");
+        assert!(item(i64::MAX, "").time().is_err());
+    }
+
+    #[test]
+    fn duplicate_snapshots_of_one_instant_keep_the_latest_update() {
+        let item = |id: i64, updated_at: &str, datetime: &str| {
+            serde_json::from_str::<AreaItem>(&format!(
+                r#"{{"id":{id},"updatedAt":"{updated_at}","datetime":"{datetime}","status":true,"createdAt":"2024-05-01T00:00:00Z"}}"#
+            ))
+            .expect("item should parse")
+        };
+        let selected = collapse_duplicates(vec![
+            item(
+                1_714_525_200,
+                "2024-05-01T02:00:00Z",
+                "2024-05-01T01:00:00Z",
+            ),
+            item(
+                1_714_525_260,
+                "2024-05-01T05:00:00Z",
+                "2024-05-01T01:00:00Z",
+            ),
+            item(
+                1_714_525_320,
+                "2024-05-01T03:00:00Z",
+                "2024-05-01T01:00:00Z",
+            ),
+            item(1_714_521_600, "2024-05-01T00:00:00Z", ""),
+            item(1_714_521_600, "2024-05-01T00:00:00Z", ""),
+            item(i64::MAX, "2024-05-02T00:00:00Z", ""),
+        ]);
+        let ids = selected.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        assert_eq!(ids, [SnapshotId(1_714_521_600), SnapshotId(1_714_525_260)]);
+    }
+
+    #[tokio::test]
+    async fn fetch_retries_rate_limits_and_server_errors() {
+        let server = MockServer::start(vec![
+            (
+                TIMESTAMPS_PATH.to_string(),
+                vec![
+                    Reply::Status(503),
+                    timestamps(&[1_714_521_600, 1_714_608_000, 1_714_694_400]),
+                ],
+            ),
+            (
+                areas_path(1_714_521_600),
+                vec![Reply::Status(429), snapshot(100.0)],
+            ),
+            (
+                areas_path(1_714_608_000),
+                vec![Reply::Status(500), Reply::Status(502), snapshot(110.0)],
+            ),
+            (areas_path(1_714_694_400), vec![Reply::Status(500)]),
+        ])
+        .await;
+
+        let areas = fetch(&server).await.expect("fetch should succeed");
+
+        assert_eq!(areas, vec![100.0, 110.0]);
+        assert_eq!(server.hits(TIMESTAMPS_PATH), 2);
+        assert_eq!(server.hits(&areas_path(1_714_521_600)), 2);
+        assert_eq!(server.hits(&areas_path(1_714_608_000)), 3);
+        // После исчерпания попыток срез пропускается, загрузка остальных продолжается.
+        assert_eq!(server.hits(&areas_path(1_714_694_400)), 3);
+    }
+
+    #[tokio::test]
+    async fn archived_snapshots_replay_into_the_same_rows() {
+        let server = MockServer::start(vec![
+            (
+                LAST_PATH.to_string(),
+                vec![Reply::Body(r#"{"id":1714608000}"#.to_string())],
+            ),
+            (areas_path(1_714_608_000), vec![snapshot(120.0)]),
+        ])
+        .await;
+        let temp = tempfile::tempdir().expect("temp dir");
+        let dir = temp.path();
+        let last = server.url(LAST_PATH);
+        let areas_base = server.url("/history");
+        let latest = fetch_last(
+            &Fetcher::new(reqwest::Client::new(), FetchConfig::default()),
+            Endpoints {
+                timestamps: "",
+                last: &last,
+                areas_base: &areas_base,
+            },
+            Some(dir),
+        )
+        .await
+        .expect("latest snapshot should load");
+        std::fs::write(dir.join("notes.txt"), "not a snapshot").expect("stray file");
+
+        let replayed = replay_raw_archive(dir);
+        let replayed = replayed.expect("archive should replay");
+        assert_eq!(replayed.snapshots, 1);
+        assert_eq!(replayed.areas.len(), latest.areas.len());
+        assert_eq!(replayed.areas[0].time_index, latest.time);
+        assert!((replayed.areas[0].area - 120.0).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn malformed_snapshots_are_skipped_and_truncated_bodies_retried() {
+        let server = MockServer::start(vec![
+            (
+                TIMESTAMPS_PATH.to_string(),
+                vec![timestamps(&[
+                    1_714_521_600,
+                    1_714_608_000,
+                    1_714_694_400,
+                    1_714_780_800,
+                ])],
+            ),
+            (areas_path(1_714_521_600), vec![snapshot(100.0)]),
+            (
+                areas_path(1_714_608_000),
+                vec![Reply::Body("{\"areas\": [".to_string())],
+            ),
+            (
+                areas_path(1_714_694_400),
+                vec![Reply::Truncated("[{\"area\": 1".to_string())],
+            ),
+            (
+                areas_path(1_714_780_800),
+                vec![
+                    Reply::Truncated("[{\"area\": 1".to_string()),
+                    snapshot(130.0),
+                ],
+            ),
+        ])
+        .await;
+
+        let areas = fetch(&server).await.expect("fetch should succeed");
+
+        assert_eq!(areas, vec![100.0, 130.0]);
+        // Испорченное тело не повторяется: ответ получен, повтор вернёт то же самое.
+        assert_eq!(server.hits(&areas_path(1_714_608_000)), 1);
+        // Оборванное тело повторяется, пока не кончатся попытки (3 в `fetch`).
+        assert_eq!(server.hits(&areas_path(1_714_694_400)), 3);
+        assert_eq!(server.hits(&areas_path(1_714_780_800)), 2);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_request_waits_for_retry_after_within_the_deadline() {
+        let path = areas_path(1_714_521_600);
+        let server = MockServer::start(vec![(
+            path.clone(),
+            vec![Reply::RateLimited(1), snapshot(100.0)],
+        )])
+        .await;
+        let url = server.url(&path);
+        let client = reqwest::Client::new();
+        let config = FetchConfig {
+            max_retries: 3,
+            retry_delay: Duration::from_millis(10),
+            ..FetchConfig::default()
+        };
+
+        let started = tokio::time::Instant::now();
+        crate::fetch::fetch_bytes(&client, &url, &config, None)
+            .await
+            .expect("retry after the pause should succeed");
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert_eq!(server.hits(&path), 2);
+
+        // Пауза дальше общего бюджета не ждётся: запрос сразу завершается ошибкой.
+        let limited = MockServer::start(vec![(path.clone(), vec![Reply::RateLimited(60)])]).await;
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        let err = crate::fetch::fetch_bytes(&client, &limited.url(&path), &config, Some(deadline))
+            .await
+            .expect_err("deadline comes before retry-after");
+        assert!(matches!(err, crate::fetch::FetchError::DeadlineExceeded));
+        assert_eq!(limited.hits(&path), 1);
+    }
+
+    #[tokio::test]
+    async fn latest_snapshot_takes_its_id_from_the_last_endpoint() {
+        let server = MockServer::start(vec![
+            (
+                LAST_PATH.to_string(),
+                vec![Reply::Body(
+                    r#"{"id":1714608000,"datetime":"2024-05-02T00:00:00Z","map":{"features":[]}}"#
+                        .to_string(),
+                )],
+            ),
+            (areas_path(1_714_608_000), vec![snapshot(120.0)]),
+        ])
+        .await;
+        let last = server.url(LAST_PATH);
+        let areas_base = server.url("/history");
+
+        let latest = fetch_last(
+            &Fetcher::new(reqwest::Client::new(), FetchConfig::default()),
+            Endpoints {
+                timestamps: "",
+                last: &last,
+                areas_base: &areas_base,
+            },
+            None,
+        )
+        .await
+        .expect("latest snapshot should load");
+
+        assert_eq!(latest.time, test_support::day(2));
+        assert_eq!(latest.areas.len(), 1);
+        assert!(
+            latest
+                .areas
+                .iter()
+                .all(|area| area.time_index == latest.time)
+        );
+    }
+
+    #[tokio::test]
+    async fn map_pair_takes_the_latest_snapshot_at_least_ago_before_the_last() {
+        let day = |day: u32| test_support::day(day).timestamp();
+        let listed = [1, 3, 6, 9].map(|d| {
+            format!(
+                r#"{{"id":{},"datetime":"x","status":true,"createdAt":"2024-05-01T00:00:00Z","updatedAt":"2024-05-01T00:00:00Z"}}"#,
+                day(d)
+            )
+        });
+        let geojson = |id: i64| {
+            (
+                format!("/history/{id}/geojson"),
+                vec![Reply::Body(format!(r#"{{"id":{id}}}"#))],
+            )
+        };
+        let server = MockServer::start(vec![
+            (
+                TIMESTAMPS_PATH.to_string(),
+                vec![Reply::Body(format!("[{}]", listed.join(",")))],
+            ),
+            geojson(day(9)),
+            geojson(day(1)),
+        ])
+        .await;
+        let timestamps = server.url(TIMESTAMPS_PATH);
+        let areas_base = server.url("/history");
+
+        let (before, after) = fetch_map_pair(
+            &Fetcher::new(reqwest::Client::new(), FetchConfig::default()),
+            chrono::TimeDelta::days(7),
+            Endpoints {
+                timestamps: &timestamps,
+                last: "",
+                areas_base: &areas_base,
+            },
+        )
+        .await
+        .expect("map pair should load");
+
+        assert_eq!(before.time.timestamp(), day(1));
+        assert_eq!(after.time.timestamp(), day(9));
+        assert_eq!(after.body, format!(r#"{{"id":{}}}"#, day(9)).as_bytes());
+    }
+
+    /// Наибольшее число одновременных запросов, пока история скачивается через `history`,
+    /// а карты фронта — через `maps`.
+    async fn peak_requests(history: &Fetcher, maps: &Fetcher) -> usize {
+        let pause = Duration::from_millis(100);
+        let ids = [1_714_521_600, 1_714_608_000, 1_714_694_400];
+        let mut routes = vec![(TIMESTAMPS_PATH.to_string(), vec![timestamps(&ids)])];
+        for id in ids {
+            let body = r##"[{"hash":"#a52714","area":1.0,"type":"occupied_after_24_02_2022"}]"##;
+            routes.push((areas_path(id), vec![Reply::Slow(pause, body.to_string())]));
+            routes.push((
+                format!("/history/{id}/geojson"),
+                vec![Reply::Slow(pause, "{}".to_string())],
+            ));
+        }
+        let server = MockServer::start(routes).await;
+        let timestamps = server.url(TIMESTAMPS_PATH);
+        let areas_base = server.url("/history");
+        let endpoints = Endpoints {
+            timestamps: &timestamps,
+            last: "",
+            areas_base: &areas_base,
+        };
+        let interrupt = Interrupt::listen();
+
+        let (fetched, pair) = tokio::join!(
+            stream_snapshots(
+                |sink| fetch_history(
+                    history,
+                    DateRange::default(),
+                    &interrupt,
+                    endpoints,
+                    None,
+                    sink
+                ),
+                |_| Ok(()),
+            ),
+            fetch_map_pair(maps, chrono::TimeDelta::days(2), endpoints),
+        );
+        assert_eq!(fetched.expect("history should load").fetched, ids.len());
+        pair.expect("map pair should load");
+        server.peak_in_flight()
+    }
+
+    #[tokio::test]
+    async fn history_and_front_maps_share_one_request_limiter() {
+        let config = FetchConfig {
+            max_concurrent_requests: 1,
+            ..FetchConfig::default()
+        };
+        let client = reqwest::Client::new();
+
+        let shared = Fetcher::new(client.clone(), config.clone());
+        assert_eq!(peak_requests(&shared, &shared).await, 1);
+
+        // Со своим пределом у каждой загрузки запросы истории и карт идут внахлёст.
+        let history = Fetcher::new(client.clone(), config.clone());
+        let maps = Fetcher::new(client, config);
+        assert_eq!(peak_requests(&history, &maps).await, 2);
+    }
+
+    #[tokio::test]
+    async fn malformed_timestamps_fail_the_fetch() {
+        let server = MockServer::start(vec![(
+            TIMESTAMPS_PATH.to_string(),
+            vec![Reply::Body("<html>maintenance</html>".to_string())],
+        )])
+        .await;
+
+        let err = fetch(&server).await.expect_err("fetch should fail");
+
+        assert!(err.contains("Failed to deserialize JSON"), "{err}");
+    }
+
+    #[test]
+    fn tolerant_parser_survives_renamed_and_missing_fields() {
+        let time = test_support::day(1);
+        let content = br##"{"data": [
+            {"hash": "#a52714", "area": 100.5, "percent": "0.5", "type": "occupied_after_24_02_2022"},
+            {"color": "#bcaaa4", "areaKm2": "12", "areaType": "unspecified", "region": "east"},
+            {"hash": "#a52714", "percent": "0.1", "type": "occupied_after_24_02_2022"},
+            {"area": 5, "percent": 0.1, "type": "occupied_after_24_02_2022"},
+            {"hash": "#a52714", "color": "#bcaaa4", "area": 7, "type": "occupied_after_24_02_2022"},
+            {"hash": 7, "area": 7, "type": "occupied_after_24_02_2022"},
+            "garbage"
+        ]}"##;
+        let (areas, report) = parse_areas(content, time).expect("payload should parse");
+
+        assert_eq!(areas.len(), 2);
+        assert!((areas[1].area - 12.0).abs() < f64::EPSILON);
+        assert_eq!(areas[1].hash, "#bcaaa4");
+        assert_eq!(areas[1].area_type, "unspecified");
+        assert_eq!(report.skipped.get("area"), Some(&1));
+        assert_eq!(report.skipped.get("not an object"), Some(&1));
+        assert_eq!(report.skipped.get("missing hash"), Some(&1));
+        assert_eq!(report.skipped.get("duplicate field"), Some(&1));
+        assert_eq!(report.skipped.get("invalid field"), Some(&1));
+        assert_eq!(report.defaulted.get("percent"), Some(&1));
+        assert!(!report.defaulted.contains_key("hash"));
+        assert_eq!(report.unknown_fields.get("region"), Some(&1));
+    }
+}
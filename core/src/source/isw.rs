@@ -0,0 +1,349 @@
+//! Оценки контроля ISW/CTP: выгрузки слоя «Assessed Russian Control» в `GeoJSON`, сложенные
+//! в локальный каталог по одному файлу на день.
+//!
+//! Дата среза берётся из имени файла (`2024-06-30.geojson`, `ACR_20240630.geojson`), площадь —
+//! сумма площадей полигонов на сфере. Если в выгрузке несколько слоёв (оценённый контроль,
+//! продвижения, заявления), задаётся `layer`: берутся только объекты со свойством с этим
+//! значением. Площади складываются без объединения, поэтому объекты слоя не должны
+//! перекрываться, как в растворённом (`dissolve`) слое ISW; на перекрытия пишется
+//! предупреждение. Шейп-файлы читаются после перевода в `GeoJSON` (`ogr2ogr -f GeoJSON`).
+//! Слой ISW включает и территории, занятые до 24.02.2022; их площадь задаётся в `pre_war_km2`
+//! и вычитается, чтобы ряд был сравним с основным.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
+use tracing::{info, warn};
+
+use super::{AreaSink, FetchSummary, Fetcher, LatestSnapshot, MapGeoJson, MapSource};
+use crate::constants;
+use crate::data::Area;
+use crate::front;
+use crate::interrupt::Interrupt;
+use crate::series::{AREA_TYPE_OCCUPIED, DateRange};
+
+const ISW_HOME_URL: &str = "https://understandingwar.org/";
+/// Значение `hash` у строк из выгрузок ISW.
+pub const ISW_HASH: &str = "isw";
+const GEOJSON_EXTENSIONS: [&str; 2] = ["geojson", "json"];
+/// Форматы даты в имени файла и длина каждого.
+const FILE_DATE_FORMATS: [(&str, usize); 2] = [("%Y-%m-%d", 10), ("%Y%m%d", 8)];
+
+/// Каталог с выгрузками ISW/CTP; срезы читаются с диска, сеть не нужна.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IswSource {
+    pub dir: PathBuf,
+    /// Площадь, занятая до 24.02.2022 (Крым, часть Донецкой и Луганской областей), км².
+    pub pre_war_km2: f64,
+    /// Значение свойства объектов нужного слоя (`Assessed Russian Control`); `None` — все объекты.
+    pub layer: Option<String>,
+}
+
+impl IswSource {
+    /// Файлы `GeoJSON` каталога по датам из имён; файлы без даты пропускаются.
+    pub fn snapshots(&self) -> Result<BTreeMap<NaiveDate, PathBuf>, String> {
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|err| format!("Failed to read {}: {err}", self.dir.display()))?;
+        let mut snapshots = BTreeMap::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|err| format!("Failed to read {}: {err}", self.dir.display()))?
+                .path();
+            let is_geojson = path.extension().is_some_and(|extension| {
+                GEOJSON_EXTENSIONS
+                    .iter()
+                    .any(|known| extension.eq_ignore_ascii_case(known))
+            });
+            if !is_geojson {
+                continue;
+            }
+            let Some(date) = path
+                .file_stem()
+                .and_then(|stem| file_date(&stem.to_string_lossy()))
+            else {
+                warn!(file = %path.display(), "No date in the ISW file name, skipping");
+                continue;
+            };
+            if let Some(previous) = snapshots.insert(date, path.clone()) {
+                return Err(format!(
+                    "ISW files {} and {} are both dated {date}",
+                    previous.display(),
+                    path.display()
+                ));
+            }
+        }
+        Ok(snapshots)
+    }
+
+    /// Строка истории за день: занятая площадь за вычетом `pre_war_km2`.
+    pub fn read_areas(&self, date: NaiveDate, path: &Path) -> Result<Vec<Area>, String> {
+        let body =
+            fs::read(path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        let polygons = self
+            .layer
+            .as_ref()
+            .map_or_else(
+                || front::all_polygons(&body),
+                |layer| front::polygons_with_property(&body, layer),
+            )
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+        if polygons.is_empty() {
+            return Err(format!(
+                "{} has no polygons{}",
+                path.display(),
+                self.layer
+                    .as_ref()
+                    .map(|layer| format!(" in layer `{layer}`"))
+                    .unwrap_or_default()
+            ));
+        }
+        let overlaps = overlapping_pairs(&polygons);
+        if overlaps > 0 {
+            warn!(
+                file = %path.display(),
+                overlaps,
+                "ISW polygons overlap and their area is counted twice; set isw_layer or dissolve the layer"
+            );
+        }
+        let total = polygons.iter().map(front::Polygon::area_km2).sum::<f64>();
+        let area = (total - self.pre_war_km2).max(0.0);
+        Ok(vec![Area {
+            time_index: date.and_time(NaiveTime::MIN).and_utc(),
+            hash: ISW_HASH.to_string(),
+            area,
+            percent: constants::ukraine_percent(area),
+            area_type: AREA_TYPE_OCCUPIED.to_string(),
+        }])
+    }
+
+    fn map(date: NaiveDate, path: &Path) -> Result<MapGeoJson, String> {
+        let body =
+            fs::read(path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        Ok(MapGeoJson {
+            time: date.and_time(NaiveTime::MIN).and_utc(),
+            body: Bytes::from(body),
+        })
+    }
+}
+
+impl MapSource for IswSource {
+    fn name(&self) -> &'static str {
+        "ISW/CTP"
+    }
+
+    fn probe_url(&self) -> &'static str {
+        ISW_HOME_URL
+    }
+
+    async fn fetch_areas(
+        &self,
+        _fetcher: &Fetcher,
+        range: DateRange,
+        interrupt: &Interrupt,
+        sink: AreaSink,
+    ) -> Result<FetchSummary, String> {
+        let snapshots = self
+            .snapshots()?
+            .into_iter()
+            .filter(|(date, _)| range.contains(*date))
+            .collect::<Vec<_>>();
+        let total = snapshots.len();
+        info!(total, dir = %self.dir.display(), "Reading ISW snapshots");
+        let interrupt = interrupt.subscribe();
+        let mut fetched = 0usize;
+        for (date, path) in snapshots {
+            if *interrupt.borrow() {
+                return Ok(FetchSummary {
+                    fetched,
+                    total,
+                    interrupted: true,
+                });
+            }
+            match self.read_areas(date, &path) {
+                Ok(areas) => {
+                    if sink.send(areas).await.is_err() {
+                        return Err(format!(
+                            "Snapshot writer stopped after {fetched} of {total} snapshots"
+                        ));
+                    }
+                    fetched += 1;
+                }
+                Err(err) => warn!(error = %err, "Failed to read the ISW snapshot"),
+            }
+        }
+        Ok(FetchSummary {
+            fetched,
+            total,
+            interrupted: false,
+        })
+    }
+
+    async fn fetch_latest(&self, _fetcher: &Fetcher) -> Result<LatestSnapshot, String> {
+        let snapshots = self.snapshots()?;
+        let (date, path) = snapshots
+            .last_key_value()
+            .ok_or_else(|| format!("{} has no ISW snapshots", self.dir.display()))?;
+        Ok(LatestSnapshot {
+            time: date.and_time(NaiveTime::MIN).and_utc(),
+            areas: self.read_areas(*date, path)?,
+        })
+    }
+
+    async fn fetch_map_pair(
+        &self,
+        _fetcher: &Fetcher,
+        ago: TimeDelta,
+    ) -> Result<(MapGeoJson, MapGeoJson), String> {
+        let snapshots = self.snapshots()?;
+        let (&after_date, after_path) = snapshots
+            .last_key_value()
+            .ok_or_else(|| format!("{} has no ISW snapshots", self.dir.display()))?;
+        let (&before_date, before_path) = snapshots
+            .range(..=after_date - ago)
+            .next_back()
+            .ok_or_else(|| format!("ISW has no snapshot {ago} before {after_date}"))?;
+        Ok((
+            Self::map(before_date, before_path)?,
+            Self::map(after_date, after_path)?,
+        ))
+    }
+}
+
+/// Число пар перекрывающихся полигонов.
+fn overlapping_pairs(polygons: &[front::Polygon]) -> usize {
+    polygons
+        .iter()
+        .enumerate()
+        .map(|(idx, polygon)| {
+            polygons[idx + 1..]
+                .iter()
+                .filter(|other| polygon.overlaps(other))
+                .count()
+        })
+        .sum()
+}
+
+/// Первая дата `YYYY-MM-DD` или `YYYYMMDD` в имени файла.
+fn file_date(stem: &str) -> Option<NaiveDate> {
+    stem.char_indices().find_map(|(start, _)| {
+        FILE_DATE_FORMATS.iter().find_map(|(format, len)| {
+            let candidate = stem.get(start..start + len)?;
+            candidate
+                .bytes()
+                .all(|byte| byte.is_ascii_digit() || byte == b'-')
+                .then(|| NaiveDate::parse_from_str(candidate, format).ok())?
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, NaiveDate};
+    use reqwest::Client;
+
+    use super::{IswSource, file_date, overlapping_pairs};
+    use crate::fetch::FetchConfig;
+    use crate::front;
+    use crate::interrupt::Interrupt;
+    use crate::series::DateRange;
+    use crate::source::{Fetcher, MapSource, stream_snapshots};
+
+    /// Квадрат 1°×1° у 48° с. ш. и дыра в четверть его площади.
+    const SQUARE: &str = r#"{"type":"FeatureCollection","features":[
+        {"type":"Feature","properties":{},"geometry":{"type":"Polygon","coordinates":[
+            [[37,48],[38,48],[38,49],[37,49],[37,48]],
+            [[37.25,48.25],[37.75,48.25],[37.75,48.75],[37.25,48.75],[37.25,48.25]]]}}
+    ]}"#;
+
+    #[tokio::test]
+    async fn isw_files_become_daily_occupied_area_rows() {
+        assert_eq!(
+            file_date("ACR_20240630"),
+            NaiveDate::from_ymd_opt(2024, 6, 30)
+        );
+        assert_eq!(file_date("notes"), None);
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let dir = temp.path();
+        std::fs::write(dir.join("2024-06-29.geojson"), SQUARE).expect("snapshot");
+        std::fs::write(dir.join("ACR_20240630.geojson"), SQUARE).expect("snapshot");
+        std::fs::write(dir.join("readme.txt"), "not a map").expect("readme");
+
+        let source = IswSource {
+            dir: dir.to_path_buf(),
+            pre_war_km2: 1_000.0,
+            layer: None,
+        };
+        let range = DateRange {
+            from: NaiveDate::from_ymd_opt(2024, 6, 30),
+            ..DateRange::default()
+        };
+        let fetcher = Fetcher::new(Client::new(), FetchConfig::default());
+        let interrupt = Interrupt::listen();
+        let mut rows = Vec::new();
+        let summary = stream_snapshots(
+            |sink| source.fetch_areas(&fetcher, range, &interrupt, sink),
+            |areas| {
+                rows.extend(areas);
+                Ok(())
+            },
+        )
+        .await;
+
+        assert_eq!(summary.expect("local files are read").fetched, 1);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(
+            rows[0].time_index,
+            DateTime::parse_from_rfc3339("2024-06-30T00:00:00Z").expect("valid time")
+        );
+        // Квадрат 1°×1° у 48,5° с. ш. — около 8 200 км², без дыры три четверти.
+        let square = 111.195 * 111.195 * 48.5_f64.to_radians().cos();
+        assert!((rows[0].area - (square * 0.75 - 1_000.0)).abs() < 10.0);
+        assert_eq!(rows[0].area_type, "occupied_after_24_02_2022");
+    }
+
+    /// Слой контроля (квадрат 37–38° в. д.), перекрывающий его слой продвижений и отдельный
+    /// квадрат контроля восточнее.
+    const LAYERED: &str = r#"{"type":"FeatureCollection","features":[
+        {"type":"Feature","properties":{"layer":"Assessed Russian Control"},"geometry":{"type":"Polygon","coordinates":[
+            [[37,48],[38,48],[38,49],[37,49],[37,48]]]}},
+        {"type":"Feature","properties":{"layer":"Assessed Russian Advances"},"geometry":{"type":"Polygon","coordinates":[
+            [[37.5,48.5],[38.5,48.5],[38.5,49.5],[37.5,49.5],[37.5,48.5]]]}},
+        {"type":"Feature","properties":{"layer":"assessed russian control"},"geometry":{"type":"Polygon","coordinates":[
+            [[39,48],[40,48],[40,49],[39,49],[39,48]]]}}
+    ]}"#;
+
+    #[test]
+    fn layer_filter_keeps_assessed_control_and_overlaps_are_detected() {
+        let everything = front::all_polygons(LAYERED.as_bytes()).expect("valid GeoJSON");
+        assert_eq!(overlapping_pairs(&everything), 1);
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("2024-06-30.geojson");
+        std::fs::write(&path, LAYERED).expect("snapshot");
+        let source = IswSource {
+            dir: temp.path().to_path_buf(),
+            pre_war_km2: 0.0,
+            layer: Some("Assessed Russian Control".to_string()),
+        };
+        let date = NaiveDate::from_ymd_opt(2024, 6, 30).expect("valid date");
+        let rows = source.read_areas(date, &path).expect("layer is found");
+        let square = 111.195 * 111.195 * 48.5_f64.to_radians().cos();
+        assert!((rows[0].area / square - 2.0).abs() < 0.01);
+        let control = front::polygons_with_property(LAYERED.as_bytes(), "Assessed Russian Control")
+            .expect("valid GeoJSON");
+        assert_eq!(overlapping_pairs(&control), 0);
+
+        let missing = IswSource {
+            layer: Some("Claimed Russian Control".to_string()),
+            ..source
+        };
+        let err = missing
+            .read_areas(date, &path)
+            .expect_err("an absent layer is an error, not a zero area");
+        assert!(err.contains("Claimed Russian Control"));
+    }
+}
@@ -0,0 +1,137 @@
+//! Тестовый HTTP-сервер для проверки загрузки без сети: на каждый путь отвечает заранее
+//! заданными ответами по очереди (последний повторяется), считает запросы и наибольшее
+//! число одновременно обрабатываемых.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Ответ на один запрос.
+#[derive(Clone, Debug)]
+pub enum Reply {
+    /// Пустой ответ с кодом статуса.
+    Status(u16),
+    /// `200 OK` с телом.
+    Body(String),
+    /// `200 OK`, но соединение закрывается, не дослав тело до `Content-Length`.
+    Truncated(String),
+    /// `429 Too Many Requests` с `Retry-After` в секундах.
+    RateLimited(u64),
+    /// `200 OK` с телом после паузы: так видно, сколько запросов идёт одновременно.
+    Slow(Duration, String),
+}
+
+#[derive(Default)]
+struct Routes {
+    replies: HashMap<String, VecDeque<Reply>>,
+    hits: HashMap<String, usize>,
+    in_flight: usize,
+    peak_in_flight: usize,
+}
+
+pub struct MockServer {
+    base: String,
+    routes: Arc<Mutex<Routes>>,
+}
+
+impl MockServer {
+    pub async fn start(routes: Vec<(String, Vec<Reply>)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("mock server should bind");
+        let base = format!(
+            "http://{}",
+            listener.local_addr().expect("mock server has an address")
+        );
+        let routes = Arc::new(Mutex::new(Routes {
+            replies: routes
+                .into_iter()
+                .map(|(path, replies)| (path, replies.into()))
+                .collect(),
+            ..Routes::default()
+        }));
+        let shared = Arc::clone(&routes);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(respond(stream, Arc::clone(&shared)));
+            }
+        });
+        Self { base, routes }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base)
+    }
+
+    pub fn hits(&self, path: &str) -> usize {
+        self.routes
+            .lock()
+            .expect("routes lock")
+            .hits
+            .get(path)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Наибольшее число запросов, которые сервер обрабатывал одновременно.
+    pub fn peak_in_flight(&self) -> usize {
+        self.routes.lock().expect("routes lock").peak_in_flight
+    }
+}
+
+async fn respond(mut stream: TcpStream, routes: Arc<Mutex<Routes>>) {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        match stream.read(&mut buffer).await {
+            Ok(0) | Err(_) => return,
+            Ok(read) => request.extend_from_slice(&buffer[..read]),
+        }
+    }
+    let path = String::from_utf8_lossy(&request)
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_string();
+    let reply = {
+        let mut routes = routes.lock().expect("routes lock");
+        *routes.hits.entry(path.clone()).or_default() += 1;
+        routes.in_flight += 1;
+        routes.peak_in_flight = routes.peak_in_flight.max(routes.in_flight);
+        routes.replies.get_mut(&path).and_then(|queue| {
+            if queue.len() > 1 {
+                queue.pop_front()
+            } else {
+                queue.front().cloned()
+            }
+        })
+    };
+    if let Some(Reply::Slow(pause, _)) = &reply {
+        tokio::time::sleep(*pause).await;
+    }
+    // Запрос перестаёт считаться до отправки ответа: клиент не увидит тело раньше.
+    routes.lock().expect("routes lock").in_flight -= 1;
+    let response = match reply {
+        None => head(404, 0),
+        Some(Reply::Status(status)) => head(status, 0),
+        Some(Reply::Body(body) | Reply::Slow(_, body)) => {
+            format!("{}{body}", head(200, body.len()))
+        }
+        Some(Reply::Truncated(body)) => format!("{}{body}", head(200, body.len() * 2)),
+        Some(Reply::RateLimited(secs)) => {
+            head(429, 0).replacen("\r\n\r\n", &format!("\r\nRetry-After: {secs}\r\n\r\n"), 1)
+        }
+    };
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+fn head(status: u16, content_length: usize) -> String {
+    format!(
+        "HTTP/1.1 {status} Mock\r\nContent-Type: application/json\r\n\
+         Content-Length: {content_length}\r\nConnection: close\r\n\r\n"
+    )
+}
@@ -0,0 +1,453 @@
+//! Хранилище истории площадей: общий интерфейс и реализация поверх CSV.
+//!
+//! `rua serve` перечитывает CSV, пока запуск по расписанию или вручную его дописывает или
+//! заменяет. Чтобы сервер не прочитал половину дописанной строки, а два запуска не писали
+//! историю одновременно, [`CsvStore`] держит блокировку файла `.history.csv.rua-lock`:
+//! общую на чтение и исключительную на запись. Блокировка — `flock` в Linux и macOS и
+//! `LockFileEx` в Windows, поэтому её снимает сама система, если процесс упал.
+
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::data::{
+    Area, AreaSpool, CsvFormat, append_csv, append_spooled, export_csv, merge_with_spool,
+    replace_with_spool,
+};
+use crate::series::{self, AreaBuckets, DateRange};
+
+/// Хранилище срезов площадей, из которого строятся ряды для модели и отчёта.
+pub trait DataStore {
+    /// Черновик, в который выгрузка пишется по мере загрузки, не держа все срезы в памяти.
+    fn spool(&self) -> Result<AreaSpool, String>;
+
+    /// Полностью заменяет содержимое хранилища черновиком; ручные точки за дни,
+    /// которых в нём нет, сохраняются.
+    fn replace_from(&mut self, spool: AreaSpool) -> Result<(), String>;
+
+    /// Заменяет черновиком только дни из `range`; история за другие дни сохраняется.
+    fn merge_from(&mut self, spool: AreaSpool, range: DateRange) -> Result<(), String>;
+
+    /// Дописывает из черновика срезы новее сохранённых и возвращает их число.
+    fn append_from(&mut self, spool: AreaSpool) -> Result<usize, String>;
+
+    /// Полностью заменяет содержимое хранилища свежей выгрузкой (см. [`Self::replace_from`]).
+    fn replace(&mut self, areas: Vec<Area>) -> Result<(), String> {
+        let mut spool = self.spool()?;
+        spool.push(areas)?;
+        self.replace_from(spool)
+    }
+
+    /// Дописывает новые срезы к уже сохранённым.
+    fn append(&mut self, areas: Vec<Area>) -> Result<(), String>;
+
+    /// Загружает все срезы в дневные бакеты.
+    fn load_buckets(&self) -> Result<AreaBuckets, String>;
+
+    /// Загружает срезы за диапазон дат (границы включительно).
+    fn load_range(&self, range: DateRange) -> Result<AreaBuckets, String> {
+        let mut buckets = self.load_buckets()?;
+        buckets.retain_range(range);
+        Ok(buckets)
+    }
+
+    /// Время последнего сохранённого среза (`None` для пустого хранилища).
+    fn latest_timestamp(&self) -> Result<Option<DateTime<Utc>>, String>;
+}
+
+/// Окончание имени файла блокировки; такие файлы не публикуются вместе с сайтом.
+pub const LOCK_SUFFIX: &str = ".rua-lock";
+
+/// Файл блокировки для CSV: скрытый файл в том же каталоге.
+pub fn lock_path(csv_path: &Path) -> PathBuf {
+    let name = csv_path
+        .file_name()
+        .map_or_else(|| "history.csv".into(), |name| name.to_string_lossy());
+    csv_path.with_file_name(format!(".{name}{LOCK_SUFFIX}"))
+}
+
+/// Блокировка истории; снимается, когда значение выходит из области видимости.
+// Файл не читается: он держится только ради блокировки до `Drop`.
+struct HistoryLock(#[allow(dead_code)] Option<File>);
+
+impl HistoryLock {
+    /// Общая блокировка на чтение. Если файл блокировки не создать (каталог только для
+    /// чтения), история читается без неё: писать туда всё равно некому.
+    fn shared(csv_path: &Path) -> Result<Self, String> {
+        let path = lock_path(csv_path);
+        let Ok(file) = open_lock(&path) else {
+            return Ok(Self(None));
+        };
+        file.lock_shared()
+            .map_err(|err| format!("Failed to lock {}: {err}", path.display()))?;
+        Ok(Self(Some(file)))
+    }
+
+    /// Исключительная блокировка на запись; ждёт, пока читатели и другой запуск закончат.
+    fn exclusive(csv_path: &Path) -> Result<Self, String> {
+        let path = lock_path(csv_path);
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+        }
+        let file =
+            open_lock(&path).map_err(|err| format!("Failed to open {}: {err}", path.display()))?;
+        file.lock()
+            .map_err(|err| format!("Failed to lock {}: {err}", path.display()))?;
+        Ok(Self(Some(file)))
+    }
+}
+
+fn open_lock(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+}
+
+/// CSV-файл в формате экспорта API.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvStore {
+    path: PathBuf,
+    format: CsvFormat,
+}
+
+impl CsvStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            format: CsvFormat::default(),
+        }
+    }
+
+    /// Задаёт запись чисел в новых строках; уже сохранённые строки не переписываются.
+    #[must_use]
+    pub const fn with_format(mut self, format: CsvFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Пишет копию истории для публикации в `target` с записью чисел `format`; хранилище не
+    /// меняется. Копию держит та же блокировка, что и историю, так что `serve` не прочитает
+    /// её наполовину записанной.
+    pub fn export_to(&self, target: &Path, format: CsvFormat) -> Result<(), String> {
+        if target == self.path {
+            return Err(format!(
+                "Published copy {} must not replace the history store",
+                target.display()
+            ));
+        }
+        let _lock = HistoryLock::shared(&self.path)?;
+        let _target_lock = HistoryLock::exclusive(target)?;
+        export_csv(&self.path, target, format)
+    }
+
+    /// Время последнего среза без блокировки: её уже держит вызывающий.
+    fn read_latest_timestamp(&self) -> Result<Option<DateTime<Utc>>, String> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        series::latest_time_index(&self.path).map_err(|err| err.to_string())
+    }
+}
+
+impl DataStore for CsvStore {
+    fn spool(&self) -> Result<AreaSpool, String> {
+        AreaSpool::create(&self.path, self.format)
+    }
+
+    fn replace_from(&mut self, spool: AreaSpool) -> Result<(), String> {
+        let _lock = HistoryLock::exclusive(&self.path)?;
+        replace_with_spool(spool, &self.path)
+    }
+
+    fn merge_from(&mut self, spool: AreaSpool, range: DateRange) -> Result<(), String> {
+        let _lock = HistoryLock::exclusive(&self.path)?;
+        merge_with_spool(spool, &self.path, range)
+    }
+
+    fn append_from(&mut self, spool: AreaSpool) -> Result<usize, String> {
+        let _lock = HistoryLock::exclusive(&self.path)?;
+        let latest = self.read_latest_timestamp()?;
+        append_spooled(spool, &self.path, latest)
+    }
+
+    fn append(&mut self, areas: Vec<Area>) -> Result<(), String> {
+        let _lock = HistoryLock::exclusive(&self.path)?;
+        append_csv(&areas, &self.path, self.format)
+    }
+
+    fn load_buckets(&self) -> Result<AreaBuckets, String> {
+        let _lock = HistoryLock::shared(&self.path)?;
+        series::load_area_buckets_cached(&self.path).map_err(|err| err.to_string())
+    }
+
+    fn load_range(&self, range: DateRange) -> Result<AreaBuckets, String> {
+        let _lock = HistoryLock::shared(&self.path)?;
+        // Кэш рядов хранит уже усреднённые дни, поэтому срез на момент читается из CSV.
+        let mut buckets = range
+            .as_of
+            .map_or_else(
+                || series::load_area_buckets_cached(&self.path),
+                |as_of| series::load_area_buckets_as_of(&self.path, as_of),
+            )
+            .map_err(|err| err.to_string())?;
+        buckets.retain_range(range);
+        Ok(buckets)
+    }
+
+    fn latest_timestamp(&self) -> Result<Option<DateTime<Utc>>, String> {
+        let _lock = HistoryLock::shared(&self.path)?;
+        self.read_latest_timestamp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CsvStore, DataStore, HistoryLock, lock_path};
+    use crate::data::CsvFormat;
+    use crate::data::test_support::{self, area_row};
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use std::fs::{File, TryLockError};
+
+    #[test]
+    fn csv_store_appends_and_filters_by_range() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("store.csv");
+        let mut store = CsvStore::new(&path);
+        assert_eq!(store.latest_timestamp().expect("empty store"), None);
+
+        store
+            .append(vec![area_row(1, 100.0)])
+            .expect("first append should succeed");
+        store
+            .append(vec![area_row(2, 110.0), area_row(3, 120.0)])
+            .expect("second append should succeed");
+
+        let latest = store.latest_timestamp().expect("latest timestamp");
+        assert_eq!(latest, Some(test_support::day(3)));
+
+        let day = |day| NaiveDate::from_ymd_opt(2024, 5, day).expect("valid date");
+        let buckets = store
+            .load_range(crate::series::DateRange {
+                from: Some(day(2)),
+                to: None,
+                as_of: None,
+            })
+            .expect("range should load");
+        let (dates, _) = crate::series::build_occupied_series(&buckets).expect("series");
+        assert_eq!(dates, vec![day(2), day(3)]);
+    }
+
+    #[test]
+    fn csv_store_writes_time_index_like_display_and_rounds_floats() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("store_format.csv");
+        let rounded_path = path.with_extension("rounded.csv");
+        let times = [
+            Utc.with_ymd_and_hms(2024, 5, 1, 7, 3, 9)
+                .single()
+                .expect("valid timestamp"),
+            Utc.with_ymd_and_hms(2024, 5, 2, 0, 0, 0)
+                .single()
+                .expect("valid timestamp")
+                + chrono::TimeDelta::milliseconds(250),
+        ];
+        let rows = || {
+            times
+                .iter()
+                .map(|&time| crate::data::Area::new(time, "#a52714", 1_234.567_89, "occupied"))
+                .collect::<Vec<_>>()
+        };
+        CsvStore::new(&path).append(rows()).expect("append");
+        CsvStore::new(&rounded_path)
+            .with_format(CsvFormat {
+                area_precision: Some(2),
+                percent_precision: Some(2),
+            })
+            .append(rows())
+            .expect("append");
+        let written = std::fs::read_to_string(&path).expect("csv");
+        let rounded = std::fs::read_to_string(&rounded_path).expect("csv");
+
+        assert_eq!(
+            written,
+            format!(
+                "time_index,hash,area,percent,area_type\n\
+                 {},#a52714,1234.56789,0,occupied\n\
+                 {},#a52714,1234.56789,0,occupied\n",
+                times[0], times[1]
+            )
+        );
+        assert!(rounded.contains("2024-05-01 07:03:09 UTC,#a52714,1234.57,0.00,occupied\n"));
+    }
+
+    #[test]
+    fn published_copy_is_rounded_and_leaves_the_store_as_is() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("data/history.csv");
+        let published = temp.path().join("dist/history.csv");
+        let time = Utc
+            .with_ymd_and_hms(2024, 5, 1, 12, 0, 0)
+            .single()
+            .expect("valid timestamp");
+        let mut store = CsvStore::new(&path);
+        store
+            .append(vec![crate::data::Area {
+                percent: 19.272_727_3,
+                ..crate::data::Area::new(time, "#a52714", 116_320.567_891, "occupied")
+            }])
+            .expect("append");
+        let format = CsvFormat::published(crate::constants::Precision::default());
+        store.export_to(&published, format).expect("export");
+
+        let raw = std::fs::read_to_string(&path).expect("store");
+        let copy = std::fs::read_to_string(&published).expect("copy");
+        assert!(
+            raw.ends_with(",#a52714,116320.567891,19.2727273,occupied\n"),
+            "{raw}"
+        );
+        assert!(
+            copy.ends_with(",#a52714,116320.57,19.2727,occupied\n"),
+            "{copy}"
+        );
+        assert!(store.export_to(&path, format).is_err());
+    }
+
+    #[test]
+    fn csv_store_replace_keeps_manual_rows_for_missing_days() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("store_manual.csv");
+        let mut store = CsvStore::new(&path);
+        let day = |day| NaiveDate::from_ymd_opt(2024, 5, day).expect("valid date");
+        let manual =
+            |date, area| crate::data::Area::manual(date, area, crate::series::AREA_TYPE_OCCUPIED);
+        store
+            .append(vec![
+                area_row(1, 100.0),
+                manual(day(2), 105.0),
+                manual(day(3), 110.0),
+            ])
+            .expect("append should succeed");
+        store
+            .replace(vec![area_row(1, 100.0), area_row(2, 104.0)])
+            .expect("replace should succeed");
+
+        let buckets = store.load_buckets().expect("store should load");
+        let (dates, values) = crate::series::build_occupied_series(&buckets).expect("series");
+        assert_eq!(dates, vec![day(1), day(2), day(3)]);
+        assert_eq!(values, vec![100.0, 104.0, 110.0]);
+        assert_eq!(
+            buckets.manual_days().iter().copied().collect::<Vec<_>>(),
+            vec![day(3)]
+        );
+    }
+
+    #[test]
+    fn ranged_merge_replaces_only_days_in_range() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("store_merge.csv");
+        let mut store = CsvStore::new(&path);
+        store
+            .append(vec![
+                area_row(1, 100.0),
+                area_row(2, 104.0),
+                area_row(3, 110.0),
+                area_row(4, 120.0),
+            ])
+            .expect("append should succeed");
+
+        let day = |day| NaiveDate::from_ymd_opt(2024, 5, day).expect("valid date");
+        let mut spool = store.spool().expect("spool should open");
+        // Срезы приходят не по порядку: загрузка идёт параллельно.
+        spool
+            .push(vec![area_row(3, 112.0), area_row(2, 106.0)])
+            .expect("push should succeed");
+        let range = crate::series::DateRange {
+            from: Some(day(2)),
+            to: Some(day(3)),
+            ..crate::series::DateRange::default()
+        };
+        store
+            .merge_from(spool, range)
+            .expect("merge should succeed");
+
+        let buckets = store.load_buckets().expect("store should load");
+        let (dates, values) = crate::series::build_occupied_series(&buckets).expect("series");
+        assert_eq!(dates, vec![day(1), day(2), day(3), day(4)]);
+        assert_eq!(values, vec![100.0, 106.0, 112.0, 120.0]);
+        let written = std::fs::read_to_string(&path).expect("csv");
+        let times = written
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').next().unwrap_or_default())
+            .collect::<Vec<_>>();
+        let mut sorted = times.clone();
+        sorted.sort_unstable();
+        assert_eq!(times, sorted, "merged history must stay in time order");
+    }
+
+    #[test]
+    fn abandoned_spool_leaves_history_untouched() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("store_spool.csv");
+        let partial = path.with_extension("csv.partial");
+        let mut store = CsvStore::new(&path);
+        store
+            .append(vec![area_row(1, 100.0)])
+            .expect("append should succeed");
+
+        let mut spool = store.spool().expect("spool should open");
+        spool
+            .push(vec![area_row(2, 110.0)])
+            .expect("push should succeed");
+        assert!(partial.exists());
+        drop(spool);
+        assert!(!partial.exists());
+
+        let mut spool = store.spool().expect("spool should open");
+        spool
+            .push(vec![area_row(1, 100.0), area_row(3, 120.0)])
+            .expect("push should succeed");
+        assert_eq!(store.append_from(spool).expect("append from spool"), 1);
+        let buckets = store.load_buckets().expect("store should load");
+        assert!(!partial.exists());
+        let day = |day| NaiveDate::from_ymd_opt(2024, 5, day).expect("valid date");
+        assert!(buckets.has_day(day(1)) && buckets.has_day(day(3)));
+        assert!(
+            !buckets.has_day(day(2)),
+            "abandoned spool must not reach the store"
+        );
+    }
+
+    #[test]
+    fn readers_hold_off_writers_until_they_finish() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("store_lock.csv");
+        let lock = lock_path(&path);
+        assert_eq!(lock, path.with_file_name(".store_lock.csv.rua-lock"));
+
+        let reader = HistoryLock::shared(&path).expect("shared lock");
+        // Второй читатель не ждёт первого, а писатель ждёт.
+        let other_reader = HistoryLock::shared(&path).expect("second shared lock");
+        let writer = File::open(&lock).expect("lock file exists");
+        assert!(matches!(writer.try_lock(), Err(TryLockError::WouldBlock)));
+        drop((reader, other_reader));
+        writer
+            .try_lock()
+            .expect("writer gets the lock once readers are done");
+        drop(writer);
+
+        let mut store = CsvStore::new(&path);
+        store
+            .append(vec![area_row(1, 100.0)])
+            .expect("append takes the exclusive lock");
+        let latest = store.latest_timestamp();
+        assert!(latest.expect("latest timestamp").is_some());
+    }
+}
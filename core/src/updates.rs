@@ -0,0 +1,171 @@
+//! Журнал обновлений данных: по записи на каждую сборку отчёта в `.rua-updates`
+//! рядом со страницей обновлений. Запись хранит число новых срезов, изменение площади
+//! с прошлой сборки и дни, значения которых источник пересмотрел задним числом.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::series::{AreaBuckets, build_occupied_and_unspecified_series};
+
+/// Имя журнала; лежит рядом со страницей обновлений, но не публикуется.
+pub const LOG_FILE_NAME: &str = ".rua-updates";
+/// Расхождение меньше этого (км²) считаем шумом округления, а не пересмотром.
+const REVISION_TOLERANCE_KM2: f64 = 1.0;
+
+/// Одна сборка отчёта.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BuildRecord {
+    pub built_at: DateTime<Utc>,
+    /// Последний день с данными.
+    pub latest_date: NaiveDate,
+    /// Срезы, появившиеся после последнего среза прошлой сборки.
+    pub new_snapshots: usize,
+    /// Занятая площадь на последний день, км².
+    pub occupied_km2: f64,
+    /// Изменение площади с прошлой сборки, км² (`None` для первой записи).
+    pub net_change_km2: Option<f64>,
+    /// Дни, значения которых изменились по сравнению с прошлой сборкой.
+    pub revised_days: Vec<NaiveDate>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UpdateLog {
+    builds: Vec<BuildRecord>,
+    /// Время последнего среза на момент прошлой сборки.
+    latest_snapshot: Option<DateTime<Utc>>,
+    /// Измеренные дневные значения прошлой сборки, км²: база для поиска пересмотров.
+    days: BTreeMap<NaiveDate, f64>,
+}
+
+/// Путь журнала для страницы обновлений `output_html`.
+pub fn log_path_for(output_html: &Path) -> PathBuf {
+    output_html.with_file_name(LOG_FILE_NAME)
+}
+
+/// Дописывает запись о сборке и возвращает весь журнал (от старых сборок к новым).
+pub fn record_build(
+    log_path: &Path,
+    buckets: &AreaBuckets,
+    built_at: DateTime<Utc>,
+) -> Result<Vec<BuildRecord>, String> {
+    let mut log = match fs::read_to_string(log_path) {
+        Ok(raw) => serde_json::from_str::<UpdateLog>(&raw)
+            .map_err(|err| format!("Failed to parse {}: {err}", log_path.display()))?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => UpdateLog::default(),
+        Err(err) => return Err(format!("Failed to read {}: {err}", log_path.display())),
+    };
+    log.append(buckets, built_at)?;
+
+    let raw = serde_json::to_string_pretty(&log)
+        .map_err(|err| format!("Failed to serialize update log: {err}"))?;
+    fs::write(log_path, raw)
+        .map_err(|err| format!("Failed to write {}: {err}", log_path.display()))?;
+    Ok(log.builds)
+}
+
+impl UpdateLog {
+    fn append(&mut self, buckets: &AreaBuckets, built_at: DateTime<Utc>) -> Result<(), String> {
+        let series =
+            build_occupied_and_unspecified_series(buckets).map_err(|err| err.to_string())?;
+        let days = series
+            .dates
+            .iter()
+            .zip(&series.occupied)
+            .zip(&series.measured)
+            .filter(|(_, measured)| **measured)
+            .map(|((date, value), _)| (*date, *value))
+            .collect::<BTreeMap<_, _>>();
+        let (Some(&latest_date), Some(&occupied_km2)) =
+            (series.dates.last(), series.occupied.last())
+        else {
+            return Err("History is empty".to_string());
+        };
+
+        let snapshots = buckets.snapshots();
+        let new_snapshots = snapshots
+            .iter()
+            .filter(|snapshot| {
+                self.latest_snapshot
+                    .is_none_or(|latest| snapshot.time > latest)
+            })
+            .count();
+        let revised_days = self
+            .days
+            .iter()
+            .filter(|(date, previous)| {
+                days.get(date)
+                    .is_some_and(|current| (current - *previous).abs() >= REVISION_TOLERANCE_KM2)
+            })
+            .map(|(date, _)| *date)
+            .collect();
+
+        self.builds.push(BuildRecord {
+            built_at,
+            latest_date,
+            new_snapshots,
+            occupied_km2,
+            net_change_km2: self
+                .builds
+                .last()
+                .map(|previous| occupied_km2 - previous.occupied_km2),
+            revised_days,
+        });
+        self.latest_snapshot = snapshots
+            .last()
+            .map(|snapshot| snapshot.time)
+            .or(self.latest_snapshot);
+        self.days = days;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, NaiveDate};
+
+    use super::UpdateLog;
+    use crate::series::load_area_buckets;
+
+    #[test]
+    fn log_counts_new_snapshots_and_detects_revisions() {
+        let first = "time_index,hash,area,percent,area_type\n\
+2024-03-01 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-03-02 00:00:00 UTC,#a52714,110.0,0.0,occupied_after_24_02_2022\n";
+        let second = "time_index,hash,area,percent,area_type\n\
+2024-03-01 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-03-02 00:00:00 UTC,#a52714,114.0,0.0,occupied_after_24_02_2022\n\
+2024-03-03 00:00:00 UTC,#a52714,120.0,0.0,occupied_after_24_02_2022\n\
+2024-03-04 00:00:00 UTC,#a52714,125.0,0.0,occupied_after_24_02_2022\n";
+        let built_at = DateTime::from_timestamp(1_709_600_000, 0).expect("valid timestamp");
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("updates.csv");
+
+        let mut log = UpdateLog::default();
+        for csv in [first, second] {
+            std::fs::write(&path, csv).expect("history should be written");
+            let buckets = load_area_buckets(&path).expect("history should load");
+            log.append(&buckets, built_at)
+                .expect("build should be recorded");
+        }
+
+        let [initial, update] = log.builds.as_slice() else {
+            panic!("expected two builds");
+        };
+        assert_eq!(initial.new_snapshots, 2);
+        assert_eq!(initial.net_change_km2, None);
+        assert_eq!(update.new_snapshots, 2);
+        assert_eq!(
+            update.latest_date,
+            NaiveDate::from_ymd_opt(2024, 3, 4).expect("valid date")
+        );
+        assert_eq!(update.net_change_km2, Some(15.0));
+        assert_eq!(
+            update.revised_days,
+            vec![NaiveDate::from_ymd_opt(2024, 3, 2).expect("valid date")]
+        );
+    }
+}
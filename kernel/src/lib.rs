@@ -0,0 +1,234 @@
+//! Расчётное ядро графика: суточное изменение, сглаживание и прореживание рядов.
+//!
+//! Крейт использует только `core` и `alloc`, без ввода-вывода и внешних зависимостей: на нём
+//! строятся графики `rua-report` и WASM-пакет `rua-wasm` (каталог `wasm/`), которым страница
+//! пересчитывает нижнюю панель без пересборки HTML. Вещественная арифметика — только
+//! `+ - * /`: `mul_add` и `powi` в `core` недоступны.
+#![no_std]
+#![allow(clippy::suboptimal_flops)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Изменение к предыдущему дню; первый день — ноль.
+#[must_use]
+pub fn daily_change_series(values: &[f64]) -> Vec<f64> {
+    if values.is_empty() {
+        return Vec::new();
+    }
+    let mut changes = Vec::with_capacity(values.len());
+    changes.push(0.0);
+    changes.extend(values.windows(2).map(|pair| pair[1] - pair[0]));
+    changes
+}
+
+/// Центрированное скользящее среднее; `None` — в окне меньше `min_periods` значений.
+#[must_use]
+pub fn moving_average(values: &[f64], window: usize, min_periods: usize) -> Vec<Option<f64>> {
+    centered(values, window, min_periods, |window, _| {
+        window.iter().sum::<f64>() / to_f64(window.len())
+    })
+}
+
+/// Локальная линейная регрессия с трикубическими весами по центрированному окну.
+#[must_use]
+pub fn local_linear(values: &[f64], window: usize, min_periods: usize) -> Vec<Option<f64>> {
+    centered(values, window, min_periods, local_linear_at)
+}
+
+/// Экспоненциальное среднее по прошлым дням с `alpha = 2 / (window + 1)`.
+#[must_use]
+pub fn exponential_average(values: &[f64], window: usize, min_periods: usize) -> Vec<Option<f64>> {
+    if window == 0 {
+        return values.iter().map(|_| None).collect();
+    }
+    let alpha = 2.0 / (to_f64(window) + 1.0);
+    let mut level = None;
+    values
+        .iter()
+        .enumerate()
+        .map(|(idx, &value)| {
+            let next = level.map_or(value, |level: f64| level + alpha * (value - level));
+            level = Some(next);
+            (idx + 1 >= min_periods).then_some(next)
+        })
+        .collect()
+}
+
+/// Применяет `estimate(окно, позиция текущего дня в окне)` к окну с центром в каждом дне.
+fn centered(
+    values: &[f64],
+    window: usize,
+    min_periods: usize,
+    estimate: fn(&[f64], usize) -> f64,
+) -> Vec<Option<f64>> {
+    if window == 0 || values.is_empty() {
+        return values.iter().map(|_| None).collect();
+    }
+    let radius = window / 2;
+    (0..values.len())
+        .map(|idx| {
+            let start = idx.saturating_sub(radius);
+            let end = (idx + radius).min(values.len() - 1);
+            (end - start + 1 >= min_periods).then(|| estimate(&values[start..=end], idx - start))
+        })
+        .collect()
+}
+
+/// Значение взвешенной прямой в точке `at`; веса `(1 - |d/h|³)³`, где `h` на день больше
+/// самого дальнего расстояния в окне, чтобы крайние дни не получали нулевой вес.
+fn local_linear_at(values: &[f64], at: usize) -> f64 {
+    let reach = to_f64(at.max(values.len() - 1 - at)) + 1.0;
+    let weight = |offset: f64| {
+        let distance = if offset < 0.0 { -offset } else { offset } / reach;
+        let tail = 1.0 - distance * distance * distance;
+        tail * tail * tail
+    };
+    let offset = |idx: usize| to_f64(idx) - to_f64(at);
+    let (mut weight_sum, mut x_sum, mut y_sum) = (0.0, 0.0, 0.0);
+    for (idx, &value) in values.iter().enumerate() {
+        let w = weight(offset(idx));
+        weight_sum += w;
+        x_sum += w * offset(idx);
+        y_sum += w * value;
+    }
+    let x_mean = x_sum / weight_sum;
+    let y_mean = y_sum / weight_sum;
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (idx, &value) in values.iter().enumerate() {
+        let w = weight(offset(idx));
+        let dx = offset(idx) - x_mean;
+        covariance += w * dx * (value - y_mean);
+        variance += w * dx * dx;
+    }
+    if variance > f64::EPSILON {
+        // Прямая проходит через взвешенный центр; в точке `at` смещение равно нулю.
+        y_mean - covariance / variance * x_mean
+    } else {
+        y_mean
+    }
+}
+
+fn to_f64(value: usize) -> f64 {
+    u32::try_from(value).map_or_else(|_| f64::from(u32::MAX), f64::from)
+}
+
+/// Объединяет два отсортированных набора индексов без повторов.
+#[must_use]
+pub fn merge_sorted_indices(left: &[usize], right: &[usize]) -> Vec<usize> {
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left_idx = 0usize;
+    let mut right_idx = 0usize;
+
+    while left_idx < left.len() && right_idx < right.len() {
+        match left[left_idx].cmp(&right[right_idx]) {
+            Ordering::Less => {
+                merged.push(left[left_idx]);
+                left_idx += 1;
+            }
+            Ordering::Greater => {
+                merged.push(right[right_idx]);
+                right_idx += 1;
+            }
+            Ordering::Equal => {
+                merged.push(left[left_idx]);
+                left_idx += 1;
+                right_idx += 1;
+            }
+        }
+    }
+
+    merged.extend_from_slice(&left[left_idx..]);
+    merged.extend_from_slice(&right[right_idx..]);
+
+    merged.dedup();
+    merged
+}
+
+/// Индексы точек, сохраняющих минимум и максимум каждого бакета, плюс крайние точки.
+#[must_use]
+pub fn downsample_min_max_indices(y: &[f64], max_points: usize) -> Vec<usize> {
+    if y.len() <= max_points || max_points < 3 {
+        return (0..y.len()).collect();
+    }
+
+    let len = y.len();
+    let mut indices = Vec::with_capacity(max_points);
+    indices.push(0);
+
+    let interior_len = len.saturating_sub(2);
+    let max_pairs = max_points.saturating_sub(2);
+    let bucket_count = (max_pairs / 2).max(1);
+    let bucket_size = interior_len.div_ceil(bucket_count);
+
+    if bucket_size != 0 {
+        let mut start = 1usize;
+        while start < len - 1 {
+            let end = (start + bucket_size).min(len - 1);
+            let mut min_idx = start;
+            let mut max_idx = start;
+            let mut min_val = y[start];
+            let mut max_val = y[start];
+
+            for (idx, &val) in y.iter().enumerate().take(end).skip(start + 1) {
+                if val < min_val {
+                    min_val = val;
+                    min_idx = idx;
+                }
+                if val > max_val {
+                    max_val = val;
+                    max_idx = idx;
+                }
+            }
+
+            match min_idx.cmp(&max_idx) {
+                Ordering::Equal => indices.push(min_idx),
+                Ordering::Less => {
+                    indices.push(min_idx);
+                    indices.push(max_idx);
+                }
+                Ordering::Greater => {
+                    indices.push(max_idx);
+                    indices.push(min_idx);
+                }
+            }
+
+            start = end;
+        }
+    }
+    indices.push(len - 1);
+
+    indices.sort_unstable();
+    indices.dedup();
+
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{daily_change_series, exponential_average, local_linear, moving_average};
+
+    #[test]
+    fn smoothers_follow_their_windows_and_min_periods() {
+        let values = [0.0, 10.0, 20.0, 30.0, 40.0];
+        assert_eq!(daily_change_series(&values), [0.0, 10.0, 10.0, 10.0, 10.0]);
+        assert_eq!(
+            moving_average(&values, 3, 3),
+            [None, Some(10.0), Some(20.0), Some(30.0), None]
+        );
+
+        // На прямой LOESS воспроизводит значения даже у края окна.
+        for (smoothed, actual) in local_linear(&values, 5, 2).iter().zip(values) {
+            let smoothed = smoothed.expect("window has enough values");
+            assert!((smoothed - actual).abs() < 1e-9);
+        }
+
+        // span 3 → alpha 0.5; первый день ниже min_periods.
+        assert_eq!(
+            exponential_average(&[0.0, 8.0, 0.0], 3, 2),
+            [None, Some(4.0), Some(2.0)]
+        );
+    }
+}
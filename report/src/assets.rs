@@ -0,0 +1,117 @@
+//! Скрипты страницы отдельными файлами в `assets/` рядом с HTML. Имя файла содержит хэш
+//! содержимого: такие файлы можно кэшировать навсегда, а изменённый скрипт получает новое имя.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::integrity::sha256_hex;
+
+/// Каталог ассетов относительно страницы.
+pub const ASSETS_DIR: &str = "assets";
+/// Сколько шестнадцатеричных знаков хэша попадает в имя файла.
+const HASH_LEN: usize = 8;
+
+/// Поведение основной страницы: графики, вкладки, живое обновление, таблицы.
+pub const PAGE_SCRIPT: Asset = Asset {
+    stem: "page",
+    extension: "js",
+    body: include_str!("assets/page.js"),
+};
+/// События аналитики; подключается только при `analytics_events`.
+pub const ANALYTICS_SCRIPT: Asset = Asset {
+    stem: "analytics",
+    extension: "js",
+    body: include_str!("assets/analytics.js"),
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Asset {
+    stem: &'static str,
+    extension: &'static str,
+    body: &'static str,
+}
+
+impl Asset {
+    /// `page.1a2b3c4d.js`.
+    pub(super) fn file_name(&self) -> String {
+        let hash = sha256_hex(self.body.as_bytes());
+        format!("{}.{}.{}", self.stem, &hash[..HASH_LEN], self.extension)
+    }
+
+    /// Относительная ссылка со страницы.
+    pub(super) fn href(&self) -> String {
+        format!("{ASSETS_DIR}/{}", self.file_name())
+    }
+
+    /// Прежние версии того же ассета: `stem.<хэш>.extension` с другим хэшем.
+    fn is_stale(&self, file_name: &str) -> bool {
+        file_name != self.file_name()
+            && file_name
+                .strip_prefix(self.stem)
+                .and_then(|rest| rest.strip_prefix('.'))
+                .and_then(|rest| rest.strip_suffix(self.extension))
+                .and_then(|hash| hash.strip_suffix('.'))
+                .is_some_and(|hash| {
+                    hash.len() == HASH_LEN && hash.bytes().all(|byte| byte.is_ascii_hexdigit())
+                })
+    }
+}
+
+/// Пишет `assets` в `<output_dir>/assets/` и удаляет их прежние версии, чтобы каталог
+/// сайта не копил устаревшие скрипты между сборками.
+pub fn write_assets(output_dir: &Path, assets: &[Asset]) -> io::Result<()> {
+    let dir = output_dir.join(ASSETS_DIR);
+    fs::create_dir_all(&dir)?;
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if assets
+            .iter()
+            .any(|asset| asset.is_stale(&name.to_string_lossy()))
+        {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    for asset in assets {
+        fs::write(dir.join(asset.file_name()), asset.body)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PAGE_SCRIPT, write_assets};
+
+    #[test]
+    fn assets_are_hashed_and_replace_previous_versions() {
+        let name = PAGE_SCRIPT.file_name();
+        let hash = name
+            .strip_prefix("page.")
+            .and_then(|rest| rest.strip_suffix(".js"))
+            .expect("name should keep stem and extension");
+        assert_eq!(hash.len(), 8);
+        assert_eq!(PAGE_SCRIPT.href(), format!("assets/{name}"));
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let dir = temp.path();
+        let assets = dir.join("assets");
+        std::fs::create_dir_all(&assets).expect("assets dir should be created");
+        std::fs::write(assets.join("page.00000000.js"), "old").expect("old asset written");
+        std::fs::write(assets.join("page.vendor.js"), "keep").expect("other file written");
+
+        write_assets(dir, &[PAGE_SCRIPT]).expect("assets should be written");
+        let mut files = std::fs::read_dir(&assets)
+            .expect("assets dir should be readable")
+            .map(|entry| {
+                entry
+                    .expect("entry")
+                    .file_name()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, vec![name, "page.vendor.js".to_string()]);
+    }
+}
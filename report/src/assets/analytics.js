@@ -0,0 +1,25 @@
+// События аналитики: клики по ссылкам с `data-track` и смена сортировки таблиц. События уходят
+// в `plausible()` или `umami.track()`, если их подключил `analytics_html`, иначе — POST-запросом
+// на адрес из `data-endpoint` тега скрипта.
+(() => {
+    const endpoint = document.currentScript && document.currentScript.dataset.endpoint;
+    const send = (name, props) => {
+        if (typeof window.plausible === 'function') {
+            window.plausible(name, { props });
+        } else if (window.umami && typeof window.umami.track === 'function') {
+            window.umami.track(name, props);
+        } else if (endpoint) {
+            const body = JSON.stringify({ name, url: location.href, domain: location.hostname, props });
+            if (!(navigator.sendBeacon && navigator.sendBeacon(endpoint, body))) {
+                fetch(endpoint, { method: 'POST', body, keepalive: true }).catch(() => {});
+            }
+        }
+    };
+    document.querySelectorAll('a[data-track]').forEach(link => {
+        link.addEventListener('click', () => send('Download', { file: link.dataset.track }));
+    });
+    const select = document.getElementById('ratio-sort');
+    if (select) {
+        select.addEventListener('change', () => send('Sort', { by: select.value }));
+    }
+})();
@@ -0,0 +1,693 @@
+// Поведение основной страницы. Скрипт подключается с `defer` после Plotly и выполняется
+// до `DOMContentLoaded`; каждый блок сам проверяет, есть ли на странице его разметка.
+
+// Живое обновление в режиме `serve`: на статической копии `/v1/summary` отвечает 404,
+// и подписка на события не создаётся.
+(() => {
+    if (!window.EventSource || !location.protocol.startsWith('http')) return;
+    const api = new URL('v1/', location.href);
+    const setText = (id, text) => {
+        const element = document.getElementById(id);
+        if (element) element.textContent = text;
+    };
+    // Площади в сводке перерисовывает переключатель единиц по `data-area-km2`.
+    const setArea = (id, km2) => {
+        const element = document.querySelector(`#${id} [data-area-km2]`);
+        if (element) element.dataset.areaKm2 = km2 === null || km2 === undefined ? '' : String(km2);
+    };
+    // График построен в единице из конфигурации: она выбрана в переключателе по умолчанию.
+    const chartUnit = document.querySelector('#area-unit option[selected]');
+    const decimalSeparator = document.getElementById('area-unit')?.dataset.decimal || ',';
+    const chartKm2PerUnit = chartUnit ? Number(chartUnit.dataset.km2) : 1000;
+    // Ось могли переключить на долю площади Украины: новые точки пересчитываются так же.
+    const chartValue = (graph, km2) => (km2 / chartKm2PerUnit) * Number(graph.dataset.axisScale || 1);
+    const PROVISIONAL = 'Предварительно';
+    // Срез с карты, которого ещё нет в истории, — отдельный маркер; без него маркер убирается.
+    const showProvisional = point => {
+        const graph = document.getElementById('area-plot');
+        if (!graph || typeof Plotly === 'undefined') return;
+        if (!graph.data) {
+            graph.addEventListener('rua:rendered', () => showProvisional(point), { once: true });
+            return;
+        }
+        const previous = graph.data.findIndex(trace => trace.name === PROVISIONAL);
+        if (previous >= 0) Plotly.deleteTraces(graph, previous);
+        if (!point) return;
+        const actual = graph.data.find(trace => trace.name === 'Факт');
+        Plotly.addTraces(graph, {
+            type: 'scatter',
+            mode: 'markers',
+            name: PROVISIONAL,
+            x: [point.date],
+            y: [chartValue(graph, point.occupied_km2)],
+            marker: { symbol: 'circle-open', size: 10, color: actual?.line?.color },
+            hovertemplate: `%{y}<br>${PROVISIONAL}: срез ${point.time}, ещё не в истории<extra></extra>`
+        });
+    };
+    const applyUpdate = update => {
+        const summary = update.summary;
+        setArea('summary-area', summary.occupied_km2);
+        setText('summary-percent', `${summary.ukraine_percent.toFixed(2).replace('.', decimalSeparator)}%`);
+        setArea('summary-daily', summary.daily_change_km2);
+        setArea('summary-weekly', summary.weekly_change_km2);
+        setText('summary-date', summary.latest_date);
+        document.dispatchEvent(new CustomEvent('rua:areas'));
+        showProvisional(summary.provisional);
+
+        const graph = document.getElementById('area-plot');
+        if (!graph || !graph.data || typeof Plotly === 'undefined') return;
+        if (!update.points.length) return;
+        const actual = graph.data.findIndex(trace => trace.name === 'Факт');
+        if (actual < 0) return;
+        Plotly.extendTraces(graph, {
+            x: [update.points.map(point => point.date)],
+            y: [update.points.map(point => chartValue(graph, point.occupied_km2))]
+        }, [actual]);
+    };
+    fetch(new URL('summary', api))
+        .then(response => {
+            if (!response.ok) return;
+            const events = new EventSource(new URL('events', api));
+            events.addEventListener('update', event => applyUpdate(JSON.parse(event.data)));
+            return response.json().then(summary => showProvisional(summary.provisional));
+        })
+        .catch(() => {});
+})();
+
+// Графики строятся после разбора страницы: видимый — в простое браузера, остальные — при
+// подлёте к области просмотра.
+(() => {
+    const idle = callback => window.requestIdleCallback
+        ? window.requestIdleCallback(callback, { timeout: 1500 })
+        : setTimeout(callback, 1);
+    const renderPlot = container => {
+        const source = document.getElementById(`${container.id}-figure`);
+        if (!source || container.dataset.rendered) return;
+        container.dataset.rendered = 'true';
+        const figure = JSON.parse(source.textContent);
+        figure.layout = figure.layout || {};
+        figure.layout.xaxis = figure.layout.xaxis || {};
+        figure.layout.yaxis = figure.layout.yaxis || {};
+        // Обработчики `rua:figure` могут поправить фигуру до отрисовки.
+        container.dispatchEvent(new CustomEvent('rua:figure', { detail: figure }));
+        Plotly.newPlot(container, figure)
+            .then(() => {
+                container.classList.remove('plot-skeleton');
+                container.removeAttribute('role');
+                container.removeAttribute('aria-label');
+                container.dispatchEvent(new CustomEvent('rua:rendered'));
+            })
+            .catch(() => {});
+    };
+    const showStaticFallback = () => {
+        document.querySelectorAll('.plot-skeleton, .chart-tabs').forEach(element => {
+            element.hidden = true;
+        });
+        const fallback = document.getElementById('static-chart-fallback');
+        if (fallback) fallback.insertAdjacentHTML('afterend', fallback.textContent);
+    };
+
+    window.addEventListener('DOMContentLoaded', () => {
+        // CDN Plotly недоступен: показываем статичную копию из <noscript>.
+        if (typeof Plotly === 'undefined') {
+            showStaticFallback();
+            return;
+        }
+        const containers = Array.from(document.querySelectorAll('.plot-skeleton'));
+        if (!window.IntersectionObserver) {
+            containers.forEach(container => idle(() => renderPlot(container)));
+            return;
+        }
+        const observer = new IntersectionObserver(entries => {
+            entries.filter(entry => entry.isIntersecting).forEach(entry => {
+                observer.unobserve(entry.target);
+                idle(() => renderPlot(entry.target));
+            });
+        }, { rootMargin: '200px 0px' });
+        containers.forEach(container => observer.observe(container));
+    });
+
+    // Вкладки основного графика: скрытая панель строится при первом показе.
+    const tabs = Array.from(document.querySelectorAll('[data-chart-tab]'));
+    tabs.forEach(tab => tab.addEventListener('click', () => {
+        tabs.forEach(other => {
+            const selected = other === tab;
+            other.setAttribute('aria-selected', String(selected));
+            const panel = document.getElementById(other.dataset.chartTab);
+            if (panel) panel.hidden = !selected;
+        });
+        const panel = document.getElementById(tab.dataset.chartTab);
+        const graph = panel && panel.querySelector('.js-plotly-plot');
+        if (graph && typeof Plotly !== 'undefined') Plotly.Plots.resize(graph);
+    }));
+})();
+
+// Постоянная ссылка на вид основного графика:
+// `#from=YYYY-MM-DD&to=YYYY-MM-DD&hide=Имя,Имя` — окно дат и скрытые линии.
+(() => {
+    const graph = document.getElementById('area-plot');
+    if (!graph) return;
+    const DATE = /^\d{4}-\d{2}-\d{2}$/;
+    const readState = () => {
+        const params = new URLSearchParams(location.hash.slice(1));
+        const from = params.get('from') || '';
+        const to = params.get('to') || '';
+        return {
+            range: DATE.test(from) && DATE.test(to) && from < to ? [from, to] : null,
+            hidden: (params.get('hide') || '').split(',').filter(Boolean),
+        };
+    };
+    const writeState = () => {
+        const params = new URLSearchParams(location.hash.slice(1));
+        const axis = graph.layout.xaxis || {};
+        if (!axis.autorange && Array.isArray(axis.range)) {
+            params.set('from', String(axis.range[0]).slice(0, 10));
+            params.set('to', String(axis.range[1]).slice(0, 10));
+        } else {
+            params.delete('from');
+            params.delete('to');
+        }
+        const hidden = [...new Set(graph.data
+            .filter(trace => trace.name && trace.visible === 'legendonly')
+            .map(trace => trace.name))];
+        if (hidden.length) params.set('hide', hidden.join(','));
+        else params.delete('hide');
+        const hash = params.toString();
+        history.replaceState(null, '', hash ? `#${hash}` : location.pathname + location.search);
+    };
+
+    graph.addEventListener('rua:figure', event => {
+        const figure = event.detail;
+        const state = readState();
+        if (state.range) {
+            figure.layout.xaxis.range = state.range;
+            figure.layout.xaxis.autorange = false;
+        }
+        (figure.data || []).forEach(trace => {
+            if (state.hidden.includes(trace.name)) trace.visible = 'legendonly';
+        });
+    });
+    graph.addEventListener('rua:rendered', () => {
+        graph.on('plotly_relayout', writeState);
+        graph.on('plotly_restyle', writeState);
+    });
+    window.addEventListener('hashchange', () => {
+        if (!graph.data || typeof Plotly === 'undefined') return;
+        const state = readState();
+        Plotly.relayout(graph, state.range
+            ? { 'xaxis.range': state.range }
+            : { 'xaxis.autorange': true });
+        const toggled = graph.data
+            .map((trace, idx) => [idx, state.hidden.includes(trace.name)])
+            .filter(([idx, hide]) => hide !== (graph.data[idx].visible === 'legendonly'));
+        if (toggled.length) {
+            Plotly.restyle(
+                graph,
+                { visible: toggled.map(([, hide]) => (hide ? 'legendonly' : true)) },
+                toggled.map(([idx]) => idx),
+            );
+        }
+    });
+})();
+
+// Переключатель единиц площади: карточки и таблицы пересчитываются из `data-area-km2`,
+// выбор запоминается в браузере. Графики остаются в единице из конфигурации.
+(() => {
+    const select = document.getElementById('area-unit');
+    if (!select) return;
+    const STORAGE_KEY = 'rua:area-unit';
+    // Разделители локали из `[page].locale`, как в числах, собранных на сервере.
+    const separators = {
+        decimal: select.dataset.decimal || ',',
+        group: select.dataset.group || '\u202f',
+        groupMin: Number(select.dataset.groupMin) || 5,
+    };
+    const localize = (number, grouped) => {
+        const [integer, fraction] = number.split('.');
+        const sign = integer.startsWith('-') || integer.startsWith('+') ? integer[0] : '';
+        let digits = sign ? integer.slice(1) : integer;
+        if (grouped && digits.length >= separators.groupMin) {
+            digits = digits.replace(/\B(?=(\d{3})+$)/g, separators.group);
+        }
+        return fraction === undefined ? sign + digits : `${sign}${digits}${separators.decimal}${fraction}`;
+    };
+    const unitOf = key => {
+        const option = Array.from(select.options).find(candidate => candidate.value === key);
+        return option && {
+            km2: Number(option.dataset.km2),
+            decimals: Number(option.dataset.decimals),
+            label: option.dataset.label,
+            change: option.dataset.change,
+        };
+    };
+    const format = (element, unit) => {
+        const raw = element.dataset.areaKm2;
+        if (!raw) return '—';
+        const value = Number(raw) / unit.km2;
+        const fixed = value.toFixed(unit.decimals);
+        const signed = element.hasAttribute('data-area-signed') && !fixed.startsWith('-') ? `+${fixed}` : fixed;
+        const number = localize(signed, !element.hasAttribute('data-area-plain'));
+        return element.hasAttribute('data-area-bare') ? number : `${number} ${unit.label}`;
+    };
+    const render = () => {
+        const level = unitOf(select.value);
+        if (!level) return;
+        const change = unitOf(level.change) || level;
+        const unitFor = kind => (kind === 'change' ? change : level);
+        document.querySelectorAll('[data-area-unit]').forEach(element => {
+            element.textContent = unitFor(element.dataset.areaUnit).label;
+        });
+        document.querySelectorAll('[data-area]').forEach(element => {
+            element.textContent = format(element, unitFor(element.dataset.area));
+        });
+    };
+
+    try {
+        const stored = localStorage.getItem(STORAGE_KEY);
+        if (stored && unitOf(stored)) select.value = stored;
+    } catch (error) {
+        // Хранилище недоступно (приватный режим): остаётся единица по умолчанию.
+    }
+    select.addEventListener('change', () => {
+        try {
+            localStorage.setItem(STORAGE_KEY, select.value);
+        } catch (error) {
+            // Выбор просто не запомнится.
+        }
+        render();
+    });
+    document.addEventListener('rua:areas', render);
+    render();
+    select.closest('.area-unit').hidden = false;
+})();
+
+// Ось основного графика: площадь в единице из конфигурации или доля площади Украины.
+// Трассы основной оси, подписи и аннотации пересчитываются множителем из разметки,
+// выбор запоминается. Нижняя панель изменений остаётся в единицах площади.
+(() => {
+    const select = document.getElementById('area-axis');
+    const graph = document.getElementById('area-plot');
+    if (!select || !graph) return;
+    const STORAGE_KEY = 'rua:area-axis';
+    const PERCENT = 'percent';
+    const percentPerUnit = Number(select.dataset.percentPerUnit);
+    const unitLabel = select.dataset.unitLabel;
+    const scaleOf = mode => (mode === PERCENT ? percentPerUnit : 1);
+    const onMainAxis = item => !item.yaxis || item.yaxis === 'y' || item.yaxis === 'y1';
+    const scaled = (values, ratio) => values.map(value => (value === null ? null : value * ratio));
+    // Исходная подпись запоминается в трассе, чтобы вернуть её при переключении обратно.
+    const hoverFor = (trace, mode) => {
+        if (trace.ruaHovertemplate === undefined) trace.ruaHovertemplate = trace.hovertemplate || '';
+        const template = trace.ruaHovertemplate;
+        if (!template || mode !== PERCENT) return template || undefined;
+        return template.split(unitLabel).join('%').replace(/%\{y(:[^}]*)?\}/g, '%{y:.2f}');
+    };
+    const titleFor = mode => (mode === PERCENT ? '% площади Украины' : unitLabel);
+    // Пересчитывает фигуру из текущего масштаба в выбранный; возвращает правки для Plotly.
+    const rescale = (data, layout, from, mode) => {
+        const ratio = scaleOf(mode) / from;
+        const indices = [];
+        const restyle = { y: [], hovertemplate: [] };
+        data.forEach((trace, idx) => {
+            if (!onMainAxis(trace) || !Array.isArray(trace.y)) return;
+            indices.push(idx);
+            restyle.y.push(scaled(trace.y, ratio));
+            restyle.hovertemplate.push(hoverFor(trace, mode));
+        });
+        const relayout = {};
+        // В виде «Индекс» кнопок шкалы подпись оси не зависит от единицы, пересчитываются деления.
+        if (layout.yaxis?.tickmode !== 'array') relayout['yaxis.title.text'] = titleFor(mode);
+        if (Array.isArray(layout.yaxis?.tickvals)) {
+            relayout['yaxis.tickvals'] = scaled(layout.yaxis.tickvals, ratio);
+        }
+        (layout.annotations || []).forEach((annotation, idx) => {
+            if (annotation.yref === 'y' && typeof annotation.y === 'number') {
+                relayout[`annotations[${idx}].y`] = annotation.y * ratio;
+            }
+        });
+        (layout.shapes || []).forEach((shape, idx) => {
+            if (shape.yref !== 'y') return;
+            relayout[`shapes[${idx}].y0`] = shape.y0 * ratio;
+            relayout[`shapes[${idx}].y1`] = shape.y1 * ratio;
+        });
+        if (Array.isArray(layout.yaxis?.range)) {
+            relayout['yaxis.range'] = scaled(layout.yaxis.range, ratio);
+        }
+        return { indices, restyle, relayout };
+    };
+    const current = () => Number(graph.dataset.axisScale || 1);
+    const render = () => {
+        if (!graph.data || typeof Plotly === 'undefined') return;
+        const { indices, restyle, relayout } = rescale(graph.data, graph.layout, current(), select.value);
+        graph.dataset.axisScale = String(scaleOf(select.value));
+        Plotly.update(graph, restyle, relayout, indices);
+    };
+
+    // График ещё не построен: запомненная ось применяется прямо к фигуре.
+    graph.addEventListener('rua:figure', event => {
+        if (select.value !== PERCENT) return;
+        const figure = event.detail;
+        const { indices, restyle, relayout } = rescale(figure.data || [], figure.layout, 1, PERCENT);
+        indices.forEach((traceIdx, idx) => {
+            figure.data[traceIdx].y = restyle.y[idx];
+            figure.data[traceIdx].hovertemplate = restyle.hovertemplate[idx];
+        });
+        if (relayout['yaxis.title.text']) {
+            figure.layout.yaxis.title = { ...(figure.layout.yaxis.title || {}), text: relayout['yaxis.title.text'] };
+        }
+        (figure.layout.annotations || []).forEach((annotation, idx) => {
+            const y = relayout[`annotations[${idx}].y`];
+            if (y !== undefined) annotation.y = y;
+        });
+        (figure.layout.shapes || []).forEach((shape, idx) => {
+            const y0 = relayout[`shapes[${idx}].y0`];
+            if (y0 === undefined) return;
+            shape.y0 = y0;
+            shape.y1 = relayout[`shapes[${idx}].y1`];
+        });
+        if (relayout['yaxis.range']) figure.layout.yaxis.range = relayout['yaxis.range'];
+        graph.dataset.axisScale = String(percentPerUnit);
+    });
+    // Кнопки шкалы из раскладки графика переписывают ось в единицах площади: данные
+    // возвращаются к ним же, подписи и деления оси кнопка уже выставила.
+    graph.addEventListener('rua:rendered', () => {
+        graph.on('plotly_buttonclicked', () => {
+            if (current() === 1) return;
+            const { indices, restyle, relayout } = rescale(graph.data, graph.layout, current(), 'unit');
+            Object.keys(relayout)
+                .filter(key => key.startsWith('yaxis.'))
+                .forEach(key => delete relayout[key]);
+            select.value = 'unit';
+            graph.dataset.axisScale = '1';
+            Plotly.update(graph, restyle, relayout, indices);
+        });
+    });
+    try {
+        if (localStorage.getItem(STORAGE_KEY) === PERCENT) select.value = PERCENT;
+    } catch (error) {
+        // Хранилище недоступно: остаётся ось в единицах площади.
+    }
+    select.addEventListener('change', () => {
+        try {
+            localStorage.setItem(STORAGE_KEY, select.value);
+        } catch (error) {
+            // Выбор просто не запомнится.
+        }
+        render();
+    });
+    select.closest('.area-axis').hidden = false;
+})();
+
+// Переключатель горизонта прогноза: на основном графике видны трассы и подпись выбранного
+// горизонта, карточка прогноза переписывается по данным варианта; выбор запоминается.
+(() => {
+    const select = document.getElementById('forecast-horizon');
+    if (!select) return;
+    const STORAGE_KEY = 'rua:forecast-horizon';
+    const graph = document.getElementById('area-plot');
+    const isForecast = group => typeof group === 'string' && group.startsWith('forecast-');
+    const setText = (id, text) => {
+        const element = document.getElementById(id);
+        if (element) element.textContent = text;
+    };
+    const setArea = (id, km2) => {
+        const element = document.querySelector(`#${id} [data-area-km2]`);
+        if (element) element.dataset.areaKm2 = km2;
+    };
+    const showCard = option => {
+        setText('summary-forecast-title', option.dataset.title);
+        setText('summary-forecast-end', option.dataset.end);
+        setArea('summary-forecast-mean', option.dataset.meanKm2);
+        setArea('summary-forecast-lower', option.dataset.lowerKm2);
+        setArea('summary-forecast-upper', option.dataset.upperKm2);
+        document.dispatchEvent(new CustomEvent('rua:areas'));
+    };
+    const showPlot = () => {
+        if (!graph || !graph.data || typeof Plotly === 'undefined') return;
+        const indices = [];
+        const visible = [];
+        graph.data.forEach((trace, idx) => {
+            if (!isForecast(trace.legendgroup)) return;
+            indices.push(idx);
+            visible.push(trace.legendgroup === select.value);
+        });
+        const layout = {};
+        (graph.layout.annotations || []).forEach((annotation, idx) => {
+            if (isForecast(annotation.name)) {
+                layout[`annotations[${idx}].visible`] = annotation.name === select.value;
+            }
+        });
+        Plotly.update(graph, { visible }, layout, indices);
+    };
+    const render = () => {
+        const option = select.options[select.selectedIndex];
+        if (!option) return;
+        showCard(option);
+        showPlot();
+    };
+
+    if (graph) {
+        // График ещё не построен: нужный горизонт включается прямо в фигуре.
+        graph.addEventListener('rua:figure', event => {
+            const figure = event.detail;
+            (figure.data || []).forEach(trace => {
+                if (isForecast(trace.legendgroup)) trace.visible = trace.legendgroup === select.value;
+            });
+            (figure.layout.annotations || []).forEach(annotation => {
+                if (isForecast(annotation.name)) annotation.visible = annotation.name === select.value;
+            });
+        });
+    }
+    try {
+        const stored = localStorage.getItem(STORAGE_KEY);
+        if (stored && Array.from(select.options).some(option => option.value === stored)) {
+            select.value = stored;
+        }
+    } catch (error) {
+        // Хранилище недоступно: остаётся горизонт из конфигурации.
+    }
+    select.addEventListener('change', () => {
+        try {
+            localStorage.setItem(STORAGE_KEY, select.value);
+        } catch (error) {
+            // Выбор просто не запомнится.
+        }
+        render();
+    });
+    render();
+    select.closest('.forecast-horizon').hidden = false;
+})();
+
+// Кнопка «Скопировать сводку»: текст готовится при сборке страницы.
+(() => {
+    const button = document.querySelector('.summary-copy');
+    const status = document.querySelector('.summary-copy-status');
+    if (!button || !status) return;
+    const fallback = text => {
+        const area = document.createElement('textarea');
+        area.value = text;
+        area.setAttribute('readonly', '');
+        area.style.position = 'fixed';
+        area.style.opacity = '0';
+        document.body.appendChild(area);
+        area.select();
+        const copied = document.execCommand('copy');
+        area.remove();
+        return copied ? Promise.resolve() : Promise.reject();
+    };
+    button.addEventListener('click', () => {
+        const text = button.dataset.copy;
+        const copy = navigator.clipboard && window.isSecureContext
+            ? navigator.clipboard.writeText(text).catch(() => fallback(text))
+            : fallback(text);
+        copy.then(
+            () => { status.textContent = 'Скопировано'; },
+            () => { status.textContent = 'Не удалось скопировать'; },
+        );
+        setTimeout(() => { status.textContent = ''; }, 3000);
+    });
+    button.hidden = false;
+})();
+
+// Таблица ряда под графиком: без JavaScript видна целиком, со скриптом — страницами.
+document.querySelectorAll('.series-details').forEach(details => {
+    const rows = Array.from(details.querySelectorAll('tbody tr'));
+    const pager = details.querySelector('.series-pager');
+    if (!pager) return;
+    const status = pager.querySelector('.series-page-status');
+    const prev = pager.querySelector('[data-page=prev]');
+    const next = pager.querySelector('[data-page=next]');
+    const pageSize = 30;
+    const pages = Math.ceil(rows.length / pageSize);
+    if (pages < 2) return;
+    let page = 0;
+    const show = () => {
+        rows.forEach((row, idx) => {
+            row.hidden = Math.floor(idx / pageSize) !== page;
+        });
+        prev.disabled = page === 0;
+        next.disabled = page === pages - 1;
+        status.textContent = `Страница ${page + 1} из ${pages}`;
+    };
+    prev.addEventListener('click', () => { page -= 1; show(); });
+    next.addEventListener('click', () => { page += 1; show(); });
+    pager.hidden = false;
+    show();
+});
+
+// Время сборки: в разметке — UTC, со скриптом — местное время читателя и «N часов назад».
+// Полная метка в UTC остаётся во всплывающей подсказке.
+(() => {
+    const stamps = Array.from(document.querySelectorAll('time[data-generated-at]'));
+    if (!stamps.length || typeof Intl === 'undefined' || !Intl.RelativeTimeFormat) return;
+    const locale = document.documentElement.lang || 'ru';
+    const absolute = new Intl.DateTimeFormat(locale, { dateStyle: 'medium', timeStyle: 'short' });
+    const relative = new Intl.RelativeTimeFormat(locale, { numeric: 'auto' });
+    const UNITS = [['day', 86400], ['hour', 3600], ['minute', 60]];
+    const ago = seconds => {
+        if (Math.abs(seconds) < 60) return relative.format(0, 'minute');
+        const [unit, size] = UNITS.find(([, size]) => Math.abs(seconds) >= size);
+        return relative.format(-Math.round(seconds / size), unit);
+    };
+    const render = () => stamps.forEach(stamp => {
+        const time = new Date(stamp.dataset.generatedAt);
+        if (Number.isNaN(time.getTime())) return;
+        stamp.textContent = `${absolute.format(time)} (${ago((Date.now() - time) / 1000)})`;
+    });
+    render();
+    setInterval(render, 60 * 1000);
+})();
+
+// Сортировка таблиц соотношений.
+(() => {
+    const select = document.getElementById('ratio-sort');
+    if (!select) return;
+    const tables = Array.from(document.querySelectorAll('.ratio-table'));
+    const sortTable = (table, key) => {
+        const tbody = table.querySelector('tbody');
+        if (!tbody) return;
+        const rows = Array.from(tbody.querySelectorAll('tr.ratio-row'));
+        rows.sort((a, b) => {
+            if (key === 'name') {
+                return a.dataset.name.localeCompare(b.dataset.name, 'ru');
+            }
+            return parseFloat(b.dataset.ratio) - parseFloat(a.dataset.ratio);
+        });
+        rows.forEach(row => tbody.appendChild(row));
+    };
+
+    const applySort = () => tables.forEach(table => sortTable(table, select.value));
+    applySort();
+    select.addEventListener('change', applySort);
+    // Переключатель наборов перестроил строки: порядок восстанавливается по текущему выбору.
+    document.addEventListener('rua:ratio-rows', event => sortTable(event.detail, select.value));
+})();
+
+// Переключение наборов территорий в таблицах соотношений по встроенному JSON `#ratio-sets`.
+(() => {
+    const source = document.getElementById('ratio-sets');
+    const selects = Array.from(document.querySelectorAll('select.ratio-set'));
+    if (!source || !selects.length) return;
+    const sets = new Map(JSON.parse(source.textContent).map(set => [set.key, set]));
+    const flagBase = source.dataset.flagBase || '';
+    const cell = (text, className) => {
+        const td = document.createElement('td');
+        if (className) td.className = className;
+        td.textContent = text;
+        return td;
+    };
+    const buildRow = row => {
+        const tr = document.createElement('tr');
+        tr.className = 'ratio-row';
+        tr.dataset.name = row.name;
+        tr.dataset.ratio = String(row.ratio_value);
+        const name = cell(row.name);
+        if (row.flag) {
+            const img = document.createElement('img');
+            img.className = 'flag';
+            img.loading = 'lazy';
+            img.alt = `Флаг ${row.name}`;
+            img.src = `${flagBase}${row.flag}.png`;
+            name.prepend(img);
+        }
+        const forecast = cell(row.forecast_ratio, 'ratio-forecast');
+        if (row.forecast_mean) forecast.title = row.forecast_mean;
+        tr.append(name, cell(row.ratio), forecast);
+        return tr;
+    };
+    selects.forEach(select => {
+        const box = select.closest('.table-box');
+        const table = box && box.querySelector('table.ratio-table');
+        if (!table) return;
+        select.addEventListener('change', () => {
+            const set = sets.get(select.value);
+            if (!set) return;
+            box.querySelector('.ratio-set-title').textContent = set.title;
+            table.querySelector('.ratio-name-column').textContent = set.name_column;
+            table.dataset.table = set.key;
+            table.querySelector('tbody').replaceChildren(...set.rows.map(buildRow));
+            document.dispatchEvent(new CustomEvent('rua:ratio-rows', { detail: table }));
+        });
+        select.hidden = false;
+    });
+})();
+
+// Пересчёт линии «Ср. изменение» WASM-ядром из `[page].wasm_url`: метод и окно сглаживания
+// меняются на странице без пересборки HTML. Пока пакет не загружен, переключатель скрыт.
+(() => {
+    const controls = document.querySelector('.smoothing-controls');
+    const source = document.getElementById('change-input');
+    const graph = document.getElementById('area-plot');
+    if (!controls || !source || !graph || !window.WebAssembly) return;
+    // Порядок совпадает с кодами метода в `rua_smooth`.
+    const METHODS = ['sma', 'ewma', 'loess'];
+    const input = JSON.parse(source.textContent);
+    const method = document.getElementById('change-smoothing-method');
+    const windowInput = document.getElementById('change-smoothing-window');
+    const recompute = kernel => {
+        if (!graph.data || typeof Plotly === 'undefined') return;
+        const trace = graph.data.findIndex(candidate => candidate.name === 'Ср. изменение');
+        const len = input.changes.length;
+        if (trace < 0 || !len) return;
+        const days = Math.max(1, Math.round(Number(windowInput.value)) || 1);
+        const values = kernel.rua_alloc(len);
+        const out = kernel.rua_alloc(len);
+        try {
+            new Float64Array(kernel.memory.buffer, values, len).set(input.changes);
+            kernel.rua_smooth(
+                METHODS.indexOf(method.value),
+                days,
+                Math.min(input.min_periods, days),
+                values,
+                len,
+                out,
+            );
+            // Вызовы ядра могут вырастить память: представления создаются заново после каждого.
+            const smoothed = new Float64Array(kernel.memory.buffer, out, len);
+            const shown = input.dates
+                .map((date, idx) => [date, smoothed[idx]])
+                .filter(([date, value]) => date >= input.baseline && !Number.isNaN(value));
+            if (!shown.length) return;
+            new Float64Array(kernel.memory.buffer, values, shown.length).set(shown.map(([, value]) => value));
+            const count = kernel.rua_downsample(values, shown.length, input.max_points || 0, out);
+            const indices = Array.from(new Float64Array(kernel.memory.buffer, out, count));
+            Plotly.restyle(graph, {
+                x: [indices.map(idx => shown[idx][0])],
+                y: [indices.map(idx => shown[idx][1])],
+            }, [trace]);
+        } finally {
+            kernel.rua_free(values, len);
+            kernel.rua_free(out, len);
+        }
+    };
+    fetch(controls.dataset.wasm)
+        .then(response => (response.ok ? response.arrayBuffer() : Promise.reject(response.status)))
+        .then(bytes => WebAssembly.instantiate(bytes))
+        .then(({ instance }) => {
+            const update = () => recompute(instance.exports);
+            method.addEventListener('change', update);
+            windowInput.addEventListener('change', update);
+            controls.hidden = false;
+        })
+        .catch(() => {});
+})();
@@ -0,0 +1,3679 @@
+//! Подготовка данных и генерация Plotly-графика.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::io;
+
+use chrono::{Datelike, NaiveDate};
+use itertools::Itertools;
+use plotly::box_plot::BoxPoints;
+use plotly::color::{Rgb, Rgba};
+use plotly::common::{
+    Anchor, DashType, Fill, Font, HoverInfo, Line, Marker, MarkerSymbol, Mode, Orientation, Title,
+    Visible,
+};
+use plotly::layout::update_menu::{
+    Button, ButtonMethod, UpdateMenu, UpdateMenuDirection, UpdateMenuType,
+};
+use plotly::layout::{
+    Annotation, Axis, AxisType, BarMode, GridPattern, HoverMode, ItemClick, Layout, LayoutGrid,
+    Legend, Margin, RowOrder, Shape, ShapeLayer, ShapeLine, ShapeType, SpikeMode, SpikeSnap,
+    TicksDirection,
+};
+use plotly::{Bar, BoxPlot, Configuration, Plot, Scatter};
+use serde_json::json;
+
+use super::compare::{self, ComparisonSource, ComparisonStats};
+use super::kernel::{daily_change_series, downsample_min_max_indices, merge_sorted_indices};
+use super::numbers::NumberLocale;
+use super::overlay::{
+    ChartRenderConfig, ChartSummary, ForecastOverlay, Milestone, ModelDiagnostics, Phase,
+    ReferenceLevel, ReferenceLine, SeriesExtreme, SeriesExtremes,
+};
+use super::smoothing::SmoothingSpec;
+use super::svg;
+use super::units::AreaUnit;
+use crate::constants::{self, AREA_THOUSANDS_DIVISOR, DATE_FORMAT};
+use crate::front::FrontShifts;
+use crate::series::{
+    AreaBuckets, CategorySeries, Granularity, InterpolationStrategy, build_category_series_with,
+    build_occupied_and_unspecified_series_with, build_snapshot_series, build_weekly_flows,
+};
+
+pub struct ChartOutput {
+    pub main_plot: Plot,
+    pub yoy_plot: Plot,
+    /// Накопленная площадь по категориям контроля (вкладка основного графика).
+    pub category_plot: Plot,
+    /// Понедельные приобретения и потери площади (вкладка основного графика).
+    pub flows_plot: Plot,
+    pub summary: ChartSummary,
+    /// Статичная SVG-копия основного графика для `<noscript>`.
+    pub static_svg: Option<String>,
+    /// Сравнение со вторым источником (если он задан).
+    pub comparison: Option<ComparisonOutput>,
+    /// Смещение линии фронта (если раздел включён и карты загружены).
+    pub front: Option<FrontOutput>,
+    /// Диагностика модели (если раздел включён и модель её рассчитала).
+    pub diagnostics: Option<ModelDiagnostics>,
+    /// Последние дни ряда для таблицы под графиком, от новых к старым.
+    pub table: Vec<SeriesTableRow>,
+    /// Данные для пересчёта нижней панели на странице (если задан `[page].wasm_url`).
+    pub change_input: Option<ChangeInput>,
+    /// Сроки до рубежей при текущем темпе (если рубежи заданы и темп известен).
+    pub milestones: Option<MilestonePace>,
+    /// Итоги этапов, попавших в загруженный ряд (если этапы заданы).
+    pub phases: Vec<PhaseStats>,
+}
+
+/// Строка таблицы «Фазы»: этап, обрезанный по загруженному ряду.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhaseStats {
+    pub label: String,
+    /// Первый и последний день этапа с известной площадью.
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Изменение занятой площади за этап, км².
+    pub net_change_km2: f64,
+    /// Среднее суточное изменение, км²/сутки; `None` для этапа в один день.
+    pub mean_rate_km2_per_day: Option<f64>,
+}
+
+/// Карточка «При текущем темпе»: последний сглаженный темп и срок до каждого рубежа.
+pub struct MilestonePace {
+    /// День, на который взят темп.
+    pub as_of: NaiveDate,
+    /// Сглаженное суточное изменение, км²/сутки.
+    pub pace_km2_per_day: f64,
+    pub etas: Vec<MilestoneEta>,
+}
+
+/// Срок до рубежа при неизменном темпе.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MilestoneEta {
+    pub label: String,
+    pub target_km2: f64,
+    /// Сколько осталось от последнего значения ряда, км²; отрицательное — рубеж ниже него.
+    pub remaining_km2: f64,
+    /// Дней до рубежа; `None`, если темп к рубежу не ведёт.
+    pub days: Option<f64>,
+}
+
+impl MilestoneEta {
+    /// Дата, к которой темп приводит к рубежу; `None` — темп не ведёт к нему или срок
+    /// длиннее тысячи лет.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn reached_on(&self, from: NaiveDate) -> Option<NaiveDate> {
+        let days = self.days?.ceil();
+        (days <= f64::from(MAX_MILESTONE_DAYS))
+            .then(|| from.checked_add_days(chrono::Days::new(days as u64)))
+            .flatten()
+    }
+}
+
+/// Несглаженное суточное изменение для пересчёта линии «Ср. изменение» WASM-ядром на странице.
+pub struct ChangeInput {
+    /// Дни ряда (YYYY-MM-DD).
+    pub dates: Vec<String>,
+    /// Суточное изменение в единице графика.
+    pub changes: Vec<f64>,
+    /// Первый показываемый день (YYYY-MM-DD).
+    pub baseline: String,
+    /// Сглаживание из конфигурации: с него начинается переключатель.
+    pub smoothing: SmoothingSpec,
+    /// Предел числа точек линии; `None` — без прореживания.
+    pub max_points: Option<usize>,
+}
+
+/// Строка таблицы под графиком.
+pub struct SeriesTableRow {
+    pub date: NaiveDate,
+    /// Занятая площадь, км²; `None` — день без среза, если пропуски не заполняются.
+    pub area_km2: Option<f64>,
+    /// Изменение к предыдущему дню, км².
+    pub change_km2: Option<f64>,
+    /// Есть ли за день срез источника (иначе значение интерполировано).
+    pub measured: bool,
+}
+
+/// Смещение линии фронта по направлениям между двумя картами.
+pub struct FrontOutput {
+    pub plot: Plot,
+    /// Время обеих карт (YYYY-MM-DD HH:MM UTC).
+    pub before: String,
+    pub after: String,
+}
+
+/// Вкладка сравнения источников: график и сводка расхождения.
+pub struct ComparisonOutput {
+    pub plot: Plot,
+    pub stats: ComparisonStats,
+    pub primary_label: String,
+    pub label: String,
+}
+
+#[derive(Clone, Debug)]
+struct PreparedChangeSeries {
+    baseline: NaiveDate,
+    smoothing: SmoothingSpec,
+    dates: Vec<NaiveDate>,
+    labels: Vec<String>,
+    values: Vec<f64>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct PreparedUnspecifiedChangePlot {
+    dates: Vec<String>,
+    reference_values: Vec<f64>,
+    values: Vec<f64>,
+}
+
+#[derive(Clone, Debug)]
+struct YoyYearSeries {
+    year: i32,
+    dates: Vec<String>,
+    values: Vec<f64>,
+}
+
+#[derive(Clone, Debug)]
+struct YoyEnvelope {
+    dates: Vec<String>,
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+}
+
+#[derive(Clone, Debug)]
+struct YoyStdSeries {
+    dates: Vec<String>,
+    values: Vec<f64>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct MonthlyBoxSeries {
+    dates: Vec<String>,
+    values: Vec<f64>,
+}
+
+const AXIS_MAIN_X: &str = "x1";
+const AXIS_MAIN_Y: &str = "y1";
+const AXIS_CHANGE_X: &str = "x1";
+const AXIS_CHANGE_Y: &str = "y2";
+const AXIS_YOY_X: &str = "x";
+const AXIS_YOY_Y: &str = "y";
+const AXIS_YOY_BOX_X: &str = "x2";
+const AXIS_YOY_BOX_Y: &str = "y2";
+const AXIS_REF_X: &str = "x";
+const AXIS_REF_Y: &str = "y";
+const AXIS_REF_PAPER: &str = "paper";
+const AXIS_REF_PIXEL: &str = "pixel";
+/// Дальше тысячи лет срок до рубежа показывается без даты.
+const MAX_MILESTONE_DAYS: u32 = 365_250;
+const LABEL_ACTUAL: &str = "Факт";
+const LABEL_IMPUTED: &str = "Интерполяция";
+const LABEL_MANUAL: &str = "Ручной ввод";
+const LABEL_EXCLUDED: &str = "Исключено из обучения";
+const LABEL_FORECAST: &str = "Прогноз";
+const LABEL_FORECAST_WEEKLY: &str = "Прогноз (средние за неделю)";
+const LABEL_SMOOTHED: &str = "Оценка модели";
+const LABEL_TREND: &str = "Темп по модели";
+const LABEL_CONFIDENCE: &str = "95%";
+const LABEL_UNSPECIFIED_BAND_MAIN: &str = "Серая зона";
+const LABEL_UNSPECIFIED_CHANGE: &str = "Ср. изменение СЗ";
+const LABEL_AVG_CHANGE: &str = "Ср. изменение";
+const LABEL_YOY_BAND: &str = "Исторический min/max";
+const LABEL_YOY_STDDEV: &str = "σ";
+const LABEL_BOX_DAILY_CHANGE: &str = "Суточное сглаженное изменение";
+const LABEL_UKRAINE: &str = "Украины";
+const LABEL_CATEGORY_PRE_WAR: &str = "Занято до 24.02.2022";
+const LABEL_CATEGORY_OCCUPIED: &str = "Занято после 24.02.2022";
+const LABEL_CATEGORY_LIBERATED: &str = "Освобождено";
+const LABEL_CATEGORY_UNSPECIFIED: &str = "Серая зона";
+const LABEL_DIVERGENCE: &str = "Расхождение";
+const LABEL_FLOW_GAINED: &str = "Занято";
+const LABEL_FLOW_LOST: &str = "Потеряно";
+const LABEL_FLOW_NET: &str = "Итог недели";
+const LABEL_FRONT_ADVANCE: &str = "Продвижение";
+const LABEL_FRONT_RETREAT: &str = "Отход";
+const LABEL_FRONT_AXIS: &str = "км вдоль направления";
+const FRONT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M UTC";
+const LABEL_MAX: &str = "Максимум";
+const LABEL_MIN: &str = "Минимум";
+const CATEGORY_STACK_GROUP: &str = "categories";
+const CATEGORY_FILL_ALPHA: f64 = 0.55;
+const HOVER_FORMAT_KM2_PER_DAY: &str = ".1f";
+const HOVER_FORMAT_DAY_MONTH: &str = "%d.%m";
+const FONT_FAMILY: &str = "PT Sans, Arial, sans-serif";
+const TICK_FORMAT_MONTH_YEAR: &str = "%b\n%Y";
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const HOVER_FORMAT_DATE: &str = "%d.%m.%Y";
+const HOVER_FORMAT_DATE_TIME: &str = "%d.%m.%Y %H:%M";
+/// Начало полномасштабного вторжения: база изменения в подсказках основного графика.
+const INVASION_START: NaiveDate = NaiveDate::from_ymd_opt(2022, 2, 24).expect("valid date");
+const HOVER_PERCENT_DECIMALS: usize = 2;
+const LABEL_SCALE_LINEAR: &str = "Линейная";
+const LABEL_SCALE_LOG: &str = "Логарифм";
+const LABEL_SCALE_INDEX: &str = "Индекс";
+/// Значение индекса на базовую дату.
+const INDEX_BASE: f64 = 100.0;
+const INDEX_TICKS_COUNT: f64 = 10.0;
+const SCALE_MENU_X: f64 = 0.0;
+const SCALE_MENU_Y: f64 = 1.0;
+const FONT_SIZE_BASE: usize = 12;
+const FONT_SIZE_AXIS_TITLE: usize = 13;
+const FONT_SIZE_AXIS_TICK: usize = 11;
+const FONT_SIZE_ANNOTATION: usize = 11;
+const LINE_WIDTH_MAIN: f64 = 2.6;
+const LINE_WIDTH_FORECAST: f64 = 2.2;
+const LINE_WIDTH_CHANGE: f64 = 1.6;
+const LINE_WIDTH_YOY: f64 = 1.8;
+const LINE_WIDTH_MARKER: f64 = 1.0;
+const MARKER_SIZE_MANUAL: usize = 9;
+const MARKER_SIZE_EXCLUDED: usize = 10;
+const MARKER_SIZE_EXTREME: usize = 8;
+const ARROW_HEAD: u8 = 2;
+const ARROW_SIZE: f64 = 0.9;
+const ARROW_WIDTH: f64 = 1.0;
+const ANNOTATION_OFFSET_X: f64 = 20.0;
+const ANNOTATION_OFFSET_Y: f64 = -34.0;
+const FORECAST_OFFSET_X: f64 = -20.0;
+const FORECAST_OFFSET_Y: f64 = -34.0;
+const CHANGE_OFFSET_X: f64 = 12.0;
+const EXTREME_OFFSET_Y: f64 = 28.0;
+const UKRAINE_LABEL_X: f64 = 0.99;
+const UKRAINE_LABEL_Y: f64 = 0.99;
+const LEGEND_X: f64 = 0.5;
+const LEGEND_Y: f64 = 1.02;
+const LEGEND_FONT_SIZE: usize = 12;
+const LEGEND_BORDER_WIDTH: usize = 1;
+const MARGIN_LEFT: usize = 80;
+const MARGIN_RIGHT: usize = 40;
+const MARGIN_TOP: usize = 70;
+const MARGIN_BOTTOM: usize = 60;
+const MARGIN_PAD: usize = 8;
+const TICK_LENGTH: usize = 6;
+const AXIS_GRID_WIDTH: usize = 1;
+const GRID_ROWS: usize = 2;
+const GRID_COLS: usize = 1;
+const GRID_Y_GAP: f64 = 0.08;
+const X_MAIN_TICKS_COUNT: usize = 14;
+const SPIKE_THICKNESS: usize = 1;
+const Y_MAIN_TICKS_COUNT: usize = 11;
+const Y_CHANGE_TICKS_COUNT: usize = 11;
+const X_YOY_TICKS_COUNT: usize = 16;
+const Y_YOY_TICKS_COUNT: usize = 12;
+const Y_YOY_MONTH_TICKS_COUNT: usize = 10;
+const YOY_ANCHOR_YEAR: i32 = 2000;
+// Кастомные якоря для box-plot по месяцам (день, час, минута) в anchor-year.
+// Значения соответствуют спецификации пользователя: 15.5 -> 15 12:00, 14.125 -> 14 03:00.
+const YOY_BOX_MONTH_ANCHORS: [(u32, u32, u32); 12] = [
+    (15, 12, 0), // Jan: 15.5
+    (14, 3, 0),  // Feb: 14.125
+    (15, 12, 0), // Mar: 15.5
+    (15, 0, 0),  // Apr: 15
+    (15, 12, 0), // May: 15.5
+    (15, 0, 0),  // Jun: 15
+    (15, 12, 0), // Jul: 15.5
+    (15, 12, 0), // Aug: 15.5
+    (15, 0, 0),  // Sep: 15
+    (15, 12, 0), // Oct: 15.5
+    (15, 0, 0),  // Nov: 15
+    (15, 12, 0), // Dec: 15.5
+];
+const COLOR_AREA: (u8, u8, u8) = (36, 100, 166);
+const COLOR_AREA_TRANSPARENT: (u8, u8, u8, f64) = (36, 100, 166, 0.0);
+const COLOR_AREA_BAND: (u8, u8, u8, f64) = (36, 100, 166, 0.2);
+const COLOR_AREA_IMPUTED: (u8, u8, u8, f64) = (36, 100, 166, 0.45);
+const COLOR_MANUAL: (u8, u8, u8) = (230, 159, 0);
+const COLOR_SMOOTHED_LINE: (u8, u8, u8) = (20, 52, 92);
+const COLOR_SMOOTHED_BAND: (u8, u8, u8, f64) = (20, 52, 92, 0.14);
+const COLOR_TREND_LINE: (u8, u8, u8) = (36, 100, 166);
+const COLOR_TREND_BAND: (u8, u8, u8, f64) = (36, 100, 166, 0.18);
+const COLOR_UNSPECIFIED_BAND: (u8, u8, u8, f64) = (128, 128, 128, 0.24);
+const COLOR_CATEGORY_PRE_WAR: (u8, u8, u8) = (20, 52, 92);
+const COLOR_CATEGORY_LIBERATED: (u8, u8, u8) = (76, 153, 96);
+const COLOR_CATEGORY_UNSPECIFIED: (u8, u8, u8) = (150, 150, 150);
+const COLOR_COMPARE_SECONDARY: (u8, u8, u8) = (200, 67, 46);
+const COLOR_DIVERGENCE_BAND: (u8, u8, u8, f64) = (200, 67, 46, 0.18);
+const COLOR_FLOW_GAINED: (u8, u8, u8) = (36, 100, 166);
+const COLOR_FLOW_LOST: (u8, u8, u8) = (200, 67, 46);
+const COLOR_UNSPECIFIED_TRANSPARENT: (u8, u8, u8, f64) = (128, 128, 128, 0.0);
+const COLOR_UNSPECIFIED_CHANGE_LINE: (u8, u8, u8) = (120, 120, 120);
+const COLOR_UNSPECIFIED_CHANGE_FILL: (u8, u8, u8, f64) = (128, 128, 128, 0.18);
+const COLOR_CHANGE_FILL: (u8, u8, u8, f64) = (220, 82, 60, 0.25);
+const COLOR_CHANGE_LINE: (u8, u8, u8) = (200, 67, 46);
+const COLOR_YOY_LINE: (u8, u8, u8) = (36, 100, 166);
+const COLOR_YOY_STDDEV: (u8, u8, u8) = (200, 67, 46);
+const COLOR_YOY_STDDEV_FILL: (u8, u8, u8, f64) = (200, 67, 46, 0.22);
+const YOY_LINE_ALPHA_MIN: f64 = 0.25;
+const YOY_LINE_ALPHA_MAX: f64 = 1.0;
+const COLOR_YOY_BAND: (u8, u8, u8, f64) = (120, 120, 120, 0.22);
+const COLOR_YOY_BAND_LINE: (u8, u8, u8, f64) = (120, 120, 120, 0.0);
+const COLOR_ARROW: (u8, u8, u8, f64) = (80, 80, 80, 0.6);
+const COLOR_MARKER_LINE: (u8, u8, u8, f64) = (80, 80, 80, 0.35);
+/// Соседние этапы чередуют оттенок фона, чтобы граница между ними была видна.
+const COLOR_PHASE_BANDS: [(u8, u8, u8, f64); 2] = [(36, 100, 166, 0.06), (230, 159, 0, 0.08)];
+const COLOR_TEXT_BASE: (u8, u8, u8) = (40, 40, 40);
+const COLOR_TEXT_ANNOTATION: (u8, u8, u8) = (32, 32, 32);
+const COLOR_PANEL_BG: (u8, u8, u8, f64) = (255, 255, 255, 0.85);
+const COLOR_PANEL_BORDER: (u8, u8, u8, f64) = (200, 200, 200, 0.75);
+const COLOR_LABEL_BG: (u8, u8, u8, f64) = (255, 255, 255, 0.8);
+const COLOR_LABEL_BORDER: (u8, u8, u8, f64) = (200, 200, 200, 0.7);
+const COLOR_AXIS_TICK: (u8, u8, u8, f64) = (0, 0, 0, 0.45);
+const COLOR_AXIS_LINE: (u8, u8, u8, f64) = (0, 0, 0, 0.35);
+const COLOR_AXIS_GRID_LIGHT: (u8, u8, u8, f64) = (0, 0, 0, 0.06);
+const COLOR_AXIS_GRID_MEDIUM: (u8, u8, u8, f64) = (0, 0, 0, 0.08);
+const COLOR_LEGEND_BG: (u8, u8, u8, f64) = (255, 255, 255, 0.75);
+const COLOR_LEGEND_BORDER: (u8, u8, u8, f64) = (210, 210, 210, 0.8);
+
+fn rgb(color: (u8, u8, u8)) -> Rgb {
+    Rgb::new(color.0, color.1, color.2)
+}
+
+fn rgba(color: (u8, u8, u8, f64)) -> Rgba {
+    Rgba::new(color.0, color.1, color.2, color.3)
+}
+
+fn usize_to_f64(value: usize, context: &str) -> f64 {
+    u32::try_from(value).map_or_else(
+        |_| {
+            tracing::warn!(
+                value,
+                context,
+                "usize value exceeds u32::MAX; clamping conversion to f64",
+            );
+            f64::from(u32::MAX)
+        },
+        f64::from,
+    )
+}
+
+#[allow(clippy::too_many_lines)]
+#[allow(clippy::large_stack_frames)]
+pub fn build_area_chart_from_buckets_with_config(
+    buckets: &AreaBuckets,
+    forecast: Option<&ForecastOverlay>,
+    render_config: &ChartRenderConfig,
+) -> Result<ChartOutput, Box<dyn Error>> {
+    let max_points = render_config.max_plot_points();
+    let unit = render_config.page.area_unit;
+    let change_unit = unit.change_unit();
+    let locale = render_config.page.locale;
+    let occupied_series =
+        build_occupied_and_unspecified_series_with(buckets, render_config.interpolation)?;
+    let dates = occupied_series.dates;
+    let occupied_area = occupied_series.occupied;
+    let unspecified_area = occupied_series.unspecified;
+    let measured = occupied_series.measured;
+    let area_dates = dates
+        .iter()
+        .map(|date| date.format(DATE_FORMAT).to_string())
+        .collect_vec();
+    let area_km2 = occupied_area
+        .iter()
+        .map(|value| value / AREA_THOUSANDS_DIVISOR)
+        .collect_vec();
+    // Ряды графика — в единицах страницы; сводка и SVG остаются в тыс. км².
+    let area_level = occupied_area
+        .iter()
+        .map(|&value| unit.of_km2(value))
+        .collect_vec();
+    let level_of = |thousands: &[f64]| {
+        thousands
+            .iter()
+            .map(|&value| unit.of_km2(value * AREA_THOUSANDS_DIVISOR))
+            .collect_vec()
+    };
+    let change_of = |km2: &[f64]| {
+        km2.iter()
+            .map(|&value| change_unit.of_km2(value))
+            .collect_vec()
+    };
+    let static_svg = svg::render_area_svg(
+        &svg::SvgSeries {
+            dates: &area_dates,
+            values: &area_km2,
+        },
+        forecast
+            .map(|forecast| svg::SvgSeries {
+                dates: &forecast.dates,
+                values: &forecast.mean,
+            })
+            .as_ref(),
+    );
+    let latest_area_km2 = area_km2.last().copied().unwrap_or_default();
+    let latest_area_sq_km = latest_area_km2 * AREA_THOUSANDS_DIVISOR;
+    let ukraine_percent = constants::ukraine_percent(latest_area_sq_km);
+    let latest_date = dates
+        .last()
+        .copied()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no dates available"))?;
+    let latest_date_label = latest_date.format(DATE_FORMAT).to_string();
+    let daily_change_km2 = if occupied_area.len() >= 2 {
+        Some(occupied_area[occupied_area.len() - 1] - occupied_area[occupied_area.len() - 2])
+    } else {
+        None
+    };
+    let weekly_change_km2 = if occupied_area.len() >= 8 {
+        Some(occupied_area[occupied_area.len() - 1] - occupied_area[occupied_area.len() - 8])
+    } else {
+        None
+    };
+    let extremes = series_extremes(&dates, &occupied_area, render_config.avg_change_start);
+    let change_series = prepare_change_series(
+        &dates,
+        &change_of(&occupied_area),
+        render_config.avg_change_start,
+        render_config.change_smoothing,
+    );
+
+    let unspecified_level = unspecified_area
+        .iter()
+        .map(|&value| unit.of_km2(value))
+        .collect_vec();
+    let (area_dates_plot, area_km2_plot, area_upper_km2_plot) = prepare_unspecified_area_plot(
+        &dates,
+        &area_dates,
+        &area_level,
+        &unspecified_level,
+        render_config.gray_zone_start,
+        max_points,
+    );
+    let unspecified_change_plot = prepare_unspecified_change_plot(
+        &change_series,
+        &dates,
+        &change_of(&unspecified_area),
+        render_config.gray_zone_start,
+        max_points / 2,
+    );
+    let hover = AreaHover::new(&dates, &occupied_area, unit, locale);
+    let day_hover = |label: &String| {
+        area_dates
+            .binary_search(label)
+            .map(|idx| hover.daily(idx))
+            .unwrap_or_default()
+    };
+    let (area_dates_actual_plot, area_km2_actual_plot, area_hover_actual_plot) =
+        if render_config.intraday {
+            let (times, occupied) = build_snapshot_series(buckets);
+            let labeled = times
+                .iter()
+                .zip(&occupied)
+                .map(|(time, &km2)| {
+                    (
+                        time.format(DATE_TIME_FORMAT).to_string(),
+                        hover.on(time.date_naive(), km2),
+                    )
+                })
+                .collect_vec();
+            let values = occupied
+                .iter()
+                .map(|&value| unit.of_km2(value))
+                .collect_vec();
+            let (labeled, values) = downsample_min_max(&labeled, &values, max_points);
+            let (labels, texts) = labeled.into_iter().unzip();
+            (labels, values.into_iter().map(Some).collect_vec(), texts)
+        } else {
+            // Интерполированные дни убираем из сплошной линии: их рисует пунктир ниже.
+            let labeled = area_dates
+                .iter()
+                .cloned()
+                .zip(measured.iter().copied())
+                .collect_vec();
+            let (labeled, values) = downsample_min_max(&labeled, &area_level, max_points);
+            labeled
+                .into_iter()
+                .zip(values)
+                .map(|((label, is_measured), value)| {
+                    let text = day_hover(&label);
+                    (label, is_measured.then_some(value), text)
+                })
+                .multiunzip()
+        };
+    let (imputed_dates_plot, imputed_km2_plot) =
+        if render_config.interpolation == InterpolationStrategy::None {
+            (Vec::new(), Vec::new())
+        } else {
+            build_imputed_segments(&area_dates, &area_level, &measured)
+        };
+    let imputed_hover_plot = imputed_dates_plot.iter().map(day_hover).collect_vec();
+    let (change_dates_plot, change_values_plot) =
+        downsample_min_max(&change_series.labels, &change_series.values, max_points / 2);
+
+    let mut plot = Plot::new();
+    if !area_dates_plot.is_empty() {
+        plot.add_trace(
+            Scatter::new(area_dates_plot.clone(), area_km2_plot)
+                .mode(Mode::Lines)
+                .line(Line::new().color(rgba(COLOR_UNSPECIFIED_TRANSPARENT)))
+                .show_legend(false)
+                .hover_info(HoverInfo::Skip)
+                .x_axis(AXIS_MAIN_X)
+                .y_axis(AXIS_MAIN_Y),
+        );
+        plot.add_trace(
+            Scatter::new(area_dates_plot, area_upper_km2_plot)
+                .mode(Mode::Lines)
+                .fill(Fill::ToNextY)
+                .fill_color(rgba(COLOR_UNSPECIFIED_BAND))
+                .line(Line::new().color(rgba(COLOR_UNSPECIFIED_TRANSPARENT)))
+                .show_legend(true)
+                .name(LABEL_UNSPECIFIED_BAND_MAIN)
+                .x_axis(AXIS_MAIN_X)
+                .y_axis(AXIS_MAIN_Y),
+        );
+    }
+    plot.add_trace(
+        Scatter::new(area_dates_actual_plot, area_km2_actual_plot)
+            .mode(Mode::Lines)
+            .line(
+                Line::new()
+                    .color(rgb(COLOR_AREA))
+                    .width(LINE_WIDTH_MAIN)
+                    .simplify(true),
+            )
+            .name(LABEL_ACTUAL)
+            .text_array(area_hover_actual_plot)
+            .hover_template(format!("%{{text}}<extra>{LABEL_ACTUAL}</extra>"))
+            .x_axis(AXIS_MAIN_X)
+            .y_axis(AXIS_MAIN_Y),
+    );
+
+    if !imputed_dates_plot.is_empty() {
+        plot.add_trace(
+            Scatter::new(imputed_dates_plot, imputed_km2_plot)
+                .mode(Mode::Lines)
+                .line(
+                    Line::new()
+                        .color(rgba(COLOR_AREA_IMPUTED))
+                        .width(LINE_WIDTH_MAIN)
+                        .dash(DashType::Dot),
+                )
+                .name(LABEL_IMPUTED)
+                .text_array(imputed_hover_plot)
+                .hover_template(format!("%{{text}}<extra>{LABEL_IMPUTED}</extra>"))
+                .x_axis(AXIS_MAIN_X)
+                .y_axis(AXIS_MAIN_Y),
+        );
+    }
+
+    let (manual_dates_plot, manual_km2_plot) =
+        build_manual_points(&dates, &area_dates, &area_level, buckets.manual_days());
+    if !manual_dates_plot.is_empty() {
+        plot.add_trace(
+            Scatter::new(manual_dates_plot, manual_km2_plot)
+                .mode(Mode::Markers)
+                .marker(
+                    Marker::new()
+                        .symbol(MarkerSymbol::Diamond)
+                        .size(MARKER_SIZE_MANUAL)
+                        .color(rgb(COLOR_MANUAL))
+                        .line(Line::new().color(rgb(COLOR_AREA)).width(LINE_WIDTH_MARKER)),
+                )
+                .name(LABEL_MANUAL)
+                .x_axis(AXIS_MAIN_X)
+                .y_axis(AXIS_MAIN_Y),
+        );
+    }
+
+    let (excluded_dates_plot, (excluded_km2_plot, excluded_reasons)) =
+        build_excluded_points(&dates, &area_dates, &area_level, buckets.excluded_days());
+    if !excluded_dates_plot.is_empty() {
+        plot.add_trace(
+            Scatter::new(excluded_dates_plot, excluded_km2_plot)
+                .mode(Mode::Markers)
+                .marker(
+                    Marker::new()
+                        .symbol(MarkerSymbol::CircleOpen)
+                        .size(MARKER_SIZE_EXCLUDED)
+                        .color(rgb(COLOR_AREA))
+                        .line(Line::new().color(rgb(COLOR_AREA)).width(LINE_WIDTH_MARKER)),
+                )
+                .text_array(excluded_reasons)
+                .hover_template(format!(
+                    "%{{y:{}}} {}<br>%{{text}}<extra>{LABEL_EXCLUDED}</extra>",
+                    unit.hover_format(),
+                    unit.label()
+                ))
+                .name(LABEL_EXCLUDED)
+                .x_axis(AXIS_MAIN_X)
+                .y_axis(AXIS_MAIN_Y),
+        );
+    }
+
+    if let Some(extremes) = &extremes {
+        let points = extremes_points(extremes);
+        plot.add_trace(
+            Scatter::new(
+                points.iter().map(|(_, point)| point.date.clone()).collect(),
+                points
+                    .iter()
+                    .map(|(_, point)| unit.of_km2(point.area_km2))
+                    .collect(),
+            )
+            .mode(Mode::Markers)
+            .marker(
+                Marker::new()
+                    .size(MARKER_SIZE_EXTREME)
+                    .color(rgb(COLOR_AREA))
+                    .line(
+                        Line::new()
+                            .color(rgb(COLOR_TEXT_BASE))
+                            .width(LINE_WIDTH_MARKER),
+                    ),
+            )
+            .text_array(points.iter().map(|(label, _)| *label).collect())
+            .hover_template(format!(
+                "%{{text}}<br>%{{y:{}}} {}<extra></extra>",
+                unit.hover_format(),
+                unit.label()
+            ))
+            .show_legend(false)
+            .x_axis(AXIS_MAIN_X)
+            .y_axis(AXIS_MAIN_Y),
+        );
+    }
+
+    let forecast_ref = forecast;
+    if render_config.smoothed
+        && let Some(smoothed) = forecast_ref.and_then(|forecast| forecast.smoothed.as_ref())
+        && !smoothed.dates.is_empty()
+    {
+        plot.add_trace(
+            Scatter::new(smoothed.dates.clone(), level_of(&smoothed.lower))
+                .mode(Mode::Lines)
+                .line(Line::new().color(rgba(COLOR_AREA_TRANSPARENT)))
+                .show_legend(false)
+                .hover_info(HoverInfo::Skip)
+                .x_axis(AXIS_MAIN_X)
+                .y_axis(AXIS_MAIN_Y),
+        );
+        plot.add_trace(
+            Scatter::new(smoothed.dates.clone(), level_of(&smoothed.upper))
+                .mode(Mode::Lines)
+                .fill(Fill::ToNextY)
+                .fill_color(rgba(COLOR_SMOOTHED_BAND))
+                .line(Line::new().color(rgba(COLOR_AREA_TRANSPARENT)))
+                .show_legend(false)
+                .hover_info(HoverInfo::Skip)
+                .x_axis(AXIS_MAIN_X)
+                .y_axis(AXIS_MAIN_Y),
+        );
+        plot.add_trace(
+            Scatter::new(smoothed.dates.clone(), level_of(&smoothed.level))
+                .mode(Mode::Lines)
+                .line(
+                    Line::new()
+                        .color(rgb(COLOR_SMOOTHED_LINE))
+                        .width(LINE_WIDTH_CHANGE),
+                )
+                .name(LABEL_SMOOTHED)
+                .x_axis(AXIS_MAIN_X)
+                .y_axis(AXIS_MAIN_Y),
+        );
+    }
+    if let Some(forecast) = forecast_ref {
+        add_forecast_traces(&mut plot, forecast, unit, true);
+        for horizon in &forecast.horizons {
+            add_forecast_traces(&mut plot, horizon, unit, false);
+        }
+    }
+
+    plot.add_trace(
+        Scatter::new(change_dates_plot, change_values_plot)
+            .mode(Mode::Lines)
+            .fill(Fill::ToZeroY)
+            .fill_color(rgba(COLOR_CHANGE_FILL))
+            .line(
+                Line::new()
+                    .color(rgb(COLOR_CHANGE_LINE))
+                    .width(LINE_WIDTH_CHANGE)
+                    .simplify(true),
+            )
+            .name(LABEL_AVG_CHANGE)
+            .x_axis(AXIS_CHANGE_X)
+            .y_axis(AXIS_CHANGE_Y),
+    );
+    if !unspecified_change_plot.dates.is_empty() {
+        plot.add_trace(
+            Scatter::new(
+                unspecified_change_plot.dates.clone(),
+                unspecified_change_plot.reference_values,
+            )
+            .mode(Mode::Lines)
+            .line(Line::new().color(rgba(COLOR_UNSPECIFIED_TRANSPARENT)))
+            .show_legend(false)
+            .hover_info(HoverInfo::Skip)
+            .x_axis(AXIS_CHANGE_X)
+            .y_axis(AXIS_CHANGE_Y),
+        );
+        plot.add_trace(
+            Scatter::new(
+                unspecified_change_plot.dates,
+                unspecified_change_plot.values,
+            )
+            .mode(Mode::Lines)
+            .fill(Fill::ToNextY)
+            .fill_color(rgba(COLOR_UNSPECIFIED_CHANGE_FILL))
+            .line(
+                Line::new()
+                    .color(rgb(COLOR_UNSPECIFIED_CHANGE_LINE))
+                    .width(LINE_WIDTH_CHANGE)
+                    .simplify(true),
+            )
+            .visible(Visible::LegendOnly)
+            .name(LABEL_UNSPECIFIED_CHANGE)
+            .x_axis(AXIS_CHANGE_X)
+            .y_axis(AXIS_CHANGE_Y),
+        );
+    }
+    if render_config.trend
+        && let Some(smoothed) = forecast.and_then(|forecast| forecast.smoothed.as_ref())
+        && !smoothed.dates.is_empty()
+    {
+        plot.add_trace(
+            Scatter::new(smoothed.dates.clone(), change_of(&smoothed.trend_lower))
+                .mode(Mode::Lines)
+                .line(Line::new().color(rgba(COLOR_AREA_TRANSPARENT)))
+                .show_legend(false)
+                .hover_info(HoverInfo::Skip)
+                .x_axis(AXIS_CHANGE_X)
+                .y_axis(AXIS_CHANGE_Y),
+        );
+        plot.add_trace(
+            Scatter::new(smoothed.dates.clone(), change_of(&smoothed.trend_upper))
+                .mode(Mode::Lines)
+                .fill(Fill::ToNextY)
+                .fill_color(rgba(COLOR_TREND_BAND))
+                .line(Line::new().color(rgba(COLOR_AREA_TRANSPARENT)))
+                .show_legend(false)
+                .hover_info(HoverInfo::Skip)
+                .x_axis(AXIS_CHANGE_X)
+                .y_axis(AXIS_CHANGE_Y),
+        );
+        plot.add_trace(
+            Scatter::new(smoothed.dates.clone(), change_of(&smoothed.trend))
+                .mode(Mode::Lines)
+                .line(
+                    Line::new()
+                        .color(rgb(COLOR_TREND_LINE))
+                        .width(LINE_WIDTH_CHANGE),
+                )
+                .name(LABEL_TREND)
+                .x_axis(AXIS_CHANGE_X)
+                .y_axis(AXIS_CHANGE_Y),
+        );
+    }
+
+    let mut annotations = Vec::new();
+    if let (Some(last_date), Some(last_km2)) = (area_dates.last(), occupied_area.last()) {
+        annotations.push(
+            Annotation::new()
+                .text(unit.format(*last_km2, locale))
+                .x(last_date.clone())
+                .y(unit.of_km2(*last_km2))
+                .x_ref(AXIS_REF_X)
+                .y_ref(AXIS_REF_Y)
+                .x_anchor(Anchor::Left)
+                .y_anchor(Anchor::Bottom)
+                .ax(ANNOTATION_OFFSET_X)
+                .ay(ANNOTATION_OFFSET_Y)
+                .ax_ref(AXIS_REF_PIXEL)
+                .ay_ref(AXIS_REF_PIXEL)
+                .show_arrow(true)
+                .arrow_head(ARROW_HEAD)
+                .arrow_size(ARROW_SIZE)
+                .arrow_width(ARROW_WIDTH)
+                .arrow_color(rgba(COLOR_ARROW))
+                .font(
+                    Font::new()
+                        .size(FONT_SIZE_ANNOTATION)
+                        .color(rgb(COLOR_TEXT_ANNOTATION)),
+                )
+                .background_color(rgba(COLOR_PANEL_BG))
+                .border_color(rgba(COLOR_PANEL_BORDER))
+                .border_width(LINE_WIDTH_MARKER),
+        );
+    }
+    if let Some(forecast) = forecast_ref {
+        annotations.extend(forecast_label(forecast, unit, locale, true));
+        for horizon in &forecast.horizons {
+            annotations.extend(forecast_label(horizon, unit, locale, false));
+        }
+    }
+    annotations.push(
+        Annotation::new()
+            .text(format!(
+                "{}% {LABEL_UKRAINE}",
+                locale.format(ukraine_percent, 2)
+            ))
+            .x(UKRAINE_LABEL_X)
+            .y(UKRAINE_LABEL_Y)
+            .x_ref(AXIS_REF_PAPER)
+            .y_ref(AXIS_REF_PAPER)
+            .x_anchor(Anchor::Right)
+            .y_anchor(Anchor::Top)
+            .show_arrow(false)
+            .font(Font::new().size(FONT_SIZE_BASE).color(rgb(COLOR_TEXT_BASE)))
+            .background_color(rgba(COLOR_LABEL_BG))
+            .border_color(rgba(COLOR_LABEL_BORDER))
+            .border_width(LINE_WIDTH_MARKER),
+    );
+    if let Some(extremes) = &extremes {
+        for (label, point) in extremes_points(extremes) {
+            // Максимум подписывается над точкой, минимум — под ней.
+            let above = label == LABEL_MAX;
+            annotations.push(
+                Annotation::new()
+                    .text(format!("{label}: {}", unit.format(point.area_km2, locale)))
+                    .x(point.date.clone())
+                    .y(unit.of_km2(point.area_km2))
+                    .x_ref(AXIS_REF_X)
+                    .y_ref(AXIS_REF_Y)
+                    .y_anchor(if above { Anchor::Bottom } else { Anchor::Top })
+                    .ax(0.0)
+                    .ay(if above {
+                        -EXTREME_OFFSET_Y
+                    } else {
+                        EXTREME_OFFSET_Y
+                    })
+                    .ax_ref(AXIS_REF_PIXEL)
+                    .ay_ref(AXIS_REF_PIXEL)
+                    .show_arrow(true)
+                    .arrow_head(ARROW_HEAD)
+                    .arrow_size(ARROW_SIZE)
+                    .arrow_width(ARROW_WIDTH)
+                    .arrow_color(rgba(COLOR_ARROW))
+                    .font(
+                        Font::new()
+                            .size(FONT_SIZE_ANNOTATION)
+                            .color(rgb(COLOR_TEXT_ANNOTATION)),
+                    )
+                    .background_color(rgba(COLOR_PANEL_BG))
+                    .border_color(rgba(COLOR_PANEL_BORDER))
+                    .border_width(LINE_WIDTH_MARKER),
+            );
+        }
+    }
+    if let (Some(last_date), Some(last_value)) =
+        (change_series.labels.last(), change_series.values.last())
+    {
+        annotations.push(
+            Annotation::new()
+                .text(format!(
+                    "◀ {} {}",
+                    locale.format_signed(*last_value, 1),
+                    change_unit.per_day_label()
+                ))
+                .x(last_date.clone())
+                .y(*last_value)
+                .x_ref(AXIS_CHANGE_X)
+                .y_ref(AXIS_CHANGE_Y)
+                .x_anchor(Anchor::Left)
+                .y_anchor(Anchor::Middle)
+                .x_shift(CHANGE_OFFSET_X)
+                .show_arrow(false)
+                .font(
+                    Font::new()
+                        .size(FONT_SIZE_ANNOTATION)
+                        .color(rgb(COLOR_TEXT_ANNOTATION)),
+                )
+                .background_color(rgba(COLOR_PANEL_BG))
+                .border_color(rgba(COLOR_PANEL_BORDER))
+                .border_width(LINE_WIDTH_MARKER),
+        );
+    }
+
+    let reference_dates = [change_series.baseline];
+    let mut marker_shapes: Vec<Shape> = reference_dates
+        .iter()
+        .map(|date| {
+            let date_str = date.format(DATE_FORMAT).to_string();
+            Shape::new()
+                .shape_type(ShapeType::Line)
+                .layer(ShapeLayer::Below)
+                .x_ref(AXIS_REF_X)
+                .y_ref(AXIS_REF_PAPER)
+                .x0(date_str.clone())
+                .x1(date_str)
+                .y0(0)
+                .y1(1)
+                .line(
+                    ShapeLine::new()
+                        .color(rgba(COLOR_MARKER_LINE))
+                        .width(LINE_WIDTH_MARKER)
+                        .dash(DashType::Dash),
+                )
+        })
+        .collect();
+    let (reference_shapes, reference_annotations) = build_reference_lines(
+        &render_config.reference_lines,
+        &dates,
+        &occupied_area,
+        unit,
+        locale,
+    );
+    marker_shapes.extend(reference_shapes);
+    annotations.extend(reference_annotations);
+    let phases = phase_stats(&render_config.phases, &dates, &occupied_area);
+    let (phase_shapes, phase_annotations) = build_phase_bands(&phases);
+    marker_shapes.extend(phase_shapes);
+    annotations.extend(phase_annotations);
+
+    // Обе панели делят одну ось X: общая подсказка и линия-указатель проходят через них сразу.
+    let main_x_axis = Axis::new()
+        .title(Title::new())
+        .tick_format(TICK_FORMAT_MONTH_YEAR)
+        .hover_format(if render_config.intraday {
+            HOVER_FORMAT_DATE_TIME
+        } else {
+            HOVER_FORMAT_DATE
+        })
+        .n_ticks(X_MAIN_TICKS_COUNT)
+        .tick_font(Font::new().size(FONT_SIZE_AXIS_TICK))
+        .show_spikes(true)
+        .spike_mode(SpikeMode::Across)
+        .spike_snap(SpikeSnap::Cursor)
+        .spike_dash(DashType::Dot)
+        .spike_thickness(SPIKE_THICKNESS)
+        .spike_color(rgba(COLOR_AXIS_LINE))
+        .ticks(TicksDirection::Outside)
+        .tick_length(TICK_LENGTH)
+        .tick_color(rgba(COLOR_AXIS_TICK))
+        .show_line(true)
+        .line_color(rgba(COLOR_AXIS_LINE))
+        .grid_color(rgba(COLOR_AXIS_GRID_LIGHT))
+        .grid_width(AXIS_GRID_WIDTH)
+        .auto_margin(true);
+    // Строки с временем внутри дня должны трактоваться как даты, а не как категории.
+    let main_x_axis = if render_config.intraday {
+        main_x_axis.type_(AxisType::Date)
+    } else {
+        main_x_axis
+    };
+    let forecast_upper_km2 = forecast_ref
+        .into_iter()
+        .flat_map(|forecast| std::iter::once(forecast).chain(&forecast.horizons))
+        .flat_map(|forecast| forecast.upper.iter())
+        .map(|&thousands| thousands * AREA_THOUSANDS_DIVISOR);
+    let max_level = occupied_area
+        .iter()
+        .copied()
+        .chain(forecast_upper_km2)
+        .map(|km2| unit.of_km2(km2))
+        .fold(0.0, f64::max);
+    let scale_menu = build_scale_menu(
+        &dates,
+        &area_level,
+        max_level,
+        render_config
+            .index_date
+            .unwrap_or(render_config.avg_change_start),
+        unit,
+        locale,
+    );
+    let layout = Layout::new()
+        .font(
+            Font::new()
+                .family(FONT_FAMILY)
+                .size(FONT_SIZE_BASE)
+                .color(rgb(COLOR_TEXT_BASE)),
+        )
+        .auto_size(true)
+        .margin(
+            Margin::new()
+                .left(MARGIN_LEFT)
+                .right(MARGIN_RIGHT)
+                .top(MARGIN_TOP)
+                .bottom(MARGIN_BOTTOM)
+                .pad(MARGIN_PAD),
+        )
+        .update_menus(vec![scale_menu])
+        .grid(
+            LayoutGrid::new()
+                .rows(GRID_ROWS)
+                .columns(GRID_COLS)
+                .pattern(GridPattern::Coupled)
+                .y_gap(GRID_Y_GAP)
+                .row_order(RowOrder::TopToBottom),
+        )
+        .hover_mode(HoverMode::XUnified)
+        .show_legend(true)
+        .legend(
+            Legend::new()
+                .orientation(Orientation::Horizontal)
+                .item_click(ItemClick::Toggle)
+                .item_double_click(ItemClick::ToggleOthers)
+                .x(LEGEND_X)
+                .x_anchor(Anchor::Center)
+                .y(LEGEND_Y)
+                .y_anchor(Anchor::Bottom)
+                .font(Font::new().size(LEGEND_FONT_SIZE))
+                .background_color(rgba(COLOR_LEGEND_BG))
+                .border_color(rgba(COLOR_LEGEND_BORDER))
+                .border_width(LEGEND_BORDER_WIDTH),
+        )
+        .annotations(annotations)
+        .shapes(marker_shapes)
+        .x_axis(main_x_axis)
+        .y_axis(
+            Axis::new()
+                .title(Title::with_text(unit.label()).font(Font::new().size(FONT_SIZE_AXIS_TITLE)))
+                .n_ticks(Y_MAIN_TICKS_COUNT)
+                .tick_font(Font::new().size(FONT_SIZE_AXIS_TICK))
+                .ticks(TicksDirection::Outside)
+                .tick_length(TICK_LENGTH)
+                .tick_color(rgba(COLOR_AXIS_TICK))
+                .separate_thousands(true)
+                .show_line(true)
+                .line_color(rgba(COLOR_AXIS_LINE))
+                .grid_color(rgba(COLOR_AXIS_GRID_MEDIUM))
+                .grid_width(AXIS_GRID_WIDTH)
+                .auto_margin(true),
+        )
+        .y_axis2(
+            Axis::new()
+                .title(
+                    Title::with_text(change_unit.per_day_label())
+                        .font(Font::new().size(FONT_SIZE_AXIS_TITLE)),
+                )
+                .hover_format(HOVER_FORMAT_KM2_PER_DAY)
+                .n_ticks(Y_CHANGE_TICKS_COUNT)
+                .tick_font(Font::new().size(FONT_SIZE_AXIS_TICK))
+                .ticks(TicksDirection::Outside)
+                .tick_length(TICK_LENGTH)
+                .tick_color(rgba(COLOR_AXIS_TICK))
+                .separate_thousands(true)
+                .show_line(true)
+                .line_color(rgba(COLOR_AXIS_LINE))
+                .grid_color(rgba(COLOR_AXIS_GRID_MEDIUM))
+                .grid_width(AXIS_GRID_WIDTH)
+                .auto_margin(true),
+        );
+
+    plot.set_layout(layout);
+    plot.set_configuration(Configuration::new().responsive(true));
+    let mut yoy_plot = build_yoy_chart(&change_series.dates, &change_series.values, change_unit);
+    let category_series = build_category_series_with(buckets, render_config.interpolation)?;
+    let mut category_plot = build_category_chart(&category_series, max_points, unit);
+    let mut flows_plot = build_flows_chart(&category_series, change_unit);
+    for plot in [
+        &mut plot,
+        &mut yoy_plot,
+        &mut category_plot,
+        &mut flows_plot,
+    ] {
+        localize_numbers(plot, locale);
+    }
+
+    let forecast_summary = forecast_ref.and_then(ForecastOverlay::summary);
+    let forecast_horizons = forecast_ref
+        .map(|forecast| {
+            forecast
+                .horizons
+                .iter()
+                .filter_map(ForecastOverlay::summary)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(ChartOutput {
+        main_plot: plot,
+        yoy_plot,
+        category_plot,
+        flows_plot,
+        summary: ChartSummary {
+            latest_date: latest_date_label,
+            latest_area_km2,
+            ukraine_percent,
+            daily_change_km2,
+            weekly_change_km2,
+            forecast: forecast_summary,
+            forecast_horizons,
+            extremes,
+        },
+        static_svg,
+        comparison: None,
+        front: None,
+        diagnostics: forecast
+            .filter(|_| render_config.diagnostics)
+            .and_then(|forecast| forecast.diagnostics.clone()),
+        change_input: render_config.page.wasm_url.is_some().then(|| ChangeInput {
+            dates: dates
+                .iter()
+                .map(|date| date.format(DATE_FORMAT).to_string())
+                .collect(),
+            changes: daily_change_series(&change_of(&occupied_area)),
+            baseline: render_config
+                .avg_change_start
+                .format(DATE_FORMAT)
+                .to_string(),
+            smoothing: render_config.change_smoothing,
+            max_points: render_config.downsample.then_some(max_points / 2),
+        }),
+        table: build_series_table(
+            &dates,
+            &occupied_area,
+            &measured,
+            render_config.interpolation,
+            render_config.table_days,
+        ),
+        milestones: milestone_pace(
+            latest_date,
+            &occupied_area,
+            render_config.change_smoothing,
+            &render_config.milestones,
+        ),
+        phases,
+    })
+}
+
+/// Темп — последнее значение сглаженного суточного изменения, как на нижней панели.
+fn milestone_pace(
+    latest_date: NaiveDate,
+    occupied_area: &[f64],
+    smoothing: SmoothingSpec,
+    milestones: &[Milestone],
+) -> Option<MilestonePace> {
+    if milestones.is_empty() {
+        return None;
+    }
+    let current_km2 = *occupied_area.last()?;
+    let pace_km2_per_day = smoothing
+        .apply(&daily_change_series(occupied_area))
+        .into_iter()
+        .rev()
+        .find_map(|value| value)?;
+    Some(MilestonePace {
+        as_of: latest_date,
+        pace_km2_per_day,
+        etas: milestone_etas(current_km2, pace_km2_per_day, milestones),
+    })
+}
+
+/// Наивная линейная экстраполяция: срок — оставшаяся площадь, делённая на темп, если темп
+/// ведёт к рубежу (рост к рубежу выше текущей площади, сокращение — к рубежу ниже).
+pub fn milestone_etas(
+    current_km2: f64,
+    pace_km2_per_day: f64,
+    milestones: &[Milestone],
+) -> Vec<MilestoneEta> {
+    milestones
+        .iter()
+        .map(|milestone| {
+            let remaining_km2 = milestone.area_km2 - current_km2;
+            let days = remaining_km2 / pace_km2_per_day;
+            MilestoneEta {
+                label: milestone.label.clone(),
+                target_km2: milestone.area_km2,
+                remaining_km2,
+                days: (days.is_finite() && days > 0.0).then_some(days),
+            }
+        })
+        .collect()
+}
+
+/// Последние `days` дней ряда от новых к старым.
+fn build_series_table(
+    dates: &[NaiveDate],
+    occupied_area: &[f64],
+    measured: &[bool],
+    interpolation: InterpolationStrategy,
+    days: usize,
+) -> Vec<SeriesTableRow> {
+    let shown = |idx: usize| {
+        let measured = measured.get(idx).copied().unwrap_or(true);
+        (measured || interpolation != InterpolationStrategy::None).then_some(occupied_area[idx])
+    };
+    (dates.len().saturating_sub(days)..dates.len())
+        .rev()
+        .map(|idx| {
+            let area_km2 = shown(idx);
+            SeriesTableRow {
+                date: dates[idx],
+                area_km2,
+                change_km2: idx
+                    .checked_sub(1)
+                    .and_then(shown)
+                    .zip(area_km2)
+                    .map(|(previous, current)| current - previous),
+                measured: measured.get(idx).copied().unwrap_or(true),
+            }
+        })
+        .collect()
+}
+
+fn prepare_change_series(
+    dates: &[NaiveDate],
+    occupied_area: &[f64],
+    baseline: NaiveDate,
+    smoothing: SmoothingSpec,
+) -> PreparedChangeSeries {
+    let smoothed_daily = smoothing.apply(&daily_change_series(occupied_area));
+    let (filtered_dates, values) = dates
+        .iter()
+        .zip(smoothed_daily)
+        .filter_map(|(date, value)| value.map(|v| (*date, v)))
+        .filter(|(date, _)| *date >= baseline)
+        .unzip::<_, _, Vec<_>, Vec<_>>();
+    let labels = filtered_dates
+        .iter()
+        .map(|date| date.format(DATE_FORMAT).to_string())
+        .collect();
+
+    PreparedChangeSeries {
+        baseline,
+        smoothing,
+        dates: filtered_dates,
+        labels,
+        values,
+    }
+}
+
+fn build_yoy_chart(change_dates: &[NaiveDate], change_values: &[f64], unit: AreaUnit) -> Plot {
+    let per_day = unit.per_day_label();
+    let mut plot = Plot::new();
+
+    if let Some(envelope) = build_yoy_envelope(change_dates, change_values) {
+        plot.add_trace(
+            Scatter::new(envelope.dates.clone(), envelope.lower)
+                .mode(Mode::Lines)
+                .line(Line::new().color(rgba(COLOR_YOY_BAND_LINE)))
+                .show_legend(false)
+                .x_axis(AXIS_YOY_X)
+                .y_axis(AXIS_YOY_Y),
+        );
+        plot.add_trace(
+            Scatter::new(envelope.dates, envelope.upper)
+                .mode(Mode::Lines)
+                .fill(Fill::ToNextY)
+                .fill_color(rgba(COLOR_YOY_BAND))
+                .line(Line::new().color(rgba(COLOR_YOY_BAND_LINE)))
+                .name(LABEL_YOY_BAND)
+                .x_axis(AXIS_YOY_X)
+                .y_axis(AXIS_YOY_Y),
+        );
+    }
+
+    if let Some(std_series) = build_yoy_stddev_series(change_dates, change_values) {
+        plot.add_trace(
+            Scatter::new(std_series.dates, std_series.values)
+                .mode(Mode::Lines)
+                .hover_template(format!(
+                    "Дата: %{{x|%d.%m}}<br>СКО: %{{y:.1f}} {per_day}<extra></extra>"
+                ))
+                .fill(Fill::ToZeroY)
+                .fill_color(rgba(COLOR_YOY_STDDEV_FILL))
+                .line(
+                    Line::new()
+                        .width(LINE_WIDTH_YOY)
+                        .dash(DashType::Solid)
+                        .color(rgb(COLOR_YOY_STDDEV)),
+                )
+                .name(LABEL_YOY_STDDEV)
+                .visible(Visible::LegendOnly)
+                .x_axis(AXIS_YOY_X)
+                .y_axis(AXIS_YOY_Y),
+        );
+    }
+
+    let year_series = build_yoy_series_by_year(change_dates, change_values);
+    let year_count = year_series.len();
+    for (idx, year_series) in year_series.into_iter().enumerate() {
+        let alpha = yoy_line_alpha(idx, year_count);
+        plot.add_trace(
+            Scatter::new(year_series.dates, year_series.values)
+                .mode(Mode::Lines)
+                .hover_template(format!(
+                    "Дата: %{{x|%d.%m}}.%{{fullData.name}}<br>Значение: %{{y:.1f}} {per_day}<extra></extra>"
+                ))
+                .line(
+                    Line::new()
+                        .width(LINE_WIDTH_YOY)
+                        .simplify(true)
+                        .color(rgba((
+                            COLOR_YOY_LINE.0,
+                            COLOR_YOY_LINE.1,
+                            COLOR_YOY_LINE.2,
+                            alpha,
+                        ))),
+                )
+                .name(year_series.year.to_string())
+                .x_axis(AXIS_YOY_X)
+                .y_axis(AXIS_YOY_Y),
+        );
+    }
+
+    let monthly_box_series = build_monthly_box_series(change_dates, change_values);
+    if !monthly_box_series.values.is_empty() {
+        plot.add_trace(
+            BoxPlot::new_xy(monthly_box_series.dates, monthly_box_series.values)
+                .name(LABEL_BOX_DAILY_CHANGE)
+                .show_legend(false)
+                .box_points(BoxPoints::False)
+                .fill_color(rgba(COLOR_CHANGE_FILL))
+                .line(
+                    Line::new()
+                        .color(rgb(COLOR_CHANGE_LINE))
+                        .width(LINE_WIDTH_CHANGE),
+                )
+                .x_axis(AXIS_YOY_BOX_X)
+                .y_axis(AXIS_YOY_BOX_Y),
+        );
+    }
+
+    plot.set_layout(build_yoy_layout(&per_day));
+    plot.set_configuration(Configuration::new().responsive(true));
+    plot
+}
+
+/// Накопленные площади категорий контроля; пустые категории не рисуются.
+fn build_category_chart(series: &CategorySeries, max_points: usize, unit: AreaUnit) -> Plot {
+    let totals = (0..series.dates.len())
+        .map(|idx| {
+            series.occupied_pre_war[idx]
+                + series.occupied[idx]
+                + series.liberated[idx]
+                + series.unspecified[idx]
+        })
+        .collect_vec();
+    let indices = downsample_min_max_indices(&totals, max_points);
+    let dates = indices
+        .iter()
+        .map(|&idx| series.dates[idx].format(DATE_FORMAT).to_string())
+        .collect_vec();
+
+    let mut plot = Plot::new();
+    for (label, values, color) in [
+        (
+            LABEL_CATEGORY_PRE_WAR,
+            &series.occupied_pre_war,
+            COLOR_CATEGORY_PRE_WAR,
+        ),
+        (LABEL_CATEGORY_OCCUPIED, &series.occupied, COLOR_AREA),
+        (
+            LABEL_CATEGORY_LIBERATED,
+            &series.liberated,
+            COLOR_CATEGORY_LIBERATED,
+        ),
+        (
+            LABEL_CATEGORY_UNSPECIFIED,
+            &series.unspecified,
+            COLOR_CATEGORY_UNSPECIFIED,
+        ),
+    ] {
+        if values.iter().all(|value| value.abs() < f64::EPSILON) {
+            continue;
+        }
+        let values_level = indices
+            .iter()
+            .map(|&idx| unit.of_km2(values[idx]))
+            .collect_vec();
+        plot.add_trace(
+            Scatter::new(dates.clone(), values_level)
+                .mode(Mode::Lines)
+                .stack_group(CATEGORY_STACK_GROUP)
+                .hover_template(format!(
+                    "%{{x}}<br>{label}: %{{y:{}}} {}<extra></extra>",
+                    unit.hover_format(),
+                    unit.label()
+                ))
+                .line(Line::new().width(LINE_WIDTH_MARKER).color(rgb(color)))
+                .fill_color(rgba((color.0, color.1, color.2, CATEGORY_FILL_ALPHA)))
+                .name(label),
+        );
+    }
+
+    plot.set_layout(build_single_panel_layout(unit.label()));
+    plot.set_configuration(Configuration::new().responsive(true));
+    plot
+}
+
+/// Этапы по загруженному ряду: границы сдвигаются к ближайшим дням с известной площадью,
+/// этап вне ряда пропускается с предупреждением.
+pub fn phase_stats(phases: &[Phase], dates: &[NaiveDate], occupied: &[f64]) -> Vec<PhaseStats> {
+    phases
+        .iter()
+        .filter_map(|phase| {
+            let known = |idx: &usize| occupied[*idx].is_finite();
+            let from = dates.partition_point(|date| *date < phase.start);
+            let to = phase.end.map_or(dates.len(), |end| {
+                dates.partition_point(|date| *date <= end)
+            });
+            let (Some(first), Some(last)) = ((from..to).find(known), (from..to).rev().find(known))
+            else {
+                tracing::warn!(
+                    label = %phase.label,
+                    start = %phase.start,
+                    "Phase is outside the loaded history; skipping"
+                );
+                return None;
+            };
+            let net_change_km2 = occupied[last] - occupied[first];
+            let days = (dates[last] - dates[first]).num_days();
+            #[allow(clippy::cast_precision_loss)]
+            let mean_rate_km2_per_day = (days > 0).then(|| net_change_km2 / days as f64);
+            Some(PhaseStats {
+                label: phase.label.clone(),
+                start: dates[first],
+                end: dates[last],
+                net_change_km2,
+                mean_rate_km2_per_day,
+            })
+        })
+        .collect()
+}
+
+/// Полосы этапов на фоне обеих панелей с подписью у верхнего края основной.
+fn build_phase_bands(phases: &[PhaseStats]) -> (Vec<Shape>, Vec<Annotation>) {
+    phases
+        .iter()
+        .zip(COLOR_PHASE_BANDS.iter().cycle())
+        .map(|(phase, &color)| {
+            let start = phase.start.format(DATE_FORMAT).to_string();
+            let shape = Shape::new()
+                .shape_type(ShapeType::Rect)
+                .layer(ShapeLayer::Below)
+                .x_ref(AXIS_REF_X)
+                .y_ref(AXIS_REF_PAPER)
+                .x0(start.clone())
+                .x1(phase.end.format(DATE_FORMAT).to_string())
+                .y0(0)
+                .y1(1)
+                .fill_color(rgba(color))
+                .line(ShapeLine::new().width(0.0));
+            let annotation = Annotation::new()
+                .text(phase.label.clone())
+                .x(start)
+                .y(1)
+                .x_ref(AXIS_REF_X)
+                .y_ref(AXIS_REF_PAPER)
+                .x_anchor(Anchor::Left)
+                .y_anchor(Anchor::Top)
+                .show_arrow(false)
+                .font(
+                    Font::new()
+                        .size(FONT_SIZE_ANNOTATION)
+                        .color(rgb(COLOR_TEXT_ANNOTATION)),
+                );
+            (shape, annotation)
+        })
+        .unzip()
+}
+
+/// Пунктирные линии уровней на основном графике с подписью у левого края.
+///
+/// Линия на дату вне загруженного ряда пропускается с предупреждением.
+fn build_reference_lines(
+    lines: &[ReferenceLine],
+    dates: &[NaiveDate],
+    occupied: &[f64],
+    unit: AreaUnit,
+    locale: NumberLocale,
+) -> (Vec<Shape>, Vec<Annotation>) {
+    lines
+        .iter()
+        .filter_map(|line| {
+            let area_km2 = match line.level {
+                ReferenceLevel::AreaKm2(area_km2) => area_km2,
+                ReferenceLevel::AsOf(date) => {
+                    let Ok(idx) = dates.binary_search(&date) else {
+                        tracing::warn!(
+                            label = %line.label,
+                            date = %date,
+                            "Reference line date is outside the loaded history; skipping"
+                        );
+                        return None;
+                    };
+                    occupied[idx]
+                }
+            };
+            let level = unit.of_km2(area_km2);
+            let shape = Shape::new()
+                .shape_type(ShapeType::Line)
+                .layer(ShapeLayer::Below)
+                .x_ref(AXIS_REF_PAPER)
+                .y_ref(AXIS_REF_Y)
+                .x0(0)
+                .x1(1)
+                .y0(level)
+                .y1(level)
+                .line(
+                    ShapeLine::new()
+                        .color(rgba(COLOR_MARKER_LINE))
+                        .width(LINE_WIDTH_MARKER)
+                        .dash(DashType::DashDot),
+                );
+            let annotation = Annotation::new()
+                .text(format!("{}: {}", line.label, unit.format(area_km2, locale)))
+                .x(0)
+                .y(level)
+                .x_ref(AXIS_REF_PAPER)
+                .y_ref(AXIS_REF_Y)
+                .x_anchor(Anchor::Left)
+                .y_anchor(Anchor::Bottom)
+                .show_arrow(false)
+                .font(
+                    Font::new()
+                        .size(FONT_SIZE_ANNOTATION)
+                        .color(rgb(COLOR_TEXT_ANNOTATION)),
+                );
+            Some((shape, annotation))
+        })
+        .unzip()
+}
+
+/// Понедельные приобретения (вверх) и потери (вниз) площади с итогом недели.
+fn build_flows_chart(series: &CategorySeries, unit: AreaUnit) -> Plot {
+    let flows = build_weekly_flows(series);
+    let in_unit = |km2: &[f64]| km2.iter().map(|&value| unit.of_km2(value)).collect_vec();
+    let weeks = flows
+        .weeks
+        .iter()
+        .map(|week| week.format(DATE_FORMAT).to_string())
+        .collect_vec();
+    let net = flows
+        .gained
+        .iter()
+        .zip(&flows.lost)
+        .map(|(gained, lost)| unit.of_km2(gained + lost))
+        .collect_vec();
+
+    let mut plot = Plot::new();
+    for (label, values, color) in [
+        (LABEL_FLOW_GAINED, in_unit(&flows.gained), COLOR_FLOW_GAINED),
+        (LABEL_FLOW_LOST, in_unit(&flows.lost), COLOR_FLOW_LOST),
+    ] {
+        plot.add_trace(
+            Bar::new(weeks.clone(), values)
+                .hover_template(format!(
+                    "Неделя с %{{x}}<br>{label}: %{{y:+{}}} {}<extra></extra>",
+                    unit.hover_format(),
+                    unit.label()
+                ))
+                .marker(Marker::new().color(rgb(color)))
+                .name(label),
+        );
+    }
+    plot.add_trace(
+        Scatter::new(weeks, net)
+            .mode(Mode::Lines)
+            .hover_template(format!(
+                "Неделя с %{{x}}<br>{LABEL_FLOW_NET}: %{{y:+{}}} {}<extra></extra>",
+                unit.hover_format(),
+                unit.label()
+            ))
+            .line(
+                Line::new()
+                    .width(LINE_WIDTH_CHANGE)
+                    .color(rgb(COLOR_TEXT_BASE)),
+            )
+            .name(LABEL_FLOW_NET),
+    );
+    plot.set_layout(build_single_panel_layout(unit.label()).bar_mode(BarMode::Relative));
+    plot.set_configuration(Configuration::new().responsive(true));
+    plot
+}
+
+/// Горизонтальные столбцы продвижения (плюс) и отхода (минус) по направлениям, с севера на юг.
+pub fn build_front_chart(shifts: &FrontShifts) -> FrontOutput {
+    let mut plot = Plot::new();
+    for (label, color, advancing) in [
+        (LABEL_FRONT_ADVANCE, COLOR_FLOW_GAINED, true),
+        (LABEL_FRONT_RETREAT, COLOR_FLOW_LOST, false),
+    ] {
+        // Plotly рисует первую категорию внизу, поэтому направления идут в обратном порядке.
+        let (names, values): (Vec<_>, Vec<_>) = shifts
+            .sectors
+            .iter()
+            .rev()
+            .map(|sector| (sector.name.clone(), sector.advance_km()))
+            .filter(|(_, advance)| (*advance >= 0.0) == advancing)
+            .unzip();
+        plot.add_trace(
+            Bar::new(values, names)
+                .orientation(Orientation::Horizontal)
+                .hover_template(format!("%{{y}}<br>{label}: %{{x:+.1f}} км<extra></extra>"))
+                .marker(Marker::new().color(rgb(color)))
+                .name(label),
+        );
+    }
+    plot.set_layout(
+        Layout::new()
+            .font(
+                Font::new()
+                    .family(FONT_FAMILY)
+                    .size(FONT_SIZE_BASE)
+                    .color(rgb(COLOR_TEXT_BASE)),
+            )
+            .auto_size(true)
+            .margin(
+                Margin::new()
+                    .left(MARGIN_LEFT)
+                    .right(MARGIN_RIGHT)
+                    .top(MARGIN_TOP)
+                    .bottom(MARGIN_BOTTOM)
+                    .pad(MARGIN_PAD),
+            )
+            .show_legend(true)
+            .legend(build_yoy_legend())
+            .bar_mode(BarMode::Relative)
+            .x_axis(build_yoy_axis_y_base(LABEL_FRONT_AXIS).zero_line(true))
+            .y_axis(
+                Axis::new()
+                    .tick_font(Font::new().size(FONT_SIZE_AXIS_TICK))
+                    .auto_margin(true),
+            ),
+    );
+    plot.set_configuration(Configuration::new().responsive(true));
+    FrontOutput {
+        plot,
+        before: shifts.before.format(FRONT_TIME_FORMAT).to_string(),
+        after: shifts.after.format(FRONT_TIME_FORMAT).to_string(),
+    }
+}
+
+/// Линии занятой площади двух источников и полоса между ними.
+pub fn build_comparison_chart(
+    primary: &AreaBuckets,
+    source: &ComparisonSource,
+    render_config: &ChartRenderConfig,
+) -> Result<ComparisonOutput, Box<dyn Error>> {
+    let unit = render_config.page.area_unit;
+    let aligned = compare::align_sources(primary, &source.buckets, render_config.interpolation)?;
+    let stats = compare::comparison_stats(&aligned);
+    let widest = aligned
+        .primary
+        .iter()
+        .zip(&aligned.secondary)
+        .map(|(a, b)| (a - b).abs())
+        .collect_vec();
+    let indices = downsample_min_max_indices(&widest, render_config.max_plot_points());
+    let dates = indices
+        .iter()
+        .map(|&idx| aligned.dates[idx].format(DATE_FORMAT).to_string())
+        .collect_vec();
+    let pick = |values: &[f64]| {
+        indices
+            .iter()
+            .map(|&idx| unit.of_km2(values[idx]))
+            .collect_vec()
+    };
+    let primary_km2 = pick(&aligned.primary);
+    let secondary_km2 = pick(&aligned.secondary);
+    let (band_low, band_high): (Vec<f64>, Vec<f64>) = primary_km2
+        .iter()
+        .zip(&secondary_km2)
+        .map(|(a, b)| (a.min(*b), a.max(*b)))
+        .unzip();
+
+    let mut plot = Plot::new();
+    plot.add_trace(
+        Scatter::new(dates.clone(), band_low)
+            .mode(Mode::Lines)
+            .line(Line::new().color(rgba(COLOR_AREA_TRANSPARENT)))
+            .show_legend(false),
+    );
+    plot.add_trace(
+        Scatter::new(dates.clone(), band_high)
+            .mode(Mode::Lines)
+            .fill(Fill::ToNextY)
+            .fill_color(rgba(COLOR_DIVERGENCE_BAND))
+            .line(Line::new().color(rgba(COLOR_AREA_TRANSPARENT)))
+            .name(LABEL_DIVERGENCE),
+    );
+    for (label, values, color) in [
+        (&source.primary_label, primary_km2, COLOR_AREA),
+        (&source.label, secondary_km2, COLOR_COMPARE_SECONDARY),
+    ] {
+        plot.add_trace(
+            Scatter::new(dates.clone(), values)
+                .mode(Mode::Lines)
+                .hover_template(format!(
+                    "%{{x}}<br>{label}: %{{y:{}}} {}<extra></extra>",
+                    unit.hover_format(),
+                    unit.label()
+                ))
+                .line(Line::new().width(LINE_WIDTH_MAIN).color(rgb(color)))
+                .name(label.as_str()),
+        );
+    }
+    plot.set_layout(build_single_panel_layout(unit.label()));
+    plot.set_configuration(Configuration::new().responsive(true));
+    localize_numbers(&mut plot, render_config.page.locale);
+
+    Ok(ComparisonOutput {
+        plot,
+        stats,
+        primary_label: source.primary_label.clone(),
+        label: source.label.clone(),
+    })
+}
+
+/// Разделители чисел на осях и в подсказках Plotly по локали страницы.
+fn localize_numbers(plot: &mut Plot, locale: NumberLocale) {
+    let layout = plot.layout().clone().separators(locale.plotly_separators());
+    plot.set_layout(layout);
+}
+
+/// Однопанельная раскладка вкладок: общая ось дат и подпись единиц по оси Y.
+fn build_single_panel_layout(y_title: &str) -> Layout {
+    Layout::new()
+        .font(
+            Font::new()
+                .family(FONT_FAMILY)
+                .size(FONT_SIZE_BASE)
+                .color(rgb(COLOR_TEXT_BASE)),
+        )
+        .auto_size(true)
+        .margin(
+            Margin::new()
+                .left(MARGIN_LEFT)
+                .right(MARGIN_RIGHT)
+                .top(MARGIN_TOP)
+                .bottom(MARGIN_BOTTOM)
+                .pad(MARGIN_PAD),
+        )
+        .show_legend(true)
+        .legend(build_yoy_legend())
+        .x_axis(
+            build_yoy_axis_x_base()
+                .tick_format(TICK_FORMAT_MONTH_YEAR)
+                .n_ticks(X_MAIN_TICKS_COUNT),
+        )
+        .y_axis(
+            build_yoy_axis_y_base(y_title)
+                .hover_format(".1f")
+                .n_ticks(Y_MAIN_TICKS_COUNT),
+        )
+}
+
+fn build_yoy_layout(y_title: &str) -> Layout {
+    Layout::new()
+        .font(
+            Font::new()
+                .family(FONT_FAMILY)
+                .size(FONT_SIZE_BASE)
+                .color(rgb(COLOR_TEXT_BASE)),
+        )
+        .auto_size(true)
+        .margin(
+            Margin::new()
+                .left(MARGIN_LEFT)
+                .right(MARGIN_RIGHT)
+                .top(MARGIN_TOP)
+                .bottom(MARGIN_BOTTOM)
+                .pad(MARGIN_PAD),
+        )
+        .grid(
+            LayoutGrid::new()
+                .rows(2)
+                .columns(1)
+                .pattern(GridPattern::Independent)
+                .y_gap(GRID_Y_GAP)
+                .row_order(RowOrder::TopToBottom),
+        )
+        .show_legend(true)
+        .legend(build_yoy_legend())
+        .x_axis(build_yoy_primary_x_axis())
+        .y_axis(build_yoy_primary_y_axis(y_title))
+        .x_axis2(build_yoy_secondary_x_axis())
+        .y_axis2(build_yoy_secondary_y_axis(y_title))
+}
+
+fn build_yoy_legend() -> Legend {
+    Legend::new()
+        .orientation(Orientation::Horizontal)
+        .item_click(ItemClick::Toggle)
+        .item_double_click(ItemClick::ToggleOthers)
+        .x(LEGEND_X)
+        .x_anchor(Anchor::Center)
+        .y(LEGEND_Y)
+        .y_anchor(Anchor::Bottom)
+        .font(Font::new().size(FONT_SIZE_AXIS_TICK))
+        .background_color(rgba(COLOR_LEGEND_BG))
+        .border_color(rgba(COLOR_LEGEND_BORDER))
+        .border_width(LEGEND_BORDER_WIDTH)
+}
+
+fn build_yoy_axis_x_base() -> Axis {
+    Axis::new()
+        .tick_format("%b")
+        .n_ticks(X_YOY_TICKS_COUNT)
+        .tick_font(Font::new().size(FONT_SIZE_AXIS_TICK))
+        .ticks(TicksDirection::Outside)
+        .tick_length(TICK_LENGTH)
+        .tick_color(rgba(COLOR_AXIS_TICK))
+        .show_line(true)
+        .line_color(rgba(COLOR_AXIS_LINE))
+        .grid_color(rgba(COLOR_AXIS_GRID_LIGHT))
+        .grid_width(AXIS_GRID_WIDTH)
+        .auto_margin(true)
+}
+
+fn build_yoy_primary_x_axis() -> Axis {
+    build_yoy_axis_x_base()
+        .title(Title::new())
+        .show_tick_labels(false)
+        .hover_format(HOVER_FORMAT_DAY_MONTH)
+}
+
+fn build_yoy_secondary_x_axis() -> Axis {
+    build_yoy_axis_x_base().matches(AXIS_YOY_X)
+}
+
+fn build_yoy_axis_y_base(y_title: &str) -> Axis {
+    Axis::new()
+        .title(Title::with_text(y_title).font(Font::new().size(FONT_SIZE_AXIS_TITLE)))
+        .hover_format(HOVER_FORMAT_KM2_PER_DAY)
+        .tick_font(Font::new().size(FONT_SIZE_AXIS_TICK))
+        .ticks(TicksDirection::Outside)
+        .tick_length(TICK_LENGTH)
+        .tick_color(rgba(COLOR_AXIS_TICK))
+        .separate_thousands(true)
+        .show_line(true)
+        .line_color(rgba(COLOR_AXIS_LINE))
+        .grid_color(rgba(COLOR_AXIS_GRID_MEDIUM))
+        .grid_width(AXIS_GRID_WIDTH)
+        .auto_margin(true)
+}
+
+fn build_yoy_primary_y_axis(y_title: &str) -> Axis {
+    build_yoy_axis_y_base(y_title).n_ticks(Y_YOY_TICKS_COUNT)
+}
+
+fn build_yoy_secondary_y_axis(y_title: &str) -> Axis {
+    build_yoy_axis_y_base(y_title).n_ticks(Y_YOY_MONTH_TICKS_COUNT)
+}
+
+fn build_monthly_box_series(change_dates: &[NaiveDate], change_values: &[f64]) -> MonthlyBoxSeries {
+    if change_dates.is_empty() || change_dates.len() != change_values.len() {
+        return MonthlyBoxSeries::default();
+    }
+
+    let (dates, values) = change_dates
+        .iter()
+        .zip(change_values.iter().copied())
+        .map(|(date, value)| (normalize_to_yoy_month(date.month()), value))
+        .unzip();
+
+    MonthlyBoxSeries { dates, values }
+}
+
+fn normalize_to_yoy_month(month: u32) -> String {
+    let month_index = month
+        .checked_sub(1)
+        .and_then(|value| usize::try_from(value).ok())
+        .expect("month must fit into usize");
+    let (day, hour, minute) = *YOY_BOX_MONTH_ANCHORS
+        .get(month_index)
+        .expect("month must be in [1..=12]");
+    let date_time = NaiveDate::from_ymd_opt(YOY_ANCHOR_YEAR, month, day)
+        .expect("YOY anchor year must support configured month/day values")
+        .and_hms_opt(hour, minute, 0)
+        .expect("configured box-plot month time must be representable");
+    date_time.format(DATE_TIME_FORMAT).to_string()
+}
+
+fn build_yoy_series_by_year(
+    change_dates: &[NaiveDate],
+    change_values: &[f64],
+) -> Vec<YoyYearSeries> {
+    let mut grouped: BTreeMap<i32, Vec<(String, f64)>> = BTreeMap::new();
+
+    for (date, value) in change_dates.iter().zip(change_values.iter()) {
+        grouped
+            .entry(date.year())
+            .or_default()
+            .push((normalize_to_yoy_day(*date), *value));
+    }
+
+    grouped
+        .into_iter()
+        .map(|(year, points)| {
+            let (dates, values) = points.into_iter().unzip::<_, _, Vec<_>, Vec<_>>();
+            YoyYearSeries {
+                year,
+                dates,
+                values,
+            }
+        })
+        .collect()
+}
+
+fn build_yoy_envelope(change_dates: &[NaiveDate], change_values: &[f64]) -> Option<YoyEnvelope> {
+    let mut envelope = BTreeMap::<NaiveDate, (f64, f64)>::new();
+
+    for (date, value) in change_dates.iter().zip(change_values.iter()) {
+        let normalized = normalized_yoy_date(*date);
+        envelope
+            .entry(normalized)
+            .and_modify(|(lower, upper)| {
+                *lower = lower.min(*value);
+                *upper = upper.max(*value);
+            })
+            .or_insert((*value, *value));
+    }
+
+    if envelope.is_empty() {
+        return None;
+    }
+
+    let mut dates = Vec::with_capacity(envelope.len());
+    let mut lower = Vec::with_capacity(envelope.len());
+    let mut upper = Vec::with_capacity(envelope.len());
+    for (date, (min_value, max_value)) in envelope {
+        dates.push(date.format(DATE_FORMAT).to_string());
+        lower.push(min_value);
+        upper.push(max_value);
+    }
+
+    Some(YoyEnvelope {
+        dates,
+        lower,
+        upper,
+    })
+}
+
+fn build_yoy_stddev_series(
+    change_dates: &[NaiveDate],
+    change_values: &[f64],
+) -> Option<YoyStdSeries> {
+    if change_dates.is_empty() || change_dates.len() != change_values.len() {
+        return None;
+    }
+
+    let mut grouped = BTreeMap::<NaiveDate, Vec<f64>>::new();
+    for (date, value) in change_dates.iter().zip(change_values.iter().copied()) {
+        grouped
+            .entry(normalized_yoy_date(*date))
+            .or_default()
+            .push(value);
+    }
+
+    let mut dates = Vec::new();
+    let mut values = Vec::new();
+    for (date, day_values) in grouped {
+        if day_values.len() < 2 {
+            continue;
+        }
+        dates.push(date.format(DATE_FORMAT).to_string());
+        values.push(stddev(&day_values));
+    }
+
+    if dates.is_empty() {
+        None
+    } else {
+        Some(YoyStdSeries { dates, values })
+    }
+}
+
+fn stddev(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / usize_to_f64(values.len(), "stddev");
+    let variance = values
+        .iter()
+        .map(|value| {
+            let delta = *value - mean;
+            delta * delta
+        })
+        .sum::<f64>()
+        / usize_to_f64(values.len(), "stddev");
+    variance.sqrt()
+}
+
+fn normalize_to_yoy_day(date: NaiveDate) -> String {
+    normalized_yoy_date(date).format(DATE_FORMAT).to_string()
+}
+
+fn normalized_yoy_date(date: NaiveDate) -> NaiveDate {
+    NaiveDate::from_ymd_opt(YOY_ANCHOR_YEAR, date.month(), date.day())
+        .expect("YOY anchor year must support all month/day combinations")
+}
+
+fn yoy_line_alpha(index: usize, total: usize) -> f64 {
+    if total <= 1 {
+        return YOY_LINE_ALPHA_MAX;
+    }
+    let position = usize_to_f64(index, "yoy_line_alpha");
+    let span = usize_to_f64(total - 1, "yoy_line_alpha");
+    (YOY_LINE_ALPHA_MAX - YOY_LINE_ALPHA_MIN).mul_add(position / span, YOY_LINE_ALPHA_MIN)
+}
+
+/// Кнопки шкалы основной панели: линейная, логарифмическая и индекс к дате. Индекс — та же
+/// линейная шкала с делениями, подписанными долей от площади на базовую дату (= 100): все
+/// трассы делятся на одну базу, поэтому данные не дублируются. Если площадь на базовую дату
+/// нулевая или дата позже ряда, кнопки индекса нет.
+fn build_scale_menu(
+    dates: &[NaiveDate],
+    area_level: &[f64],
+    max_level: f64,
+    index_date: NaiveDate,
+    unit: AreaUnit,
+    locale: NumberLocale,
+) -> UpdateMenu {
+    let scale = |label: &str, axis_type: &str| {
+        Button::new()
+            .label(label)
+            .method(ButtonMethod::Relayout)
+            .args(json!([{
+                "yaxis.type": axis_type,
+                "yaxis.autorange": true,
+                "yaxis.tickmode": "auto",
+                "yaxis.title.text": unit.label(),
+            }]))
+    };
+    let mut buttons = vec![
+        scale(LABEL_SCALE_LINEAR, "linear"),
+        scale(LABEL_SCALE_LOG, "log"),
+    ];
+    let base_idx = dates.partition_point(|date| *date < index_date);
+    if let Some(&base) = area_level.get(base_idx).filter(|base| **base > 0.0) {
+        let (tick_values, tick_labels) = index_ticks(max_level, base, locale);
+        buttons.push(
+            Button::new()
+                .label(LABEL_SCALE_INDEX)
+                .method(ButtonMethod::Relayout)
+                .args(json!([{
+                    "yaxis.type": "linear",
+                    "yaxis.autorange": true,
+                    "yaxis.tickmode": "array",
+                    "yaxis.tickvals": tick_values,
+                    "yaxis.ticktext": tick_labels,
+                    "yaxis.title.text": format!(
+                        "Индекс, {} = 100",
+                        dates[base_idx].format(HOVER_FORMAT_DATE)
+                    ),
+                }])),
+        );
+    }
+    UpdateMenu::new()
+        .ty(UpdateMenuType::Buttons)
+        .direction(UpdateMenuDirection::Right)
+        .active(0)
+        .buttons(buttons)
+        .x(SCALE_MENU_X)
+        .x_anchor(Anchor::Left)
+        .y(SCALE_MENU_Y)
+        .y_anchor(Anchor::Top)
+        .font(Font::new().size(FONT_SIZE_AXIS_TICK))
+        .background_color(rgba(COLOR_LEGEND_BG))
+        .border_color(rgba(COLOR_LEGEND_BORDER))
+}
+
+/// Деления вида «Индекс»: круглые значения индекса от нуля до максимума графика,
+/// пересчитанные в единицы графика.
+fn index_ticks(max_level: f64, base: f64, locale: NumberLocale) -> (Vec<f64>, Vec<String>) {
+    let max_index = max_level / base * INDEX_BASE;
+    let raw_step = max_index / INDEX_TICKS_COUNT;
+    let magnitude = 10_f64.powf(raw_step.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= raw_step)
+        .unwrap_or(raw_step);
+    let decimals = usize::from(step < 1.0);
+    std::iter::successors(Some(0.0), |index| Some(index + step))
+        .take_while(|index| *index <= max_index + step)
+        .map(|index| (index / INDEX_BASE * base, locale.format(index, decimals)))
+        .unzip()
+}
+
+/// Полоса 95% и средняя линия прогноза; `visible = false` — для горизонтов, которые
+/// страница показывает по выбору.
+fn add_forecast_traces(plot: &mut Plot, forecast: &ForecastOverlay, unit: AreaUnit, visible: bool) {
+    if forecast.dates.is_empty() {
+        return;
+    }
+    let level_of = |thousands: &[f64]| {
+        thousands
+            .iter()
+            .map(|&value| unit.of_km2(value * AREA_THOUSANDS_DIVISOR))
+            .collect_vec()
+    };
+    let visibility = if visible {
+        Visible::True
+    } else {
+        Visible::False
+    };
+    plot.add_trace(
+        Scatter::new(forecast.dates.clone(), level_of(&forecast.lower))
+            .mode(Mode::Lines)
+            .line(Line::new().color(rgba(COLOR_AREA_TRANSPARENT)))
+            .show_legend(false)
+            .hover_info(HoverInfo::Skip)
+            .legend_group(forecast.group())
+            .visible(visibility.clone())
+            .x_axis(AXIS_MAIN_X)
+            .y_axis(AXIS_MAIN_Y),
+    );
+    plot.add_trace(
+        Scatter::new(forecast.dates.clone(), level_of(&forecast.upper))
+            .mode(Mode::Lines)
+            .fill(Fill::ToNextY)
+            .fill_color(rgba(COLOR_AREA_BAND))
+            .line(Line::new().color(rgba(COLOR_AREA_TRANSPARENT)))
+            .show_legend(false)
+            .name(LABEL_CONFIDENCE)
+            .legend_group(forecast.group())
+            .visible(visibility.clone())
+            .x_axis(AXIS_MAIN_X)
+            .y_axis(AXIS_MAIN_Y),
+    );
+    let mean = Scatter::new(forecast.dates.clone(), level_of(&forecast.mean))
+        .line(
+            Line::new()
+                .color(rgb(COLOR_AREA))
+                .width(LINE_WIDTH_FORECAST)
+                .dash(DashType::Dash),
+        )
+        .legend_group(forecast.group())
+        .visible(visibility)
+        .x_axis(AXIS_MAIN_X)
+        .y_axis(AXIS_MAIN_Y);
+    plot.add_trace(match forecast.granularity {
+        Granularity::Day => mean.mode(Mode::Lines).name(LABEL_FORECAST),
+        Granularity::Week => mean
+            .mode(Mode::LinesMarkers)
+            .name(LABEL_FORECAST_WEEKLY)
+            .hover_template(format!(
+                "Неделя вокруг %{{x|%d.%m.%Y}}<br>%{{y:{}}} {}<extra></extra>",
+                unit.hover_format(),
+                unit.label()
+            )),
+    });
+}
+
+/// Подпись последней точки прогноза.
+fn forecast_label(
+    forecast: &ForecastOverlay,
+    unit: AreaUnit,
+    locale: NumberLocale,
+    visible: bool,
+) -> Option<Annotation> {
+    let (last_date, last_mean) = (forecast.dates.last()?, forecast.mean.last()?);
+    let last_mean_km2 = last_mean * AREA_THOUSANDS_DIVISOR;
+    Some(
+        Annotation::new()
+            .text(unit.format(last_mean_km2, locale))
+            .x(last_date.clone())
+            .y(unit.of_km2(last_mean_km2))
+            .x_ref(AXIS_REF_X)
+            .y_ref(AXIS_REF_Y)
+            .x_anchor(Anchor::Right)
+            .y_anchor(Anchor::Bottom)
+            .ax(FORECAST_OFFSET_X)
+            .ay(FORECAST_OFFSET_Y)
+            .ax_ref(AXIS_REF_PIXEL)
+            .ay_ref(AXIS_REF_PIXEL)
+            .show_arrow(true)
+            .arrow_head(ARROW_HEAD)
+            .arrow_size(ARROW_SIZE)
+            .arrow_width(ARROW_WIDTH)
+            .arrow_color(rgba(COLOR_ARROW))
+            .font(
+                Font::new()
+                    .size(FONT_SIZE_ANNOTATION)
+                    .color(rgb(COLOR_TEXT_ANNOTATION)),
+            )
+            .background_color(rgba(COLOR_PANEL_BG))
+            .border_color(rgba(COLOR_PANEL_BORDER))
+            .border_width(LINE_WIDTH_MARKER)
+            .name(forecast.group())
+            .visible(visible),
+    )
+}
+
+/// Максимум всего ряда и минимум с `min_since`; из равных значений берётся первый день.
+fn series_extremes(
+    dates: &[NaiveDate],
+    occupied_area: &[f64],
+    min_since: NaiveDate,
+) -> Option<SeriesExtremes> {
+    let points = || {
+        dates
+            .iter()
+            .zip(occupied_area)
+            .filter(|(_, area)| area.is_finite())
+            .map(|(date, area)| (*date, *area))
+    };
+    let to_extreme = |(date, area_km2): (NaiveDate, f64)| SeriesExtreme {
+        date: date.format(DATE_FORMAT).to_string(),
+        area_km2,
+    };
+    let max = points().reduce(|best, point| if point.1 > best.1 { point } else { best })?;
+    let min = points()
+        .filter(|(date, _)| *date >= min_since)
+        .reduce(|best, point| if point.1 < best.1 { point } else { best });
+    Some(SeriesExtremes {
+        max: to_extreme(max),
+        min: min.map(to_extreme),
+        min_since: min_since.format(DATE_FORMAT).to_string(),
+    })
+}
+
+/// Точки для отметки на графике с подписями.
+fn extremes_points(extremes: &SeriesExtremes) -> Vec<(&'static str, &SeriesExtreme)> {
+    std::iter::once((LABEL_MAX, &extremes.max))
+        .chain(extremes.min.iter().map(|min| (LABEL_MIN, min)))
+        .collect()
+}
+
+/// Считает посуточные изменения, сохраняя длину ряда (первое значение = 0).
+/// Даунсемплит ряд, сохраняя минимум/максимум в бакетах, чтобы ускорить отрисовку.
+fn downsample_min_max<X: Clone>(x: &[X], y: &[f64], max_points: usize) -> (Vec<X>, Vec<f64>) {
+    if x.len() != y.len() {
+        return (x.to_vec(), y.to_vec());
+    }
+    let indices = downsample_min_max_indices(y, max_points);
+
+    let mut out_x = Vec::with_capacity(indices.len());
+    let mut out_y = Vec::with_capacity(indices.len());
+    for idx in indices {
+        out_x.push(x[idx].clone());
+        out_y.push(y[idx]);
+    }
+
+    (out_x, out_y)
+}
+
+/// Даунсемплит две Y-серии с общим набором X-индексов для корректного `fill`.
+fn downsample_min_max_shared_x<X: Clone>(
+    x: &[X],
+    lower: &[f64],
+    upper: &[f64],
+    max_points: usize,
+) -> (Vec<X>, Vec<f64>, Vec<f64>) {
+    if x.len() != lower.len() || x.len() != upper.len() {
+        return (x.to_vec(), lower.to_vec(), upper.to_vec());
+    }
+    let lower_indices = downsample_min_max_indices(lower, max_points);
+    let upper_indices = downsample_min_max_indices(upper, max_points);
+    let indices = merge_sorted_indices(&lower_indices, &upper_indices);
+
+    let mut out_x = Vec::with_capacity(indices.len());
+    let mut out_lower = Vec::with_capacity(indices.len());
+    let mut out_upper = Vec::with_capacity(indices.len());
+    for idx in indices {
+        out_x.push(x[idx].clone());
+        out_lower.push(lower[idx]);
+        out_upper.push(upper[idx]);
+    }
+
+    (out_x, out_lower, out_upper)
+}
+
+/// Фильтрует ряд по дате (включительно), сохраняя выравнивание X/Y.
+fn filter_series_from_date<X: Clone>(
+    dates: &[NaiveDate],
+    x: &[X],
+    y: &[f64],
+    start_date: NaiveDate,
+) -> (Vec<X>, Vec<f64>) {
+    if dates.len() != x.len() || x.len() != y.len() {
+        return (x.to_vec(), y.to_vec());
+    }
+
+    let mut out_x = Vec::with_capacity(x.len());
+    let mut out_y = Vec::with_capacity(y.len());
+    for ((date, x_value), y_value) in dates.iter().zip(x).zip(y.iter().copied()) {
+        if *date >= start_date {
+            out_x.push(x_value.clone());
+            out_y.push(y_value);
+        }
+    }
+    (out_x, out_y)
+}
+
+/// Фильтрует общий X и две Y-серии по дате (включительно).
+fn filter_shared_series_from_date<X: Clone>(
+    dates: &[NaiveDate],
+    x: &[X],
+    lower: &[f64],
+    upper: &[f64],
+    start_date: NaiveDate,
+) -> (Vec<X>, Vec<f64>, Vec<f64>) {
+    if dates.len() != x.len() || x.len() != lower.len() || lower.len() != upper.len() {
+        return (x.to_vec(), lower.to_vec(), upper.to_vec());
+    }
+
+    let mut out_x = Vec::with_capacity(x.len());
+    let mut out_lower = Vec::with_capacity(lower.len());
+    let mut out_upper = Vec::with_capacity(upper.len());
+    for (((date, x_value), lower_value), upper_value) in dates
+        .iter()
+        .zip(x)
+        .zip(lower.iter().copied())
+        .zip(upper.iter().copied())
+    {
+        if *date >= start_date {
+            out_x.push(x_value.clone());
+            out_lower.push(lower_value);
+            out_upper.push(upper_value);
+        }
+    }
+    (out_x, out_lower, out_upper)
+}
+
+/// Точки ручного ввода (`rua append`) поверх фактической линии; не прореживаются.
+fn build_manual_points(
+    dates: &[NaiveDate],
+    labels: &[String],
+    values: &[f64],
+    manual: &BTreeSet<NaiveDate>,
+) -> (Vec<String>, Vec<f64>) {
+    dates
+        .iter()
+        .zip(labels.iter().zip(values))
+        .filter(|(date, _)| manual.contains(date))
+        .map(|(_, (label, value))| (label.clone(), *value))
+        .unzip()
+}
+
+/// Исключённые из обучения дни: дата, фактическое значение и причина.
+fn build_excluded_points(
+    dates: &[NaiveDate],
+    labels: &[String],
+    values: &[f64],
+    excluded: &BTreeMap<NaiveDate, String>,
+) -> (Vec<String>, (Vec<f64>, Vec<String>)) {
+    dates
+        .iter()
+        .zip(labels.iter().zip(values))
+        .filter_map(|(date, (label, value))| {
+            excluded
+                .get(date)
+                .map(|reason| (label.clone(), (*value, reason.clone())))
+        })
+        .unzip()
+}
+
+/// Отрезки интерполированных дней вместе с соседними измеренными точками; между отрезками — разрыв.
+/// Подсказки основного графика: площадь и доля Украины, изменение за сутки и с
+/// [`INVASION_START`]. Слой источника считает площадь, занятую после этой даты, поэтому
+/// без среза на саму дату база — ноль.
+struct AreaHover<'a> {
+    dates: &'a [NaiveDate],
+    occupied_km2: &'a [f64],
+    invasion_km2: f64,
+    unit: AreaUnit,
+    locale: NumberLocale,
+}
+
+impl<'a> AreaHover<'a> {
+    fn new(
+        dates: &'a [NaiveDate],
+        occupied_km2: &'a [f64],
+        unit: AreaUnit,
+        locale: NumberLocale,
+    ) -> Self {
+        let invasion_km2 = dates
+            .binary_search(&INVASION_START)
+            .map_or(0.0, |idx| occupied_km2[idx]);
+        Self {
+            dates,
+            occupied_km2,
+            invasion_km2,
+            unit,
+            locale,
+        }
+    }
+
+    /// Подсказка дневной точки ряда.
+    fn daily(&self, idx: usize) -> String {
+        let previous = idx
+            .checked_sub(1)
+            .and_then(|previous| self.occupied_km2.get(previous));
+        self.text(self.occupied_km2[idx], previous.copied())
+    }
+
+    /// Подсказка среза внутри дня: изменение считается от дневного значения накануне.
+    fn on(&self, date: NaiveDate, km2: f64) -> String {
+        let previous = date
+            .pred_opt()
+            .and_then(|previous| self.dates.binary_search(&previous).ok())
+            .map(|idx| self.occupied_km2[idx]);
+        self.text(km2, previous)
+    }
+
+    fn text(&self, km2: f64, previous_day_km2: Option<f64>) -> String {
+        let (unit, locale) = (self.unit, self.locale);
+        format!(
+            "{} · {}% Украины<br>За сутки: {}<br>С {}: {}",
+            unit.format(km2, locale),
+            locale.format(constants::ukraine_percent(km2), HOVER_PERCENT_DECIMALS),
+            unit.change_unit()
+                .format_change(previous_day_km2.map(|previous| km2 - previous), locale),
+            INVASION_START.format("%d.%m.%Y"),
+            unit.format_change(Some(km2 - self.invasion_km2), locale),
+        )
+    }
+}
+
+fn build_imputed_segments(
+    labels: &[String],
+    values: &[f64],
+    measured: &[bool],
+) -> (Vec<String>, Vec<Option<f64>>) {
+    let is_imputed = |idx: usize| measured.get(idx).is_some_and(|measured| !measured);
+    let mut out_labels = Vec::new();
+    let mut out_values = Vec::new();
+    let mut previous = None;
+    for (idx, (label, value)) in labels.iter().zip(values).enumerate() {
+        let keep =
+            is_imputed(idx) || is_imputed(idx + 1) || idx.checked_sub(1).is_some_and(is_imputed);
+        if !keep {
+            continue;
+        }
+        if previous.is_some_and(|prev| prev + 1 != idx) {
+            out_labels.push(label.clone());
+            out_values.push(None);
+        }
+        out_labels.push(label.clone());
+        out_values.push(Some(*value));
+        previous = Some(idx);
+    }
+    (out_labels, out_values)
+}
+
+/// Полоса «Серой зоны» над занятой площадью; оба ряда в одной единице.
+fn prepare_unspecified_area_plot(
+    dates: &[NaiveDate],
+    area_dates: &[String],
+    area_level: &[f64],
+    unspecified_level: &[f64],
+    start_date: NaiveDate,
+    max_points: usize,
+) -> (Vec<String>, Vec<f64>, Vec<f64>) {
+    let area_upper = area_level
+        .iter()
+        .zip(unspecified_level.iter())
+        .map(|(occupied, unspecified)| occupied + unspecified)
+        .collect_vec();
+    let (area_dates_filtered, area_km2_filtered, area_upper_km2_filtered) =
+        filter_shared_series_from_date(dates, area_dates, area_level, &area_upper, start_date);
+    downsample_min_max_shared_x(
+        &area_dates_filtered,
+        &area_km2_filtered,
+        &area_upper_km2_filtered,
+        max_points,
+    )
+}
+
+fn prepare_unspecified_change_plot(
+    reference_change_series: &PreparedChangeSeries,
+    dates: &[NaiveDate],
+    unspecified_area: &[f64],
+    start_date: NaiveDate,
+    max_points: usize,
+) -> PreparedUnspecifiedChangePlot {
+    let (dates_filtered, area_filtered) =
+        filter_series_from_date(dates, dates, unspecified_area, start_date);
+    let change_series = prepare_change_series(
+        &dates_filtered,
+        &area_filtered,
+        reference_change_series.baseline,
+        reference_change_series.smoothing,
+    );
+    let reference_by_date: BTreeMap<NaiveDate, f64> = reference_change_series
+        .dates
+        .iter()
+        .copied()
+        .zip(reference_change_series.values.iter().copied())
+        .collect();
+    let (relative_dates, reference_values, relative_values) = change_series
+        .dates
+        .iter()
+        .zip(change_series.labels.iter().cloned())
+        .zip(change_series.values.iter().copied())
+        .filter_map(|((date, label), unspecified_change)| {
+            reference_by_date
+                .get(date)
+                .copied()
+                .map(|reference_change| {
+                    (
+                        label,
+                        reference_change,
+                        reference_change + unspecified_change,
+                    )
+                })
+        })
+        .fold(
+            (Vec::new(), Vec::new(), Vec::new()),
+            |mut acc, (date, reference, relative)| {
+                acc.0.push(date);
+                acc.1.push(reference);
+                acc.2.push(relative);
+                acc
+            },
+        );
+
+    let (relative_dates, reference_values, relative_values) = downsample_min_max_shared_x(
+        &relative_dates,
+        &reference_values,
+        &relative_values,
+        max_points,
+    );
+
+    PreparedUnspecifiedChangePlot {
+        dates: relative_dates,
+        reference_values,
+        values: relative_values,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ChartRenderConfig, LABEL_ACTUAL, LABEL_IMPUTED, LABEL_MANUAL, LABEL_TREND,
+        LABEL_UNSPECIFIED_BAND_MAIN, LABEL_UNSPECIFIED_CHANGE, Phase, PhaseStats,
+        PreparedChangeSeries, ReferenceLevel, ReferenceLine, YOY_LINE_ALPHA_MAX,
+        YOY_LINE_ALPHA_MIN, build_area_chart_from_buckets_with_config, build_monthly_box_series,
+        build_yoy_envelope, build_yoy_series_by_year, build_yoy_stddev_series,
+        downsample_min_max_shared_x, normalize_to_yoy_month, prepare_unspecified_change_plot,
+        series_extremes, yoy_line_alpha,
+    };
+    use crate::{AreaUnit, PageConfig, SmoothingSpec};
+    use chrono::NaiveDate;
+    use serde_json::Value;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use crate::series::load_area_buckets;
+
+    static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    fn write_temp_csv(contents: &str) -> PathBuf {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before UNIX_EPOCH")
+            .as_nanos();
+        let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let mut path = std::env::temp_dir();
+        path.push(format!("rua_chart_test_{timestamp}_{counter}.csv"));
+        std::fs::write(&path, contents).expect("failed to write test csv");
+        path
+    }
+
+    fn remove_temp_csv(path: &Path) {
+        let _ = std::fs::remove_file(path);
+    }
+
+    const SAMPLE_GRAY_ZONE_CSV: &str = "time_index,hash,area,percent,area_type\n\
+2024-05-01 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-05-01 00:00:00 UTC,#01579b,20.0,0.0,other_territories\n\
+2024-05-01 00:00:00 UTC,#bcaaa4,10.0,0.0,unspecified\n\
+2024-05-02 00:00:00 UTC,#a52714,120.0,0.0,occupied_after_24_02_2022\n\
+2024-05-02 00:00:00 UTC,#01579b,30.0,0.0,other_territories\n\
+2024-05-02 00:00:00 UTC,#bcaaa4,15.0,0.0,unspecified\n\
+2024-05-03 00:00:00 UTC,#a52714,140.0,0.0,occupied_after_24_02_2022\n\
+2024-05-03 00:00:00 UTC,#01579b,35.0,0.0,other_territories\n\
+2024-05-03 00:00:00 UTC,#bcaaa4,25.0,0.0,unspecified\n";
+
+    const SAMPLE_GRAY_ZONE_THRESHOLD_CSV: &str = "time_index,hash,area,percent,area_type\n\
+2023-02-04 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2023-02-04 00:00:00 UTC,#01579b,20.0,0.0,other_territories\n\
+2023-02-04 00:00:00 UTC,#bcaaa4,10.0,0.0,unspecified\n\
+2023-02-05 00:00:00 UTC,#a52714,110.0,0.0,occupied_after_24_02_2022\n\
+2023-02-05 00:00:00 UTC,#01579b,20.0,0.0,other_territories\n\
+2023-02-05 00:00:00 UTC,#bcaaa4,15.0,0.0,unspecified\n\
+2023-02-06 00:00:00 UTC,#a52714,120.0,0.0,occupied_after_24_02_2022\n\
+2023-02-06 00:00:00 UTC,#01579b,20.0,0.0,other_territories\n\
+2023-02-06 00:00:00 UTC,#bcaaa4,20.0,0.0,unspecified\n\
+2023-02-07 00:00:00 UTC,#a52714,130.0,0.0,occupied_after_24_02_2022\n\
+2023-02-07 00:00:00 UTC,#01579b,20.0,0.0,other_territories\n\
+2023-02-07 00:00:00 UTC,#bcaaa4,21.0,0.0,unspecified\n";
+
+    fn build_chart_from_csv(csv: &str) -> super::ChartOutput {
+        let path = write_temp_csv(csv);
+        let buckets = load_area_buckets(&path).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+        build_area_chart_from_buckets_with_config(&buckets, None, &ChartRenderConfig::default())
+            .expect("failed to build chart")
+    }
+
+    fn build_chart_from_csv_with_config(
+        csv: &str,
+        render_config: &ChartRenderConfig,
+    ) -> super::ChartOutput {
+        let path = write_temp_csv(csv);
+        let buckets = load_area_buckets(&path).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+        build_area_chart_from_buckets_with_config(&buckets, None, render_config)
+            .expect("failed to build chart")
+    }
+
+    fn collect_main_traces(chart: &super::ChartOutput) -> Vec<Value> {
+        chart
+            .main_plot
+            .data()
+            .iter()
+            .map(|trace| serde_json::from_str::<Value>(&trace.to_json()).expect("invalid trace"))
+            .collect::<Vec<_>>()
+    }
+
+    fn parse_trace_dates(trace: &Value) -> Vec<NaiveDate> {
+        trace
+            .get("x")
+            .and_then(Value::as_array)
+            .expect("x values are missing")
+            .iter()
+            .map(|value| value.as_str().expect("x must be string"))
+            .map(|value| NaiveDate::parse_from_str(value, "%Y-%m-%d").expect("valid x date"))
+            .collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn series_table_lists_latest_days_first() {
+        let chart = build_chart_from_csv_with_config(
+            SAMPLE_GRAY_ZONE_CSV,
+            &ChartRenderConfig {
+                table_days: 2,
+                ..ChartRenderConfig::default()
+            },
+        );
+
+        let rows = chart
+            .table
+            .iter()
+            .map(|row| {
+                (
+                    row.date.format("%Y-%m-%d").to_string(),
+                    row.area_km2,
+                    row.change_km2,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                ("2024-05-03".to_string(), Some(105.0), Some(15.0)),
+                ("2024-05-02".to_string(), Some(90.0), Some(10.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn milestone_etas_extrapolate_the_current_pace() {
+        let milestone = |label: &str, area_km2| super::Milestone {
+            label: label.to_string(),
+            area_km2,
+        };
+        let milestones = [
+            milestone("ahead", 110_000.0),
+            milestone("far", 100_000_000.0),
+            milestone("behind", 90_000.0),
+        ];
+        let etas = super::milestone_etas(100_000.0, 20.0, &milestones);
+        let from = NaiveDate::from_ymd_opt(2025, 1, 1).expect("valid date");
+
+        assert_eq!(etas[0].days, Some(500.0));
+        assert_eq!(
+            etas[0].reached_on(from),
+            NaiveDate::from_ymd_opt(2026, 5, 16)
+        );
+        assert!(etas[1].days.is_some());
+        assert_eq!(etas[1].reached_on(from), None);
+        assert!((etas[2].remaining_km2 + 10_000.0).abs() < 1e-9);
+        assert_eq!(etas[2].days, None);
+        // При отходе срок считается до рубежа ниже текущей площади.
+        let retreat = super::milestone_etas(100_000.0, -20.0, &milestones);
+        assert_eq!(retreat[0].days, None);
+        assert_eq!(retreat[2].days, Some(500.0));
+    }
+
+    #[test]
+    fn summary_template_substitutes_latest_figures() {
+        let summary = super::ChartSummary {
+            latest_date: "2025-01-02".to_string(),
+            latest_area_km2: 112.345,
+            ukraine_percent: 18.614,
+            daily_change_km2: Some(12.4),
+            weekly_change_km2: None,
+            forecast: None,
+            forecast_horizons: Vec::new(),
+            extremes: None,
+        };
+        let message = summary.render_template(
+            "Update {date}: {area} ({percent}%), {daily}/{weekly}, forecast {forecast}",
+        );
+        assert_eq!(
+            message,
+            "Update 2025-01-02: 112.3 (18.61%), +12/—, forecast —"
+        );
+    }
+
+    #[test]
+    fn yoy_grouping_normalizes_dates_to_anchor_year() {
+        let change_dates = vec![
+            NaiveDate::from_ymd_opt(2023, 1, 2).expect("valid date"),
+            NaiveDate::from_ymd_opt(2023, 12, 31).expect("valid date"),
+            NaiveDate::from_ymd_opt(2024, 2, 29).expect("valid date"),
+        ];
+        let change_values = vec![1.5, -2.0, 0.25];
+
+        let grouped = build_yoy_series_by_year(&change_dates, &change_values);
+        assert_eq!(grouped.len(), 2);
+
+        assert_eq!(grouped[0].year, 2023);
+        assert_eq!(grouped[0].dates, vec!["2000-01-02", "2000-12-31"]);
+        assert_eq!(grouped[0].values, vec![1.5, -2.0]);
+
+        assert_eq!(grouped[1].year, 2024);
+        assert_eq!(grouped[1].dates, vec!["2000-02-29"]);
+        assert_eq!(grouped[1].values, vec![0.25]);
+    }
+
+    #[test]
+    fn envelope_is_computed_per_calendar_day() {
+        let change_dates = vec![
+            NaiveDate::from_ymd_opt(2023, 1, 1).expect("valid date"),
+            NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date"),
+            NaiveDate::from_ymd_opt(2024, 1, 2).expect("valid date"),
+            NaiveDate::from_ymd_opt(2025, 1, 2).expect("valid date"),
+        ];
+        let change_values = vec![10.0, -5.0, 7.0, 3.0];
+
+        let envelope =
+            build_yoy_envelope(&change_dates, &change_values).expect("envelope should exist");
+        assert_eq!(envelope.dates, vec!["2000-01-01", "2000-01-02"]);
+        assert_eq!(envelope.lower, vec![-5.0, 3.0]);
+        assert_eq!(envelope.upper, vec![10.0, 7.0]);
+    }
+
+    #[test]
+    fn yoy_stddev_is_computed_per_calendar_day() {
+        let change_dates = vec![
+            NaiveDate::from_ymd_opt(2023, 1, 1).expect("valid date"),
+            NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date"),
+            NaiveDate::from_ymd_opt(2023, 1, 2).expect("valid date"),
+            NaiveDate::from_ymd_opt(2024, 1, 2).expect("valid date"),
+            NaiveDate::from_ymd_opt(2025, 1, 2).expect("valid date"),
+        ];
+        let change_values = vec![1.0, 5.0, 2.0, 4.0, 6.0];
+
+        let stddev = build_yoy_stddev_series(&change_dates, &change_values)
+            .expect("stddev series should exist");
+        assert_eq!(stddev.dates, vec!["2000-01-01", "2000-01-02"]);
+        assert!((stddev.values[0] - 2.0).abs() < 1e-12);
+        assert!((stddev.values[1] - (8.0_f64 / 3.0).sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn yoy_stddev_requires_at_least_two_years_per_day() {
+        let change_dates = vec![
+            NaiveDate::from_ymd_opt(2023, 1, 1).expect("valid date"),
+            NaiveDate::from_ymd_opt(2023, 1, 2).expect("valid date"),
+        ];
+        let change_values = vec![1.0, 2.0];
+
+        assert!(build_yoy_stddev_series(&change_dates, &change_values).is_none());
+    }
+
+    #[test]
+    fn yoy_line_alpha_is_strongest_for_newest_year() {
+        let oldest = yoy_line_alpha(0, 5);
+        let middle = yoy_line_alpha(2, 5);
+        let newest = yoy_line_alpha(4, 5);
+
+        assert!((oldest - YOY_LINE_ALPHA_MIN).abs() < f64::EPSILON);
+        assert!(oldest < middle);
+        assert!(middle < newest);
+        assert!((newest - YOY_LINE_ALPHA_MAX).abs() < f64::EPSILON);
+        assert!((yoy_line_alpha(0, 1) - YOY_LINE_ALPHA_MAX).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn monthly_box_series_groups_by_calendar_month_and_normalizes_x() {
+        let change_dates = vec![
+            NaiveDate::from_ymd_opt(2024, 1, 15).expect("valid date"),
+            NaiveDate::from_ymd_opt(2024, 2, 1).expect("valid date"),
+            NaiveDate::from_ymd_opt(2025, 1, 20).expect("valid date"),
+        ];
+        let change_values = vec![1.5, -2.0, 3.0];
+
+        let monthly = build_monthly_box_series(&change_dates, &change_values);
+        assert_eq!(
+            monthly.dates,
+            vec![
+                "2000-01-15 12:00:00",
+                "2000-02-14 03:00:00",
+                "2000-01-15 12:00:00",
+            ]
+        );
+        assert_eq!(monthly.values, change_values);
+    }
+
+    #[test]
+    fn monthly_box_series_keeps_daily_values_without_monthly_aggregation() {
+        let change_dates = vec![
+            NaiveDate::from_ymd_opt(2024, 3, 1).expect("valid date"),
+            NaiveDate::from_ymd_opt(2024, 3, 2).expect("valid date"),
+            NaiveDate::from_ymd_opt(2024, 3, 3).expect("valid date"),
+        ];
+        let change_values = vec![10.0, -4.0, 2.0];
+
+        let monthly = build_monthly_box_series(&change_dates, &change_values);
+        assert_eq!(
+            monthly.dates,
+            vec![
+                "2000-03-15 12:00:00",
+                "2000-03-15 12:00:00",
+                "2000-03-15 12:00:00",
+            ]
+        );
+        assert_eq!(monthly.values, change_values);
+    }
+
+    #[test]
+    fn monthly_box_series_includes_partial_month_edges() {
+        let change_dates = vec![
+            NaiveDate::from_ymd_opt(2024, 1, 30).expect("valid date"),
+            NaiveDate::from_ymd_opt(2024, 1, 31).expect("valid date"),
+            NaiveDate::from_ymd_opt(2024, 2, 1).expect("valid date"),
+        ];
+        let change_values = vec![1.0, 2.0, 3.0];
+
+        let monthly = build_monthly_box_series(&change_dates, &change_values);
+        assert_eq!(
+            monthly.dates,
+            vec![
+                "2000-01-15 12:00:00",
+                "2000-01-15 12:00:00",
+                "2000-02-14 03:00:00",
+            ]
+        );
+        assert_eq!(monthly.values, change_values);
+    }
+
+    #[test]
+    fn monthly_box_series_uses_configured_month_anchor_coordinates() {
+        let expected = vec![
+            "2000-01-15 12:00:00",
+            "2000-02-14 03:00:00",
+            "2000-03-15 12:00:00",
+            "2000-04-15 00:00:00",
+            "2000-05-15 12:00:00",
+            "2000-06-15 00:00:00",
+            "2000-07-15 12:00:00",
+            "2000-08-15 12:00:00",
+            "2000-09-15 00:00:00",
+            "2000-10-15 12:00:00",
+            "2000-11-15 00:00:00",
+            "2000-12-15 12:00:00",
+        ];
+
+        let actual = (1..=12).map(normalize_to_yoy_month).collect::<Vec<_>>();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn main_plot_adds_unspecified_band_before_actual_line() {
+        let chart = build_chart_from_csv(SAMPLE_GRAY_ZONE_CSV);
+        let traces = collect_main_traces(&chart);
+
+        let band_idx = traces
+            .iter()
+            .position(|trace| {
+                trace.get("name").and_then(Value::as_str) == Some(LABEL_UNSPECIFIED_BAND_MAIN)
+                    && trace.get("yaxis").and_then(Value::as_str) == Some("y1")
+                    && trace.get("xaxis").and_then(Value::as_str) == Some("x1")
+                    && trace.get("fill").and_then(Value::as_str) == Some("tonexty")
+                    && trace
+                        .get("fillcolor")
+                        .and_then(Value::as_str)
+                        .is_some_and(|color| {
+                            color.contains("128,128,128") || color.contains("128, 128, 128")
+                        })
+            })
+            .expect("unspecified fill trace not found");
+        let actual_idx = traces
+            .iter()
+            .position(|trace| trace.get("name").and_then(Value::as_str) == Some(LABEL_ACTUAL))
+            .expect("actual trace not found");
+
+        assert!(
+            actual_idx > band_idx,
+            "actual line must be drawn after unspecified band; actual_idx={actual_idx}, band_idx={band_idx}"
+        );
+    }
+
+    #[test]
+    fn main_plot_adds_unspecified_change_trace_on_lower_panel() {
+        let chart = build_chart_from_csv(SAMPLE_GRAY_ZONE_CSV);
+        let traces = collect_main_traces(&chart);
+
+        let lower_panel_trace = traces
+            .iter()
+            .find(|trace| {
+                trace.get("name").and_then(Value::as_str) == Some(LABEL_UNSPECIFIED_CHANGE)
+                    && trace.get("xaxis").and_then(Value::as_str) == Some("x1")
+                    && trace.get("yaxis").and_then(Value::as_str) == Some("y2")
+            })
+            .expect("unspecified change trace on lower panel not found");
+        assert_eq!(
+            lower_panel_trace.get("fill").and_then(Value::as_str),
+            Some("tonexty")
+        );
+        assert_eq!(
+            lower_panel_trace.get("visible").and_then(Value::as_str),
+            Some("legendonly")
+        );
+        let values = lower_panel_trace
+            .get("y")
+            .and_then(Value::as_array)
+            .expect("y values are missing");
+        assert!(
+            !values.is_empty(),
+            "unspecified change trace must not be empty"
+        );
+    }
+
+    #[test]
+    fn gray_zone_is_rendered_from_threshold_date_inclusive() {
+        let chart = build_chart_from_csv(SAMPLE_GRAY_ZONE_THRESHOLD_CSV);
+        let traces = collect_main_traces(&chart);
+
+        let threshold = NaiveDate::from_ymd_opt(2023, 2, 5).expect("valid date");
+
+        let upper_gray_trace = traces
+            .iter()
+            .find(|trace| {
+                trace.get("name").and_then(Value::as_str) == Some(LABEL_UNSPECIFIED_BAND_MAIN)
+                    && trace.get("yaxis").and_then(Value::as_str) == Some("y1")
+                    && trace.get("fill").and_then(Value::as_str) == Some("tonexty")
+            })
+            .expect("upper gray-zone trace not found");
+        let upper_x = parse_trace_dates(upper_gray_trace);
+        assert!(
+            !upper_x.is_empty(),
+            "upper gray-zone trace must not be empty"
+        );
+        assert!(upper_x.iter().all(|date| *date >= threshold));
+        assert!(upper_x.contains(&threshold));
+
+        let lower_gray_trace = traces
+            .iter()
+            .find(|trace| {
+                trace.get("name").and_then(Value::as_str) == Some(LABEL_UNSPECIFIED_CHANGE)
+                    && trace.get("xaxis").and_then(Value::as_str) == Some("x1")
+                    && trace.get("yaxis").and_then(Value::as_str) == Some("y2")
+            })
+            .expect("lower gray-zone trace not found");
+        let lower_x = parse_trace_dates(lower_gray_trace);
+        assert!(
+            !lower_x.is_empty(),
+            "lower gray-zone trace must not be empty"
+        );
+        assert!(lower_x.iter().all(|date| *date >= threshold));
+        assert!(lower_x.contains(&threshold));
+    }
+
+    #[test]
+    fn gray_zone_start_date_respects_render_config() {
+        let render_config = ChartRenderConfig {
+            gray_zone_start: NaiveDate::from_ymd_opt(2023, 2, 4).expect("valid date"),
+            ..ChartRenderConfig::default()
+        };
+        let chart =
+            build_chart_from_csv_with_config(SAMPLE_GRAY_ZONE_THRESHOLD_CSV, &render_config);
+        let traces = collect_main_traces(&chart);
+        let threshold = NaiveDate::from_ymd_opt(2023, 2, 4).expect("valid date");
+
+        let upper_gray_trace = traces
+            .iter()
+            .find(|trace| {
+                trace.get("name").and_then(Value::as_str) == Some(LABEL_UNSPECIFIED_BAND_MAIN)
+                    && trace.get("yaxis").and_then(Value::as_str) == Some("y1")
+                    && trace.get("fill").and_then(Value::as_str) == Some("tonexty")
+            })
+            .expect("upper gray-zone trace not found");
+        let upper_x = parse_trace_dates(upper_gray_trace);
+        assert!(
+            !upper_x.is_empty(),
+            "upper gray-zone trace must not be empty"
+        );
+        assert!(upper_x.iter().all(|date| *date >= threshold));
+        assert!(upper_x.contains(&threshold));
+
+        let lower_gray_trace = traces
+            .iter()
+            .find(|trace| {
+                trace.get("name").and_then(Value::as_str) == Some(LABEL_UNSPECIFIED_CHANGE)
+                    && trace.get("xaxis").and_then(Value::as_str) == Some("x1")
+                    && trace.get("yaxis").and_then(Value::as_str) == Some("y2")
+            })
+            .expect("lower gray-zone trace not found");
+        let lower_x = parse_trace_dates(lower_gray_trace);
+        assert!(
+            !lower_x.is_empty(),
+            "lower gray-zone trace must not be empty"
+        );
+        assert!(lower_x.iter().all(|date| *date >= threshold));
+        assert!(lower_x.contains(&threshold));
+    }
+
+    #[test]
+    fn plot_legends_allow_toggle_visibility() {
+        let chart = build_chart_from_csv(SAMPLE_GRAY_ZONE_CSV);
+        let main_plot_json =
+            serde_json::from_str::<Value>(&chart.main_plot.to_json()).expect("invalid plot json");
+        let main_legend = main_plot_json
+            .get("layout")
+            .and_then(|layout| layout.get("legend"))
+            .expect("main legend is missing");
+        assert_eq!(
+            main_legend.get("itemclick").and_then(Value::as_str),
+            Some("toggle")
+        );
+        assert_eq!(
+            main_legend.get("itemdoubleclick").and_then(Value::as_str),
+            Some("toggleothers")
+        );
+
+        let yoy_plot_json =
+            serde_json::from_str::<Value>(&chart.yoy_plot.to_json()).expect("invalid plot json");
+        let yoy_legend = yoy_plot_json
+            .get("layout")
+            .and_then(|layout| layout.get("legend"))
+            .expect("yoy legend is missing");
+        assert_eq!(
+            yoy_legend.get("itemclick").and_then(Value::as_str),
+            Some("toggle")
+        );
+        assert_eq!(
+            yoy_legend.get("itemdoubleclick").and_then(Value::as_str),
+            Some("toggleothers")
+        );
+    }
+
+    #[test]
+    fn extremes_take_the_first_record_and_the_minimum_after_baseline() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 1, day).expect("valid date");
+        let dates = [date(1), date(2), date(3), date(4), date(5), date(6)];
+        let occupied = [50.0, 300.0, 300.0, 120.0, f64::NAN, 150.0];
+        let extremes = series_extremes(&dates, &occupied, date(3)).expect("series is not empty");
+        let max = &extremes.max;
+        assert_eq!((max.date.as_str(), max.area_km2), ("2024-01-02", 300.0));
+        let min = extremes.min.expect("days after baseline");
+        assert_eq!((min.date.as_str(), min.area_km2), ("2024-01-04", 120.0));
+        assert_eq!(extremes.min_since, "2024-01-03");
+
+        let before_baseline =
+            series_extremes(&dates[..2], &occupied[..2], date(3)).expect("series is not empty");
+        assert_eq!(before_baseline.min, None);
+        assert_eq!(series_extremes(&[], &[], date(3)), None);
+    }
+
+    #[test]
+    fn reference_lines_resolve_dates_and_skip_missing_days() {
+        let render_config = ChartRenderConfig {
+            reference_lines: vec![
+                ReferenceLine {
+                    label: "На 02.05".to_string(),
+                    level: ReferenceLevel::AsOf(
+                        NaiveDate::from_ymd_opt(2024, 5, 2).expect("valid date"),
+                    ),
+                },
+                ReferenceLine {
+                    label: "Фиксированная".to_string(),
+                    level: ReferenceLevel::AreaKm2(500.0),
+                },
+                ReferenceLine {
+                    label: "Вне ряда".to_string(),
+                    level: ReferenceLevel::AsOf(
+                        NaiveDate::from_ymd_opt(2022, 2, 23).expect("valid date"),
+                    ),
+                },
+            ],
+            ..ChartRenderConfig::default()
+        };
+        let chart = build_chart_from_csv_with_config(SAMPLE_GRAY_ZONE_CSV, &render_config);
+        let plot_json =
+            serde_json::from_str::<Value>(&chart.main_plot.to_json()).expect("invalid plot json");
+        let levels = plot_json
+            .pointer("/layout/shapes")
+            .and_then(Value::as_array)
+            .expect("shapes are missing")
+            .iter()
+            .filter(|shape| shape.get("yref").and_then(Value::as_str) == Some("y"))
+            .filter_map(|shape| shape.get("y0").and_then(Value::as_f64))
+            .collect::<Vec<_>>();
+        // 2024-05-02: 120 км² занято минус 30 км² освобождено.
+        assert_eq!(levels, vec![0.09, 0.5]);
+
+        let labels = plot_json
+            .pointer("/layout/annotations")
+            .and_then(Value::as_array)
+            .expect("annotations are missing")
+            .iter()
+            .filter_map(|annotation| annotation.get("text").and_then(Value::as_str))
+            .filter(|text| text.starts_with("На 02.05") || text.starts_with("Вне ряда"))
+            .collect::<Vec<_>>();
+        assert_eq!(labels, vec!["На 02.05: 0,1 тыс. км²"]);
+        assert_eq!(
+            plot_json
+                .pointer("/layout/separators")
+                .and_then(Value::as_str),
+            Some(",\u{202f}")
+        );
+    }
+
+    #[test]
+    fn phases_are_clipped_to_the_series_and_drawn_as_bands() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 5, day).expect("valid date");
+        let phase = |label: &str, start, end| Phase {
+            label: label.to_string(),
+            start,
+            end,
+        };
+        let render_config = ChartRenderConfig {
+            phases: vec![
+                phase(
+                    "Весна",
+                    NaiveDate::from_ymd_opt(2024, 4, 1).expect("valid date"),
+                    Some(date(2)),
+                ),
+                phase("Лето", date(2), None),
+                phase(
+                    "До ряда",
+                    NaiveDate::from_ymd_opt(2023, 1, 1).expect("valid date"),
+                    NaiveDate::from_ymd_opt(2023, 2, 1),
+                ),
+            ],
+            ..ChartRenderConfig::default()
+        };
+        let chart = build_chart_from_csv_with_config(SAMPLE_GRAY_ZONE_CSV, &render_config);
+        // Занято за вычетом освобождённого: 80, 90 и 105 км².
+        assert_eq!(
+            chart.phases,
+            vec![
+                PhaseStats {
+                    label: "Весна".to_string(),
+                    start: date(1),
+                    end: date(2),
+                    net_change_km2: 10.0,
+                    mean_rate_km2_per_day: Some(10.0),
+                },
+                PhaseStats {
+                    label: "Лето".to_string(),
+                    start: date(2),
+                    end: date(3),
+                    net_change_km2: 15.0,
+                    mean_rate_km2_per_day: Some(15.0),
+                },
+            ]
+        );
+
+        let plot_json =
+            serde_json::from_str::<Value>(&chart.main_plot.to_json()).expect("invalid plot json");
+        let bands = plot_json
+            .pointer("/layout/shapes")
+            .and_then(Value::as_array)
+            .expect("shapes are missing")
+            .iter()
+            .filter(|shape| shape.get("type").and_then(Value::as_str) == Some("rect"))
+            .filter_map(|shape| Some((shape.get("x0")?.as_str()?, shape.get("x1")?.as_str()?)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bands,
+            vec![("2024-05-01", "2024-05-02"), ("2024-05-02", "2024-05-03")]
+        );
+    }
+
+    #[test]
+    fn area_unit_rescales_plotted_levels_and_axis_titles() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-07-01 06:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-07-02 06:00:00 UTC,#a52714,120.0,0.0,occupied_after_24_02_2022\n";
+        let render_config = ChartRenderConfig {
+            page: PageConfig {
+                area_unit: AreaUnit::Hectares,
+                ..PageConfig::default()
+            },
+            ..ChartRenderConfig::default()
+        };
+        let chart = build_chart_from_csv_with_config(csv, &render_config);
+        let traces = collect_main_traces(&chart);
+        let actual = traces
+            .iter()
+            .find(|trace| trace.get("name").and_then(Value::as_str) == Some(LABEL_ACTUAL))
+            .expect("actual trace not found");
+        let y = actual
+            .get("y")
+            .and_then(Value::as_array)
+            .expect("y values are missing")
+            .iter()
+            .filter_map(Value::as_f64)
+            .collect::<Vec<_>>();
+        assert_eq!(y, vec![10_000.0, 12_000.0]);
+
+        let plot_json =
+            serde_json::from_str::<Value>(&chart.main_plot.to_json()).expect("invalid plot json");
+        let title = |axis: &str| {
+            plot_json
+                .pointer(&format!("/layout/{axis}/title/text"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+        assert_eq!(title("yaxis").as_deref(), Some("га"));
+        assert_eq!(title("yaxis2").as_deref(), Some("га/сутки"));
+        // Сводка для карточек и уведомлений не зависит от единицы страницы.
+        assert!((chart.summary.latest_area_km2 - 0.12).abs() < 1e-9);
+    }
+
+    #[test]
+    fn actual_hover_shows_percent_and_changes_against_baselines() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2022-02-24 06:00:00 UTC,#a52714,1000.0,0.0,occupied_after_24_02_2022\n\
+2022-02-25 06:00:00 UTC,#a52714,61355.0,0.0,occupied_after_24_02_2022\n";
+        let chart = build_chart_from_csv_with_config(csv, &ChartRenderConfig::default());
+        let traces = collect_main_traces(&chart);
+        let actual = traces
+            .iter()
+            .find(|trace| trace.get("name").and_then(Value::as_str) == Some(LABEL_ACTUAL))
+            .expect("actual trace not found");
+        let texts = actual
+            .get("text")
+            .and_then(Value::as_array)
+            .expect("hover texts are missing")
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                "1,0 тыс. км² · 0,17% Украины<br>За сутки: —<br>С 24.02.2022: +0,0 тыс. км²",
+                "61,4 тыс. км² · 10,17% Украины<br>За сутки: +60\u{202f}355 км²\
+<br>С 24.02.2022: +60,4 тыс. км²",
+            ]
+        );
+        assert_eq!(
+            actual.get("hovertemplate").and_then(Value::as_str),
+            Some("%{text}<extra>Факт</extra>")
+        );
+    }
+
+    #[test]
+    fn main_plot_panels_share_x_axis_with_unified_hover() {
+        let chart = build_chart_from_csv(SAMPLE_GRAY_ZONE_CSV);
+        let plot_json =
+            serde_json::from_str::<Value>(&chart.main_plot.to_json()).expect("invalid plot json");
+        let layout = |pointer: &str| plot_json.pointer(&format!("/layout/{pointer}")).cloned();
+        assert_eq!(layout("hovermode"), Some(Value::from("x unified")));
+        assert_eq!(layout("grid/pattern"), Some(Value::from("coupled")));
+        assert_eq!(layout("xaxis/showspikes"), Some(Value::from(true)));
+        assert_eq!(layout("xaxis/spikemode"), Some(Value::from("across")));
+        assert_eq!(layout("xaxis2"), None);
+        assert!(
+            collect_main_traces(&chart)
+                .iter()
+                .all(|trace| trace.get("xaxis").and_then(Value::as_str) == Some("x1"))
+        );
+    }
+
+    #[test]
+    fn scale_menu_switches_to_log_and_index_views() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-07-01 06:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-07-02 06:00:00 UTC,#a52714,120.0,0.0,occupied_after_24_02_2022\n";
+        let render_config = ChartRenderConfig {
+            index_date: NaiveDate::from_ymd_opt(2024, 7, 1),
+            ..ChartRenderConfig::default()
+        };
+        let chart = build_chart_from_csv_with_config(csv, &render_config);
+        let plot_json =
+            serde_json::from_str::<Value>(&chart.main_plot.to_json()).expect("invalid plot json");
+        let buttons = plot_json
+            .pointer("/layout/updatemenus/0/buttons")
+            .and_then(Value::as_array)
+            .expect("scale menu is missing");
+        let labels = buttons
+            .iter()
+            .filter_map(|button| button.get("label").and_then(Value::as_str))
+            .collect::<Vec<_>>();
+        assert_eq!(labels, vec!["Линейная", "Логарифм", "Индекс"]);
+        assert_eq!(
+            buttons[1].pointer("/args/0/yaxis.type"),
+            Some(&Value::from("log"))
+        );
+
+        let index = buttons[2].pointer("/args/0").expect("index args");
+        assert_eq!(
+            index.get("yaxis.title.text").and_then(Value::as_str),
+            Some("Индекс, 01.07.2024 = 100")
+        );
+        let tick_text = index
+            .get("yaxis.ticktext")
+            .and_then(Value::as_array)
+            .expect("tick labels")
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tick_text,
+            vec!["0", "20", "40", "60", "80", "100", "120", "140"]
+        );
+        let base_tick = index
+            .pointer("/yaxis.tickvals/5")
+            .and_then(Value::as_f64)
+            .expect("base tick");
+        assert!((base_tick - 0.1).abs() < 1e-9);
+
+        let late = ChartRenderConfig {
+            index_date: NaiveDate::from_ymd_opt(2024, 8, 1),
+            ..ChartRenderConfig::default()
+        };
+        let chart = build_chart_from_csv_with_config(csv, &late);
+        let plot_json =
+            serde_json::from_str::<Value>(&chart.main_plot.to_json()).expect("invalid plot json");
+        assert!(
+            plot_json
+                .pointer("/layout/updatemenus/0/buttons/2")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn intraday_mode_plots_snapshots_on_datetime_axis() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-07-01 06:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-07-01 18:00:00 UTC,#a52714,110.0,0.0,occupied_after_24_02_2022\n\
+2024-07-02 06:00:00 UTC,#a52714,120.0,0.0,occupied_after_24_02_2022\n";
+        let render_config = ChartRenderConfig {
+            intraday: true,
+            ..ChartRenderConfig::default()
+        };
+        let chart = build_chart_from_csv_with_config(csv, &render_config);
+        let traces = collect_main_traces(&chart);
+        let actual = traces
+            .iter()
+            .find(|trace| trace.get("name").and_then(Value::as_str) == Some(LABEL_ACTUAL))
+            .expect("actual trace not found");
+        let x = actual
+            .get("x")
+            .and_then(Value::as_array)
+            .expect("x values are missing");
+        assert_eq!(x.len(), 3);
+        assert_eq!(x[1].as_str(), Some("2024-07-01 18:00:00"));
+
+        let plot_json =
+            serde_json::from_str::<Value>(&chart.main_plot.to_json()).expect("invalid plot json");
+        assert_eq!(
+            plot_json
+                .pointer("/layout/xaxis/type")
+                .and_then(Value::as_str),
+            Some("date")
+        );
+    }
+
+    #[test]
+    fn interpolated_days_are_drawn_as_separate_dotted_trace() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-08-01 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-08-04 00:00:00 UTC,#a52714,130.0,0.0,occupied_after_24_02_2022\n\
+2024-08-05 00:00:00 UTC,#a52714,140.0,0.0,occupied_after_24_02_2022\n";
+        let chart = build_chart_from_csv(csv);
+        let traces = collect_main_traces(&chart);
+        let actual = traces
+            .iter()
+            .find(|trace| trace.get("name").and_then(Value::as_str) == Some(LABEL_ACTUAL))
+            .expect("actual trace not found");
+        let actual_y = actual
+            .get("y")
+            .and_then(Value::as_array)
+            .expect("y values are missing");
+        assert!(actual_y[1].is_null() && actual_y[2].is_null());
+        assert!(actual_y[3].is_number());
+
+        let imputed = traces
+            .iter()
+            .find(|trace| trace.get("name").and_then(Value::as_str) == Some(LABEL_IMPUTED))
+            .expect("imputed trace not found");
+        assert_eq!(
+            parse_trace_dates(imputed),
+            (1..=4)
+                .map(|day| NaiveDate::from_ymd_opt(2024, 8, day).expect("valid date"))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            imputed.pointer("/line/dash").and_then(Value::as_str),
+            Some("dot")
+        );
+    }
+
+    #[test]
+    fn manual_points_are_drawn_as_markers() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-08-01 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-08-02 00:00:00 UTC,manual,120.0,0.0,occupied_after_24_02_2022\n\
+2024-08-02 00:00:00 UTC,manual,10.0,0.0,other_territories\n";
+        let chart = build_chart_from_csv(csv);
+        let traces = collect_main_traces(&chart);
+        let manual = traces
+            .iter()
+            .find(|trace| trace.get("name").and_then(Value::as_str) == Some(LABEL_MANUAL))
+            .expect("manual trace not found");
+
+        assert_eq!(manual.get("mode").and_then(Value::as_str), Some("markers"));
+        assert_eq!(
+            manual.get("x").and_then(Value::as_array).map(Vec::len),
+            Some(1)
+        );
+        let y = manual
+            .pointer("/y/0")
+            .and_then(Value::as_f64)
+            .expect("manual y is missing");
+        assert!((y - 0.11).abs() < 1e-9);
+    }
+
+    #[test]
+    fn model_trend_is_drawn_on_change_panel_only_when_enabled() {
+        let csv = "time_index,hash,area,percent,area_type\n\
+2024-08-01 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-08-02 00:00:00 UTC,#a52714,110.0,0.0,occupied_after_24_02_2022\n";
+        let path = write_temp_csv(csv);
+        let buckets = load_area_buckets(&path).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+        let overlay = super::ForecastOverlay {
+            dates: vec!["2024-08-03".to_string()],
+            mean: vec![0.12],
+            lower: vec![0.11],
+            upper: vec![0.13],
+            granularity: crate::series::Granularity::Day,
+            smoothed: Some(crate::SmoothedOverlay {
+                dates: vec!["2024-08-01".to_string(), "2024-08-02".to_string()],
+                level: vec![0.1, 0.11],
+                lower: vec![0.09, 0.1],
+                upper: vec![0.11, 0.12],
+                trend: vec![9.0, 10.0],
+                trend_lower: vec![7.0, 8.0],
+                trend_upper: vec![11.0, 12.0],
+            }),
+            horizons: Vec::new(),
+            training: None,
+            diagnostics: None,
+            stale: None,
+        };
+        let find_trend = |render_config: ChartRenderConfig| {
+            let chart =
+                build_area_chart_from_buckets_with_config(&buckets, Some(&overlay), &render_config)
+                    .expect("failed to build chart");
+            collect_main_traces(&chart)
+                .into_iter()
+                .find(|trace| trace.get("name").and_then(Value::as_str) == Some(LABEL_TREND))
+        };
+
+        assert!(find_trend(ChartRenderConfig::default()).is_none());
+        let trend = find_trend(ChartRenderConfig {
+            trend: true,
+            ..ChartRenderConfig::default()
+        })
+        .expect("trend trace not found");
+        assert_eq!(trend.get("yaxis").and_then(Value::as_str), Some("y2"));
+    }
+
+    #[test]
+    fn extra_forecast_horizons_are_drawn_hidden_and_summarised() {
+        let path = write_temp_csv(SAMPLE_GRAY_ZONE_CSV);
+        let buckets = load_area_buckets(&path).expect("failed to load area buckets");
+        remove_temp_csv(&path);
+        let forecast = |days: u32| super::ForecastOverlay {
+            dates: (1..=days).map(|day| format!("2024-06-{day:02}")).collect(),
+            mean: vec![0.15; days as usize],
+            lower: vec![0.14; days as usize],
+            upper: vec![0.16; days as usize],
+            granularity: crate::series::Granularity::Day,
+            smoothed: None,
+            horizons: Vec::new(),
+            training: None,
+            diagnostics: None,
+            stale: None,
+        };
+        let overlay = super::ForecastOverlay {
+            horizons: vec![forecast(14)],
+            ..forecast(7)
+        };
+        let chart = build_area_chart_from_buckets_with_config(
+            &buckets,
+            Some(&overlay),
+            &ChartRenderConfig::default(),
+        )
+        .expect("failed to build chart");
+
+        let visibility = |group: &str| {
+            collect_main_traces(&chart)
+                .iter()
+                .filter(|trace| trace.get("legendgroup").and_then(Value::as_str) == Some(group))
+                .map(|trace| trace.get("visible").cloned().unwrap_or(Value::Null))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(visibility("forecast-7"), vec![Value::Bool(true); 3]);
+        assert_eq!(visibility("forecast-14"), vec![Value::Bool(false); 3]);
+        let plot_json =
+            serde_json::from_str::<Value>(&chart.main_plot.to_json()).expect("invalid plot json");
+        let hidden_label = plot_json
+            .pointer("/layout/annotations")
+            .and_then(Value::as_array)
+            .expect("annotations are missing")
+            .iter()
+            .find(|annotation| {
+                annotation.get("name").and_then(Value::as_str) == Some("forecast-14")
+            })
+            .expect("label of the extra horizon is missing");
+        assert_eq!(hidden_label.get("visible"), Some(&Value::Bool(false)));
+
+        let primary = chart.summary.forecast.expect("forecast summary");
+        assert_eq!(
+            (primary.horizon_days, primary.end_date.as_str()),
+            (7, "2024-06-07")
+        );
+        let horizons = &chart.summary.forecast_horizons;
+        assert_eq!(horizons.len(), 1);
+        assert_eq!(
+            (horizons[0].group.as_str(), horizons[0].end_date.as_str()),
+            ("forecast-14", "2024-06-14")
+        );
+    }
+
+    #[test]
+    fn shared_downsample_keeps_upper_extrema() {
+        let x: Vec<u32> = (0..12).collect();
+        let lower = vec![0.0; x.len()];
+        let mut upper = vec![0.0; x.len()];
+        upper[6] = 10.0;
+
+        let (x_out, lower_out, upper_out) = downsample_min_max_shared_x(&x, &lower, &upper, 4);
+        assert_eq!(x_out.len(), lower_out.len());
+        assert_eq!(x_out.len(), upper_out.len());
+        assert!(
+            x_out.contains(&6),
+            "downsampled x must contain upper extremum index"
+        );
+        assert!(upper_out.iter().any(|value| (*value - 10.0).abs() < 1e-12));
+    }
+
+    #[test]
+    fn unspecified_change_plot_is_relative_to_reference_change() {
+        let dates = vec![
+            NaiveDate::from_ymd_opt(2023, 2, 4).expect("valid date"),
+            NaiveDate::from_ymd_opt(2023, 2, 5).expect("valid date"),
+            NaiveDate::from_ymd_opt(2023, 2, 6).expect("valid date"),
+            NaiveDate::from_ymd_opt(2023, 2, 7).expect("valid date"),
+        ];
+        let areas = vec![3_500.0, 10.0, 12.0, 13.0];
+        let reference_change = PreparedChangeSeries {
+            baseline: NaiveDate::from_ymd_opt(2022, 11, 23).expect("valid date"),
+            smoothing: SmoothingSpec::default(),
+            dates: vec![
+                NaiveDate::from_ymd_opt(2023, 2, 5).expect("valid date"),
+                NaiveDate::from_ymd_opt(2023, 2, 6).expect("valid date"),
+                NaiveDate::from_ymd_opt(2023, 2, 7).expect("valid date"),
+            ],
+            labels: vec![
+                "2023-02-05".to_string(),
+                "2023-02-06".to_string(),
+                "2023-02-07".to_string(),
+            ],
+            values: vec![100.0, 101.0, 102.0],
+        };
+
+        let plot = prepare_unspecified_change_plot(
+            &reference_change,
+            &dates,
+            &areas,
+            NaiveDate::from_ymd_opt(2023, 2, 5).expect("valid date"),
+            100,
+        );
+
+        assert!(!plot.dates.is_empty());
+        assert_eq!(plot.dates[0], "2023-02-05");
+        assert_eq!(plot.reference_values, vec![100.0, 101.0, 102.0]);
+        assert_eq!(plot.values.len(), 3);
+        assert!((plot.values[0] - 101.0).abs() < 1e-12);
+        assert!((plot.values[1] - 102.0).abs() < 1e-12);
+        assert!((plot.values[2] - 103.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn category_chart_stacks_non_empty_categories() {
+        let csv = format!(
+            "{SAMPLE_GRAY_ZONE_CSV}\
+2024-05-01 00:00:00 UTC,#880e4f,43.0,0.0,occupied_to_24_02_2022\n\
+2024-05-03 00:00:00 UTC,#880e4f,43.0,0.0,occupied_to_24_02_2022\n"
+        );
+        let chart = build_chart_from_csv(&csv);
+        let traces = chart
+            .category_plot
+            .data()
+            .iter()
+            .map(|trace| serde_json::from_str::<Value>(&trace.to_json()).expect("invalid trace"))
+            .collect::<Vec<_>>();
+
+        let names = traces
+            .iter()
+            .map(|trace| trace["name"].as_str().expect("trace name"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "Занято до 24.02.2022",
+                "Занято после 24.02.2022",
+                "Освобождено",
+                "Серая зона"
+            ]
+        );
+        assert!(
+            traces
+                .iter()
+                .all(|trace| trace["stackgroup"] == "categories")
+        );
+        assert_eq!(traces[0]["y"], serde_json::json!([0.043, 0.043, 0.043]));
+        assert_eq!(traces[1]["y"], serde_json::json!([0.08, 0.09, 0.105]));
+
+        let without_pre_war = build_chart_from_csv(SAMPLE_GRAY_ZONE_CSV);
+        assert_eq!(without_pre_war.category_plot.data().len(), 3);
+    }
+}
@@ -0,0 +1,176 @@
+//! Сравнение двух источников карт: выравнивание рядов занятой площади по датам
+//! и статистика расхождения между ними.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+
+use chrono::NaiveDate;
+
+use crate::series::{
+    AreaBuckets, InterpolationStrategy, build_occupied_and_unspecified_series_with,
+};
+
+/// Меньше общих точек — корреляция не считается.
+const MIN_CORRELATION_POINTS: usize = 3;
+
+/// История второго источника и подписи обоих рядов.
+pub struct ComparisonSource {
+    pub primary_label: String,
+    pub label: String,
+    pub buckets: AreaBuckets,
+}
+
+/// Ряды двух источников на общих датах, км².
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlignedSeries {
+    pub dates: Vec<NaiveDate>,
+    pub primary: Vec<f64>,
+    pub secondary: Vec<f64>,
+    /// `true`, если за день есть срезы обоих источников (интерполяция не участвует в статистике).
+    pub measured: Vec<bool>,
+}
+
+/// Сводка расхождения по дням, измеренным обоими источниками.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComparisonStats {
+    pub overlap_days: usize,
+    /// Корреляция Пирсона уровней.
+    pub correlation: Option<f64>,
+    /// Корреляция суточных изменений (только пары соседних измеренных дней).
+    pub change_correlation: Option<f64>,
+    /// Средняя абсолютная разница, км².
+    pub mean_abs_diff_km2: Option<f64>,
+    /// День наибольшего расхождения и его величина (второй минус основной), км².
+    pub max_diff: Option<(NaiveDate, f64)>,
+}
+
+/// Строит оба ряда одной стратегией заполнения и оставляет общие даты.
+pub fn align_sources(
+    primary: &AreaBuckets,
+    secondary: &AreaBuckets,
+    strategy: InterpolationStrategy,
+) -> Result<AlignedSeries, Box<dyn Error>> {
+    let primary = build_occupied_and_unspecified_series_with(primary, strategy)?;
+    let secondary = build_occupied_and_unspecified_series_with(secondary, strategy)?;
+    let secondary_by_date = secondary
+        .dates
+        .iter()
+        .zip(secondary.occupied.iter().zip(&secondary.measured))
+        .map(|(date, (value, measured))| (*date, (*value, *measured)))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut aligned = AlignedSeries {
+        dates: Vec::new(),
+        primary: Vec::new(),
+        secondary: Vec::new(),
+        measured: Vec::new(),
+    };
+    for ((date, value), measured) in primary
+        .dates
+        .iter()
+        .zip(&primary.occupied)
+        .zip(&primary.measured)
+    {
+        if let Some((other, other_measured)) = secondary_by_date.get(date) {
+            aligned.dates.push(*date);
+            aligned.primary.push(*value);
+            aligned.secondary.push(*other);
+            aligned.measured.push(*measured && *other_measured);
+        }
+    }
+    if aligned.dates.is_empty() {
+        return Err("sources have no overlapping dates".into());
+    }
+    Ok(aligned)
+}
+
+pub fn comparison_stats(aligned: &AlignedSeries) -> ComparisonStats {
+    let points = (0..aligned.dates.len())
+        .filter(|&idx| aligned.measured[idx])
+        .map(|idx| {
+            (
+                aligned.dates[idx],
+                aligned.primary[idx],
+                aligned.secondary[idx],
+            )
+        })
+        .collect::<Vec<_>>();
+    let (primary, secondary): (Vec<f64>, Vec<f64>) =
+        points.iter().map(|(_, a, b)| (*a, *b)).unzip();
+    let (primary_changes, secondary_changes): (Vec<f64>, Vec<f64>) = points
+        .windows(2)
+        .filter(|pair| (pair[1].0 - pair[0].0).num_days() == 1)
+        .map(|pair| (pair[1].1 - pair[0].1, pair[1].2 - pair[0].2))
+        .unzip();
+    let diffs = points
+        .iter()
+        .map(|(date, a, b)| (*date, b - a))
+        .collect::<Vec<_>>();
+
+    ComparisonStats {
+        overlap_days: points.len(),
+        correlation: pearson(&primary, &secondary),
+        change_correlation: pearson(&primary_changes, &secondary_changes),
+        mean_abs_diff_km2: (!diffs.is_empty())
+            .then(|| diffs.iter().map(|(_, diff)| diff.abs()).sum::<f64>() / len_f64(diffs.len())),
+        max_diff: diffs
+            .iter()
+            .copied()
+            .max_by(|(_, left), (_, right)| left.abs().total_cmp(&right.abs())),
+    }
+}
+
+fn pearson(x: &[f64], y: &[f64]) -> Option<f64> {
+    if x.len() != y.len() || x.len() < MIN_CORRELATION_POINTS {
+        return None;
+    }
+    let n = len_f64(x.len());
+    let mean_x = x.iter().sum::<f64>() / n;
+    let mean_y = y.iter().sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (a, b) in x.iter().zip(y) {
+        let (dx, dy) = (a - mean_x, b - mean_y);
+        cov = dx.mul_add(dy, cov);
+        var_x = dx.mul_add(dx, var_x);
+        var_y = dy.mul_add(dy, var_y);
+    }
+    let denominator = (var_x * var_y).sqrt();
+    (denominator > f64::EPSILON).then(|| cov / denominator)
+}
+
+#[allow(clippy::cast_precision_loss)]
+const fn len_f64(len: usize) -> f64 {
+    len as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Days, NaiveDate};
+
+    use super::{AlignedSeries, comparison_stats};
+
+    #[test]
+    fn stats_use_only_days_measured_by_both_sources() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date");
+        let dates = (0..6)
+            .map(|day| start.checked_add_days(Days::new(day)).expect("valid date"))
+            .collect::<Vec<_>>();
+        let aligned = AlignedSeries {
+            dates: dates.clone(),
+            primary: vec![100.0, 110.0, 120.0, 130.0, 140.0, 150.0],
+            secondary: vec![105.0, 115.0, 125.0, 999.0, 145.0, 165.0],
+            measured: vec![true, true, true, false, true, true],
+        };
+
+        let stats = comparison_stats(&aligned);
+
+        assert_eq!(stats.overlap_days, 5);
+        assert_eq!(stats.max_diff, Some((dates[5], 15.0)));
+        let mean = stats.mean_abs_diff_km2.expect("mean diff");
+        assert!((mean - 7.0).abs() < 1e-9);
+        let correlation = stats.correlation.expect("level correlation");
+        assert!(correlation > 0.99);
+        // Соседние измеренные пары: (0,1), (1,2), (4,5); изменения основного ряда постоянны.
+        assert_eq!(stats.change_correlation, None);
+    }
+}
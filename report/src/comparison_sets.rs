@@ -0,0 +1,182 @@
+//! Наборы территорий для таблиц соотношений: встроенные справочники из [`super::data`] и
+//! пользовательские CSV (`name,area_km2[,flag]`) из `[page].comparison_sets`.
+
+use std::error::Error;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use super::data::{EU_COUNTRIES, JAPAN_PREFECTURES, UK_COUNTIES, UNFRIENDLY_COUNTRIES, US_STATES};
+
+/// Наборы таблиц по умолчанию: страны и штаты США.
+pub const DEFAULT_COMPARISON_SETS: [&str; 2] = ["countries", "us_states"];
+
+/// Встроенные наборы: ключ, заголовок таблицы и подпись колонки названий.
+const BUILTIN_SETS: &[(&str, &str, &str)] = &[
+    ("countries", "Страны", "Страна"),
+    ("us_states", "США — штаты", "Штат"),
+    ("eu", "Евросоюз", "Страна"),
+    ("uk_counties", "Англия — графства", "Графство"),
+    ("japan_prefectures", "Япония — префектуры", "Префектура"),
+];
+
+/// Одна территория набора.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComparisonEntry {
+    pub name: String,
+    pub area_km2: f64,
+    /// Код флага для `<flag_base_url><код>.png`; без него строка идёт без флага.
+    pub flag: Option<String>,
+}
+
+/// Именованный набор территорий — одна таблица соотношений на странице.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComparisonSet {
+    pub key: String,
+    pub title: String,
+    pub name_column: String,
+    pub entries: Vec<ComparisonEntry>,
+}
+
+#[derive(Deserialize)]
+struct EntryRecord {
+    name: String,
+    area_km2: f64,
+    #[serde(default)]
+    flag: Option<String>,
+}
+
+impl ComparisonSet {
+    /// Встроенный набор по ключу из [`builtin_keys`].
+    pub fn builtin(key: &str) -> Option<Self> {
+        let &(key, title, name_column) = BUILTIN_SETS.iter().find(|(known, ..)| *known == key)?;
+        let flagged = |rows: &[(&str, f64, &str)]| {
+            rows.iter()
+                .map(|(name, area, flag)| ComparisonEntry {
+                    name: (*name).to_string(),
+                    area_km2: *area,
+                    flag: Some((*flag).to_string()),
+                })
+                .collect()
+        };
+        let plain = |rows: &[(&str, f64)]| {
+            rows.iter()
+                .map(|(name, area)| ComparisonEntry {
+                    name: (*name).to_string(),
+                    area_km2: *area,
+                    flag: None,
+                })
+                .collect()
+        };
+        let entries = match key {
+            "countries" => flagged(UNFRIENDLY_COUNTRIES),
+            "us_states" => flagged(US_STATES),
+            "eu" => flagged(EU_COUNTRIES),
+            "uk_counties" => plain(UK_COUNTIES),
+            "japan_prefectures" => plain(JAPAN_PREFECTURES),
+            _ => return None,
+        };
+        Some(Self {
+            key: key.to_string(),
+            title: title.to_string(),
+            name_column: name_column.to_string(),
+            entries,
+        })
+    }
+
+    /// Читает набор из CSV с колонками `name`, `area_km2` и необязательной `flag`.
+    pub fn from_csv(
+        key: String,
+        title: String,
+        name_column: String,
+        path: &Path,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut reader = csv::Reader::from_path(path)?;
+        let mut entries = Vec::new();
+        for record in reader.deserialize::<EntryRecord>() {
+            let record = record?;
+            if !record.area_km2.is_finite() || record.area_km2 <= 0.0 {
+                return Err(format!(
+                    "area_km2 of `{}` in {} must be positive",
+                    record.name,
+                    path.display()
+                )
+                .into());
+            }
+            entries.push(ComparisonEntry {
+                name: record.name,
+                area_km2: record.area_km2,
+                flag: record
+                    .flag
+                    .map(|flag| flag.trim().to_string())
+                    .filter(|flag| !flag.is_empty()),
+            });
+        }
+        if entries.is_empty() {
+            return Err(format!("{} has no rows", path.display()).into());
+        }
+        Ok(Self {
+            key,
+            title,
+            name_column,
+            entries,
+        })
+    }
+}
+
+/// Ключи встроенных наборов.
+pub fn builtin_keys() -> impl Iterator<Item = &'static str> {
+    BUILTIN_SETS.iter().map(|(key, ..)| *key)
+}
+
+/// Наборы по умолчанию ([`DEFAULT_COMPARISON_SETS`]).
+pub fn default_comparison_sets() -> Vec<ComparisonSet> {
+    DEFAULT_COMPARISON_SETS
+        .iter()
+        .filter_map(|key| ComparisonSet::builtin(key))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ComparisonSet, builtin_keys};
+
+    #[test]
+    fn builtin_sets_and_csv_sets_load() {
+        for key in builtin_keys() {
+            let set = ComparisonSet::builtin(key).expect("builtin set");
+            assert!(!set.entries.is_empty(), "{key}");
+            assert!(
+                set.entries.iter().all(|entry| entry.area_km2 > 0.0),
+                "{key}"
+            );
+        }
+        assert_eq!(
+            ComparisonSet::builtin("japan_prefectures").map(|set| set.entries.len()),
+            Some(47)
+        );
+        assert!(ComparisonSet::builtin("mars").is_none());
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("sets.csv");
+        std::fs::write(
+            &path,
+            "name,area_km2,flag\nБавария,70550,\nТироль,12648,at\n",
+        )
+        .expect("csv");
+        let set = ComparisonSet::from_csv(
+            "custom".to_string(),
+            "Земли".to_string(),
+            "Земля".to_string(),
+            &path,
+        );
+        std::fs::write(&path, "name,area_km2\nНигде,0\n").expect("csv");
+        let invalid =
+            ComparisonSet::from_csv("bad".to_string(), String::new(), String::new(), &path);
+        let set = set.expect("custom set");
+        assert_eq!(set.entries.len(), 2);
+        assert_eq!(set.entries[0].flag, None);
+        assert_eq!(set.entries[1].flag.as_deref(), Some("at"));
+        assert!(invalid.is_err());
+    }
+}
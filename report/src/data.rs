@@ -0,0 +1,268 @@
+//! Статические данные для таблиц в отчёте.
+
+pub const UNFRIENDLY_COUNTRIES: &[(&str, f64, &str)] = &[
+    ("Австралия", 7_692_024.0, "au"),
+    ("Албания", 28_748.0, "al"),
+    ("Андорра", 468.0, "ad"),
+    ("Багамские Острова", 13_943.0, "bs"),
+    ("Великобритания", 244_376.0, "gb"),
+    ("Австрия", 83_871.0, "at"),
+    ("Бельгия", 30_528.0, "be"),
+    ("Болгария", 110_879.0, "bg"),
+    ("Германия", 357_114.0, "de"),
+    ("Греция", 131_990.0, "gr"),
+    ("Дания", 43_094.0, "dk"),
+    ("Ирландия", 70_273.0, "ie"),
+    ("Испания", 505_992.0, "es"),
+    ("Италия", 301_336.0, "it"),
+    ("Республика Кипр", 9251.0, "cy"),
+    ("Латвия", 64_559.0, "lv"),
+    ("Литва", 65_300.0, "lt"),
+    ("Люксембург", 2586.0, "lu"),
+    ("Мальта", 316.0, "mt"),
+    ("Нидерланды", 41_865.0, "nl"),
+    ("Польша", 312_679.0, "pl"),
+    ("Португалия", 92_090.0, "pt"),
+    ("Румыния", 238_391.0, "ro"),
+    ("Словения", 20_273.0, "si"),
+    ("Финляндия", 338_455.0, "fi"),
+    ("Франция", 543_908.0, "fr"),
+    ("Хорватия", 56_594.0, "hr"),
+    ("Чехия", 78_865.0, "cz"),
+    ("Швеция", 450_295.0, "se"),
+    ("Эстония", 45_227.0, "ee"),
+    ("Исландия", 103_000.0, "is"),
+    ("Канада", 9_984_670.0, "ca"),
+    ("Лихтенштейн", 160.0, "li"),
+    ("Микронезия", 702.0, "fm"),
+    ("Монако", 2.02, "mc"),
+    ("Новая Зеландия", 268_838.0, "nz"),
+    ("Норвегия", 386_224.0, "no"),
+    ("Республика Корея", 100_210.0, "kr"),
+    ("Сан-Марино", 61.0, "sm"),
+    ("Северная Македония", 25_713.0, "mk"),
+    ("Сингапур", 710.0, "sg"),
+    ("Соединённые Штаты Америки", 9_525_067.0, "us"),
+    ("Китайская Республика", 36_197.0, "tw"),
+    ("Черногория", 13_812.0, "me"),
+    ("Швейцария", 41_284.0, "ch"),
+    ("Япония", 377_930.0, "jp"),
+];
+
+pub const US_STATES: &[(&str, f64, &str)] = &[
+    ("Алабама", 135_767.0, "us-al"),
+    ("Аляска", 1_723_337.0, "us-ak"),
+    ("Аризона", 295_234.0, "us-az"),
+    ("Арканзас", 137_732.0, "us-ar"),
+    ("Калифорния", 423_967.0, "us-ca"),
+    ("Колорадо", 269_601.0, "us-co"),
+    ("Коннектикут", 14_357.0, "us-ct"),
+    ("Делавэр", 6446.0, "us-de"),
+    ("Флорида", 170_312.0, "us-fl"),
+    ("Джорджия", 153_910.0, "us-ga"),
+    ("Гавайи", 28_313.0, "us-hi"),
+    ("Айдахо", 216_443.0, "us-id"),
+    ("Иллинойс", 149_995.0, "us-il"),
+    ("Индиана", 94_326.0, "us-in"),
+    ("Айова", 145_746.0, "us-ia"),
+    ("Канзас", 213_100.0, "us-ks"),
+    ("Кентукки", 104_656.0, "us-ky"),
+    ("Луизиана", 135_659.0, "us-la"),
+    ("Мэн", 91_633.0, "us-me"),
+    ("Мэриленд", 32_131.0, "us-md"),
+    ("Массачусетс", 27_336.0, "us-ma"),
+    ("Мичиган", 250_487.0, "us-mi"),
+    ("Миннесота", 225_163.0, "us-mn"),
+    ("Миссисипи", 125_438.0, "us-ms"),
+    ("Миссури", 180_540.0, "us-mo"),
+    ("Монтана", 380_831.0, "us-mt"),
+    ("Небраска", 200_330.0, "us-ne"),
+    ("Невада", 286_380.0, "us-nv"),
+    ("Нью-Гэмпшир", 24_214.0, "us-nh"),
+    ("Нью-Джерси", 22_591.0, "us-nj"),
+    ("Нью-Мексико", 314_917.0, "us-nm"),
+    ("Нью-Йорк", 141_297.0, "us-ny"),
+    ("Северная Каролина", 139_391.0, "us-nc"),
+    ("Северная Дакота", 183_108.0, "us-nd"),
+    ("Огайо", 116_098.0, "us-oh"),
+    ("Оклахома", 181_037.0, "us-ok"),
+    ("Орегон", 254_799.0, "us-or"),
+    ("Пенсильвания", 119_280.0, "us-pa"),
+    ("Род-Айленд", 4001.0, "us-ri"),
+    ("Южная Каролина", 82_933.0, "us-sc"),
+    ("Южная Дакота", 199_729.0, "us-sd"),
+    ("Теннесси", 109_153.0, "us-tn"),
+    ("Техас", 695_662.0, "us-tx"),
+    ("Юта", 219_882.0, "us-ut"),
+    ("Вермонт", 24_906.0, "us-vt"),
+    ("Виргиния", 110_787.0, "us-va"),
+    ("Вашингтон", 184_661.0, "us-wa"),
+    ("Западная Виргиния", 62_756.0, "us-wv"),
+    ("Висконсин", 169_635.0, "us-wi"),
+    ("Вайоминг", 253_335.0, "us-wy"),
+];
+
+/// Страны Евросоюза, км².
+pub const EU_COUNTRIES: &[(&str, f64, &str)] = &[
+    ("Австрия", 83_871.0, "at"),
+    ("Бельгия", 30_528.0, "be"),
+    ("Болгария", 110_879.0, "bg"),
+    ("Венгрия", 93_030.0, "hu"),
+    ("Германия", 357_114.0, "de"),
+    ("Греция", 131_990.0, "gr"),
+    ("Дания", 43_094.0, "dk"),
+    ("Ирландия", 70_273.0, "ie"),
+    ("Испания", 505_992.0, "es"),
+    ("Италия", 301_336.0, "it"),
+    ("Республика Кипр", 9251.0, "cy"),
+    ("Латвия", 64_559.0, "lv"),
+    ("Литва", 65_300.0, "lt"),
+    ("Люксембург", 2586.0, "lu"),
+    ("Мальта", 316.0, "mt"),
+    ("Нидерланды", 41_865.0, "nl"),
+    ("Польша", 312_679.0, "pl"),
+    ("Португалия", 92_090.0, "pt"),
+    ("Румыния", 238_391.0, "ro"),
+    ("Словакия", 49_035.0, "sk"),
+    ("Словения", 20_273.0, "si"),
+    ("Финляндия", 338_455.0, "fi"),
+    ("Франция", 543_908.0, "fr"),
+    ("Хорватия", 56_594.0, "hr"),
+    ("Чехия", 78_865.0, "cz"),
+    ("Швеция", 450_295.0, "se"),
+    ("Эстония", 45_227.0, "ee"),
+];
+
+/// Церемониальные графства Англии, км².
+pub const UK_COUNTIES: &[(&str, f64)] = &[
+    ("Бедфордшир", 1235.0),
+    ("Беркшир", 1262.0),
+    ("Бристоль", 110.0),
+    ("Бакингемшир", 1874.0),
+    ("Кембриджшир", 3389.0),
+    ("Чешир", 2343.0),
+    ("Лондонский Сити", 2.9),
+    ("Корнуолл", 3563.0),
+    ("Камбрия", 6768.0),
+    ("Дербишир", 2625.0),
+    ("Девон", 6707.0),
+    ("Дорсет", 2653.0),
+    ("Дарем", 2721.0),
+    ("Восточный Райдинг Йоркшира", 2479.0),
+    ("Восточный Сассекс", 1792.0),
+    ("Эссекс", 3670.0),
+    ("Глостершир", 3150.0),
+    ("Большой Лондон", 1572.0),
+    ("Большой Манчестер", 1276.0),
+    ("Хэмпшир", 3769.0),
+    ("Херефордшир", 2180.0),
+    ("Хартфордшир", 1643.0),
+    ("Остров Уайт", 380.0),
+    ("Кент", 3736.0),
+    ("Ланкашир", 3075.0),
+    ("Лестершир", 2156.0),
+    ("Линкольншир", 6959.0),
+    ("Мерсисайд", 645.0),
+    ("Норфолк", 5371.0),
+    ("Северный Йоркшир", 8654.0),
+    ("Нортгемптоншир", 2364.0),
+    ("Нортумберленд", 5013.0),
+    ("Ноттингемшир", 2159.0),
+    ("Оксфордшир", 2605.0),
+    ("Ратленд", 382.0),
+    ("Шропшир", 3487.0),
+    ("Сомерсет", 4171.0),
+    ("Южный Йоркшир", 1552.0),
+    ("Стаффордшир", 2714.0),
+    ("Суффолк", 3801.0),
+    ("Суррей", 1663.0),
+    ("Тайн-энд-Уир", 540.0),
+    ("Уорикшир", 1975.0),
+    ("Уэст-Мидлендс", 902.0),
+    ("Западный Сассекс", 1991.0),
+    ("Западный Йоркшир", 2029.0),
+    ("Уилтшир", 3485.0),
+    ("Вустершир", 1741.0),
+];
+
+/// Префектуры Японии, км².
+pub const JAPAN_PREFECTURES: &[(&str, f64)] = &[
+    ("Хоккайдо", 83_424.0),
+    ("Аомори", 9646.0),
+    ("Иватэ", 15_275.0),
+    ("Мияги", 7282.0),
+    ("Акита", 11_638.0),
+    ("Ямагата", 9323.0),
+    ("Фукусима", 13_784.0),
+    ("Ибараки", 6097.0),
+    ("Тотиги", 6408.0),
+    ("Гумма", 6362.0),
+    ("Сайтама", 3798.0),
+    ("Тиба", 5158.0),
+    ("Токио", 2194.0),
+    ("Канагава", 2416.0),
+    ("Ниигата", 12_584.0),
+    ("Тояма", 4248.0),
+    ("Исикава", 4186.0),
+    ("Фукуи", 4191.0),
+    ("Яманаси", 4465.0),
+    ("Нагано", 13_562.0),
+    ("Гифу", 10_621.0),
+    ("Сидзуока", 7777.0),
+    ("Айти", 5173.0),
+    ("Миэ", 5774.0),
+    ("Сига", 4017.0),
+    ("Киото", 4612.0),
+    ("Осака", 1905.0),
+    ("Хиого", 8401.0),
+    ("Нара", 3691.0),
+    ("Вакаяма", 4725.0),
+    ("Тоттори", 3507.0),
+    ("Симанэ", 6708.0),
+    ("Окаяма", 7114.0),
+    ("Хиросима", 8479.0),
+    ("Ямагути", 6113.0),
+    ("Токусима", 4147.0),
+    ("Кагава", 1877.0),
+    ("Эхимэ", 5676.0),
+    ("Коти", 7104.0),
+    ("Фукуока", 4987.0),
+    ("Сага", 2441.0),
+    ("Нагасаки", 4131.0),
+    ("Кумамото", 7409.0),
+    ("Оита", 6341.0),
+    ("Миядзаки", 7735.0),
+    ("Кагосима", 9187.0),
+    ("Окинава", 2282.0),
+];
+
+/// Области Украины, АР Крым и города со специальным статусом, км².
+pub const UKRAINE_REGIONS: &[(&str, f64)] = &[
+    ("Винницкая", 26_513.0),
+    ("Волынская", 20_144.0),
+    ("Днепропетровская", 31_914.0),
+    ("Донецкая", 26_517.0),
+    ("Житомирская", 29_832.0),
+    ("Закарпатская", 12_777.0),
+    ("Запорожская", 27_180.0),
+    ("Ивано-Франковская", 13_928.0),
+    ("Киевская", 28_131.0),
+    ("Кировоградская", 24_588.0),
+    ("Луганская", 26_684.0),
+    ("Львовская", 21_833.0),
+    ("Николаевская", 24_598.0),
+    ("Одесская", 33_310.0),
+    ("Полтавская", 28_748.0),
+    ("Ровенская", 20_047.0),
+    ("Сумская", 23_834.0),
+    ("Тернопольская", 13_823.0),
+    ("Харьковская", 31_415.0),
+    ("Херсонская", 28_461.0),
+    ("Хмельницкая", 20_645.0),
+    ("Черкасская", 20_900.0),
+    ("Черновицкая", 8_097.0),
+    ("Черниговская", 31_865.0),
+    ("АР Крым", 26_081.0),
+    ("Киев", 839.0),
+    ("Севастополь", 864.0),
+];
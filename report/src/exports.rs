@@ -0,0 +1,246 @@
+//! Выгрузки для сторонних потребителей: фигура Plotly целиком, как её рисует страница, и
+//! соотношения площади с территориями из таблиц страницы.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use plotly::Plot;
+use serde_json::{Value, json};
+
+use super::constants::{AREA_THOUSANDS_DIVISOR, Precision, round_to};
+use super::overlay::ChartSummary;
+use super::page;
+use super::page_config::PageConfig;
+
+/// Фигура в JSON (`data`, `layout`, `config`) с атрибутами, которых нет в plotly-rs:
+/// пунктирная сетка осей и общая подсказка панелей с одной осью X (`hoversubplots`).
+/// Дробные числа трасс округляются до `precision.chart` знаков: полная точность `f64`
+/// только раздувает страницу.
+pub fn figure_json(plot: &Plot, precision: Precision) -> String {
+    let mut figure = serde_json::to_value(plot).unwrap_or_default();
+    if let Some(data) = figure.get_mut("data") {
+        round_numbers(data, precision.chart);
+    }
+    if let Some(layout) = figure.get_mut("layout").and_then(Value::as_object_mut) {
+        // Оси по умолчанию тоже получают пунктир, даже если раскладка их не задаёт.
+        for key in ["xaxis", "yaxis"] {
+            layout
+                .entry(key)
+                .or_insert_with(|| Value::Object(serde_json::Map::new()));
+        }
+        for (key, axis) in layout.iter_mut() {
+            if is_grid_axis(key)
+                && let Some(axis) = axis.as_object_mut()
+            {
+                axis.insert("griddash".to_string(), Value::from("dash"));
+            }
+        }
+        layout.insert("hoversubplots".to_string(), Value::from("axis"));
+    }
+    figure.to_string()
+}
+
+/// Округляет все дробные числа внутри `value`; целые остаются как есть.
+fn round_numbers(value: &mut Value, decimals: usize) {
+    match value {
+        Value::Number(number) if !number.is_i64() && !number.is_u64() => {
+            if let Some(rounded) = number
+                .as_f64()
+                .and_then(|float| serde_json::Number::from_f64(round_to(float, decimals)))
+            {
+                *number = rounded;
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| round_numbers(item, decimals)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|field| round_numbers(field, decimals)),
+        _ => {}
+    }
+}
+
+/// Пишет фигуру основного графика в `figure.json`: её можно перерисовать в своём окружении
+/// Plotly вызовом `Plotly.newPlot(div, figure)`.
+pub fn write_figure_json(
+    plot: &Plot,
+    precision: Precision,
+    output_json: &Path,
+) -> Result<(), Box<dyn Error>> {
+    write_export(output_json, &figure_json(plot, precision))
+}
+
+/// Соотношения последней площади и прогноза на конец горизонта с каждой территорией наборов
+/// `[page].comparison_sets`; строки считает тот же код, что и таблицы страницы.
+pub fn ratios_json(summary: &ChartSummary, page: &PageConfig) -> String {
+    let precision = page.precision;
+    let area = |thousands: f64| round_to(thousands * AREA_THOUSANDS_DIVISOR, precision.area_km2);
+    let ratio = |value: f64| round_to(value, precision.ratio);
+    let sets = page::ratio_sets(summary, page)
+        .into_iter()
+        .map(|set| {
+            let rows = set
+                .rows
+                .iter()
+                .map(|row| {
+                    json!({
+                        "name": row.name,
+                        "flag": row.flag,
+                        "area_km2": round_to(row.area_km2, precision.area_km2),
+                        "ratio": ratio(row.ratio_value),
+                        "forecast": row.forecast_values.map(|forecast| json!({
+                            "mean": ratio(forecast.mean),
+                            "lower": ratio(forecast.lower),
+                            "upper": ratio(forecast.upper),
+                        })),
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({ "key": set.key, "title": set.title, "rows": rows })
+        })
+        .collect::<Vec<_>>();
+    let forecast = summary.forecast.as_ref().map(|forecast| {
+        json!({
+            "horizon_days": forecast.horizon_days,
+            "end_date": forecast.end_date,
+            "mean_km2": area(forecast.mean_km2),
+            "lower_km2": area(forecast.lower_km2),
+            "upper_km2": area(forecast.upper_km2),
+        })
+    });
+    json!({
+        "date": summary.latest_date,
+        "area_km2": area(summary.latest_area_km2),
+        "forecast": forecast,
+        "sets": sets,
+    })
+    .to_string()
+}
+
+/// Пишет соотношения в `ratios.json`.
+pub fn write_ratios_json(
+    summary: &ChartSummary,
+    page: &PageConfig,
+    output_json: &Path,
+) -> Result<(), Box<dyn Error>> {
+    write_export(output_json, &ratios_json(summary, page))
+}
+
+fn write_export(path: &Path, body: &str) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, body)?;
+    Ok(())
+}
+
+/// `xaxis`, `yaxis2` и т. п.: оси декартовых панелей.
+fn is_grid_axis(key: &str) -> bool {
+    ["xaxis", "yaxis"].iter().any(|prefix| {
+        key.strip_prefix(prefix)
+            .is_some_and(|rest| rest.chars().all(|ch| ch.is_ascii_digit()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Precision, ratios_json, write_figure_json};
+    use crate::overlay::{ChartSummary, ForecastSummary};
+    use crate::page_config::PageConfig;
+    use plotly::layout::{Axis, Layout};
+    use plotly::{Plot, Scatter};
+    use serde_json::Value;
+
+    #[test]
+    fn figure_json_keeps_data_and_adds_page_only_layout() {
+        let mut plot = Plot::new();
+        plot.add_trace(Scatter::new(vec!["2024-07-01"], vec![116.320_567_8]).name("Факт"));
+        plot.set_layout(
+            Layout::new()
+                .x_axis(Axis::new())
+                .y_axis2(Axis::new())
+                .title("t"),
+        );
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("figure.json");
+        write_figure_json(&plot, Precision::default(), &path).expect("failed to write figure");
+        let written = std::fs::read_to_string(&path).expect("failed to read figure");
+
+        let figure = serde_json::from_str::<Value>(&written).expect("invalid figure json");
+        assert_eq!(
+            figure.pointer("/data/0/name").and_then(Value::as_str),
+            Some("Факт")
+        );
+        assert_eq!(
+            figure.pointer("/data/0/y/0").and_then(Value::as_f64),
+            Some(116.3206)
+        );
+        assert_eq!(
+            figure
+                .pointer("/layout/xaxis/griddash")
+                .and_then(Value::as_str),
+            Some("dash")
+        );
+        assert_eq!(
+            figure
+                .pointer("/layout/yaxis2/griddash")
+                .and_then(Value::as_str),
+            Some("dash")
+        );
+        assert!(figure.pointer("/layout/title/griddash").is_none());
+        assert_eq!(
+            figure
+                .pointer("/layout/hoversubplots")
+                .and_then(Value::as_str),
+            Some("axis")
+        );
+        assert!(figure.get("config").is_some());
+    }
+
+    #[test]
+    fn ratios_json_has_latest_and_forecast_ratio_per_territory() {
+        let summary = ChartSummary {
+            latest_date: "2025-01-02".to_string(),
+            latest_area_km2: 110.0,
+            ukraine_percent: 18.2,
+            daily_change_km2: None,
+            weekly_change_km2: None,
+            forecast: Some(ForecastSummary {
+                horizon_days: 30,
+                group: String::new(),
+                end_date: "2025-02-01".to_string(),
+                mean_km2: 121.0,
+                lower_km2: 115.5,
+                upper_km2: 126.5,
+                training: None,
+                stale: None,
+            }),
+            forecast_horizons: Vec::new(),
+            extremes: None,
+        };
+        let ratios = serde_json::from_str::<Value>(&ratios_json(&summary, &PageConfig::default()))
+            .expect("invalid ratios json");
+
+        assert_eq!(ratios["area_km2"].as_f64(), Some(110_000.0));
+        assert_eq!(ratios["forecast"]["end_date"].as_str(), Some("2025-02-01"));
+        let sets = ratios["sets"].as_array().expect("sets");
+        assert_eq!(
+            sets.iter()
+                .filter_map(|set| set["key"].as_str())
+                .collect::<Vec<_>>(),
+            ["countries", "us_states"]
+        );
+        let row = &sets[0]["rows"][0];
+        let area = row["area_km2"].as_f64().expect("territory area");
+        // Соотношения округлены до `precision.ratio` (трёх) знаков.
+        assert!((row["ratio"].as_f64().expect("ratio") - 110_000.0 / area).abs() <= 5e-4);
+        assert!(
+            (row["forecast"]["mean"].as_f64().expect("forecast ratio") - 121_000.0 / area).abs()
+                <= 5e-4
+        );
+    }
+}
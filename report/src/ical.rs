@@ -0,0 +1,396 @@
+//! Календарь iCalendar (RFC 5545): смены тренда, пользовательские события и конец горизонта
+//! прогноза — для подписки из календарных приложений.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+
+use super::{ForecastOverlay, SmoothedOverlay};
+use crate::constants::DATE_FORMAT;
+use crate::integrity::sha256_hex;
+
+const PRODUCT_ID: &str = "-//hexqnt//rua//RU";
+const CALENDAR_NAME: &str = "Динамика занятых территорий";
+const UID_DOMAIN: &str = "rua";
+/// Предел длины строки в октетах без учёта CRLF; длинные строки переносятся (RFC 5545, 3.1).
+const MAX_LINE_OCTETS: usize = 75;
+/// Режимы тренда короче этого срока считаются шумом и не дают отдельной смены.
+const MIN_REGIME_DAYS: usize = 14;
+/// Длина префикса хэша заголовка в UID пользовательского события.
+const EVENT_UID_HASH_LEN: usize = 12;
+
+/// Событие из конфигурации (`[[events]]`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CalendarEvent {
+    pub date: NaiveDate,
+    pub title: String,
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum EntryKind {
+    Changepoint,
+    Milestone,
+    ForecastEnd,
+}
+
+impl EntryKind {
+    const fn category(self) -> &'static str {
+        match self {
+            Self::Changepoint => "Смена тренда",
+            Self::Milestone => "Событие",
+            Self::ForecastEnd => "Прогноз",
+        }
+    }
+}
+
+/// Однодневное событие календаря.
+#[derive(Debug)]
+struct Entry {
+    kind: EntryKind,
+    date: NaiveDate,
+    uid: String,
+    summary: String,
+    description: String,
+}
+
+/// Направление сглаженного тренда с учётом 95% интервала.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TrendRegime {
+    Growth,
+    Decline,
+    Flat,
+}
+
+impl TrendRegime {
+    fn classify(lower: f64, upper: f64) -> Self {
+        if lower > 0.0 {
+            Self::Growth
+        } else if upper < 0.0 {
+            Self::Decline
+        } else {
+            Self::Flat
+        }
+    }
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Growth => "рост занятой площади",
+            Self::Decline => "сокращение занятой площади",
+            Self::Flat => "без значимых изменений",
+        }
+    }
+}
+
+/// Непрерывный участок одного режима: индексы `[start, end)` в сглаженном ряду.
+#[derive(Clone, Copy, Debug)]
+struct RegimeRun {
+    regime: TrendRegime,
+    start: usize,
+    end: usize,
+}
+
+impl RegimeRun {
+    const fn days(self) -> usize {
+        self.end - self.start
+    }
+
+    fn mean_trend(self, trend: &[f64]) -> f64 {
+        let values = &trend[self.start..self.end];
+        #[allow(clippy::cast_precision_loss)]
+        let count = values.len() as f64;
+        values.iter().sum::<f64>() / count
+    }
+}
+
+/// Собирает календарь и сохраняет его в `output_ics`.
+pub fn write_calendar(
+    forecast: &ForecastOverlay,
+    events: &[CalendarEvent],
+    generated_at: DateTime<Utc>,
+    output_ics: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = output_ics.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let entries = collect_entries(forecast, events)?;
+    fs::write(output_ics, render_calendar(&entries, generated_at))?;
+    Ok(())
+}
+
+fn collect_entries(
+    forecast: &ForecastOverlay,
+    events: &[CalendarEvent],
+) -> Result<Vec<Entry>, Box<dyn Error>> {
+    let mut entries = match &forecast.smoothed {
+        Some(smoothed) => changepoint_entries(smoothed)?,
+        None => Vec::new(),
+    };
+    entries.extend(events.iter().map(milestone_entry));
+    if let Some(entry) = forecast_end_entry(forecast)? {
+        entries.push(entry);
+    }
+    entries.sort_by_key(|entry| (entry.date, entry.kind));
+    Ok(entries)
+}
+
+/// Находит смены режима сглаженного тренда; короткие участки поглощаются предыдущим режимом.
+fn trend_regimes(smoothed: &SmoothedOverlay) -> Vec<RegimeRun> {
+    let mut runs: Vec<RegimeRun> = Vec::new();
+    for (idx, (lower, upper)) in smoothed
+        .trend_lower
+        .iter()
+        .zip(&smoothed.trend_upper)
+        .enumerate()
+    {
+        let regime = TrendRegime::classify(*lower, *upper);
+        match runs.last_mut() {
+            Some(run) if run.regime == regime => run.end = idx + 1,
+            _ => runs.push(RegimeRun {
+                regime,
+                start: idx,
+                end: idx + 1,
+            }),
+        }
+    }
+
+    let mut merged: Vec<RegimeRun> = Vec::with_capacity(runs.len());
+    for run in runs {
+        match merged.last_mut() {
+            Some(last) if last.regime == run.regime || run.days() < MIN_REGIME_DAYS => {
+                last.end = run.end;
+            }
+            _ => merged.push(run),
+        }
+    }
+    merged
+}
+
+fn changepoint_entries(smoothed: &SmoothedOverlay) -> Result<Vec<Entry>, Box<dyn Error>> {
+    let regimes = trend_regimes(smoothed);
+    regimes
+        .windows(2)
+        .map(|pair| {
+            let (previous, current) = (pair[0], pair[1]);
+            let date = parse_date(&smoothed.dates[current.start])?;
+            Ok(Entry {
+                kind: EntryKind::Changepoint,
+                date,
+                uid: format!("trend-{}@{UID_DOMAIN}", date.format("%Y%m%d")),
+                summary: format!("Смена тренда: {}", current.regime.label()),
+                description: format!(
+                    "Средний темп нового режима: {:+.1} км²/сутки за {} дн.\nДо этого: {}, {:+.1} км²/сутки за {} дн.",
+                    current.mean_trend(&smoothed.trend),
+                    current.days(),
+                    previous.regime.label(),
+                    previous.mean_trend(&smoothed.trend),
+                    previous.days()
+                ),
+            })
+        })
+        .collect()
+}
+
+fn milestone_entry(event: &CalendarEvent) -> Entry {
+    let hash = sha256_hex(event.title.as_bytes());
+    Entry {
+        kind: EntryKind::Milestone,
+        date: event.date,
+        uid: format!(
+            "event-{}-{}@{UID_DOMAIN}",
+            event.date.format("%Y%m%d"),
+            &hash[..EVENT_UID_HASH_LEN]
+        ),
+        summary: event.title.clone(),
+        description: event.description.clone().unwrap_or_default(),
+    }
+}
+
+/// Последний день прогноза; UID постоянный, чтобы календарь переносил событие, а не копил их.
+fn forecast_end_entry(forecast: &ForecastOverlay) -> Result<Option<Entry>, Box<dyn Error>> {
+    let (Some(date), Some(mean), Some(lower), Some(upper)) = (
+        forecast.dates.last(),
+        forecast.mean.last(),
+        forecast.lower.last(),
+        forecast.upper.last(),
+    ) else {
+        return Ok(None);
+    };
+    Ok(Some(Entry {
+        kind: EntryKind::ForecastEnd,
+        date: parse_date(date)?,
+        uid: format!("forecast-end@{UID_DOMAIN}"),
+        summary: "Конец горизонта прогноза".to_string(),
+        description: format!(
+            "Прогноз занятой площади: {mean:.1} тыс. км² (95% интервал {lower:.1}–{upper:.1})"
+        ),
+    }))
+}
+
+fn parse_date(raw: &str) -> Result<NaiveDate, Box<dyn Error>> {
+    NaiveDate::parse_from_str(raw, DATE_FORMAT)
+        .map_err(|err| format!("invalid date {raw:?}: {err}").into())
+}
+
+fn render_calendar(entries: &[Entry], generated_at: DateTime<Utc>) -> String {
+    let stamp = generated_at.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{PRODUCT_ID}"));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "METHOD:PUBLISH");
+    push_line(
+        &mut out,
+        &format!("X-WR-CALNAME:{}", escape_text(CALENDAR_NAME)),
+    );
+    for entry in entries {
+        let end = entry
+            .date
+            .checked_add_days(Days::new(1))
+            .unwrap_or(entry.date);
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", entry.uid));
+        push_line(&mut out, &format!("DTSTAMP:{stamp}"));
+        push_line(
+            &mut out,
+            &format!("DTSTART;VALUE=DATE:{}", entry.date.format("%Y%m%d")),
+        );
+        push_line(
+            &mut out,
+            &format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+        );
+        push_line(
+            &mut out,
+            &format!("SUMMARY:{}", escape_text(&entry.summary)),
+        );
+        if !entry.description.is_empty() {
+            push_line(
+                &mut out,
+                &format!("DESCRIPTION:{}", escape_text(&entry.description)),
+            );
+        }
+        push_line(
+            &mut out,
+            &format!("CATEGORIES:{}", escape_text(entry.kind.category())),
+        );
+        push_line(&mut out, "TRANSP:TRANSPARENT");
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Экранирует значение TEXT (RFC 5545, 3.3.11).
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Дописывает строку с CRLF, перенося её по границам символов UTF-8 каждые 75 октетов.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for ch in line.chars() {
+        let len = ch.len_utf8();
+        if width + len > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += len;
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Days, NaiveDate};
+
+    use super::{CalendarEvent, MAX_LINE_OCTETS, collect_entries, render_calendar};
+    use crate::{ForecastOverlay, SmoothedOverlay};
+
+    #[test]
+    fn calendar_lists_changepoints_events_and_forecast_end() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date");
+        // 40 дней роста, 3 дня шума, 40 дней сокращения.
+        let trend = (0..83)
+            .map(|day| match day {
+                0..40 => 20.0,
+                40..43 => 0.0,
+                _ => -15.0,
+            })
+            .collect::<Vec<f64>>();
+        let dates = (0..83)
+            .map(|day| {
+                start
+                    .checked_add_days(Days::new(day))
+                    .expect("valid date")
+                    .format("%Y-%m-%d")
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        let forecast = ForecastOverlay {
+            dates: vec!["2024-03-24".to_string(), "2024-03-25".to_string()],
+            mean: vec![110.0, 110.5],
+            lower: vec![109.0, 109.2],
+            upper: vec![111.0, 111.8],
+            granularity: crate::series::Granularity::Day,
+            smoothed: Some(SmoothedOverlay {
+                dates,
+                level: vec![100.0; 83],
+                lower: vec![99.0; 83],
+                upper: vec![101.0; 83],
+                trend_lower: trend.iter().map(|value| value - 5.0).collect(),
+                trend_upper: trend.iter().map(|value| value + 5.0).collect(),
+                trend,
+            }),
+            horizons: Vec::new(),
+            training: None,
+            diagnostics: None,
+            stale: None,
+        };
+        let events = [CalendarEvent {
+            date: NaiveDate::from_ymd_opt(2024, 2, 1).expect("valid date"),
+            title: "Переговоры; раунд 2, Стамбул".to_string(),
+            description: Some(
+                "Очень длинное описание события, которое не помещается в одну строку календаря"
+                    .to_string(),
+            ),
+        }];
+
+        let entries = collect_entries(&forecast, &events).expect("entries should build");
+        let calendar = render_calendar(
+            &entries,
+            DateTime::from_timestamp(1_700_000_000, 0).expect("valid timestamp"),
+        );
+
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 3);
+        assert!(calendar.contains("UID:trend-20240213@rua\r\n"));
+        assert!(calendar.contains("DTSTART;VALUE=DATE:20240213\r\n"));
+        let unfolded = calendar.replace("\r\n ", "");
+        assert!(unfolded.contains("SUMMARY:Смена тренда: сокращение занятой площади\r\n"));
+        assert!(calendar.contains("SUMMARY:Переговоры\\; раунд 2\\, Стамбул\r\n"));
+        assert!(calendar.contains("UID:forecast-end@rua\r\nDTSTAMP:20231114T221320Z\r\n"));
+        assert!(calendar.contains("DTEND;VALUE=DATE:20240326\r\n"));
+        assert!(
+            calendar
+                .split("\r\n")
+                .all(|line| line.len() <= MAX_LINE_OCTETS)
+        );
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+    }
+}
@@ -0,0 +1,365 @@
+//! Утилиты для построения графика динамики занятых территорий.
+//!
+//! Страницы и графики (plotly, maud, minify-html) собираются с фичей `render`. Без неё остаются
+//! настройки, календарь и книга Excel, а функции отрисовки возвращают ошибку.
+
+// Без `render` часть справочников и ядра нужна только отрисовке.
+#![cfg_attr(not(feature = "render"), allow(dead_code))]
+
+#[cfg(feature = "render")]
+mod assets;
+#[cfg(feature = "render")]
+mod chart;
+mod compare;
+mod comparison_sets;
+mod data;
+#[cfg(feature = "render")]
+pub mod exports;
+mod ical;
+#[cfg(feature = "render")]
+mod methodology;
+mod numbers;
+mod overlay;
+#[cfg(feature = "render")]
+mod page;
+mod page_config;
+mod raster;
+mod smoothing;
+#[cfg(feature = "render")]
+mod style;
+#[cfg(feature = "render")]
+mod svg;
+mod units;
+#[cfg(feature = "render")]
+mod updates;
+#[cfg(feature = "xlsx")]
+mod xlsx;
+
+use std::error::Error;
+#[cfg(feature = "render")]
+use std::fs;
+use std::path::Path;
+
+use rua_core::{constants, front, integrity, model, series};
+use rua_kernel as kernel;
+
+use crate::front::FrontShifts;
+use crate::series::{AreaBuckets, InterpolationStrategy};
+use chrono::{DateTime, Utc};
+use rua_core::methodology::Section;
+use rua_core::updates::BuildRecord;
+
+pub use compare::ComparisonSource;
+pub use comparison_sets::{ComparisonSet, builtin_keys as comparison_set_keys};
+pub use ical::CalendarEvent;
+pub use numbers::NumberLocale;
+pub use overlay::{
+    ChartRenderConfig, ChartSummary, ForecastOverlay, Milestone, ModelDiagnostics, Phase,
+    ReferenceLevel, ReferenceLine, SmoothedOverlay, StaleForecast,
+};
+pub use page_config::{
+    FontStrategy, PageConfig, RegionOccupation, ReportFeatures, region_area, region_names,
+};
+pub use raster::render_line_png;
+pub use smoothing::{SmoothingMethod, SmoothingSpec};
+pub use units::AreaUnit;
+
+const DEFAULT_HISTORY_CSV_LINK: &str = "history.csv";
+const DEFAULT_FORECAST_CSV_LINK: &str = "forecast.csv";
+const SOURCE_DATE_EPOCH_ENV: &str = "SOURCE_DATE_EPOCH";
+#[cfg(not(feature = "render"))]
+const RENDER_DISABLED: &str = "rua was built without the `report` feature";
+
+#[derive(Clone, Debug)]
+pub struct DownloadLinks {
+    pub history: String,
+    pub forecast: String,
+}
+
+impl Default for DownloadLinks {
+    fn default() -> Self {
+        Self {
+            history: DEFAULT_HISTORY_CSV_LINK.to_string(),
+            forecast: DEFAULT_FORECAST_CSV_LINK.to_string(),
+        }
+    }
+}
+
+/// Необязательные разделы страницы, данные для которых загружаются отдельно от истории,
+/// и необязательные выгрузки графика.
+#[derive(Clone, Copy, Default)]
+pub struct ExtraSections<'a> {
+    /// Второй источник для вкладки сравнения.
+    pub comparison: Option<&'a ComparisonSource>,
+    /// Смещение линии фронта по направлениям.
+    pub front: Option<&'a FrontShifts>,
+    /// Куда выгрузить фигуру основного графика (`figure.json`).
+    pub figure_json: Option<&'a Path>,
+    /// Куда выгрузить соотношения с территориями из таблиц (`ratios.json`).
+    pub ratios_json: Option<&'a Path>,
+}
+
+#[cfg(feature = "render")]
+pub fn draw_area_chart_with_forecast_from_buckets_and_config(
+    buckets: &AreaBuckets,
+    output_html: &Path,
+    forecast: Option<&ForecastOverlay>,
+    render_config: &ChartRenderConfig,
+    download_links: Option<DownloadLinks>,
+    minify_html: bool,
+    extra: ExtraSections<'_>,
+) -> Result<ChartSummary, Box<dyn Error>> {
+    let forecast = forecast.filter(|_| render_config.page.features.forecast);
+    let comparison = extra
+        .comparison
+        .map(|source| chart::build_comparison_chart(buckets, source, render_config))
+        .transpose()?;
+    let mut chart =
+        chart::build_area_chart_from_buckets_with_config(buckets, forecast, render_config)?;
+    chart.comparison = comparison;
+    chart.front = extra.front.map(chart::build_front_chart);
+    if let Some(output_json) = extra.figure_json {
+        exports::write_figure_json(&chart.main_plot, render_config.page.precision, output_json)?;
+    }
+    if let Some(output_json) = extra.ratios_json {
+        exports::write_ratios_json(&chart.summary, &render_config.page, output_json)?;
+    }
+    render_plot(
+        &chart,
+        &render_config.page,
+        output_html,
+        download_links,
+        minify_html,
+    )?;
+    Ok(chart.summary)
+}
+
+/// Заглушка для сборки без фичи `render`.
+#[cfg(not(feature = "render"))]
+pub fn draw_area_chart_with_forecast_from_buckets_and_config(
+    _buckets: &AreaBuckets,
+    _output_html: &Path,
+    _forecast: Option<&ForecastOverlay>,
+    _render_config: &ChartRenderConfig,
+    _download_links: Option<DownloadLinks>,
+    _minify_html: bool,
+    _extra: ExtraSections<'_>,
+) -> Result<ChartSummary, Box<dyn Error>> {
+    Err(RENDER_DISABLED.into())
+}
+
+/// Выгружает дневной ряд, прогноз, итоги по годам и соотношения в книгу Excel.
+#[cfg(feature = "xlsx")]
+pub fn write_workbook(
+    buckets: &AreaBuckets,
+    forecast: Option<&ForecastOverlay>,
+    interpolation: InterpolationStrategy,
+    smoothing: SmoothingSpec,
+    output_xlsx: &Path,
+) -> Result<(), Box<dyn Error>> {
+    xlsx::write_workbook(buckets, forecast, interpolation, smoothing, output_xlsx)
+}
+
+/// Заглушка для сборки без фичи `xlsx`.
+#[cfg(not(feature = "xlsx"))]
+pub fn write_workbook(
+    _buckets: &AreaBuckets,
+    _forecast: Option<&ForecastOverlay>,
+    _interpolation: InterpolationStrategy,
+    _smoothing: SmoothingSpec,
+    _output_xlsx: &Path,
+) -> Result<(), Box<dyn Error>> {
+    Err("rua was built without the `xlsx` feature".into())
+}
+
+/// Сохраняет календарь `.ics`: смены тренда по `forecast.smoothed`, события `events`
+/// и последний день прогноза.
+pub fn write_calendar(
+    forecast: &ForecastOverlay,
+    events: &[CalendarEvent],
+    output_ics: &Path,
+) -> Result<(), Box<dyn Error>> {
+    ical::write_calendar(forecast, events, generated_at(), output_ics)
+}
+
+/// Сохраняет страницу журнала обновлений; `home_href` ведёт обратно на основной график,
+/// счётчик посещений и локаль чисел берутся из настроек основной страницы.
+#[cfg(feature = "render")]
+pub fn write_updates_page(
+    builds: &[BuildRecord],
+    home_href: &str,
+    page: &PageConfig,
+    output_html: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = output_html.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(
+        output_html,
+        updates::render_updates_page(builds, home_href, page, generated_at()),
+    )?;
+    Ok(())
+}
+
+/// Заглушка для сборки без фичи `render`.
+#[cfg(not(feature = "render"))]
+pub fn write_updates_page(
+    _builds: &[BuildRecord],
+    _home_href: &str,
+    _page: &PageConfig,
+    _output_html: &Path,
+) -> Result<(), Box<dyn Error>> {
+    Err(RENDER_DISABLED.into())
+}
+
+/// Сохраняет страницу «Методология»; `csv_href` ведёт на CSV с теми же параметрами.
+#[cfg(feature = "render")]
+pub fn write_methodology_page(
+    sections: &[Section],
+    home_href: &str,
+    csv_href: &str,
+    page: &PageConfig,
+    output_html: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = output_html.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(
+        output_html,
+        methodology::render_methodology_page(sections, home_href, csv_href, page, generated_at()),
+    )?;
+    Ok(())
+}
+
+/// Заглушка для сборки без фичи `render`.
+#[cfg(not(feature = "render"))]
+pub fn write_methodology_page(
+    _sections: &[Section],
+    _home_href: &str,
+    _csv_href: &str,
+    _page: &PageConfig,
+    _output_html: &Path,
+) -> Result<(), Box<dyn Error>> {
+    Err(RENDER_DISABLED.into())
+}
+
+/// Время сборки страницы; `SOURCE_DATE_EPOCH` фиксирует его для воспроизводимых сборок.
+pub fn generated_at() -> DateTime<Utc> {
+    std::env::var(SOURCE_DATE_EPOCH_ENV)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<i64>().ok())
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .unwrap_or_else(Utc::now)
+}
+
+#[cfg(feature = "render")]
+fn render_plot(
+    chart: &chart::ChartOutput,
+    branding: &PageConfig,
+    output_html: &Path,
+    download_links: Option<DownloadLinks>,
+    minify_html: bool,
+) -> Result<(), Box<dyn Error>> {
+    // Создаём директорию для HTML, если её ещё нет.
+    if let Some(parent) = output_html.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let generated_at = generated_at();
+    let links = download_links.unwrap_or_default();
+    let page = page::render_plot_page(chart, branding, generated_at, &links);
+    let output_dir = output_html.parent().unwrap_or_else(|| Path::new(""));
+    assets::write_assets(output_dir, &page::page_assets(branding))?;
+    if minify_html {
+        let cfg = minify_html::Cfg::new();
+        let minified = minify_html::minify(page.as_bytes(), &cfg);
+        fs::write(output_html, minified)?;
+    } else {
+        fs::write(output_html, page)?;
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "render"))]
+mod tests {
+    use super::{
+        ChartRenderConfig, ExtraSections, ForecastOverlay, ReportFeatures,
+        draw_area_chart_with_forecast_from_buckets_and_config,
+    };
+    use crate::series::{Granularity, load_area_buckets};
+
+    const HISTORY_CSV: &str = "time_index,hash,area,percent,area_type\n\
+2024-05-01 00:00:00 UTC,#a52714,100.0,0.0,occupied_after_24_02_2022\n\
+2024-05-02 00:00:00 UTC,#a52714,120.0,0.0,occupied_after_24_02_2022\n\
+2024-05-03 00:00:00 UTC,#a52714,140.0,0.0,occupied_after_24_02_2022\n";
+
+    /// Страница с заданными флагами `[page.features]`, без минификации.
+    fn render_with(features: ReportFeatures) -> String {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let csv = temp.path().join("history.csv");
+        std::fs::write(&csv, HISTORY_CSV).expect("history should be written");
+        let buckets = load_area_buckets(&csv).expect("history should load");
+        let overlay = ForecastOverlay {
+            dates: vec!["2024-05-04".to_string(), "2024-05-05".to_string()],
+            mean: vec![0.15, 0.16],
+            lower: vec![0.14, 0.15],
+            upper: vec![0.16, 0.17],
+            granularity: Granularity::Day,
+            smoothed: None,
+            horizons: Vec::new(),
+            training: None,
+            diagnostics: None,
+            stale: None,
+        };
+        let mut config = ChartRenderConfig::default();
+        config.page.features = features;
+        let output = temp.path().join("index.html");
+        draw_area_chart_with_forecast_from_buckets_and_config(
+            &buckets,
+            &output,
+            Some(&overlay),
+            &config,
+            None,
+            false,
+            ExtraSections::default(),
+        )
+        .expect("page should render");
+        std::fs::read_to_string(&output).expect("page should be readable")
+    }
+
+    #[test]
+    fn disabled_features_drop_their_sections_and_forecast_traces() {
+        let markers = [
+            "yoy-plot",
+            "Соотношение к территориям недружественных стран",
+            "deepstatemap.live",
+            "forecast-2",
+        ];
+        let full = render_with(ReportFeatures::default());
+        for marker in markers {
+            assert!(
+                full.contains(marker),
+                "default page should contain {marker}"
+            );
+        }
+
+        let bare = render_with(ReportFeatures {
+            forecast: false,
+            comparison_tables: false,
+            change_panel: false,
+            map_link: false,
+        });
+        for marker in markers {
+            assert!(
+                !bare.contains(marker),
+                "page without features contains {marker}"
+            );
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Страница `methodology.html`: приложение с параметрами расчёта этой сборки.
+
+use chrono::{DateTime, Utc};
+use maud::{DOCTYPE, PreEscaped, html};
+
+use super::page_config::PageConfig;
+use super::style;
+use rua_core::methodology::Section;
+
+const PAGE_TITLE: &str = "Методология расчёта";
+const PAGE_SUBTITLE: &str = "Параметры конвейера в этой сборке: собраны из кода и конфига, \
+                             поэтому совпадают с тем, что посчитано на графике.";
+const BUILT_AT_FORMAT: &str = "%Y-%m-%d %H:%M UTC";
+const STYLE_PLACEHOLDER: &str = "/*rua:methodology-css*/";
+
+pub fn render_methodology_page(
+    sections: &[Section],
+    home_href: &str,
+    csv_href: &str,
+    page_config: &PageConfig,
+    generated_at: DateTime<Utc>,
+) -> String {
+    let page = html! {
+        (DOCTYPE)
+        html lang="ru" {
+            head {
+                meta charset="utf-8";
+                meta name="viewport" content="width=device-width, initial-scale=1";
+                title { (PAGE_TITLE) }
+                style { (PreEscaped(STYLE_PLACEHOLDER)) }
+                @if let Some(analytics_html) = &page_config.analytics_html {
+                    (PreEscaped(analytics_html))
+                }
+            }
+            body {
+                div class="page" {
+                    header class="hero" {
+                        div {
+                            h1 class="title" { (PAGE_TITLE) }
+                            p class="subtitle" { (PAGE_SUBTITLE) }
+                        }
+                    }
+                    @for section in sections {
+                        section class="table-card" {
+                            h2 class="table-title" { (section.title) }
+                            p class="ratio-note" { (section.summary) }
+                            table class="ratio-table" {
+                                thead {
+                                    tr {
+                                        th { "Параметр" }
+                                        th { "Значение" }
+                                        th { "Что делает" }
+                                    }
+                                }
+                                tbody {
+                                    @for parameter in &section.parameters {
+                                        tr {
+                                            td { code { (parameter.name) } }
+                                            td { (parameter.value) }
+                                            td { (parameter.description) }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    footer {
+                        "Сгенерировано: " (generated_at.format(BUILT_AT_FORMAT)) " · "
+                        a href=(csv_href) download { "CSV" } " · "
+                        a href=(home_href) { "К графику" }
+                    }
+                }
+            }
+        }
+    };
+    let page = page.into_string();
+    let styles = style::page_styles(&page);
+    page.replacen(
+        STYLE_PLACEHOLDER,
+        &format!("{}{}", styles.critical, styles.deferred),
+        1,
+    )
+}
@@ -0,0 +1,120 @@
+//! Числа для людей: десятичная запятая для русского и украинского, тонкий неразрывный
+//! пробел между разрядами. CSV и JSON по-прежнему пишутся с точкой через
+//! [`crate::constants::Precision`], чтобы их читали программы.
+
+use serde::Deserialize;
+
+/// Узкий неразрывный пробел: разделяет разряды и не переносит число на новую строку.
+const THIN_SPACE: char = '\u{202f}';
+
+/// Локаль чисел страницы (`[page].locale`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberLocale {
+    #[default]
+    Ru,
+    Uk,
+    En,
+}
+
+impl NumberLocale {
+    pub const fn decimal_separator(self) -> char {
+        match self {
+            Self::Ru | Self::Uk => ',',
+            Self::En => '.',
+        }
+    }
+
+    pub const fn group_separator(self) -> char {
+        match self {
+            Self::Ru | Self::Uk => THIN_SPACE,
+            Self::En => ',',
+        }
+    }
+
+    /// С какой длины целой части разряды разделяются: по русской типографике
+    /// четырёхзначные числа пишутся слитно.
+    pub const fn min_grouped_digits(self) -> usize {
+        match self {
+            Self::Ru | Self::Uk => 5,
+            Self::En => 4,
+        }
+    }
+
+    /// `116 320,5`
+    pub fn format(self, value: f64, decimals: usize) -> String {
+        self.render(value, decimals, false, true)
+    }
+
+    /// `+1 250` и `-3`
+    pub fn format_signed(self, value: f64, decimals: usize) -> String {
+        self.render(value, decimals, true, true)
+    }
+
+    /// Без разделителей разрядов, чтобы число из таблицы копировалось как есть.
+    pub fn format_plain(self, value: f64, decimals: usize, signed: bool) -> String {
+        self.render(value, decimals, signed, false)
+    }
+
+    /// Значение `layout.separators` Plotly: десятичный разделитель, затем разделитель разрядов.
+    pub fn plotly_separators(self) -> String {
+        [self.decimal_separator(), self.group_separator()]
+            .iter()
+            .collect()
+    }
+
+    fn render(self, value: f64, decimals: usize, signed: bool, grouped: bool) -> String {
+        let raw = if signed {
+            format!("{value:+.decimals$}")
+        } else {
+            format!("{value:.decimals$}")
+        };
+        // `NaN` и бесконечности выводятся как есть.
+        let Some(start) = raw.find(|ch: char| ch.is_ascii_digit()) else {
+            return raw;
+        };
+        let (sign, digits) = raw.split_at(start);
+        let (integer, fraction) = digits
+            .split_once('.')
+            .map_or((digits, None), |(integer, fraction)| {
+                (integer, Some(fraction))
+            });
+        let mut out = String::from(sign);
+        if grouped && integer.len() >= self.min_grouped_digits() {
+            for (idx, digit) in integer.chars().enumerate() {
+                if idx > 0 && (integer.len() - idx) % 3 == 0 {
+                    out.push(self.group_separator());
+                }
+                out.push(digit);
+            }
+        } else {
+            out.push_str(integer);
+        }
+        if let Some(fraction) = fraction {
+            out.push(self.decimal_separator());
+            out.push_str(fraction);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NumberLocale;
+
+    #[test]
+    fn locales_place_separators_like_their_typography() {
+        assert_eq!(NumberLocale::Ru.format(108.4, 1), "108,4");
+        assert_eq!(NumberLocale::Ru.format(1234.0, 0), "1234");
+        assert_eq!(NumberLocale::Uk.format(116_320.46, 1), "116\u{202f}320,5");
+        assert_eq!(
+            NumberLocale::Ru.format_signed(-12_345.0, 0),
+            "-12\u{202f}345"
+        );
+        assert_eq!(NumberLocale::Ru.format_signed(7.0, 0), "+7");
+        assert_eq!(NumberLocale::En.format(1234.5, 1), "1,234.5");
+        assert_eq!(NumberLocale::Ru.format_plain(116_320.0, 0, false), "116320");
+        assert_eq!(NumberLocale::Ru.format(f64::NAN, 1), "NaN");
+        assert_eq!(NumberLocale::Ru.plotly_separators(), ",\u{202f}");
+    }
+}
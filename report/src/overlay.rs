@@ -0,0 +1,341 @@
+//! Данные и настройки графика без отрисовки: прогноз и сглаживание поверх истории, параметры
+//! графика и сводка для страницы. Модуль не зависит от plotly и maud, поэтому типы доступны и
+//! в сборке без фичи `render`.
+
+use chrono::NaiveDate;
+
+use super::page_config::PageConfig;
+use super::smoothing::SmoothingSpec;
+use crate::constants::DATE_FORMAT;
+use crate::model::{Diagnostics, TrainingSpan};
+use crate::series::{Granularity, InterpolationStrategy};
+use rua_core::methodology::{Parameter, Section};
+
+const MAX_PLOT_POINTS: usize = 900;
+const DEFAULT_TABLE_DAYS: usize = 90;
+const DEFAULT_AVG_CHANGE_START: (i32, u32, u32) = (2022, 11, 23);
+const DEFAULT_GRAY_ZONE_START: (i32, u32, u32) = (2023, 2, 5);
+const MISSING_VALUE: &str = "—";
+
+#[derive(Clone, Debug)]
+pub struct ForecastOverlay {
+    pub dates: Vec<String>,
+    pub mean: Vec<f64>,
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+    /// Шаг точек прогноза; недельные точки стоят в середине своего 7-дневного блока.
+    pub granularity: Granularity,
+    /// Сглаженная траектория модели по истории (если рассчитана).
+    pub smoothed: Option<SmoothedOverlay>,
+    /// Прогнозы той же модели на другие горизонты: скрыты, пока их не выберут на странице.
+    pub horizons: Vec<Self>,
+    /// Границы ряда, на котором обучена модель (нет в старых CSV прогноза).
+    pub training: Option<TrainingSpan>,
+    /// Диагностика модели для раздела страницы (если рассчитана).
+    pub diagnostics: Option<ModelDiagnostics>,
+    /// Прогноз прошлой сборки, если модель на свежих данных не обучилась.
+    pub stale: Option<StaleForecast>,
+}
+
+/// Прогноз прошлой сборки, показанный вместо неудавшегося обучения модели.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StaleForecast {
+    /// Последний день истории, на котором обучен показанный прогноз.
+    pub trained_to: NaiveDate,
+    /// Сколько дней истории появилось после него.
+    pub age_days: i64,
+}
+
+impl ForecastOverlay {
+    /// Горизонт в днях: недельные точки покрывают по 7 дней.
+    pub const fn horizon_days(&self) -> usize {
+        self.dates.len() * self.granularity.step_days()
+    }
+
+    /// Группа трасс и подписи прогноза на графике; по ней их переключает страница.
+    pub fn group(&self) -> String {
+        format!("forecast-{}", self.horizon_days())
+    }
+
+    pub fn summary(&self) -> Option<ForecastSummary> {
+        Some(ForecastSummary {
+            horizon_days: self.horizon_days(),
+            group: self.group(),
+            end_date: self.dates.last()?.clone(),
+            mean_km2: *self.mean.last()?,
+            lower_km2: *self.lower.last()?,
+            upper_km2: *self.upper.last()?,
+            training: self.training.map(|span| {
+                (
+                    span.from.format(DATE_FORMAT).to_string(),
+                    span.to.format(DATE_FORMAT).to_string(),
+                )
+            }),
+            stale: self.stale.map(|stale| {
+                format!(
+                    "Прогноз прошлой сборки: обучен по {}, {} дн. назад — модель не обучилась на свежих данных",
+                    stale.trained_to.format(DATE_FORMAT),
+                    stale.age_days
+                )
+            }),
+        })
+    }
+}
+
+/// Проверка остатков модели и её выбранные гиперпараметры.
+#[derive(Clone, Debug)]
+pub struct ModelDiagnostics {
+    pub model: String,
+    pub stats: Diagnostics,
+    /// Пары «параметр — значение» в порядке вывода.
+    pub hyperparameters: Vec<(String, String)>,
+}
+
+/// Сглаженный уровень с 95% интервалом (в тыс. км²) и темп изменения (в км²/сутки).
+#[derive(Clone, Debug)]
+pub struct SmoothedOverlay {
+    pub dates: Vec<String>,
+    pub level: Vec<f64>,
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+    pub trend: Vec<f64>,
+    pub trend_lower: Vec<f64>,
+    pub trend_upper: Vec<f64>,
+}
+
+/// Горизонтальная опорная линия основного графика.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReferenceLine {
+    pub label: String,
+    pub level: ReferenceLevel,
+}
+
+/// Уровень опорной линии.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReferenceLevel {
+    /// Занятая площадь на указанную дату (берётся из ряда).
+    AsOf(NaiveDate),
+    /// Фиксированная площадь, км².
+    AreaKm2(f64),
+}
+
+/// Рубеж карточки «При текущем темпе»: занятая площадь, до которой считается срок.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Milestone {
+    pub label: String,
+    /// Целевая занятая площадь, км².
+    pub area_km2: f64,
+}
+
+/// Этап войны: фон основного графика от `start` до `end` и строка таблицы «Фазы».
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Phase {
+    pub label: String,
+    pub start: NaiveDate,
+    /// Последний день этапа; `None` — этап продолжается до конца ряда.
+    pub end: Option<NaiveDate>,
+}
+
+/// Параметры временных порогов для отрисовки графиков.
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ChartRenderConfig {
+    /// Дата начала отображения «Серой зоны» (верхний и нижний графики).
+    pub gray_zone_start: NaiveDate,
+    /// Дата начала отображения «Ср. изменения» (красная линия и YoY-блок).
+    pub avg_change_start: NaiveDate,
+    /// Прореживать ли длинные ряды перед отрисовкой (min/max по окнам).
+    pub downsample: bool,
+    /// Рисовать фактическую линию по отдельным срезам API (ось X — дата и время).
+    pub intraday: bool,
+    /// Заполнение дней без срезов; `None` рвёт фактическую линию на пропусках.
+    pub interpolation: InterpolationStrategy,
+    /// Рисовать сглаженную траекторию модели, если она есть в прогнозе.
+    pub smoothed: bool,
+    /// Рисовать темп изменения по модели с 95% интервалом на нижней панели.
+    pub trend: bool,
+    /// Сглаживание суточного изменения на нижней панели.
+    pub change_smoothing: SmoothingSpec,
+    /// Раздел диагностики модели под графиками.
+    pub diagnostics: bool,
+    /// Горизонтальные опорные линии на основном графике.
+    pub reference_lines: Vec<ReferenceLine>,
+    /// Сколько последних дней показать в таблице под графиком (`0` — без таблицы).
+    pub table_days: usize,
+    /// Базовая дата вида «Индекс» основного графика; без неё — `avg_change_start`.
+    pub index_date: Option<NaiveDate>,
+    /// Рубежи карточки «При текущем темпе»; пустой список — без карточки.
+    pub milestones: Vec<Milestone>,
+    /// Этапы на фоне основного графика и в таблице «Фазы»; пустой список — без них.
+    pub phases: Vec<Phase>,
+    /// Оформление HTML-страницы.
+    pub page: PageConfig,
+}
+
+impl ChartRenderConfig {
+    /// Предел числа точек на линии: без прореживания ряды передаются целиком.
+    pub const fn max_plot_points(&self) -> usize {
+        if self.downsample {
+            MAX_PLOT_POINTS
+        } else {
+            usize::MAX
+        }
+    }
+}
+
+impl Default for ChartRenderConfig {
+    fn default() -> Self {
+        Self {
+            gray_zone_start: NaiveDate::from_ymd_opt(
+                DEFAULT_GRAY_ZONE_START.0,
+                DEFAULT_GRAY_ZONE_START.1,
+                DEFAULT_GRAY_ZONE_START.2,
+            )
+            .expect("DEFAULT_GRAY_ZONE_START must be valid"),
+            avg_change_start: NaiveDate::from_ymd_opt(
+                DEFAULT_AVG_CHANGE_START.0,
+                DEFAULT_AVG_CHANGE_START.1,
+                DEFAULT_AVG_CHANGE_START.2,
+            )
+            .expect("DEFAULT_AVG_CHANGE_START must be valid"),
+            downsample: true,
+            intraday: false,
+            interpolation: InterpolationStrategy::default(),
+            smoothed: false,
+            trend: false,
+            diagnostics: false,
+            change_smoothing: SmoothingSpec::default(),
+            reference_lines: Vec::new(),
+            table_days: DEFAULT_TABLE_DAYS,
+            index_date: None,
+            milestones: Vec::new(),
+            phases: Vec::new(),
+            page: PageConfig::default(),
+        }
+    }
+}
+
+impl ChartRenderConfig {
+    /// Что и как рисует график, для приложения «Методология».
+    pub fn methodology(&self) -> Section {
+        let smoothing = self.change_smoothing;
+        Section {
+            title: "График",
+            summary: "Основной график показывает дневной ряд и прогноз, нижняя панель — \
+                      суточное изменение занятой площади со сглаживанием.",
+            parameters: vec![
+                Parameter::new(
+                    "change_smoothing",
+                    format!(
+                        "{}, окно {} дн., не меньше {} значений",
+                        smoothing.method.key(),
+                        smoothing.window,
+                        smoothing.min_periods
+                    ),
+                    "Сглаживание суточного изменения на нижней панели",
+                ),
+                Parameter::new(
+                    "avg_change_start",
+                    self.avg_change_start,
+                    "С этой даты считаются «Ср. изменение» и сравнение год-к-году",
+                ),
+                Parameter::new(
+                    "gray_zone_start",
+                    self.gray_zone_start,
+                    "С этой даты показывается «Серая зона»",
+                ),
+                Parameter::new(
+                    "downsample",
+                    if self.downsample {
+                        format!("до {MAX_PLOT_POINTS} точек")
+                    } else {
+                        "нет".to_string()
+                    },
+                    "Длинные ряды прореживаются по минимумам и максимумам окон",
+                ),
+                Parameter::new(
+                    "intraday",
+                    self.intraday,
+                    "Фактическая линия по отдельным срезам без дневного усреднения",
+                ),
+            ],
+        }
+    }
+}
+
+/// Сводные метрики для HTML-страницы (единицы указаны в комментариях).
+#[derive(Clone, Debug)]
+pub struct ChartSummary {
+    /// Дата последнего доступного среза (YYYY-MM-DD).
+    pub latest_date: String,
+    /// Текущая площадь в тыс. км².
+    pub latest_area_km2: f64,
+    /// Доля от площади Украины (в процентах).
+    pub ukraine_percent: f64,
+    /// Изменение за сутки в км² (может отсутствовать при коротком ряде).
+    pub daily_change_km2: Option<f64>,
+    /// Изменение за 7 дней в км² (может отсутствовать при коротком ряде).
+    pub weekly_change_km2: Option<f64>,
+    /// Сводка по прогнозу (если он передан).
+    pub forecast: Option<ForecastSummary>,
+    /// Сводки прогнозов на другие горизонты, от коротких к длинным.
+    pub forecast_horizons: Vec<ForecastSummary>,
+    /// Исторический максимум и минимум ряда (отмечены на графике).
+    pub extremes: Option<SeriesExtremes>,
+}
+
+impl ChartSummary {
+    /// Подставляет показатели в текстовый шаблон с плейсхолдерами
+    /// `{date}`, `{area}`, `{percent}`, `{daily}`, `{weekly}`, `{forecast}`.
+    pub fn render_template(&self, template: &str) -> String {
+        let format_change = |value: Option<f64>| {
+            value.map_or_else(|| MISSING_VALUE.to_string(), |val| format!("{val:+.0}"))
+        };
+        let forecast_mean = self.forecast.as_ref().map_or_else(
+            || MISSING_VALUE.to_string(),
+            |forecast| format!("{:.1}", forecast.mean_km2),
+        );
+        template
+            .replace("{date}", &self.latest_date)
+            .replace("{area}", &format!("{:.1}", self.latest_area_km2))
+            .replace("{percent}", &format!("{:.2}", self.ukraine_percent))
+            .replace("{daily}", &format_change(self.daily_change_km2))
+            .replace("{weekly}", &format_change(self.weekly_change_km2))
+            .replace("{forecast}", &forecast_mean)
+    }
+}
+
+/// Краткая сводка по прогнозу (в тыс. км²).
+#[derive(Clone, Debug)]
+pub struct ForecastSummary {
+    pub horizon_days: usize,
+    /// Группа трасс прогноза на графике ([`ForecastOverlay::group`]).
+    pub group: String,
+    pub end_date: String,
+    pub mean_km2: f64,
+    pub lower_km2: f64,
+    pub upper_km2: f64,
+    /// Период обучения модели: с, по (YYYY-MM-DD).
+    pub training: Option<(String, String)>,
+    /// Пометка о возрасте, если показан прогноз прошлой сборки.
+    pub stale: Option<String>,
+}
+
+/// День рекордного уровня площади.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesExtreme {
+    /// Дата (YYYY-MM-DD).
+    pub date: String,
+    pub area_km2: f64,
+}
+
+/// Максимум за весь ряд и минимум после начала «Ср. изменения»: до него площадь
+/// росла скачками первых месяцев войны, и минимум всего ряда ничего не говорит.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesExtremes {
+    pub max: SeriesExtreme,
+    /// `None`, если ряд закончился раньше `min_since`.
+    pub min: Option<SeriesExtreme>,
+    /// С какой даты ищется минимум (YYYY-MM-DD).
+    pub min_since: String,
+}
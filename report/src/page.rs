@@ -0,0 +1,1171 @@
+//! Рендер HTML-страницы с Plotly-графиком. Страница собирается из частей (`head`, `hero`,
+//! сводка, карточка графика, таблицы, подвал); поведение — в скриптах `assets/*.js`.
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use maud::{DOCTYPE, Markup, PreEscaped, html};
+use plotly::Plot;
+use serde::Serialize;
+
+use super::DownloadLinks;
+use super::assets::{self, Asset};
+use super::chart::{
+    ChangeInput, ChartOutput, ComparisonOutput, MilestoneEta, MilestonePace, PhaseStats,
+    SeriesTableRow,
+};
+use super::comparison_sets::{ComparisonEntry, ComparisonSet};
+use super::exports;
+use super::numbers::NumberLocale;
+use super::overlay::{ChartSummary, ForecastSummary, ModelDiagnostics, SeriesExtremes};
+use super::page_config::{GOOGLE_FONTS_CSS, PageConfig, ReportFeatures, region_area};
+use super::smoothing::SmoothingMethod;
+use super::style;
+use super::units::AreaUnit;
+use crate::constants::{self, AREA_THOUSANDS_DIVISOR, DATE_FORMAT};
+
+const GENERATED_AT_FORMAT: &str = "%Y-%m-%d %H:%M UTC";
+const PLOTLY_CDN: &str = "https://cdn.plot.ly/plotly-2.35.2.min.js";
+const SOURCE_MAP_URL: &str = "https://deepstatemap.live";
+const SERIES_IMPUTED_TITLE: &str = "Нет среза за день, значение интерполировано";
+const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+const DAYS_PER_YEAR: f64 = 365.25;
+const MILESTONE_DATE_FORMAT: &str = "%m.%Y";
+
+/// Метки, на место которых после рендера подставляется CSS, отобранный под разметку страницы.
+const CRITICAL_STYLE_PLACEHOLDER: &str = "/*rua:critical-css*/";
+const DEFERRED_STYLE_PLACEHOLDER: &str = "/*rua:deferred-css*/";
+
+/// Скрипты, на которые ссылается страница; `render_plot` пишет их в `assets/` рядом с HTML.
+pub fn page_assets(branding: &PageConfig) -> Vec<Asset> {
+    let mut page_assets = vec![assets::PAGE_SCRIPT];
+    if branding.analytics_events {
+        page_assets.push(assets::ANALYTICS_SCRIPT);
+    }
+    page_assets
+}
+
+pub fn render_plot_page(
+    chart: &ChartOutput,
+    branding: &PageConfig,
+    generated_at: DateTime<Utc>,
+    download_links: &DownloadLinks,
+) -> String {
+    let features = branding.features;
+    let generated = generated_time(generated_at);
+    let page = html! {
+        (DOCTYPE)
+        html lang="ru" {
+            (head(branding))
+            body {
+                div class="page" {
+                    (hero(branding))
+                    (summary_section(&chart.summary, branding, &generated, download_links))
+                    (chart_card(chart, branding))
+                    @if features.change_panel {
+                        div class="card" {
+                            h2 class="card-title" { "Год-к-году: среднее суточное изменение" }
+                            (lazy_plot("yoy-plot", &chart.yoy_plot, branding))
+                        }
+                    }
+                    @if let Some(front) = &chart.front {
+                        div class="card" {
+                            h2 class="card-title" { "Смещение линии фронта по направлениям" }
+                            (lazy_plot("front-plot", &front.plot, branding))
+                            p class="ratio-note" {
+                                "Между картами " (front.before) " и " (front.after) ". "
+                                "Положение фронта — первый выход из занятой территории на отрезке "
+                                "направления; плюс — продвижение, минус — отход."
+                            }
+                        }
+                    }
+                    @if let Some(diagnostics) = &chart.diagnostics {
+                        (diagnostics_card(diagnostics, branding))
+                    }
+                    @if let Some(pace) = &chart.milestones {
+                        (milestone_card(pace, branding))
+                    }
+                    @if !chart.phases.is_empty() {
+                        (phase_table(&chart.phases, branding))
+                    }
+                    @if features.comparison_tables {
+                        (ratio_tables(&chart.summary, branding))
+                    }
+                    @if !branding.regions.is_empty() {
+                        (region_table(branding))
+                    }
+                    (footer(&generated, features))
+                }
+                style { (PreEscaped(DEFERRED_STYLE_PLACEHOLDER)) }
+            }
+        }
+    };
+    let page = page.into_string();
+    let styles = style::page_styles(&page);
+    page.replacen(CRITICAL_STYLE_PLACEHOLDER, &styles.critical, 1)
+        .replacen(DEFERRED_STYLE_PLACEHOLDER, &styles.deferred, 1)
+}
+
+/// `<head>`: описание для поисковиков и соцсетей, шрифты, скрипты и критический CSS.
+/// Скрипты подключаются с `defer`, поэтому выполняются по порядку после Plotly.
+fn head(branding: &PageConfig) -> Markup {
+    html! {
+        head {
+            meta charset="utf-8";
+            meta name="viewport" content="width=device-width, initial-scale=1";
+            meta name="description" content=(branding.description);
+            meta name="keywords" content=(branding.keywords);
+            link rel="canonical" href=(branding.site_url);
+            link rel="icon" href=(branding.favicon);
+            meta property="og:title" content=(branding.title);
+            meta property="og:description" content=(branding.description);
+            meta property="og:type" content="website";
+            meta property="og:url" content=(branding.site_url);
+            meta property="og:site_name" content=(branding.site_name);
+            meta name="twitter:card" content="summary";
+            meta name="twitter:title" content=(branding.title);
+            meta name="twitter:description" content=(branding.description);
+            title { (branding.title) }
+            @if let Some(fonts) = branding.fonts_stylesheet() {
+                @if fonts == GOOGLE_FONTS_CSS {
+                    link rel="preconnect" href="https://fonts.googleapis.com";
+                    link rel="preconnect" href="https://fonts.gstatic.com" crossorigin;
+                }
+                link rel="stylesheet" href=(fonts);
+            }
+            script src=(PLOTLY_CDN) defer {}
+            script src=(assets::PAGE_SCRIPT.href()) defer {}
+            @if branding.analytics_events {
+                script
+                    src=(assets::ANALYTICS_SCRIPT.href())
+                    data-endpoint=[branding.analytics_endpoint.as_deref()]
+                    defer {}
+            }
+            style { (PreEscaped(CRITICAL_STYLE_PLACEHOLDER)) }
+            @if let Some(analytics_html) = &branding.analytics_html {
+                (PreEscaped(analytics_html))
+            }
+        }
+    }
+}
+
+/// Шапка: заголовок, подзаголовок и ссылка на репозиторий.
+fn hero(branding: &PageConfig) -> Markup {
+    html! {
+        header class="hero" {
+            div {
+                h1 class="title" { (branding.title) }
+                p class="subtitle" {
+                    (branding.subtitle)
+                }
+            }
+            @if let Some(repo_url) = &branding.repo_url {
+                div class="hero-aside" {
+                    a class="link" href=(repo_url) aria-label="Репозиторий" {
+                        svg viewBox="0 0 24 24" aria-hidden="true" focusable="false" {
+                            path fill="currentColor" d="M12 .5C5.65.5.5 5.8.5 12.3c0 5.2 3.4 9.6 8.1 11.1.6.1.8-.3.8-.6v-2.1c-3.3.7-4-1.6-4-1.6-.5-1.3-1.3-1.7-1.3-1.7-1.1-.8.1-.8.1-.8 1.2.1 1.9 1.3 1.9 1.3 1.1 1.9 2.9 1.3 3.6 1 .1-.8.4-1.3.7-1.6-2.7-.3-5.5-1.4-5.5-6 0-1.3.5-2.3 1.2-3.2-.1-.3-.5-1.5.1-3.1 0 0 1-.3 3.3 1.2 1-.3 2-.4 3-.4s2 .1 3 .4c2.3-1.5 3.3-1.2 3.3-1.2.6 1.6.2 2.8.1 3.1.8.9 1.2 2 1.2 3.2 0 4.6-2.8 5.6-5.5 5.9.4.4.8 1.1.8 2.2v3.3c0 .3.2.7.8.6 4.7-1.5 8.1-5.9 8.1-11.1C23.5 5.8 18.4.5 12 .5z" {}
+                        }
+                        (branding.repo_text)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Карточки сводки и кнопка «Скопировать сводку».
+fn summary_section(
+    summary: &ChartSummary,
+    branding: &PageConfig,
+    generated: &Markup,
+    download_links: &DownloadLinks,
+) -> Markup {
+    let ukraine_percent_label = format!("{}%", branding.locale.format(summary.ukraine_percent, 2));
+    let track = branding.analytics_events;
+    html! {
+        section class="summary" {
+            div class="summary-grid" {
+                div class="summary-card" {
+                    div class="summary-label" { "Текущая площадь" }
+                    div class="summary-value" id="summary-area" {
+                        (area_text(
+                            Some(summary.latest_area_km2 * AREA_THOUSANDS_DIVISOR),
+                            branding,
+                            AreaFormat::LEVEL,
+                        ))
+                    }
+                    div class="summary-sub" {
+                        "Доля от Украины: " span id="summary-percent" { (ukraine_percent_label) }
+                    }
+                }
+                div class="summary-card" {
+                    div class="summary-label" { "Изменения" }
+                    div class="summary-value" {
+                        span id="summary-daily" {
+                            (area_text(summary.daily_change_km2, branding, AreaFormat::CHANGE))
+                        }
+                        " за сутки"
+                    }
+                    div class="summary-sub" {
+                        span id="summary-weekly" {
+                            (area_text(summary.weekly_change_km2, branding, AreaFormat::CHANGE))
+                        }
+                        " за 7 дней"
+                    }
+                }
+                @if let Some(extremes) = &summary.extremes {
+                    (extremes_card(extremes, branding))
+                }
+                div class="summary-card" {
+                    div class="summary-label" {
+                        "Последний срез"
+                        (download_icon(&download_links.history, track))
+                    }
+                    div class="summary-value" id="summary-date" { (summary.latest_date) }
+                    div class="summary-sub" { "Сгенерировано: " (generated) }
+                }
+                @if let Some(forecast) = &summary.forecast {
+                    (forecast_card(forecast, &summary.forecast_horizons, branding, download_links))
+                } @else {
+                    div class="summary-card" {
+                        div class="summary-label" { "Прогноз" }
+                        div class="summary-value" { "Нет данных" }
+                        div class="summary-sub" { "Запустите с командой forecast" }
+                    }
+                }
+            }
+            div class="summary-actions" {
+                button type="button" class="summary-copy"
+                    data-copy=(build_share_text(summary, branding)) hidden {
+                    "Скопировать сводку"
+                }
+                span class="summary-copy-status" aria-live="polite" {}
+                (area_unit_toggle(branding))
+            }
+        }
+    }
+}
+
+fn forecast_card(
+    forecast: &ForecastSummary,
+    horizons: &[ForecastSummary],
+    branding: &PageConfig,
+    download_links: &DownloadLinks,
+) -> Markup {
+    let level = |thousands: f64, format| {
+        area_text(Some(thousands * AREA_THOUSANDS_DIVISOR), branding, format)
+    };
+    html! {
+        div class="summary-card" {
+            div class="summary-label" {
+                "Прогноз"
+                (download_icon(&download_links.forecast, branding.analytics_events))
+            }
+            div class="summary-value" id="summary-forecast-mean" {
+                (level(forecast.mean_km2, AreaFormat::LEVEL))
+            }
+            div class="summary-sub" {
+                span id="summary-forecast-title" { (forecast_title(forecast)) }
+                " · 95%: "
+                span id="summary-forecast-lower" { (level(forecast.lower_km2, AreaFormat::LEVEL_BARE)) }
+                "–"
+                span id="summary-forecast-upper" { (level(forecast.upper_km2, AreaFormat::LEVEL)) }
+                " · до " span id="summary-forecast-end" { (forecast.end_date) }
+            }
+            @if let Some((from, to)) = &forecast.training {
+                div class="summary-sub" id="summary-forecast-training" {
+                    "Обучение: " (from) " — " (to)
+                }
+            }
+            @if let Some(stale) = &forecast.stale {
+                div class="summary-sub summary-stale" id="summary-forecast-stale" { (stale) }
+            }
+            @if !horizons.is_empty() {
+                (forecast_horizon_toggle(forecast, horizons))
+            }
+        }
+    }
+}
+
+fn forecast_title(forecast: &ForecastSummary) -> String {
+    format!("Через {} дн.", forecast.horizon_days)
+}
+
+/// Переключатель горизонтов прогноза одной модели: скрипт страницы показывает трассы
+/// выбранного горизонта и переписывает карточку по данным варианта. Без скрипта скрыт.
+fn forecast_horizon_toggle(primary: &ForecastSummary, others: &[ForecastSummary]) -> Markup {
+    let mut horizons = std::iter::once(primary).chain(others).collect::<Vec<_>>();
+    horizons.sort_by_key(|forecast| forecast.horizon_days);
+    html! {
+        label class="forecast-horizon" hidden {
+            "Горизонт: "
+            select id="forecast-horizon" {
+                @for forecast in horizons {
+                    option
+                        value=(forecast.group)
+                        selected[forecast.group == primary.group]
+                        data-title=(forecast_title(forecast))
+                        data-end=(forecast.end_date)
+                        data-mean-km2=(forecast.mean_km2 * AREA_THOUSANDS_DIVISOR)
+                        data-lower-km2=(forecast.lower_km2 * AREA_THOUSANDS_DIVISOR)
+                        data-upper-km2=(forecast.upper_km2 * AREA_THOUSANDS_DIVISOR) {
+                        (forecast.horizon_days) " дн."
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Исторический максимум и минимум после начала «Ср. изменения» — те же точки, что
+/// отмечены на графике.
+fn extremes_card(extremes: &SeriesExtremes, branding: &PageConfig) -> Markup {
+    html! {
+        div class="summary-card" {
+            div class="summary-label" { "Максимум" }
+            div class="summary-value" {
+                (area_text(Some(extremes.max.area_km2), branding, AreaFormat::LEVEL))
+            }
+            div class="summary-sub" {
+                (extremes.max.date)
+                @if let Some(min) = &extremes.min {
+                    " · минимум с " (extremes.min_since) ": "
+                    (area_text(Some(min.area_km2), branding, AreaFormat::LEVEL))
+                    " (" (min.date) ")"
+                }
+            }
+        }
+    }
+}
+
+/// Переключатель единиц площади; без скрипта скрыт. Пересчёт по данным вариантов
+/// делает скрипт страницы, графики остаются в единице из конфигурации.
+fn area_unit_toggle(branding: &PageConfig) -> Markup {
+    let locale = branding.locale;
+    html! {
+        label class="area-unit" hidden {
+            "Единицы: "
+            select id="area-unit"
+                data-decimal=(locale.decimal_separator())
+                data-group=(locale.group_separator())
+                data-group-min=(locale.min_grouped_digits()) {
+                @for unit in AreaUnit::ALL {
+                    option
+                        value=(unit.key())
+                        selected[unit == branding.area_unit]
+                        data-km2=(unit.km2_per_unit())
+                        data-decimals=(unit.decimals())
+                        data-label=(unit.label())
+                        data-change=(unit.change_unit().key()) {
+                        (unit.label())
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Ось основного графика: площадь в единице из конфигурации или доля площади Украины.
+/// Трассы пересчитывает скрипт страницы; без скрипта скрыт.
+fn area_axis_toggle(branding: &PageConfig) -> Markup {
+    let unit = branding.area_unit;
+    html! {
+        label class="area-axis" hidden {
+            "Ось: "
+            select id="area-axis"
+                data-percent-per-unit=(constants::ukraine_percent(unit.km2_per_unit()))
+                data-unit-label=(unit.label()) {
+                option value="unit" selected { (unit.label()) }
+                option value="percent" { "% Украины" }
+            }
+        }
+    }
+}
+
+/// Как показать площадь: уровень или изменение, со знаком, с подписью единицы и
+/// с разделителями разрядов.
+#[derive(Clone, Copy)]
+#[allow(clippy::struct_excessive_bools)]
+struct AreaFormat {
+    /// Изменения показываются в [`AreaUnit::change_unit`].
+    change: bool,
+    signed: bool,
+    labelled: bool,
+    grouped: bool,
+}
+
+impl AreaFormat {
+    const LEVEL: Self = Self {
+        change: false,
+        signed: false,
+        labelled: true,
+        grouped: true,
+    };
+    const LEVEL_BARE: Self = Self {
+        labelled: false,
+        ..Self::LEVEL
+    };
+    const CHANGE: Self = Self {
+        change: true,
+        signed: true,
+        labelled: true,
+        grouped: true,
+    };
+    /// Ячейки таблицы ряда: единица указана в заголовке столбца, а число без разделителей
+    /// разрядов, чтобы копировалось как есть.
+    const TABLE_VALUE: Self = Self {
+        change: true,
+        signed: false,
+        labelled: false,
+        grouped: false,
+    };
+    const TABLE_CHANGE: Self = Self {
+        signed: true,
+        ..Self::TABLE_VALUE
+    };
+
+    fn render(self, km2: Option<f64>, unit: AreaUnit, locale: NumberLocale) -> String {
+        let unit = if self.change {
+            unit.change_unit()
+        } else {
+            unit
+        };
+        let Some(km2) = km2 else {
+            return "—".to_string();
+        };
+        let value = unit.of_km2(km2);
+        let number = match (self.grouped, self.signed) {
+            (true, true) => locale.format_signed(value, unit.decimals()),
+            (true, false) => locale.format(value, unit.decimals()),
+            (false, signed) => locale.format_plain(value, unit.decimals(), signed),
+        };
+        if self.labelled {
+            format!("{number} {}", unit.label())
+        } else {
+            number
+        }
+    }
+}
+
+/// Площадь в единице страницы; `data-area-km2` хранит исходное значение для переключателя.
+fn area_text(km2: Option<f64>, branding: &PageConfig, format: AreaFormat) -> Markup {
+    html! {
+        span
+            data-area-km2=(km2.map_or_else(String::new, |km2| km2.to_string()))
+            data-area=(if format.change { "change" } else { "level" })
+            data-area-signed[format.signed]
+            data-area-bare[!format.labelled]
+            data-area-plain[!format.grouped] {
+            (format.render(km2, branding.area_unit, branding.locale))
+        }
+    }
+}
+
+/// Подпись единицы в заголовке таблицы, которую тоже меняет переключатель.
+fn area_unit_label(unit: AreaUnit) -> Markup {
+    html! {
+        span data-area-unit="change" { (unit.change_unit().label()) }
+    }
+}
+
+/// Значок скачивания CSV в подписи карточки; `track` размечает ссылку для событий аналитики.
+fn download_icon(href: &str, track: bool) -> Markup {
+    let label = format!("Скачать {href}");
+    html! {
+        a class="summary-label-icon"
+            href=(href)
+            data-track=[track.then_some(href)]
+            download
+            aria-label=(&label)
+            title=(&label) {
+            svg viewBox="0 0 24 24" aria-hidden="true" focusable="false" {
+                path
+                    fill="currentColor"
+                    d="M12 3v10.17l3.59-3.58L17 11l-5 5-5-5 1.41-1.41L11 13.17V3h1zm-7 14h14v2H5v-2z" {}
+            }
+        }
+    }
+}
+
+/// Карточка основного графика с вкладками и таблицей ряда.
+fn chart_card(chart: &ChartOutput, branding: &PageConfig) -> Markup {
+    let comparison = chart.comparison.as_ref();
+    html! {
+        div class="card" {
+            div class="chart-tabs" role="tablist" {
+                button type="button" class="chart-tab" role="tab"
+                    aria-selected="true" data-chart-tab="area-panel" { "Динамика" }
+                button type="button" class="chart-tab" role="tab"
+                    aria-selected="false" data-chart-tab="category-panel" { "По категориям" }
+                button type="button" class="chart-tab" role="tab"
+                    aria-selected="false" data-chart-tab="flows-panel" { "Занято и потеряно" }
+                @if comparison.is_some() {
+                    button type="button" class="chart-tab" role="tab"
+                        aria-selected="false" data-chart-tab="compare-panel" { "Сравнение источников" }
+                }
+            }
+            div id="area-panel" role="tabpanel" {
+                div class="series-badges" {
+                    span class="badge actual" { "Факт" }
+                    @if chart.summary.forecast.is_some() {
+                        span class="badge forecast" { "Прогноз" }
+                    }
+                    (area_axis_toggle(branding))
+                }
+                @if let (Some(url), Some(input)) = (&branding.wasm_url, &chart.change_input) {
+                    (smoothing_controls(url, input))
+                }
+                (lazy_plot("area-plot", &chart.main_plot, branding))
+                @if let Some(svg) = &chart.static_svg {
+                    noscript id="static-chart-fallback" {
+                        div class="static-chart" { (PreEscaped(svg)) }
+                        p class="static-chart-note" {
+                            "Упрощённая версия графика: для интерактивного режима включите JavaScript."
+                        }
+                    }
+                }
+                @if !chart.table.is_empty() {
+                    (series_table(&chart.table, branding))
+                }
+            }
+            div id="category-panel" role="tabpanel" hidden {
+                (lazy_plot("category-plot", &chart.category_plot, branding))
+            }
+            div id="flows-panel" role="tabpanel" hidden {
+                (lazy_plot("flows-plot", &chart.flows_plot, branding))
+            }
+            @if let Some(comparison) = comparison {
+                div id="compare-panel" role="tabpanel" hidden {
+                    div class="summary-grid" {
+                        @for (label, value, sub) in comparison_cards(comparison, branding) {
+                            div class="summary-card" {
+                                div class="summary-label" { (label) }
+                                div class="summary-value" { (value) }
+                                div class="summary-sub" { (sub) }
+                            }
+                        }
+                    }
+                    (lazy_plot("compare-plot", &comparison.plot, branding))
+                }
+            }
+        }
+    }
+}
+
+/// Переключатель сглаживания нижней панели; скрыт, пока скрипт не загрузит WASM-ядро.
+fn smoothing_controls(url: &str, input: &ChangeInput) -> Markup {
+    let data = serde_json::json!({
+        "dates": input.dates,
+        "changes": input.changes,
+        "baseline": input.baseline,
+        "min_periods": input.smoothing.min_periods,
+        "max_points": input.max_points,
+    })
+    .to_string()
+    .replace("</", "<\\/");
+    html! {
+        div class="smoothing-controls" data-wasm=(url) hidden {
+            label {
+                "Сглаживание изменения "
+                select id="change-smoothing-method" {
+                    @for method in SmoothingMethod::ALL {
+                        option value=(method.key()) selected[method == input.smoothing.method] {
+                            (method.label())
+                        }
+                    }
+                }
+            }
+            label {
+                "окно, дней "
+                input id="change-smoothing-window" type="number" min="1" max="90"
+                    value=(input.smoothing.window);
+            }
+            script type="application/json" id="change-input" { (PreEscaped(data)) }
+        }
+    }
+}
+
+/// Таблица последних дней ряда под графиком; постраничный вывод включает скрипт страницы.
+fn series_table(rows: &[SeriesTableRow], branding: &PageConfig) -> Markup {
+    html! {
+        details class="series-details" {
+            summary { "Показать таблицей" }
+            div class="series-table-scroll" role="region" tabindex="0"
+                aria-label="Занятая площадь по дням" {
+                table class="ratio-table series-table" {
+                    thead {
+                        tr {
+                            th scope="col" { "Дата" }
+                            th scope="col" { "Площадь, " (area_unit_label(branding.area_unit)) }
+                            th scope="col" { "За сутки, " (area_unit_label(branding.area_unit)) }
+                        }
+                    }
+                    tbody {
+                        @for row in rows {
+                            tr {
+                                td {
+                                    (row.date.format(DATE_FORMAT))
+                                    @if !row.measured {
+                                        span class="series-imputed" title=(SERIES_IMPUTED_TITLE) { "*" }
+                                    }
+                                }
+                                td { (area_text(row.area_km2, branding, AreaFormat::TABLE_VALUE)) }
+                                td { (area_text(row.change_km2, branding, AreaFormat::TABLE_CHANGE)) }
+                            }
+                        }
+                    }
+                }
+            }
+            div class="series-pager" hidden {
+                button type="button" data-page="prev" { "← Новее" }
+                span class="series-page-status" aria-live="polite" {}
+                button type="button" data-page="next" { "Старее →" }
+            }
+            @if rows.iter().any(|row| !row.measured) {
+                p class="ratio-note" { "* " (SERIES_IMPUTED_TITLE) "." }
+            }
+        }
+    }
+}
+
+/// Максимум таблиц соотношений рядом; остальные наборы выбираются переключателем.
+const VISIBLE_RATIO_TABLES: usize = 2;
+
+/// Таблицы соотношений площади с наборами территорий из `[page].comparison_sets`.
+fn ratio_tables(summary: &ChartSummary, branding: &PageConfig) -> Markup {
+    let tables = ratio_sets(summary, branding);
+    let switchable = tables.len() > VISIBLE_RATIO_TABLES;
+    let flags = branding.flag_base_url.as_str();
+    html! {
+        section class="table-card" {
+            h2 class="table-title" { "Соотношение к территориям недружественных стран" }
+            div class="table-controls" {
+                span { "Сортировка:" }
+                select id="ratio-sort" {
+                    option value="ratio" selected { "по соотношению" }
+                    option value="name" { "по имени" }
+                }
+            }
+            div class="table-grid" {
+                @for table in tables.iter().take(VISIBLE_RATIO_TABLES) {
+                    (ratio_table(table, switchable.then_some(tables.as_slice()), flags))
+                }
+            }
+            @if switchable {
+                // Строки всех наборов: переключатель перестраивает таблицу без запроса к серверу.
+                script type="application/json" id="ratio-sets" data-flag-base=(flags) {
+                    (PreEscaped(serde_json::to_string(&tables).unwrap_or_default().replace("</", "<\\/")))
+                }
+            }
+            p class="ratio-note" {
+                "Соотношение рассчитано по последнему значению графика. "
+                "Прогноз — по границам 95% интервала на конец горизонта, среднее — в подсказке."
+            }
+        }
+    }
+}
+
+/// Строки таблиц соотношений по наборам `[page].comparison_sets`; из них же собирается
+/// `ratios.json`.
+pub fn ratio_sets<'a>(summary: &ChartSummary, branding: &'a PageConfig) -> Vec<RatioSet<'a>> {
+    let latest_area_sq_km = summary.latest_area_km2 * AREA_THOUSANDS_DIVISOR;
+    let forecast_area_sq_km = summary.forecast.as_ref().map(|forecast| ForecastArea {
+        mean: forecast.mean_km2 * AREA_THOUSANDS_DIVISOR,
+        lower: forecast.lower_km2 * AREA_THOUSANDS_DIVISOR,
+        upper: forecast.upper_km2 * AREA_THOUSANDS_DIVISOR,
+    });
+    branding
+        .comparison_sets
+        .iter()
+        .map(|set| RatioSet {
+            key: &set.key,
+            title: &set.title,
+            name_column: &set.name_column,
+            rows: build_country_rows(set, latest_area_sq_km, forecast_area_sq_km, branding.locale),
+        })
+        .collect()
+}
+
+/// Сроки до рубежей при неизменном сглаженном темпе — с оговоркой, что это не прогноз.
+fn milestone_card(pace: &MilestonePace, branding: &PageConfig) -> Markup {
+    html! {
+        section class="table-card" id="milestones" {
+            h2 class="table-title" { "При текущем темпе" }
+            p class="ratio-note" {
+                "Темп на " (pace.as_of.format(DATE_FORMAT)) ": "
+                (area_text(Some(pace.pace_km2_per_day), branding, AreaFormat::CHANGE))
+                " в сутки — последнее значение сглаженного суточного изменения с нижней панели."
+            }
+            table class="ratio-table" {
+                thead { tr { th { "Рубеж" } th { "Осталось" } th { "Срок" } } }
+                tbody {
+                    @for eta in &pace.etas {
+                        tr {
+                            td {
+                                (eta.label) " ("
+                                (area_text(Some(eta.target_km2), branding, AreaFormat::LEVEL)) ")"
+                            }
+                            td { (area_text(Some(eta.remaining_km2), branding, AreaFormat::CHANGE)) }
+                            td { (milestone_eta_text(eta, pace.as_of, branding)) }
+                        }
+                    }
+                }
+            }
+            p class="ratio-note" {
+                "Это наивная линейная экстраполяция, а не прогноз: темп считается неизменным, "
+                "хотя за время войны он менялся в разы и даже менял знак. "
+                "Прогноз модели с интервалом — в карточке «Прогноз»."
+            }
+        }
+    }
+}
+
+/// Итоги этапов с фона основного графика: изменение площади и средний суточный темп.
+fn phase_table(phases: &[PhaseStats], branding: &PageConfig) -> Markup {
+    html! {
+        section class="table-card" id="phases" {
+            h2 class="table-title" { "Фазы" }
+            table class="ratio-table" {
+                thead {
+                    tr { th { "Этап" } th { "Период" } th { "Изменение" } th { "Ср. темп в сутки" } }
+                }
+                tbody {
+                    @for phase in phases {
+                        tr {
+                            td { (phase.label) }
+                            td { (phase.start.format(DATE_FORMAT)) " — " (phase.end.format(DATE_FORMAT)) }
+                            td { (area_text(Some(phase.net_change_km2), branding, AreaFormat::CHANGE)) }
+                            td { (area_text(phase.mean_rate_km2_per_day, branding, AreaFormat::CHANGE)) }
+                        }
+                    }
+                }
+            }
+            p class="ratio-note" {
+                "Границы этапа сдвинуты к ближайшим дням загруженного ряда; "
+                "темп — изменение за этап, делённое на число дней в нём."
+            }
+        }
+    }
+}
+
+fn milestone_eta_text(eta: &MilestoneEta, from: NaiveDate, branding: &PageConfig) -> String {
+    let Some(days) = eta.days else {
+        return if eta.remaining_km2 > 0.0 {
+            "Не при текущем темпе".to_string()
+        } else {
+            "Уже пройден".to_string()
+        };
+    };
+    let duration = if days < DAYS_PER_YEAR {
+        format!("≈ {} дн.", branding.locale.format(days.ceil(), 0))
+    } else {
+        format!("≈ {} года", branding.locale.format(days / DAYS_PER_YEAR, 1))
+    };
+    match eta.reached_on(from) {
+        Some(date) => format!("{duration} (к {})", date.format(MILESTONE_DATE_FORMAT)),
+        None => duration,
+    }
+}
+
+/// Раздел для проверки модели: стандартизованные инновации против белого шума N(0, 1).
+fn diagnostics_card(diagnostics: &ModelDiagnostics, branding: &PageConfig) -> Markup {
+    let stats = &diagnostics.stats;
+    let number = |value: f64, decimals| branding.locale.format(value, decimals);
+    let ljung_box = &stats.ljung_box;
+    let verdict = if ljung_box.p_value >= 0.05 {
+        "значимой автокорреляции остатков нет"
+    } else {
+        "остатки автокоррелированы — модель недоучитывает динамику, интервалы могут быть узки"
+    };
+    let observations = f64::from(u32::try_from(stats.observations).unwrap_or(u32::MAX));
+    let acf_band = 1.96 / observations.sqrt();
+    let summary = [
+        ("Наблюдений", stats.observations.to_string()),
+        ("NLL", number(stats.nll, 2)),
+        ("σ уровня, км²", number(stats.sigma_level, 2)),
+        ("σ тренда, км²", number(stats.sigma_trend, 2)),
+        ("σ наблюдений, км²", number(stats.sigma_obs, 2)),
+        (
+            "Льюнг — Бокс",
+            format!(
+                "Q({}) = {}, p = {}",
+                ljung_box.lags,
+                number(ljung_box.statistic, 2),
+                number(ljung_box.p_value, 3)
+            ),
+        ),
+        ("Асимметрия", number(stats.qq.skewness, 2)),
+        ("Эксцесс", number(stats.qq.excess_kurtosis, 2)),
+        (
+            "За пределами ±1,96",
+            format!("{}% (норма 5%)", number(stats.qq.outside_95 * 100.0, 1)),
+        ),
+    ];
+    html! {
+        section class="table-card" id="model-diagnostics" {
+            h2 class="table-title" { "Диагностика модели" }
+            p class="ratio-note" {
+                "Модель " (diagnostics.model) ". Стандартизованные ошибки прогноза на шаг вперёд; "
+                "у верной модели это независимый шум N(0, 1). По тесту Льюнга — Бокса " (verdict) "."
+            }
+            table class="ratio-table" {
+                tbody {
+                    @for (label, value) in &summary {
+                        tr { td { (label) } td { (value) } }
+                    }
+                }
+            }
+            table class="ratio-table" {
+                thead { tr { th { "Лаг" } th { "Автокорреляция" } } }
+                tbody {
+                    @for (lag, rho) in stats.acf.iter().enumerate() {
+                        tr {
+                            td { (lag + 1) }
+                            td {
+                                (number(*rho, 3))
+                                @if rho.abs() > acf_band { " *" }
+                            }
+                        }
+                    }
+                }
+            }
+            p class="ratio-note" {
+                "* — за пределами ±" (number(acf_band, 3)) ", 95% границы для белого шума."
+            }
+            table class="ratio-table" {
+                thead { tr { th { "Квантиль" } th { "N(0, 1)" } th { "Остатки" } } }
+                tbody {
+                    @for (level, normal, sample) in &stats.qq.quantiles {
+                        tr {
+                            td { (number(level * 100.0, 1)) "%" }
+                            td { (number(*normal, 2)) }
+                            td { (number(*sample, 2)) }
+                        }
+                    }
+                }
+            }
+            @if !diagnostics.hyperparameters.is_empty() {
+                table class="ratio-table" {
+                    thead { tr { th { "Гиперпараметр" } th { "Значение" } } }
+                    tbody {
+                        @for (name, value) in &diagnostics.hyperparameters {
+                            tr { td { (name) } td { (value) } }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Доля занятой территории каждой области с полосой заполнения, от большей к меньшей.
+fn region_table(branding: &PageConfig) -> Markup {
+    let mut rows = branding
+        .regions
+        .iter()
+        .filter_map(|region| {
+            let area = region_area(&region.name)?;
+            Some((
+                region,
+                area,
+                (region.occupied_km2 / area * 100.0).clamp(0.0, 100.0),
+            ))
+        })
+        .collect::<Vec<_>>();
+    rows.sort_by(|a, b| b.2.total_cmp(&a.2));
+    html! {
+        section class="table-card" {
+            h2 class="table-title" { "Доля занятой территории по областям" }
+            table class="ratio-table region-table" {
+                thead {
+                    tr {
+                        th { "Область" }
+                        th { "Занято" }
+                        th { "Площадь" }
+                        th { "Доля" }
+                        th { "На дату" }
+                    }
+                }
+                tbody {
+                    @for (region, area, percent) in rows {
+                        tr {
+                            td { (region.name) }
+                            td { (area_text(Some(region.occupied_km2), branding, AreaFormat::LEVEL)) }
+                            td { (area_text(Some(area), branding, AreaFormat::LEVEL)) }
+                            td class="region-share" {
+                                span class="share-bar"
+                                    role="progressbar"
+                                    aria-valuemin="0"
+                                    aria-valuemax="100"
+                                    aria-valuenow=(format!("{percent:.1}")) {
+                                    span class="share-bar-fill" style=(format!("width: {percent:.1}%")) {}
+                                }
+                                span class="share-value" { (branding.locale.format(percent, 1)) "%" }
+                            }
+                            td { time datetime=(region.as_of.format(DATE_FORMAT)) { (region.as_of.format(DATE_FORMAT)) } }
+                        }
+                    }
+                }
+            }
+            p class="ratio-note" {
+                "Занятая площадь по областям задаётся в конфигурации сборки на дату в последнем "
+                "столбце и может отставать от последнего значения графика."
+            }
+        }
+    }
+}
+
+/// Таблица одного набора; с `choices` в заголовке появляется переключатель наборов.
+fn ratio_table(table: &RatioSet, choices: Option<&[RatioSet]>, flag_base_url: &str) -> Markup {
+    html! {
+        div class="table-box" {
+            h3 {
+                span class="ratio-set-title" { (table.title) }
+                @if let Some(choices) = choices {
+                    select class="ratio-set" aria-label="Набор территорий" hidden {
+                        @for choice in choices {
+                            option value=(choice.key) selected[choice.key == table.key] {
+                                (choice.title)
+                            }
+                        }
+                    }
+                }
+            }
+            table class="ratio-table" data-table=(table.key) {
+                thead {
+                    tr {
+                        th class="ratio-name-column" { (table.name_column) }
+                        th { "Соотношение" }
+                        th { "Прогноз" }
+                    }
+                }
+                tbody {
+                    @for row in &table.rows {
+                        tr class="ratio-row" data-name=(row.name) data-ratio=(row.ratio_value) {
+                            td {
+                                @if let Some(flag) = &row.flag {
+                                    img
+                                        class="flag"
+                                        loading="lazy"
+                                        alt=(format!("Флаг {}", row.name))
+                                        src=(format!("{flag_base_url}{flag}.png")) {}
+                                }
+                                (row.name)
+                            }
+                            td { (&row.ratio) }
+                            td class="ratio-forecast" title=[row.forecast_mean.as_deref()] { (&row.forecast_ratio) }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Время сборки в UTC; скрипт страницы переписывает его в часовом поясе читателя.
+fn generated_time(generated_at: DateTime<Utc>) -> Markup {
+    let label = generated_at.format(GENERATED_AT_FORMAT).to_string();
+    let iso = generated_at.to_rfc3339_opts(SecondsFormat::Secs, true);
+    html! {
+        time datetime=(iso) data-generated-at=(iso) title=(label) { (label) }
+    }
+}
+
+fn footer(generated: &Markup, features: ReportFeatures) -> Markup {
+    html! {
+        footer {
+            "Версия: " (APP_VERSION) " · Сгенерировано: " (generated) " · RUA"
+            @if features.map_link {
+                " · Источник: "
+                a href=(SOURCE_MAP_URL) { "deepstatemap.live" }
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct CountryRow {
+    pub name: String,
+    pub flag: Option<String>,
+    ratio: String,
+    pub ratio_value: f64,
+    /// Диапазон соотношения по 95% интервалу прогноза.
+    forecast_ratio: String,
+    forecast_mean: Option<String>,
+    /// Площадь территории, км²; в JSON переключателя не нужна.
+    #[serde(skip)]
+    pub area_km2: f64,
+    /// Соотношения по прогнозу числами, для `ratios.json`.
+    #[serde(skip)]
+    pub forecast_values: Option<RatioForecast>,
+}
+
+/// Соотношения площади на конец горизонта прогноза: среднее и 95% интервал.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct RatioForecast {
+    pub mean: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Готовые строки набора; он же уходит в JSON переключателя.
+#[derive(Serialize)]
+pub struct RatioSet<'a> {
+    pub key: &'a str,
+    pub title: &'a str,
+    name_column: &'a str,
+    pub rows: Vec<CountryRow>,
+}
+
+/// Прогноз площади на конец горизонта, км².
+#[derive(Clone, Copy)]
+struct ForecastArea {
+    mean: f64,
+    lower: f64,
+    upper: f64,
+}
+
+fn build_country_rows(
+    set: &ComparisonSet,
+    latest_area_sq_km: f64,
+    forecast: Option<ForecastArea>,
+    locale: NumberLocale,
+) -> Vec<CountryRow> {
+    set.entries
+        .iter()
+        .map(|entry| country_row(entry, latest_area_sq_km, forecast, locale))
+        .collect()
+}
+
+fn country_row(
+    entry: &ComparisonEntry,
+    latest_area_sq_km: f64,
+    forecast: Option<ForecastArea>,
+    locale: NumberLocale,
+) -> CountryRow {
+    let area = entry.area_km2;
+    let ratio_value = latest_area_sq_km / area;
+    let ratio = |value: f64| locale.format(value, 2);
+    CountryRow {
+        name: entry.name.clone(),
+        flag: entry.flag.clone(),
+        ratio: format!("{}x", ratio(ratio_value)),
+        ratio_value,
+        forecast_ratio: forecast.map_or_else(
+            || "—".to_string(),
+            |forecast| {
+                format!(
+                    "{}–{}x",
+                    ratio(forecast.lower / area),
+                    ratio(forecast.upper / area)
+                )
+            },
+        ),
+        forecast_mean: forecast
+            .map(|forecast| format!("Среднее: {}x", ratio(forecast.mean / area))),
+        area_km2: area,
+        forecast_values: forecast.map(|forecast| RatioForecast {
+            mean: forecast.mean / area,
+            lower: forecast.lower / area,
+            upper: forecast.upper / area,
+        }),
+    }
+}
+
+/// Контейнер графика со скелетоном и данными фигуры; сам график строит скрипт ленивой загрузки.
+fn lazy_plot(id: &str, plot: &Plot, branding: &PageConfig) -> Markup {
+    // `</` внутри JSON закрыл бы тег `<script>` раньше времени.
+    let figure = exports::figure_json(plot, branding.precision).replace("</", "<\\/");
+    html! {
+        div id=(id) class="plotly-graph-div plot-skeleton" role="img" aria-label="Загрузка графика…" {}
+        script type="application/json" id=(format!("{id}-figure")) { (PreEscaped(figure)) }
+    }
+}
+
+/// Карточки сводки сравнения: заголовок, значение и пояснение.
+fn comparison_cards(
+    comparison: &ComparisonOutput,
+    branding: &PageConfig,
+) -> Vec<(String, Markup, String)> {
+    let stats = &comparison.stats;
+    let format_correlation = |value: Option<f64>| {
+        value.map_or_else(|| "—".to_string(), |value| branding.locale.format(value, 3))
+    };
+    vec![
+        (
+            "Общие дни".to_string(),
+            html! { (stats.overlap_days) },
+            format!("{} и {}", comparison.primary_label, comparison.label),
+        ),
+        (
+            "Корреляция".to_string(),
+            html! { (format_correlation(stats.correlation)) },
+            format!(
+                "Суточных изменений: {}",
+                format_correlation(stats.change_correlation)
+            ),
+        ),
+        (
+            "Среднее расхождение".to_string(),
+            area_text(
+                stats.mean_abs_diff_km2,
+                branding,
+                AreaFormat {
+                    signed: false,
+                    ..AreaFormat::CHANGE
+                },
+            ),
+            "По модулю, за день".to_string(),
+        ),
+        (
+            "Наибольшее расхождение".to_string(),
+            area_text(
+                stats.max_diff.map(|(_, diff)| diff),
+                branding,
+                AreaFormat::CHANGE,
+            ),
+            stats.max_diff.map_or_else(
+                || "—".to_string(),
+                |(date, _)| {
+                    format!(
+                        "{}, {} минус {}",
+                        date.format(DATE_FORMAT),
+                        comparison.label,
+                        comparison.primary_label
+                    )
+                },
+            ),
+        ),
+    ]
+}
+
+/// Текстовая сводка для кнопки «Скопировать сводку»: её вставляют в посты, поэтому
+/// без разметки и со ссылкой на страницу.
+fn build_share_text(summary: &ChartSummary, branding: &PageConfig) -> String {
+    let (unit, locale) = (branding.area_unit, branding.locale);
+    let change_unit = unit.change_unit();
+    let current = format!(
+        "На {}: {} ({}% Украины), за сутки {}, за 7 дней {}.",
+        summary.latest_date,
+        unit.format(summary.latest_area_km2 * AREA_THOUSANDS_DIVISOR, locale),
+        locale.format(summary.ukraine_percent, 2),
+        change_unit.format_change(summary.daily_change_km2, locale),
+        change_unit.format_change(summary.weekly_change_km2, locale),
+    );
+    let bound = |thousands: f64| {
+        AreaFormat::LEVEL_BARE.render(Some(thousands * AREA_THOUSANDS_DIVISOR), unit, locale)
+    };
+    let forecast = summary.forecast.as_ref().map(|forecast| {
+        format!(
+            "Прогноз на {}: {} (95%: {}–{}).",
+            forecast.end_date,
+            unit.format(forecast.mean_km2 * AREA_THOUSANDS_DIVISOR, locale),
+            bound(forecast.lower_km2),
+            bound(forecast.upper_km2),
+        )
+    });
+    [Some(current), forecast, Some(branding.site_url.clone())]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
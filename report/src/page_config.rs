@@ -0,0 +1,188 @@
+//! Оформление страницы и справочник областей: настройки, которые читает конфиг, без разметки.
+
+use chrono::NaiveDate;
+use rua_core::constants::Precision;
+use serde::Deserialize;
+
+use super::comparison_sets::{self, ComparisonSet};
+use super::data::UKRAINE_REGIONS;
+use super::numbers::NumberLocale;
+use super::units::AreaUnit;
+
+const DEFAULT_PAGE_TITLE: &str = "Территория подконтрольная РФ с начала СВО";
+const DEFAULT_PAGE_SUBTITLE: &str = "Динамика занятых территорий по датам.";
+const DEFAULT_PAGE_DESCRIPTION: &str = "Интерактивная страница с графиком динамики контролируемой территории в российско-украинском конфликте: площадь, изменения и прогноз.";
+const DEFAULT_PAGE_KEYWORDS: &str = "российско-украинский конфликт, контроль территории, площадь, динамика, график, прогноз, статистика";
+const DEFAULT_SITE_URL: &str = "https://rua.hexq.ru/";
+const DEFAULT_SITE_NAME: &str = "RUA";
+const DEFAULT_FAVICON_DATA_URI: &str = "data:image/svg+xml,%3Csvg%20xmlns='http://www.w3.org/2000/svg'%20viewBox='0%200%2064%2064'%3E%3Crect%20width='64'%20height='64'%20rx='14'%20fill='%232464a6'/%3E%3Ctext%20x='32'%20y='41'%20font-size='28'%20text-anchor='middle'%20font-family='IBM%20Plex%20Sans,%20Arial,%20sans-serif'%20fill='white'%3ER%3C/text%3E%3C/svg%3E";
+const DEFAULT_REPO_URL: &str = "https://github.com/hexqnt/rua";
+const DEFAULT_REPO_TEXT: &str = "github.com/hexqnt/rua";
+const FLAG_CDN_BASE: &str = "https://flagcdn.com/24x18/";
+pub const GOOGLE_FONTS_CSS: &str =
+    "https://fonts.googleapis.com/css2?family=IBM+Plex+Sans:wght@400;500;600&display=swap";
+
+/// Откуда страница берёт шрифт IBM Plex Sans (`[page].fonts`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FontStrategy {
+    /// Только системные шрифты, без внешних запросов.
+    System,
+    /// Копия woff2 в `assets/fonts/` рядом со страницей: посетитель к Google не обращается.
+    #[default]
+    SelfHosted,
+    /// Стили и шрифты напрямую с Google Fonts.
+    Google,
+}
+
+/// Оформление страницы: заголовок, описание для поисковиков и соцсетей, ссылки и аналитика.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageConfig {
+    pub title: String,
+    pub subtitle: String,
+    pub description: String,
+    pub keywords: String,
+    /// Канонический адрес страницы (`og:url`).
+    pub site_url: String,
+    pub site_name: String,
+    /// Ссылка на иконку; подходит и `data:` URI.
+    pub favicon: String,
+    /// Ссылка в шапке страницы; `None` скрывает её.
+    pub repo_url: Option<String>,
+    pub repo_text: String,
+    /// HTML-фрагмент счётчика посещений, вставляется в конец `<head>` как есть.
+    pub analytics_html: Option<String>,
+    /// Отправлять события скачивания CSV и сортировки таблиц.
+    pub analytics_events: bool,
+    /// Куда слать события, если на странице нет `plausible()` или `umami.track()`:
+    /// JSON `{name, url, domain, props}` в формате Events API Plausible.
+    pub analytics_endpoint: Option<String>,
+    pub features: ReportFeatures,
+    /// Единица площади по умолчанию; посетитель может сменить её переключателем в сводке.
+    pub area_unit: AreaUnit,
+    /// Разделители в числах на странице и графиках.
+    pub locale: NumberLocale,
+    /// Занятая площадь по областям; пустой список скрывает таблицу долей.
+    pub regions: Vec<RegionOccupation>,
+    /// Наборы территорий для таблиц соотношений: первые два показаны сразу, остальные
+    /// доступны в переключателе над таблицами.
+    pub comparison_sets: Vec<ComparisonSet>,
+    /// Адрес пакета `rua-wasm`: с ним на странице появляется переключатель сглаживания.
+    pub wasm_url: Option<String>,
+    /// Откуда брать шрифты страницы.
+    pub fonts: FontStrategy,
+    /// Стили шрифтов: Google Fonts или локальная копия в `assets/fonts/`.
+    pub fonts_css_url: String,
+    /// Каталог флагов `<код>.png`: flagcdn или локальная копия в `assets/flags/`.
+    pub flag_base_url: String,
+    /// Скачивать флаги в `assets/` рядом со страницей вместо ссылок на CDN.
+    pub self_host_assets: bool,
+    /// Точность чисел в данных графиков и `ratios.json` (`[precision]`).
+    pub precision: Precision,
+}
+
+/// Занятая площадь одной области из [`region_area`] на дату оценки.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegionOccupation {
+    pub name: String,
+    pub occupied_km2: f64,
+    /// День, на который оценена площадь: значения задаются вручную и стареют.
+    pub as_of: NaiveDate,
+}
+
+/// Площадь области по её названию в таблице, км².
+pub fn region_area(name: &str) -> Option<f64> {
+    UKRAINE_REGIONS
+        .iter()
+        .find(|(region, _)| *region == name)
+        .map(|(_, area)| *area)
+}
+
+/// Названия областей из справочника площадей.
+pub fn region_names() -> impl Iterator<Item = &'static str> {
+    UKRAINE_REGIONS.iter().map(|(name, _)| *name)
+}
+
+/// Какие блоки показывать на странице; по умолчанию включены все.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ReportFeatures {
+    /// Прогноз на графике, в карточке сводки и в таблицах соотношений.
+    pub forecast: bool,
+    /// Таблицы соотношений со странами и штатами США.
+    pub comparison_tables: bool,
+    /// Карточка «Год-к-году» со средним суточным изменением.
+    pub change_panel: bool,
+    /// Ссылка на карту-источник в подвале.
+    pub map_link: bool,
+}
+
+impl Default for ReportFeatures {
+    fn default() -> Self {
+        Self {
+            forecast: true,
+            comparison_tables: true,
+            change_panel: true,
+            map_link: true,
+        }
+    }
+}
+
+impl PageConfig {
+    /// Коды флагов таблиц соотношений (`ua`, `us-ca`, …) без повторов: их скачивает
+    /// `self_host_assets`.
+    pub fn flag_codes(&self) -> Vec<&str> {
+        let mut codes = self
+            .comparison_sets
+            .iter()
+            .flat_map(|set| &set.entries)
+            .filter_map(|entry| entry.flag.as_deref())
+            .collect::<Vec<_>>();
+        codes.sort_unstable();
+        codes.dedup();
+        codes
+    }
+
+    /// Стили шрифтов для `<link>`; `None` — страница обходится системными шрифтами. При
+    /// `self-hosted` ссылка даётся только на локальную копию: если скачать её не удалось,
+    /// страница не обращается к Google, а показывается системным шрифтом.
+    pub fn fonts_stylesheet(&self) -> Option<&str> {
+        match self.fonts {
+            FontStrategy::System => None,
+            FontStrategy::SelfHosted => {
+                (!self.fonts_css_url.contains("://")).then_some(self.fonts_css_url.as_str())
+            }
+            FontStrategy::Google => Some(&self.fonts_css_url),
+        }
+    }
+}
+
+impl Default for PageConfig {
+    fn default() -> Self {
+        Self {
+            title: DEFAULT_PAGE_TITLE.to_string(),
+            subtitle: DEFAULT_PAGE_SUBTITLE.to_string(),
+            description: DEFAULT_PAGE_DESCRIPTION.to_string(),
+            keywords: DEFAULT_PAGE_KEYWORDS.to_string(),
+            site_url: DEFAULT_SITE_URL.to_string(),
+            site_name: DEFAULT_SITE_NAME.to_string(),
+            favicon: DEFAULT_FAVICON_DATA_URI.to_string(),
+            repo_url: Some(DEFAULT_REPO_URL.to_string()),
+            repo_text: DEFAULT_REPO_TEXT.to_string(),
+            analytics_html: None,
+            analytics_events: false,
+            analytics_endpoint: None,
+            features: ReportFeatures::default(),
+            area_unit: AreaUnit::default(),
+            locale: NumberLocale::default(),
+            regions: Vec::new(),
+            comparison_sets: comparison_sets::default_comparison_sets(),
+            wasm_url: None,
+            fonts: FontStrategy::default(),
+            fonts_css_url: GOOGLE_FONTS_CSS.to_string(),
+            flag_base_url: FLAG_CDN_BASE.to_string(),
+            self_host_assets: false,
+            precision: Precision::default(),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Минимальный растровый рендер ряда в PNG (для вложений, где Plotly недоступен).
+
+use std::io::{self, Write};
+
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+const PNG_BIT_DEPTH: u8 = 8;
+const PNG_COLOR_TYPE_RGB: u8 = 2;
+const PNG_FILTER_NONE: u8 = 0;
+const BYTES_PER_PIXEL: usize = 3;
+const CRC32_POLYNOMIAL: u32 = 0xEDB8_8320;
+const CHART_MARGIN: usize = 16;
+const GRID_LINES: usize = 4;
+const COLOR_BACKGROUND: [u8; 3] = [255, 255, 255];
+const COLOR_GRID: [u8; 3] = [232, 232, 232];
+const COLOR_LINE: [u8; 3] = [36, 100, 166];
+
+/// Рисует ряд ломаной линией на белом фоне и кодирует результат в PNG.
+pub fn render_line_png(values: &[f64], width: usize, height: usize) -> io::Result<Vec<u8>> {
+    if width <= 2 * CHART_MARGIN || height <= 2 * CHART_MARGIN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "PNG canvas is smaller than chart margins",
+        ));
+    }
+    let mut canvas = Canvas::new(width, height);
+    let plot_width = width - 2 * CHART_MARGIN;
+    let plot_height = height - 2 * CHART_MARGIN;
+
+    for line in 0..=GRID_LINES {
+        let y = CHART_MARGIN + plot_height * line / GRID_LINES;
+        for x in CHART_MARGIN..(CHART_MARGIN + plot_width) {
+            canvas.set(x, y, COLOR_GRID);
+        }
+    }
+
+    let finite = values.iter().copied().filter(|value| value.is_finite());
+    let (min, max) = finite.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    });
+    if values.len() >= 2 && min.is_finite() && max.is_finite() {
+        let span = if max - min > f64::EPSILON {
+            max - min
+        } else {
+            1.0
+        };
+        let last_index = to_f64(values.len() - 1);
+        let points = values
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| value.is_finite())
+            .map(|(idx, value)| {
+                let x = to_f64(idx) / last_index * to_f64(plot_width - 1);
+                let y = (1.0 - (value - min) / span) * to_f64(plot_height - 1);
+                (to_pixel(x) + CHART_MARGIN, to_pixel(y) + CHART_MARGIN)
+            })
+            .collect::<Vec<_>>();
+        for pair in points.windows(2) {
+            canvas.line(pair[0], pair[1], COLOR_LINE);
+            // Второй проход со сдвигом даёт линию толщиной 2 px.
+            canvas.line(
+                (pair[0].0, pair[0].1 + 1),
+                (pair[1].0, pair[1].1 + 1),
+                COLOR_LINE,
+            );
+        }
+    }
+
+    canvas.encode_png()
+}
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        let pixels = COLOR_BACKGROUND
+            .iter()
+            .copied()
+            .cycle()
+            .take(width * height * BYTES_PER_PIXEL)
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize, color: [u8; 3]) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let offset = (y * self.width + x) * BYTES_PER_PIXEL;
+        self.pixels[offset..offset + BYTES_PER_PIXEL].copy_from_slice(&color);
+    }
+
+    /// Растеризует отрезок алгоритмом Брезенхэма.
+    fn line(&mut self, from: (usize, usize), to: (usize, usize), color: [u8; 3]) {
+        let (mut x, mut y) = (from.0.cast_signed(), from.1.cast_signed());
+        let (x1, y1) = (to.0.cast_signed(), to.1.cast_signed());
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let step_x = if x < x1 { 1 } else { -1 };
+        let step_y = if y < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        loop {
+            self.set(x.cast_unsigned(), y.cast_unsigned(), color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let doubled = 2 * err;
+            if doubled >= dy {
+                err += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                err += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    fn encode_png(&self) -> io::Result<Vec<u8>> {
+        let row_len = self.width * BYTES_PER_PIXEL;
+        let mut raw = Vec::with_capacity(self.height * (row_len + 1));
+        for row in self.pixels.chunks(row_len) {
+            raw.push(PNG_FILTER_NONE);
+            raw.extend_from_slice(row);
+        }
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&raw)?;
+        let compressed = encoder.finish()?;
+
+        let to_u32 = |value: usize| {
+            u32::try_from(value).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, "PNG dimension exceeds u32")
+            })
+        };
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&to_u32(self.width)?.to_be_bytes());
+        header.extend_from_slice(&to_u32(self.height)?.to_be_bytes());
+        header.extend_from_slice(&[PNG_BIT_DEPTH, PNG_COLOR_TYPE_RGB, 0, 0, 0]);
+
+        let mut png = PNG_SIGNATURE.to_vec();
+        write_chunk(&mut png, *b"IHDR", &header)?;
+        write_chunk(&mut png, *b"IDAT", &compressed)?;
+        write_chunk(&mut png, *b"IEND", &[])?;
+        Ok(png)
+    }
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: [u8; 4], data: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(data.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PNG chunk is too large"))?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(&kind);
+    out.extend_from_slice(data);
+    let crc = crc32(kind.iter().chain(data));
+    out.extend_from_slice(&crc.to_be_bytes());
+    Ok(())
+}
+
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = u32::MAX;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (CRC32_POLYNOMIAL & mask);
+        }
+    }
+    !crc
+}
+
+fn to_f64(value: usize) -> f64 {
+    f64::from(u32::try_from(value).unwrap_or(u32::MAX))
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+const fn to_pixel(value: f64) -> usize {
+    value.round().max(0.0) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PNG_SIGNATURE, crc32, render_line_png};
+
+    #[test]
+    fn crc32_matches_reference_value() {
+        assert_eq!(crc32(b"IEND".iter()), 0xAE42_6082);
+    }
+
+    #[test]
+    fn renders_valid_png_header() {
+        let png = render_line_png(&[1.0, 3.0, 2.0, 5.0], 120, 80).expect("png should render");
+        assert_eq!(&png[..8], &PNG_SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(
+            u32::from_be_bytes([png[16], png[17], png[18], png[19]]),
+            120
+        );
+        assert_eq!(u32::from_be_bytes([png[20], png[21], png[22], png[23]]), 80);
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+    }
+}
@@ -0,0 +1,79 @@
+//! Настройка сглаживания суточного изменения для нижней панели графика и выгрузки в Excel.
+//!
+//! Все методы возвращают ряд той же длины, что и вход, с `None` там, где в окне меньше
+//! `min_periods` значений: скользящее среднее и LOESS берут окно с центром в текущем дне,
+//! экспоненциальное среднее — только прошлые дни, поэтому не заглядывает вперёд.
+
+use serde::Deserialize;
+
+use super::kernel;
+
+pub const DEFAULT_SMOOTHING_WINDOW: usize = 5;
+pub const DEFAULT_SMOOTHING_MIN_PERIODS: usize = 3;
+
+/// Метод сглаживания (`[chart.change_smoothing].method`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmoothingMethod {
+    /// Центрированное скользящее среднее.
+    #[default]
+    Sma,
+    /// Экспоненциальное среднее с `alpha = 2 / (window + 1)`.
+    Ewma,
+    /// Локальная линейная регрессия с трикубическими весами по окну.
+    Loess,
+}
+
+impl SmoothingMethod {
+    pub const ALL: [Self; 3] = [Self::Sma, Self::Ewma, Self::Loess];
+
+    /// Имя метода в конфигурации и в переключателе страницы.
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Sma => "sma",
+            Self::Ewma => "ewma",
+            Self::Loess => "loess",
+        }
+    }
+
+    /// Подпись в переключателе страницы.
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Sma => "Скользящее среднее",
+            Self::Ewma => "Экспоненциальное",
+            Self::Loess => "LOESS",
+        }
+    }
+}
+
+/// Как сглаживать суточное изменение.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SmoothingSpec {
+    pub method: SmoothingMethod,
+    /// Ширина окна в днях (для EWMA — эквивалентный «span»).
+    pub window: usize,
+    /// Сколько значений должно попасть в окно, чтобы точка была показана.
+    pub min_periods: usize,
+}
+
+impl Default for SmoothingSpec {
+    fn default() -> Self {
+        Self {
+            method: SmoothingMethod::default(),
+            window: DEFAULT_SMOOTHING_WINDOW,
+            min_periods: DEFAULT_SMOOTHING_MIN_PERIODS,
+        }
+    }
+}
+
+impl SmoothingSpec {
+    /// Сглаженный ряд; `None` — окно недозаполнено.
+    pub fn apply(&self, values: &[f64]) -> Vec<Option<f64>> {
+        let smooth = match self.method {
+            SmoothingMethod::Sma => kernel::moving_average,
+            SmoothingMethod::Ewma => kernel::exponential_average,
+            SmoothingMethod::Loess => kernel::local_linear,
+        };
+        smooth(values, self.window, self.min_periods)
+    }
+}
@@ -0,0 +1,694 @@
+//! Стили страницы: критические правила встраиваются в `<head>`, остальные — в конец `<body>`.
+//! Перед вставкой CSS минифицируется, а правила с классами и id, которых нет в разметке, выбрасываются.
+
+use std::fmt::Write as _;
+
+/// Правила для первого экрана: шапка, сводка и графики.
+const CRITICAL_CSS: &str = r#"
+:root {
+    color-scheme: light;
+    --bg: #f7f6f2;
+    --card: #ffffff;
+    --ink: #1f2430;
+    --muted: #56606f;
+    --accent: #2464a6;
+    --border: rgba(31, 36, 48, 0.08);
+}
+* { box-sizing: border-box; }
+body {
+    margin: 0;
+    background: var(--bg);
+    color: var(--ink);
+    font-family: "IBM Plex Sans", "PT Sans", system-ui, -apple-system, "Segoe UI", Roboto, sans-serif;
+}
+.page {
+    max-width: 1240px;
+    margin: 40px auto 60px;
+    padding: 0 24px;
+}
+.hero {
+    display: flex;
+    flex-wrap: wrap;
+    gap: 16px;
+    align-items: center;
+    justify-content: space-between;
+    margin-bottom: 22px;
+}
+.hero-aside {
+    display: flex;
+    flex-direction: column;
+    gap: 10px;
+    align-items: flex-end;
+}
+.title {
+    font-size: 26px;
+    font-weight: 600;
+    margin: 0;
+}
+.subtitle {
+    margin: 6px 0 0;
+    color: var(--muted);
+    font-size: 13px;
+}
+.link {
+    display: inline-flex;
+    align-items: center;
+    gap: 8px;
+    padding: 8px 14px;
+    border-radius: 999px;
+    border: 1px solid rgba(36, 100, 166, 0.25);
+    color: var(--accent);
+    font-weight: 500;
+    text-decoration: none;
+    transition: transform 0.2s ease, background 0.2s ease;
+}
+.link:hover {
+    transform: translateY(-1px);
+    background: rgba(36, 100, 166, 0.08);
+}
+.link svg {
+    width: 16px;
+    height: 16px;
+    display: block;
+}
+.card {
+    background: var(--card);
+    border-radius: 18px;
+    padding: 16px;
+    border: 1px solid var(--border);
+    overflow-x: auto;
+}
+.card + .card {
+    margin-top: 18px;
+}
+.chart-tabs {
+    display: flex;
+    gap: 6px;
+    margin-bottom: 12px;
+}
+.chart-tab {
+    font: inherit;
+    font-size: 13px;
+    padding: 5px 12px;
+    border-radius: 999px;
+    border: 1px solid var(--border);
+    background: transparent;
+    color: var(--muted);
+    cursor: pointer;
+}
+.chart-tab[aria-selected="true"] {
+    border-color: rgba(36, 100, 166, 0.45);
+    background: rgba(36, 100, 166, 0.08);
+    color: var(--ink);
+}
+.series-badges {
+    display: flex;
+    flex-wrap: wrap;
+    gap: 8px;
+    margin-bottom: 10px;
+}
+.badge {
+    display: inline-flex;
+    align-items: center;
+    gap: 8px;
+    padding: 4px 10px;
+    border-radius: 999px;
+    border: 1px solid rgba(36, 100, 166, 0.25);
+    background: rgba(36, 100, 166, 0.08);
+    color: var(--accent);
+    font-size: 11px;
+    font-weight: 600;
+    letter-spacing: 0.02em;
+}
+.badge::before {
+    content: "";
+    width: 8px;
+    height: 8px;
+    border-radius: 50%;
+    background: var(--accent);
+}
+.badge.forecast {
+    border-style: dashed;
+    background: rgba(36, 100, 166, 0.04);
+}
+.summary {
+    margin: 14px 0 18px;
+}
+.summary-grid {
+    display: grid;
+    grid-template-columns: repeat(auto-fit, minmax(220px, 1fr));
+    gap: 14px;
+}
+.summary-card {
+    background: var(--card);
+    border-radius: 16px;
+    padding: 14px 16px;
+    border: 1px solid var(--border);
+}
+.summary-label {
+    font-size: 11px;
+    text-transform: uppercase;
+    letter-spacing: 0.08em;
+    color: var(--muted);
+}
+.summary-label-icon {
+    display: inline-flex;
+    align-items: center;
+    justify-content: center;
+    margin-left: 6px;
+    color: var(--accent);
+    text-decoration: none;
+}
+.summary-label-icon:hover {
+    color: #1b4f87;
+}
+.summary-label-icon svg {
+    width: 12px;
+    height: 12px;
+}
+.summary-value {
+    font-size: 20px;
+    font-weight: 600;
+    margin-top: 6px;
+}
+.summary-sub {
+    margin-top: 6px;
+    font-size: 12px;
+    color: var(--muted);
+}
+.summary-stale {
+    color: #a65f00;
+}
+.summary-actions {
+    display: flex;
+    align-items: center;
+    gap: 10px;
+    margin-top: 10px;
+    font-size: 12px;
+    color: var(--muted);
+}
+.summary-copy {
+    font: inherit;
+    padding: 6px 12px;
+    border-radius: 999px;
+    border: 1px solid var(--border);
+    background: var(--card);
+    color: var(--ink);
+    cursor: pointer;
+}
+.summary-copy[hidden] {
+    display: none;
+}
+.area-unit {
+    margin-left: auto;
+}
+.area-axis {
+    margin-left: auto;
+    font-size: 12px;
+    color: var(--muted);
+}
+.forecast-horizon {
+    display: block;
+    margin-top: 6px;
+    font-size: 12px;
+    color: var(--muted);
+}
+.smoothing-controls {
+    display: flex;
+    flex-wrap: wrap;
+    gap: 12px;
+    margin: 0 0 8px;
+    font-size: 12px;
+    color: var(--muted);
+}
+.smoothing-controls input {
+    width: 4em;
+}
+.area-unit[hidden],
+.area-axis[hidden],
+.forecast-horizon[hidden],
+.smoothing-controls[hidden] {
+    display: none;
+}
+.area-unit select,
+.area-axis select,
+.forecast-horizon select,
+.smoothing-controls select,
+.smoothing-controls input {
+    font: inherit;
+    padding: 4px 8px;
+    border-radius: 8px;
+    border: 1px solid var(--border);
+    background: #fff;
+    color: var(--ink);
+}
+.summary-link {
+    display: inline-flex;
+    margin-top: 8px;
+    font-size: 12px;
+    color: var(--accent);
+    text-decoration: none;
+    border-bottom: 1px dashed rgba(36, 100, 166, 0.45);
+}
+.card-title {
+    margin: 0 0 8px;
+    font-size: 16px;
+    font-weight: 600;
+}
+.card-subtitle {
+    margin: 0 0 12px;
+    font-size: 12px;
+    color: var(--muted);
+}
+#area-plot {
+    width: 100%;
+    min-height: 640px;
+}
+#yoy-plot {
+    width: 100%;
+    min-height: 620px;
+}
+#category-plot {
+    width: 100%;
+    min-height: 560px;
+}
+#flows-plot {
+    width: 100%;
+    min-height: 560px;
+}
+#compare-plot {
+    width: 100%;
+    min-height: 560px;
+}
+.plot-skeleton {
+    border-radius: 12px;
+    background: linear-gradient(90deg, #f1f3f6 25%, #e6e9ee 50%, #f1f3f6 75%);
+    background-size: 200% 100%;
+    animation: plot-skeleton-shimmer 1.4s ease-in-out infinite;
+}
+@keyframes plot-skeleton-shimmer {
+    from { background-position: 200% 0; }
+    to { background-position: -200% 0; }
+}
+@media (prefers-reduced-motion: reduce) {
+    .plot-skeleton { animation: none; }
+}
+@media (max-width: 900px) {
+.title { font-size: 22px; }
+    #area-plot { min-height: 560px; }
+    #yoy-plot { min-height: 500px; }
+    .hero-aside { width: 100%; align-items: flex-start; }
+}
+"#;
+
+/// Правила для блоков ниже первого экрана: таблицы, подвал, статичный график.
+const DEFERRED_CSS: &str = r"
+.flag {
+    width: 24px;
+    height: 18px;
+    margin-right: 8px;
+    border-radius: 2px;
+    box-shadow: 0 0 0 1px rgba(0, 0, 0, 0.08);
+    object-fit: cover;
+}
+.static-chart svg {
+    width: 100%;
+    height: auto;
+}
+.static-chart-note {
+    color: var(--muted);
+    font-size: 13px;
+}
+.table-card {
+    background: var(--card);
+    border-radius: 18px;
+    padding: 18px 20px;
+    border: 1px solid var(--border);
+    margin-top: 18px;
+}
+.table-grid {
+    display: grid;
+    grid-template-columns: repeat(2, minmax(0, 1fr));
+    gap: 16px;
+}
+.table-box h3 {
+    margin: 0 0 8px;
+    font-size: 13px;
+    font-weight: 600;
+}
+.table-box h3 .ratio-set {
+    margin-left: 8px;
+    font: inherit;
+    font-weight: 400;
+    padding: 2px 6px;
+    border-radius: 6px;
+    border: 1px solid var(--border);
+    background: #fff;
+    color: var(--ink);
+}
+.table-controls {
+    display: flex;
+    align-items: center;
+    gap: 10px;
+    margin-bottom: 10px;
+    font-size: 12px;
+    color: var(--muted);
+}
+.table-controls select {
+    font: inherit;
+    padding: 6px 8px;
+    border-radius: 8px;
+    border: 1px solid var(--border);
+    background: #fff;
+    color: var(--ink);
+}
+.table-title {
+    margin: 0 0 10px;
+    font-size: 16px;
+    font-weight: 600;
+}
+.ratio-table {
+    width: 100%;
+    border-collapse: collapse;
+    font-size: 13px;
+}
+.ratio-table th,
+.ratio-table td {
+    padding: 8px 10px;
+    border-bottom: 1px solid var(--border);
+    text-align: left;
+}
+.ratio-table th {
+    color: var(--muted);
+    font-weight: 500;
+    letter-spacing: 0.02em;
+    text-transform: uppercase;
+    font-size: 11px;
+}
+.ratio-table tbody tr:nth-child(even) {
+    background: rgba(31, 36, 48, 0.02);
+}
+.ratio-table td.ratio-forecast {
+    color: var(--muted);
+}
+.region-table td + td,
+.region-table th + th {
+    text-align: right;
+    font-variant-numeric: tabular-nums;
+}
+.region-share {
+    white-space: nowrap;
+}
+.share-bar {
+    display: inline-block;
+    width: 120px;
+    height: 8px;
+    margin-right: 8px;
+    vertical-align: middle;
+    border-radius: 999px;
+    background: rgba(31, 36, 48, 0.08);
+    overflow: hidden;
+}
+.share-bar-fill {
+    display: block;
+    height: 100%;
+    background: var(--accent);
+}
+.share-value {
+    display: inline-block;
+    min-width: 44px;
+}
+.series-details {
+    margin-top: 12px;
+    font-size: 13px;
+}
+.series-details summary {
+    cursor: pointer;
+    color: var(--muted);
+}
+.series-table-scroll {
+    max-height: 420px;
+    overflow: auto;
+    margin-top: 8px;
+}
+.series-table td + td,
+.series-table th + th {
+    text-align: right;
+    font-variant-numeric: tabular-nums;
+}
+.series-imputed {
+    margin-left: 2px;
+    color: var(--muted);
+}
+.series-pager {
+    display: flex;
+    align-items: center;
+    gap: 10px;
+    margin-top: 8px;
+    font-size: 12px;
+    color: var(--muted);
+}
+.series-pager[hidden] {
+    display: none;
+}
+.series-pager button {
+    font: inherit;
+    padding: 4px 10px;
+    border-radius: 8px;
+    border: 1px solid var(--border);
+    background: #fff;
+    color: var(--ink);
+    cursor: pointer;
+}
+.series-pager button:disabled {
+    opacity: 0.5;
+    cursor: default;
+}
+.ratio-note {
+    margin-top: 10px;
+    font-size: 11px;
+    color: var(--muted);
+}
+footer {
+    margin-top: 16px;
+    font-size: 12px;
+    color: var(--muted);
+    text-align: right;
+}
+footer a {
+    color: inherit;
+    text-decoration: none;
+    border-bottom: 1px dotted rgba(86, 96, 111, 0.6);
+}
+@media (max-width: 900px) {
+    .table-grid { grid-template-columns: 1fr; }
+}
+";
+
+/// Критические и отложенные стили, подготовленные для конкретной страницы.
+pub struct PageStyles {
+    pub critical: String,
+    pub deferred: String,
+}
+
+/// Оставляет только правила, применимые к `html`, и минифицирует результат.
+pub fn page_styles(html: &str) -> PageStyles {
+    let used = UsedSelectors::collect(html);
+    PageStyles {
+        critical: minify_css(&strip_unused_rules(CRITICAL_CSS, &used)),
+        deferred: minify_css(&strip_unused_rules(DEFERRED_CSS, &used)),
+    }
+}
+
+/// Классы и id, встречающиеся в атрибутах `class` и `id` разметки.
+struct UsedSelectors {
+    classes: Vec<String>,
+    ids: Vec<String>,
+}
+
+impl UsedSelectors {
+    fn collect(html: &str) -> Self {
+        let attribute_values = |name: &str| {
+            let pattern = format!("{name}=\"");
+            html.match_indices(&pattern)
+                .filter_map(|(start, _)| {
+                    let value = &html[start + pattern.len()..];
+                    value.find('"').map(|end| &value[..end])
+                })
+                .flat_map(str::split_whitespace)
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        let mut classes = attribute_values(" class");
+        classes.sort_unstable();
+        classes.dedup();
+        let mut ids = attribute_values(" id");
+        ids.sort_unstable();
+        ids.dedup();
+        Self { classes, ids }
+    }
+
+    /// Селектор применим, если все его классы и id есть в разметке (теги и псевдоклассы не проверяются).
+    fn matches(&self, selector: &str) -> bool {
+        let mut chars = selector.char_indices().peekable();
+        while let Some((idx, ch)) = chars.next() {
+            let known = match ch {
+                '.' => &self.classes,
+                '#' => &self.ids,
+                _ => continue,
+            };
+            let name_start = idx + 1;
+            let mut name_end = name_start;
+            while let Some(&(next_idx, next)) = chars.peek() {
+                if next.is_ascii_alphanumeric() || next == '-' || next == '_' {
+                    name_end = next_idx + next.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let name = &selector[name_start..name_end];
+            if !name.is_empty()
+                && known
+                    .binary_search_by(|item| item.as_str().cmp(name))
+                    .is_err()
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Удаляет правила, ни один селектор которых не применим; `@media` обрабатывается рекурсивно.
+fn strip_unused_rules(css: &str, used: &UsedSelectors) -> String {
+    let mut output = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(open) = rest.find('{') {
+        let prelude = rest[..open].trim();
+        let Some(close) = matching_brace(rest, open) else {
+            break;
+        };
+        let block = &rest[open + 1..close];
+        if prelude.starts_with("@media") {
+            let inner = strip_unused_rules(block, used);
+            if !inner.trim().is_empty() {
+                let _ = write!(output, "{prelude}{{{inner}}}");
+            }
+        } else if prelude.starts_with('@')
+            || prelude
+                .split(',')
+                .any(|selector| used.matches(selector.trim()))
+        {
+            let _ = write!(output, "{prelude}{{{block}}}");
+        }
+        rest = &rest[close + 1..];
+    }
+    output
+}
+
+fn matching_brace(css: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (idx, ch) in css[open..].char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Убирает комментарии и лишние пробелы; строки в кавычках не трогает.
+fn minify_css(css: &str) -> String {
+    let mut output = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+    let mut pending_space = false;
+    while let Some(ch) = chars.next() {
+        match ch {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = '\0';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            '"' | '\'' => {
+                flush_space(&mut output, &mut pending_space);
+                output.push(ch);
+                for next in chars.by_ref() {
+                    output.push(next);
+                    if next == ch {
+                        break;
+                    }
+                }
+            }
+            ch if ch.is_whitespace() => pending_space = !output.is_empty(),
+            '{' | '}' | ';' | ',' | ':' | '>' => {
+                pending_space = false;
+                if ch == '}' && output.ends_with(';') {
+                    output.pop();
+                }
+                output.push(ch);
+            }
+            _ => {
+                flush_space(&mut output, &mut pending_space);
+                output.push(ch);
+            }
+        }
+    }
+    output
+}
+
+/// Пробел нужен только между токенами, а не после разделителей.
+fn flush_space(output: &mut String, pending_space: &mut bool) {
+    if *pending_space && !output.ends_with(['{', '}', ';', ',', ':', '>']) {
+        output.push(' ');
+    }
+    *pending_space = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CRITICAL_CSS, DEFERRED_CSS, minify_css, page_styles};
+
+    #[test]
+    fn styles_are_minified_and_unused_rules_dropped() {
+        let html = r#"<div class="page"><header class="hero"><h1 class="title">t</h1></header>
+            <div class="card"><div id="area-plot"></div></div><footer>f</footer></div>"#;
+        let styles = page_styles(html);
+
+        assert!(styles.critical.contains(".title{"));
+        assert!(
+            styles
+                .critical
+                .contains("#area-plot{width:100%;min-height:640px}")
+        );
+        assert!(
+            styles
+                .critical
+                .contains("@media (max-width:900px){.title{font-size:22px}")
+        );
+        assert!(!styles.critical.contains(".summary-link"));
+        assert!(!styles.critical.contains("#yoy-plot"));
+        assert!(styles.deferred.starts_with("footer{"));
+        assert!(!styles.deferred.contains(".ratio-table"));
+        assert!(
+            styles.critical.len() + styles.deferred.len()
+                < (CRITICAL_CSS.len() + DEFERRED_CSS.len()) / 3
+        );
+
+        assert_eq!(
+            minify_css("a  b { content: \" x \" ; /* note */ }"),
+            "a b{content:\" x \"}"
+        );
+    }
+}
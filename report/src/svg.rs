@@ -0,0 +1,204 @@
+//! Статичная SVG-версия основного графика для читателей без JavaScript.
+
+use std::fmt::Write as _;
+
+const SVG_WIDTH: f64 = 960.0;
+const SVG_HEIGHT: f64 = 360.0;
+const MARGIN_LEFT: f64 = 64.0;
+const MARGIN_RIGHT: f64 = 16.0;
+const MARGIN_TOP: f64 = 16.0;
+const MARGIN_BOTTOM: f64 = 32.0;
+const GRID_LINES: usize = 4;
+/// Предел вершин ломаной: больше точек на ширине в ~900 px не различимо, а HTML растёт.
+const MAX_SVG_POINTS: usize = 600;
+const COLOR_ACTUAL: &str = "#2464a6";
+const COLOR_FORECAST: &str = "#d9822b";
+const COLOR_GRID: &str = "#e8e8e8";
+const COLOR_TEXT: &str = "#555";
+const FONT_SIZE: u32 = 12;
+
+/// Ряд для SVG: подписи дат и значения в тыс. км².
+pub struct SvgSeries<'a> {
+    pub dates: &'a [String],
+    pub values: &'a [f64],
+}
+
+/// Рисует факт сплошной линией и прогноз (если есть) пунктиром на общей оси времени.
+/// Возвращает `None`, если рисовать нечего.
+pub fn render_area_svg(actual: &SvgSeries<'_>, forecast: Option<&SvgSeries<'_>>) -> Option<String> {
+    let points = actual.values.len() + forecast.map_or(0, |series| series.values.len());
+    if actual.values.len() < 2 {
+        return None;
+    }
+    let all_values = actual
+        .values
+        .iter()
+        .chain(forecast.map_or(&[][..], |series| series.values))
+        .copied()
+        .filter(|value| value.is_finite());
+    let (min, max) = all_values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    });
+    if !min.is_finite() || !max.is_finite() {
+        return None;
+    }
+    let span = if max - min > f64::EPSILON {
+        max - min
+    } else {
+        1.0
+    };
+    let plot_width = SVG_WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = SVG_HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let last_index = to_f64(points - 1);
+    let project = |idx: usize, value: f64| {
+        (
+            to_f64(idx).mul_add(plot_width / last_index, MARGIN_LEFT),
+            (1.0 - (value - min) / span).mul_add(plot_height, MARGIN_TOP),
+        )
+    };
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {SVG_WIDTH} {SVG_HEIGHT}\" role=\"img\" \
+         aria-label=\"Динамика площади, тыс. км²\" font-family=\"sans-serif\" font-size=\"{FONT_SIZE}\">"
+    );
+    for line in 0..=GRID_LINES {
+        let fraction = to_f64(line) / to_f64(GRID_LINES);
+        let y = fraction.mul_add(plot_height, MARGIN_TOP);
+        let label = fraction.mul_add(-span, max);
+        let _ = write!(
+            svg,
+            "<line x1=\"{MARGIN_LEFT}\" x2=\"{:.1}\" y1=\"{y:.1}\" y2=\"{y:.1}\" stroke=\"{COLOR_GRID}\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\" fill=\"{COLOR_TEXT}\">{label:.1}</text>",
+            MARGIN_LEFT + plot_width,
+            MARGIN_LEFT - 6.0,
+            y + 4.0,
+        );
+    }
+
+    let actual_points = actual
+        .values
+        .iter()
+        .copied()
+        .enumerate()
+        .collect::<Vec<_>>();
+    let _ = write!(
+        svg,
+        "<polyline fill=\"none\" stroke=\"{COLOR_ACTUAL}\" stroke-width=\"2\" points=\"{}\"/>",
+        polyline(&actual_points, &project)
+    );
+    let mut last_label = actual.dates.last();
+    if let Some(series) = forecast.filter(|series| !series.values.is_empty()) {
+        let offset = actual.values.len() - 1;
+        // Прогноз начинается от последней фактической точки, чтобы линии не разрывались.
+        let forecast_points = std::iter::once((offset, actual.values[offset]))
+            .chain(
+                series
+                    .values
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, value)| (offset + idx + 1, *value)),
+            )
+            .collect::<Vec<_>>();
+        let _ = write!(
+            svg,
+            "<polyline fill=\"none\" stroke=\"{COLOR_FORECAST}\" stroke-width=\"2\" stroke-dasharray=\"6 4\" points=\"{}\"/>",
+            polyline(&forecast_points, &project)
+        );
+        last_label = series.dates.last().or(last_label);
+    }
+
+    let label_y = SVG_HEIGHT - 10.0;
+    if let Some(first) = actual.dates.first() {
+        let _ = write!(
+            svg,
+            "<text x=\"{MARGIN_LEFT}\" y=\"{label_y}\" fill=\"{COLOR_TEXT}\">{}</text>",
+            escape(first)
+        );
+    }
+    if let Some(last) = last_label {
+        let _ = write!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{label_y}\" text-anchor=\"end\" fill=\"{COLOR_TEXT}\">{}</text>",
+            MARGIN_LEFT + plot_width,
+            escape(last)
+        );
+    }
+    svg.push_str("</svg>");
+    Some(svg)
+}
+
+/// Атрибут `points` с прореживанием до [`MAX_SVG_POINTS`]; последняя точка сохраняется всегда.
+fn polyline(points: &[(usize, f64)], project: &impl Fn(usize, f64) -> (f64, f64)) -> String {
+    let total = points.len();
+    let step = total.div_ceil(MAX_SVG_POINTS).max(1);
+    let mut rendered = String::new();
+    for (position, &(idx, value)) in points.iter().enumerate() {
+        if !value.is_finite() || (position % step != 0 && position + 1 != total) {
+            continue;
+        }
+        let (x, y) = project(idx, value);
+        if !rendered.is_empty() {
+            rendered.push(' ');
+        }
+        let _ = write!(rendered, "{x:.1},{y:.1}");
+    }
+    rendered
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn to_f64(value: usize) -> f64 {
+    u32::try_from(value).map_or_else(|_| f64::from(u32::MAX), f64::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_SVG_POINTS, SvgSeries, render_area_svg};
+
+    #[test]
+    fn svg_draws_actual_and_forecast_with_bounded_vertex_count() {
+        let dates = (0..2000).map(|day| format!("d{day}")).collect::<Vec<_>>();
+        let values = (0..2000).map(f64::from).collect::<Vec<_>>();
+        let forecast_dates = vec!["f1".to_string(), "f2".to_string()];
+        let forecast_values = vec![2000.0, 2001.0];
+        let svg = render_area_svg(
+            &SvgSeries {
+                dates: &dates,
+                values: &values,
+            },
+            Some(&SvgSeries {
+                dates: &forecast_dates,
+                values: &forecast_values,
+            }),
+        )
+        .expect("series is long enough");
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("stroke-dasharray"));
+        assert!(svg.contains(">d0</text>"));
+        assert!(svg.contains(">f2</text>"));
+        let actual_points = svg
+            .split("points=\"")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .expect("actual polyline")
+            .split(' ')
+            .count();
+        assert!(actual_points <= MAX_SVG_POINTS + 1);
+
+        assert!(
+            render_area_svg(
+                &SvgSeries {
+                    dates: &dates[..1],
+                    values: &values[..1],
+                },
+                None
+            )
+            .is_none()
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Единицы площади для подписей графиков, карточек сводки и таблиц. Все ряды хранятся в км²
+//! и переводятся в выбранную единицу только при выводе.
+
+use serde::Deserialize;
+
+use super::numbers::NumberLocale;
+
+/// Квадратных километров в квадратной миле.
+const KM2_PER_SQUARE_MILE: f64 = 2.589_988_110_336;
+
+/// Единица площади страницы (`[page].area_unit`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum AreaUnit {
+    #[serde(rename = "km2")]
+    SquareKilometres,
+    /// Уровни — в тысячах км², изменения — в км².
+    #[default]
+    #[serde(rename = "thousand_km2")]
+    ThousandSquareKilometres,
+    #[serde(rename = "mi2")]
+    SquareMiles,
+    #[serde(rename = "hectares")]
+    Hectares,
+}
+
+impl AreaUnit {
+    pub const ALL: [Self; 4] = [
+        Self::SquareKilometres,
+        Self::ThousandSquareKilometres,
+        Self::SquareMiles,
+        Self::Hectares,
+    ];
+
+    /// Имя единицы в конфигурации и в переключателе страницы.
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::SquareKilometres => "km2",
+            Self::ThousandSquareKilometres => "thousand_km2",
+            Self::SquareMiles => "mi2",
+            Self::Hectares => "hectares",
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::SquareKilometres => "км²",
+            Self::ThousandSquareKilometres => "тыс. км²",
+            Self::SquareMiles => "кв. миль",
+            Self::Hectares => "га",
+        }
+    }
+
+    /// Сколько км² в одной единице.
+    pub const fn km2_per_unit(self) -> f64 {
+        match self {
+            Self::SquareKilometres => 1.0,
+            Self::ThousandSquareKilometres => 1000.0,
+            Self::SquareMiles => KM2_PER_SQUARE_MILE,
+            Self::Hectares => 0.01,
+        }
+    }
+
+    /// Знаков после запятой: тысячи км² без десятых теряют суточные изменения.
+    pub const fn decimals(self) -> usize {
+        match self {
+            Self::ThousandSquareKilometres => 1,
+            Self::SquareKilometres | Self::SquareMiles | Self::Hectares => 0,
+        }
+    }
+
+    /// Единица изменений за сутки и неделю: в тысячах км² они почти всегда округлились бы к нулю.
+    #[must_use]
+    pub const fn change_unit(self) -> Self {
+        match self {
+            Self::ThousandSquareKilometres => Self::SquareKilometres,
+            unit => unit,
+        }
+    }
+
+    pub fn of_km2(self, km2: f64) -> f64 {
+        km2 / self.km2_per_unit()
+    }
+
+    /// Площадь в км² с подписью единицы: `116,3 тыс. км²`.
+    pub fn format(self, km2: f64, locale: NumberLocale) -> String {
+        format!(
+            "{} {}",
+            locale.format(self.of_km2(km2), self.decimals()),
+            self.label()
+        )
+    }
+
+    /// Изменение в км² со знаком и подписью; `None` — прочерк.
+    pub fn format_change(self, km2: Option<f64>, locale: NumberLocale) -> String {
+        km2.map_or_else(
+            || "—".to_string(),
+            |km2| {
+                format!(
+                    "{} {}",
+                    locale.format_signed(self.of_km2(km2), self.decimals()),
+                    self.label()
+                )
+            },
+        )
+    }
+
+    pub fn per_day_label(self) -> String {
+        format!("{}/сутки", self.label())
+    }
+
+    /// Формат значения `%{y:…}` в подсказках Plotly.
+    pub const fn hover_format(self) -> &'static str {
+        match self.decimals() {
+            0 => ",.0f",
+            _ => ".1f",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AreaUnit;
+    use crate::NumberLocale;
+
+    #[test]
+    fn units_convert_from_km2_and_keep_changes_readable() {
+        let ru = NumberLocale::Ru;
+        assert_eq!(
+            AreaUnit::ThousandSquareKilometres.format(116_320.0, ru),
+            "116,3 тыс. км²"
+        );
+        assert_eq!(AreaUnit::Hectares.format(1.5, ru), "150 га");
+        assert_eq!(
+            AreaUnit::SquareMiles.format(2.589_988_110_336, ru),
+            "1 кв. миль"
+        );
+        assert_eq!(
+            AreaUnit::ThousandSquareKilometres
+                .change_unit()
+                .format_change(Some(12.4), ru),
+            "+12 км²"
+        );
+        assert_eq!(AreaUnit::Hectares.format_change(None, ru), "—");
+        for unit in AreaUnit::ALL {
+            let parsed: AreaUnit = serde_json::from_value(serde_json::json!(unit.key()))
+                .expect("key should parse back");
+            assert_eq!(parsed, unit);
+        }
+    }
+}
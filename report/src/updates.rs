@@ -0,0 +1,115 @@
+//! Страница `updates.html`: журнал сборок со сводкой поступивших данных.
+
+use chrono::{DateTime, Utc};
+use maud::{DOCTYPE, PreEscaped, html};
+
+use super::page_config::PageConfig;
+use super::style;
+use super::units::AreaUnit;
+use crate::constants::DATE_FORMAT;
+use rua_core::updates::BuildRecord;
+
+const PAGE_TITLE: &str = "Журнал обновлений данных";
+const PAGE_SUBTITLE: &str =
+    "Каждая сборка отчёта: новые срезы, изменение площади и пересмотры прошлых дней.";
+const BUILT_AT_FORMAT: &str = "%Y-%m-%d %H:%M UTC";
+/// Сколько пересмотренных дат показывать в строке, остальные сворачиваются в счётчик.
+const MAX_REVISED_DAYS_SHOWN: usize = 5;
+const STYLE_PLACEHOLDER: &str = "/*rua:updates-css*/";
+
+/// Журнал от новых сборок к старым.
+pub fn render_updates_page(
+    builds: &[BuildRecord],
+    home_href: &str,
+    page_config: &PageConfig,
+    generated_at: DateTime<Utc>,
+) -> String {
+    let page = html! {
+        (DOCTYPE)
+        html lang="ru" {
+            head {
+                meta charset="utf-8";
+                meta name="viewport" content="width=device-width, initial-scale=1";
+                meta name="robots" content="noindex";
+                title { (PAGE_TITLE) }
+                style { (PreEscaped(STYLE_PLACEHOLDER)) }
+                @if let Some(analytics_html) = &page_config.analytics_html {
+                    (PreEscaped(analytics_html))
+                }
+            }
+            body {
+                div class="page" {
+                    header class="hero" {
+                        div {
+                            h1 class="title" { (PAGE_TITLE) }
+                            p class="subtitle" { (PAGE_SUBTITLE) }
+                        }
+                    }
+                    section class="table-card" {
+                        table class="ratio-table" {
+                            thead {
+                                tr {
+                                    th { "Сборка" }
+                                    th { "Последний день" }
+                                    th { "Новых срезов" }
+                                    th { "Изменение" }
+                                    th { "Пересмотры" }
+                                }
+                            }
+                            tbody {
+                                @for build in builds.iter().rev() {
+                                    tr {
+                                        td { (build.built_at.format(BUILT_AT_FORMAT)) }
+                                        td { (build.latest_date.format(DATE_FORMAT)) }
+                                        td { (build.new_snapshots) }
+                                        td {
+                                            (AreaUnit::SquareKilometres.format_change(build.net_change_km2, page_config.locale))
+                                        }
+                                        td { (format_revisions(build)) }
+                                    }
+                                }
+                            }
+                        }
+                        p class="ratio-note" {
+                            "Пересмотр — день, значение которого изменилось по сравнению с прошлой сборкой "
+                            "не меньше чем на 1 км²."
+                        }
+                    }
+                    footer {
+                        "Сгенерировано: " (generated_at.format(BUILT_AT_FORMAT)) " · "
+                        a href=(home_href) { "К графику" }
+                    }
+                }
+            }
+        }
+    };
+    let page = page.into_string();
+    let styles = style::page_styles(&page);
+    page.replacen(
+        STYLE_PLACEHOLDER,
+        &format!("{}{}", styles.critical, styles.deferred),
+        1,
+    )
+}
+
+fn format_revisions(build: &BuildRecord) -> String {
+    if build.revised_days.is_empty() {
+        return "—".to_string();
+    }
+    let shown = build
+        .revised_days
+        .iter()
+        .take(MAX_REVISED_DAYS_SHOWN)
+        .map(|date| date.format(DATE_FORMAT).to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let hidden = build
+        .revised_days
+        .len()
+        .saturating_sub(MAX_REVISED_DAYS_SHOWN);
+    if hidden == 0 {
+        shown
+    } else {
+        format!("{shown} и ещё {hidden}")
+    }
+}
@@ -0,0 +1,347 @@
+//! Выгрузка в Excel: дневной ряд, прогноз, итоги по годам и соотношения с площадями стран.
+
+use std::error::Error;
+use std::path::Path;
+
+use chrono::{Datelike, NaiveDate};
+use rust_xlsxwriter::{ExcelDateTime, Format, FormatAlign, FormatBorder, Workbook, Worksheet};
+
+use super::ForecastOverlay;
+use super::data::{UNFRIENDLY_COUNTRIES, US_STATES};
+use super::kernel::daily_change_series;
+use super::smoothing::SmoothingSpec;
+use crate::constants::{AREA_THOUSANDS_DIVISOR, DATE_FORMAT};
+use crate::series::{
+    AreaBuckets, InterpolationStrategy, OccupiedUnspecifiedSeries,
+    build_occupied_and_unspecified_series_with,
+};
+
+const SHEET_SERIES: &str = "Ряд";
+const SHEET_FORECAST: &str = "Прогноз";
+const SHEET_YEARS: &str = "По годам";
+const SHEET_RATIOS: &str = "Соотношения";
+const NUM_FORMAT_DATE: &str = "yyyy-mm-dd";
+const NUM_FORMAT_KM2: &str = "#,##0";
+const NUM_FORMAT_CHANGE: &str = "+#,##0;-#,##0;0";
+const NUM_FORMAT_THOUSAND_KM2: &str = "#,##0.0";
+const NUM_FORMAT_RATIO: &str = "0.00\"x\"";
+const HEADER_BACKGROUND: u32 = 0x00E8_EEF6;
+const COLUMN_WIDTH_DATE: f64 = 12.0;
+const COLUMN_WIDTH_NUMBER: f64 = 16.0;
+const COLUMN_WIDTH_NAME: f64 = 28.0;
+
+/// Итоги календарного года по дневному ряду (км²).
+#[derive(Clone, Debug, PartialEq)]
+struct YearRow {
+    year: i32,
+    start_km2: f64,
+    end_km2: f64,
+    days: u32,
+}
+
+impl YearRow {
+    fn change_km2(&self) -> f64 {
+        self.end_km2 - self.start_km2
+    }
+
+    /// Среднее суточное изменение; для года из одного дня — ноль.
+    fn daily_change_km2(&self) -> f64 {
+        if self.days > 1 {
+            self.change_km2() / f64::from(self.days - 1)
+        } else {
+            0.0
+        }
+    }
+}
+
+struct Formats {
+    header: Format,
+    date: Format,
+    km2: Format,
+    change: Format,
+    thousand_km2: Format,
+    ratio: Format,
+}
+
+impl Formats {
+    fn new() -> Self {
+        Self {
+            header: Format::new()
+                .set_bold()
+                .set_align(FormatAlign::Center)
+                .set_text_wrap()
+                .set_background_color(HEADER_BACKGROUND)
+                .set_border_bottom(FormatBorder::Thin),
+            date: Format::new().set_num_format(NUM_FORMAT_DATE),
+            km2: Format::new().set_num_format(NUM_FORMAT_KM2),
+            change: Format::new().set_num_format(NUM_FORMAT_CHANGE),
+            thousand_km2: Format::new().set_num_format(NUM_FORMAT_THOUSAND_KM2),
+            ratio: Format::new().set_num_format(NUM_FORMAT_RATIO),
+        }
+    }
+}
+
+/// Собирает книгу Excel и сохраняет её в `output_xlsx`.
+pub fn write_workbook(
+    buckets: &AreaBuckets,
+    forecast: Option<&ForecastOverlay>,
+    interpolation: InterpolationStrategy,
+    smoothing: SmoothingSpec,
+    output_xlsx: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let series = build_occupied_and_unspecified_series_with(buckets, interpolation)?;
+    let formats = Formats::new();
+    let mut workbook = Workbook::new();
+    write_series_sheet(&mut workbook, &formats, &series, smoothing)?;
+    if let Some(forecast) = forecast {
+        write_forecast_sheet(&mut workbook, &formats, forecast)?;
+    }
+    write_years_sheet(
+        &mut workbook,
+        &formats,
+        &yearly_rows(&series.dates, &series.occupied),
+    )?;
+    let latest_km2 = series.occupied.last().copied().unwrap_or_default();
+    let forecast_km2 = forecast
+        .and_then(|forecast| forecast.mean.last())
+        .map(|mean| mean * AREA_THOUSANDS_DIVISOR);
+    write_ratios_sheet(&mut workbook, &formats, latest_km2, forecast_km2)?;
+
+    if let Some(parent) = output_xlsx.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    workbook.save(output_xlsx)?;
+    Ok(())
+}
+
+fn write_series_sheet(
+    workbook: &mut Workbook,
+    formats: &Formats,
+    series: &OccupiedUnspecifiedSeries,
+    smoothing: SmoothingSpec,
+) -> Result<(), Box<dyn Error>> {
+    let sheet = workbook.add_worksheet().set_name(SHEET_SERIES)?;
+    write_header(
+        sheet,
+        formats,
+        &[
+            ("Дата", COLUMN_WIDTH_DATE),
+            ("Занято, км²", COLUMN_WIDTH_NUMBER),
+            ("Серая зона, км²", COLUMN_WIDTH_NUMBER),
+            ("Изменение за сутки, км²", COLUMN_WIDTH_NUMBER),
+            ("Есть срез", COLUMN_WIDTH_DATE),
+            ("Сглаженное изменение, км²", COLUMN_WIDTH_NUMBER),
+        ],
+    )?;
+    let smoothed = smoothing.apply(&daily_change_series(&series.occupied));
+    let mut previous = None;
+    for (row, idx) in (1..).zip(0..series.dates.len()) {
+        let occupied = series.occupied[idx];
+        write_date(sheet, row, 0, series.dates[idx], formats)?;
+        sheet.write_number_with_format(row, 1, occupied, &formats.km2)?;
+        sheet.write_number_with_format(row, 2, series.unspecified[idx], &formats.km2)?;
+        if let Some(previous) = previous {
+            sheet.write_number_with_format(row, 3, occupied - previous, &formats.change)?;
+        }
+        sheet.write_string(
+            row,
+            4,
+            if series.measured[idx] {
+                "да"
+            } else {
+                "нет"
+            },
+        )?;
+        if let Some(smoothed) = smoothed[idx] {
+            sheet.write_number_with_format(row, 5, smoothed, &formats.change)?;
+        }
+        previous = Some(occupied);
+    }
+    finish_table(sheet, series.dates.len(), 5)
+}
+
+fn write_forecast_sheet(
+    workbook: &mut Workbook,
+    formats: &Formats,
+    forecast: &ForecastOverlay,
+) -> Result<(), Box<dyn Error>> {
+    let sheet = workbook.add_worksheet().set_name(SHEET_FORECAST)?;
+    write_header(
+        sheet,
+        formats,
+        &[
+            ("Дата", COLUMN_WIDTH_DATE),
+            ("Среднее, тыс. км²", COLUMN_WIDTH_NUMBER),
+            ("Нижняя граница 95%, тыс. км²", COLUMN_WIDTH_NUMBER),
+            ("Верхняя граница 95%, тыс. км²", COLUMN_WIDTH_NUMBER),
+        ],
+    )?;
+    for (row, idx) in (1..).zip(0..forecast.dates.len()) {
+        let date = NaiveDate::parse_from_str(&forecast.dates[idx], DATE_FORMAT)?;
+        write_date(sheet, row, 0, date, formats)?;
+        for (col, values) in (1..).zip([&forecast.mean, &forecast.lower, &forecast.upper]) {
+            sheet.write_number_with_format(row, col, values[idx], &formats.thousand_km2)?;
+        }
+    }
+    finish_table(sheet, forecast.dates.len(), 3)
+}
+
+fn write_years_sheet(
+    workbook: &mut Workbook,
+    formats: &Formats,
+    years: &[YearRow],
+) -> Result<(), Box<dyn Error>> {
+    let sheet = workbook.add_worksheet().set_name(SHEET_YEARS)?;
+    write_header(
+        sheet,
+        formats,
+        &[
+            ("Год", COLUMN_WIDTH_DATE),
+            ("На начало, км²", COLUMN_WIDTH_NUMBER),
+            ("На конец, км²", COLUMN_WIDTH_NUMBER),
+            ("Изменение, км²", COLUMN_WIDTH_NUMBER),
+            ("В среднем за сутки, км²", COLUMN_WIDTH_NUMBER),
+            ("Дней в ряду", COLUMN_WIDTH_DATE),
+        ],
+    )?;
+    for (row, year) in (1..).zip(years) {
+        sheet.write_number(row, 0, year.year)?;
+        sheet.write_number_with_format(row, 1, year.start_km2, &formats.km2)?;
+        sheet.write_number_with_format(row, 2, year.end_km2, &formats.km2)?;
+        sheet.write_number_with_format(row, 3, year.change_km2(), &formats.change)?;
+        sheet.write_number_with_format(row, 4, year.daily_change_km2(), &formats.change)?;
+        sheet.write_number(row, 5, year.days)?;
+    }
+    finish_table(sheet, years.len(), 5)
+}
+
+fn write_ratios_sheet(
+    workbook: &mut Workbook,
+    formats: &Formats,
+    latest_km2: f64,
+    forecast_km2: Option<f64>,
+) -> Result<(), Box<dyn Error>> {
+    let sheet = workbook.add_worksheet().set_name(SHEET_RATIOS)?;
+    write_header(
+        sheet,
+        formats,
+        &[
+            ("Территория", COLUMN_WIDTH_NAME),
+            ("Группа", COLUMN_WIDTH_NUMBER),
+            ("Площадь, км²", COLUMN_WIDTH_NUMBER),
+            ("Соотношение", COLUMN_WIDTH_NUMBER),
+            ("Соотношение по прогнозу", COLUMN_WIDTH_NUMBER),
+        ],
+    )?;
+    let territories = UNFRIENDLY_COUNTRIES
+        .iter()
+        .map(|(name, area, _)| (*name, "Страна", *area))
+        .chain(
+            US_STATES
+                .iter()
+                .map(|(name, area, _)| (*name, "Штат США", *area)),
+        )
+        .collect::<Vec<_>>();
+    for (row, (name, group, area)) in (1..).zip(&territories) {
+        sheet.write_string(row, 0, *name)?;
+        sheet.write_string(row, 1, *group)?;
+        sheet.write_number_with_format(row, 2, *area, &formats.km2)?;
+        sheet.write_number_with_format(row, 3, latest_km2 / area, &formats.ratio)?;
+        if let Some(forecast_km2) = forecast_km2 {
+            sheet.write_number_with_format(row, 4, forecast_km2 / area, &formats.ratio)?;
+        }
+    }
+    finish_table(sheet, territories.len(), 4)
+}
+
+fn write_header(
+    sheet: &mut Worksheet,
+    formats: &Formats,
+    columns: &[(&str, f64)],
+) -> Result<(), Box<dyn Error>> {
+    for (col, (title, width)) in (0..).zip(columns) {
+        sheet.write_string_with_format(0, col, *title, &formats.header)?;
+        sheet.set_column_width(col, *width)?;
+    }
+    Ok(())
+}
+
+/// Закрепляет заголовок и включает автофильтр по заполненной области.
+fn finish_table(sheet: &mut Worksheet, rows: usize, last_col: u16) -> Result<(), Box<dyn Error>> {
+    sheet.set_freeze_panes(1, 0)?;
+    if rows > 0 {
+        sheet.autofilter(0, 0, u32::try_from(rows)?, last_col)?;
+    }
+    Ok(())
+}
+
+fn write_date(
+    sheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    date: NaiveDate,
+    formats: &Formats,
+) -> Result<(), Box<dyn Error>> {
+    let date = ExcelDateTime::from_ymd(
+        u16::try_from(date.year())?,
+        u8::try_from(date.month())?,
+        u8::try_from(date.day())?,
+    )?;
+    sheet.write_datetime_with_format(row, col, &date, &formats.date)?;
+    Ok(())
+}
+
+fn yearly_rows(dates: &[NaiveDate], occupied: &[f64]) -> Vec<YearRow> {
+    let mut rows: Vec<YearRow> = Vec::new();
+    for (date, value) in dates.iter().zip(occupied) {
+        match rows.last_mut() {
+            Some(row) if row.year == date.year() => {
+                row.end_km2 = *value;
+                row.days += 1;
+            }
+            _ => rows.push(YearRow {
+                year: date.year(),
+                start_km2: *value,
+                end_km2: *value,
+                days: 1,
+            }),
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{YearRow, yearly_rows};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn yearly_rows_span_calendar_years() {
+        let start = NaiveDate::from_ymd_opt(2023, 12, 30).expect("valid date");
+        let dates = (0..4)
+            .map(|offset| start + chrono::Duration::days(offset))
+            .collect::<Vec<_>>();
+        let rows = yearly_rows(&dates, &[100.0, 110.0, 130.0, 160.0]);
+
+        assert_eq!(
+            rows,
+            [
+                YearRow {
+                    year: 2023,
+                    start_km2: 100.0,
+                    end_km2: 110.0,
+                    days: 2,
+                },
+                YearRow {
+                    year: 2024,
+                    start_km2: 130.0,
+                    end_km2: 160.0,
+                    days: 2,
+                },
+            ]
+        );
+        assert!((rows[1].daily_change_km2() - 30.0).abs() < 1e-12);
+    }
+}
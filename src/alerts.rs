@@ -0,0 +1,187 @@
+//! Правила оповещений (`[[alerts]]`): проверяются после каждого обновления данных.
+//!
+//! Сработавшие правила уходят в уведомления мессенджеров вместе с обычной сводкой и
+//! записываются в манифест сборки `build.json`; без `[notifications]` они только пишутся в лог.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::constants::AREA_THOUSANDS_DIVISOR;
+use crate::report::ForecastOverlay;
+
+/// Что проверяет правило.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlertCondition {
+    /// Суточное изменение больше `above_km2` в каждом из последних `days` дней.
+    DailyChange { above_km2: f64, days: usize },
+    /// Верхняя граница 95% интервала прогноза доходит до `km2`, а текущая площадь ещё ниже.
+    ForecastUpper { km2: f64 },
+    /// Последний срез источника старше `hours` часов.
+    Stale { hours: u32 },
+}
+
+/// Правило с подписью для уведомлений и манифеста.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlertRule {
+    pub name: String,
+    pub condition: AlertCondition,
+}
+
+impl AlertCondition {
+    /// Подпись правила, если в конфиге не задано `name`.
+    pub fn default_name(&self) -> String {
+        match self {
+            Self::DailyChange { above_km2, days } => {
+                format!("Суточное изменение > {above_km2} км² {days} дн. подряд")
+            }
+            Self::ForecastUpper { km2 } => format!("Прогноз достигает {km2} км²"),
+            Self::Stale { hours } => format!("Нет свежих данных {hours} ч"),
+        }
+    }
+}
+
+/// Сработавшее правило.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AlertMatch {
+    pub rule: String,
+    pub message: String,
+}
+
+/// Данные обновления, по которым проверяются правила.
+pub struct AlertInput<'a> {
+    /// Дневной ряд занятой площади, км².
+    pub dates: &'a [NaiveDate],
+    pub occupied: &'a [f64],
+    pub forecast: Option<&'a ForecastOverlay>,
+    pub latest_snapshot: Option<DateTime<Utc>>,
+    pub now: DateTime<Utc>,
+}
+
+/// Сработавшие правила в порядке конфига.
+pub fn evaluate(rules: &[AlertRule], input: &AlertInput) -> Vec<AlertMatch> {
+    rules
+        .iter()
+        .filter_map(|rule| {
+            let message = match rule.condition {
+                AlertCondition::DailyChange { above_km2, days } => {
+                    daily_change(input, above_km2, days)
+                }
+                AlertCondition::ForecastUpper { km2 } => forecast_upper(input, km2),
+                AlertCondition::Stale { hours } => stale(input, hours),
+            }?;
+            Some(AlertMatch {
+                rule: rule.name.clone(),
+                message,
+            })
+        })
+        .collect()
+}
+
+fn daily_change(input: &AlertInput, above_km2: f64, days: usize) -> Option<String> {
+    let changes = input
+        .occupied
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .collect::<Vec<_>>();
+    if days == 0 {
+        return None;
+    }
+    let streak = changes.get(changes.len().checked_sub(days)?..)?;
+    if streak.iter().any(|change| *change <= above_km2) {
+        return None;
+    }
+    let latest = streak.last()?;
+    Some(format!(
+        "изменение выше {above_km2} км² {days} дн. подряд, за {}: {latest:+.0} км²",
+        input.dates.last()?
+    ))
+}
+
+fn forecast_upper(input: &AlertInput, km2: f64) -> Option<String> {
+    let forecast = input.forecast?;
+    if input.occupied.last().is_none_or(|latest| *latest >= km2) {
+        return None;
+    }
+    let idx = forecast
+        .upper
+        .iter()
+        .position(|upper| upper * AREA_THOUSANDS_DIVISOR >= km2)?;
+    Some(format!(
+        "верхняя граница прогноза достигает {km2} км² к {}",
+        forecast.dates.get(idx)?
+    ))
+}
+
+fn stale(input: &AlertInput, hours: u32) -> Option<String> {
+    let latest = input.latest_snapshot?;
+    let age = input.now.signed_duration_since(latest).num_hours();
+    (age > i64::from(hours)).then(|| {
+        format!(
+            "последний срез {} — {age} ч назад",
+            latest.format("%Y-%m-%d %H:%M UTC")
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, NaiveDate};
+
+    use super::{AlertCondition, AlertInput, AlertRule, evaluate};
+    use crate::report::ForecastOverlay;
+    use crate::series::Granularity;
+
+    #[test]
+    fn rules_match_streaks_crossings_and_stale_data() {
+        let dates = (1..=5)
+            .map(|day| NaiveDate::from_ymd_opt(2025, 3, day).expect("valid date"))
+            .collect::<Vec<_>>();
+        let occupied = [100_000.0, 100_050.0, 100_200.0, 100_350.0, 100_500.0];
+        let forecast = ForecastOverlay {
+            dates: vec!["2025-03-06".to_string(), "2025-03-07".to_string()],
+            mean: vec![100.6, 100.7],
+            lower: vec![100.5, 100.5],
+            upper: vec![100.8, 101.2],
+            granularity: Granularity::Day,
+            smoothed: None,
+            horizons: Vec::new(),
+            training: None,
+            diagnostics: None,
+            stale: None,
+        };
+        let now = DateTime::from_timestamp(1_741_219_200, 0).expect("valid timestamp");
+        let input = AlertInput {
+            dates: &dates,
+            occupied: &occupied,
+            forecast: Some(&forecast),
+            latest_snapshot: Some(now - chrono::Duration::hours(50)),
+            now,
+        };
+        let rule = |condition: AlertCondition| AlertRule {
+            name: condition.default_name(),
+            condition,
+        };
+        let rules = [
+            rule(AlertCondition::DailyChange {
+                above_km2: 100.0,
+                days: 3,
+            }),
+            rule(AlertCondition::DailyChange {
+                above_km2: 100.0,
+                days: 4,
+            }),
+            rule(AlertCondition::ForecastUpper { km2: 101_000.0 }),
+            rule(AlertCondition::ForecastUpper { km2: 90_000.0 }),
+            rule(AlertCondition::Stale { hours: 48 }),
+            rule(AlertCondition::Stale { hours: 72 }),
+        ];
+        let matched = evaluate(&rules, &input);
+        assert_eq!(
+            matched.iter().map(|alert| &alert.rule).collect::<Vec<_>>(),
+            [&rules[0].name, &rules[2].name, &rules[4].name]
+        );
+        assert!(matched[0].message.contains("+150 км²"));
+        assert!(matched[1].message.ends_with("2025-03-07"));
+        assert!(matched[2].message.contains("50 ч назад"));
+    }
+}
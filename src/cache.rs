@@ -0,0 +1,117 @@
+//! Кэш сборки отчёта: манифест `.rua-cache` с хэшами входов и выходов рядом с HTML.
+//! Если данные и настройки не менялись, а выходы на месте, пересборку можно пропустить.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::integrity::sha256_hex;
+
+/// Имя манифеста; лежит в каталоге сайта, но не публикуется.
+pub const MANIFEST_FILE_NAME: &str = ".rua-cache";
+const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    key: String,
+    /// Путь выхода → SHA-256 содержимого на момент сборки.
+    outputs: BTreeMap<PathBuf, String>,
+}
+
+/// Ключ сборки и место его хранения.
+#[derive(Debug)]
+pub struct BuildCache {
+    manifest_path: PathBuf,
+    key: String,
+}
+
+impl BuildCache {
+    /// Считает ключ по версии, настройкам сборки (`settings`) и содержимому входных файлов.
+    pub fn new(output_html: &Path, settings: &str, inputs: &[&Path]) -> Result<Self, String> {
+        let mut material = format!("{APP_VERSION}\n{settings}\n");
+        for input in inputs {
+            let bytes = fs::read(input)
+                .map_err(|err| format!("Failed to read {}: {err}", input.display()))?;
+            material.push_str(&sha256_hex(&bytes));
+            material.push('\n');
+        }
+        Ok(Self {
+            manifest_path: output_html.with_file_name(MANIFEST_FILE_NAME),
+            key: sha256_hex(material.as_bytes()),
+        })
+    }
+
+    /// Ключ совпадает с сохранённым, и все выходы прошлой сборки не изменились.
+    pub fn is_fresh(&self) -> bool {
+        let Ok(raw) = fs::read_to_string(&self.manifest_path) else {
+            return false;
+        };
+        let Ok(manifest) = serde_json::from_str::<Manifest>(&raw) else {
+            return false;
+        };
+        manifest.key == self.key
+            && !manifest.outputs.is_empty()
+            && manifest.outputs.iter().all(|(path, expected)| {
+                fs::read(path).is_ok_and(|bytes| sha256_hex(&bytes) == *expected)
+            })
+    }
+
+    /// Записывает манифест после успешной сборки.
+    pub fn store(&self, outputs: &[PathBuf]) -> Result<(), String> {
+        let outputs = outputs
+            .iter()
+            .map(|path| {
+                fs::read(path)
+                    .map(|bytes| (path.clone(), sha256_hex(&bytes)))
+                    .map_err(|err| format!("Failed to read {}: {err}", path.display()))
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+        let manifest = Manifest {
+            key: self.key.clone(),
+            outputs,
+        };
+        let raw = serde_json::to_string_pretty(&manifest)
+            .map_err(|err| format!("Failed to serialize build cache: {err}"))?;
+        fs::write(&self.manifest_path, raw)
+            .map_err(|err| format!("Failed to write {}: {err}", self.manifest_path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildCache;
+
+    #[test]
+    fn cache_is_fresh_only_for_unchanged_inputs_and_outputs() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let dir = temp.path();
+        let input = dir.join("history.csv");
+        let output = dir.join("index.html");
+        std::fs::write(&input, "time_index,hash\n1,a\n").expect("input should be written");
+        std::fs::write(&output, "<html></html>").expect("output should be written");
+
+        let cache = BuildCache::new(&output, "settings", &[&input]).expect("key should build");
+        assert!(!cache.is_fresh());
+        cache
+            .store(std::slice::from_ref(&output))
+            .expect("manifest should be stored");
+        assert!(cache.is_fresh());
+        assert!(
+            !BuildCache::new(&output, "other settings", &[&input])
+                .expect("key should build")
+                .is_fresh()
+        );
+
+        std::fs::write(&output, "<html>edited</html>").expect("output should be rewritten");
+        let output_changed = cache.is_fresh();
+        std::fs::write(&input, "time_index,hash\n1,a\n2,b\n").expect("input should be rewritten");
+        let input_changed = BuildCache::new(&output, "settings", &[&input])
+            .expect("key should build")
+            .is_fresh();
+
+        assert!(!output_changed);
+        assert!(!input_changed);
+    }
+}
@@ -1,4 +0,0 @@
-//! Общие константы форматирования, чтобы не дублировать строки и единицы измерения.
-
-pub const DATE_FORMAT: &str = "%Y-%m-%d";
-pub const AREA_THOUSANDS_DIVISOR: f64 = 1000.0;
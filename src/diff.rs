@@ -0,0 +1,149 @@
+//! Разница двух опубликованных сводок (`rua report-diff OLD NEW`): что изменилось в площади,
+//! суточной и недельной динамике и прогнозе. Текст предназначен для уведомлений и сообщений
+//! коммитов в CI. Сводка — JSON в формате ответа `/v1/summary` режима `serve`.
+
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::constants::AREA_THOUSANDS_DIVISOR;
+
+/// Изменения меньше этого порога (км²) считаются шумом округления.
+const AREA_EPSILON_KM2: f64 = 0.5;
+
+#[derive(Debug, Deserialize)]
+pub struct Snapshot {
+    latest_date: String,
+    occupied_km2: f64,
+    ukraine_percent: f64,
+    #[serde(default)]
+    daily_change_km2: Option<f64>,
+    #[serde(default)]
+    weekly_change_km2: Option<f64>,
+    #[serde(default)]
+    forecast: Option<SnapshotForecast>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SnapshotForecast {
+    date: String,
+    mean_km2: f64,
+    lower_km2: f64,
+    upper_km2: f64,
+}
+
+pub fn load(path: &Path) -> Result<Snapshot, String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    serde_json::from_str(&raw).map_err(|err| format!("Invalid summary {}: {err}", path.display()))
+}
+
+/// Построчное описание изменений от `old` к `new`.
+pub fn describe(old: &Snapshot, new: &Snapshot) -> String {
+    let area_delta = new.occupied_km2 - old.occupied_km2;
+    let forecast_moved = match (&old.forecast, &new.forecast) {
+        (Some(old), Some(new)) => {
+            old.date != new.date || (new.mean_km2 - old.mean_km2).abs() >= AREA_EPSILON_KM2
+        }
+        (None, None) => false,
+        _ => true,
+    };
+    if old.latest_date == new.latest_date && area_delta.abs() < AREA_EPSILON_KM2 && !forecast_moved
+    {
+        return format!("Без изменений: данные на {}", new.latest_date);
+    }
+
+    let forecast = match (&old.forecast, &new.forecast) {
+        (Some(old), Some(new)) => format!(
+            "Прогноз: {} на {} → {} на {} ({}), 95%: {}–{} тыс. км²",
+            thousands(old.mean_km2),
+            old.date,
+            thousands(new.mean_km2),
+            new.date,
+            signed_km2(Some(new.mean_km2 - old.mean_km2)),
+            thousands(new.lower_km2),
+            thousands(new.upper_km2)
+        ),
+        (None, Some(new)) => format!(
+            "Прогноз: {} тыс. км² на {} (новый)",
+            thousands(new.mean_km2),
+            new.date
+        ),
+        (Some(_), None) => "Прогноз: больше не публикуется".to_string(),
+        (None, None) => "Прогноз: нет".to_string(),
+    };
+    [
+        if old.latest_date == new.latest_date {
+            format!("Дата: {}", new.latest_date)
+        } else {
+            format!("Дата: {} → {}", old.latest_date, new.latest_date)
+        },
+        format!(
+            "Площадь: {} → {} тыс. км² ({})",
+            thousands(old.occupied_km2),
+            thousands(new.occupied_km2),
+            signed_km2(Some(area_delta))
+        ),
+        format!(
+            "Доля Украины: {:.2}% → {:.2}% ({:+.2} п. п.)",
+            old.ukraine_percent,
+            new.ukraine_percent,
+            new.ukraine_percent - old.ukraine_percent
+        ),
+        format!(
+            "За сутки: {} → {}",
+            signed_km2(old.daily_change_km2),
+            signed_km2(new.daily_change_km2)
+        ),
+        format!(
+            "За 7 дней: {} → {}",
+            signed_km2(old.weekly_change_km2),
+            signed_km2(new.weekly_change_km2)
+        ),
+        forecast,
+    ]
+    .join("\n")
+}
+
+fn thousands(value_km2: f64) -> String {
+    format!("{:.1}", value_km2 / AREA_THOUSANDS_DIVISOR)
+}
+
+fn signed_km2(value: Option<f64>) -> String {
+    value.map_or_else(|| "—".to_string(), |value| format!("{value:+.0} км²"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Snapshot, describe};
+
+    fn snapshot(raw: &str) -> Snapshot {
+        serde_json::from_str(raw).expect("summary should parse")
+    }
+
+    #[test]
+    fn describes_area_and_forecast_shift() {
+        let old = snapshot(
+            r#"{"latest_date":"2024-03-01","occupied_km2":110000.0,"ukraine_percent":18.22,
+                "daily_change_km2":40.0,"weekly_change_km2":null,
+                "forecast":{"date":"2025-03-01","mean_km2":114000.0,"lower_km2":112000.0,"upper_km2":116000.0}}"#,
+        );
+        let new = snapshot(
+            r#"{"latest_date":"2024-03-02","occupied_km2":110120.0,"ukraine_percent":18.24,
+                "daily_change_km2":120.0,"weekly_change_km2":500.0,
+                "forecast":{"date":"2025-03-02","mean_km2":114260.0,"lower_km2":112100.0,"upper_km2":116400.0}}"#,
+        );
+
+        assert_eq!(
+            describe(&old, &new),
+            "Дата: 2024-03-01 → 2024-03-02\n\
+             Площадь: 110.0 → 110.1 тыс. км² (+120 км²)\n\
+             Доля Украины: 18.22% → 18.24% (+0.02 п. п.)\n\
+             За сутки: +40 км² → +120 км²\n\
+             За 7 дней: — → +500 км²\n\
+             Прогноз: 114.0 на 2025-03-01 → 114.3 на 2025-03-02 (+260 км²), 95%: 112.1–116.4 тыс. км²"
+        );
+        assert_eq!(describe(&new, &new), "Без изменений: данные на 2024-03-02");
+    }
+}
@@ -0,0 +1,210 @@
+//! `rua doctor`: проверка окружения перед запуском — доступность API (DNS, соединение и TLS),
+//! настройка прокси, права на запись в каталоги выгрузок и читаемость уже сохранённых данных.
+//! Каждая проверка сама по себе и заканчивается подсказкой, что делать при ошибке.
+
+use std::fs;
+use std::path::Path;
+
+use reqwest::{Client, Url};
+
+use crate::fetch::{self, ProxyConfig};
+use crate::model;
+use crate::series;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// Не мешает запуску, но стоит обратить внимание.
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Check {
+    pub name: String,
+    pub status: Status,
+    pub detail: String,
+}
+
+impl Check {
+    pub fn new(name: impl Into<String>, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+pub fn check_proxy(config: &ProxyConfig) -> Check {
+    match fetch::describe_proxy(config) {
+        Ok(Some(proxy)) => Check::new("proxy", Status::Ok, proxy),
+        Ok(None) => Check::new("proxy", Status::Ok, "not configured, connecting directly"),
+        Err(err) => Check::new(
+            "proxy",
+            Status::Fail,
+            format!(
+                "{err}; fix [fetch].proxy or the proxy environment variables, \
+                 otherwise downloads fall back to a direct connection"
+            ),
+        ),
+    }
+}
+
+/// Разрешает имя хоста `url`, затем делает запрос через `client` (с теми же прокси и
+/// таймаутами, что и загрузка). Через прокси DNS разрешает прокси, и его ошибка не фатальна.
+pub async fn check_api(client: &Client, url: &str, via_proxy: bool) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let Some((host, port)) = Url::parse(url).ok().and_then(|parsed| {
+        Some((
+            parsed.host_str()?.to_string(),
+            parsed.port_or_known_default()?,
+        ))
+    }) else {
+        return vec![Check::new(
+            "api",
+            Status::Fail,
+            format!("invalid URL {url}"),
+        )];
+    };
+    let dns = match tokio::net::lookup_host((host.as_str(), port)).await {
+        Ok(mut addresses) => addresses.next().map_or_else(
+            || Check::new("dns", Status::Fail, format!("{host} has no addresses")),
+            |address| Check::new("dns", Status::Ok, format!("{host} → {}", address.ip())),
+        ),
+        Err(err) => Check::new(
+            "dns",
+            if via_proxy {
+                Status::Warn
+            } else {
+                Status::Fail
+            },
+            format!("failed to resolve {host}: {err}; check the network and DNS settings"),
+        ),
+    };
+    checks.push(dns);
+
+    checks.push(match client.get(url).send().await {
+        Ok(response) if response.status().is_success() => Check::new(
+            "api",
+            Status::Ok,
+            format!("{url} answered {}", response.status()),
+        ),
+        Ok(response) => Check::new(
+            "api",
+            Status::Fail,
+            format!(
+                "{url} answered {}; the API may be down or blocking this address",
+                response.status()
+            ),
+        ),
+        Err(err) => Check::new("api", Status::Fail, describe_request_error(url, &err)),
+    });
+    checks
+}
+
+fn describe_request_error(url: &str, err: &reqwest::Error) -> String {
+    let hint = if err.is_timeout() {
+        "request timed out; raise [fetch].request_timeout_secs or check the proxy"
+    } else if err.is_connect() {
+        "connection or TLS handshake failed; check the firewall, proxy and system certificates"
+    } else {
+        "request failed"
+    };
+    // Причина reqwest лежит в цепочке `source`: там текст ошибки TLS или соединения.
+    let mut causes = Vec::new();
+    let mut source = std::error::Error::source(err);
+    while let Some(cause) = source {
+        causes.push(cause.to_string());
+        source = cause.source();
+    }
+    if causes.is_empty() {
+        format!("{url}: {hint}")
+    } else {
+        format!("{url}: {hint} ({})", causes.join(": "))
+    }
+}
+
+/// Можно ли писать в каталог `dir`: создаёт его при необходимости и пишет пробный файл.
+pub fn check_writable(dir: &Path) -> Check {
+    let name = format!("write {}", dir.display());
+    let probe = dir.join(format!(".rua-doctor-{}", std::process::id()));
+    match fs::create_dir_all(dir).and_then(|()| fs::write(&probe, b"")) {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            Check::new(name, Status::Ok, "writable")
+        }
+        Err(err) => Check::new(
+            name,
+            Status::Fail,
+            format!("{err}; create the directory or fix its permissions"),
+        ),
+    }
+}
+
+/// Читается ли CSV истории; отсутствующий файл не ошибка — его создаст загрузка.
+pub fn check_history(path: &Path) -> Check {
+    let name = format!("history {}", path.display());
+    if !path.exists() {
+        return Check::new(
+            name,
+            Status::Warn,
+            "not found; `download` or `run` will create it",
+        );
+    }
+    match series::load_area_buckets(path) {
+        Ok(buckets) => {
+            let latest = series::latest_time_index(path)
+                .ok()
+                .flatten()
+                .map_or_else(String::new, |latest| format!(", latest snapshot {latest}"));
+            Check::new(
+                name,
+                Status::Ok,
+                format!("{} days{latest}", buckets.day_count()),
+            )
+        }
+        Err(err) => Check::new(
+            name,
+            Status::Fail,
+            format!("{err}; re-download it with `mode = \"download\"`"),
+        ),
+    }
+}
+
+/// Читается ли CSV прогноза; отсутствующий файл не ошибка — его создаст `forecast` или `run`.
+pub fn check_forecast(path: &Path) -> Check {
+    let name = format!("forecast {}", path.display());
+    if !path.exists() {
+        return Check::new(
+            name,
+            Status::Warn,
+            "not found; `forecast` or `run` will create it",
+        );
+    }
+    match model::read_forecast_csv(path) {
+        Ok(forecast) => Check::new(name, Status::Ok, format!("{} points", forecast.dates.len())),
+        Err(err) => Check::new(
+            name,
+            Status::Fail,
+            format!("{err}; rebuild it with `mode = \"forecast\"`"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Status, check_forecast, check_history, check_writable};
+
+    #[test]
+    fn file_checks_treat_missing_data_as_warnings_and_garbage_as_failures() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let dir = temp.path();
+        assert_eq!(check_writable(&dir.join("out")).status, Status::Ok);
+        assert_eq!(check_history(&dir.join("missing.csv")).status, Status::Warn);
+
+        let garbage = dir.join("garbage.csv");
+        std::fs::write(&garbage, "not,a\nforecast").expect("file should be written");
+        assert_eq!(check_forecast(&garbage).status, Status::Fail);
+    }
+}
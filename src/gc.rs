@@ -0,0 +1,304 @@
+//! Политика хранения накопленных данных (`[retention]`): команда `rua gc` и очистка раз в
+//! заданный интервал в режиме `serve`.
+//!
+//! Архив сырых ответов за последние `raw_keep_days` дней хранится целиком, раньше — только
+//! последний срез каждого месяца (UTC): `rebuild-history` по такому архиву восстанавливает
+//! старую историю помесячно. Датированные архивы прогнозов (`<имя>-ГГГГ-ММ-ДД.csv`) старше срока
+//! сжимаются в `.csv.gz` — живые `history.csv` и выгрузки без даты в имени не трогаются, а
+//! копии удалённых историй, которыми давно не пользовались, удаляются и при следующей сборке
+//! скачиваются заново.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Utc};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+
+const RAW_ARCHIVE_SUFFIX: &str = ".json.gz";
+const FORECAST_EXTENSION: &str = "csv";
+const SECS_PER_DAY: u64 = 86_400;
+/// Длина `ГГГГ-ММ-ДД` в конце имени архива прогноза.
+const DATE_SUFFIX_LEN: usize = 10;
+
+/// Что и сколько хранить; каталоги без значения не трогаются.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetentionConfig {
+    /// Архив сырых ответов (`raw_archive_dir`).
+    pub raw_archive: Option<PathBuf>,
+    /// Сколько последних дней архива хранить без прореживания.
+    pub raw_keep_days: u32,
+    /// Каталог с датированными CSV прогнозов, которые сжимаются после
+    /// `compress_forecasts_after_days`.
+    pub forecast_dir: Option<PathBuf>,
+    pub compress_forecasts_after_days: u32,
+    /// Каталог копий удалённых историй (`.rua-remote`).
+    pub remote_cache: Option<PathBuf>,
+    /// Сколько дней хранить копию, которой не пользовались.
+    pub remote_cache_keep_days: u32,
+    /// Пауза между очистками в режиме `serve`; `None` — только командой `rua gc`.
+    pub interval: Option<Duration>,
+}
+
+/// Итог очистки; при `dry_run` — что было бы сделано.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GcSummary {
+    pub raw_removed: usize,
+    pub forecasts_compressed: usize,
+    pub cache_removed: usize,
+    /// Размер удалённых файлов (без выигрыша от сжатия), байт.
+    pub removed_bytes: u64,
+}
+
+impl GcSummary {
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} raw snapshots removed, {} forecasts compressed, {} cached copies removed ({:.1} MiB)",
+            self.raw_removed,
+            self.forecasts_compressed,
+            self.cache_removed,
+            mebibytes(self.removed_bytes)
+        )
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn mebibytes(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+/// Применяет политику на момент `now`; с `dry_run` только считает.
+pub fn collect(
+    config: &RetentionConfig,
+    now: SystemTime,
+    dry_run: bool,
+) -> Result<GcSummary, String> {
+    let mut summary = GcSummary::default();
+    if let Some(dir) = &config.raw_archive {
+        let cutoff = DateTime::<Utc>::from(now) - TimeDelta::days(config.raw_keep_days.into());
+        for path in thinned_raw_snapshots(dir, cutoff)? {
+            summary.removed_bytes += remove(&path, dry_run)?;
+            summary.raw_removed += 1;
+        }
+    }
+    if let Some(dir) = &config.forecast_dir {
+        for path in files_older_than(dir, now, config.compress_forecasts_after_days)? {
+            if is_dated_forecast_archive(&path) {
+                if !dry_run {
+                    compress(&path)?;
+                }
+                summary.forecasts_compressed += 1;
+            }
+        }
+    }
+    if let Some(dir) = &config.remote_cache {
+        for path in files_older_than(dir, now, config.remote_cache_keep_days)? {
+            summary.removed_bytes += remove(&path, dry_run)?;
+            summary.cache_removed += 1;
+        }
+    }
+    Ok(summary)
+}
+
+/// Повторяет очистку каждые `interval`, пока жив процесс; ошибки только в лог.
+pub async fn watch(config: RetentionConfig, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let current = config.clone();
+        match tokio::task::spawn_blocking(move || collect(&current, SystemTime::now(), false)).await
+        {
+            Ok(Ok(summary)) => tracing::info!(summary = %summary.to_text(), "Applied retention"),
+            Ok(Err(err)) => tracing::warn!(error = %err, "Failed to apply retention"),
+            Err(err) => tracing::warn!(error = %err, "Retention task panicked"),
+        }
+    }
+}
+
+/// Срезы архива старше `cutoff`, кроме последнего в каждом месяце.
+fn thinned_raw_snapshots(dir: &Path, cutoff: DateTime<Utc>) -> Result<Vec<PathBuf>, String> {
+    let mut months = BTreeMap::<(i32, u32), Vec<(DateTime<Utc>, PathBuf)>>::new();
+    for path in list_files(dir)? {
+        let Some(time) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(RAW_ARCHIVE_SUFFIX))
+            .and_then(|stem| stem.parse::<i64>().ok())
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        else {
+            continue;
+        };
+        if time < cutoff {
+            months
+                .entry((time.year(), time.month()))
+                .or_default()
+                .push((time, path));
+        }
+    }
+    Ok(months
+        .into_values()
+        .flat_map(|mut snapshots| {
+            snapshots.sort();
+            snapshots.pop();
+            snapshots.into_iter().map(|(_, path)| path)
+        })
+        .collect())
+}
+
+/// `<имя>-ГГГГ-ММ-ДД.csv`: только такие файлы — архивные срезы, остальные CSV могут быть живыми
+/// выгрузками или хранилищем.
+fn is_dated_forecast_archive(path: &Path) -> bool {
+    if !path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(FORECAST_EXTENSION))
+    {
+        return false;
+    }
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| {
+            stem.len()
+                .checked_sub(DATE_SUFFIX_LEN)
+                .map(|at| stem.split_at(at))
+        })
+        .is_some_and(|(name, date)| {
+            name.len() > 1
+                && name.ends_with('-')
+                && NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok()
+        })
+}
+
+/// Файлы каталога, не менявшиеся дольше `days` дней.
+fn files_older_than(dir: &Path, now: SystemTime, days: u32) -> Result<Vec<PathBuf>, String> {
+    let cutoff = now - Duration::from_secs(u64::from(days) * SECS_PER_DAY);
+    let mut old = Vec::new();
+    for path in list_files(dir)? {
+        let modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        if modified < cutoff {
+            old.push(path);
+        }
+    }
+    Ok(old)
+}
+
+/// Обычные файлы каталога; отсутствующий каталог — пустой список.
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(format!("Failed to read {}: {err}", dir.display())),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|err| format!("Failed to read {}: {err}", dir.display()))?
+            .path();
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn remove(path: &Path, dry_run: bool) -> Result<u64, String> {
+    let size = fs::metadata(path).map_or(0, |metadata| metadata.len());
+    if !dry_run {
+        fs::remove_file(path)
+            .map_err(|err| format!("Failed to remove {}: {err}", path.display()))?;
+    }
+    Ok(size)
+}
+
+/// Сжимает файл в `<имя>.gz` рядом с ним и удаляет исходник.
+fn compress(path: &Path) -> Result<(), String> {
+    let mut archive = path.as_os_str().to_owned();
+    archive.push(".gz");
+    let archive = PathBuf::from(archive);
+    let mut input =
+        File::open(path).map_err(|err| format!("Failed to open {}: {err}", path.display()))?;
+    let output = File::create(&archive)
+        .map_err(|err| format!("Failed to create {}: {err}", archive.display()))?;
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    std::io::copy(&mut input, &mut encoder)
+        .and_then(|_| encoder.finish().map(drop))
+        .map_err(|err| format!("Failed to write {}: {err}", archive.display()))?;
+    fs::remove_file(path).map_err(|err| format!("Failed to remove {}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use std::time::SystemTime;
+
+    use chrono::DateTime;
+
+    use super::{RetentionConfig, SECS_PER_DAY, collect};
+
+    #[test]
+    fn old_raw_snapshots_keep_the_last_per_month() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let dir = temp.path();
+        let raw = dir.join("raw");
+        let forecasts = dir.join("forecasts");
+        std::fs::create_dir_all(&raw).expect("raw dir");
+        std::fs::create_dir_all(&forecasts).expect("forecast dir");
+        let stamp = |time: &str| {
+            DateTime::parse_from_rfc3339(time)
+                .expect("valid time")
+                .timestamp()
+        };
+        for time in [
+            "2024-01-05T10:00:00Z",
+            "2024-01-20T10:00:00Z",
+            "2024-02-03T10:00:00Z",
+            "2024-03-01T10:00:00Z",
+            "2024-03-02T10:00:00Z",
+        ] {
+            std::fs::write(raw.join(format!("{}.json.gz", stamp(time))), b"{}").expect("snapshot");
+        }
+        std::fs::write(raw.join("notes.txt"), b"kept").expect("notes");
+        std::fs::write(forecasts.join("forecast-2024-03-01.csv"), b"date,mean\n")
+            .expect("forecast");
+        std::fs::write(forecasts.join("forecast.csv"), b"date,mean\n").expect("live export");
+        std::fs::write(forecasts.join("history.csv"), b"date,area\n").expect("history");
+
+        // Все срезы старше окна; прогноз записан только что, а «сейчас» — через двое суток.
+        let now = SystemTime::now() + Duration::from_secs(2 * SECS_PER_DAY);
+        let config = RetentionConfig {
+            raw_archive: Some(raw.clone()),
+            raw_keep_days: 30,
+            forecast_dir: Some(forecasts.clone()),
+            compress_forecasts_after_days: 1,
+            remote_cache: Some(dir.join("missing")),
+            remote_cache_keep_days: 30,
+            interval: None,
+        };
+        let dry = collect(&config, now, true).expect("dry run");
+        assert_eq!(dry.raw_removed, 2);
+        assert_eq!(dry.forecasts_compressed, 1);
+        assert!(forecasts.join("forecast-2024-03-01.csv").exists());
+
+        let summary = collect(&config, now, false).expect("gc");
+        let exists = |time| raw.join(format!("{}.json.gz", stamp(time))).exists();
+        let kept = [
+            exists("2024-01-05T10:00:00Z"),
+            exists("2024-01-20T10:00:00Z"),
+            exists("2024-02-03T10:00:00Z"),
+            exists("2024-03-02T10:00:00Z"),
+        ];
+        let compressed = forecasts.join("forecast-2024-03-01.csv.gz").exists();
+        let live = [
+            forecasts.join("forecast.csv").exists(),
+            forecasts.join("history.csv").exists(),
+        ];
+        assert_eq!(summary, dry);
+        assert_eq!(kept, [false, true, true, true]);
+        assert!(compressed);
+        assert_eq!(live, [true, true]);
+    }
+}
@@ -0,0 +1,161 @@
+//! Один запуск на каталог выгрузки: задание cron и ручной запуск не должны одновременно
+//! писать историю и файлы сайта.
+//!
+//! Запуск держит блокировку файла `.rua-instance` в каталоге выгрузки (`flock` или `LockFileEx`)
+//! и пишет в него свой PID и время старта, чтобы второй запуск мог сказать, кого он ждёт.
+//! Блокировку снимает система, даже если процесс упал, поэтому брошенный файл не мешает
+//! следующему запуску: непустой файл без блокировки — след упавшего запуска, о нём пишется
+//! предупреждение. Сам файл не удаляется: другой запуск мог уже открыть его и ждать.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{SecondsFormat, Utc};
+
+/// Имя файла блокировки; лежит в каталоге сайта, но не публикуется.
+pub const LOCK_FILE_NAME: &str = ".rua-instance";
+/// Как часто проверять, освободилась ли блокировка.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Блокировка каталога выгрузки; снимается, когда значение выходит из области видимости.
+#[derive(Debug)]
+pub struct InstanceLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Берёт блокировку `dir`. Если её держит другой запуск, ждёт не дольше `wait`, а без
+    /// `wait` сразу возвращает ошибку с PID и временем старта этого запуска.
+    pub async fn acquire(dir: &Path, wait: Option<Duration>) -> Result<Self, String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+        let path = dir.join(LOCK_FILE_NAME);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(|err| format!("Failed to open {}: {err}", path.display()))?;
+        let deadline = wait.map(|wait| tokio::time::Instant::now() + wait);
+        let mut announced = false;
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) => {
+                    let holder = read_holder(&mut file);
+                    match deadline {
+                        Some(deadline) if tokio::time::Instant::now() < deadline => {
+                            if !announced {
+                                tracing::info!(
+                                    lock = %path.display(),
+                                    holder,
+                                    "Waiting for another rua run to finish"
+                                );
+                                announced = true;
+                            }
+                            tokio::time::sleep(POLL_INTERVAL).await;
+                        }
+                        _ => {
+                            return Err(format!(
+                                "Another rua run ({holder}) is writing to {}; wait for it to finish or pass --wait-for-lock",
+                                dir.display()
+                            ));
+                        }
+                    }
+                }
+                Err(TryLockError::Error(err)) => {
+                    return Err(format!("Failed to lock {}: {err}", path.display()));
+                }
+            }
+        }
+        let previous = read_holder(&mut file);
+        if !previous.is_empty() {
+            tracing::warn!(
+                lock = %path.display(),
+                previous,
+                "Taking over a stale lock left by a run that did not finish"
+            );
+        }
+        let holder = format!(
+            "pid {}, started {}",
+            std::process::id(),
+            Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+        );
+        rewrite(&mut file, &holder)
+            .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+        Ok(Self { file, path })
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // Пустой файл — запуск закончился штатно; блокировка снимается при закрытии файла.
+        if let Err(err) = rewrite(&mut self.file, "") {
+            tracing::warn!(
+                lock = %self.path.display(),
+                error = %err,
+                "Failed to clear the instance lock"
+            );
+        }
+    }
+}
+
+/// Кто держит или держал блокировку: строка из файла, пустая для свободного файла.
+fn read_holder(file: &mut File) -> String {
+    let mut holder = String::new();
+    let _ = file
+        .rewind()
+        .and_then(|()| file.read_to_string(&mut holder));
+    holder.trim().to_string()
+}
+
+fn rewrite(file: &mut File, content: &str) -> std::io::Result<()> {
+    file.set_len(0)?;
+    file.rewind()?;
+    file.write_all(content.as_bytes())?;
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{InstanceLock, LOCK_FILE_NAME};
+
+    #[tokio::test]
+    async fn second_run_waits_or_fails_and_stale_locks_are_taken_over() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let dir = temp.path();
+        let lock_path = dir.join(LOCK_FILE_NAME);
+
+        let first = InstanceLock::acquire(dir, None).await.expect("first run");
+        let holder = std::fs::read_to_string(&lock_path).expect("holder is written");
+        assert!(holder.contains(&format!("pid {}", std::process::id())));
+        let busy = InstanceLock::acquire(dir, None).await;
+        let timed_out = InstanceLock::acquire(dir, Some(Duration::from_millis(600))).await;
+        drop(first);
+        assert_eq!(std::fs::read_to_string(&lock_path).expect("lock file"), "");
+        let err = busy.expect_err("second run must not start");
+        assert!(err.contains(&holder) && err.contains("--wait-for-lock"));
+        assert!(timed_out.is_err());
+
+        // Файл от упавшего запуска без блокировки не мешает.
+        std::fs::write(&lock_path, "pid 1, started 2024-05-01T00:00:00Z").expect("stale lock");
+        let taken = InstanceLock::acquire(dir, None).await;
+        let waited = {
+            let _taken = taken.expect("stale lock is taken over");
+            let dir = dir.to_path_buf();
+            let waiter = tokio::spawn(async move {
+                InstanceLock::acquire(&dir, Some(Duration::from_secs(10))).await
+            });
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            waiter
+        };
+        let waited = waited.await.expect("waiter task");
+        waited.expect("waiting run starts once the first one finishes");
+    }
+}
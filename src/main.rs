@@ -1,42 +1,188 @@
-mod constants;
-mod data;
-mod fetch;
-mod model;
-mod report;
-mod series;
-
-use chrono::NaiveDate;
-use clap::Parser;
+mod alerts;
+mod cache;
+mod diff;
+mod doctor;
+mod gc;
+mod instance;
+mod manifest;
+mod notify;
+mod publish;
+mod rebuild;
+mod remote;
+mod selfhost;
+#[cfg(feature = "serve")]
+mod serve;
+mod tune;
+
+use rua_core::{
+    constants, data, exclusions, fetch, front, integrity, interrupt, methodology, model, series,
+    source, store, updates,
+};
+use rua_report as report;
+
+use chrono::{DateTime, NaiveDate, Utc, Weekday};
+use clap::{Parser, Subcommand};
 use flate2::Compression;
+use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use serde::{Deserialize, Deserializer};
 use std::fmt;
 use std::fs::{self, File};
-use std::io::IsTerminal;
-use std::num::NonZeroUsize;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
 
-use crate::constants::{AREA_THOUSANDS_DIVISOR, DATE_FORMAT};
-use crate::data::{fetch_areas, to_csv};
+use crate::constants::{AREA_THOUSANDS_DIVISOR, DATE_FORMAT, UKRAINE_AREA_SQ_KM};
+use crate::data::Area;
+use crate::interrupt::Interrupt;
+use crate::notify::Notifier;
 use crate::series::AreaBuckets;
+use crate::source::{DeepStateSource, Fetcher, MapSource, stream_snapshots};
+use crate::store::{CsvStore, DataStore};
 use tracing_subscriber::EnvFilter;
 
 const APP_ABOUT: &str = "RUA - Dynamic transition of territory in the Russian-Ukrainian conflict";
 const DEFAULT_OUTPUT_HTML: &str = "dist/index.html";
+/// Опубликованная копия истории с точностью `[precision]`.
 const DEFAULT_HISTORY_CSV: &str = "dist/history.csv";
+/// Хранилище истории с значениями источника как есть; вне каталога сайта.
+const DEFAULT_STORE_CSV: &str = "data/history.csv";
 const DEFAULT_FORECAST_CSV: &str = "dist/forecast.csv";
 const CSV_ARCHIVE_EXTENSION: &str = "gz";
 const DEFAULT_FORECAST_HORIZON_DAYS: usize = 365;
-const FETCH_MAX_RETRIES: u32 = 10;
-const FETCH_DELAY_SECS: u64 = 2;
+const DEFAULT_GH_PAGES_WORKTREE: &str = "gh-pages";
+const DEFAULT_GH_PAGES_BRANCH: &str = "gh-pages";
+const DEFAULT_GH_PAGES_REMOTE: &str = "origin";
+const DEFAULT_COMPARE_LABEL: &str = "Второй источник";
+const DEFAULT_COMPARE_PRIMARY_LABEL: &str = "DeepStateMap";
+const DEFAULT_GH_PAGES_COMMIT_MESSAGE: &str =
+    "Update site: {date} · {area} тыс. км² ({daily} км² за сутки)";
+const DEFAULT_SMTP_PORT: u16 = 587;
+const DEFAULT_SMTP_PASSWORD_ENV: &str = "RUA_SMTP_PASSWORD";
+const DEFAULT_EMAIL_SUBJECT: &str = "RUA: {date} · {area} тыс. км² ({daily} км² за сутки)";
+const DEFAULT_TELEGRAM_TOKEN_ENV: &str = "RUA_TELEGRAM_TOKEN";
+const DEFAULT_DISCORD_WEBHOOK_ENV: &str = "RUA_DISCORD_WEBHOOK";
+const DEFAULT_SLACK_WEBHOOK_ENV: &str = "RUA_SLACK_WEBHOOK";
+const DEFAULT_TUNE_CSV: &str = "dist/tune.csv";
+const DEFAULT_TUNE_HORIZON_DAYS: usize = 30;
+const DEFAULT_TUNE_FOLDS: usize = 4;
+const DEFAULT_TUNE_LAMBDA: [f64; 5] = [1.0, 2.0, 5.0, 10.0, 20.0];
+const DEFAULT_TUNE_DAMPING: [f64; 3] = [0.8, 0.9, 1.0];
+const DEFAULT_SERVE_BIND: &str = "127.0.0.1:8080";
+const DEFAULT_SERVE_SITE_DIR: &str = "dist";
+const DEFAULT_SERVE_CACHE_MAX_AGE_SECS: u64 = 300;
+const DEFAULT_SERVE_RELOAD_INTERVAL_SECS: u64 = 60;
+const DEFAULT_RETENTION_RAW_KEEP_DAYS: u32 = 90;
+const DEFAULT_RETENTION_COMPRESS_FORECASTS_AFTER_DAYS: u32 = 30;
+const DEFAULT_RETENTION_REMOTE_CACHE_KEEP_DAYS: u32 = 30;
+const DEFAULT_RETENTION_INTERVAL_HOURS: u64 = 24;
+const SECS_PER_HOUR: u64 = 3_600;
 
 #[derive(Parser, Debug)]
-#[command(name = "rua", about = APP_ABOUT)]
+#[command(name = "rua", about = APP_ABOUT, subcommand_negates_reqs = true)]
 struct Args {
     /// TOML-файл с параметрами запуска.
-    #[arg(long = "config", value_name = "PATH")]
-    config: PathBuf,
+    #[arg(long = "config", value_name = "PATH", required = true)]
+    config: Option<PathBuf>,
+    /// Окно обучения модели вместо `training_window` из конфига: `all`, `YYYY-MM-DD` или `365d`.
+    #[arg(long = "training-window", value_name = "WINDOW")]
+    training_window: Option<model::TrainingWindow>,
+    /// Строит выходы по данным, известным на этот момент: `YYYY-MM-DD` (конец суток UTC) или
+    /// время в формате `time_index`. Только для `forecast`, `render` и команды `tune`.
+    #[arg(long = "as-of", value_name = "TIMESTAMP", value_parser = series::parse_as_of)]
+    as_of: Option<DateTime<Utc>>,
+    /// Начало диапазона дат вместо `from` из конфига (`YYYY-MM-DD`, включительно).
+    #[arg(long = "from", value_name = "DATE")]
+    from: Option<NaiveDate>,
+    /// Конец диапазона дат вместо `to` из конфига (`YYYY-MM-DD`, включительно).
+    #[arg(long = "to", value_name = "DATE")]
+    to: Option<NaiveDate>,
+    /// Отдавать в график все дневные точки вместо `[chart] downsample` из конфига.
+    #[arg(long = "no-downsample")]
+    no_downsample: bool,
+    /// Сколько секунд ждать, пока другой запуск освободит каталог выгрузки; без флага второй
+    /// запуск сразу завершается с ошибкой.
+    #[arg(long = "wait-for-lock", value_name = "SECS")]
+    wait_for_lock: Option<u64>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Дописывает в историю точку, сверенную вручную по карте; она помечается как ручной ввод.
+    Append(AppendArgs),
+    /// Сравнивает две сводки `/v1/summary` и печатает, что изменилось в опубликованных числах.
+    ReportDiff(ReportDiffArgs),
+    /// Проверяет окружение: доступность API, прокси, права на запись и сохранённые данные.
+    Doctor,
+    /// Пересобирает историю из архива сырых ответов текущим разбором и печатает отличия.
+    RebuildHistory(RebuildHistoryArgs),
+    /// Применяет политику хранения `[retention]` из `--config`: прореживает архив сырых
+    /// ответов, сжимает старые прогнозы и удаляет давние копии удалённых историй.
+    Gc(GcArgs),
+    /// Подбирает параметры trend filter по сетке `[tune]` из `--config` и печатает таблицу
+    /// лидеров бэктеста.
+    Tune(TuneArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct TuneArgs {
+    /// Записать лучшие параметры в `[trend_filter]` конфига, даже если `tune.persist = false`.
+    #[arg(long)]
+    persist: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct GcArgs {
+    /// Только напечатать, что было бы удалено и сжато.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct RebuildHistoryArgs {
+    /// Каталог архива (`raw_archive_dir` из `[run]` или `[download]`).
+    #[arg(long, value_name = "DIR")]
+    archive: PathBuf,
+    /// Хранилище истории (`store_csv`), которое пересобирается.
+    #[arg(long, value_name = "PATH", default_value = DEFAULT_STORE_CSV)]
+    csv: PathBuf,
+    /// Только напечатать отличия, не переписывая CSV.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct ReportDiffArgs {
+    /// Предыдущая сводка (JSON).
+    #[arg(value_name = "OLD")]
+    old: PathBuf,
+    /// Новая сводка (JSON).
+    #[arg(value_name = "NEW")]
+    new: PathBuf,
+    /// Записать текст в файл вместо стандартного вывода.
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+struct AppendArgs {
+    /// День точки (YYYY-MM-DD).
+    #[arg(long, value_name = "DATE")]
+    date: NaiveDate,
+    /// Слой «занято после 24.02.2022», км².
+    #[arg(long = "area-occupied", value_name = "KM2")]
+    area_occupied: f64,
+    /// Слой UA внутри занятой территории, км²; вычитается из занятой площади.
+    #[arg(long = "area-ua", value_name = "KM2")]
+    area_ua: f64,
+    /// Хранилище истории (`store_csv`), в которое дописывается точка.
+    #[arg(long, value_name = "PATH", default_value = DEFAULT_STORE_CSV)]
+    csv: PathBuf,
 }
 
 const fn default_horizon_days() -> NonZeroUsize {
@@ -48,6 +194,10 @@ const fn default_minify_html() -> bool {
     true
 }
 
+const fn default_forecast_fallback() -> bool {
+    true
+}
+
 fn default_output_html() -> PathBuf {
     PathBuf::from(DEFAULT_OUTPUT_HTML)
 }
@@ -56,14 +206,196 @@ fn default_history_csv() -> PathBuf {
     PathBuf::from(DEFAULT_HISTORY_CSV)
 }
 
+fn default_store_csv() -> PathBuf {
+    PathBuf::from(DEFAULT_STORE_CSV)
+}
+
 fn default_forecast_csv() -> PathBuf {
     PathBuf::from(DEFAULT_FORECAST_CSV)
 }
 
+fn default_serve_bind() -> SocketAddr {
+    DEFAULT_SERVE_BIND
+        .parse()
+        .expect("DEFAULT_SERVE_BIND must be a valid socket address")
+}
+
+fn default_serve_site_dir() -> PathBuf {
+    PathBuf::from(DEFAULT_SERVE_SITE_DIR)
+}
+
+const fn default_serve_cache_max_age_secs() -> u64 {
+    DEFAULT_SERVE_CACHE_MAX_AGE_SECS
+}
+
+const fn default_serve_reload_interval_secs() -> u64 {
+    DEFAULT_SERVE_RELOAD_INTERVAL_SECS
+}
+
+fn default_gh_pages_worktree() -> PathBuf {
+    PathBuf::from(DEFAULT_GH_PAGES_WORKTREE)
+}
+
+fn default_gh_pages_branch() -> String {
+    DEFAULT_GH_PAGES_BRANCH.to_string()
+}
+
+fn default_gh_pages_remote() -> String {
+    DEFAULT_GH_PAGES_REMOTE.to_string()
+}
+
+fn default_gh_pages_commit_message() -> String {
+    DEFAULT_GH_PAGES_COMMIT_MESSAGE.to_string()
+}
+
+const fn default_retention_raw_keep_days() -> u32 {
+    DEFAULT_RETENTION_RAW_KEEP_DAYS
+}
+
+const fn default_retention_compress_forecasts_after_days() -> u32 {
+    DEFAULT_RETENTION_COMPRESS_FORECASTS_AFTER_DAYS
+}
+
+const fn default_retention_remote_cache_keep_days() -> u32 {
+    DEFAULT_RETENTION_REMOTE_CACHE_KEEP_DAYS
+}
+
+const fn default_retention_interval_hours() -> u64 {
+    DEFAULT_RETENTION_INTERVAL_HOURS
+}
+
+const fn default_front_days() -> NonZeroU32 {
+    NonZeroU32::new(front::DEFAULT_FRONT_DAYS).expect("default front days is non-zero")
+}
+
+fn default_compare_label() -> String {
+    DEFAULT_COMPARE_LABEL.to_string()
+}
+
+fn default_compare_primary_label() -> String {
+    DEFAULT_COMPARE_PRIMARY_LABEL.to_string()
+}
+
+const fn default_smtp_port() -> u16 {
+    DEFAULT_SMTP_PORT
+}
+
+fn default_smtp_password_env() -> String {
+    DEFAULT_SMTP_PASSWORD_ENV.to_string()
+}
+
+fn default_email_subject() -> String {
+    DEFAULT_EMAIL_SUBJECT.to_string()
+}
+
+const fn default_digest_weekday() -> Weekday {
+    Weekday::Mon
+}
+
+fn default_telegram_token_env() -> String {
+    DEFAULT_TELEGRAM_TOKEN_ENV.to_string()
+}
+
+fn default_discord_webhook_env() -> String {
+    DEFAULT_DISCORD_WEBHOOK_ENV.to_string()
+}
+
+fn default_slack_webhook_env() -> String {
+    DEFAULT_SLACK_WEBHOOK_ENV.to_string()
+}
+
+fn default_tune_csv() -> PathBuf {
+    PathBuf::from(DEFAULT_TUNE_CSV)
+}
+
+const fn default_tune_horizon_days() -> NonZeroUsize {
+    NonZeroUsize::new(DEFAULT_TUNE_HORIZON_DAYS)
+        .expect("DEFAULT_TUNE_HORIZON_DAYS must be non-zero")
+}
+
+const fn default_tune_folds() -> NonZeroUsize {
+    NonZeroUsize::new(DEFAULT_TUNE_FOLDS).expect("DEFAULT_TUNE_FOLDS must be non-zero")
+}
+
+const fn default_calibration_folds() -> NonZeroUsize {
+    NonZeroUsize::new(model::DEFAULT_CALIBRATION_FOLDS)
+        .expect("DEFAULT_CALIBRATION_FOLDS must be non-zero")
+}
+
+fn default_tune_lambda() -> Vec<f64> {
+    DEFAULT_TUNE_LAMBDA.to_vec()
+}
+
+fn default_tune_epsilon() -> Vec<f64> {
+    vec![model::TrendFilterConfig::default().epsilon]
+}
+
+fn default_tune_huber_delta() -> Vec<f64> {
+    vec![model::TrendFilterConfig::default().huber_delta]
+}
+
+fn default_tune_damping() -> Vec<f64> {
+    DEFAULT_TUNE_DAMPING.to_vec()
+}
+
+fn default_fetch_max_retries() -> NonZeroU32 {
+    NonZeroU32::new(fetch::FetchConfig::default().max_retries)
+        .expect("default fetch retries must be non-zero")
+}
+
+fn default_fetch_retry_delay_secs() -> u64 {
+    fetch::FetchConfig::default().retry_delay.as_secs()
+}
+
+fn default_fetch_connect_timeout_secs() -> NonZeroU64 {
+    NonZeroU64::new(fetch::FetchConfig::default().connect_timeout.as_secs())
+        .expect("default connect timeout must be non-zero")
+}
+
+fn default_fetch_request_timeout_secs() -> NonZeroU64 {
+    NonZeroU64::new(fetch::FetchConfig::default().request_timeout.as_secs())
+        .expect("default request timeout must be non-zero")
+}
+
+fn default_fetch_pool_idle_timeout_secs() -> u64 {
+    fetch::FetchConfig::default().pool_idle_timeout.as_secs()
+}
+
+fn default_fetch_pool_max_idle_per_host() -> usize {
+    fetch::FetchConfig::default().pool_max_idle_per_host
+}
+
+fn default_fetch_max_concurrent_requests() -> NonZeroUsize {
+    NonZeroUsize::new(fetch::FetchConfig::default().max_concurrent_requests)
+        .expect("default request concurrency must be non-zero")
+}
+
+fn default_fetch_tcp_keepalive_secs() -> u64 {
+    fetch::FetchConfig::default()
+        .tcp_keepalive
+        .map_or(0, |interval| interval.as_secs())
+}
+
+const fn default_integrity_checksums() -> bool {
+    true
+}
+
+fn default_proxy_password_env() -> String {
+    fetch::ProxyConfig::default().password_env
+}
+
 fn default_gray_zone_start() -> NaiveDate {
     report::ChartRenderConfig::default().gray_zone_start
 }
 
+fn default_chart_downsample() -> bool {
+    report::ChartRenderConfig::default().downsample
+}
+
+fn default_chart_table_days() -> usize {
+    report::ChartRenderConfig::default().table_days
+}
+
 fn default_avg_change_start() -> NaiveDate {
     report::ChartRenderConfig::default().avg_change_start
 }
@@ -75,6 +407,7 @@ enum Mode {
     Download,
     Forecast,
     Render,
+    Serve,
 }
 
 impl fmt::Display for Mode {
@@ -84,6 +417,7 @@ impl fmt::Display for Mode {
             Self::Download => f.write_str("download"),
             Self::Forecast => f.write_str("forecast"),
             Self::Render => f.write_str("render"),
+            Self::Serve => f.write_str("serve"),
         }
     }
 }
@@ -95,19 +429,67 @@ struct AppConfigFile {
     #[serde(default)]
     archive_csv: bool,
     #[serde(default)]
+    from: Option<NaiveDate>,
+    #[serde(default)]
+    to: Option<NaiveDate>,
+    #[serde(default)]
     run: RunConfigFile,
     #[serde(default)]
     download: DownloadConfigFile,
     #[serde(default)]
+    fetch: FetchConfigFile,
+    #[serde(default)]
     forecast: ForecastConfigFile,
     #[serde(default)]
     render: RenderConfigFile,
     #[serde(default)]
+    tune: TuneConfigFile,
+    #[serde(default)]
+    serve: ServeConfigFile,
+    #[serde(default)]
     chart: ChartConfigFile,
     #[serde(default)]
+    page: PageFile,
+    #[serde(default)]
     model: ModelKind,
     #[serde(default)]
+    interpolation: InterpolationFile,
+    /// Окно обучения: `all`, дата начала или `365d`; по умолчанию с 22.11.2022.
+    #[serde(default, deserialize_with = "parse_training_window")]
+    training_window: Option<model::TrainingWindow>,
+    #[serde(default)]
     trend_filter: Option<TrendFilterFile>,
+    #[serde(default)]
+    llt: Option<LltFile>,
+    #[serde(default)]
+    gh_pages: Option<GhPagesFile>,
+    #[serde(default)]
+    email: Option<EmailFile>,
+    #[serde(default)]
+    notifications: Option<NotificationsFile>,
+    #[serde(default)]
+    alerts: Vec<AlertFile>,
+    #[serde(default)]
+    integrity: Option<IntegrityFile>,
+    #[serde(default)]
+    events: Vec<EventFile>,
+    #[serde(default)]
+    compare: Option<CompareFile>,
+    #[serde(default)]
+    regressors: Option<RegressorsFile>,
+    #[serde(default)]
+    seasonality: Option<SeasonalityFile>,
+    #[serde(default)]
+    calibration: Option<CalibrationFile>,
+    #[serde(default)]
+    front: Option<FrontFile>,
+    #[serde(default)]
+    retention: Option<RetentionFile>,
+    #[serde(default)]
+    precision: PrecisionFile,
+    /// Файл с днями, исключёнными из обучения (см. `exclusions.rs`).
+    #[serde(default)]
+    exclusions: Option<PathBuf>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -117,12 +499,54 @@ struct RunConfigFile {
     output_html: PathBuf,
     #[serde(default = "default_minify_html")]
     minify_html: bool,
+    /// Хранилище, в которое скачивается история; `output_history_csv` — его копия для сайта.
+    #[serde(default = "default_store_csv")]
+    store_csv: PathBuf,
     #[serde(default = "default_history_csv")]
     output_history_csv: PathBuf,
     #[serde(default = "default_forecast_csv")]
     output_forecast_csv: PathBuf,
     #[serde(default = "default_horizon_days")]
     horizon_days: NonZeroUsize,
+    /// Дополнительные горизонты той же модели: `forecast_<N>d.csv` рядом с основным прогнозом.
+    #[serde(default)]
+    extra_horizons_days: Vec<NonZeroUsize>,
+    #[serde(default)]
+    granularity: series::Granularity,
+    #[serde(default)]
+    output_smoothed_csv: Option<PathBuf>,
+    #[serde(default)]
+    output_trend_csv: Option<PathBuf>,
+    #[serde(default)]
+    output_full_resolution_csv: Option<PathBuf>,
+    #[serde(default)]
+    output_percent_csv: Option<PathBuf>,
+    #[serde(default)]
+    output_figure_json: Option<PathBuf>,
+    #[serde(default)]
+    output_ratios_json: Option<PathBuf>,
+    #[serde(default)]
+    output_gaps_csv: Option<PathBuf>,
+    #[serde(default)]
+    output_xlsx: Option<PathBuf>,
+    #[serde(default)]
+    output_ics: Option<PathBuf>,
+    #[serde(default)]
+    output_updates_html: Option<PathBuf>,
+    /// Страница «Методология»; рядом пишется CSV с тем же именем.
+    #[serde(default)]
+    output_methodology_html: Option<PathBuf>,
+    #[serde(default)]
+    mirror_url: Option<String>,
+    /// Каталог для сырых ответов API (`<id>.json.gz`), из которых `rebuild-history`
+    /// пересобирает историю.
+    #[serde(default)]
+    raw_archive_dir: Option<PathBuf>,
+    #[serde(default)]
+    build_cache: bool,
+    /// Если модель не обучилась, показывать прогноз прошлой сборки вместо ошибки.
+    #[serde(default = "default_forecast_fallback")]
+    forecast_fallback: bool,
 }
 
 impl Default for RunConfigFile {
@@ -130,9 +554,27 @@ impl Default for RunConfigFile {
         Self {
             output_html: default_output_html(),
             minify_html: default_minify_html(),
+            store_csv: default_store_csv(),
             output_history_csv: default_history_csv(),
             output_forecast_csv: default_forecast_csv(),
             horizon_days: default_horizon_days(),
+            extra_horizons_days: Vec::new(),
+            granularity: series::Granularity::default(),
+            output_smoothed_csv: None,
+            output_trend_csv: None,
+            output_full_resolution_csv: None,
+            output_percent_csv: None,
+            output_figure_json: None,
+            output_ratios_json: None,
+            output_gaps_csv: None,
+            output_xlsx: None,
+            output_ics: None,
+            output_updates_html: None,
+            output_methodology_html: None,
+            mirror_url: None,
+            raw_archive_dir: None,
+            build_cache: false,
+            forecast_fallback: default_forecast_fallback(),
         }
     }
 }
@@ -140,14 +582,89 @@ impl Default for RunConfigFile {
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct DownloadConfigFile {
+    #[serde(default = "default_store_csv")]
+    store_csv: PathBuf,
     #[serde(default = "default_history_csv")]
     output_csv: PathBuf,
+    #[serde(default)]
+    mirror_url: Option<String>,
+    #[serde(default)]
+    raw_archive_dir: Option<PathBuf>,
+    /// Каталог выгрузок ISW/CTP в `GeoJSON` вместо API `DeepStateMap`.
+    #[serde(default)]
+    isw_dir: Option<PathBuf>,
+    /// Площадь, занятая до 24.02.2022, которая вычитается из слоя ISW, км².
+    #[serde(default)]
+    isw_pre_war_km2: f64,
+    /// Значение свойства объектов слоя оценённого контроля; без него берутся все объекты.
+    #[serde(default)]
+    isw_layer: Option<String>,
 }
 
 impl Default for DownloadConfigFile {
     fn default() -> Self {
         Self {
+            store_csv: default_store_csv(),
             output_csv: default_history_csv(),
+            mirror_url: None,
+            raw_archive_dir: None,
+            isw_dir: None,
+            isw_pre_war_km2: 0.0,
+            isw_layer: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FetchConfigFile {
+    #[serde(default = "default_fetch_max_retries")]
+    max_retries: NonZeroU32,
+    #[serde(default = "default_fetch_retry_delay_secs")]
+    retry_delay_secs: u64,
+    #[serde(default = "default_fetch_connect_timeout_secs")]
+    connect_timeout_secs: NonZeroU64,
+    #[serde(default = "default_fetch_request_timeout_secs")]
+    request_timeout_secs: NonZeroU64,
+    #[serde(default)]
+    deadline_secs: Option<NonZeroU64>,
+    #[serde(default = "default_fetch_pool_idle_timeout_secs")]
+    pool_idle_timeout_secs: u64,
+    #[serde(default = "default_fetch_pool_max_idle_per_host")]
+    pool_max_idle_per_host: usize,
+    #[serde(default = "default_fetch_tcp_keepalive_secs")]
+    tcp_keepalive_secs: u64,
+    #[serde(default)]
+    http2_keepalive_secs: u64,
+    #[serde(default = "default_fetch_max_concurrent_requests")]
+    max_concurrent_requests: NonZeroUsize,
+    #[serde(default)]
+    proxy: Option<String>,
+    #[serde(default)]
+    proxy_username: Option<String>,
+    #[serde(default = "default_proxy_password_env")]
+    proxy_password_env: String,
+    #[serde(default)]
+    no_proxy: Option<String>,
+}
+
+impl Default for FetchConfigFile {
+    fn default() -> Self {
+        Self {
+            max_retries: default_fetch_max_retries(),
+            retry_delay_secs: default_fetch_retry_delay_secs(),
+            connect_timeout_secs: default_fetch_connect_timeout_secs(),
+            request_timeout_secs: default_fetch_request_timeout_secs(),
+            deadline_secs: None,
+            pool_idle_timeout_secs: default_fetch_pool_idle_timeout_secs(),
+            pool_max_idle_per_host: default_fetch_pool_max_idle_per_host(),
+            tcp_keepalive_secs: default_fetch_tcp_keepalive_secs(),
+            http2_keepalive_secs: 0,
+            max_concurrent_requests: default_fetch_max_concurrent_requests(),
+            proxy: None,
+            proxy_username: None,
+            proxy_password_env: default_proxy_password_env(),
+            no_proxy: None,
         }
     }
 }
@@ -161,6 +678,15 @@ struct ForecastConfigFile {
     output_csv: PathBuf,
     #[serde(default = "default_horizon_days")]
     horizon_days: NonZeroUsize,
+    /// Дополнительные горизонты той же модели: `forecast_<N>d.csv` рядом с `output_csv`.
+    #[serde(default)]
+    extra_horizons_days: Vec<NonZeroUsize>,
+    #[serde(default)]
+    granularity: series::Granularity,
+    #[serde(default)]
+    output_smoothed_csv: Option<PathBuf>,
+    #[serde(default)]
+    output_trend_csv: Option<PathBuf>,
 }
 
 impl Default for ForecastConfigFile {
@@ -169,6 +695,10 @@ impl Default for ForecastConfigFile {
             csv: default_history_csv(),
             output_csv: default_forecast_csv(),
             horizon_days: default_horizon_days(),
+            extra_horizons_days: Vec::new(),
+            granularity: series::Granularity::default(),
+            output_smoothed_csv: None,
+            output_trend_csv: None,
         }
     }
 }
@@ -184,6 +714,27 @@ struct RenderConfigFile {
     output_html: PathBuf,
     #[serde(default = "default_minify_html")]
     minify_html: bool,
+    #[serde(default)]
+    output_full_resolution_csv: Option<PathBuf>,
+    #[serde(default)]
+    output_percent_csv: Option<PathBuf>,
+    #[serde(default)]
+    output_figure_json: Option<PathBuf>,
+    #[serde(default)]
+    output_ratios_json: Option<PathBuf>,
+    #[serde(default)]
+    output_gaps_csv: Option<PathBuf>,
+    #[serde(default)]
+    output_xlsx: Option<PathBuf>,
+    #[serde(default)]
+    output_ics: Option<PathBuf>,
+    #[serde(default)]
+    output_updates_html: Option<PathBuf>,
+    /// Страница «Методология»; рядом пишется CSV с тем же именем.
+    #[serde(default)]
+    output_methodology_html: Option<PathBuf>,
+    #[serde(default)]
+    build_cache: bool,
 }
 
 impl Default for RenderConfigFile {
@@ -193,70 +744,611 @@ impl Default for RenderConfigFile {
             forecast_csv: None,
             output_html: default_output_html(),
             minify_html: default_minify_html(),
+            output_full_resolution_csv: None,
+            output_percent_csv: None,
+            output_figure_json: None,
+            output_ratios_json: None,
+            output_gaps_csv: None,
+            output_xlsx: None,
+            output_ics: None,
+            output_updates_html: None,
+            output_methodology_html: None,
+            build_cache: false,
         }
     }
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
-struct ChartConfigFile {
-    #[serde(default = "default_gray_zone_start")]
-    gray_zone_start: NaiveDate,
-    #[serde(default = "default_avg_change_start")]
-    avg_change_start: NaiveDate,
+struct TuneConfigFile {
+    #[serde(default = "default_history_csv")]
+    csv: PathBuf,
+    #[serde(default = "default_tune_csv")]
+    output_csv: PathBuf,
+    #[serde(default = "default_tune_lambda")]
+    lambda: Vec<f64>,
+    #[serde(default = "default_tune_epsilon")]
+    epsilon: Vec<f64>,
+    #[serde(default = "default_tune_huber_delta")]
+    huber_delta: Vec<f64>,
+    #[serde(default = "default_tune_damping")]
+    damping: Vec<f64>,
+    #[serde(default = "default_tune_horizon_days")]
+    horizon_days: NonZeroUsize,
+    #[serde(default = "default_tune_folds")]
+    folds: NonZeroUsize,
+    #[serde(default)]
+    gap_days: usize,
+    #[serde(default)]
+    train_days: Option<NonZeroUsize>,
+    #[serde(default)]
+    persist: bool,
 }
 
-impl Default for ChartConfigFile {
+impl Default for TuneConfigFile {
     fn default() -> Self {
         Self {
-            gray_zone_start: default_gray_zone_start(),
-            avg_change_start: default_avg_change_start(),
+            csv: default_history_csv(),
+            output_csv: default_tune_csv(),
+            lambda: default_tune_lambda(),
+            epsilon: default_tune_epsilon(),
+            huber_delta: default_tune_huber_delta(),
+            damping: default_tune_damping(),
+            horizon_days: default_tune_horizon_days(),
+            folds: default_tune_folds(),
+            gap_days: 0,
+            train_days: None,
+            persist: false,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
-#[serde(rename_all = "kebab-case")]
-enum ModelKind {
-    #[default]
-    #[serde(alias = "trend_filter")]
-    TrendFilter,
-    Llt,
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ServeConfigFile {
+    #[serde(default = "default_serve_bind")]
+    bind: SocketAddr,
+    #[serde(default = "default_history_csv")]
+    csv: PathBuf,
+    #[serde(default = "default_forecast_csv")]
+    forecast_csv: PathBuf,
+    #[serde(default = "default_serve_site_dir")]
+    site_dir: PathBuf,
+    #[serde(default = "default_serve_cache_max_age_secs")]
+    cache_max_age_secs: u64,
+    #[serde(default = "default_serve_reload_interval_secs")]
+    reload_interval_secs: u64,
+    #[serde(default)]
+    live_interval_secs: u64,
 }
 
-impl fmt::Display for ModelKind {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::TrendFilter => f.write_str("trend-filter"),
-            Self::Llt => f.write_str("llt"),
+impl Default for ServeConfigFile {
+    fn default() -> Self {
+        Self {
+            bind: default_serve_bind(),
+            csv: default_history_csv(),
+            forecast_csv: default_forecast_csv(),
+            site_dir: default_serve_site_dir(),
+            cache_max_age_secs: default_serve_cache_max_age_secs(),
+            reload_interval_secs: default_serve_reload_interval_secs(),
+            live_interval_secs: 0,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct NonNegativeFinite(f64);
-
-impl NonNegativeFinite {
-    const fn get(self) -> f64 {
-        self.0
-    }
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)]
+struct ChartConfigFile {
+    #[serde(default = "default_gray_zone_start")]
+    gray_zone_start: NaiveDate,
+    #[serde(default = "default_avg_change_start")]
+    avg_change_start: NaiveDate,
+    #[serde(default = "default_chart_downsample")]
+    downsample: bool,
+    #[serde(default)]
+    intraday: bool,
+    #[serde(default)]
+    interpolation: InterpolationFile,
+    #[serde(default)]
+    smoothed: bool,
+    #[serde(default)]
+    trend: bool,
+    /// Раздел диагностики LLT-модели на странице.
+    #[serde(default)]
+    diagnostics: bool,
+    #[serde(default)]
+    change_smoothing: SmoothingFile,
+    #[serde(default)]
+    reference_lines: Vec<ReferenceLineFile>,
+    #[serde(default = "default_chart_table_days")]
+    table_days: usize,
+    /// Базовая дата вида «Индекс»; без неё — `avg_change_start`.
+    #[serde(default)]
+    index_date: Option<NaiveDate>,
+    #[serde(default)]
+    milestones: Vec<MilestoneFile>,
+    #[serde(default)]
+    phases: Vec<PhaseFile>,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct PositiveFinite(f64);
+/// Сглаживание суточного изменения (`[chart.change_smoothing]`).
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+struct SmoothingFile {
+    method: report::SmoothingMethod,
+    window: NonZeroUsize,
+    min_periods: NonZeroUsize,
+}
 
-impl PositiveFinite {
-    const fn get(self) -> f64 {
-        self.0
+impl Default for SmoothingFile {
+    fn default() -> Self {
+        let spec = report::SmoothingSpec::default();
+        Self {
+            method: spec.method,
+            window: NonZeroUsize::new(spec.window).expect("default window is non-zero"),
+            min_periods: NonZeroUsize::new(spec.min_periods)
+                .expect("default min_periods is non-zero"),
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct UnitIntervalFinite(f64);
-
-impl UnitIntervalFinite {
-    const fn get(self) -> f64 {
-        self.0
+/// Оформление страницы (`[page]`); незаданные поля берутся из оформления по умолчанию.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PageFile {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    subtitle: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    keywords: Option<String>,
+    #[serde(default)]
+    site_url: Option<String>,
+    #[serde(default)]
+    site_name: Option<String>,
+    #[serde(default)]
+    favicon: Option<String>,
+    /// Пустая строка скрывает ссылку на репозиторий.
+    #[serde(default)]
+    repo_url: Option<String>,
+    #[serde(default)]
+    repo_text: Option<String>,
+    #[serde(default)]
+    analytics_html: Option<String>,
+    #[serde(default)]
+    analytics_events: bool,
+    #[serde(default)]
+    analytics_endpoint: Option<String>,
+    #[serde(default)]
+    features: ReportFeaturesFile,
+    /// `km2`, `thousand_km2`, `mi2` или `hectares`.
+    #[serde(default)]
+    area_unit: Option<report::AreaUnit>,
+    /// `ru`, `uk` или `en`: разделители в числах страницы и графиков.
+    #[serde(default)]
+    locale: Option<report::NumberLocale>,
+    #[serde(default)]
+    regions: Vec<RegionFile>,
+    /// Пустой список — страны и штаты США.
+    #[serde(default)]
+    comparison_sets: Vec<ComparisonSetFile>,
+    #[serde(default)]
+    wasm_url: Option<String>,
+    #[serde(default)]
+    self_host_assets: bool,
+    #[serde(default)]
+    fonts: report::FontStrategy,
+}
+
+/// Занятая площадь области (`[[page.regions]]`) для таблицы долей на странице.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegionFile {
+    name: String,
+    occupied_km2: f64,
+    /// День, на который оценена площадь; печатается рядом со значением.
+    as_of: NaiveDate,
+}
+
+/// Набор территорий (`[[page.comparison_sets]]`): встроенный по ключу или CSV
+/// `name,area_km2[,flag]`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ComparisonSetFile {
+    #[serde(default)]
+    builtin: Option<String>,
+    #[serde(default)]
+    csv: Option<PathBuf>,
+    /// Ключ набора в разметке; для CSV по умолчанию — имя файла.
+    #[serde(default)]
+    key: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    name_column: Option<String>,
+}
+
+/// Блоки страницы (`[page.features]`); незаданные включены.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReportFeaturesFile {
+    #[serde(default)]
+    forecast: Option<bool>,
+    #[serde(default)]
+    comparison_tables: Option<bool>,
+    #[serde(default)]
+    change_panel: Option<bool>,
+    #[serde(default)]
+    map_link: Option<bool>,
+}
+
+/// Опорная линия основного графика (`[[chart.reference_lines]]`): площадь на дату или в км².
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReferenceLineFile {
+    label: String,
+    #[serde(default)]
+    date: Option<NaiveDate>,
+    #[serde(default)]
+    area_km2: Option<f64>,
+}
+
+/// Рубеж карточки «При текущем темпе» (`[[chart.milestones]]`): площадь в км² или доля Украины.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MilestoneFile {
+    label: String,
+    #[serde(default)]
+    area_km2: Option<f64>,
+    #[serde(default)]
+    percent: Option<f64>,
+}
+
+/// Этап войны на фоне основного графика (`[[chart.phases]]`); без `end` — до конца ряда.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PhaseFile {
+    label: String,
+    start: NaiveDate,
+    #[serde(default)]
+    end: Option<NaiveDate>,
+}
+
+impl Default for ChartConfigFile {
+    fn default() -> Self {
+        Self {
+            gray_zone_start: default_gray_zone_start(),
+            avg_change_start: default_avg_change_start(),
+            downsample: default_chart_downsample(),
+            intraday: false,
+            interpolation: InterpolationFile::default(),
+            smoothed: false,
+            trend: false,
+            diagnostics: false,
+            change_smoothing: SmoothingFile::default(),
+            reference_lines: Vec::new(),
+            table_days: default_chart_table_days(),
+            index_date: None,
+            milestones: Vec::new(),
+            phases: Vec::new(),
+        }
+    }
+}
+
+/// Событие для календаря `.ics` (`[[events]]`).
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EventFile {
+    date: NaiveDate,
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// Раздел смещения линии фронта (`[front]`); карты загружаются только в режиме `run`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FrontFile {
+    #[serde(default = "default_front_days")]
+    days: NonZeroU32,
+    #[serde(default)]
+    occupied_fills: Option<Vec<String>>,
+}
+
+/// Политика хранения (`[retention]`, см. `gc.rs`); каталоги берутся из разделов режимов.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RetentionFile {
+    #[serde(default = "default_retention_raw_keep_days")]
+    raw_keep_days: u32,
+    #[serde(default)]
+    forecast_dir: Option<PathBuf>,
+    #[serde(default = "default_retention_compress_forecasts_after_days")]
+    compress_forecasts_after_days: u32,
+    #[serde(default = "default_retention_remote_cache_keep_days")]
+    remote_cache_keep_days: u32,
+    /// Как часто чистить в режиме `serve`; 0 — только командой `rua gc`.
+    #[serde(default = "default_retention_interval_hours")]
+    interval_hours: u64,
+}
+
+/// Точность публикуемых чисел (`[precision]`); пропущенные поля — по умолчанию.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PrecisionFile {
+    #[serde(default)]
+    area_km2: Option<usize>,
+    #[serde(default)]
+    percent: Option<usize>,
+    #[serde(default)]
+    rate_km2_per_day: Option<usize>,
+    #[serde(default)]
+    variance: Option<usize>,
+    #[serde(default)]
+    ratio: Option<usize>,
+    #[serde(default)]
+    chart: Option<usize>,
+}
+
+/// Второй источник для вкладки сравнения (`[compare]`): история в формате `history.csv`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CompareFile {
+    csv: PathBuf,
+    #[serde(default = "default_compare_label")]
+    label: String,
+    #[serde(default = "default_compare_primary_label")]
+    primary_label: String,
+}
+
+/// Экзогенные дневные показатели для LLT-модели (`[regressors]`).
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegressorsFile {
+    csv: PathBuf,
+    #[serde(default)]
+    columns: Vec<String>,
+}
+
+/// Подбор параметров LLT-модели (`[llt]`).
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LltFile {
+    /// Сколько начальных точек пробовать; по умолчанию [`model::DEFAULT_RESTARTS`].
+    #[serde(default)]
+    restarts: Option<NonZeroUsize>,
+    #[serde(default)]
+    sigma_prior: Option<SigmaPriorFile>,
+}
+
+/// Веса штрафов на сигмы LLT-модели (`[llt.sigma_prior]`); не заданные берутся по умолчанию.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SigmaPriorFile {
+    #[serde(default, deserialize_with = "parse_non_negative")]
+    level: Option<NonNegativeFinite>,
+    #[serde(default, deserialize_with = "parse_non_negative")]
+    trend: Option<NonNegativeFinite>,
+    #[serde(default, deserialize_with = "parse_non_negative")]
+    obs: Option<NonNegativeFinite>,
+}
+
+impl SigmaPriorFile {
+    fn resolve(self) -> model::SigmaPrior {
+        let defaults = model::SigmaPrior::default();
+        let weight = |value: Option<NonNegativeFinite>, default| {
+            value.map_or(default, NonNegativeFinite::get)
+        };
+        model::SigmaPrior {
+            level: weight(self.level, defaults.level),
+            trend: weight(self.trend, defaults.trend),
+            obs: weight(self.obs, defaults.obs),
+        }
+    }
+}
+
+/// Сезонные эффекты темпа LLT-модели (`[seasonality]`).
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SeasonalityFile {
+    #[serde(default)]
+    period: model::SeasonalPeriod,
+    #[serde(default, deserialize_with = "parse_non_negative")]
+    penalty: Option<NonNegativeFinite>,
+}
+
+/// Конформная калибровка интервалов прогноза по бэктесту (`[calibration]`).
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CalibrationFile {
+    #[serde(default = "default_calibration_folds")]
+    folds: NonZeroUsize,
+    #[serde(default)]
+    gap_days: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IntegrityFile {
+    #[serde(default = "default_integrity_checksums")]
+    checksums: bool,
+    #[serde(default)]
+    minisign_secret_key: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GhPagesFile {
+    #[serde(default = "default_gh_pages_worktree")]
+    worktree: PathBuf,
+    #[serde(default = "default_gh_pages_branch")]
+    branch: String,
+    #[serde(default = "default_gh_pages_commit_message")]
+    commit_message: String,
+    #[serde(default)]
+    push: bool,
+    #[serde(default = "default_gh_pages_remote")]
+    remote: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+enum SmtpSecurityFile {
+    #[default]
+    Starttls,
+    Tls,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+enum DigestScheduleFile {
+    #[default]
+    Daily,
+    Weekly,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EmailFile {
+    smtp_host: String,
+    #[serde(default = "default_smtp_port")]
+    smtp_port: u16,
+    #[serde(default)]
+    security: SmtpSecurityFile,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default = "default_smtp_password_env")]
+    password_env: String,
+    from: String,
+    to: Vec<String>,
+    #[serde(default = "default_email_subject")]
+    subject: String,
+    #[serde(default)]
+    schedule: DigestScheduleFile,
+    #[serde(default = "default_digest_weekday")]
+    weekday: Weekday,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NotificationsFile {
+    #[serde(default)]
+    link: Option<String>,
+    #[serde(default)]
+    targets: Vec<NotifierFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+enum NotifierFile {
+    Telegram {
+        chat_id: String,
+        #[serde(default = "default_telegram_token_env")]
+        token_env: String,
+    },
+    Discord {
+        #[serde(default = "default_discord_webhook_env")]
+        webhook_env: String,
+    },
+    Slack {
+        #[serde(default = "default_slack_webhook_env")]
+        webhook_env: String,
+    },
+}
+
+/// Правило оповещения (`[[alerts]]`); `name` по умолчанию описывает условие.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+enum AlertFile {
+    DailyChange {
+        #[serde(default)]
+        name: Option<String>,
+        above_km2: f64,
+        #[serde(default = "default_alert_days")]
+        days: NonZeroUsize,
+    },
+    ForecastUpper {
+        #[serde(default)]
+        name: Option<String>,
+        crosses_km2: f64,
+    },
+    Stale {
+        #[serde(default)]
+        name: Option<String>,
+        hours: NonZeroU32,
+    },
+}
+
+const fn default_alert_days() -> NonZeroUsize {
+    NonZeroUsize::MIN
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+enum InterpolationFile {
+    #[default]
+    Linear,
+    Previous,
+    Spline,
+    None,
+}
+
+impl From<InterpolationFile> for series::InterpolationStrategy {
+    fn from(value: InterpolationFile) -> Self {
+        match value {
+            InterpolationFile::Linear => Self::Linear,
+            InterpolationFile::Previous => Self::Previous,
+            InterpolationFile::Spline => Self::Spline,
+            InterpolationFile::None => Self::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+enum ModelKind {
+    #[default]
+    #[serde(alias = "trend_filter")]
+    TrendFilter,
+    Llt,
+}
+
+impl fmt::Display for ModelKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TrendFilter => f.write_str("trend-filter"),
+            Self::Llt => f.write_str("llt"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct NonNegativeFinite(f64);
+
+impl NonNegativeFinite {
+    const fn get(self) -> f64 {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PositiveFinite(f64);
+
+impl PositiveFinite {
+    const fn get(self) -> f64 {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct UnitIntervalFinite(f64);
+
+impl UnitIntervalFinite {
+    const fn get(self) -> f64 {
+        self.0
     }
 }
 
@@ -275,6 +1367,15 @@ where
     .transpose()
 }
 
+fn parse_training_window<'de, D>(deserializer: D) -> Result<Option<model::TrainingWindow>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|raw| raw.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
 fn parse_positive<'de, D>(deserializer: D) -> Result<Option<PositiveFinite>, D::Error>
 where
     D: Deserializer<'de>,
@@ -321,26 +1422,65 @@ struct TrendFilterFile {
     damping: Option<UnitIntervalFinite>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 enum ModeConfig {
     Run(RunConfig),
     Download(DownloadConfig),
     Forecast(ForecastConfig),
     Render(RenderConfig),
+    Serve(ServeConfig),
+}
+
+impl ModeConfig {
+    /// Каталог, который режим переписывает: его держит [`instance::InstanceLock`]. `serve`
+    /// только читает и блокировку не берёт.
+    fn output_dir(&self) -> Option<&Path> {
+        let output = match self {
+            Self::Run(config) => &config.output_history_csv,
+            Self::Download(config) => &config.output_csv,
+            Self::Forecast(config) => &config.output_csv,
+            Self::Render(config) => &config.output_html,
+            Self::Serve(_) => return None,
+        };
+        Some(output.parent().unwrap_or_else(|| Path::new(".")))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct RunConfig {
     output_html: PathBuf,
     minify_html: bool,
+    store_csv: PathBuf,
     output_history_csv: PathBuf,
     output_forecast_csv: PathBuf,
     horizon_days: NonZeroUsize,
+    /// Без повторов и без `horizon_days`, по возрастанию.
+    extra_horizons_days: Vec<NonZeroUsize>,
+    granularity: series::Granularity,
+    output_smoothed_csv: Option<PathBuf>,
+    output_trend_csv: Option<PathBuf>,
+    output_full_resolution_csv: Option<PathBuf>,
+    output_percent_csv: Option<PathBuf>,
+    output_figure_json: Option<PathBuf>,
+    output_ratios_json: Option<PathBuf>,
+    output_gaps_csv: Option<PathBuf>,
+    output_xlsx: Option<PathBuf>,
+    output_ics: Option<PathBuf>,
+    output_updates_html: Option<PathBuf>,
+    output_methodology_html: Option<PathBuf>,
+    mirror_url: Option<String>,
+    raw_archive_dir: Option<PathBuf>,
+    build_cache: bool,
+    forecast_fallback: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 struct DownloadConfig {
+    store_csv: PathBuf,
     output_csv: PathBuf,
+    mirror_url: Option<String>,
+    raw_archive_dir: Option<PathBuf>,
+    isw: Option<source::IswSource>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -348,29 +1488,133 @@ struct ForecastConfig {
     csv: PathBuf,
     output_csv: PathBuf,
     horizon_days: NonZeroUsize,
+    /// Без повторов и без `horizon_days`, по возрастанию.
+    extra_horizons_days: Vec<NonZeroUsize>,
+    granularity: series::Granularity,
+    output_smoothed_csv: Option<PathBuf>,
+    output_trend_csv: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct RenderConfig {
     csv: PathBuf,
+    /// Адрес истории, если `csv` задан как `http(s)://`; тогда `csv` — её локальная копия.
+    csv_url: Option<String>,
     forecast_csv: PathBuf,
     output_html: PathBuf,
     minify_html: bool,
+    output_full_resolution_csv: Option<PathBuf>,
+    output_percent_csv: Option<PathBuf>,
+    output_figure_json: Option<PathBuf>,
+    output_ratios_json: Option<PathBuf>,
+    output_gaps_csv: Option<PathBuf>,
+    output_xlsx: Option<PathBuf>,
+    output_ics: Option<PathBuf>,
+    output_updates_html: Option<PathBuf>,
+    output_methodology_html: Option<PathBuf>,
+    build_cache: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TuneConfig {
+    csv: PathBuf,
+    output_csv: PathBuf,
+    grid: tune::TuneGrid,
+    backtest: model::BacktestConfig,
+    persist: bool,
+}
+
+/// Параметры режима `serve`; сам сервер собирается с фичей `serve`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ServeConfig {
+    /// Адрес, на котором слушает сервер.
+    bind: SocketAddr,
+    /// Исторический CSV, из которого строится ряд.
+    csv: PathBuf,
+    /// CSV прогноза; при его отсутствии `/v1/forecast` отвечает 404.
+    forecast_csv: PathBuf,
+    /// Каталог со статическим сайтом (`index.html` и выгрузки).
+    site_dir: PathBuf,
+    /// Значение `max-age` в `Cache-Control` для ответов API.
+    cache_max_age: Duration,
+    /// Как часто проверять CSV на изменения; `None` — данные загружаются один раз.
+    reload_interval: Option<Duration>,
+    /// Как часто спрашивать у источника последний срез карты; `None` — только история.
+    live_interval: Option<Duration>,
+    /// Точность чисел в ответах API.
+    precision: constants::Precision,
 }
 
 #[derive(Debug, Clone)]
 struct AppConfig {
     mode: Mode,
     archive_csv: bool,
+    range: series::DateRange,
     mode_config: ModeConfig,
     model: ResolvedModelConfig,
     chart: report::ChartRenderConfig,
+    gh_pages: Option<publish::GhPagesConfig>,
+    email: Option<notify::EmailConfig>,
+    notifications: Option<notify::NotificationsConfig>,
+    alerts: Vec<alerts::AlertRule>,
+    fetch: fetch::FetchConfig,
+    integrity: Option<integrity::IntegrityConfig>,
+    events: Vec<report::CalendarEvent>,
+    compare: Option<CompareConfig>,
+    front: Option<front::FrontConfig>,
+    retention: Option<gc::RetentionConfig>,
+    tune: TuneConfig,
+    precision: constants::Precision,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CompareConfig {
+    csv: PathBuf,
+    label: String,
+    primary_label: String,
 }
 
 #[derive(Debug, Clone)]
 struct ResolvedModelConfig {
     kind: ModelKind,
     trend_filter: model::TrendFilterConfig,
+    interpolation: series::InterpolationStrategy,
+    regressors: Option<RegressorsConfig>,
+    seasonality: Option<model::SeasonalityConfig>,
+    exclusions: Option<PathBuf>,
+    /// Калибровка интервалов; `None` — гауссовский интервал модели как есть.
+    calibration: Option<model::CalibrationConfig>,
+    /// Стартов подбора LLT-модели.
+    restarts: usize,
+    sigma_prior: model::SigmaPrior,
+    training_window: model::TrainingWindow,
+}
+
+impl ResolvedModelConfig {
+    /// Параметры LLT-модели (она же сглаживает историю при любом `model`).
+    fn llt(&self) -> model::ModelConfig {
+        model::ModelConfig {
+            interpolation: self.interpolation,
+            seasonality: self.seasonality,
+            restarts: self.restarts,
+            sigma_prior: self.sigma_prior,
+            training_window: self.training_window,
+            ..model::ModelConfig::default()
+        }
+    }
+
+    /// Задаёт окно обучения обеим моделям.
+    const fn with_training_window(mut self, window: model::TrainingWindow) -> Self {
+        self.training_window = window;
+        self.trend_filter.training_window = window;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RegressorsConfig {
+    csv: PathBuf,
+    columns: Vec<String>,
 }
 
 fn build_forecast_overlay(forecast: &model::Forecast) -> report::ForecastOverlay {
@@ -395,9 +1639,88 @@ fn build_forecast_overlay(forecast: &model::Forecast) -> report::ForecastOverlay
             .iter()
             .map(|v| v / AREA_THOUSANDS_DIVISOR)
             .collect(),
+        granularity: forecast.granularity,
+        smoothed: None,
+        horizons: Vec::new(),
+        training: forecast.training,
+        diagnostics: None,
+        stale: None,
     }
 }
 
+fn build_smoothed_overlay(smoothed: &model::SmoothedStates) -> report::SmoothedOverlay {
+    let to_thousands = |values: &[f64]| {
+        values
+            .iter()
+            .map(|v| v / AREA_THOUSANDS_DIVISOR)
+            .collect::<Vec<_>>()
+    };
+    let (lower, upper) = smoothed.level_band();
+    let (trend_lower, trend_upper) = smoothed.trend_band();
+    report::SmoothedOverlay {
+        dates: smoothed
+            .dates
+            .iter()
+            .map(|date| date.format(DATE_FORMAT).to_string())
+            .collect(),
+        level: to_thousands(&smoothed.level),
+        lower: to_thousands(&lower),
+        upper: to_thousands(&upper),
+        trend: smoothed.trend.clone(),
+        trend_lower,
+        trend_upper,
+    }
+}
+
+fn resolve_tune_config(config: TuneConfigFile, cwd: &Path) -> Result<TuneConfig, String> {
+    let check = |field: &str, values: &[f64], valid: fn(f64) -> bool, rule: &str| {
+        if values.is_empty() {
+            return Err(format!("Field tune.{field} must not be empty"));
+        }
+        if values
+            .iter()
+            .any(|value| !value.is_finite() || !valid(*value))
+        {
+            return Err(format!(
+                "Field tune.{field} must contain finite values {rule}"
+            ));
+        }
+        Ok(())
+    };
+    check("lambda", &config.lambda, |value| value >= 0.0, ">= 0")?;
+    check("epsilon", &config.epsilon, |value| value > 0.0, "> 0")?;
+    check(
+        "huber_delta",
+        &config.huber_delta,
+        |value| value >= 0.0,
+        ">= 0",
+    )?;
+    check(
+        "damping",
+        &config.damping,
+        |value| (0.0..=1.0).contains(&value),
+        "in [0, 1]",
+    )?;
+
+    Ok(TuneConfig {
+        csv: resolve_runtime_path_from(&config.csv, cwd),
+        output_csv: resolve_runtime_path_from(&config.output_csv, cwd),
+        grid: tune::TuneGrid {
+            lambda: config.lambda,
+            epsilon: config.epsilon,
+            huber_delta: config.huber_delta,
+            damping: config.damping,
+        },
+        backtest: model::BacktestConfig {
+            horizon_days: config.horizon_days.get(),
+            folds: config.folds.get(),
+            gap_days: config.gap_days,
+            train_days: config.train_days.map(NonZeroUsize::get),
+        },
+        persist: config.persist,
+    })
+}
+
 fn resolve_trend_filter_config(overrides: Option<TrendFilterFile>) -> model::TrendFilterConfig {
     let mut cfg = model::TrendFilterConfig::default();
     if let Some(overrides) = overrides {
@@ -420,12 +1743,34 @@ fn resolve_trend_filter_config(overrides: Option<TrendFilterFile>) -> model::Tre
 fn resolve_model_config(
     kind: ModelKind,
     overrides: Option<TrendFilterFile>,
+    interpolation: series::InterpolationStrategy,
+    regressors: Option<RegressorsConfig>,
+    seasonality: Option<model::SeasonalityConfig>,
 ) -> ResolvedModelConfig {
     match kind {
-        ModelKind::TrendFilter => ResolvedModelConfig {
-            kind: ModelKind::TrendFilter,
-            trend_filter: resolve_trend_filter_config(overrides),
-        },
+        ModelKind::TrendFilter => {
+            if regressors.is_some() {
+                tracing::warn!("regressors section ignored for trend filter model");
+            }
+            if seasonality.is_some() {
+                tracing::warn!("seasonality section ignored for trend filter model");
+            }
+            ResolvedModelConfig {
+                kind: ModelKind::TrendFilter,
+                trend_filter: model::TrendFilterConfig {
+                    interpolation,
+                    ..resolve_trend_filter_config(overrides)
+                },
+                interpolation,
+                regressors: None,
+                seasonality: None,
+                exclusions: None,
+                calibration: None,
+                restarts: model::DEFAULT_RESTARTS,
+                sigma_prior: model::SigmaPrior::default(),
+                training_window: model::TrainingWindow::default(),
+            }
+        }
         ModelKind::Llt => {
             if overrides.is_some() {
                 tracing::warn!("trend_filter section ignored for LLT model");
@@ -433,6 +1778,14 @@ fn resolve_model_config(
             ResolvedModelConfig {
                 kind: ModelKind::Llt,
                 trend_filter: model::TrendFilterConfig::default(),
+                interpolation,
+                regressors,
+                seasonality,
+                exclusions: None,
+                calibration: None,
+                restarts: model::DEFAULT_RESTARTS,
+                sigma_prior: model::SigmaPrior::default(),
+                training_window: model::TrainingWindow::default(),
             }
         }
     }
@@ -456,42 +1809,317 @@ fn resolve_runtime_path(path: &Path) -> Result<PathBuf, String> {
     Ok(resolve_runtime_path_from(path, &cwd))
 }
 
+#[allow(clippy::too_many_lines)]
 fn resolve_app_config(config: AppConfigFile, cwd: &Path) -> Result<AppConfig, String> {
-    let model = resolve_model_config(config.model, config.trend_filter);
+    if let (Some(from), Some(to)) = (config.from, config.to)
+        && from > to
+    {
+        return Err(format!(
+            "Field from ({from}) must not be later than to ({to})"
+        ));
+    }
+    let range = series::DateRange {
+        from: config.from,
+        to: config.to,
+        as_of: None,
+    };
+    let precision = resolve_precision(&config.precision)?;
+    let model_interpolation = config.interpolation.into();
+    if model_interpolation == series::InterpolationStrategy::None {
+        return Err(
+            "Field interpolation = \"none\" is only supported in [chart]; the model needs a continuous series"
+                .to_string(),
+        );
+    }
+    let regressors = config.regressors.map(|regressors| RegressorsConfig {
+        csv: resolve_runtime_path_from(&regressors.csv, cwd),
+        columns: regressors.columns,
+    });
+    let seasonality = config
+        .seasonality
+        .map(|seasonality| model::SeasonalityConfig {
+            period: seasonality.period,
+            penalty: seasonality
+                .penalty
+                .map_or(model::DEFAULT_SEASONAL_PENALTY, NonNegativeFinite::get),
+        });
+    let model = ResolvedModelConfig {
+        exclusions: config
+            .exclusions
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        calibration: config
+            .calibration
+            .map(|calibration| model::CalibrationConfig {
+                folds: calibration.folds.get(),
+                gap_days: calibration.gap_days,
+            }),
+        restarts: config
+            .llt
+            .as_ref()
+            .and_then(|llt| llt.restarts)
+            .map_or(model::DEFAULT_RESTARTS, NonZeroUsize::get),
+        sigma_prior: config
+            .llt
+            .and_then(|llt| llt.sigma_prior)
+            .map_or_else(model::SigmaPrior::default, SigmaPriorFile::resolve),
+        ..resolve_model_config(
+            config.model,
+            config.trend_filter,
+            model_interpolation,
+            regressors,
+            seasonality,
+        )
+    }
+    .with_training_window(config.training_window.unwrap_or_default());
+    let reference_lines = config
+        .chart
+        .reference_lines
+        .into_iter()
+        .map(resolve_reference_line)
+        .collect::<Result<Vec<_>, _>>()?;
+    let milestones = config
+        .chart
+        .milestones
+        .into_iter()
+        .map(resolve_milestone)
+        .collect::<Result<Vec<_>, _>>()?;
+    let phases = config
+        .chart
+        .phases
+        .into_iter()
+        .map(resolve_phase)
+        .collect::<Result<Vec<_>, _>>()?;
     let chart = report::ChartRenderConfig {
         gray_zone_start: config.chart.gray_zone_start,
         avg_change_start: config.chart.avg_change_start,
+        downsample: config.chart.downsample,
+        intraday: config.chart.intraday,
+        interpolation: config.chart.interpolation.into(),
+        smoothed: config.chart.smoothed,
+        trend: config.chart.trend,
+        diagnostics: config.chart.diagnostics
+            && if model.kind == ModelKind::Llt {
+                true
+            } else {
+                tracing::warn!("chart.diagnostics ignored for trend filter model");
+                false
+            },
+        change_smoothing: resolve_smoothing(&config.chart.change_smoothing)?,
+        reference_lines,
+        table_days: config.chart.table_days,
+        index_date: config.chart.index_date,
+        milestones,
+        phases,
+        page: report::PageConfig {
+            precision,
+            ..resolve_page_config(config.page, cwd)?
+        },
     };
 
     let run = RunConfig {
         output_html: resolve_runtime_path_from(&config.run.output_html, cwd),
         minify_html: config.run.minify_html,
+        store_csv: resolve_runtime_path_from(&config.run.store_csv, cwd),
         output_history_csv: resolve_runtime_path_from(&config.run.output_history_csv, cwd),
         output_forecast_csv: resolve_runtime_path_from(&config.run.output_forecast_csv, cwd),
         horizon_days: config.run.horizon_days,
+        extra_horizons_days: resolve_extra_horizons(
+            config.run.horizon_days,
+            config.run.extra_horizons_days,
+        ),
+        granularity: config.run.granularity,
+        output_smoothed_csv: config
+            .run
+            .output_smoothed_csv
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        output_trend_csv: config
+            .run
+            .output_trend_csv
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        output_full_resolution_csv: config
+            .run
+            .output_full_resolution_csv
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        output_percent_csv: config
+            .run
+            .output_percent_csv
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        output_figure_json: config
+            .run
+            .output_figure_json
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        output_ratios_json: config
+            .run
+            .output_ratios_json
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        output_gaps_csv: config
+            .run
+            .output_gaps_csv
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        output_xlsx: config
+            .run
+            .output_xlsx
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        output_ics: config
+            .run
+            .output_ics
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        output_updates_html: config
+            .run
+            .output_updates_html
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        output_methodology_html: config
+            .run
+            .output_methodology_html
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        mirror_url: config.run.mirror_url,
+        raw_archive_dir: config
+            .run
+            .raw_archive_dir
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        build_cache: config.run.build_cache,
+        forecast_fallback: config.run.forecast_fallback,
     };
 
+    if run.store_csv == run.output_history_csv {
+        return Err("run.store_csv must differ from run.output_history_csv".to_string());
+    }
+    if config.download.store_csv == config.download.output_csv {
+        return Err("download.store_csv must differ from download.output_csv".to_string());
+    }
+    if config.download.isw_dir.is_some() && config.download.mirror_url.is_some() {
+        return Err("download.isw_dir and download.mirror_url are mutually exclusive".to_string());
+    }
+    if !config.download.isw_pre_war_km2.is_finite() || config.download.isw_pre_war_km2 < 0.0 {
+        return Err("download.isw_pre_war_km2 must be a non-negative number".to_string());
+    }
     let download = DownloadConfig {
+        store_csv: resolve_runtime_path_from(&config.download.store_csv, cwd),
         output_csv: resolve_runtime_path_from(&config.download.output_csv, cwd),
+        mirror_url: config.download.mirror_url,
+        raw_archive_dir: config
+            .download
+            .raw_archive_dir
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        isw: config.download.isw_dir.map(|dir| source::IswSource {
+            dir: resolve_runtime_path_from(&dir, cwd),
+            pre_war_km2: config.download.isw_pre_war_km2,
+            layer: config
+                .download
+                .isw_layer
+                .filter(|layer| !layer.trim().is_empty()),
+        }),
     };
 
     let forecast = ForecastConfig {
         csv: resolve_runtime_path_from(&config.forecast.csv, cwd),
         output_csv: resolve_runtime_path_from(&config.forecast.output_csv, cwd),
         horizon_days: config.forecast.horizon_days,
+        extra_horizons_days: resolve_extra_horizons(
+            config.forecast.horizon_days,
+            config.forecast.extra_horizons_days,
+        ),
+        granularity: config.forecast.granularity,
+        output_smoothed_csv: config
+            .forecast
+            .output_smoothed_csv
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
+        output_trend_csv: config
+            .forecast
+            .output_trend_csv
+            .map(|path| resolve_runtime_path_from(&path, cwd)),
     };
 
     let render_mode = if let Some(path) = config.render.forecast_csv {
+        let output_html = resolve_runtime_path_from(&config.render.output_html, cwd);
+        let csv_url = config
+            .render
+            .csv
+            .to_str()
+            .filter(|source| remote::is_url(source))
+            .map(str::to_string);
         Some(RenderConfig {
-            csv: resolve_runtime_path_from(&config.render.csv, cwd),
+            csv: csv_url.as_deref().map_or_else(
+                || resolve_runtime_path_from(&config.render.csv, cwd),
+                |url| remote::cache_path(output_html.parent().unwrap_or(cwd), url),
+            ),
+            csv_url,
             forecast_csv: resolve_runtime_path_from(&path, cwd),
-            output_html: resolve_runtime_path_from(&config.render.output_html, cwd),
+            output_html,
             minify_html: config.render.minify_html,
+            output_full_resolution_csv: config
+                .render
+                .output_full_resolution_csv
+                .map(|path| resolve_runtime_path_from(&path, cwd)),
+            output_percent_csv: config
+                .render
+                .output_percent_csv
+                .map(|path| resolve_runtime_path_from(&path, cwd)),
+            output_figure_json: config
+                .render
+                .output_figure_json
+                .map(|path| resolve_runtime_path_from(&path, cwd)),
+            output_ratios_json: config
+                .render
+                .output_ratios_json
+                .map(|path| resolve_runtime_path_from(&path, cwd)),
+            output_gaps_csv: config
+                .render
+                .output_gaps_csv
+                .map(|path| resolve_runtime_path_from(&path, cwd)),
+            output_xlsx: config
+                .render
+                .output_xlsx
+                .map(|path| resolve_runtime_path_from(&path, cwd)),
+            output_ics: config
+                .render
+                .output_ics
+                .map(|path| resolve_runtime_path_from(&path, cwd)),
+            output_updates_html: config
+                .render
+                .output_updates_html
+                .map(|path| resolve_runtime_path_from(&path, cwd)),
+            output_methodology_html: config
+                .render
+                .output_methodology_html
+                .map(|path| resolve_runtime_path_from(&path, cwd)),
+            build_cache: config.render.build_cache,
         })
     } else {
         None
     };
 
+    let retention = config.retention.map(|retention| {
+        let raw_archive = if config.mode == Mode::Download {
+            download
+                .raw_archive_dir
+                .clone()
+                .or_else(|| run.raw_archive_dir.clone())
+        } else {
+            run.raw_archive_dir
+                .clone()
+                .or_else(|| download.raw_archive_dir.clone())
+        };
+        // Копии удалённых историй лежат рядом с HTML, который собирает или раздаёт режим.
+        let site_dir = match (config.mode, &render_mode) {
+            (Mode::Render, Some(render)) => render.output_html.parent().map(Path::to_path_buf),
+            (Mode::Serve, _) => Some(resolve_runtime_path_from(&config.serve.site_dir, cwd)),
+            _ => run.output_html.parent().map(Path::to_path_buf),
+        };
+        gc::RetentionConfig {
+            raw_archive,
+            raw_keep_days: retention.raw_keep_days,
+            forecast_dir: retention
+                .forecast_dir
+                .map(|dir| resolve_runtime_path_from(&dir, cwd)),
+            compress_forecasts_after_days: retention.compress_forecasts_after_days,
+            remote_cache: site_dir.map(|dir| dir.join(remote::CACHE_DIR_NAME)),
+            remote_cache_keep_days: retention.remote_cache_keep_days,
+            interval: (retention.interval_hours > 0)
+                .then(|| Duration::from_secs(retention.interval_hours * SECS_PER_HOUR)),
+        }
+    });
+
     let mode_config = match config.mode {
         Mode::Run => ModeConfig::Run(run),
         Mode::Download => ModeConfig::Download(download),
@@ -504,17 +2132,471 @@ fn resolve_app_config(config: AppConfigFile, cwd: &Path) -> Result<AppConfig, St
             };
             ModeConfig::Render(render)
         }
+        Mode::Serve => ModeConfig::Serve(ServeConfig {
+            bind: config.serve.bind,
+            csv: resolve_runtime_path_from(&config.serve.csv, cwd),
+            forecast_csv: resolve_runtime_path_from(&config.serve.forecast_csv, cwd),
+            site_dir: resolve_runtime_path_from(&config.serve.site_dir, cwd),
+            cache_max_age: Duration::from_secs(config.serve.cache_max_age_secs),
+            reload_interval: (config.serve.reload_interval_secs > 0)
+                .then(|| Duration::from_secs(config.serve.reload_interval_secs)),
+            live_interval: (config.serve.live_interval_secs > 0)
+                .then(|| Duration::from_secs(config.serve.live_interval_secs)),
+            precision,
+        }),
     };
 
+    let gh_pages = config.gh_pages.map(|gh_pages| publish::GhPagesConfig {
+        worktree: resolve_runtime_path_from(&gh_pages.worktree, cwd),
+        branch: gh_pages.branch,
+        commit_message: gh_pages.commit_message,
+        push: gh_pages.push,
+        remote: gh_pages.remote,
+    });
+
+    let email = config.email.map(resolve_email_config).transpose()?;
+    let notifications = config.notifications.map(resolve_notifications_config);
+    let alerts = config
+        .alerts
+        .into_iter()
+        .map(resolve_alert)
+        .collect::<Result<Vec<_>, _>>()?;
+    let fetch = fetch::FetchConfig {
+        max_retries: config.fetch.max_retries.get(),
+        retry_delay: Duration::from_secs(config.fetch.retry_delay_secs),
+        connect_timeout: Duration::from_secs(config.fetch.connect_timeout_secs.get()),
+        request_timeout: Duration::from_secs(config.fetch.request_timeout_secs.get()),
+        deadline: config
+            .fetch
+            .deadline_secs
+            .map(|secs| Duration::from_secs(secs.get())),
+        pool_idle_timeout: Duration::from_secs(config.fetch.pool_idle_timeout_secs),
+        pool_max_idle_per_host: config.fetch.pool_max_idle_per_host,
+        tcp_keepalive: (config.fetch.tcp_keepalive_secs > 0)
+            .then(|| Duration::from_secs(config.fetch.tcp_keepalive_secs)),
+        http2_keepalive: (config.fetch.http2_keepalive_secs > 0)
+            .then(|| Duration::from_secs(config.fetch.http2_keepalive_secs)),
+        max_concurrent_requests: config.fetch.max_concurrent_requests.get(),
+        proxy: fetch::ProxyConfig {
+            url: config.fetch.proxy,
+            username: config.fetch.proxy_username,
+            password_env: config.fetch.proxy_password_env,
+            no_proxy: config.fetch.no_proxy,
+        },
+    };
+    let integrity = config
+        .integrity
+        .map(|integrity| integrity::IntegrityConfig {
+            checksums: integrity.checksums,
+            minisign_secret_key: integrity
+                .minisign_secret_key
+                .map(|path| resolve_runtime_path_from(&path, cwd)),
+        });
+
+    let events = config
+        .events
+        .into_iter()
+        .map(|event| {
+            if event.title.trim().is_empty() {
+                return Err(format!(
+                    "Field events.title must not be empty ({})",
+                    event.date
+                ));
+            }
+            Ok(report::CalendarEvent {
+                date: event.date,
+                title: event.title,
+                description: event.description,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let front = config
+        .front
+        .map(|front| {
+            let fills = front
+                .occupied_fills
+                .unwrap_or_else(|| front::FrontConfig::default().occupied_fills);
+            if fills.is_empty() {
+                return Err("Field front.occupied_fills must not be empty".to_string());
+            }
+            Ok(front::FrontConfig {
+                days: front.days.get(),
+                occupied_fills: fills,
+            })
+        })
+        .transpose()?;
+    let compare = config.compare.map(|compare| CompareConfig {
+        csv: resolve_runtime_path_from(&compare.csv, cwd),
+        label: compare.label,
+        primary_label: compare.primary_label,
+    });
+    let tune = resolve_tune_config(config.tune, cwd)?;
+
     Ok(AppConfig {
         mode: config.mode,
         archive_csv: config.archive_csv,
+        range,
         mode_config,
         model,
         chart,
+        gh_pages,
+        email,
+        notifications,
+        alerts,
+        fetch,
+        integrity,
+        events,
+        compare,
+        front,
+        retention,
+        tune,
+        precision,
+    })
+}
+
+fn resolve_precision(precision: &PrecisionFile) -> Result<constants::Precision, String> {
+    let defaults = constants::Precision::default();
+    let resolved = constants::Precision {
+        area_km2: precision.area_km2.unwrap_or(defaults.area_km2),
+        percent: precision.percent.unwrap_or(defaults.percent),
+        rate_km2_per_day: precision
+            .rate_km2_per_day
+            .unwrap_or(defaults.rate_km2_per_day),
+        variance: precision.variance.unwrap_or(defaults.variance),
+        ratio: precision.ratio.unwrap_or(defaults.ratio),
+        chart: precision.chart.unwrap_or(defaults.chart),
+    };
+    for (field, decimals) in [
+        ("area_km2", resolved.area_km2),
+        ("percent", resolved.percent),
+        ("rate_km2_per_day", resolved.rate_km2_per_day),
+        ("variance", resolved.variance),
+        ("ratio", resolved.ratio),
+        ("chart", resolved.chart),
+    ] {
+        if decimals > constants::MAX_PRECISION_DECIMALS {
+            return Err(format!(
+                "Field precision.{field} must be at most {}",
+                constants::MAX_PRECISION_DECIMALS
+            ));
+        }
+    }
+    Ok(resolved)
+}
+
+fn resolve_page_config(page: PageFile, cwd: &Path) -> Result<report::PageConfig, String> {
+    let defaults = report::PageConfig::default();
+    if page.analytics_endpoint.is_some() && !page.analytics_events {
+        tracing::warn!("page.analytics_endpoint ignored without analytics_events = true");
+    }
+    let regions = resolve_regions(page.regions)?;
+    let comparison_sets = if page.comparison_sets.is_empty() {
+        defaults.comparison_sets
+    } else {
+        resolve_comparison_sets(page.comparison_sets, cwd)?
+    };
+    Ok(report::PageConfig {
+        title: page.title.unwrap_or(defaults.title),
+        subtitle: page.subtitle.unwrap_or(defaults.subtitle),
+        description: page.description.unwrap_or(defaults.description),
+        keywords: page.keywords.unwrap_or(defaults.keywords),
+        site_url: page.site_url.unwrap_or(defaults.site_url),
+        site_name: page.site_name.unwrap_or(defaults.site_name),
+        favicon: page.favicon.unwrap_or(defaults.favicon),
+        repo_url: match page.repo_url {
+            Some(url) if url.trim().is_empty() => None,
+            Some(url) => Some(url),
+            None => defaults.repo_url,
+        },
+        repo_text: page.repo_text.unwrap_or(defaults.repo_text),
+        analytics_html: page
+            .analytics_html
+            .filter(|snippet| !snippet.trim().is_empty()),
+        analytics_events: page.analytics_events,
+        features: report::ReportFeatures {
+            forecast: page.features.forecast.unwrap_or(defaults.features.forecast),
+            comparison_tables: page
+                .features
+                .comparison_tables
+                .unwrap_or(defaults.features.comparison_tables),
+            change_panel: page
+                .features
+                .change_panel
+                .unwrap_or(defaults.features.change_panel),
+            map_link: page.features.map_link.unwrap_or(defaults.features.map_link),
+        },
+        area_unit: page.area_unit.unwrap_or(defaults.area_unit),
+        locale: page.locale.unwrap_or(defaults.locale),
+        analytics_endpoint: page
+            .analytics_endpoint
+            .filter(|endpoint| !endpoint.trim().is_empty()),
+        regions,
+        comparison_sets,
+        wasm_url: page.wasm_url.filter(|url| !url.trim().is_empty()),
+        fonts: page.fonts,
+        fonts_css_url: defaults.fonts_css_url,
+        flag_base_url: defaults.flag_base_url,
+        self_host_assets: page.self_host_assets,
+        precision: defaults.precision,
+    })
+}
+
+fn resolve_regions(regions: Vec<RegionFile>) -> Result<Vec<report::RegionOccupation>, String> {
+    let mut seen = std::collections::BTreeSet::new();
+    regions
+        .into_iter()
+        .map(|region| {
+            let Some(area) = report::region_area(&region.name) else {
+                return Err(format!(
+                    "Unknown page.regions.name `{}` (expected one of: {})",
+                    region.name,
+                    report::region_names().collect::<Vec<_>>().join(", ")
+                ));
+            };
+            if !region.occupied_km2.is_finite() || !(0.0..=area).contains(&region.occupied_km2) {
+                return Err(format!(
+                    "Field page.regions.occupied_km2 must be between 0 and {area} ({})",
+                    region.name
+                ));
+            }
+            if !seen.insert(region.name.clone()) {
+                return Err(format!(
+                    "Region {} is listed twice in page.regions",
+                    region.name
+                ));
+            }
+            Ok(report::RegionOccupation {
+                name: region.name,
+                occupied_km2: region.occupied_km2,
+                as_of: region.as_of,
+            })
+        })
+        .collect()
+}
+
+fn resolve_comparison_sets(
+    sets: Vec<ComparisonSetFile>,
+    cwd: &Path,
+) -> Result<Vec<report::ComparisonSet>, String> {
+    let mut seen = std::collections::BTreeSet::new();
+    sets.into_iter()
+        .map(|set| {
+            let mut resolved = match (set.builtin, set.csv) {
+                (Some(key), None) => report::ComparisonSet::builtin(&key).ok_or_else(|| {
+                    format!(
+                        "Unknown page.comparison_sets.builtin `{key}` (expected one of: {})",
+                        report::comparison_set_keys().collect::<Vec<_>>().join(", ")
+                    )
+                })?,
+                (None, Some(csv)) => {
+                    let path = resolve_runtime_path_from(&csv, cwd);
+                    // Ключ, заголовок и колонку ниже можно переопределить полями набора.
+                    let stem = path
+                        .file_stem()
+                        .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+                    let name_column = "Территория".to_string();
+                    report::ComparisonSet::from_csv(stem.clone(), stem, name_column, &path)
+                        .map_err(|err| {
+                            format!("Failed to read comparison set {}: {err}", path.display())
+                        })?
+                }
+                _ => {
+                    return Err(
+                        "Exactly one of page.comparison_sets.builtin and csv must be set"
+                            .to_string(),
+                    );
+                }
+            };
+            if let Some(key) = set.key {
+                resolved.key = key;
+            }
+            if let Some(title) = set.title {
+                resolved.title = title;
+            }
+            if let Some(name_column) = set.name_column {
+                resolved.name_column = name_column;
+            }
+            if resolved.key.trim().is_empty() {
+                return Err("Field page.comparison_sets.key must not be empty".to_string());
+            }
+            if !seen.insert(resolved.key.clone()) {
+                return Err(format!(
+                    "Comparison set {} is listed twice in page.comparison_sets",
+                    resolved.key
+                ));
+            }
+            Ok(resolved)
+        })
+        .collect()
+}
+
+fn resolve_smoothing(smoothing: &SmoothingFile) -> Result<report::SmoothingSpec, String> {
+    if smoothing.min_periods > smoothing.window {
+        return Err(format!(
+            "Field chart.change_smoothing.min_periods ({}) must not exceed window ({})",
+            smoothing.min_periods, smoothing.window
+        ));
+    }
+    Ok(report::SmoothingSpec {
+        method: smoothing.method,
+        window: smoothing.window.get(),
+        min_periods: smoothing.min_periods.get(),
+    })
+}
+
+fn resolve_reference_line(line: ReferenceLineFile) -> Result<report::ReferenceLine, String> {
+    if line.label.trim().is_empty() {
+        return Err("Field chart.reference_lines.label must not be empty".to_string());
+    }
+    let level = match (line.date, line.area_km2) {
+        (Some(date), None) => report::ReferenceLevel::AsOf(date),
+        (None, Some(area_km2)) if area_km2.is_finite() && area_km2 >= 0.0 => {
+            report::ReferenceLevel::AreaKm2(area_km2)
+        }
+        (None, Some(_)) => {
+            return Err(format!(
+                "Field chart.reference_lines.area_km2 must be a non-negative number ({})",
+                line.label
+            ));
+        }
+        _ => {
+            return Err(format!(
+                "Exactly one of chart.reference_lines.date and area_km2 must be set ({})",
+                line.label
+            ));
+        }
+    };
+    Ok(report::ReferenceLine {
+        label: line.label,
+        level,
+    })
+}
+
+fn resolve_milestone(milestone: MilestoneFile) -> Result<report::Milestone, String> {
+    if milestone.label.trim().is_empty() {
+        return Err("Field chart.milestones.label must not be empty".to_string());
+    }
+    let area_km2 = match (milestone.area_km2, milestone.percent) {
+        (Some(area_km2), None) => area_km2,
+        (None, Some(percent)) => percent / 100.0 * UKRAINE_AREA_SQ_KM,
+        _ => {
+            return Err(format!(
+                "Exactly one of chart.milestones.area_km2 and percent must be set ({})",
+                milestone.label
+            ));
+        }
+    };
+    if !area_km2.is_finite() || area_km2 < 0.0 {
+        return Err(format!(
+            "Milestone area must be a non-negative number ({})",
+            milestone.label
+        ));
+    }
+    Ok(report::Milestone {
+        label: milestone.label,
+        area_km2,
+    })
+}
+
+fn resolve_phase(phase: PhaseFile) -> Result<report::Phase, String> {
+    if phase.label.trim().is_empty() {
+        return Err("Field chart.phases.label must not be empty".to_string());
+    }
+    if phase.end.is_some_and(|end| end < phase.start) {
+        return Err(format!(
+            "Field chart.phases.end must not be before start ({})",
+            phase.label
+        ));
+    }
+    Ok(report::Phase {
+        label: phase.label,
+        start: phase.start,
+        end: phase.end,
     })
 }
 
+fn resolve_email_config(email: EmailFile) -> Result<notify::EmailConfig, String> {
+    if email.to.is_empty() {
+        return Err("Field email.to must list at least one recipient".to_string());
+    }
+    Ok(notify::EmailConfig {
+        smtp_host: email.smtp_host,
+        smtp_port: email.smtp_port,
+        security: match email.security {
+            SmtpSecurityFile::Starttls => notify::SmtpSecurity::StartTls,
+            SmtpSecurityFile::Tls => notify::SmtpSecurity::Tls,
+        },
+        username: email.username,
+        password_env: email.password_env,
+        from: email.from,
+        to: email.to,
+        subject: email.subject,
+        schedule: match email.schedule {
+            DigestScheduleFile::Daily => notify::DigestSchedule::Daily,
+            DigestScheduleFile::Weekly => notify::DigestSchedule::Weekly(email.weekday),
+        },
+    })
+}
+
+fn resolve_notifications_config(notifications: NotificationsFile) -> notify::NotificationsConfig {
+    let targets = notifications
+        .targets
+        .into_iter()
+        .map(|target| match target {
+            NotifierFile::Telegram { chat_id, token_env } => {
+                notify::NotifierTarget::Telegram(notify::TelegramNotifier { chat_id, token_env })
+            }
+            NotifierFile::Discord { webhook_env } => {
+                notify::NotifierTarget::Discord(notify::DiscordNotifier { webhook_env })
+            }
+            NotifierFile::Slack { webhook_env } => {
+                notify::NotifierTarget::Slack(notify::SlackNotifier { webhook_env })
+            }
+        })
+        .collect();
+    notify::NotificationsConfig {
+        link: notifications.link,
+        targets,
+    }
+}
+
+fn resolve_alert(alert: AlertFile) -> Result<alerts::AlertRule, String> {
+    let (name, condition) = match alert {
+        AlertFile::DailyChange {
+            name,
+            above_km2,
+            days,
+        } if above_km2.is_finite() => (
+            name,
+            alerts::AlertCondition::DailyChange {
+                above_km2,
+                days: days.get(),
+            },
+        ),
+        AlertFile::ForecastUpper { name, crosses_km2 }
+            if crosses_km2.is_finite() && crosses_km2 > 0.0 =>
+        {
+            (
+                name,
+                alerts::AlertCondition::ForecastUpper { km2: crosses_km2 },
+            )
+        }
+        AlertFile::Stale { name, hours } => {
+            (name, alerts::AlertCondition::Stale { hours: hours.get() })
+        }
+        AlertFile::DailyChange { .. } => {
+            return Err("Field alerts.above_km2 must be a finite number".to_string());
+        }
+        AlertFile::ForecastUpper { .. } => {
+            return Err("Field alerts.crosses_km2 must be a positive number".to_string());
+        }
+    };
+    let name = name
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| condition.default_name());
+    Ok(alerts::AlertRule { name, condition })
+}
+
 fn load_app_config(path: &Path) -> Result<AppConfig, String> {
     if !path.exists() {
         return Err(format!("Config {} does not exist", path.display()));
@@ -528,131 +2610,1469 @@ fn load_app_config(path: &Path) -> Result<AppConfig, String> {
     resolve_app_config(parsed, &cwd)
 }
 
-fn train_forecast_from_csv(
-    csv_path: &Path,
+fn resolve_extra_horizons(
     horizon_days: NonZeroUsize,
+    mut extra: Vec<NonZeroUsize>,
+) -> Vec<NonZeroUsize> {
+    extra.retain(|days| *days != horizon_days);
+    extra.sort_unstable();
+    extra.dedup();
+    extra
+}
+
+/// Обучает модель один раз и строит по ней прогнозы на каждый из `horizons_days`
+/// (в том же порядке).
+fn train_forecasts_from_buckets(
+    buckets: &AreaBuckets,
+    horizons_days: &[NonZeroUsize],
+    granularity: series::Granularity,
     model_config: &ResolvedModelConfig,
+) -> Result<(Vec<model::Forecast>, Option<model::Calibration>), String> {
+    let steps = |horizon_days: &NonZeroUsize| granularity.steps(horizon_days.get());
+    let regressors = load_regressors(model_config)?;
+    let mut forecasts = match model_config.kind {
+        ModelKind::TrendFilter => {
+            model::train_trend_filter_from_buckets(buckets, model_config.trend_filter, granularity)
+                .map(|fitted| {
+                    info(&format!(
+                        "Fitted trend filter: {}",
+                        fitted.optimization.describe()
+                    ));
+                    horizons_days
+                        .iter()
+                        .map(|days| fitted.forecast(steps(days)))
+                        .collect::<Vec<_>>()
+                })
+                .map_err(|err| err.to_string())?
+        }
+        ModelKind::Llt => {
+            let fitted = model::train_from_buckets(
+                buckets,
+                model_config.llt(),
+                regressors.as_ref(),
+                granularity,
+            )
+            .map_err(|err| err.to_string())?;
+            tracing::debug!(
+                nll = fitted.nll,
+                sigma_level = fitted.sigma_level,
+                sigma_trend = fitted.sigma_trend,
+                sigma_obs = fitted.sigma_obs,
+                "Fitted LLT model"
+            );
+            info(&format!(
+                "Fitted LLT model: {}",
+                fitted.optimization.describe()
+            ));
+            if let Some(regression) = &fitted.regression {
+                for (name, coefficient) in regression.names.iter().zip(&regression.coefficients) {
+                    info(&format!("Regressor {name}: {coefficient:+.3} km² per unit"));
+                }
+            }
+            if let Some(seasonality) = &fitted.seasonality {
+                for (group, effect) in seasonality.effects.iter().enumerate() {
+                    info(&format!(
+                        "Seasonal effect {}: {effect:+.1} km²/day",
+                        seasonality.period.label(group)
+                    ));
+                }
+            }
+            horizons_days
+                .iter()
+                .map(|days| fitted.forecast(steps(days)))
+                .collect()
+        }
+    };
+    let (Some(config), Some(primary)) = (model_config.calibration, horizons_days.first()) else {
+        return Ok((forecasts, None));
+    };
+    let calibration = model::calibrate_from_buckets(
+        buckets,
+        model_config.interpolation,
+        model_config.training_window,
+        granularity,
+        steps(primary),
+        config,
+        |dates, values, steps| {
+            forecast_from_series(dates, values, steps, model_config, regressors.as_ref())
+        },
+    )
+    .map_err(|err| format!("Failed to calibrate forecast intervals: {err}"))?;
+    info(&format!(
+        "Calibrated forecast intervals: width ×{:.2}, backtest coverage {:.0}% of nominal {:.0}% over {} days",
+        calibration.factor,
+        calibration.empirical_coverage * 100.0,
+        calibration.nominal_coverage * 100.0,
+        calibration.residuals
+    ));
+    for forecast in &mut forecasts {
+        forecast.calibrate(calibration.factor);
+    }
+    Ok((forecasts, Some(calibration)))
+}
+
+/// Обучает модель из `model_config` на части ряда и прогнозирует `steps` шагов (для бэктеста).
+fn forecast_from_series(
+    dates: &[NaiveDate],
+    values: &[f64],
+    steps: usize,
+    model_config: &ResolvedModelConfig,
+    regressors: Option<&model::Regressors>,
 ) -> Result<model::Forecast, String> {
     match model_config.kind {
         ModelKind::TrendFilter => {
-            model::train_trend_filter_from_csv(csv_path, model_config.trend_filter)
-                .map(|fitted| fitted.forecast(horizon_days.get()))
-                .map_err(|err| err.to_string())
+            model::train_trend_filter_from_series(dates, values, model_config.trend_filter)
+                .map(|fitted| fitted.forecast(steps))
+        }
+        ModelKind::Llt => {
+            model::train_from_series_with_regressors(dates, values, model_config.llt(), regressors)
+                .map(|fitted| fitted.forecast(steps))
+        }
+    }
+    .map_err(|err| err.to_string())
+}
+
+/// CSV прогноза на дополнительный горизонт: `forecast.csv` → `forecast_365d.csv`.
+fn horizon_csv_path(forecast_csv: &Path, horizon_days: NonZeroUsize) -> PathBuf {
+    let stem = forecast_csv
+        .file_stem()
+        .map_or_else(|| "forecast".into(), |stem| stem.to_string_lossy());
+    let extension = forecast_csv
+        .extension()
+        .map_or_else(|| "csv".into(), |extension| extension.to_string_lossy());
+    forecast_csv.with_file_name(format!("{stem}_{horizon_days}d.{extension}"))
+}
+
+/// Пишет прогнозы на дополнительные горизонты рядом с `forecast_csv` и возвращает их пути.
+fn write_extra_forecasts(
+    forecasts: &[model::Forecast],
+    horizons_days: &[NonZeroUsize],
+    forecast_csv: &Path,
+    precision: constants::Precision,
+) -> Result<Vec<PathBuf>, String> {
+    forecasts
+        .iter()
+        .zip(horizons_days)
+        .map(|(forecast, days)| {
+            let path = horizon_csv_path(forecast_csv, *days);
+            model::write_forecast_csv(forecast, &path, precision)
+                .map_err(|err| format!("Failed to write forecast CSV {}: {err}", path.display()))?;
+            success(&format!("Saved {days}-day forecast to {}", path.display()));
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Прогнозы прошлой сборки на все горизонты (основной первым) из CSV или их архивов.
+fn load_previous_forecasts(
+    forecast_csv: &Path,
+    extra_horizons_days: &[NonZeroUsize],
+    archive: bool,
+) -> Result<Vec<model::Forecast>, String> {
+    std::iter::once(forecast_csv.to_path_buf())
+        .chain(
+            extra_horizons_days
+                .iter()
+                .map(|days| horizon_csv_path(forecast_csv, *days)),
+        )
+        .map(|csv_path| {
+            let path = if archive {
+                archive_path_for(&csv_path)?
+            } else {
+                csv_path
+            };
+            let file = File::open(&path)
+                .map_err(|err| format!("Failed to open {}: {err}", path.display()))?;
+            let reader = if archive {
+                csv::Reader::from_reader(Box::new(GzDecoder::new(file)) as Box<dyn Read>)
+            } else {
+                csv::Reader::from_reader(Box::new(file) as Box<dyn Read>)
+            };
+            model::read_forecast_from_reader(reader)
+                .map_err(|err| format!("Failed to read {}: {err}", path.display()))
+        })
+        .collect()
+}
+
+/// Отметка для страницы: по какой день обучен прогноз прошлой сборки и насколько он отстал.
+fn stale_forecast(forecast: &model::Forecast, buckets: &AreaBuckets) -> report::StaleForecast {
+    let trained_to = forecast.training.map_or_else(
+        || {
+            forecast
+                .dates
+                .first()
+                .and_then(NaiveDate::pred_opt)
+                .unwrap_or_default()
+        },
+        |span| span.to,
+    );
+    let latest = buckets
+        .latest_snapshot_time()
+        .map_or(trained_to, |time| time.date_naive());
+    report::StaleForecast {
+        trained_to,
+        age_days: (latest - trained_to).num_days().max(0),
+    }
+}
+
+/// Сжимает CSV в архивы рядом с ними и удаляет исходники (`archive_csv = true`).
+fn archive_and_remove(csv_paths: &[PathBuf]) -> Result<(), String> {
+    for csv_path in csv_paths {
+        let path = archive_csv_file(csv_path)?;
+        success(&format!("Saved archive to {}", path.display()));
+        remove_csv_file(csv_path)?;
+    }
+    Ok(())
+}
+
+fn load_regressors(
+    model_config: &ResolvedModelConfig,
+) -> Result<Option<model::Regressors>, String> {
+    model_config
+        .regressors
+        .as_ref()
+        .map(|config| {
+            model::Regressors::from_csv(&config.csv, &config.columns)
+                .map_err(|err| format!("Failed to read {}: {err}", config.csv.display()))
+        })
+        .transpose()
+}
+
+/// Входные файлы модели помимо истории: регрессоры и список исключений.
+fn model_inputs(model_config: &ResolvedModelConfig) -> [Option<&Path>; 2] {
+    [
+        model_config
+            .regressors
+            .as_ref()
+            .map(|regressors| regressors.csv.as_path()),
+        model_config.exclusions.as_deref(),
+    ]
+}
+
+/// Убирает из обучения дни из списка исключений; на графике они остаются полыми маркерами.
+fn apply_exclusions(
+    buckets: &mut AreaBuckets,
+    model_config: &ResolvedModelConfig,
+) -> Result<(), String> {
+    let Some(path) = &model_config.exclusions else {
+        return Ok(());
+    };
+    buckets.exclude_from_training(exclusions::load(path)?);
+    for (date, note) in buckets.excluded_days() {
+        info(&format!(
+            "Excluding {} from training: {note}",
+            date.format(DATE_FORMAT)
+        ));
+    }
+    Ok(())
+}
+
+/// Закрепляет входы на моменте `--as-of`. Режимы, которые скачивают или раздают живые данные,
+/// закрепить нельзя: их результат прошлое не воспроизводит.
+/// Флаги `--from` и `--to` переопределяют одноимённые поля конфига.
+fn override_range(
+    config: AppConfig,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<AppConfig, String> {
+    let range = series::DateRange {
+        from: from.or(config.range.from),
+        to: to.or(config.range.to),
+        ..config.range
+    };
+    if let (Some(from), Some(to)) = (range.from, range.to)
+        && from > to
+    {
+        return Err(format!(
+            "Date range start {from} must not be later than its end {to}"
+        ));
+    }
+    Ok(AppConfig { range, ..config })
+}
+
+/// Флаг `--no-downsample` выключает прореживание графика поверх `[chart] downsample`.
+fn override_downsample(config: AppConfig, no_downsample: bool) -> AppConfig {
+    if !no_downsample {
+        return config;
+    }
+    AppConfig {
+        chart: report::ChartRenderConfig {
+            downsample: false,
+            ..config.chart
+        },
+        ..config
+    }
+}
+
+fn pin_as_of(config: AppConfig, as_of: Option<DateTime<Utc>>) -> Result<AppConfig, String> {
+    let Some(as_of) = as_of else {
+        return Ok(config);
+    };
+    if matches!(config.mode, Mode::Run | Mode::Download | Mode::Serve) {
+        return Err(format!(
+            "--as-of is not supported in mode = \"{}\"; use forecast or render",
+            config.mode
+        ));
+    }
+    Ok(AppConfig {
+        range: series::DateRange {
+            as_of: Some(as_of),
+            ..config.range
+        },
+        ..config
+    })
+}
+
+/// Прогноз из CSV при `--as-of` должен быть обучен только на известной тогда истории, иначе
+/// страница показала бы то, чего в тот момент ещё не знали.
+fn check_forecast_as_of(
+    overlay: &report::ForecastOverlay,
+    as_of: Option<DateTime<Utc>>,
+) -> Result<(), String> {
+    let Some(as_of) = as_of else {
+        return Ok(());
+    };
+    let last_known = overlay.training.map(|training| training.to).or_else(|| {
+        overlay
+            .dates
+            .first()
+            .and_then(|date| NaiveDate::parse_from_str(date, DATE_FORMAT).ok())
+            .and_then(|date| date.pred_opt())
+    });
+    match last_known {
+        Some(day) if day > as_of.date_naive() => Err(format!(
+            "Forecast CSV uses history through {day}, after --as-of {}; rebuild it with mode = \"forecast\" and the same --as-of",
+            as_of.to_rfc3339()
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn load_forecast_overlay(forecast_csv: &Path) -> Result<report::ForecastOverlay, String> {
+    model::read_forecast_csv(forecast_csv)
+        .map(|forecast| build_forecast_overlay(&forecast))
+        .map_err(|err| err.to_string())
+}
+
+fn init_logging() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("rua=info"));
+    let ansi = rua_core::platform::ansi_enabled(&std::io::stdout());
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(false)
+        .with_ansi(ansi)
+        .compact()
+        .init();
+}
+
+fn headline(message: &str) {
+    tracing::info!(status = "start", "{message}");
+}
+
+fn info(message: &str) {
+    tracing::info!(status = "info", "{message}");
+}
+
+fn success(message: &str) {
+    tracing::info!(status = "ok", "{message}");
+}
+
+fn error(message: &str) {
+    tracing::error!(status = "err", "{message}");
+}
+
+/// Каталоги выгрузок и сохранённые данные, которые проверяет `doctor`.
+struct DoctorTargets {
+    outputs: Vec<PathBuf>,
+    history_csv: Option<PathBuf>,
+    forecast_csv: Option<PathBuf>,
+}
+
+impl Default for DoctorTargets {
+    fn default() -> Self {
+        Self {
+            outputs: vec![default_output_html()],
+            history_csv: Some(PathBuf::from(DEFAULT_STORE_CSV)),
+            forecast_csv: Some(PathBuf::from(DEFAULT_FORECAST_CSV)),
+        }
+    }
+}
+
+fn doctor_targets(mode_config: &ModeConfig) -> DoctorTargets {
+    let optional = |paths: &[&Option<PathBuf>]| {
+        paths
+            .iter()
+            .filter_map(|path| (*path).clone())
+            .collect::<Vec<_>>()
+    };
+    match mode_config {
+        ModeConfig::Run(config) => DoctorTargets {
+            outputs: [
+                config.output_html.clone(),
+                config.output_history_csv.clone(),
+            ]
+            .into_iter()
+            .chain(optional(&[
+                &config.output_smoothed_csv,
+                &config.output_trend_csv,
+                &config.output_full_resolution_csv,
+                &config.output_percent_csv,
+                &config.output_figure_json,
+                &config.output_ratios_json,
+                &config.output_gaps_csv,
+                &config.output_xlsx,
+                &config.output_ics,
+                &config.output_updates_html,
+            ]))
+            .collect(),
+            history_csv: Some(config.store_csv.clone()),
+            forecast_csv: Some(config.output_forecast_csv.clone()),
+        },
+        ModeConfig::Download(config) => DoctorTargets {
+            outputs: vec![config.output_csv.clone()],
+            history_csv: Some(config.store_csv.clone()),
+            forecast_csv: None,
+        },
+        ModeConfig::Forecast(config) => DoctorTargets {
+            outputs: optional(&[&config.output_smoothed_csv, &config.output_trend_csv]),
+            history_csv: Some(config.csv.clone()),
+            forecast_csv: Some(config.output_csv.clone()),
+        },
+        ModeConfig::Render(config) => DoctorTargets {
+            outputs: std::iter::once(config.output_html.clone())
+                .chain(optional(&[
+                    &config.output_full_resolution_csv,
+                    &config.output_percent_csv,
+                    &config.output_figure_json,
+                    &config.output_ratios_json,
+                    &config.output_gaps_csv,
+                    &config.output_xlsx,
+                    &config.output_ics,
+                    &config.output_updates_html,
+                ]))
+                .collect(),
+            history_csv: Some(config.csv.clone()),
+            forecast_csv: Some(config.forecast_csv.clone()),
+        },
+        ModeConfig::Serve(config) => DoctorTargets {
+            outputs: Vec::new(),
+            history_csv: Some(config.csv.clone()),
+            forecast_csv: Some(config.forecast_csv.clone()),
+        },
+    }
+}
+
+/// Код выхода подкоманды: ошибка печатается, и процесс завершается с ненулевым кодом.
+fn exit_code(result: Result<(), String>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            error(&err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Запускает проверки `doctor` и выводит по строке на каждую; без `--config` проверяются
+/// сетевые настройки и пути по умолчанию. Возвращает число проваленных проверок.
+async fn run_doctor(config_path: Option<&Path>) -> usize {
+    let mut checks = Vec::new();
+    let config = config_path.and_then(|path| {
+        match resolve_runtime_path(path).and_then(|path| load_app_config(&path)) {
+            Ok(config) => {
+                checks.push(doctor::Check::new(
+                    "config",
+                    doctor::Status::Ok,
+                    path.display().to_string(),
+                ));
+                Some(config)
+            }
+            Err(err) => {
+                checks.push(doctor::Check::new(
+                    "config",
+                    doctor::Status::Fail,
+                    format!("{err}; the remaining checks use default settings"),
+                ));
+                None
+            }
+        }
+    });
+    let fetch_config = config
+        .as_ref()
+        .map_or_else(fetch::FetchConfig::default, |config| config.fetch.clone());
+    let targets = config
+        .as_ref()
+        .map_or_else(DoctorTargets::default, |config| {
+            doctor_targets(&config.mode_config)
+        });
+
+    let proxy = doctor::check_proxy(&fetch_config.proxy);
+    let via_proxy = proxy.status == doctor::Status::Ok
+        && matches!(fetch::describe_proxy(&fetch_config.proxy), Ok(Some(_)));
+    checks.push(proxy);
+    let client = fetch::build_client(&fetch_config);
+    checks.extend(
+        doctor::check_api(&client, DeepStateSource::default().probe_url(), via_proxy).await,
+    );
+
+    let output_dirs = targets
+        .outputs
+        .iter()
+        .chain(&targets.history_csv)
+        .chain(&targets.forecast_csv)
+        .map(|path| {
+            path.parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or_else(|| Path::new("."))
+                .to_path_buf()
+        })
+        .collect::<std::collections::BTreeSet<_>>();
+    checks.extend(output_dirs.iter().map(|dir| doctor::check_writable(dir)));
+    checks.extend(
+        targets
+            .history_csv
+            .iter()
+            .map(|path| doctor::check_history(path)),
+    );
+    checks.extend(
+        targets
+            .forecast_csv
+            .iter()
+            .map(|path| doctor::check_forecast(path)),
+    );
+
+    let mut failed = 0usize;
+    for check in &checks {
+        let line = format!("{}: {}", check.name, check.detail);
+        match check.status {
+            doctor::Status::Ok => success(&line),
+            doctor::Status::Warn => tracing::warn!(status = "warn", "{line}"),
+            doctor::Status::Fail => {
+                failed += 1;
+                error(&line);
+            }
+        }
+    }
+    if failed == 0 {
+        success(&format!("All {} checks passed", checks.len()));
+    } else {
+        error(&format!("{failed} of {} checks failed", checks.len()));
+    }
+    failed
+}
+
+fn write_report_diff(args: &ReportDiffArgs) -> Result<(), String> {
+    let old = diff::load(&resolve_runtime_path(&args.old)?)?;
+    let new = diff::load(&resolve_runtime_path(&args.new)?)?;
+    let text = diff::describe(&old, &new);
+    match &args.output {
+        Some(output) => {
+            let output = resolve_runtime_path(output)?;
+            fs::write(&output, format!("{text}\n"))
+                .map_err(|err| format!("Failed to write {}: {err}", output.display()))?;
+            success(&format!("Saved report diff to {}", output.display()));
+        }
+        None => println!("{text}"),
+    }
+    Ok(())
+}
+
+/// Прогоняет архив сырых ответов через текущий разбор, печатает отличия от CSV и, если это
+/// не `--dry-run`, заменяет им историю.
+fn rebuild_history(args: &RebuildHistoryArgs) -> Result<(), String> {
+    let archive = resolve_runtime_path(&args.archive)?;
+    let csv = resolve_runtime_path(&args.csv)?;
+    let replayed = source::replay_raw_archive(&archive)?;
+    if replayed.snapshots == 0 {
+        return Err(format!("{} has no archived snapshots", archive.display()));
+    }
+    info(&format!(
+        "Replayed {} archived snapshots from {}",
+        replayed.snapshots,
+        archive.display()
+    ));
+    let old = if csv.exists() {
+        data::read_areas(&csv)?
+    } else {
+        Vec::new()
+    };
+    let (areas, diff) = rebuild::rebuild(old, replayed.areas);
+    println!("{}", diff.to_text());
+    if args.dry_run {
+        info("Dry run; history is unchanged");
+        return Ok(());
+    }
+    CsvStore::new(&csv).replace(areas)?;
+    success(&format!("Rebuilt history in {}", csv.display()));
+    Ok(())
+}
+
+/// Применяет `[retention]` из конфига; без раздела — ошибка, чтобы опечатка не прошла молча.
+fn run_gc(config_path: Option<&Path>, args: &GcArgs) -> Result<(), String> {
+    let config_path = config_path.ok_or("gc needs --config with a [retention] section")?;
+    let config = load_app_config(&resolve_runtime_path(config_path)?)?;
+    let retention = config
+        .retention
+        .ok_or_else(|| format!("{} has no [retention] section", config_path.display()))?;
+    let summary = gc::collect(&retention, SystemTime::now(), args.dry_run)?;
+    if args.dry_run {
+        info(&format!("Dry run: {}", summary.to_text()));
+    } else {
+        success(&summary.to_text());
+    }
+    Ok(())
+}
+
+/// Подбирает trend filter по разделу `[tune]` конфига. `--from`/`--to`, `--as-of` и
+/// `--training-window` сужают историю так же, как у прогноза; `mode` конфига не важен.
+async fn run_tune(args: &Args, tune_args: &TuneArgs) -> Result<(), String> {
+    let config_path = args
+        .config
+        .as_deref()
+        .ok_or("tune needs --config with a [tune] section")?;
+    let config_path = resolve_runtime_path(config_path)?;
+    let config = override_range(load_app_config(&config_path)?, args.from, args.to)?;
+    let model_config = match args.training_window {
+        Some(window) => config.model.with_training_window(window),
+        None => config.model,
+    };
+    let range = series::DateRange {
+        as_of: args.as_of,
+        ..config.range
+    };
+    let tune = TuneConfig {
+        persist: config.tune.persist || tune_args.persist,
+        ..config.tune
+    };
+    let _instance = instance::InstanceLock::acquire(
+        tune.output_csv.parent().unwrap_or_else(|| Path::new(".")),
+        args.wait_for_lock.map(Duration::from_secs),
+    )
+    .await?;
+    tracing::info!(
+        input_csv = %tune.csv.display(),
+        output_csv = %tune.output_csv.display(),
+        persist = tune.persist,
+        range = %range,
+        "Tuning trend filter"
+    );
+    tune_trend_filter(&tune, &model_config, range, &config_path)
+}
+
+/// Дописывает ручную точку; дни, которые уже есть в истории, не перезаписываются.
+fn append_manual_point(args: &AppendArgs) -> Result<(), String> {
+    for (field, value) in [
+        ("--area-occupied", args.area_occupied),
+        ("--area-ua", args.area_ua),
+    ] {
+        if !value.is_finite() || value < 0.0 {
+            return Err(format!(
+                "{field} must be a non-negative number, got {value}"
+            ));
+        }
+    }
+    if args.area_ua > args.area_occupied {
+        return Err(format!(
+            "--area-ua ({}) must not exceed --area-occupied ({})",
+            args.area_ua, args.area_occupied
+        ));
+    }
+    let csv = resolve_runtime_path(&args.csv)?;
+    let mut store = CsvStore::new(&csv);
+    if csv.exists() && store.load_buckets()?.has_day(args.date) {
+        return Err(format!(
+            "{} already has data for {}",
+            csv.display(),
+            args.date
+        ));
+    }
+    store.append(vec![
+        Area::manual(args.date, args.area_occupied, series::AREA_TYPE_OCCUPIED),
+        Area::manual(args.date, args.area_ua, series::AREA_TYPE_OTHER),
+    ])?;
+    success(&format!(
+        "Appended manual point for {} to {} ({:.0} км² occupied)",
+        args.date,
+        csv.display(),
+        args.area_occupied - args.area_ua
+    ));
+    Ok(())
+}
+
+async fn download_to_store(
+    store: &mut (impl DataStore + Send),
+    source: &(impl MapSource + Sync),
+    fetcher: &Fetcher,
+    range: series::DateRange,
+    interrupt: &Interrupt,
+) -> Result<(), String> {
+    info(&format!("Downloading history from {}", source.name()));
+    // Срезы пишутся в черновик по мере загрузки; история заменяется, только когда он полон:
+    // загрузку не прервали и ни один срез не пропущен из-за ошибки.
+    let mut spool = store.spool()?;
+    let summary = stream_snapshots(
+        |sink| source.fetch_areas(fetcher, range, interrupt, sink),
+        |areas| spool.push(areas),
+    )
+    .await?;
+    tracing::debug!(rows = spool.rows(), "Spooled downloaded snapshots");
+    if !summary.interrupted && summary.fetched == summary.total {
+        // Выгрузка за диапазон заменяет только его дни: история вне окна остаётся.
+        return if range.is_unbounded() {
+            store.replace_from(spool)
+        } else {
+            store.merge_from(spool, range)
+        };
+    }
+    // Неполная выгрузка не заменяет историю: иначе пропущенные дни пропали бы из неё.
+    // Дописываются только срезы новее сохранённых.
+    let saved = store.append_from(spool)?;
+    let outcome = if summary.interrupted {
+        "interrupted"
+    } else {
+        "incomplete"
+    };
+    Err(format!(
+        "Download {outcome} after {} of {} snapshots; appended {saved} new rows to the store",
+        summary.fetched, summary.total
+    ))
+}
+
+/// Пишет в `output_csv` копию хранилища для сайта с точностью `[precision]`: значения
+/// источника остаются только в хранилище.
+fn publish_history(
+    store: &CsvStore,
+    output_csv: &Path,
+    precision: constants::Precision,
+) -> Result<(), String> {
+    store.export_to(output_csv, data::CsvFormat::published(precision))?;
+    tracing::debug!(output_csv = %output_csv.display(), "Published history copy");
+    Ok(())
+}
+
+/// Скачивает историю из API. Если задано зеркало, а локальной истории ещё нет, она один раз
+/// заполняется готовым CSV с него; дальше запуски обновляют её из API.
+async fn download_history(
+    output_csv: &Path,
+    mirror_url: Option<&str>,
+    raw_archive: Option<&Path>,
+    fetcher: &Fetcher,
+    range: series::DateRange,
+    interrupt: &Interrupt,
+) -> Result<(), String> {
+    let mut store = CsvStore::new(output_csv);
+    if let Some(url) = mirror_url
+        && store.latest_timestamp()?.is_none()
+    {
+        return bootstrap_from_mirror(&mut store, url, fetcher, range).await;
+    }
+    download_to_store(
+        &mut store,
+        &DeepStateSource {
+            raw_archive: raw_archive.map(Path::to_path_buf),
+        },
+        fetcher,
+        range,
+        interrupt,
+    )
+    .await
+}
+
+/// Загружает ранее опубликованный CSV (или `.csv.gz`) с зеркала, сверяет его
+/// с опубликованным рядом `<url>.sha256` и заменяет им историю в хранилище.
+async fn bootstrap_from_mirror(
+    store: &mut CsvStore,
+    url: &str,
+    fetcher: &Fetcher,
+    range: series::DateRange,
+) -> Result<(), String> {
+    info(&format!("Bootstrapping history from mirror {url}"));
+    let deadline = fetcher.deadline();
+    let checksum_url = format!("{url}.sha256");
+    let checksum = fetcher
+        .fetch_bytes(&checksum_url, deadline)
+        .await
+        .map_err(|err| format!("Failed to fetch mirror checksum {checksum_url}: {err}"))?;
+    let expected = integrity::parse_checksum(&String::from_utf8_lossy(&checksum))
+        .map_err(|err| format!("Invalid mirror checksum {checksum_url}: {err}"))?;
+    let body = fetcher
+        .fetch_bytes(url, deadline)
+        .await
+        .map_err(|err| format!("Failed to fetch mirror {url}: {err}"))?;
+    integrity::verify_checksum(&body, &expected)
+        .map_err(|err| format!("Mirror {url} failed verification: {err}"))?;
+
+    let csv = if url.ends_with(&format!(".{CSV_ARCHIVE_EXTENSION}")) {
+        let mut decoded = Vec::new();
+        GzDecoder::new(body.as_ref())
+            .read_to_end(&mut decoded)
+            .map_err(|err| format!("Failed to decompress mirror {url}: {err}"))?;
+        decoded
+    } else {
+        body.to_vec()
+    };
+    let mut areas = data::parse_areas(&csv, url)?;
+    success(&format!("Verified mirror checksum {expected}"));
+    // С `--from`/`--to` зеркало заполняет только дни диапазона, как и загрузка из API.
+    areas.retain(|area| range.contains(area.date()));
+    // Через хранилище: запись атомарна, под блокировкой истории, ручные точки сохраняются.
+    store.replace(areas)
+}
+
+/// Публикует контрольные суммы и подписи рядом с готовыми CSV (или их архивами).
+fn publish_integrity(
+    csv_paths: &[&Path],
+    archive: bool,
+    config: Option<&integrity::IntegrityConfig>,
+) -> Result<(), String> {
+    let Some(config) = config else {
+        return Ok(());
+    };
+    for csv_path in csv_paths {
+        let artifact = if archive {
+            archive_path_for(csv_path)?
+        } else {
+            csv_path.to_path_buf()
+        };
+        for path in integrity::publish_artifact(&artifact, config)? {
+            success(&format!("Saved {}", path.display()));
         }
-        ModelKind::Llt => model::train_from_csv(csv_path, model::ModelConfig::default())
-            .map(|fitted| fitted.forecast(horizon_days.get()))
-            .map_err(|err| err.to_string()),
     }
+    Ok(())
+}
+
+/// Ключ кэша сборки, если кэш включён; без читаемых входов отчёт просто собирается заново.
+fn open_build_cache(
+    enabled: bool,
+    output_html: &Path,
+    settings: &str,
+    inputs: &[&Path],
+) -> Option<cache::BuildCache> {
+    if !enabled {
+        return None;
+    }
+    cache::BuildCache::new(output_html, settings, inputs)
+        .inspect_err(|err| tracing::warn!("Build cache is unavailable: {err}"))
+        .ok()
+}
+
+fn store_build_cache(
+    build_cache: Option<&cache::BuildCache>,
+    outputs: impl IntoIterator<Item = Option<PathBuf>>,
+) {
+    let Some(build_cache) = build_cache else {
+        return;
+    };
+    let outputs = outputs.into_iter().flatten().collect::<Vec<_>>();
+    if let Err(err) = build_cache.store(&outputs) {
+        error(&format!("Failed to save build cache: {err}"));
+    }
+}
+
+fn file_name_for(path: &Path) -> Result<String, String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Path {} has no file name", path.display()))
+}
+
+fn archive_path_for(csv_path: &Path) -> Result<PathBuf, String> {
+    let file_name = file_name_for(csv_path)?;
+    let archive_name = format!("{file_name}.{CSV_ARCHIVE_EXTENSION}");
+    let mut archive_path = csv_path.to_path_buf();
+    archive_path.set_file_name(archive_name);
+    Ok(archive_path)
+}
+
+fn archive_csv_file(csv_path: &Path) -> Result<PathBuf, String> {
+    let archive_path = archive_path_for(csv_path)?;
+    if let Some(parent) = archive_path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    let mut input = File::open(csv_path)
+        .map_err(|err| format!("Failed to open CSV {}: {err}", csv_path.display()))?;
+    let output = File::create(&archive_path)
+        .map_err(|err| format!("Failed to create archive {}: {err}", archive_path.display()))?;
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    std::io::copy(&mut input, &mut encoder)
+        .map_err(|err| format!("Failed to write archive {}: {err}", archive_path.display()))?;
+    encoder.finish().map_err(|err| {
+        format!(
+            "Failed to finalize archive {}: {err}",
+            archive_path.display()
+        )
+    })?;
+    Ok(archive_path)
+}
+
+fn remove_csv_file(csv_path: &Path) -> Result<(), String> {
+    fs::remove_file(csv_path)
+        .map_err(|err| format!("Failed to remove CSV {}: {err}", csv_path.display()))
+}
+
+fn download_name(csv_path: &Path, archive: bool) -> Result<String, String> {
+    if archive {
+        let archive_path = archive_path_for(csv_path)?;
+        file_name_for(&archive_path)
+    } else {
+        file_name_for(csv_path)
+    }
+}
+
+fn publish_site(
+    output_html: &Path,
+    config: Option<&publish::GhPagesConfig>,
+    summary: &report::ChartSummary,
+) -> Result<(), String> {
+    let Some(config) = config else {
+        return Ok(());
+    };
+    let site_dir = output_html
+        .parent()
+        .ok_or_else(|| format!("Path {} has no parent directory", output_html.display()))?;
+    info(&format!(
+        "Publishing {} to branch {}",
+        site_dir.display(),
+        config.branch
+    ));
+    match publish::publish_to_gh_pages(site_dir, config, summary)? {
+        publish::PublishOutcome::Committed { message, pushed } => {
+            success(&format!(
+                "Committed site to {}{}: {message}",
+                config.branch,
+                if pushed { " and pushed" } else { "" }
+            ));
+        }
+        publish::PublishOutcome::Unchanged => {
+            info("Site is unchanged; nothing to publish");
+        }
+    }
+    Ok(())
+}
+
+/// Отправляет дайджест, если подошёл срок и за этот период он ещё не уходил: период
+/// последней отправки хранится рядом с `output_html`, поэтому частые запуски в день рассылки
+/// не шлют письмо повторно.
+async fn send_digest(
+    buckets: &AreaBuckets,
+    summary: &report::ChartSummary,
+    config: Option<&notify::EmailConfig>,
+    output_html: &Path,
+) -> Result<(), String> {
+    let Some(config) = config else {
+        return Ok(());
+    };
+    let today = Utc::now().date_naive();
+    if !config.schedule.is_due(today) {
+        info("Digest email is not due today; skipping");
+        return Ok(());
+    }
+    let period = config.schedule.period(today);
+    let state_path = notify::digest_state_path(output_html);
+    if notify::digest_sent(&state_path, &period) {
+        info(&format!(
+            "Digest email for {period} was already sent; skipping"
+        ));
+        return Ok(());
+    }
+    let (_, occupied) = series::build_occupied_series(buckets)
+        .map_err(|err| format!("Failed to build series for digest: {err}"))?;
+    let digest = notify::Digest::build(summary, &occupied)?;
+    notify::send_email_digest(config, &digest).await?;
+    notify::record_digest_sent(&state_path, &period)?;
+    success(&format!("Sent digest email to {}", config.to.join(", ")));
+    Ok(())
+}
+
+/// Проверяет правила `[[alerts]]` на свежих данных; сработавшие пишутся в лог.
+fn evaluate_alerts(
+    rules: &[alerts::AlertRule],
+    buckets: &AreaBuckets,
+    forecast: Option<&report::ForecastOverlay>,
+) -> Vec<alerts::AlertMatch> {
+    if rules.is_empty() {
+        return Vec::new();
+    }
+    let (dates, occupied) = match series::build_occupied_series(buckets) {
+        Ok(series) => series,
+        Err(err) => {
+            error(&format!("Failed to build series for alerts: {err}"));
+            return Vec::new();
+        }
+    };
+    let matched = alerts::evaluate(
+        rules,
+        &alerts::AlertInput {
+            dates: &dates,
+            occupied: &occupied,
+            forecast,
+            latest_snapshot: buckets.snapshots().last().map(|snapshot| snapshot.time),
+            now: Utc::now(),
+        },
+    );
+    for alert in &matched {
+        tracing::warn!(status = "alert", rule = %alert.rule, "{}", alert.message);
+    }
+    matched
+}
+
+async fn send_notifications(
+    client: &reqwest::Client,
+    summary: &report::ChartSummary,
+    alerts: &[alerts::AlertMatch],
+    config: Option<&notify::NotificationsConfig>,
+) {
+    let Some(config) = config else {
+        return;
+    };
+    let notification = notify::Notification {
+        summary: summary.clone(),
+        link: config.link.clone(),
+        alerts: alerts.to_vec(),
+    };
+    for target in &config.targets {
+        match target.send(client, &notification).await {
+            Ok(()) => success(&format!("Sent {} notification", target.name())),
+            Err(err) => error(&format!(
+                "Failed to send {} notification: {err}",
+                target.name()
+            )),
+        }
+    }
+}
+
+fn export_full_resolution(
+    buckets: &AreaBuckets,
+    output_csv: Option<&Path>,
+    precision: constants::Precision,
+) -> Result<(), String> {
+    let Some(output_csv) = output_csv else {
+        return Ok(());
+    };
+    series::write_full_resolution_csv(buckets, output_csv, precision)
+        .map_err(|err| format!("Failed to write full-resolution CSV: {err}"))?;
+    success(&format!(
+        "Saved full-resolution CSV to {}",
+        output_csv.display()
+    ));
+    Ok(())
+}
+
+fn export_percent_history(
+    buckets: &AreaBuckets,
+    output_csv: Option<&Path>,
+    precision: constants::Precision,
+) -> Result<(), String> {
+    let Some(output_csv) = output_csv else {
+        return Ok(());
+    };
+    series::write_percent_history_csv(buckets, output_csv, precision)
+        .map_err(|err| format!("Failed to write percent history CSV: {err}"))?;
+    success(&format!(
+        "Saved percent history CSV to {}",
+        output_csv.display()
+    ));
+    Ok(())
 }
 
-fn train_forecast_from_buckets(
+/// Сглаживает историю LLT-моделью, если нужен CSV или линия на графике.
+fn smooth_history(
     buckets: &AreaBuckets,
-    horizon_days: NonZeroUsize,
     model_config: &ResolvedModelConfig,
-) -> Result<model::Forecast, String> {
-    match model_config.kind {
-        ModelKind::TrendFilter => {
-            model::train_trend_filter_from_buckets(buckets, model_config.trend_filter)
-                .map(|fitted| fitted.forecast(horizon_days.get()))
-                .map_err(|err| err.to_string())
-        }
-        ModelKind::Llt => model::train_from_buckets(buckets, model::ModelConfig::default())
-            .map(|fitted| fitted.forecast(horizon_days.get()))
-            .map_err(|err| err.to_string()),
+    output_csv: Option<&Path>,
+    trend_csv: Option<&Path>,
+    plot: bool,
+    precision: constants::Precision,
+) -> Result<Option<model::SmoothedStates>, String> {
+    if output_csv.is_none() && trend_csv.is_none() && !plot {
+        return Ok(None);
     }
+    let regressors = load_regressors(model_config)?;
+    let smoothed = model::smooth_from_buckets(buckets, model_config.llt(), regressors.as_ref())
+        .map_err(|err| format!("Failed to smooth history: {err}"))?;
+    if let Some(output_csv) = output_csv {
+        model::write_smoothed_csv(&smoothed, output_csv, precision)
+            .map_err(|err| format!("Failed to write smoothed CSV: {err}"))?;
+        success(&format!(
+            "Saved smoothed states to {}",
+            output_csv.display()
+        ));
+    }
+    if let Some(trend_csv) = trend_csv {
+        model::write_trend_csv(&smoothed, trend_csv, precision)
+            .map_err(|err| format!("Failed to write trend CSV: {err}"))?;
+        success(&format!("Saved trend to {}", trend_csv.display()));
+    }
+    Ok(Some(smoothed))
 }
 
-fn load_forecast_overlay(forecast_csv: &Path) -> Result<report::ForecastOverlay, String> {
-    model::read_forecast_csv(forecast_csv)
-        .map(|forecast| build_forecast_overlay(&forecast))
-        .map_err(|err| err.to_string())
+/// Перебирает сетку параметров trend filter, сохраняет таблицу лидеров и (по запросу) победителя.
+fn tune_trend_filter(
+    config: &TuneConfig,
+    model_config: &ResolvedModelConfig,
+    range: series::DateRange,
+    config_path: &Path,
+) -> Result<(), String> {
+    let base = model_config.trend_filter;
+    let mut buckets = CsvStore::new(&config.csv)
+        .load_range(range)
+        .map_err(|err| format!("Failed to read history CSV: {err}"))?;
+    apply_exclusions(&mut buckets, model_config)?;
+    let (dates, values) =
+        model::load_target_series_from_buckets(&buckets, base.interpolation, base.training_window)
+            .map_err(|err| format!("Failed to build training series: {err}"))?;
+    let candidates = config.grid.configs(base).len();
+    info(&format!(
+        "Backtesting {candidates} configurations ({} folds × {} days)",
+        config.backtest.folds, config.backtest.horizon_days
+    ));
+    let results = tune::grid_search(&dates, &values, base, &config.grid, config.backtest)?;
+    tune::write_leaderboard_csv(&results, &config.output_csv)?;
+
+    let best = results[0];
+    success(&format!(
+        "Saved leaderboard to {}; best lambda = {}, epsilon = {}, huber_delta = {}, damping = {} (MAE {:.1} км²)",
+        config.output_csv.display(),
+        best.config.lambda,
+        best.config.epsilon,
+        best.config.huber_delta,
+        best.config.damping,
+        best.loss
+    ));
+    if config.persist {
+        tune::persist_trend_filter(config_path, &best.config)?;
+        success(&format!(
+            "Saved best trend_filter to {}",
+            config_path.display()
+        ));
+    }
+    Ok(())
 }
 
-fn init_logging() {
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("rua=info"));
-    let ansi = std::io::stdout().is_terminal();
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .with_target(false)
-        .with_ansi(ansi)
-        .compact()
-        .init();
+fn export_gap_report(buckets: &AreaBuckets, output_csv: Option<&Path>) -> Result<(), String> {
+    let Some(output_csv) = output_csv else {
+        return Ok(());
+    };
+    let series = series::build_occupied_and_unspecified_series(buckets)
+        .map_err(|err| format!("Failed to build series for gap report: {err}"))?;
+    let gaps = series::find_gaps(&series);
+    series::write_gap_report_csv(&gaps, output_csv)
+        .map_err(|err| format!("Failed to write gap report: {err}"))?;
+    let missing_days: i64 = gaps.iter().map(|gap| gap.days()).sum();
+    let longest = gaps.iter().map(|gap| gap.days()).max().unwrap_or_default();
+    success(&format!(
+        "Saved gap report to {} ({} gaps, {missing_days} days, longest {longest})",
+        output_csv.display(),
+        gaps.len()
+    ));
+    Ok(())
 }
 
-fn headline(message: &str) {
-    tracing::info!(status = "start", "{message}");
+fn export_workbook(
+    buckets: &AreaBuckets,
+    forecast: &report::ForecastOverlay,
+    interpolation: series::InterpolationStrategy,
+    smoothing: report::SmoothingSpec,
+    output_xlsx: Option<&Path>,
+) -> Result<(), String> {
+    let Some(output_xlsx) = output_xlsx else {
+        return Ok(());
+    };
+    report::write_workbook(
+        buckets,
+        Some(forecast),
+        interpolation,
+        smoothing,
+        output_xlsx,
+    )
+    .map_err(|err| format!("Failed to write Excel workbook: {err}"))?;
+    success(&format!(
+        "Saved Excel workbook to {}",
+        output_xlsx.display()
+    ));
+    Ok(())
 }
 
-fn info(message: &str) {
-    tracing::info!(status = "info", "{message}");
+/// Раздел диагностики: статистики остатков LLT-модели и её настройки из конфига.
+fn build_model_diagnostics(
+    smoothed: &model::SmoothedStates,
+    model_config: &ResolvedModelConfig,
+) -> Option<report::ModelDiagnostics> {
+    // Вложенные настройки разворачиваются в строки `раздел.поле`.
+    fn flatten(prefix: &str, value: serde_json::Value, rows: &mut Vec<(String, String)>) {
+        match value {
+            serde_json::Value::Object(fields) => {
+                for (name, value) in fields {
+                    let name = if prefix.is_empty() {
+                        name
+                    } else {
+                        format!("{prefix}.{name}")
+                    };
+                    flatten(&name, value, rows);
+                }
+            }
+            serde_json::Value::String(text) => rows.push((prefix.to_string(), text)),
+            serde_json::Value::Null => rows.push((prefix.to_string(), "—".to_string())),
+            other => rows.push((prefix.to_string(), other.to_string())),
+        }
+    }
+    let mut hyperparameters = Vec::new();
+    if let Ok(value) = serde_json::to_value(model_config.llt()) {
+        flatten("", value, &mut hyperparameters);
+    }
+    Some(report::ModelDiagnostics {
+        model: ModelKind::Llt.to_string(),
+        stats: smoothed.diagnostics.clone()?,
+        hyperparameters,
+    })
 }
 
-fn success(message: &str) {
-    tracing::info!(status = "ok", "{message}");
+/// Модель и её гиперпараметры для манифеста сборки.
+fn model_info(
+    model_config: &ResolvedModelConfig,
+    calibration: Option<model::Calibration>,
+    training: Option<model::TrainingSpan>,
+) -> manifest::ModelInfo {
+    let hyperparameters = match model_config.kind {
+        ModelKind::TrendFilter => serde_json::to_value(model_config.trend_filter),
+        ModelKind::Llt => serde_json::to_value(model_config.llt()).map(|mut value| {
+            if let (Some(regressors), Some(fields)) =
+                (&model_config.regressors, value.as_object_mut())
+            {
+                fields.insert(
+                    "regressors".to_string(),
+                    serde_json::json!(regressors.columns),
+                );
+            }
+            value
+        }),
+    };
+    manifest::ModelInfo {
+        kind: model_config.kind.to_string(),
+        hyperparameters: hyperparameters.unwrap_or_default(),
+        calibration,
+        training,
+    }
 }
 
-fn error(message: &str) {
-    tracing::error!(status = "err", "{message}");
+/// Пишет `build.json` рядом с `output_html` и встраивает манифест в каждую страницу сборки.
+fn publish_build_manifest(
+    manifest: &manifest::BuildManifest,
+    output_html: &Path,
+    pages: &[&Path],
+) -> Result<PathBuf, String> {
+    let output_dir = output_html
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    for page in pages {
+        manifest.embed_in_page(page)?;
+    }
+    let path = manifest.write(output_dir)?;
+    success(&format!("Saved build manifest to {}", path.display()));
+    Ok(path)
 }
 
-async fn download_to_csv(output_csv: &Path) -> Result<(), String> {
-    let delay = Duration::from_secs(FETCH_DELAY_SECS);
-    let client = fetch::build_client();
-    let areas = fetch_areas(&client, FETCH_MAX_RETRIES, delay).await?;
-    to_csv(areas, output_csv)?;
+/// Дописывает сборку в журнал рядом с `output_updates_html` и пересобирает страницу журнала.
+fn export_updates(
+    buckets: &series::AreaBuckets,
+    output_html: &Path,
+    output_updates_html: Option<&Path>,
+    page: &report::PageConfig,
+) -> Result<(), String> {
+    let Some(output_updates_html) = output_updates_html else {
+        return Ok(());
+    };
+    let builds = updates::record_build(
+        &updates::log_path_for(output_updates_html),
+        buckets,
+        report::generated_at(),
+    )
+    .map_err(|err| format!("Failed to update build log: {err}"))?;
+    let home_href = output_html
+        .file_name()
+        .map_or_else(|| "index.html".into(), |name| name.to_string_lossy());
+    report::write_updates_page(&builds, &home_href, page, output_updates_html)
+        .map_err(|err| format!("Failed to write updates page: {err}"))?;
+    success(&format!(
+        "Saved updates page to {}",
+        output_updates_html.display()
+    ));
     Ok(())
 }
 
-fn file_name_for(path: &Path) -> Result<String, String> {
-    path.file_name()
-        .map(|name| name.to_string_lossy().to_string())
-        .ok_or_else(|| format!("Path {} has no file name", path.display()))
+/// Рендерит главную страницу, журнал обновлений и методологию параллельно: страницы друг от
+/// друга не зависят. Ошибки возвращаются в том же порядке, что и при сборке по очереди.
+fn render_pages(
+    main: impl FnOnce() -> Result<report::ChartSummary, String> + Send,
+    updates: impl FnOnce() -> Result<(), String> + Send,
+    methodology: impl FnOnce() -> Result<(), String> + Send,
+) -> Result<report::ChartSummary, String> {
+    let join = |handle: std::thread::ScopedJoinHandle<'_, Result<(), String>>| {
+        handle
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    };
+    std::thread::scope(|scope| {
+        let updates = scope.spawn(updates);
+        let methodology = scope.spawn(methodology);
+        let summary = main();
+        let updates = join(updates);
+        let methodology = join(methodology);
+        let summary = summary?;
+        updates?;
+        methodology?;
+        Ok(summary)
+    })
 }
 
-fn archive_path_for(csv_path: &Path) -> Result<PathBuf, String> {
-    let file_name = file_name_for(csv_path)?;
-    let archive_name = format!("{file_name}.{CSV_ARCHIVE_EXTENSION}");
-    let mut archive_path = csv_path.to_path_buf();
-    archive_path.set_file_name(archive_name);
-    Ok(archive_path)
+/// Приложение «Методология» (`output_methodology_html`): страница и CSV рядом с ней.
+fn export_methodology(
+    model_config: &ResolvedModelConfig,
+    chart: &report::ChartRenderConfig,
+    output_html: &Path,
+    output_methodology_html: Option<&Path>,
+) -> Result<(), String> {
+    let Some(output_methodology_html) = output_methodology_html else {
+        return Ok(());
+    };
+    let sections = [
+        series::methodology(model_config.interpolation, chart.interpolation),
+        match model_config.kind {
+            ModelKind::Llt => model::methodology_llt(&model_config.llt()),
+            ModelKind::TrendFilter => model::methodology_trend_filter(&model_config.trend_filter),
+        },
+        model::methodology_intervals(model_config.calibration),
+        chart.methodology(),
+    ];
+    let output_csv = methodology::csv_path_for(output_methodology_html);
+    methodology::write_csv(&sections, &output_csv)
+        .map_err(|err| format!("Failed to write methodology CSV: {err}"))?;
+    let home_href = output_html
+        .file_name()
+        .map_or_else(|| "index.html".into(), |name| name.to_string_lossy());
+    let csv_href = output_csv
+        .file_name()
+        .map_or_else(|| "methodology.csv".into(), |name| name.to_string_lossy());
+    report::write_methodology_page(
+        &sections,
+        &home_href,
+        &csv_href,
+        &chart.page,
+        output_methodology_html,
+    )
+    .map_err(|err| format!("Failed to write methodology page: {err}"))?;
+    success(&format!(
+        "Saved methodology page to {}",
+        output_methodology_html.display()
+    ));
+    Ok(())
 }
 
-fn archive_csv_file(csv_path: &Path) -> Result<PathBuf, String> {
-    let archive_path = archive_path_for(csv_path)?;
-    if let Some(parent) = archive_path.parent()
-        && !parent.as_os_str().is_empty()
-    {
-        fs::create_dir_all(parent)
-            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+/// Смещение линии фронта между последней картой и картой `days` дней назад; раздел
+/// необязательный, поэтому ошибка загрузки только пропускает его.
+/// Оформление со ссылками на локальные копии шрифтов (`[page].fonts = "self-hosted"`) и флагов
+/// (`[page].self_host_assets`); если скачать их не удалось, флаги остаются на CDN, а шрифты —
+/// системными.
+async fn with_self_hosted_assets(
+    chart: report::ChartRenderConfig,
+    output_html: &Path,
+    client: &reqwest::Client,
+    fetch_config: &fetch::FetchConfig,
+) -> report::ChartRenderConfig {
+    if !chart.page.self_host_assets && chart.page.fonts != report::FontStrategy::SelfHosted {
+        return chart;
+    }
+    let output_dir = output_html.parent().unwrap_or_else(|| Path::new(""));
+    match selfhost::self_host_assets(client, fetch_config, output_dir, &chart.page).await {
+        Ok(page) => report::ChartRenderConfig { page, ..chart },
+        Err(err) => {
+            tracing::warn!(error = %err, "Using system fonts and CDN links for flags");
+            chart
+        }
     }
-    let mut input = File::open(csv_path)
-        .map_err(|err| format!("Failed to open CSV {}: {err}", csv_path.display()))?;
-    let output = File::create(&archive_path)
-        .map_err(|err| format!("Failed to create archive {}: {err}", archive_path.display()))?;
-    let mut encoder = GzEncoder::new(output, Compression::default());
-    std::io::copy(&mut input, &mut encoder)
-        .map_err(|err| format!("Failed to write archive {}: {err}", archive_path.display()))?;
-    encoder.finish().map_err(|err| {
-        format!(
-            "Failed to finalize archive {}: {err}",
-            archive_path.display()
-        )
-    })?;
-    Ok(archive_path)
 }
 
-fn remove_csv_file(csv_path: &Path) -> Result<(), String> {
-    fs::remove_file(csv_path)
-        .map_err(|err| format!("Failed to remove CSV {}: {err}", csv_path.display()))
+#[cfg(feature = "geo")]
+async fn load_front_shifts(
+    source: &(impl MapSource + Sync),
+    front: Option<&front::FrontConfig>,
+    fetcher: &Fetcher,
+) -> Option<front::FrontShifts> {
+    let front = front?;
+    let shifts = async {
+        let (before, after) = source
+            .fetch_map_pair(fetcher, chrono::TimeDelta::days(i64::from(front.days)))
+            .await?;
+        front::front_shifts(
+            (before.time, &before.body),
+            (after.time, &after.body),
+            front,
+        )
+    };
+    match shifts.await {
+        Ok(shifts) => {
+            tracing::info!(
+                before = %shifts.before,
+                after = %shifts.after,
+                sectors = shifts.sectors.len(),
+                "Measured front line shifts"
+            );
+            Some(shifts)
+        }
+        Err(err) => {
+            tracing::warn!(error = %err, "Skipping the front line section");
+            None
+        }
+    }
 }
 
-fn download_name(csv_path: &Path, archive: bool) -> Result<String, String> {
-    if archive {
-        let archive_path = archive_path_for(csv_path)?;
-        file_name_for(&archive_path)
-    } else {
-        file_name_for(csv_path)
+/// Заглушка для сборки без фичи `geo`: раздел о линии фронта пропускается.
+#[cfg(not(feature = "geo"))]
+#[allow(clippy::unused_async)]
+async fn load_front_shifts(
+    _source: &(impl MapSource + Sync),
+    front: Option<&front::FrontConfig>,
+    _fetcher: &Fetcher,
+) -> Option<front::FrontShifts> {
+    if front.is_some() {
+        tracing::warn!("rua was built without the `geo` feature; skipping the front line section");
     }
+    None
+}
+
+/// Загружает историю второго источника за тот же диапазон дат.
+fn load_comparison(
+    compare: Option<&CompareConfig>,
+    range: series::DateRange,
+) -> Result<Option<report::ComparisonSource>, String> {
+    let Some(compare) = compare else {
+        return Ok(None);
+    };
+    let buckets = CsvStore::new(&compare.csv)
+        .load_range(range)
+        .map_err(|err| format!("Failed to read comparison CSV: {err}"))?;
+    Ok(Some(report::ComparisonSource {
+        primary_label: compare.primary_label.clone(),
+        label: compare.label.clone(),
+        buckets,
+    }))
+}
+
+fn export_calendar(
+    forecast: &report::ForecastOverlay,
+    events: &[report::CalendarEvent],
+    output_ics: Option<&Path>,
+) -> Result<(), String> {
+    let Some(output_ics) = output_ics else {
+        return Ok(());
+    };
+    report::write_calendar(forecast, events, output_ics)
+        .map_err(|err| format!("Failed to write calendar: {err}"))?;
+    success(&format!("Saved calendar to {}", output_ics.display()));
+    Ok(())
 }
 
 fn build_download_links(
@@ -668,44 +4088,123 @@ fn build_download_links(
 
 #[tokio::main]
 #[allow(clippy::too_many_lines)]
-async fn main() {
-    let args = Args::parse();
+async fn main() -> ExitCode {
+    let mut args = Args::parse();
     init_logging();
-    headline(APP_ABOUT);
 
-    let config_path = match resolve_runtime_path(&args.config) {
+    let config_path = match args.command.take() {
+        Some(Command::Append(append)) => {
+            headline(APP_ABOUT);
+            return exit_code(append_manual_point(&append));
+        }
+        // Без заголовка в логе: стандартный вывод целиком занимает текст разницы.
+        Some(Command::ReportDiff(report_diff)) => {
+            return exit_code(write_report_diff(&report_diff));
+        }
+        Some(Command::Doctor) => {
+            headline(APP_ABOUT);
+            let failed = run_doctor(args.config.as_deref()).await;
+            return if failed == 0 {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            };
+        }
+        Some(Command::RebuildHistory(rebuild)) => {
+            headline(APP_ABOUT);
+            return exit_code(rebuild_history(&rebuild));
+        }
+        Some(Command::Gc(gc)) => {
+            headline(APP_ABOUT);
+            return exit_code(run_gc(args.config.as_deref(), &gc));
+        }
+        Some(Command::Tune(tune)) => {
+            headline(APP_ABOUT);
+            return exit_code(run_tune(&args, &tune).await);
+        }
+        None => args.config.unwrap_or_default(),
+    };
+    headline(APP_ABOUT);
+    let config_path = match resolve_runtime_path(&config_path) {
         Ok(path) => path,
         Err(err) => {
             eprintln!("{err}");
-            return;
+            return ExitCode::FAILURE;
         }
     };
-    let app_config = match load_app_config(&config_path) {
-        Ok(config) => config,
+    let app_config = match load_app_config(&config_path)
+        .and_then(|config| override_range(config, args.from, args.to))
+        .and_then(|config| pin_as_of(config, args.as_of))
+        .map(|config| override_downsample(config, args.no_downsample))
+    {
+        Ok(config) => match args.training_window {
+            Some(window) => AppConfig {
+                model: config.model.with_training_window(window),
+                ..config
+            },
+            None => config,
+        },
         Err(err) => {
             error(&err);
-            return;
+            return ExitCode::FAILURE;
         }
     };
 
     let AppConfig {
         mode,
         archive_csv,
+        range,
         mode_config,
         model: model_config,
         chart: chart_config,
+        gh_pages,
+        email,
+        notifications,
+        alerts: alert_rules,
+        fetch: fetch_config,
+        integrity: integrity_config,
+        events,
+        compare,
+        front: front_config,
+        retention,
+        tune: _,
+        precision,
     } = app_config;
+    // Один клиент на весь запуск: пул соединений переиспользуется между загрузкой и уведомлениями.
+    let client = fetch::build_client(&fetch_config);
+    // Все загрузки запуска идут через один предел одновременных запросов и один индикатор.
+    let fetcher = Fetcher::new(client.clone(), fetch_config.clone());
+    let interrupt = Interrupt::listen();
 
     tracing::info!(
         mode = %mode,
         archive_csv,
         config_path = %config_path.display(),
         model = %model_config.kind,
+        training_window = %model_config.training_window,
+        range = %range,
         gray_zone_start = %chart_config.gray_zone_start,
         avg_change_start = %chart_config.avg_change_start,
         "Loaded configuration"
     );
 
+    let _instance = match mode_config.output_dir() {
+        Some(dir) => {
+            match instance::InstanceLock::acquire(dir, args.wait_for_lock.map(Duration::from_secs))
+                .await
+            {
+                Ok(lock) => Some(lock),
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Ошибки после сборки (публикация, рассылка) не прерывают запуск, но дают ненулевой код.
+    let mut status = ExitCode::SUCCESS;
     match mode_config {
         ModeConfig::Run(config) => {
             tracing::info!(
@@ -713,6 +4212,7 @@ async fn main() {
                 model = %model_config.kind,
                 archive_csv,
                 horizon_days = config.horizon_days.get(),
+                store_csv = %config.store_csv.display(),
                 output_history_csv = %config.output_history_csv.display(),
                 output_forecast_csv = %config.output_forecast_csv.display(),
                 output_html = %config.output_html.display(),
@@ -727,78 +4227,333 @@ async fn main() {
                 Ok(links) => links,
                 Err(err) => {
                     error(&err);
-                    return;
+                    return ExitCode::FAILURE;
                 }
             };
             info(&format!(
                 "Saving history CSV to {}",
-                config.output_history_csv.display()
+                config.store_csv.display()
             ));
-            if let Err(err) = download_to_csv(&config.output_history_csv).await {
+            let store = CsvStore::new(&config.store_csv);
+            // Карты для раздела о линии фронта от истории не зависят и скачиваются одновременно
+            // с ней; общий `fetcher` держит обе загрузки в одном пределе запросов к API.
+            let front_source = DeepStateSource::default();
+            let (downloaded, front) = tokio::join!(
+                download_history(
+                    &config.store_csv,
+                    config.mirror_url.as_deref(),
+                    config.raw_archive_dir.as_deref(),
+                    &fetcher,
+                    range,
+                    &interrupt,
+                ),
+                load_front_shifts(&front_source, front_config.as_ref(), &fetcher),
+            );
+            if let Err(err) = downloaded
+                .and_then(|()| publish_history(&store, &config.output_history_csv, precision))
+            {
                 error(&err);
-                return;
+                return ExitCode::FAILURE;
             }
             if archive_csv {
                 match archive_csv_file(&config.output_history_csv) {
                     Ok(path) => success(&format!("Saved archive to {}", path.display())),
                     Err(err) => {
                         error(&err);
-                        return;
+                        return ExitCode::FAILURE;
                     }
                 }
             }
+            let build_cache = open_build_cache(
+                config.build_cache,
+                &config.output_html,
+                &format!(
+                    "{range:?}\n{archive_csv}\n{model_config:?}\n{chart_config:?}\n{config:?}\n{events:?}\n{compare:?}\n{front_config:?}\n{precision:?}\n{integrity_config:?}"
+                ),
+                &[
+                    Some(config.store_csv.as_path()),
+                    compare.as_ref().map(|compare| compare.csv.as_path()),
+                ]
+                .into_iter()
+                .chain(model_inputs(&model_config))
+                .flatten()
+                .collect::<Vec<_>>(),
+            );
+            if build_cache
+                .as_ref()
+                .is_some_and(cache::BuildCache::is_fresh)
+            {
+                if archive_csv && let Err(err) = remove_csv_file(&config.output_history_csv) {
+                    error(&err);
+                }
+                success("History is unchanged; skipping forecast and HTML rebuild");
+                return ExitCode::SUCCESS;
+            }
+            let input_digests = match manifest::hash_inputs(
+                &std::iter::once(Some(config.store_csv.as_path()))
+                    .chain(model_inputs(&model_config))
+                    .flatten()
+                    .collect::<Vec<_>>(),
+            ) {
+                Ok(digests) => digests,
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            };
 
-            let buckets = match series::load_area_buckets(&config.output_history_csv) {
+            let mut buckets = match store.load_range(range) {
                 Ok(buckets) => buckets,
                 Err(err) => {
                     error(&format!("Failed to read history CSV: {err}"));
-                    return;
+                    return ExitCode::FAILURE;
                 }
             };
+            if let Err(err) = apply_exclusions(&mut buckets, &model_config) {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
+            if let Err(err) = export_full_resolution(
+                &buckets,
+                config.output_full_resolution_csv.as_deref(),
+                precision,
+            ) {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
+            if let Err(err) =
+                export_percent_history(&buckets, config.output_percent_csv.as_deref(), precision)
+            {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
+            if let Err(err) = export_gap_report(&buckets, config.output_gaps_csv.as_deref()) {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
             if archive_csv && let Err(err) = remove_csv_file(&config.output_history_csv) {
                 error(&err);
-                return;
+                return ExitCode::FAILURE;
             }
 
-            let forecast =
-                match train_forecast_from_buckets(&buckets, config.horizon_days, &model_config) {
-                    Ok(forecast) => forecast,
-                    Err(err) => {
-                        error(&format!("Failed to train forecast model: {err}"));
-                        return;
-                    }
-                };
+            let horizons_days = std::iter::once(config.horizon_days)
+                .chain(config.extra_horizons_days.iter().copied())
+                .collect::<Vec<_>>();
+            let mut training_failure = None;
+            let (forecast, extra_forecasts, calibration) = match train_forecasts_from_buckets(
+                &buckets,
+                &horizons_days,
+                config.granularity,
+                &model_config,
+            )
+            .or_else(|err| {
+                if !config.forecast_fallback {
+                    return Err(err);
+                }
+                // Прошлые CSV ещё не перезаписаны: берём их, пока модель не починят.
+                let previous = load_previous_forecasts(
+                    &config.output_forecast_csv,
+                    &config.extra_horizons_days,
+                    archive_csv,
+                )
+                .map_err(|fallback_err| {
+                    format!("{err}; no previous forecast to fall back to: {fallback_err}")
+                })?;
+                tracing::warn!(error = %err, "Failed to train forecast model; reusing the previous forecast");
+                training_failure = Some(err);
+                Ok((previous, None))
+            }) {
+                Ok((mut forecasts, calibration)) => {
+                    let extra = forecasts.split_off(1);
+                    (forecasts.remove(0), extra, calibration)
+                }
+                Err(err) => {
+                    error(&format!("Failed to train forecast model: {err}"));
+                    return ExitCode::FAILURE;
+                }
+            };
+            let stale = training_failure
+                .is_some()
+                .then(|| stale_forecast(&forecast, &buckets));
 
-            if let Err(err) = model::write_forecast_csv(&forecast, &config.output_forecast_csv) {
+            if let Err(err) =
+                model::write_forecast_csv(&forecast, &config.output_forecast_csv, precision)
+            {
                 error(&format!("Failed to write forecast CSV: {err}"));
-                return;
+                return ExitCode::FAILURE;
             }
+            let extra_forecast_csvs = match write_extra_forecasts(
+                &extra_forecasts,
+                &config.extra_horizons_days,
+                &config.output_forecast_csv,
+                precision,
+            ) {
+                Ok(paths) => paths,
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            };
             if archive_csv {
                 match archive_csv_file(&config.output_forecast_csv) {
                     Ok(path) => success(&format!("Saved archive to {}", path.display())),
                     Err(err) => {
                         error(&err);
-                        return;
+                        return ExitCode::FAILURE;
                     }
                 }
                 if let Err(err) = remove_csv_file(&config.output_forecast_csv) {
                     error(&err);
-                    return;
+                    return ExitCode::FAILURE;
+                }
+                if let Err(err) = archive_and_remove(&extra_forecast_csvs) {
+                    error(&err);
+                    return ExitCode::FAILURE;
                 }
             }
+            if let Err(err) = publish_integrity(
+                &[
+                    config.output_history_csv.as_path(),
+                    config.output_forecast_csv.as_path(),
+                ]
+                .into_iter()
+                .chain(extra_forecast_csvs.iter().map(PathBuf::as_path))
+                .collect::<Vec<_>>(),
+                archive_csv,
+                integrity_config.as_ref(),
+            ) {
+                error(&format!("Failed to publish checksums: {err}"));
+                return ExitCode::FAILURE;
+            }
 
-            let overlay = build_forecast_overlay(&forecast);
-            if let Err(err) = report::draw_area_chart_with_forecast_from_buckets_and_config(
+            let smoothed = match smooth_history(
                 &buckets,
-                &config.output_html,
-                Some(&overlay),
-                chart_config,
-                Some(download_links),
-                config.minify_html,
+                &model_config,
+                config.output_smoothed_csv.as_deref(),
+                config.output_trend_csv.as_deref(),
+                chart_config.smoothed
+                    || chart_config.trend
+                    || chart_config.diagnostics
+                    || config.output_ics.is_some(),
+                precision,
+            ) {
+                Ok(smoothed) => smoothed,
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let mut overlay = build_forecast_overlay(&forecast);
+            overlay.smoothed = smoothed.as_ref().map(build_smoothed_overlay);
+            overlay.diagnostics = smoothed
+                .as_ref()
+                .and_then(|smoothed| build_model_diagnostics(smoothed, &model_config));
+            overlay.horizons = extra_forecasts.iter().map(build_forecast_overlay).collect();
+            overlay.stale = stale;
+            if let Err(err) = export_workbook(
+                &buckets,
+                &overlay,
+                model_config.interpolation,
+                chart_config.change_smoothing,
+                config.output_xlsx.as_deref(),
+            ) {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
+            if let Err(err) = export_calendar(&overlay, &events, config.output_ics.as_deref()) {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
+            let comparison = match load_comparison(compare.as_ref(), range) {
+                Ok(comparison) => comparison,
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let chart_config =
+                with_self_hosted_assets(chart_config, &config.output_html, &client, &fetch_config)
+                    .await;
+            let summary = match render_pages(
+                || {
+                    report::draw_area_chart_with_forecast_from_buckets_and_config(
+                        &buckets,
+                        &config.output_html,
+                        Some(&overlay),
+                        &chart_config,
+                        Some(download_links),
+                        config.minify_html,
+                        report::ExtraSections {
+                            comparison: comparison.as_ref(),
+                            front: front.as_ref(),
+                            figure_json: config.output_figure_json.as_deref(),
+                            ratios_json: config.output_ratios_json.as_deref(),
+                        },
+                    )
+                    .map_err(|err| format!("Failed to render forecast chart: {err}"))
+                },
+                || {
+                    export_updates(
+                        &buckets,
+                        &config.output_html,
+                        config.output_updates_html.as_deref(),
+                        &chart_config.page,
+                    )
+                },
+                || {
+                    export_methodology(
+                        &model_config,
+                        &chart_config,
+                        &config.output_html,
+                        config.output_methodology_html.as_deref(),
+                    )
+                },
             ) {
-                error(&format!("Failed to render forecast chart: {err}"));
-                return;
+                Ok(summary) => summary,
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let mut alerts = evaluate_alerts(&alert_rules, &buckets, Some(&overlay));
+            if let (Some(err), Some(stale)) = (&training_failure, stale) {
+                alerts.push(alerts::AlertMatch {
+                    rule: "Прогноз не обновлён".to_string(),
+                    message: format!(
+                        "модель не обучилась: {err}; показан прогноз, обученный по {}",
+                        stale.trained_to.format(DATE_FORMAT)
+                    ),
+                });
             }
+            let build_manifest = manifest::BuildManifest::new(
+                report::generated_at(),
+                "run",
+                input_digests,
+                manifest::RowCounts {
+                    history_snapshots: buckets.snapshots().len(),
+                    history_days: buckets.day_count(),
+                    forecast_days: overlay.dates.len(),
+                },
+                model_info(&model_config, calibration, overlay.training),
+            )
+            .with_alerts(alerts.clone());
+            let manifest_path = match publish_build_manifest(
+                &build_manifest,
+                &config.output_html,
+                &[
+                    Some(config.output_html.as_path()),
+                    config.output_updates_html.as_deref(),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>(),
+            ) {
+                Ok(path) => path,
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            };
 
             success(&format!(
                 "Saved forecast to {} and {}",
@@ -812,18 +4567,70 @@ async fn main() {
                 },
                 config.output_html.display()
             ));
+            let forecast_artifact = if archive_csv {
+                archive_path_for(&config.output_forecast_csv).ok()
+            } else {
+                Some(config.output_forecast_csv.clone())
+            };
+            store_build_cache(
+                build_cache.as_ref(),
+                [
+                    Some(config.output_html.clone()),
+                    forecast_artifact,
+                    config.output_smoothed_csv.clone(),
+                    config.output_trend_csv.clone(),
+                    config.output_full_resolution_csv.clone(),
+                    config.output_percent_csv.clone(),
+                    config.output_figure_json.clone(),
+                    config.output_ratios_json.clone(),
+                    config.output_gaps_csv.clone(),
+                    config.output_xlsx.clone(),
+                    config.output_ics.clone(),
+                    config.output_updates_html.clone(),
+                    Some(manifest_path),
+                ],
+            );
+            if let Err(err) = publish_site(&config.output_html, gh_pages.as_ref(), &summary) {
+                error(&format!("Failed to publish site: {err}"));
+                status = ExitCode::FAILURE;
+            }
+            if let Err(err) =
+                send_digest(&buckets, &summary, email.as_ref(), &config.output_html).await
+            {
+                error(&format!("Failed to send digest: {err}"));
+                status = ExitCode::FAILURE;
+            }
+            send_notifications(&client, &summary, &alerts, notifications.as_ref()).await;
         }
         ModeConfig::Download(config) => {
             tracing::info!(
                 mode = "download",
                 archive_csv,
+                store_csv = %config.store_csv.display(),
                 output_csv = %config.output_csv.display(),
                 "Downloading history data"
             );
-            info(&format!("Saving CSV to {}", config.output_csv.display()));
-            if let Err(err) = download_to_csv(&config.output_csv).await {
+            info(&format!("Saving CSV to {}", config.store_csv.display()));
+            let mut store = CsvStore::new(&config.store_csv);
+            let downloaded = match &config.isw {
+                Some(isw) => download_to_store(&mut store, isw, &fetcher, range, &interrupt).await,
+                None => {
+                    download_history(
+                        &config.store_csv,
+                        config.mirror_url.as_deref(),
+                        config.raw_archive_dir.as_deref(),
+                        &fetcher,
+                        range,
+                        &interrupt,
+                    )
+                    .await
+                }
+            };
+            if let Err(err) =
+                downloaded.and_then(|()| publish_history(&store, &config.output_csv, precision))
+            {
                 error(&err);
-                return;
+                return ExitCode::FAILURE;
             }
             if archive_csv {
                 match archive_csv_file(&config.output_csv) {
@@ -831,18 +4638,26 @@ async fn main() {
                         success(&format!("Saved archive to {}", path.display()));
                         if let Err(err) = remove_csv_file(&config.output_csv) {
                             error(&err);
-                            return;
+                            return ExitCode::FAILURE;
                         }
                     }
                     Err(err) => {
                         error(&err);
-                        return;
+                        return ExitCode::FAILURE;
                     }
                 }
             }
             if !archive_csv {
                 success(&format!("Saved CSV to {}", config.output_csv.display()));
             }
+            if let Err(err) = publish_integrity(
+                &[&config.output_csv],
+                archive_csv,
+                integrity_config.as_ref(),
+            ) {
+                error(&format!("Failed to publish checksums: {err}"));
+                status = ExitCode::FAILURE;
+            }
         }
         ModeConfig::Forecast(config) => {
             tracing::info!(
@@ -854,18 +4669,72 @@ async fn main() {
                 output_csv = %config.output_csv.display(),
                 "Training forecast model"
             );
-            let forecast =
-                match train_forecast_from_csv(&config.csv, config.horizon_days, &model_config) {
-                    Ok(forecast) => forecast,
-                    Err(err) => {
-                        error(&format!("Failed to train forecast model: {err}"));
-                        return;
-                    }
-                };
+            let mut buckets = match CsvStore::new(&config.csv).load_range(range) {
+                Ok(buckets) => buckets,
+                Err(err) => {
+                    error(&format!("Failed to read history CSV: {err}"));
+                    return ExitCode::FAILURE;
+                }
+            };
+            if let Err(err) = apply_exclusions(&mut buckets, &model_config) {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
+            let horizons_days = std::iter::once(config.horizon_days)
+                .chain(config.extra_horizons_days.iter().copied())
+                .collect::<Vec<_>>();
+            let (forecast, extra_forecasts) = match train_forecasts_from_buckets(
+                &buckets,
+                &horizons_days,
+                config.granularity,
+                &model_config,
+            ) {
+                Ok((mut forecasts, _calibration)) => {
+                    let extra = forecasts.split_off(1);
+                    (forecasts.remove(0), extra)
+                }
+                Err(err) => {
+                    error(&format!("Failed to train forecast model: {err}"));
+                    return ExitCode::FAILURE;
+                }
+            };
 
-            if let Err(err) = model::write_forecast_csv(&forecast, &config.output_csv) {
+            if let Err(err) = model::write_forecast_csv(&forecast, &config.output_csv, precision) {
                 error(&format!("Failed to write forecast CSV: {err}"));
-                return;
+                return ExitCode::FAILURE;
+            }
+            let extra_forecast_csvs = match write_extra_forecasts(
+                &extra_forecasts,
+                &config.extra_horizons_days,
+                &config.output_csv,
+                precision,
+            ) {
+                Ok(paths) => paths,
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            if let Err(err) = publish_integrity(
+                &std::iter::once(config.output_csv.as_path())
+                    .chain(extra_forecast_csvs.iter().map(PathBuf::as_path))
+                    .collect::<Vec<_>>(),
+                false,
+                integrity_config.as_ref(),
+            ) {
+                error(&format!("Failed to publish checksums: {err}"));
+                return ExitCode::FAILURE;
+            }
+            if let Err(err) = smooth_history(
+                &buckets,
+                &model_config,
+                config.output_smoothed_csv.as_deref(),
+                config.output_trend_csv.as_deref(),
+                false,
+                precision,
+            ) {
+                error(&err);
+                return ExitCode::FAILURE;
             }
             if archive_csv {
                 match archive_csv_file(&config.output_csv) {
@@ -873,14 +4742,18 @@ async fn main() {
                         success(&format!("Saved archive to {}", path.display()));
                         if let Err(err) = remove_csv_file(&config.output_csv) {
                             error(&err);
-                            return;
+                            return ExitCode::FAILURE;
                         }
                     }
                     Err(err) => {
                         error(&err);
-                        return;
+                        return ExitCode::FAILURE;
                     }
                 }
+                if let Err(err) = archive_and_remove(&extra_forecast_csvs) {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
             }
             success(&format!(
                 "Saved forecast to {}",
@@ -904,72 +4777,456 @@ async fn main() {
                 minify_html = config.minify_html,
                 "Rendering HTML report"
             );
-            let download_links =
+            if let Some(url) = &config.csv_url
+                && let Err(err) = remote::refresh(&client, &fetch_config, url, &config.csv).await
+            {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
+            let mut download_links =
                 match build_download_links(&config.csv, &config.forecast_csv, archive_csv) {
                     Ok(links) => links,
                     Err(err) => {
                         error(&err);
-                        return;
+                        return ExitCode::FAILURE;
                     }
                 };
-            if archive_csv {
-                match archive_csv_file(&config.csv) {
-                    Ok(path) => success(&format!("Saved archive to {}", path.display())),
-                    Err(err) => {
-                        error(&err);
-                        return;
+            // Удалённую историю публикует её владелец: ссылка ведёт на источник, копия не архивируется.
+            if let Some(url) = &config.csv_url {
+                download_links.history.clone_from(url);
+            }
+            if archive_csv {
+                if config.csv_url.is_none() {
+                    match archive_csv_file(&config.csv) {
+                        Ok(path) => success(&format!("Saved archive to {}", path.display())),
+                        Err(err) => {
+                            error(&err);
+                            return ExitCode::FAILURE;
+                        }
                     }
                 }
                 match archive_csv_file(&config.forecast_csv) {
                     Ok(path) => success(&format!("Saved archive to {}", path.display())),
                     Err(err) => {
                         error(&err);
-                        return;
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            let build_cache = open_build_cache(
+                config.build_cache,
+                &config.output_html,
+                &format!(
+                    "{range:?}\n{archive_csv}\n{model_config:?}\n{chart_config:?}\n{config:?}\n{events:?}\n{compare:?}\n{precision:?}\n{integrity_config:?}"
+                ),
+                &[
+                    Some(config.csv.as_path()),
+                    Some(config.forecast_csv.as_path()),
+                    compare.as_ref().map(|compare| compare.csv.as_path()),
+                ]
+                .into_iter()
+                .chain(model_inputs(&model_config))
+                .flatten()
+                .collect::<Vec<_>>(),
+            );
+            if build_cache
+                .as_ref()
+                .is_some_and(cache::BuildCache::is_fresh)
+            {
+                if archive_csv {
+                    let local = config.csv_url.is_none().then_some(&config.csv);
+                    for csv in local.into_iter().chain([&config.forecast_csv]) {
+                        if let Err(err) = remove_csv_file(csv) {
+                            error(&err);
+                        }
                     }
                 }
+                success("Inputs are unchanged; skipping HTML rebuild");
+                return ExitCode::SUCCESS;
             }
-            let overlay = match load_forecast_overlay(&config.forecast_csv) {
+            let input_digests = match manifest::hash_inputs(
+                &[
+                    Some(config.csv.as_path()),
+                    Some(config.forecast_csv.as_path()),
+                ]
+                .into_iter()
+                .chain(model_inputs(&model_config))
+                .flatten()
+                .collect::<Vec<_>>(),
+            ) {
+                Ok(digests) => digests,
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let mut overlay = match load_forecast_overlay(&config.forecast_csv) {
                 Ok(overlay) => overlay,
                 Err(err) => {
                     error(&format!("Failed to read forecast CSV: {err}"));
-                    return;
+                    return ExitCode::FAILURE;
                 }
             };
+            if let Err(err) = check_forecast_as_of(&overlay, range.as_of) {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
 
-            if let Err(err) = report::draw_area_chart_with_forecast_and_config(
-                &config.csv,
-                &config.output_html,
-                Some(&overlay),
-                chart_config,
-                Some(download_links),
-                config.minify_html,
+            let mut buckets = match CsvStore::new(&config.csv).load_range(range) {
+                Ok(buckets) => buckets,
+                Err(err) => {
+                    error(&format!("Failed to read history CSV: {err}"));
+                    return ExitCode::FAILURE;
+                }
+            };
+            if let Err(err) = apply_exclusions(&mut buckets, &model_config) {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
+            if let Err(err) = export_full_resolution(
+                &buckets,
+                config.output_full_resolution_csv.as_deref(),
+                precision,
+            ) {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
+            if let Err(err) =
+                export_percent_history(&buckets, config.output_percent_csv.as_deref(), precision)
+            {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
+            if let Err(err) = export_gap_report(&buckets, config.output_gaps_csv.as_deref()) {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
+            match smooth_history(
+                &buckets,
+                &model_config,
+                None,
+                None,
+                chart_config.smoothed
+                    || chart_config.trend
+                    || chart_config.diagnostics
+                    || config.output_ics.is_some(),
+                precision,
+            ) {
+                Ok(smoothed) => {
+                    overlay.smoothed = smoothed.as_ref().map(build_smoothed_overlay);
+                    overlay.diagnostics = smoothed
+                        .as_ref()
+                        .and_then(|smoothed| build_model_diagnostics(smoothed, &model_config));
+                }
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            }
+            if let Err(err) = export_workbook(
+                &buckets,
+                &overlay,
+                model_config.interpolation,
+                chart_config.change_smoothing,
+                config.output_xlsx.as_deref(),
             ) {
-                error(&format!("Failed to render forecast chart: {err}"));
-                return;
+                error(&err);
+                return ExitCode::FAILURE;
             }
+            if let Err(err) = export_calendar(&overlay, &events, config.output_ics.as_deref()) {
+                error(&err);
+                return ExitCode::FAILURE;
+            }
+            let comparison = match load_comparison(compare.as_ref(), range) {
+                Ok(comparison) => comparison,
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let chart_config =
+                with_self_hosted_assets(chart_config, &config.output_html, &client, &fetch_config)
+                    .await;
+            let summary = match render_pages(
+                || {
+                    report::draw_area_chart_with_forecast_from_buckets_and_config(
+                        &buckets,
+                        &config.output_html,
+                        Some(&overlay),
+                        &chart_config,
+                        Some(download_links),
+                        config.minify_html,
+                        report::ExtraSections {
+                            comparison: comparison.as_ref(),
+                            figure_json: config.output_figure_json.as_deref(),
+                            ratios_json: config.output_ratios_json.as_deref(),
+                            ..report::ExtraSections::default()
+                        },
+                    )
+                    .map_err(|err| format!("Failed to render forecast chart: {err}"))
+                },
+                || {
+                    export_updates(
+                        &buckets,
+                        &config.output_html,
+                        config.output_updates_html.as_deref(),
+                        &chart_config.page,
+                    )
+                },
+                || {
+                    export_methodology(
+                        &model_config,
+                        &chart_config,
+                        &config.output_html,
+                        config.output_methodology_html.as_deref(),
+                    )
+                },
+            ) {
+                Ok(summary) => summary,
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let alerts = evaluate_alerts(&alert_rules, &buckets, Some(&overlay));
+            let build_manifest = manifest::BuildManifest::new(
+                report::generated_at(),
+                "render",
+                input_digests,
+                manifest::RowCounts {
+                    history_snapshots: buckets.snapshots().len(),
+                    history_days: buckets.day_count(),
+                    forecast_days: overlay.dates.len(),
+                },
+                model_info(&model_config, None, overlay.training),
+            )
+            .with_alerts(alerts.clone())
+            .with_as_of(range.as_of);
+            let manifest_path = match publish_build_manifest(
+                &build_manifest,
+                &config.output_html,
+                &[
+                    Some(config.output_html.as_path()),
+                    config.output_updates_html.as_deref(),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>(),
+            ) {
+                Ok(path) => path,
+                Err(err) => {
+                    error(&err);
+                    return ExitCode::FAILURE;
+                }
+            };
             if archive_csv {
-                if let Err(err) = remove_csv_file(&config.csv) {
+                if config.csv_url.is_none()
+                    && let Err(err) = remove_csv_file(&config.csv)
+                {
                     error(&err);
-                    return;
+                    return ExitCode::FAILURE;
                 }
                 if let Err(err) = remove_csv_file(&config.forecast_csv) {
                     error(&err);
-                    return;
+                    return ExitCode::FAILURE;
                 }
             }
             success(&format!("Saved HTML to {}", config.output_html.display()));
+            store_build_cache(
+                build_cache.as_ref(),
+                [
+                    Some(config.output_html.clone()),
+                    config.output_full_resolution_csv.clone(),
+                    config.output_percent_csv.clone(),
+                    config.output_figure_json.clone(),
+                    config.output_ratios_json.clone(),
+                    config.output_gaps_csv.clone(),
+                    config.output_xlsx.clone(),
+                    config.output_ics.clone(),
+                    config.output_updates_html.clone(),
+                    Some(manifest_path),
+                ],
+            );
+            if let Err(err) = publish_site(&config.output_html, gh_pages.as_ref(), &summary) {
+                error(&format!("Failed to publish site: {err}"));
+                status = ExitCode::FAILURE;
+            }
+            if let Err(err) =
+                send_digest(&buckets, &summary, email.as_ref(), &config.output_html).await
+            {
+                error(&format!("Failed to send digest: {err}"));
+                status = ExitCode::FAILURE;
+            }
+            send_notifications(&client, &summary, &alerts, notifications.as_ref()).await;
+        }
+        ModeConfig::Serve(config) => {
+            tracing::info!(
+                mode = "serve",
+                bind = %config.bind,
+                input_csv = %config.csv.display(),
+                forecast_csv = %config.forecast_csv.display(),
+                site_dir = %config.site_dir.display(),
+                "Starting server"
+            );
+            if let Some((retention, interval)) = retention
+                .and_then(|retention| retention.interval.map(|interval| (retention, interval)))
+            {
+                tokio::spawn(gc::watch(retention, interval));
+            }
+            #[cfg(feature = "serve")]
+            let served = serve::serve(config, range, model_config.interpolation, fetcher).await;
+            #[cfg(not(feature = "serve"))]
+            let served: Result<(), String> =
+                Err("rua was built without the `serve` feature".to_string());
+            if let Err(err) = served {
+                error(&err);
+                status = ExitCode::FAILURE;
+            }
         }
     }
+    status
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        AppConfigFile, DownloadConfig, Mode, ModeConfig, ModelKind, RenderConfig,
-        resolve_app_config,
+        AppConfigFile, DEFAULT_TUNE_CSV, DEFAULT_TUNE_DAMPING, DownloadConfig, Mode, ModeConfig,
+        ModelKind, RenderConfig, render_pages, resolve_app_config,
     };
+    use crate::data::test_support::area_row;
     use chrono::NaiveDate;
     use std::path::Path;
+    use std::time::Duration;
+
+    /// Источник с неполной загрузкой: отдаёт срезы `(день мая 2024, площадь)` из `total`
+    /// ожидаемых; остальные прерваны по Ctrl-C или не скачались.
+    struct PartialSource {
+        snapshots: Vec<(u32, f64)>,
+        total: usize,
+        interrupted: bool,
+    }
+
+    impl crate::source::MapSource for PartialSource {
+        fn name(&self) -> &'static str {
+            "partial"
+        }
+
+        fn probe_url(&self) -> &'static str {
+            "http://127.0.0.1/"
+        }
+
+        async fn fetch_areas(
+            &self,
+            _fetcher: &crate::source::Fetcher,
+            _range: crate::series::DateRange,
+            _interrupt: &crate::interrupt::Interrupt,
+            sink: crate::source::AreaSink,
+        ) -> Result<crate::source::FetchSummary, String> {
+            for (day, area) in &self.snapshots {
+                sink.send(vec![area_row(*day, *area)])
+                    .await
+                    .map_err(|err| err.to_string())?;
+            }
+            Ok(crate::source::FetchSummary {
+                fetched: self.snapshots.len(),
+                total: self.total,
+                interrupted: self.interrupted,
+            })
+        }
+
+        async fn fetch_latest(
+            &self,
+            _fetcher: &crate::source::Fetcher,
+        ) -> Result<crate::source::LatestSnapshot, String> {
+            Err("no live endpoint".to_string())
+        }
+
+        async fn fetch_map_pair(
+            &self,
+            _fetcher: &crate::source::Fetcher,
+            _ago: chrono::TimeDelta,
+        ) -> Result<(crate::source::MapGeoJson, crate::source::MapGeoJson), String> {
+            Err("no maps".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn interrupted_download_keeps_history_and_appends_new_snapshots() {
+        use crate::store::{CsvStore, DataStore};
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("interrupted.csv");
+        let mut store = CsvStore::new(&path);
+        store
+            .append(vec![area_row(1, 100.0), area_row(2, 110.0)])
+            .expect("history should be written");
+
+        let source = PartialSource {
+            snapshots: vec![(4, 130.0), (2, 110.0)],
+            total: 5,
+            interrupted: true,
+        };
+        let err = super::download_to_store(
+            &mut store,
+            &source,
+            &crate::source::Fetcher::new(
+                reqwest::Client::new(),
+                crate::fetch::FetchConfig::default(),
+            ),
+            crate::series::DateRange::default(),
+            &crate::interrupt::Interrupt::listen(),
+        )
+        .await
+        .expect_err("interrupted download should report an error");
+        let buckets = store.load_buckets().expect("history should load");
+
+        assert!(err.contains("2 of 5"), "{err}");
+        assert!(err.contains("appended 1 new rows"), "{err}");
+        for day in [1, 2, 4] {
+            assert!(buckets.has_day(area_row(day, 0.0).date()), "day {day}");
+        }
+    }
+
+    #[tokio::test]
+    async fn download_with_a_failed_snapshot_keeps_the_skipped_day() {
+        use crate::store::{CsvStore, DataStore};
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("incomplete.csv");
+        let mut store = CsvStore::new(&path);
+        store
+            .append(vec![
+                area_row(1, 100.0),
+                area_row(2, 110.0),
+                area_row(3, 120.0),
+            ])
+            .expect("history should be written");
+
+        // Срез за 2 мая не скачался: источник предупредил и продолжил без него.
+        let source = PartialSource {
+            snapshots: vec![(1, 100.0), (3, 120.0), (4, 130.0)],
+            total: 4,
+            interrupted: false,
+        };
+        let err = super::download_to_store(
+            &mut store,
+            &source,
+            &crate::source::Fetcher::new(
+                reqwest::Client::new(),
+                crate::fetch::FetchConfig::default(),
+            ),
+            crate::series::DateRange::default(),
+            &crate::interrupt::Interrupt::listen(),
+        )
+        .await
+        .expect_err("incomplete download should report an error");
+        let buckets = store.load_buckets().expect("history should load");
+
+        assert!(err.contains("incomplete after 3 of 4"), "{err}");
+        for day in 1..=4 {
+            assert!(buckets.has_day(area_row(day, 0.0).date()), "day {day}");
+        }
+    }
 
     #[test]
     fn model_kind_supports_aliases() {
@@ -1021,6 +5278,10 @@ mod tests {
                     run.output_html,
                     Path::new("workspace").join("dist/index.html")
                 );
+                assert_eq!(
+                    run.store_csv,
+                    Path::new("workspace").join("data/history.csv")
+                );
                 assert_eq!(
                     run.output_history_csv,
                     Path::new("workspace").join("dist/history.csv")
@@ -1034,6 +5295,332 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_multiple_notification_targets() {
+        let config: AppConfigFile = toml::from_str(
+            "mode = \"run\"\n[notifications]\nlink = \"https://example.org\"\n\
+             [[notifications.targets]]\nkind = \"telegram\"\nchat_id = \"@rua\"\n\
+             [[notifications.targets]]\nkind = \"slack\"\nwebhook_env = \"SLACK_URL\"",
+        )
+        .expect("notification targets should parse");
+        let resolved = resolve_app_config(config, Path::new("workspace"))
+            .expect("notification config should resolve");
+        let notifications = resolved.notifications.expect("notifications should be set");
+        assert_eq!(notifications.link.as_deref(), Some("https://example.org"));
+        assert_eq!(
+            notifications.targets,
+            vec![
+                crate::notify::NotifierTarget::Telegram(crate::notify::TelegramNotifier {
+                    chat_id: "@rua".to_string(),
+                    token_env: "RUA_TELEGRAM_TOKEN".to_string(),
+                }),
+                crate::notify::NotifierTarget::Slack(crate::notify::SlackNotifier {
+                    webhook_env: "SLACK_URL".to_string(),
+                }),
+            ]
+        );
+
+        let err = toml::from_str::<AppConfigFile>(
+            "mode = \"run\"\n[[notifications.targets]]\nkind = \"discord\"\nchat_id = \"1\"",
+        )
+        .expect_err("unknown target field should fail parse");
+        assert!(err.to_string().contains("unknown field"));
+    }
+
+    #[test]
+    fn no_downsample_flag_overrides_the_chart_section() {
+        let config: AppConfigFile = toml::from_str("mode = \"run\"\n[chart]\ndownsample = true")
+            .expect("chart section should parse");
+        let resolved =
+            resolve_app_config(config, Path::new("workspace")).expect("config should resolve");
+        assert!(
+            super::override_downsample(resolved.clone(), false)
+                .chart
+                .downsample
+        );
+        assert!(!super::override_downsample(resolved, true).chart.downsample);
+    }
+
+    #[test]
+    fn resolves_date_range_and_rejects_inverted_bounds() {
+        let config: AppConfigFile =
+            toml::from_str("mode = \"run\"\nfrom = \"2024-01-01\"\nto = \"2024-06-30\"")
+                .expect("date range should parse");
+        let resolved =
+            resolve_app_config(config, Path::new("workspace")).expect("date range should resolve");
+        assert_eq!(resolved.range.from, NaiveDate::from_ymd_opt(2024, 1, 1));
+        assert_eq!(resolved.range.to, NaiveDate::from_ymd_opt(2024, 6, 30));
+        // `--from` сдвигает только начало; `--to` раньше начала — ошибка.
+        let overridden = super::override_range(resolved, NaiveDate::from_ymd_opt(2024, 3, 1), None)
+            .expect("flags should override the range");
+        assert_eq!(overridden.range.from, NaiveDate::from_ymd_opt(2024, 3, 1));
+        assert_eq!(overridden.range.to, NaiveDate::from_ymd_opt(2024, 6, 30));
+        assert!(
+            super::override_range(overridden, None, NaiveDate::from_ymd_opt(2024, 2, 1)).is_err()
+        );
+
+        let config: AppConfigFile =
+            toml::from_str("mode = \"run\"\nfrom = \"2024-06-30\"\nto = \"2024-01-01\"")
+                .expect("inverted range should parse");
+        let err = resolve_app_config(config, Path::new("workspace"))
+            .expect_err("inverted range should fail");
+        assert!(err.contains("must not be later"));
+    }
+
+    #[test]
+    fn interpolation_is_configured_per_analysis() {
+        let config: AppConfigFile = toml::from_str(
+            "mode = \"run\"\ninterpolation = \"previous\"\n[chart]\ninterpolation = \"none\"",
+        )
+        .expect("interpolation should parse");
+        let resolved = resolve_app_config(config, Path::new("workspace"))
+            .expect("interpolation should resolve");
+        assert_eq!(
+            resolved.model.interpolation,
+            crate::series::InterpolationStrategy::Previous
+        );
+        assert_eq!(
+            resolved.chart.interpolation,
+            crate::series::InterpolationStrategy::None
+        );
+
+        let config: AppConfigFile = toml::from_str("mode = \"run\"\ninterpolation = \"none\"")
+            .expect("model interpolation should parse");
+        let err = resolve_app_config(config, Path::new("workspace"))
+            .expect_err("model cannot train on broken series");
+        assert!(err.contains("only supported in [chart]"));
+    }
+
+    #[test]
+    fn pages_render_side_by_side_and_report_errors_in_page_order() {
+        let rendered = std::sync::atomic::AtomicUsize::new(0);
+        let page = |name: &str| {
+            rendered.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Err(format!("{name} failed"))
+        };
+        let result = render_pages(
+            || Err("main page failed".to_string()),
+            || page("updates"),
+            || page("methodology"),
+        );
+        assert_eq!(result.err().as_deref(), Some("main page failed"));
+        assert_eq!(rendered.into_inner(), 2);
+    }
+
+    #[test]
+    fn resolves_tune_section_and_validates_grid() {
+        let config: AppConfigFile = toml::from_str(
+            "mode = \"forecast\"\n[tune]\nlambda = [1.0, 10.0]\nfolds = 2\npersist = true",
+        )
+        .expect("tune config should parse");
+        let tune = resolve_app_config(config, Path::new("workspace"))
+            .expect("tune should resolve")
+            .tune;
+        assert_eq!(tune.grid.lambda, vec![1.0, 10.0]);
+        assert_eq!(tune.grid.damping, DEFAULT_TUNE_DAMPING.to_vec());
+        assert_eq!(tune.backtest.folds, 2);
+        assert_eq!(
+            tune.output_csv,
+            Path::new("workspace").join(DEFAULT_TUNE_CSV)
+        );
+        assert!(tune.persist);
+
+        let config: AppConfigFile =
+            toml::from_str("mode = \"forecast\"\n[tune]\ndamping = [1.5]").expect("should parse");
+        let err = resolve_app_config(config, Path::new("workspace"))
+            .expect_err("damping outside [0, 1] must be rejected");
+        assert!(err.contains("tune.damping"));
+    }
+
+    #[test]
+    fn resolves_fetch_timeouts_and_deadline() {
+        let config: AppConfigFile = toml::from_str(
+            "mode = \"download\"\n[fetch]\nrequest_timeout_secs = 15\ndeadline_secs = 600",
+        )
+        .expect("fetch config should parse");
+        let resolved =
+            resolve_app_config(config, Path::new("workspace")).expect("fetch should resolve");
+        assert_eq!(resolved.fetch.request_timeout, Duration::from_secs(15));
+        assert_eq!(resolved.fetch.deadline, Some(Duration::from_mins(10)));
+        assert_eq!(
+            resolved.fetch.connect_timeout,
+            crate::fetch::FetchConfig::default().connect_timeout
+        );
+
+        assert!(
+            toml::from_str::<AppConfigFile>("mode = \"download\"\n[fetch]\nmax_retries = 0")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn resolves_page_branding_over_defaults() {
+        let config: AppConfigFile = toml::from_str(
+            "mode = \"run\"\n[page]\ntitle = \"Зеркало\"\nrepo_url = \"\"\nanalytics_html = \"<script defer src=\\\"/a.js\\\"></script>\"",
+        )
+        .expect("page config should parse");
+        let resolved =
+            resolve_app_config(config, Path::new("workspace")).expect("page should resolve");
+        let page = resolved.chart.page;
+        assert_eq!(page.title, "Зеркало");
+        assert_eq!(page.site_url, crate::report::PageConfig::default().site_url);
+        assert_eq!(page.repo_url, None);
+        assert_eq!(
+            page.analytics_html.as_deref(),
+            Some("<script defer src=\"/a.js\"></script>")
+        );
+    }
+
+    #[test]
+    fn resolves_region_shares_and_rejects_unknown_regions() {
+        let parse = |regions: &str| {
+            let config: AppConfigFile =
+                toml::from_str(&format!("mode = \"run\"\n{regions}")).expect("config should parse");
+            resolve_app_config(config, Path::new("workspace"))
+        };
+        let resolved = parse(
+            "[[page.regions]]\nname = \"Запорожская\"\noccupied_km2 = 20000\nas_of = \"2024-05-01\"\n\
+             [[page.regions]]\nname = \"Киев\"\noccupied_km2 = 0\nas_of = \"2024-05-01\"",
+        )
+        .expect("regions should resolve");
+        assert_eq!(resolved.chart.page.regions.len(), 2);
+        assert_eq!(resolved.chart.page.regions[0].name, "Запорожская");
+        assert_eq!(
+            resolved.chart.page.regions[0].as_of,
+            NaiveDate::from_ymd_opt(2024, 5, 1).expect("valid date")
+        );
+
+        let unknown = parse(
+            "[[page.regions]]\nname = \"Атлантида\"\noccupied_km2 = 1\nas_of = \"2024-05-01\"",
+        )
+        .expect_err("unknown region is rejected");
+        assert!(unknown.contains("Атлантида"));
+        let oversized =
+            parse("[[page.regions]]\nname = \"Киев\"\noccupied_km2 = 1000\nas_of = \"2024-05-01\"")
+                .expect_err("occupied area above the region area is rejected");
+        assert!(oversized.contains("between 0 and 839"));
+        toml::from_str::<AppConfigFile>(
+            "mode = \"run\"\n[[page.regions]]\nname = \"Киев\"\noccupied_km2 = 0",
+        )
+        .expect_err("a region without as_of is rejected");
+    }
+
+    #[test]
+    fn resolves_change_smoothing_and_rejects_oversized_min_periods() {
+        let parse = |smoothing: &str| {
+            let config: AppConfigFile = toml::from_str(&format!(
+                "mode = \"run\"\n[chart.change_smoothing]\n{smoothing}"
+            ))
+            .expect("config should parse");
+            resolve_app_config(config, Path::new("workspace"))
+        };
+        let resolved = parse("method = \"loess\"\nwindow = 9").expect("smoothing should resolve");
+        assert_eq!(
+            resolved.chart.change_smoothing,
+            crate::report::SmoothingSpec {
+                method: crate::report::SmoothingMethod::Loess,
+                window: 9,
+                min_periods: 3,
+            }
+        );
+        let err = parse("window = 2\nmin_periods = 3").expect_err("min_periods above window");
+        assert!(err.contains("must not exceed window"));
+    }
+
+    #[test]
+    fn resolves_comparison_sets_and_rejects_unknown_builtins() {
+        let parse = |sets: &str| {
+            let config: AppConfigFile =
+                toml::from_str(&format!("mode = \"run\"\n{sets}")).expect("config should parse");
+            resolve_app_config(config, Path::new("workspace")).map(|config| config.chart.page)
+        };
+        let page = parse(
+            "[[page.comparison_sets]]\nbuiltin = \"eu\"\n\
+             [[page.comparison_sets]]\nbuiltin = \"japan_prefectures\"\ntitle = \"Япония\"",
+        )
+        .expect("builtin sets should resolve");
+        assert_eq!(
+            page.comparison_sets
+                .iter()
+                .map(|set| (set.key.as_str(), set.title.as_str()))
+                .collect::<Vec<_>>(),
+            [("eu", "Евросоюз"), ("japan_prefectures", "Япония")]
+        );
+        assert!(page.flag_codes().contains(&"sk"));
+        let defaults = parse("").expect("default sets");
+        assert_eq!(defaults.comparison_sets.len(), 2);
+        let err =
+            parse("[[page.comparison_sets]]\nbuiltin = \"mars\"").expect_err("unknown builtin set");
+        assert!(err.contains("uk_counties"));
+        let err =
+            parse("[[page.comparison_sets]]\ntitle = \"Пусто\"").expect_err("set without a source");
+        assert!(err.contains("Exactly one of"));
+    }
+
+    #[test]
+    fn resolves_alert_rules_with_default_names() {
+        let config: AppConfigFile = toml::from_str(
+            "mode = \"run\"\n\
+             [[alerts]]\nkind = \"daily-change\"\nabove_km2 = 100\ndays = 3\n\
+             [[alerts]]\nkind = \"stale\"\nname = \"Источник молчит\"\nhours = 48",
+        )
+        .expect("alerts should parse");
+        let resolved =
+            resolve_app_config(config, Path::new("workspace")).expect("alerts should resolve");
+        assert_eq!(
+            resolved.alerts,
+            vec![
+                crate::alerts::AlertRule {
+                    name: "Суточное изменение > 100 км² 3 дн. подряд".to_string(),
+                    condition: crate::alerts::AlertCondition::DailyChange {
+                        above_km2: 100.0,
+                        days: 3,
+                    },
+                },
+                crate::alerts::AlertRule {
+                    name: "Источник молчит".to_string(),
+                    condition: crate::alerts::AlertCondition::Stale { hours: 48 },
+                },
+            ]
+        );
+        assert!(
+            toml::from_str::<AppConfigFile>(
+                "mode = \"run\"\n[[alerts]]\nkind = \"stale\"\nhours = 0"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn resolves_integrity_and_mirror() {
+        let config: AppConfigFile = toml::from_str(
+            "mode = \"download\"\n[download]\nmirror_url = \"https://rua.hexq.ru/history.csv.gz\"\n[integrity]\nminisign_secret_key = \"keys/rua.key\"",
+        )
+        .expect("integrity config should parse");
+        let resolved =
+            resolve_app_config(config, Path::new("repo")).expect("integrity should resolve");
+        assert_eq!(
+            resolved.integrity,
+            Some(crate::integrity::IntegrityConfig {
+                checksums: true,
+                minisign_secret_key: Some(Path::new("repo").join("keys/rua.key")),
+            })
+        );
+        let ModeConfig::Download(download) = resolved.mode_config else {
+            panic!("download mode expected");
+        };
+        assert_eq!(
+            download.mirror_url.as_deref(),
+            Some("https://rua.hexq.ru/history.csv.gz")
+        );
+
+        let config: AppConfigFile =
+            toml::from_str("mode = \"download\"").expect("default config should parse");
+        let resolved =
+            resolve_app_config(config, Path::new("repo")).expect("default should resolve");
+        assert_eq!(resolved.integrity, None);
+    }
+
     #[test]
     fn rejects_unknown_fields() {
         let err = toml::from_str::<AppConfigFile>("mode = \"run\"\nunknown = 1")
@@ -1049,6 +5636,25 @@ mod tests {
         assert!(err.to_string().to_lowercase().contains("nonzero"));
     }
 
+    #[test]
+    fn extra_horizons_skip_the_primary_one_and_get_their_own_csv() {
+        let config: AppConfigFile = toml::from_str(
+            "mode = \"forecast\"\n[forecast]\nhorizon_days = 90\nextra_horizons_days = [365, 30, 90, 30]",
+        )
+        .expect("forecast config should parse");
+        let resolved =
+            resolve_app_config(config, Path::new("repo")).expect("config should resolve");
+        let ModeConfig::Forecast(forecast) = resolved.mode_config else {
+            panic!("forecast mode expected");
+        };
+        let days = |days| std::num::NonZeroUsize::new(days).expect("non-zero");
+        assert_eq!(forecast.extra_horizons_days, vec![days(30), days(365)]);
+        assert_eq!(
+            super::horizon_csv_path(&forecast.output_csv, days(365)),
+            Path::new("repo").join("dist/forecast_365d.csv")
+        );
+    }
+
     #[test]
     fn render_mode_requires_forecast_csv() {
         let config: AppConfigFile = toml::from_str("mode = \"render\"")
@@ -1070,9 +5676,20 @@ mod tests {
         assert_eq!(
             resolved.mode_config,
             ModeConfig::Download(DownloadConfig {
+                store_csv: Path::new("repo").join("data/history.csv"),
                 output_csv: Path::new("repo").join("out/history.csv"),
+                mirror_url: None,
+                raw_archive_dir: None,
+                isw: None,
             })
         );
+
+        let config: AppConfigFile =
+            toml::from_str("mode = \"download\"\n[download]\nstore_csv = \"dist/history.csv\"")
+                .expect("download config should parse");
+        let err = resolve_app_config(config, Path::new("repo"))
+            .expect_err("store and published copy must differ");
+        assert!(err.contains("store_csv"), "{err}");
     }
 
     #[test]
@@ -1089,11 +5706,38 @@ mod tests {
             resolved.mode_config,
             ModeConfig::Render(RenderConfig {
                 csv: Path::new("repo").join("dist/history.csv"),
+                csv_url: None,
                 forecast_csv: Path::new("repo").join("dist/forecast.csv"),
                 output_html: Path::new("repo").join("dist/custom.html"),
                 minify_html: true,
+                output_full_resolution_csv: None,
+                output_percent_csv: None,
+                output_figure_json: None,
+                output_ratios_json: None,
+                output_gaps_csv: None,
+                output_xlsx: None,
+                output_ics: None,
+                output_updates_html: None,
+                output_methodology_html: None,
+                build_cache: false,
             })
         );
+
+        let url = "https://example.org/history.csv";
+        let config: AppConfigFile = toml::from_str(&format!(
+            "mode = \"render\"\n[render]\ncsv = \"{url}\"\nforecast_csv = \"dist/forecast.csv\""
+        ))
+        .expect("render config should parse");
+        let resolved =
+            resolve_app_config(config, Path::new("repo")).expect("config should resolve");
+        let ModeConfig::Render(render) = resolved.mode_config else {
+            panic!("render mode expected");
+        };
+        assert_eq!(render.csv_url.as_deref(), Some(url));
+        assert_eq!(
+            render.csv,
+            crate::remote::cache_path(&Path::new("repo").join("dist"), url)
+        );
     }
 
     #[test]
@@ -1114,4 +5758,72 @@ mod tests {
             NaiveDate::from_ymd_opt(2022, 12, 1).expect("valid date")
         );
     }
+
+    #[test]
+    fn forecast_csv_round_trips_into_the_chart_overlay() {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 6).expect("valid date");
+        let forecast = crate::model::Forecast {
+            dates: vec![start, start + chrono::Days::new(7)],
+            mean: vec![116_320.5, 116_410.25],
+            lower: vec![116_100.0, 116_000.0],
+            upper: vec![116_540.0, 116_800.0],
+            variance: vec![12_100.0, 40_000.0],
+            granularity: crate::series::Granularity::Week,
+            training: Some(crate::model::TrainingSpan {
+                from: NaiveDate::from_ymd_opt(2022, 11, 21).expect("valid date"),
+                to: start - chrono::Days::new(7),
+            }),
+        };
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("forecast_overlay.csv");
+        crate::model::write_forecast_csv(&forecast, &path, crate::constants::Precision::default())
+            .expect("forecast should be written");
+        let overlay = super::load_forecast_overlay(&path);
+
+        let overlay = overlay.expect("forecast should load");
+        let expected = super::build_forecast_overlay(&forecast);
+        assert_eq!(overlay.dates, vec!["2025-01-06", "2025-01-13"]);
+        assert_eq!(overlay.mean, expected.mean);
+        assert_eq!(overlay.lower, expected.lower);
+        assert_eq!(overlay.upper, expected.upper);
+        assert_eq!(overlay.granularity, crate::series::Granularity::Week);
+        assert_eq!(overlay.training, forecast.training);
+    }
+
+    #[test]
+    fn previous_forecasts_load_from_archives_for_every_horizon() {
+        let start = NaiveDate::from_ymd_opt(2025, 3, 1).expect("valid date");
+        let forecast = |mean: f64| crate::model::Forecast {
+            dates: vec![start],
+            mean: vec![mean],
+            lower: vec![mean - 100.0],
+            upper: vec![mean + 100.0],
+            variance: vec![2_500.0],
+            granularity: crate::series::Granularity::Day,
+            training: None,
+        };
+        let temp = tempfile::tempdir().expect("temp dir");
+        let dir = temp.path();
+        let primary = dir.join("forecast.csv");
+        let extra = std::num::NonZeroUsize::new(365).expect("non-zero");
+        let extra_csv = super::horizon_csv_path(&primary, extra);
+        let precision = crate::constants::Precision::default();
+        crate::model::write_forecast_csv(&forecast(116_000.0), &primary, precision)
+            .expect("primary");
+        crate::model::write_forecast_csv(&forecast(117_000.0), &extra_csv, precision)
+            .expect("extra");
+        super::archive_and_remove(&[primary.clone(), extra_csv]).expect("archives");
+
+        let previous = super::load_previous_forecasts(&primary, &[extra], true);
+        let missing = super::load_previous_forecasts(&primary, &[extra], false);
+
+        let previous = previous.expect("archived forecasts should load");
+        assert_eq!(previous.len(), 2);
+        assert!((previous[1].mean[0] - 117_000.0).abs() < 1e-9);
+        assert!(missing.is_err());
+        let buckets = crate::series::AreaBuckets::default();
+        let stale = super::stale_forecast(&previous[0], &buckets);
+        assert_eq!(stale.trained_to, start.pred_opt().expect("valid date"));
+        assert_eq!(stale.age_days, 0);
+    }
 }
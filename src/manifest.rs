@@ -0,0 +1,197 @@
+//! Манифест сборки `build.json`: версия и коммит rua, хэши входных данных, время генерации,
+//! объём данных и параметры модели. Та же информация встраивается HTML-комментарием
+//! в каждую страницу, чтобы любой опубликованный артефакт можно было отследить до сборки.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::alerts::AlertMatch;
+use crate::integrity::sha256_hex;
+use crate::model::{Calibration, TrainingSpan};
+
+pub const MANIFEST_FILE_NAME: &str = "build.json";
+const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Хэш коммита исходников; задаётся `build.rs` или переменной окружения при сборке.
+const GIT_COMMIT: Option<&str> = option_env!("RUA_GIT_COMMIT");
+const HTML_COMMENT_PREFIX: &str = "<!-- rua-build ";
+const HTML_COMMENT_SUFFIX: &str = " -->";
+
+#[derive(Debug, Serialize)]
+pub struct BuildManifest {
+    pub version: &'static str,
+    pub git_commit: Option<&'static str>,
+    pub generated_at: DateTime<Utc>,
+    pub mode: String,
+    pub inputs: Vec<InputDigest>,
+    pub rows: RowCounts,
+    pub model: ModelInfo,
+    /// Правила `[[alerts]]`, сработавшие на этой сборке.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alerts: Vec<AlertMatch>,
+    /// Момент `--as-of`, на который закреплены входы.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// Входной файл: имя и SHA-256 содержимого.
+#[derive(Debug, Serialize)]
+pub struct InputDigest {
+    pub file: String,
+    pub sha256: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowCounts {
+    /// Срезы источника в загруженном диапазоне.
+    pub history_snapshots: usize,
+    /// Дни со срезами.
+    pub history_days: usize,
+    /// Дни прогноза.
+    pub forecast_days: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelInfo {
+    pub kind: String,
+    pub hyperparameters: serde_json::Value,
+    /// Множитель конформной калибровки интервалов, если она включена.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<Calibration>,
+    /// Первый и последний день обучающего ряда.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub training: Option<TrainingSpan>,
+}
+
+impl BuildManifest {
+    pub fn new(
+        generated_at: DateTime<Utc>,
+        mode: impl Into<String>,
+        inputs: Vec<InputDigest>,
+        rows: RowCounts,
+        model: ModelInfo,
+    ) -> Self {
+        Self {
+            version: APP_VERSION,
+            git_commit: GIT_COMMIT,
+            generated_at,
+            mode: mode.into(),
+            inputs,
+            rows,
+            model,
+            alerts: Vec::new(),
+            as_of: None,
+        }
+    }
+
+    /// Добавляет сработавшие правила оповещений.
+    pub fn with_alerts(self, alerts: Vec<AlertMatch>) -> Self {
+        Self { alerts, ..self }
+    }
+
+    /// Отмечает, что сборка воспроизводит данные на момент `as_of`.
+    pub fn with_as_of(self, as_of: Option<DateTime<Utc>>) -> Self {
+        Self { as_of, ..self }
+    }
+
+    /// Пишет `build.json` в `output_dir` и возвращает его путь.
+    pub fn write(&self, output_dir: &Path) -> Result<PathBuf, String> {
+        let path = output_dir.join(MANIFEST_FILE_NAME);
+        let raw = serde_json::to_string_pretty(self)
+            .map_err(|err| format!("Failed to serialize build manifest: {err}"))?;
+        fs::write(&path, raw)
+            .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Вставляет манифест комментарием сразу после `<!DOCTYPE html>` страницы `page`.
+    pub fn embed_in_page(&self, page: &Path) -> Result<(), String> {
+        let html = fs::read_to_string(page)
+            .map_err(|err| format!("Failed to read {}: {err}", page.display()))?;
+        let html = self.embed(&html)?;
+        fs::write(page, html).map_err(|err| format!("Failed to write {}: {err}", page.display()))
+    }
+
+    fn embed(&self, html: &str) -> Result<String, String> {
+        // `--` внутри комментария недопустим; в JSON он возможен только в строках,
+        // где `\u002d` — эквивалентная запись дефиса.
+        let json = serde_json::to_string(self)
+            .map_err(|err| format!("Failed to serialize build manifest: {err}"))?
+            .replace("--", "-\\u002d");
+        let comment = format!("{HTML_COMMENT_PREFIX}{json}{HTML_COMMENT_SUFFIX}");
+        let insert_at = if html.starts_with("<!") {
+            html.find('>').map_or(0, |end| end + 1)
+        } else {
+            0
+        };
+        Ok(format!(
+            "{}{comment}{}",
+            &html[..insert_at],
+            &html[insert_at..]
+        ))
+    }
+}
+
+/// Хэширует входные файлы; в манифест попадает только имя файла, без локального пути.
+pub fn hash_inputs(paths: &[&Path]) -> Result<Vec<InputDigest>, String> {
+    paths
+        .iter()
+        .map(|path| {
+            let bytes = fs::read(path)
+                .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+            Ok(InputDigest {
+                file: path.file_name().map_or_else(
+                    || path.display().to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                ),
+                sha256: sha256_hex(&bytes),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::{BuildManifest, InputDigest, ModelInfo, RowCounts};
+
+    #[test]
+    fn manifest_comment_follows_doctype_and_stays_valid_json() {
+        let manifest = BuildManifest::new(
+            DateTime::from_timestamp(1_700_000_000, 0).expect("valid timestamp"),
+            "render",
+            vec![InputDigest {
+                file: "history--v2.csv".to_string(),
+                sha256: "abc".to_string(),
+            }],
+            RowCounts {
+                history_snapshots: 3,
+                history_days: 2,
+                forecast_days: 30,
+            },
+            ModelInfo {
+                kind: "llt".to_string(),
+                hyperparameters: serde_json::json!({ "history": 365 }),
+                calibration: None,
+                training: None,
+            },
+        );
+
+        let page = manifest
+            .embed("<!DOCTYPE html><html></html>")
+            .expect("manifest should embed");
+
+        let comment = page
+            .strip_prefix("<!DOCTYPE html><!-- rua-build ")
+            .and_then(|rest| rest.strip_suffix(" --><html></html>"))
+            .expect("comment should follow the doctype");
+        assert!(!comment.contains("--"));
+        let parsed = serde_json::from_str::<serde_json::Value>(comment).expect("valid json");
+        assert_eq!(parsed["inputs"][0]["file"], "history--v2.csv");
+        assert_eq!(parsed["rows"]["forecast_days"], 30);
+        assert_eq!(parsed["generated_at"], "2023-11-14T22:13:20Z");
+    }
+}
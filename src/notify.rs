@@ -0,0 +1,456 @@
+//! Уведомления о свежих данных: сборка дайджеста и рассылка.
+
+#[cfg(feature = "email")]
+mod email;
+mod webhook;
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use reqwest::Client;
+
+pub use webhook::{DiscordNotifier, SlackNotifier, TelegramNotifier};
+
+use crate::alerts::AlertMatch;
+use crate::report::{ChartSummary, render_line_png};
+
+const DELTA_HORIZONS: [(usize, &str); 5] = [
+    (1, "За сутки"),
+    (7, "За 7 дней"),
+    (30, "За 30 дней"),
+    (90, "За 90 дней"),
+    (365, "За год"),
+];
+const CHART_WIDTH: usize = 800;
+const CHART_HEIGHT: usize = 360;
+const CHART_DAYS: usize = 365;
+const NOTIFICATION_CHANGES_TEMPLATE: &str = "За сутки: {daily} км² · за неделю: {weekly} км²";
+const NOTIFICATION_LINK_TEXT: &str = "Открыть график";
+/// Символы Markdown Discord, которые в тексте экранируются обратной косой чертой.
+const DISCORD_MARKDOWN_CHARS: &[char] = &['\\', '*', '_', '~', '`', '|', '[', ']', '>'];
+#[cfg_attr(not(feature = "email"), allow(dead_code))]
+const MISSING_VALUE: &str = "—";
+/// Файл рядом с HTML с периодом последнего отправленного дайджеста; в публикацию не попадает.
+pub const DIGEST_STATE_FILE_NAME: &str = ".rua-digest";
+
+/// Периодичность рассылки дайджеста.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DigestSchedule {
+    Daily,
+    Weekly(Weekday),
+}
+
+impl DigestSchedule {
+    /// Нужно ли отправлять дайджест в указанный день.
+    pub fn is_due(self, today: NaiveDate) -> bool {
+        match self {
+            Self::Daily => true,
+            Self::Weekly(weekday) => today.weekday() == weekday,
+        }
+    }
+
+    /// Период рассылки, в который попадает день: сам день или ISO-неделя.
+    pub fn period(self, today: NaiveDate) -> String {
+        match self {
+            Self::Daily => today.format("%Y-%m-%d").to_string(),
+            Self::Weekly(_) => {
+                let week = today.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+        }
+    }
+}
+
+/// Файл с периодом последнего дайджеста для страницы `output_html`.
+pub fn digest_state_path(output_html: &Path) -> PathBuf {
+    output_html.with_file_name(DIGEST_STATE_FILE_NAME)
+}
+
+/// Отправлялся ли уже дайджест за `period`; без файла — не отправлялся.
+pub fn digest_sent(state_path: &Path, period: &str) -> bool {
+    fs::read_to_string(state_path).is_ok_and(|sent| sent.trim() == period)
+}
+
+/// Запоминает, что дайджест за `period` отправлен.
+pub fn record_digest_sent(state_path: &Path, period: &str) -> Result<(), String> {
+    fs::write(state_path, format!("{period}\n"))
+        .map_err(|err| format!("Failed to write {}: {err}", state_path.display()))
+}
+
+/// Способ защиты SMTP-соединения.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    StartTls,
+    Tls,
+}
+
+/// Параметры SMTP-рассылки дайджеста.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmailConfig {
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    /// Имя переменной окружения с паролем SMTP (пароль в конфиге не хранится).
+    pub password_env: String,
+    pub from: String,
+    pub to: Vec<String>,
+    pub subject: String,
+    pub schedule: DigestSchedule,
+}
+
+/// Короткое уведомление о свежих данных для мессенджеров.
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub summary: ChartSummary,
+    /// Ссылка на опубликованную страницу.
+    pub link: Option<String>,
+    /// Сработавшие правила `[[alerts]]`; идут в сообщении после прогноза.
+    pub alerts: Vec<AlertMatch>,
+}
+
+/// Канал доставки уведомлений.
+pub trait Notifier {
+    /// Название канала для логов.
+    fn name(&self) -> &'static str;
+
+    /// Отправляет уведомление.
+    async fn send(&self, client: &Client, notification: &Notification) -> Result<(), String>;
+}
+
+/// Один из настроенных каналов уведомлений.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotifierTarget {
+    Telegram(TelegramNotifier),
+    Discord(DiscordNotifier),
+    Slack(SlackNotifier),
+}
+
+impl Notifier for NotifierTarget {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Telegram(notifier) => notifier.name(),
+            Self::Discord(notifier) => notifier.name(),
+            Self::Slack(notifier) => notifier.name(),
+        }
+    }
+
+    async fn send(&self, client: &Client, notification: &Notification) -> Result<(), String> {
+        match self {
+            Self::Telegram(notifier) => notifier.send(client, notification).await,
+            Self::Discord(notifier) => notifier.send(client, notification).await,
+            Self::Slack(notifier) => notifier.send(client, notification).await,
+        }
+    }
+}
+
+/// Параметры рассылки коротких уведомлений.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotificationsConfig {
+    pub link: Option<String>,
+    pub targets: Vec<NotifierTarget>,
+}
+
+/// Диалект разметки получателя: жирный текст и ссылки оформляются по-разному.
+/// Telegram получает HTML (`parse_mode: HTML`): в его Markdown `_` из имени правила вроде
+/// `daily_change` не экранируется, и бот отвечает 400.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Markup {
+    Telegram,
+    Discord,
+    Slack,
+}
+
+impl Markup {
+    /// Текст из конфига и данных как есть, без разметки получателя.
+    fn escape(self, text: &str) -> String {
+        match self {
+            // В mrkdwn Slack экранируются только `&`, `<` и `>`, как в HTML.
+            Self::Telegram | Self::Slack => text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;"),
+            Self::Discord => {
+                let mut escaped = String::with_capacity(text.len());
+                for ch in text.chars() {
+                    if DISCORD_MARKDOWN_CHARS.contains(&ch) {
+                        escaped.push('\\');
+                    }
+                    escaped.push(ch);
+                }
+                escaped
+            }
+        }
+    }
+
+    fn bold(self, text: &str) -> String {
+        let text = self.escape(text);
+        match self {
+            Self::Telegram => format!("<b>{text}</b>"),
+            Self::Slack => format!("*{text}*"),
+            Self::Discord => format!("**{text}**"),
+        }
+    }
+
+    fn link(self, text: &str, url: &str) -> String {
+        let text = self.escape(text);
+        match self {
+            Self::Telegram => format!(
+                "<a href=\"{}\">{text}</a>",
+                self.escape(url).replace('"', "&quot;")
+            ),
+            Self::Discord => format!("[{text}]({url})"),
+            Self::Slack => format!("<{url}|{text}>"),
+        }
+    }
+}
+
+/// Общий текст уведомления: площадь, изменения, прогноз и ссылка.
+pub fn format_notification(notification: &Notification, markup: Markup) -> String {
+    let summary = &notification.summary;
+    let mut text = format!(
+        "{}: {:.1} тыс. км² ({:.2}% Украины)\n{}",
+        markup.bold(&format!("Территория на {}", summary.latest_date)),
+        summary.latest_area_km2,
+        summary.ukraine_percent,
+        summary.render_template(NOTIFICATION_CHANGES_TEMPLATE)
+    );
+    if let Some(forecast) = &summary.forecast {
+        let _ = write!(
+            text,
+            "\nПрогноз на {}: {:.1} тыс. км² (95%: {:.1}–{:.1})",
+            forecast.end_date, forecast.mean_km2, forecast.lower_km2, forecast.upper_km2
+        );
+    }
+    for alert in &notification.alerts {
+        let _ = write!(
+            text,
+            "\n⚠ {}: {}",
+            markup.bold(&alert.rule),
+            markup.escape(&alert.message)
+        );
+    }
+    if let Some(link) = &notification.link {
+        let _ = write!(text, "\n{}", markup.link(NOTIFICATION_LINK_TEXT, link));
+    }
+    text
+}
+
+/// Строка таблицы изменений за период.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeltaRow {
+    pub label: &'static str,
+    pub days: usize,
+    /// Изменение площади в км² (нет значения, если ряд короче периода).
+    pub change_km2: Option<f64>,
+}
+
+/// Дайджест с ключевыми показателями, таблицей изменений и PNG-графиком.
+#[cfg_attr(not(feature = "email"), allow(dead_code))]
+#[derive(Clone, Debug)]
+pub struct Digest {
+    pub summary: ChartSummary,
+    pub deltas: Vec<DeltaRow>,
+    pub chart_png: Vec<u8>,
+}
+
+impl Digest {
+    /// Собирает дайджест по сводке страницы и дневному ряду площадей (км²).
+    pub fn build(summary: &ChartSummary, occupied_km2: &[f64]) -> Result<Self, String> {
+        let deltas = build_delta_rows(occupied_km2);
+        let chart_window = &occupied_km2[occupied_km2.len().saturating_sub(CHART_DAYS)..];
+        let chart_png = render_line_png(chart_window, CHART_WIDTH, CHART_HEIGHT)
+            .map_err(|err| format!("Failed to render digest chart: {err}"))?;
+        Ok(Self {
+            summary: summary.clone(),
+            deltas,
+            chart_png,
+        })
+    }
+
+    /// Текстовая версия дайджеста.
+    #[cfg_attr(not(feature = "email"), allow(dead_code))]
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Территория на {}: {:.1} тыс. км² ({:.2}% Украины)\n",
+            self.summary.latest_date, self.summary.latest_area_km2, self.summary.ukraine_percent
+        );
+        if let Some(forecast) = &self.summary.forecast {
+            let _ = writeln!(
+                text,
+                "Прогноз на {}: {:.1} тыс. км² (95%: {:.1}–{:.1})",
+                forecast.end_date, forecast.mean_km2, forecast.lower_km2, forecast.upper_km2
+            );
+        }
+        text.push('\n');
+        for row in &self.deltas {
+            let _ = writeln!(text, "{}: {}", row.label, format_delta(row.change_km2));
+        }
+        text
+    }
+
+    /// HTML-версия дайджеста с таблицей изменений.
+    #[cfg(feature = "email")]
+    pub fn to_html(&self) -> String {
+        maud::html! {
+            p {
+                "Территория на " (self.summary.latest_date) ": "
+                strong { (format!("{:.1} тыс. км²", self.summary.latest_area_km2)) }
+                (format!(" ({:.2}% Украины)", self.summary.ukraine_percent))
+            }
+            @if let Some(forecast) = &self.summary.forecast {
+                p {
+                    (format!(
+                        "Прогноз на {}: {:.1} тыс. км² (95%: {:.1}–{:.1})",
+                        forecast.end_date, forecast.mean_km2, forecast.lower_km2, forecast.upper_km2
+                    ))
+                }
+            }
+            table cellpadding="4" {
+                thead { tr { th align="left" { "Период" } th align="right" { "Изменение" } } }
+                tbody {
+                    @for row in &self.deltas {
+                        tr {
+                            td { (row.label) }
+                            td align="right" { (format_delta(row.change_km2)) }
+                        }
+                    }
+                }
+            }
+        }
+        .into_string()
+    }
+}
+
+/// Отправляет дайджест по SMTP.
+#[cfg(feature = "email")]
+pub async fn send_email_digest(config: &EmailConfig, digest: &Digest) -> Result<(), String> {
+    email::send(config, digest).await
+}
+
+/// Заглушка для сборки без фичи `email`.
+#[cfg(not(feature = "email"))]
+#[allow(clippy::unused_async)]
+pub async fn send_email_digest(_config: &EmailConfig, _digest: &Digest) -> Result<(), String> {
+    Err("rua was built without the `email` feature".to_string())
+}
+
+fn build_delta_rows(occupied_km2: &[f64]) -> Vec<DeltaRow> {
+    DELTA_HORIZONS
+        .iter()
+        .map(|(days, label)| DeltaRow {
+            label,
+            days: *days,
+            change_km2: occupied_km2
+                .len()
+                .checked_sub(days + 1)
+                .and_then(|start| Some(occupied_km2.last()? - occupied_km2[start])),
+        })
+        .collect()
+}
+
+#[cfg_attr(not(feature = "email"), allow(dead_code))]
+fn format_delta(value: Option<f64>) -> String {
+    value.map_or_else(|| MISSING_VALUE.to_string(), |val| format!("{val:+.0} км²"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DigestSchedule, Markup, Notification, build_delta_rows, digest_sent, digest_state_path,
+        format_notification, record_digest_sent,
+    };
+    use crate::alerts::AlertMatch;
+    use crate::report::ChartSummary;
+    use chrono::{NaiveDate, Weekday};
+
+    fn sample_notification() -> Notification {
+        Notification {
+            summary: ChartSummary {
+                latest_date: "2025-01-02".to_string(),
+                latest_area_km2: 112.3,
+                ukraine_percent: 18.61,
+                daily_change_km2: Some(12.0),
+                weekly_change_km2: None,
+                forecast: None,
+                forecast_horizons: Vec::new(),
+                extremes: None,
+            },
+            link: Some("https://example.org/".to_string()),
+            alerts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn notification_uses_target_markup() {
+        let notification = sample_notification();
+        let slack = format_notification(&notification, Markup::Slack);
+        assert_eq!(
+            slack,
+            "*Территория на 2025-01-02*: 112.3 тыс. км² (18.61% Украины)\n\
+             За сутки: +12 км² · за неделю: — км²\n\
+             <https://example.org/|Открыть график>"
+        );
+        let discord = format_notification(&notification, Markup::Discord);
+        assert!(discord.starts_with("**Территория на 2025-01-02**"));
+        assert!(discord.ends_with("[Открыть график](https://example.org/)"));
+    }
+
+    #[test]
+    fn notification_escapes_rule_names_and_messages() {
+        let notification = Notification {
+            link: Some("https://example.org/?from=1&to=2".to_string()),
+            alerts: vec![AlertMatch {
+                rule: "daily_change".to_string(),
+                message: "rise > 100 km² *today*".to_string(),
+            }],
+            ..sample_notification()
+        };
+        let telegram = format_notification(&notification, Markup::Telegram);
+        assert!(telegram.starts_with("<b>Территория на 2025-01-02</b>"));
+        assert!(telegram.contains("\n⚠ <b>daily_change</b>: rise &gt; 100 km² *today*\n"));
+        assert!(
+            telegram
+                .ends_with("<a href=\"https://example.org/?from=1&amp;to=2\">Открыть график</a>")
+        );
+        let discord = format_notification(&notification, Markup::Discord);
+        assert!(discord.contains("⚠ **daily\\_change**: rise \\> 100 km² \\*today\\*"));
+    }
+
+    #[test]
+    fn delta_rows_skip_horizons_longer_than_series() {
+        let values = (0..10).map(f64::from).collect::<Vec<_>>();
+        let rows = build_delta_rows(&values);
+        assert_eq!(rows[0].change_km2, Some(1.0));
+        assert_eq!(rows[1].change_km2, Some(7.0));
+        assert_eq!(rows[2].change_km2, None);
+    }
+
+    #[test]
+    fn weekly_schedule_is_due_only_on_configured_weekday() {
+        let monday = NaiveDate::from_ymd_opt(2025, 1, 6).expect("valid date");
+        let tuesday = NaiveDate::from_ymd_opt(2025, 1, 7).expect("valid date");
+        let schedule = DigestSchedule::Weekly(Weekday::Mon);
+        assert!(schedule.is_due(monday));
+        assert!(!schedule.is_due(tuesday));
+        assert!(DigestSchedule::Daily.is_due(tuesday));
+    }
+
+    #[test]
+    fn digest_is_sent_once_per_period() {
+        let monday = NaiveDate::from_ymd_opt(2025, 1, 6).expect("valid date");
+        let next_monday = NaiveDate::from_ymd_opt(2025, 1, 13).expect("valid date");
+        let schedule = DigestSchedule::Weekly(Weekday::Mon);
+        assert_eq!(schedule.period(monday), "2025-W02");
+        assert_eq!(DigestSchedule::Daily.period(monday), "2025-01-06");
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let state = digest_state_path(&temp.path().join("index.html"));
+        assert!(!digest_sent(&state, &schedule.period(monday)));
+        record_digest_sent(&state, &schedule.period(monday)).expect("state is written");
+        assert!(digest_sent(&state, &schedule.period(monday)));
+        assert!(!digest_sent(&state, &schedule.period(next_monday)));
+    }
+}
@@ -0,0 +1,67 @@
+//! Отправка дайджеста по SMTP через `lettre`.
+
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use super::{Digest, EmailConfig, SmtpSecurity};
+
+const CHART_ATTACHMENT_NAME: &str = "chart.png";
+const CHART_CONTENT_TYPE: &str = "image/png";
+
+pub(super) async fn send(config: &EmailConfig, digest: &Digest) -> Result<(), String> {
+    let from: Mailbox = config
+        .from
+        .parse()
+        .map_err(|err| format!("Invalid email.from '{}': {err}", config.from))?;
+    let mut builder = Message::builder()
+        .from(from)
+        .subject(digest.summary.render_template(&config.subject));
+    for recipient in &config.to {
+        let mailbox: Mailbox = recipient
+            .parse()
+            .map_err(|err| format!("Invalid email.to '{recipient}': {err}"))?;
+        builder = builder.to(mailbox);
+    }
+
+    let content_type = ContentType::parse(CHART_CONTENT_TYPE)
+        .map_err(|err| format!("Invalid attachment content type: {err}"))?;
+    let attachment = Attachment::new(CHART_ATTACHMENT_NAME.to_string())
+        .body(digest.chart_png.clone(), content_type);
+    let message = builder
+        .multipart(
+            MultiPart::mixed()
+                .multipart(MultiPart::alternative_plain_html(
+                    digest.to_text(),
+                    digest.to_html(),
+                ))
+                .singlepart(attachment),
+        )
+        .map_err(|err| format!("Failed to build digest email: {err}"))?;
+
+    let relay = match config.security {
+        SmtpSecurity::StartTls => {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)
+        }
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.smtp_host),
+    }
+    .map_err(|err| format!("Invalid SMTP host '{}': {err}", config.smtp_host))?;
+    let mut transport = relay.port(config.smtp_port);
+    if let Some(username) = &config.username {
+        let password = std::env::var(&config.password_env).map_err(|err| {
+            format!(
+                "SMTP password variable {} is not set: {err}",
+                config.password_env
+            )
+        })?;
+        transport = transport.credentials(Credentials::new(username.clone(), password));
+    }
+
+    transport
+        .build()
+        .send(message)
+        .await
+        .map_err(|err| format!("Failed to send digest email: {err}"))?;
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! Короткие уведомления через Telegram Bot API и входящие вебхуки Discord/Slack.
+
+use reqwest::Client;
+use serde_json::json;
+
+use super::{Markup, Notification, Notifier, format_notification};
+
+const TELEGRAM_API_BASE: &str = "https://api.telegram.org";
+const TELEGRAM_PARSE_MODE: &str = "HTML";
+
+/// Сообщение в чат или канал через Telegram-бота.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TelegramNotifier {
+    /// `chat_id` или `@username` канала.
+    pub chat_id: String,
+    /// Имя переменной окружения с токеном бота.
+    pub token_env: String,
+}
+
+/// Сообщение во входящий вебхук Discord.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiscordNotifier {
+    /// Имя переменной окружения с URL вебхука.
+    pub webhook_env: String,
+}
+
+/// Сообщение во входящий вебхук Slack.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlackNotifier {
+    /// Имя переменной окружения с URL вебхука.
+    pub webhook_env: String,
+}
+
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &'static str {
+        "Telegram"
+    }
+
+    async fn send(&self, client: &Client, notification: &Notification) -> Result<(), String> {
+        let token = read_secret(&self.token_env)?;
+        let payload = json!({
+            "chat_id": self.chat_id,
+            "text": format_notification(notification, Markup::Telegram),
+            "parse_mode": TELEGRAM_PARSE_MODE,
+            "disable_web_page_preview": true,
+        });
+        post_json(
+            client,
+            &format!("{TELEGRAM_API_BASE}/bot{token}/sendMessage"),
+            &payload,
+        )
+        .await
+    }
+}
+
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> &'static str {
+        "Discord"
+    }
+
+    async fn send(&self, client: &Client, notification: &Notification) -> Result<(), String> {
+        let url = read_secret(&self.webhook_env)?;
+        let payload = json!({ "content": format_notification(notification, Markup::Discord) });
+        post_json(client, &url, &payload).await
+    }
+}
+
+impl Notifier for SlackNotifier {
+    fn name(&self) -> &'static str {
+        "Slack"
+    }
+
+    async fn send(&self, client: &Client, notification: &Notification) -> Result<(), String> {
+        let url = read_secret(&self.webhook_env)?;
+        let payload = json!({
+            "text": format_notification(notification, Markup::Slack),
+            "mrkdwn": true,
+        });
+        post_json(client, &url, &payload).await
+    }
+}
+
+fn read_secret(env_name: &str) -> Result<String, String> {
+    std::env::var(env_name).map_err(|err| format!("Variable {env_name} is not set: {err}"))
+}
+
+/// Отправляет JSON и не раскрывает URL в ошибке: в нём может быть токен.
+#[cfg(feature = "notify")]
+async fn post_json(client: &Client, url: &str, payload: &serde_json::Value) -> Result<(), String> {
+    let response = client
+        .post(url)
+        .json(payload)
+        .send()
+        .await
+        .map_err(|err| format!("Request failed: {}", err.without_url()))?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        let body = response.text().await.unwrap_or_default();
+        Err(format!("Unexpected status {status}: {}", body.trim()))
+    }
+}
+
+/// Заглушка для сборки без фичи `notify`.
+#[cfg(not(feature = "notify"))]
+#[allow(clippy::unused_async)]
+async fn post_json(
+    _client: &Client,
+    _url: &str,
+    _payload: &serde_json::Value,
+) -> Result<(), String> {
+    Err("rua was built without the `notify` feature".to_string())
+}
@@ -0,0 +1,281 @@
+//! Публикация собранного сайта в ветку `gh-pages` через локальный git worktree.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cache;
+use crate::instance;
+use crate::notify;
+use crate::remote;
+use crate::report::ChartSummary;
+use crate::series;
+use crate::store;
+use crate::updates;
+
+const GIT_BIN: &str = "git";
+const GIT_DIR_NAME: &str = ".git";
+const NOJEKYLL_FILE: &str = ".nojekyll";
+
+/// Параметры публикации в ветку GitHub Pages.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GhPagesConfig {
+    /// Путь к worktree, в который коммитится содержимое сайта.
+    pub worktree: PathBuf,
+    /// Имя ветки публикации.
+    pub branch: String,
+    /// Шаблон сообщения коммита с плейсхолдерами `{date}`, `{area}`, `{percent}`, `{daily}`, `{weekly}`, `{forecast}`.
+    pub commit_message: String,
+    /// Пушить ли ветку после коммита.
+    pub push: bool,
+    /// Remote для `git push`.
+    pub remote: String,
+}
+
+/// Результат публикации.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PublishOutcome {
+    Committed { message: String, pushed: bool },
+    Unchanged,
+}
+
+/// Синхронизирует `site_dir` в worktree ветки публикации и коммитит изменения.
+pub fn publish_to_gh_pages(
+    site_dir: &Path,
+    config: &GhPagesConfig,
+    summary: &ChartSummary,
+) -> Result<PublishOutcome, String> {
+    if config.worktree.starts_with(site_dir) {
+        return Err(format!(
+            "gh-pages worktree {} must not be inside site directory {}",
+            config.worktree.display(),
+            site_dir.display()
+        ));
+    }
+    ensure_worktree(&config.worktree, &config.branch)?;
+    sync_site_dir(site_dir, &config.worktree)?;
+    fs::write(config.worktree.join(NOJEKYLL_FILE), "")
+        .map_err(|err| format!("Failed to write {NOJEKYLL_FILE}: {err}"))?;
+
+    run_git(&config.worktree, &["add", "--all"])?;
+    let status = run_git(&config.worktree, &["status", "--porcelain"])?;
+    if status.trim().is_empty() {
+        return Ok(PublishOutcome::Unchanged);
+    }
+
+    let message = summary.render_template(&config.commit_message);
+    run_git(&config.worktree, &["commit", "--quiet", "-m", &message])?;
+    if config.push {
+        run_git(
+            &config.worktree,
+            &["push", &config.remote, &format!("HEAD:{}", config.branch)],
+        )?;
+    }
+    Ok(PublishOutcome::Committed {
+        message,
+        pushed: config.push,
+    })
+}
+
+fn ensure_worktree(worktree: &Path, branch: &str) -> Result<(), String> {
+    if worktree.join(GIT_DIR_NAME).exists() {
+        return Ok(());
+    }
+    let parent = worktree
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(parent)
+        .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    let worktree_arg = worktree.to_string_lossy();
+    let branch_ref = format!("refs/heads/{branch}");
+    if run_git(parent, &["rev-parse", "--verify", "--quiet", &branch_ref]).is_ok() {
+        run_git(parent, &["worktree", "add", &worktree_arg, branch])?;
+    } else {
+        // Ветки ещё нет: создаём пустой orphan-коммит без истории основной ветки.
+        run_git(parent, &["worktree", "add", "--detach", &worktree_arg])?;
+        run_git(worktree, &["checkout", "--quiet", "--orphan", branch])?;
+        run_git(
+            worktree,
+            &["rm", "-r", "--quiet", "--cached", "--ignore-unmatch", "."],
+        )?;
+    }
+    Ok(())
+}
+
+/// Заменяет содержимое worktree (кроме `.git`) файлами сайта.
+fn sync_site_dir(site_dir: &Path, worktree: &Path) -> Result<(), String> {
+    let entries = fs::read_dir(worktree)
+        .map_err(|err| format!("Failed to read {}: {err}", worktree.display()))?;
+    for entry in entries {
+        let entry = entry.map_err(|err| format!("Failed to read worktree entry: {err}"))?;
+        if entry.file_name() == GIT_DIR_NAME {
+            continue;
+        }
+        let path = entry.path();
+        let result = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        result.map_err(|err| format!("Failed to remove {}: {err}", path.display()))?;
+    }
+    copy_dir_recursive(site_dir, worktree)
+}
+
+fn copy_dir_recursive(source: &Path, target: &Path) -> Result<(), String> {
+    fs::create_dir_all(target)
+        .map_err(|err| format!("Failed to create {}: {err}", target.display()))?;
+    let entries = fs::read_dir(source)
+        .map_err(|err| format!("Failed to read {}: {err}", source.display()))?;
+    for entry in entries {
+        let entry = entry.map_err(|err| format!("Failed to read site entry: {err}"))?;
+        if entry.file_name() == cache::MANIFEST_FILE_NAME
+            || entry.file_name() == instance::LOCK_FILE_NAME
+            || entry.file_name() == updates::LOG_FILE_NAME
+            || entry.file_name() == notify::DIGEST_STATE_FILE_NAME
+            || entry.file_name() == remote::CACHE_DIR_NAME
+            || [series::SERIES_CACHE_SUFFIX, store::LOCK_SUFFIX]
+                .iter()
+                .any(|suffix| entry.file_name().to_string_lossy().ends_with(suffix))
+        {
+            continue;
+        }
+        let from = entry.path();
+        let to = target.join(entry.file_name());
+        if from.is_dir() {
+            copy_dir_recursive(&from, &to)?;
+        } else {
+            fs::copy(&from, &to).map_err(|err| {
+                format!(
+                    "Failed to copy {} to {}: {err}",
+                    from.display(),
+                    to.display()
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(GIT_BIN)
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|err| format!("Failed to run git {}: {err}", args.join(" ")))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::{GhPagesConfig, PublishOutcome, publish_to_gh_pages, run_git};
+    use crate::report::ChartSummary;
+
+    fn summary() -> ChartSummary {
+        ChartSummary {
+            latest_date: "2025-01-02".to_string(),
+            latest_area_km2: 112.3,
+            ukraine_percent: 18.61,
+            daily_change_km2: Some(12.0),
+            weekly_change_km2: None,
+            forecast: None,
+            forecast_horizons: Vec::new(),
+            extremes: None,
+        }
+    }
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().expect("file has a parent")).expect("parent dir");
+        fs::write(path, contents).expect("file should be written");
+    }
+
+    #[test]
+    fn publishes_site_into_orphan_branch_and_tracks_removals() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let repo = temp.path().join("repo");
+        let site = temp.path().join("site");
+        fs::create_dir_all(&repo).expect("repo dir");
+        for args in [
+            &["init", "--quiet", "--initial-branch", "main"][..],
+            &["config", "user.name", "rua"],
+            &["config", "user.email", "rua@example.org"],
+            &["config", "commit.gpgsign", "false"],
+        ] {
+            run_git(&repo, args).expect("repo should be configured");
+        }
+        write(&repo.join("src.txt"), "main branch");
+        run_git(&repo, &["add", "--all"]).expect("add");
+        run_git(&repo, &["commit", "--quiet", "-m", "main"]).expect("main commit");
+
+        write(&site.join("index.html"), "<html></html>");
+        write(&site.join("assets/app.js"), "console.log(1);");
+        // Служебные файлы сборки остаются в каталоге сайта, но не публикуются.
+        write(&site.join(crate::cache::MANIFEST_FILE_NAME), "cache");
+        write(
+            &site.join(crate::notify::DIGEST_STATE_FILE_NAME),
+            "2025-01-02",
+        );
+        write(
+            &site.join(format!("history.csv{}", crate::store::LOCK_SUFFIX)),
+            "",
+        );
+        write(
+            &site.join(crate::remote::CACHE_DIR_NAME).join("remote.csv"),
+            "date,area",
+        );
+
+        let worktree = repo.join("gh-pages");
+        let config = GhPagesConfig {
+            worktree: worktree.clone(),
+            branch: "gh-pages".to_string(),
+            commit_message: "Update {date}".to_string(),
+            push: false,
+            remote: "origin".to_string(),
+        };
+        let tracked = || {
+            let mut files = run_git(&worktree, &["ls-files"])
+                .expect("ls-files")
+                .lines()
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+
+        let first = publish_to_gh_pages(&site, &config, &summary()).expect("first publish");
+        assert_eq!(
+            first,
+            PublishOutcome::Committed {
+                message: "Update 2025-01-02".to_string(),
+                pushed: false,
+            }
+        );
+        // Orphan-ветка: один коммит без истории и файлов основной ветки.
+        let commits = run_git(&worktree, &["rev-list", "--count", "gh-pages"]).expect("rev-list");
+        assert_eq!(commits.trim(), "1");
+        assert_eq!(tracked(), [".nojekyll", "assets/app.js", "index.html"]);
+
+        let second = publish_to_gh_pages(&site, &config, &summary()).expect("second publish");
+        assert_eq!(second, PublishOutcome::Unchanged);
+
+        fs::remove_dir_all(site.join("assets")).expect("stale assets removed");
+        write(&site.join("about.html"), "<html></html>");
+        let third = publish_to_gh_pages(&site, &config, &summary()).expect("third publish");
+        assert!(matches!(third, PublishOutcome::Committed { .. }));
+        assert_eq!(tracked(), [".nojekyll", "about.html", "index.html"]);
+        assert!(!worktree.join("assets").exists());
+    }
+}
@@ -0,0 +1,209 @@
+//! Пересборка истории из архива сырых ответов (`rua rebuild-history`): срезы из архива
+//! заново проходят текущий разбор и заменяют свои строки в CSV, а отчёт показывает, что
+//! изменилось относительно старой истории. Срезы, которых в архиве нет, и ручные точки
+//! остаются как были, поэтому неполный архив не теряет историю.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::data::Area;
+use crate::series::MANUAL_HASH;
+
+/// Расхождение площади меньше этого порога (км²) — погрешность записи в CSV.
+const AREA_EPSILON_KM2: f64 = 1e-3;
+/// Сколько изменённых срезов перечислять в отчёте поимённо.
+const LISTED_CHANGES: usize = 20;
+
+/// Что изменилось в одном срезе.
+#[derive(Debug, PartialEq)]
+pub struct ChangedSnapshot {
+    pub time: DateTime<Utc>,
+    pub old_rows: usize,
+    pub new_rows: usize,
+    /// Слои с другой суммарной площадью: (слой, было, стало), км².
+    pub layers: Vec<(String, f64, f64)>,
+}
+
+/// Отличия пересобранной истории от старой.
+#[derive(Debug, Default, PartialEq)]
+pub struct HistoryDiff {
+    /// Срезы архива, которых не было в старой истории.
+    pub added: Vec<DateTime<Utc>>,
+    /// Срезы старой истории, которых нет в архиве: оставлены без изменений.
+    pub kept: Vec<DateTime<Utc>>,
+    pub changed: Vec<ChangedSnapshot>,
+    pub unchanged: usize,
+}
+
+/// Собирает новую историю: срезы `replayed` вместо своих строк в `old`, остальное из `old`.
+pub fn rebuild(old: Vec<Area>, replayed: Vec<Area>) -> (Vec<Area>, HistoryDiff) {
+    let archived = replayed
+        .iter()
+        .map(|area| area.time_index)
+        .collect::<BTreeSet<_>>();
+    let (old, manual): (Vec<_>, Vec<_>) =
+        old.into_iter().partition(|area| area.hash != MANUAL_HASH);
+    let old_rows = group(&old);
+    let new_rows = group(&replayed);
+
+    let mut diff = HistoryDiff::default();
+    for (time, rows) in &new_rows {
+        let Some(previous) = old_rows.get(time) else {
+            diff.added.push(*time);
+            continue;
+        };
+        let layers = changed_layers(previous, rows);
+        if layers.is_empty() && rows_match(previous, rows) {
+            diff.unchanged += 1;
+        } else {
+            diff.changed.push(ChangedSnapshot {
+                time: *time,
+                old_rows: previous.len(),
+                new_rows: rows.len(),
+                layers,
+            });
+        }
+    }
+    diff.kept = old_rows
+        .keys()
+        .filter(|time| !archived.contains(time))
+        .copied()
+        .collect();
+
+    let mut areas = replayed;
+    areas.extend(
+        old.into_iter()
+            .chain(manual)
+            .filter(|area| !archived.contains(&area.time_index)),
+    );
+    areas.sort_by_key(|area| area.time_index);
+    (areas, diff)
+}
+
+impl HistoryDiff {
+    /// Отчёт для консоли: итоги и первые изменённые срезы.
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Без изменений: {}; изменено: {}; новых: {}; нет в архиве (оставлены): {}",
+            self.unchanged,
+            self.changed.len(),
+            self.added.len(),
+            self.kept.len()
+        );
+        for change in self.changed.iter().take(LISTED_CHANGES) {
+            let _ = write!(
+                text,
+                "\n~ {}: строк {} → {}",
+                timestamp(change.time),
+                change.old_rows,
+                change.new_rows
+            );
+            for (layer, old, new) in &change.layers {
+                let _ = write!(text, "; {layer}: {old:.1} → {new:.1} км²");
+            }
+        }
+        if self.changed.len() > LISTED_CHANGES {
+            let _ = write!(
+                text,
+                "\n… и ещё {} изменённых срезов",
+                self.changed.len() - LISTED_CHANGES
+            );
+        }
+        for time in self.added.iter().take(LISTED_CHANGES) {
+            let _ = write!(text, "\n+ {}", timestamp(*time));
+        }
+        text
+    }
+}
+
+/// Строки по срезам: время → отсортированные (слой, цвет, площадь).
+fn group(areas: &[Area]) -> BTreeMap<DateTime<Utc>, Vec<(&str, &str, f64)>> {
+    let mut grouped = BTreeMap::<_, Vec<_>>::new();
+    for area in areas {
+        grouped.entry(area.time_index).or_default().push((
+            area.area_type.as_str(),
+            area.hash.as_str(),
+            area.area,
+        ));
+    }
+    for rows in grouped.values_mut() {
+        rows.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)).then(a.2.total_cmp(&b.2)));
+    }
+    grouped
+}
+
+fn rows_match(old: &[(&str, &str, f64)], new: &[(&str, &str, f64)]) -> bool {
+    old.len() == new.len()
+        && old
+            .iter()
+            .zip(new)
+            .all(|(a, b)| a.0 == b.0 && a.1 == b.1 && (a.2 - b.2).abs() < AREA_EPSILON_KM2)
+}
+
+fn changed_layers(old: &[(&str, &str, f64)], new: &[(&str, &str, f64)]) -> Vec<(String, f64, f64)> {
+    let totals = |rows: &[(&str, &str, f64)]| {
+        let mut totals = BTreeMap::<String, f64>::new();
+        for (layer, _, area) in rows {
+            *totals.entry((*layer).to_string()).or_default() += area;
+        }
+        totals
+    };
+    let (old, new) = (totals(old), totals(new));
+    old.keys()
+        .chain(new.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|layer| {
+            let before = old.get(layer).copied().unwrap_or_default();
+            let after = new.get(layer).copied().unwrap_or_default();
+            ((after - before).abs() >= AREA_EPSILON_KM2).then(|| (layer.clone(), before, after))
+        })
+        .collect()
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rebuild;
+    use crate::data::Area;
+    use crate::data::test_support::{area_row, day as at};
+
+    #[test]
+    fn rebuild_replaces_archived_snapshots_and_keeps_the_rest() {
+        let old = vec![
+            area_row(1, 100.0),
+            area_row(2, 110.0),
+            area_row(3, 120.0),
+            Area::manual(at(4).date_naive(), 130.0, "occupied_after_24_02_2022"),
+        ];
+        let replayed = vec![
+            area_row(2, 110.0),
+            area_row(3, 118.0),
+            Area::new(at(3), "#880e4f", 5.0, "occupied_to_24_02_2022"),
+            area_row(5, 140.0),
+        ];
+
+        let (areas, diff) = rebuild(old, replayed);
+
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.added, [at(5)]);
+        assert_eq!(diff.kept, [at(1)]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].time, at(3));
+        assert_eq!((diff.changed[0].old_rows, diff.changed[0].new_rows), (1, 2));
+        assert_eq!(diff.changed[0].layers.len(), 2);
+        assert_eq!(
+            areas.iter().map(|area| area.area).collect::<Vec<_>>(),
+            [100.0, 110.0, 118.0, 5.0, 130.0, 140.0]
+        );
+        assert!(
+            diff.to_text()
+                .starts_with("Без изменений: 1; изменено: 1; новых: 1")
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! История по адресу `http(s)://` для сборок только с отчётом (`mode = "render"`).
+//!
+//! CSV скачивается общим клиентом в `.rua-remote/` рядом с выходным HTML и дальше читается как
+//! обычный файл, вместе с двоичным кэшем рядов. Рядом с копией хранится её `ETag`: следующая
+//! сборка спрашивает сервер условным запросом и не скачивает неизменившийся файл заново. Если
+//! сервер недоступен, отчёт строится по последней копии с предупреждением.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use flate2::read::GzDecoder;
+use reqwest::Client;
+
+use crate::fetch::{self, Conditional, FetchConfig};
+use crate::integrity::sha256_hex;
+
+/// Каталог копий в каталоге сайта; в публикацию не попадает.
+pub const CACHE_DIR_NAME: &str = ".rua-remote";
+const ETAG_EXTENSION: &str = "etag";
+
+/// Задан ли источник адресом `http://` или `https://`, а не путём.
+pub fn is_url(source: &str) -> bool {
+    ["http://", "https://"].iter().any(|scheme| {
+        source
+            .get(..scheme.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+    })
+}
+
+/// Локальная копия удалённого CSV: имя — начало хэша адреса, поэтому разные адреса не
+/// перетирают друг друга.
+pub fn cache_path(site_dir: &Path, url: &str) -> PathBuf {
+    site_dir
+        .join(CACHE_DIR_NAME)
+        .join(format!("{}.csv", &sha256_hex(url.as_bytes())[..16]))
+}
+
+/// Обновляет копию `url` в `cache_path`, если файл на сервере изменился; `.csv.gz`
+/// распаковывается. Без сети остаётся прежняя копия, а без копии — ошибка.
+pub async fn refresh(
+    client: &Client,
+    fetch_config: &FetchConfig,
+    url: &str,
+    cache_path: &Path,
+) -> Result<(), String> {
+    let etag_path = cache_path.with_extension(ETAG_EXTENSION);
+    let etag = cache_path
+        .exists()
+        .then(|| fs::read_to_string(&etag_path).ok())
+        .flatten()
+        .filter(|etag| !etag.is_empty());
+    let deadline = fetch_config
+        .deadline
+        .map(|budget| tokio::time::Instant::now() + budget);
+    match fetch::fetch_if_changed(client, url, fetch_config, deadline, etag.as_deref()).await {
+        Ok(Conditional::NotModified) => {
+            tracing::info!(url, "Remote history is unchanged; using the cached copy");
+            // Время изменения — время последнего использования: по нему `rua gc` удаляет копии.
+            let _ = fs::File::options()
+                .write(true)
+                .open(cache_path)
+                .and_then(|file| file.set_modified(SystemTime::now()));
+            Ok(())
+        }
+        Ok(Conditional::Modified { body, etag }) => {
+            let gzipped = Path::new(url)
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("gz"));
+            let csv = if gzipped {
+                let mut decoded = Vec::new();
+                GzDecoder::new(body.as_ref())
+                    .read_to_end(&mut decoded)
+                    .map_err(|err| format!("Failed to decompress {url}: {err}"))?;
+                decoded
+            } else {
+                body.to_vec()
+            };
+            if let Some(parent) = cache_path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+            }
+            fs::write(cache_path, csv)
+                .map_err(|err| format!("Failed to write {}: {err}", cache_path.display()))?;
+            // Пустой файл — сервер не прислал `ETag`, и копия скачивается каждый раз.
+            fs::write(&etag_path, etag.unwrap_or_default())
+                .map_err(|err| format!("Failed to write {}: {err}", etag_path.display()))?;
+            tracing::info!(url, cache = %cache_path.display(), "Downloaded remote history");
+            Ok(())
+        }
+        Err(err) if cache_path.exists() => {
+            tracing::warn!(url, error = %err, "Failed to refresh remote history; using the cached copy");
+            Ok(())
+        }
+        Err(err) => Err(format!("Failed to download history {url}: {err}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CACHE_DIR_NAME, cache_path, is_url, refresh};
+    use crate::fetch::FetchConfig;
+
+    #[tokio::test]
+    async fn remote_history_falls_back_to_the_cached_copy() {
+        assert!(is_url("https://example.org/history.csv"));
+        assert!(is_url("HTTP://example.org/history.csv"));
+        assert!(!is_url("dist/history.csv"));
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let dir = temp.path();
+        // Недоступный адрес: запрос гарантированно не проходит.
+        let url = "http://127.0.0.1:9/history.csv";
+        let path = cache_path(dir, url);
+        assert!(path.starts_with(dir.join(CACHE_DIR_NAME)));
+        assert_ne!(path, cache_path(dir, "http://127.0.0.1:9/other.csv"));
+
+        let config = FetchConfig {
+            max_retries: 1,
+            ..FetchConfig::default()
+        };
+        let client = reqwest::Client::new();
+        let missing = refresh(&client, &config, url, &path).await;
+        std::fs::create_dir_all(path.parent().expect("cache dir")).expect("cache dir");
+        std::fs::write(&path, "time_index,hash,area,percent,area_type\n").expect("cached copy");
+        let cached = refresh(&client, &config, url, &path).await;
+        assert!(missing.expect_err("no copy to fall back to").contains(url));
+        assert!(cached.is_ok());
+    }
+}
@@ -1,103 +0,0 @@
-//! Статические данные для таблиц в отчёте.
-
-pub(super) const UNFRIENDLY_COUNTRIES: &[(&str, f64, &str)] = &[
-    ("Австралия", 7_692_024.0, "au"),
-    ("Албания", 28_748.0, "al"),
-    ("Андорра", 468.0, "ad"),
-    ("Багамские Острова", 13_943.0, "bs"),
-    ("Великобритания", 244_376.0, "gb"),
-    ("Австрия", 83_871.0, "at"),
-    ("Бельгия", 30_528.0, "be"),
-    ("Болгария", 110_879.0, "bg"),
-    ("Германия", 357_114.0, "de"),
-    ("Греция", 131_990.0, "gr"),
-    ("Дания", 43_094.0, "dk"),
-    ("Ирландия", 70_273.0, "ie"),
-    ("Испания", 505_992.0, "es"),
-    ("Италия", 301_336.0, "it"),
-    ("Республика Кипр", 9251.0, "cy"),
-    ("Латвия", 64_559.0, "lv"),
-    ("Литва", 65_300.0, "lt"),
-    ("Люксембург", 2586.0, "lu"),
-    ("Мальта", 316.0, "mt"),
-    ("Нидерланды", 41_865.0, "nl"),
-    ("Польша", 312_679.0, "pl"),
-    ("Португалия", 92_090.0, "pt"),
-    ("Румыния", 238_391.0, "ro"),
-    ("Словения", 20_273.0, "si"),
-    ("Финляндия", 338_455.0, "fi"),
-    ("Франция", 543_908.0, "fr"),
-    ("Хорватия", 56_594.0, "hr"),
-    ("Чехия", 78_865.0, "cz"),
-    ("Швеция", 450_295.0, "se"),
-    ("Эстония", 45_227.0, "ee"),
-    ("Исландия", 103_000.0, "is"),
-    ("Канада", 9_984_670.0, "ca"),
-    ("Лихтенштейн", 160.0, "li"),
-    ("Микронезия", 702.0, "fm"),
-    ("Монако", 2.02, "mc"),
-    ("Новая Зеландия", 268_838.0, "nz"),
-    ("Норвегия", 386_224.0, "no"),
-    ("Республика Корея", 100_210.0, "kr"),
-    ("Сан-Марино", 61.0, "sm"),
-    ("Северная Македония", 25_713.0, "mk"),
-    ("Сингапур", 710.0, "sg"),
-    ("Соединённые Штаты Америки", 9_525_067.0, "us"),
-    ("Китайская Республика", 36_197.0, "tw"),
-    ("Черногория", 13_812.0, "me"),
-    ("Швейцария", 41_284.0, "ch"),
-    ("Япония", 377_930.0, "jp"),
-];
-
-pub(super) const US_STATES: &[(&str, f64, &str)] = &[
-    ("Алабама", 135_767.0, "us-al"),
-    ("Аляска", 1_723_337.0, "us-ak"),
-    ("Аризона", 295_234.0, "us-az"),
-    ("Арканзас", 137_732.0, "us-ar"),
-    ("Калифорния", 423_967.0, "us-ca"),
-    ("Колорадо", 269_601.0, "us-co"),
-    ("Коннектикут", 14_357.0, "us-ct"),
-    ("Делавэр", 6446.0, "us-de"),
-    ("Флорида", 170_312.0, "us-fl"),
-    ("Джорджия", 153_910.0, "us-ga"),
-    ("Гавайи", 28_313.0, "us-hi"),
-    ("Айдахо", 216_443.0, "us-id"),
-    ("Иллинойс", 149_995.0, "us-il"),
-    ("Индиана", 94_326.0, "us-in"),
-    ("Айова", 145_746.0, "us-ia"),
-    ("Канзас", 213_100.0, "us-ks"),
-    ("Кентукки", 104_656.0, "us-ky"),
-    ("Луизиана", 135_659.0, "us-la"),
-    ("Мэн", 91_633.0, "us-me"),
-    ("Мэриленд", 32_131.0, "us-md"),
-    ("Массачусетс", 27_336.0, "us-ma"),
-    ("Мичиган", 250_487.0, "us-mi"),
-    ("Миннесота", 225_163.0, "us-mn"),
-    ("Миссисипи", 125_438.0, "us-ms"),
-    ("Миссури", 180_540.0, "us-mo"),
-    ("Монтана", 380_831.0, "us-mt"),
-    ("Небраска", 200_330.0, "us-ne"),
-    ("Невада", 286_380.0, "us-nv"),
-    ("Нью-Гэмпшир", 24_214.0, "us-nh"),
-    ("Нью-Джерси", 22_591.0, "us-nj"),
-    ("Нью-Мексико", 314_917.0, "us-nm"),
-    ("Нью-Йорк", 141_297.0, "us-ny"),
-    ("Северная Каролина", 139_391.0, "us-nc"),
-    ("Северная Дакота", 183_108.0, "us-nd"),
-    ("Огайо", 116_098.0, "us-oh"),
-    ("Оклахома", 181_037.0, "us-ok"),
-    ("Орегон", 254_799.0, "us-or"),
-    ("Пенсильвания", 119_280.0, "us-pa"),
-    ("Род-Айленд", 4001.0, "us-ri"),
-    ("Южная Каролина", 82_933.0, "us-sc"),
-    ("Южная Дакота", 199_729.0, "us-sd"),
-    ("Теннесси", 109_153.0, "us-tn"),
-    ("Техас", 695_662.0, "us-tx"),
-    ("Юта", 219_882.0, "us-ut"),
-    ("Вермонт", 24_906.0, "us-vt"),
-    ("Виргиния", 110_787.0, "us-va"),
-    ("Вашингтон", 184_661.0, "us-wa"),
-    ("Западная Виргиния", 62_756.0, "us-wv"),
-    ("Висконсин", 169_635.0, "us-wi"),
-    ("Вайоминг", 253_335.0, "us-wy"),
-];
@@ -0,0 +1,255 @@
+//! Локальные копии шрифтов (`[page].fonts = "self-hosted"`) и флагов (`[page].self_host_assets`).
+//!
+//! Google Fonts и flagcdn открываются не во всех регионах, поэтому при сборке их файлы
+//! скачиваются в `assets/fonts/` и `assets/flags/` рядом с HTML, а страница ссылается на
+//! копии. Скачанные файлы остаются между сборками и заново не запрашиваются: стили шрифтов
+//! лежат под хэшем исходного адреса, флаги — под своим кодом.
+
+use std::fs;
+use std::path::Path;
+
+use futures::{StreamExt, TryStreamExt, stream};
+use reqwest::Client;
+use tracing::info;
+
+use crate::fetch::{self, FetchConfig};
+use crate::integrity::sha256_hex;
+use crate::report::{FontStrategy, PageConfig};
+
+/// Каталоги копий относительно страницы.
+const FONTS_DIR: &str = "assets/fonts";
+const FLAGS_DIR: &str = "assets/flags";
+const HASH_LEN: usize = 8;
+const DOWNLOAD_CONCURRENCY: usize = 8;
+
+/// Скачивает недостающие шрифты и флаги в `output_dir` и возвращает оформление со ссылками
+/// на копии; копируется только то, что выбрано в конфиге. При ошибке флаги остаются на CDN,
+/// а страница без копии шрифтов показывается системными.
+pub async fn self_host_assets(
+    client: &Client,
+    fetch_config: &FetchConfig,
+    output_dir: &Path,
+    page: &PageConfig,
+) -> Result<PageConfig, String> {
+    let fonts_css_url = if page.fonts == FontStrategy::SelfHosted {
+        self_host_fonts(client, fetch_config, output_dir, &page.fonts_css_url).await?
+    } else {
+        page.fonts_css_url.clone()
+    };
+    let flag_base_url = if page.self_host_assets {
+        self_host_flags(client, fetch_config, output_dir, page).await?
+    } else {
+        page.flag_base_url.clone()
+    };
+    Ok(PageConfig {
+        fonts_css_url,
+        flag_base_url,
+        ..page.clone()
+    })
+}
+
+/// Стили шрифтов с `url(...)`, переписанными на файлы рядом со стилями.
+async fn self_host_fonts(
+    client: &Client,
+    fetch_config: &FetchConfig,
+    output_dir: &Path,
+    css_url: &str,
+) -> Result<String, String> {
+    let dir = output_dir.join(FONTS_DIR);
+    let css_name = format!("{}.css", &sha256_hex(css_url.as_bytes())[..HASH_LEN]);
+    let href = format!("{FONTS_DIR}/{css_name}");
+    let css_path = dir.join(&css_name);
+    if let Ok(css) = fs::read_to_string(&css_path)
+        && local_font_files(&css).all(|file| dir.join(file).is_file())
+    {
+        return Ok(href);
+    }
+
+    let css = download(client, fetch_config, css_url).await?;
+    let css = String::from_utf8(css).map_err(|err| format!("Fonts CSS is not UTF-8: {err}"))?;
+    let (rewritten, fonts) = rewrite_font_urls(&css);
+    create_dir(&dir)?;
+    let downloaded = stream::iter(
+        fonts
+            .into_iter()
+            .filter(|(_, file)| !dir.join(file).is_file()),
+    )
+    .map(|(url, file)| {
+        let path = dir.join(file);
+        async move { save(&path, &download(client, fetch_config, &url).await?) }
+    })
+    .buffer_unordered(DOWNLOAD_CONCURRENCY)
+    .try_collect::<Vec<()>>()
+    .await?
+    .len();
+    save(&css_path, rewritten.as_bytes())?;
+    info!(css = %href, downloaded, "Saved self-hosted fonts");
+    Ok(href)
+}
+
+/// Флаги всех строк таблиц соотношений; уже скачанные пропускаются.
+async fn self_host_flags(
+    client: &Client,
+    fetch_config: &FetchConfig,
+    output_dir: &Path,
+    page: &PageConfig,
+) -> Result<String, String> {
+    let dir = output_dir.join(FLAGS_DIR);
+    let base_url = &page.flag_base_url;
+    let missing = page
+        .flag_codes()
+        .into_iter()
+        .filter(|code| !dir.join(format!("{code}.png")).is_file())
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        create_dir(&dir)?;
+        stream::iter(missing.iter())
+            .map(|code| {
+                let path = dir.join(format!("{code}.png"));
+                let url = format!("{base_url}{code}.png");
+                async move { save(&path, &download(client, fetch_config, &url).await?) }
+            })
+            .buffer_unordered(DOWNLOAD_CONCURRENCY)
+            .try_collect::<Vec<()>>()
+            .await?;
+        info!(downloaded = missing.len(), "Saved self-hosted flags");
+    }
+    Ok(format!("{FLAGS_DIR}/"))
+}
+
+/// Заменяет адреса в `url(...)` на имена файлов и возвращает пары (адрес, файл) для загрузки.
+fn rewrite_font_urls(css: &str) -> (String, Vec<(String, String)>) {
+    let mut rewritten = String::with_capacity(css.len());
+    let mut fonts = Vec::new();
+    let mut rest = css;
+    while let Some(start) = rest.find("url(") {
+        let (head, tail) = rest.split_at(start + "url(".len());
+        rewritten.push_str(head);
+        let Some(end) = tail.find(')') else {
+            rest = tail;
+            break;
+        };
+        let url = tail[..end].trim().trim_matches(['"', '\'']);
+        let file = font_file_name(url);
+        rewritten.push_str(&file);
+        if !fonts
+            .iter()
+            .any(|(known, _): &(String, String)| known == url)
+        {
+            fonts.push((url.to_string(), file));
+        }
+        rest = &tail[end..];
+    }
+    rewritten.push_str(rest);
+    (rewritten, fonts)
+}
+
+/// Имя файла шрифта: хэш адреса и расширение последнего сегмента пути.
+fn font_file_name(url: &str) -> String {
+    let extension = url
+        .rsplit('/')
+        .next()
+        .and_then(|segment| segment.split(['?', '#']).next())
+        .and_then(|segment| segment.rsplit_once('.'))
+        .map(|(_, extension)| extension)
+        .filter(|extension| extension.bytes().all(|byte| byte.is_ascii_alphanumeric()))
+        .unwrap_or("font");
+    format!(
+        "{}.{extension}",
+        &sha256_hex(url.as_bytes())[..2 * HASH_LEN]
+    )
+}
+
+/// Файлы шрифтов, на которые ссылаются уже переписанные стили.
+fn local_font_files(css: &str) -> impl Iterator<Item = &str> {
+    css.split("url(")
+        .skip(1)
+        .filter_map(|tail| tail.split(')').next())
+        .map(str::trim)
+}
+
+async fn download(
+    client: &Client,
+    fetch_config: &FetchConfig,
+    url: &str,
+) -> Result<Vec<u8>, String> {
+    fetch::fetch_bytes(client, url, fetch_config, None)
+        .await
+        .map(|bytes| bytes.to_vec())
+        .map_err(|err| format!("Failed to download {url}: {err}"))
+}
+
+fn save(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    fs::write(path, bytes).map_err(|err| format!("Failed to write {}: {err}", path.display()))
+}
+
+fn create_dir(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|err| format!("Failed to create {}: {err}", dir.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FLAGS_DIR, FONTS_DIR, local_font_files, rewrite_font_urls, self_host_assets};
+    use crate::fetch::FetchConfig;
+    use crate::report::PageConfig;
+
+    #[test]
+    fn font_urls_are_rewritten_to_local_files() {
+        let css = "@font-face { src: url(https://fonts.gstatic.com/s/plex/a.woff2) format('woff2'); }\n\
+                   @font-face { src: url('https://fonts.gstatic.com/s/plex/a.woff2'); }";
+        let (rewritten, fonts) = rewrite_font_urls(css);
+        assert_eq!(fonts.len(), 1);
+        assert_eq!(fonts[0].0, "https://fonts.gstatic.com/s/plex/a.woff2");
+        assert_eq!(
+            std::path::Path::new(&fonts[0].1).extension(),
+            Some("woff2".as_ref())
+        );
+        assert!(!rewritten.contains("gstatic"));
+        assert_eq!(
+            local_font_files(&rewritten).collect::<Vec<_>>(),
+            [fonts[0].1.as_str(), fonts[0].1.as_str()]
+        );
+    }
+
+    #[tokio::test]
+    async fn cached_copies_are_used_without_network() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let dir = temp.path();
+        let page = PageConfig {
+            // Недоступный адрес: любой запрос к сети провалил бы тест.
+            fonts_css_url: "http://127.0.0.1:9/fonts.css".to_string(),
+            flag_base_url: "http://127.0.0.1:9/flags/".to_string(),
+            self_host_assets: true,
+            ..PageConfig::default()
+        };
+        let fonts = dir.join(FONTS_DIR);
+        let flags = dir.join(FLAGS_DIR);
+        std::fs::create_dir_all(&fonts).expect("fonts dir");
+        std::fs::create_dir_all(&flags).expect("flags dir");
+        let css = format!(
+            "{}.css",
+            &crate::integrity::sha256_hex(page.fonts_css_url.as_bytes())[..8]
+        );
+        std::fs::write(fonts.join(&css), "src: url(plex.woff2)").expect("css");
+        std::fs::write(fonts.join("plex.woff2"), "font").expect("font");
+        for code in page.flag_codes() {
+            std::fs::write(flags.join(format!("{code}.png")), "png").expect("flag");
+        }
+
+        let config = FetchConfig {
+            max_retries: 1,
+            ..FetchConfig::default()
+        };
+        let client = reqwest::Client::new();
+        let hosted = self_host_assets(&client, &config, dir, &page).await;
+        let hosted = hosted.expect("cached copies need no downloads");
+        assert_eq!(hosted.fonts_css_url, format!("assets/fonts/{css}"));
+        assert_eq!(hosted.flag_base_url, "assets/flags/");
+        assert_eq!(
+            hosted.fonts_stylesheet(),
+            Some(hosted.fonts_css_url.as_str())
+        );
+        // Без копии `self-hosted` не ссылается на Google: остаются системные шрифты.
+        assert_eq!(page.fonts_stylesheet(), None);
+    }
+}
@@ -0,0 +1,958 @@
+//! HTTP-сервис: статический сайт из `dist`, JSON-эндпоинты `/v1/*` для программного доступа
+//! и поток server-sent events `/v1/events` о новых данных.
+
+use std::convert::Infallible;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use bytes::Bytes;
+use chrono::{DateTime, Datelike, NaiveDate, SecondsFormat, Utc};
+use futures::{Stream, StreamExt, stream};
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Empty, Full, StreamBody};
+use hyper::body::{Body, Frame};
+use hyper::header::{self, HeaderValue};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::ServeConfig;
+use crate::constants::{self, DATE_FORMAT, Precision, round_to};
+use crate::integrity::sha256_hex;
+use crate::model::{self, Forecast};
+use crate::series::{self, DateRange, InterpolationStrategy, Snapshot};
+use crate::source::{DeepStateSource, Fetcher, MapSource};
+use crate::store::{CsvStore, DataStore};
+
+const INDEX_FILE: &str = "index.html";
+const WEEKLY_CHANGE_DAYS: usize = 7;
+const CONTENT_TYPE_JSON: &str = "application/json";
+const CONTENT_TYPE_EVENT_STREAM: &str = "text/event-stream";
+const EVENTS_PATH: &str = "/v1/events";
+/// Интервал комментариев-пингов в потоке событий, чтобы прокси не закрывали простаивающее соединение.
+const EVENTS_KEEPALIVE: Duration = Duration::from_secs(30);
+
+/// Шаг агрегации ряда в `/v1/series`: для недели и месяца берётся последнее значение периода.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Granularity {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Granularity {
+    fn parse(raw: &str) -> Result<Self, String> {
+        match raw {
+            "daily" => Ok(Self::Daily),
+            "weekly" => Ok(Self::Weekly),
+            "monthly" => Ok(Self::Monthly),
+            other => Err(format!(
+                "unknown granularity `{other}` (expected daily, weekly or monthly)"
+            )),
+        }
+    }
+
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    fn period(self, date: NaiveDate) -> (i32, u32) {
+        match self {
+            Self::Daily => (date.year(), date.ordinal()),
+            Self::Weekly => {
+                let week = date.iso_week();
+                (week.year(), week.week())
+            }
+            Self::Monthly => (date.year(), date.month()),
+        }
+    }
+}
+
+/// Данные, из которых отвечает API.
+#[derive(Clone)]
+struct Dataset {
+    dates: Vec<NaiveDate>,
+    occupied_km2: Vec<f64>,
+    forecast: Option<Forecast>,
+    /// Время последнего среза в CSV истории.
+    latest_time: Option<DateTime<Utc>>,
+    /// Срез с карты, которого ещё нет в истории; на графике помечается как предварительный.
+    provisional: Option<Snapshot>,
+    precision: Precision,
+}
+
+impl Dataset {
+    fn load(
+        config: &ServeConfig,
+        range: DateRange,
+        interpolation: InterpolationStrategy,
+    ) -> Result<Self, String> {
+        let buckets = CsvStore::new(&config.csv).load_range(range)?;
+        let (dates, occupied_km2) = series::build_occupied_series_with(&buckets, interpolation)
+            .map_err(|err| {
+                format!(
+                    "Failed to build series from {}: {err}",
+                    config.csv.display()
+                )
+            })?;
+        let forecast = match model::read_forecast_csv(&config.forecast_csv) {
+            Ok(forecast) => Some(forecast),
+            Err(err) => {
+                warn!(
+                    path = %config.forecast_csv.display(),
+                    error = %err,
+                    "Forecast CSV is unavailable, /v1/forecast will return 404"
+                );
+                None
+            }
+        };
+        Ok(Self {
+            dates,
+            occupied_km2,
+            forecast,
+            latest_time: buckets.latest_snapshot_time(),
+            provisional: None,
+            precision: config.precision,
+        })
+    }
+
+    fn area(&self, km2: f64) -> f64 {
+        round_to(km2, self.precision.area_km2)
+    }
+
+    /// Срез с карты новее истории?
+    fn is_ahead(&self, snapshot: &Snapshot) -> bool {
+        self.latest_time.is_none_or(|latest| snapshot.time > latest)
+    }
+
+    /// Новая версия данных с предварительной точкой, если срез новее истории и ещё не показан.
+    fn with_provisional(&self, snapshot: Snapshot) -> Option<Self> {
+        if !self.is_ahead(&snapshot) || self.provisional == Some(snapshot) {
+            return None;
+        }
+        let mut next = self.clone();
+        next.provisional = Some(snapshot);
+        Some(next)
+    }
+}
+
+#[derive(Serialize)]
+struct SeriesPoint {
+    date: String,
+    occupied_km2: f64,
+}
+
+#[derive(Serialize)]
+struct SeriesResponse {
+    granularity: &'static str,
+    points: Vec<SeriesPoint>,
+}
+
+#[derive(Serialize)]
+struct ForecastPoint {
+    date: String,
+    mean_km2: f64,
+    lower_km2: f64,
+    upper_km2: f64,
+}
+
+#[derive(Serialize)]
+struct ForecastResponse {
+    horizon_days: usize,
+    points: Vec<ForecastPoint>,
+}
+
+/// Предварительное значение из последнего среза карты, ещё не попавшего в историю.
+#[derive(Serialize)]
+struct ProvisionalPoint {
+    time: String,
+    date: String,
+    occupied_km2: f64,
+}
+
+#[derive(Serialize)]
+struct SummaryResponse {
+    latest_date: String,
+    occupied_km2: f64,
+    ukraine_percent: f64,
+    daily_change_km2: Option<f64>,
+    weekly_change_km2: Option<f64>,
+    forecast: Option<ForecastPoint>,
+    provisional: Option<ProvisionalPoint>,
+}
+
+/// Событие `update`: свежая сводка и дни, появившиеся после предыдущей версии данных.
+#[derive(Serialize)]
+struct UpdateEvent {
+    summary: SummaryResponse,
+    points: Vec<SeriesPoint>,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// Ошибка запроса к API с HTTP-статусом ответа.
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message: message.into(),
+        }
+    }
+}
+
+type ResponseBody = BoxBody<Bytes, Infallible>;
+
+/// Загружает данные и обслуживает запросы, пока процесс не будет остановлен.
+pub async fn serve(
+    config: ServeConfig,
+    range: DateRange,
+    interpolation: InterpolationStrategy,
+    fetcher: Fetcher,
+) -> Result<(), String> {
+    let (updates, receiver) =
+        watch::channel(Arc::new(Dataset::load(&config, range, interpolation)?));
+    let config = Arc::new(config);
+    if let Some(interval) = config.reload_interval {
+        tokio::spawn(watch_history(
+            Arc::clone(&config),
+            range,
+            interpolation,
+            interval,
+            updates.clone(),
+        ));
+    }
+    if let Some(interval) = config.live_interval {
+        tokio::spawn(watch_latest(
+            DeepStateSource::default(),
+            fetcher,
+            interval,
+            updates.clone(),
+        ));
+    }
+    let listener = TcpListener::bind(config.bind)
+        .await
+        .map_err(|err| format!("Failed to bind {}: {err}", config.bind))?;
+    info!(bind = %config.bind, site_dir = %config.site_dir.display(), "Serving");
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                warn!(error = %err, "Failed to accept connection");
+                continue;
+            }
+        };
+        let receiver = receiver.clone();
+        let config = Arc::clone(&config);
+        tokio::spawn(async move {
+            let service = service_fn(move |request| {
+                let receiver = receiver.clone();
+                let config = Arc::clone(&config);
+                async move { Ok::<_, Infallible>(handle(&receiver, &config, &request).await) }
+            });
+            if let Err(err) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                warn!(peer = %peer, error = %err, "Connection failed");
+            }
+        });
+    }
+}
+
+/// Время изменения CSV истории и прогноза: признак того, что данные пора перечитать.
+fn modified_times(config: &ServeConfig) -> (Option<SystemTime>, Option<SystemTime>) {
+    let modified = |path: &Path| {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    };
+    (modified(&config.csv), modified(&config.forecast_csv))
+}
+
+/// Периодически проверяет CSV и публикует новую версию данных подписчикам.
+async fn watch_history(
+    config: Arc<ServeConfig>,
+    range: DateRange,
+    interpolation: InterpolationStrategy,
+    interval: Duration,
+    updates: watch::Sender<Arc<Dataset>>,
+) {
+    let mut seen = modified_times(&config);
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let current = modified_times(&config);
+        if current == seen {
+            continue;
+        }
+        seen = current;
+        match Dataset::load(&config, range, interpolation) {
+            Ok(mut dataset) => {
+                info!("History changed, publishing update");
+                // Предварительная точка остаётся, пока срез не появится в истории.
+                dataset.provisional = updates
+                    .borrow()
+                    .provisional
+                    .filter(|snapshot| dataset.is_ahead(snapshot));
+                updates.send_replace(Arc::new(dataset));
+            }
+            Err(err) => warn!(error = %err, "Failed to reload history, keeping previous data"),
+        }
+    }
+}
+
+/// Периодически запрашивает последний срез карты и публикует его как предварительную точку.
+async fn watch_latest<S: MapSource>(
+    source: S,
+    fetcher: Fetcher,
+    interval: Duration,
+    updates: watch::Sender<Arc<Dataset>>,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let latest = match source.fetch_latest(&fetcher).await {
+            Ok(latest) => latest,
+            Err(err) => {
+                warn!(error = %err, "Failed to fetch the latest snapshot");
+                continue;
+            }
+        };
+        let Some(snapshot) = series::snapshot_from_areas(&latest.areas) else {
+            warn!(time = %latest.time, "Latest snapshot has no occupied areas, skipping");
+            continue;
+        };
+        let next = updates.borrow().with_provisional(snapshot);
+        if let Some(next) = next {
+            info!(
+                time = %snapshot.time,
+                occupied_km2 = snapshot.occupied,
+                "New map snapshot ahead of history, publishing provisional value"
+            );
+            updates.send_replace(Arc::new(next));
+        }
+    }
+}
+
+/// Отвечает на запрос. HEAD обрабатывается как GET, но без тела: заголовки и `Content-Length`
+/// те же, что у GET.
+async fn handle<B: Sync>(
+    updates: &watch::Receiver<Arc<Dataset>>,
+    config: &ServeConfig,
+    request: &Request<B>,
+) -> Response<ResponseBody> {
+    match *request.method() {
+        Method::GET => route(updates, config, request).await,
+        Method::HEAD => without_body(route(updates, config, request).await),
+        ref method => {
+            let mut response = error_response(&ApiError {
+                status: StatusCode::METHOD_NOT_ALLOWED,
+                message: format!("method {method} is not allowed"),
+            });
+            response
+                .headers_mut()
+                .insert(header::ALLOW, HeaderValue::from_static("GET, HEAD"));
+            response.map(BodyExt::boxed)
+        }
+    }
+}
+
+fn without_body(response: Response<ResponseBody>) -> Response<ResponseBody> {
+    let (mut parts, body) = response.into_parts();
+    if let Some(length) = body.size_hint().exact() {
+        parts
+            .headers
+            .insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+    }
+    Response::from_parts(parts, Empty::new().boxed())
+}
+
+async fn route<B: Sync>(
+    updates: &watch::Receiver<Arc<Dataset>>,
+    config: &ServeConfig,
+    request: &Request<B>,
+) -> Response<ResponseBody> {
+    let path = request.uri().path();
+    if path == EVENTS_PATH {
+        return events_response(updates.clone());
+    }
+    let dataset = Arc::clone(&updates.borrow());
+    if path.starts_with("/v1/") {
+        let if_none_match = request
+            .headers()
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok());
+        return api_response(
+            &dataset,
+            path,
+            request.uri().query().unwrap_or_default(),
+            if_none_match,
+            config.cache_max_age,
+        )
+        .map(BodyExt::boxed);
+    }
+    static_response(&config.site_dir, path)
+        .await
+        .map(BodyExt::boxed)
+}
+
+/// Поток `text/event-stream`: событие `update` на каждую новую версию данных.
+fn events_response(updates: watch::Receiver<Arc<Dataset>>) -> Response<ResponseBody> {
+    let body = StreamBody::new(event_stream(updates).map(|chunk| Ok(Frame::data(chunk))));
+    let mut response = Response::new(BodyExt::boxed(body));
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(CONTENT_TYPE_EVENT_STREAM),
+    );
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    response
+}
+
+fn event_stream(mut updates: watch::Receiver<Arc<Dataset>>) -> impl Stream<Item = Bytes> {
+    let last = Arc::clone(&updates.borrow_and_update());
+    stream::unfold((updates, last), |(mut updates, last)| async move {
+        tokio::select! {
+            changed = updates.changed() => {
+                changed.ok()?;
+                let current = Arc::clone(&updates.borrow_and_update());
+                let event = update_event(&last, &current);
+                Some((Bytes::from(event), (updates, current)))
+            }
+            () = tokio::time::sleep(EVENTS_KEEPALIVE) => {
+                Some((Bytes::from_static(b": keepalive\n\n"), (updates, last)))
+            }
+        }
+    })
+}
+
+/// Кадр SSE `update` с днями, которых не было в предыдущей версии данных.
+fn update_event(previous: &Dataset, current: &Dataset) -> String {
+    let last_sent = previous.dates.last().copied();
+    let points = current
+        .dates
+        .iter()
+        .zip(&current.occupied_km2)
+        .filter(|(date, _)| last_sent.is_none_or(|last| **date > last))
+        .map(|(date, &occupied_km2)| SeriesPoint {
+            date: date.format(DATE_FORMAT).to_string(),
+            occupied_km2: current.area(occupied_km2),
+        })
+        .collect();
+    let Some(summary) = summary(current) else {
+        return String::new();
+    };
+    serde_json::to_string(&UpdateEvent { summary, points })
+        .map(|data| format!("event: update\ndata: {data}\n\n"))
+        .unwrap_or_default()
+}
+
+/// Отвечает на запрос к `/v1/*`; тело сопровождается `ETag` и `Cache-Control`.
+fn api_response(
+    dataset: &Dataset,
+    path: &str,
+    query: &str,
+    if_none_match: Option<&str>,
+    cache_max_age: Duration,
+) -> Response<Full<Bytes>> {
+    let body = match path {
+        "/v1/series" => series_body(dataset, query),
+        "/v1/forecast" => forecast_body(dataset, query),
+        "/v1/summary" => summary_body(dataset),
+        other => Err(ApiError::not_found(format!("unknown endpoint {other}"))),
+    };
+    let body = match body {
+        Ok(body) => body,
+        Err(err) => return error_response(&err),
+    };
+
+    let etag = format!("\"{}\"", sha256_hex(&body));
+    let cache_control = format!("public, max-age={}", cache_max_age.as_secs());
+    let mut response = if if_none_match.is_some_and(|tags| etag_matches(tags, &etag)) {
+        let mut response = Response::new(Full::default());
+        *response.status_mut() = StatusCode::NOT_MODIFIED;
+        response
+    } else {
+        Response::new(Full::new(Bytes::from(body)))
+    };
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(CONTENT_TYPE_JSON),
+    );
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        headers.insert(header::ETAG, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&cache_control) {
+        headers.insert(header::CACHE_CONTROL, value);
+    }
+    response
+}
+
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// Простенький разбор строки запроса: значения в API не требуют percent-декодирования.
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find_map(|(key, value)| (key == name && !value.is_empty()).then_some(value))
+}
+
+fn parse_date_param(query: &str, name: &str) -> Result<Option<NaiveDate>, ApiError> {
+    query_param(query, name)
+        .map(|raw| {
+            NaiveDate::parse_from_str(raw, DATE_FORMAT)
+                .map_err(|err| ApiError::bad_request(format!("invalid {name} `{raw}`: {err}")))
+        })
+        .transpose()
+}
+
+fn to_json(value: &impl Serialize) -> Result<Vec<u8>, ApiError> {
+    serde_json::to_vec(value).map_err(|err| ApiError {
+        status: StatusCode::INTERNAL_SERVER_ERROR,
+        message: err.to_string(),
+    })
+}
+
+fn series_body(dataset: &Dataset, query: &str) -> Result<Vec<u8>, ApiError> {
+    let range = DateRange {
+        from: parse_date_param(query, "from")?,
+        to: parse_date_param(query, "to")?,
+        as_of: None,
+    };
+    let granularity = query_param(query, "granularity")
+        .map_or(Ok(Granularity::Daily), Granularity::parse)
+        .map_err(ApiError::bad_request)?;
+
+    let mut points: Vec<(NaiveDate, f64)> = Vec::new();
+    for (&date, &value) in dataset.dates.iter().zip(&dataset.occupied_km2) {
+        if !range.contains(date) {
+            continue;
+        }
+        match points.last_mut() {
+            Some(last) if granularity.period(last.0) == granularity.period(date) => {
+                *last = (date, value);
+            }
+            _ => points.push((date, value)),
+        }
+    }
+    to_json(&SeriesResponse {
+        granularity: granularity.as_str(),
+        points: points
+            .into_iter()
+            .map(|(date, occupied_km2)| SeriesPoint {
+                date: date.format(DATE_FORMAT).to_string(),
+                occupied_km2: dataset.area(occupied_km2),
+            })
+            .collect(),
+    })
+}
+
+fn forecast_point(dataset: &Dataset, forecast: &Forecast, idx: usize) -> ForecastPoint {
+    ForecastPoint {
+        date: forecast.dates[idx].format(DATE_FORMAT).to_string(),
+        mean_km2: dataset.area(forecast.mean[idx]),
+        lower_km2: dataset.area(forecast.lower[idx]),
+        upper_km2: dataset.area(forecast.upper[idx]),
+    }
+}
+
+fn forecast_body(dataset: &Dataset, query: &str) -> Result<Vec<u8>, ApiError> {
+    let forecast = dataset
+        .forecast
+        .as_ref()
+        .ok_or_else(|| ApiError::not_found("forecast is not available"))?;
+    let horizon = query_param(query, "horizon")
+        .map(|raw| {
+            raw.parse::<usize>()
+                .ok()
+                .filter(|horizon| *horizon > 0)
+                .ok_or_else(|| {
+                    ApiError::bad_request(format!("invalid horizon `{raw}`: expected days > 0"))
+                })
+        })
+        .transpose()?
+        .map_or(forecast.dates.len(), |horizon| {
+            horizon.min(forecast.dates.len())
+        });
+    to_json(&ForecastResponse {
+        horizon_days: horizon,
+        points: (0..horizon)
+            .map(|idx| forecast_point(dataset, forecast, idx))
+            .collect(),
+    })
+}
+
+fn summary_body(dataset: &Dataset) -> Result<Vec<u8>, ApiError> {
+    let summary = summary(dataset).ok_or_else(|| ApiError::not_found("history is empty"))?;
+    to_json(&summary)
+}
+
+fn summary(dataset: &Dataset) -> Option<SummaryResponse> {
+    let (Some(latest_date), Some(&latest)) = (dataset.dates.last(), dataset.occupied_km2.last())
+    else {
+        return None;
+    };
+    let change_over = |days: usize| {
+        dataset.occupied_km2.len().checked_sub(days + 1).map(|idx| {
+            round_to(
+                latest - dataset.occupied_km2[idx],
+                dataset.precision.rate_km2_per_day,
+            )
+        })
+    };
+    Some(SummaryResponse {
+        latest_date: latest_date.format(DATE_FORMAT).to_string(),
+        occupied_km2: dataset.area(latest),
+        ukraine_percent: round_to(
+            constants::ukraine_percent(latest),
+            dataset.precision.percent,
+        ),
+        daily_change_km2: change_over(1),
+        weekly_change_km2: change_over(WEEKLY_CHANGE_DAYS),
+        forecast: dataset
+            .forecast
+            .as_ref()
+            .map(|forecast| forecast_point(dataset, forecast, forecast.dates.len() - 1)),
+        provisional: dataset.provisional.map(|snapshot| ProvisionalPoint {
+            time: snapshot.time.to_rfc3339_opts(SecondsFormat::Secs, true),
+            date: snapshot.time.date_naive().format(DATE_FORMAT).to_string(),
+            occupied_km2: dataset.area(snapshot.occupied),
+        }),
+    })
+}
+
+fn error_response(err: &ApiError) -> Response<Full<Bytes>> {
+    let body = serde_json::to_vec(&ErrorResponse {
+        error: err.message.clone(),
+    })
+    .unwrap_or_default();
+    let mut response = Response::new(Full::new(Bytes::from(body)));
+    *response.status_mut() = err.status;
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(CONTENT_TYPE_JSON),
+    );
+    response
+}
+
+/// Путь к файлу сайта; `None` для путей, выходящих за пределы каталога, и для скрытых файлов
+/// и каталогов (блокировки, кэш сборки, копии удалённых историй), которые не публикуются.
+fn site_file(site_dir: &Path, request_path: &str) -> Option<PathBuf> {
+    let relative = Path::new(request_path.trim_start_matches('/'));
+    if relative.components().any(|component| match component {
+        Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+        _ => true,
+    }) {
+        return None;
+    }
+    let path = site_dir.join(relative);
+    Some(
+        if request_path.ends_with('/') || relative.as_os_str().is_empty() {
+            path.join(INDEX_FILE)
+        } else {
+            path
+        },
+    )
+}
+
+fn content_type_for(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("csv") => "text/csv; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("json") => CONTENT_TYPE_JSON,
+        Some("png") => "image/png",
+        Some("svg") => "image/svg+xml",
+        Some("woff2") => "font/woff2",
+        Some("woff") => "font/woff",
+        Some("ttf") => "font/ttf",
+        Some("ics") => "text/calendar; charset=utf-8",
+        Some("wasm") => "application/wasm",
+        Some("gz") => "application/gzip",
+        Some("sha256" | "minisig" | "txt") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+async fn static_response(site_dir: &Path, request_path: &str) -> Response<Full<Bytes>> {
+    let not_found = || error_response(&ApiError::not_found(format!("{request_path} not found")));
+    let Some(path) = site_file(site_dir, request_path) else {
+        return not_found();
+    };
+    tokio::fs::read(&path).await.map_or_else(
+        |_| not_found(),
+        |content| {
+            let mut response = Response::new(Full::new(Bytes::from(content)));
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(content_type_for(&path)),
+            );
+            response
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dataset, api_response, handle, site_file, update_event};
+    use crate::ServeConfig;
+    use crate::model::Forecast;
+    use crate::series::Snapshot;
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use http_body_util::BodyExt;
+    use hyper::header;
+    use hyper::{Method, Request, StatusCode};
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::watch;
+
+    fn dataset() -> Dataset {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 29).expect("valid date");
+        let dates = start.iter_days().take(10).collect::<Vec<_>>();
+        let forecast_dates = dates[9].iter_days().skip(1).take(3).collect::<Vec<_>>();
+        Dataset {
+            occupied_km2: (0..10)
+                .map(|day| f64::from(day).mul_add(10.0, 100_000.0))
+                .collect(),
+            dates,
+            forecast: Some(Forecast {
+                dates: forecast_dates,
+                mean: vec![100_100.0, 100_110.0, 100_120.0],
+                lower: vec![100_000.0; 3],
+                upper: vec![100_200.0; 3],
+                variance: vec![1.0; 3],
+                granularity: crate::series::Granularity::Day,
+                training: None,
+            }),
+            latest_time: Some(
+                Utc.with_ymd_and_hms(2024, 2, 7, 10, 0, 0)
+                    .single()
+                    .expect("valid time"),
+            ),
+            provisional: None,
+            precision: crate::constants::Precision::default(),
+        }
+    }
+
+    fn body_json(
+        response: hyper::Response<http_body_util::Full<bytes::Bytes>>,
+    ) -> serde_json::Value {
+        let bytes = futures::executor::block_on(response.into_body().collect())
+            .expect("body should collect")
+            .to_bytes();
+        serde_json::from_slice(&bytes).expect("body should be JSON")
+    }
+
+    #[test]
+    fn api_filters_aggregates_and_supports_conditional_requests() {
+        let data = dataset();
+        let max_age = Duration::from_mins(5);
+        let response = api_response(
+            &data,
+            "/v1/series",
+            "from=2024-01-30&granularity=weekly",
+            None,
+            max_age,
+        );
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "public, max-age=300"
+        );
+        let etag = response.headers()[header::ETAG]
+            .to_str()
+            .expect("ascii etag")
+            .to_string();
+        let json = body_json(response);
+        let points = json["points"].as_array().expect("points array");
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0]["date"], "2024-02-04");
+        assert_eq!(points[1]["date"], "2024-02-07");
+
+        let cached = api_response(
+            &data,
+            "/v1/series",
+            "from=2024-01-30&granularity=weekly",
+            Some(&etag),
+            max_age,
+        );
+        assert_eq!(cached.status(), StatusCode::NOT_MODIFIED);
+
+        let forecast = body_json(api_response(
+            &data,
+            "/v1/forecast",
+            "horizon=2",
+            None,
+            max_age,
+        ));
+        assert_eq!(forecast["horizon_days"], 2);
+
+        let summary = body_json(api_response(&data, "/v1/summary", "", None, max_age));
+        assert_eq!(summary["daily_change_km2"], 10.0);
+        assert_eq!(summary["weekly_change_km2"], 70.0);
+        // 100 090 км² — 16,583547…% Украины, в ответе четыре знака после запятой.
+        assert_eq!(summary["ukraine_percent"], 16.5835);
+        assert_eq!(summary["forecast"]["date"], "2024-02-10");
+
+        let bad = api_response(&data, "/v1/series", "granularity=hourly", None, max_age);
+        assert_eq!(bad.status(), StatusCode::BAD_REQUEST);
+        let missing = api_response(&data, "/v1/unknown", "", None, max_age);
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn update_event_carries_only_new_days() {
+        let current = dataset();
+        let previous = Dataset {
+            dates: current.dates[..8].to_vec(),
+            occupied_km2: current.occupied_km2[..8].to_vec(),
+            forecast: None,
+            ..current.clone()
+        };
+        let event = update_event(&previous, &current);
+        let data = event
+            .strip_prefix("event: update\ndata: ")
+            .and_then(|rest| rest.strip_suffix("\n\n"))
+            .expect("SSE frame");
+        let json: serde_json::Value = serde_json::from_str(data).expect("event data is JSON");
+        let points = json["points"].as_array().expect("points array");
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0]["date"], "2024-02-06");
+        assert_eq!(json["summary"]["latest_date"], "2024-02-07");
+    }
+
+    #[test]
+    fn provisional_point_is_shown_only_while_ahead_of_history() {
+        let data = dataset();
+        let snapshot = |hour| Snapshot {
+            time: Utc
+                .with_ymd_and_hms(2024, 2, 7, hour, 0, 0)
+                .single()
+                .expect("valid time"),
+            occupied: 100_095.0,
+            unspecified: 0.0,
+        };
+        assert!(data.with_provisional(snapshot(9)).is_none());
+
+        let live = data
+            .with_provisional(snapshot(18))
+            .expect("newer snapshot is provisional");
+        assert!(live.with_provisional(snapshot(18)).is_none());
+        let event = update_event(&data, &live);
+        assert!(event.contains(r#""provisional":{"time":"2024-02-07T18:00:00Z""#));
+        assert!(event.contains(r#""points":[]"#));
+
+        let summary = body_json(api_response(
+            &live,
+            "/v1/summary",
+            "",
+            None,
+            Duration::from_mins(5),
+        ));
+        assert_eq!(summary["provisional"]["occupied_km2"], 100_095.0);
+        assert_eq!(summary["occupied_km2"], 100_090.0);
+    }
+
+    #[test]
+    fn static_paths_stay_inside_site_dir() {
+        let site = Path::new("dist");
+        assert_eq!(site_file(site, "/"), Some(site.join("index.html")));
+        assert_eq!(
+            site_file(site, "/history.csv"),
+            Some(site.join("history.csv"))
+        );
+        assert_eq!(site_file(site, "/../Cargo.toml"), None);
+        assert_eq!(site_file(site, "/.rua-instance"), None);
+        assert_eq!(site_file(site, "/.rua-remote/history.csv"), None);
+        assert_eq!(site_file(site, "/.history.csv.rua-lock"), None);
+    }
+
+    #[tokio::test]
+    async fn static_files_answer_get_and_head_with_their_content_type() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        std::fs::write(temp.path().join("index.html"), "<!doctype html>")
+            .expect("index should be written");
+        let config = ServeConfig {
+            bind: "127.0.0.1:0".parse().expect("valid address"),
+            csv: temp.path().join("history.csv"),
+            forecast_csv: temp.path().join("forecast.csv"),
+            site_dir: temp.path().to_path_buf(),
+            cache_max_age: Duration::from_mins(5),
+            reload_interval: None,
+            live_interval: None,
+            precision: crate::constants::Precision::default(),
+        };
+        let (_updates, receiver) = watch::channel(Arc::new(dataset()));
+        let request = |method: Method, uri: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(())
+                .expect("valid request")
+        };
+
+        let head = handle(&receiver, &config, &request(Method::HEAD, "/")).await;
+        assert_eq!(head.status(), StatusCode::OK);
+        assert_eq!(head.headers()[header::CONTENT_LENGTH], "15");
+        assert_eq!(
+            head.headers()[header::CONTENT_TYPE],
+            "text/html; charset=utf-8"
+        );
+        let body = head.into_body().collect().await.expect("body").to_bytes();
+        assert!(body.is_empty());
+
+        let post = handle(&receiver, &config, &request(Method::POST, "/")).await;
+        assert_eq!(post.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(post.headers()[header::ALLOW], "GET, HEAD");
+
+        // Самостоятельно размещённые шрифты: браузер не применит CSS с `octet-stream`.
+        let fonts = temp.path().join("assets/fonts");
+        std::fs::create_dir_all(&fonts).expect("fonts dir");
+        std::fs::write(fonts.join("1a2b3c4d.css"), "@font-face {}").expect("stylesheet");
+        std::fs::write(fonts.join("plex.woff2"), "font").expect("font");
+        for (uri, content_type) in [
+            ("/assets/fonts/1a2b3c4d.css", "text/css; charset=utf-8"),
+            ("/assets/fonts/plex.woff2", "font/woff2"),
+        ] {
+            let response = handle(&receiver, &config, &request(Method::GET, uri)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[header::CONTENT_TYPE], content_type);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Подбор гиперпараметров trend filter перебором по сетке с оценкой на бэктесте.
+
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use itertools::iproduct;
+use toml_edit::DocumentMut;
+
+use crate::constants::format_csv_float;
+use crate::model::{self, BacktestConfig, TrendFilterConfig};
+
+const TREND_FILTER_SECTION: &str = "trend_filter";
+
+/// Значения гиперпараметров, декартово произведение которых перебирается.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TuneGrid {
+    pub lambda: Vec<f64>,
+    pub epsilon: Vec<f64>,
+    pub huber_delta: Vec<f64>,
+    pub damping: Vec<f64>,
+}
+
+impl TuneGrid {
+    /// Все комбинации сетки поверх базовой конфигурации (остальные поля не меняются).
+    pub fn configs(&self, base: TrendFilterConfig) -> Vec<TrendFilterConfig> {
+        iproduct!(
+            &self.lambda,
+            &self.epsilon,
+            &self.huber_delta,
+            &self.damping
+        )
+        .map(
+            |(&lambda, &epsilon, &huber_delta, &damping)| TrendFilterConfig {
+                lambda,
+                epsilon,
+                huber_delta,
+                damping,
+                ..base
+            },
+        )
+        .collect()
+    }
+}
+
+/// Строка таблицы лидеров: конфигурация и её ошибка на бэктесте (MAE, км²).
+#[derive(Clone, Copy, Debug)]
+pub struct TuneResult {
+    pub config: TrendFilterConfig,
+    pub loss: f64,
+}
+
+/// Оценивает каждую комбинацию сетки и возвращает результаты по возрастанию ошибки.
+/// Комбинации, на которых обучение не сошлось, пропускаются с предупреждением.
+pub fn grid_search(
+    dates: &[NaiveDate],
+    values: &[f64],
+    base: TrendFilterConfig,
+    grid: &TuneGrid,
+    backtest: BacktestConfig,
+) -> Result<Vec<TuneResult>, String> {
+    let mut results = Vec::new();
+    for config in grid.configs(base) {
+        match model::backtest_trend_filter_from_series(dates, values, config, backtest) {
+            Ok(loss) if loss.is_finite() => results.push(TuneResult { config, loss }),
+            Ok(_) => tracing::warn!(
+                lambda = config.lambda,
+                epsilon = config.epsilon,
+                huber_delta = config.huber_delta,
+                damping = config.damping,
+                "Backtest loss is not finite, skipping"
+            ),
+            Err(err) => tracing::warn!(
+                lambda = config.lambda,
+                epsilon = config.epsilon,
+                huber_delta = config.huber_delta,
+                damping = config.damping,
+                error = %err,
+                "Backtest failed, skipping"
+            ),
+        }
+    }
+    if results.is_empty() {
+        return Err("no grid point produced a valid backtest".to_string());
+    }
+    results.sort_by(|left, right| left.loss.total_cmp(&right.loss));
+    Ok(results)
+}
+
+pub fn write_leaderboard_csv(results: &[TuneResult], output_path: &Path) -> Result<(), String> {
+    if let Some(parent) = output_path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    let mut writer = csv::Writer::from_path(output_path)
+        .map_err(|err| format!("Failed to open {}: {err}", output_path.display()))?;
+    writer
+        .write_record([
+            "rank",
+            "lambda",
+            "epsilon",
+            "huber_delta",
+            "damping",
+            "mae_km2",
+        ])
+        .map_err(|err| err.to_string())?;
+    for (idx, result) in results.iter().enumerate() {
+        writer
+            .write_record([
+                (idx + 1).to_string(),
+                format_csv_float(result.config.lambda),
+                format_csv_float(result.config.epsilon),
+                format_csv_float(result.config.huber_delta),
+                format_csv_float(result.config.damping),
+                format_csv_float(result.loss),
+            ])
+            .map_err(|err| err.to_string())?;
+    }
+    writer.flush().map_err(|err| err.to_string())
+}
+
+/// Записывает параметры победителя в секцию `[trend_filter]` конфигурационного файла.
+/// Меняются только четыре подобранных ключа: комментарии, порядок и остальные ключи остаются.
+pub fn persist_trend_filter(config_path: &Path, config: &TrendFilterConfig) -> Result<(), String> {
+    let raw = fs::read_to_string(config_path)
+        .map_err(|err| format!("Failed to read {}: {err}", config_path.display()))?;
+    let mut document = raw
+        .parse::<DocumentMut>()
+        .map_err(|err| format!("Failed to parse {}: {err}", config_path.display()))?;
+
+    let section = document
+        .entry(TREND_FILTER_SECTION)
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .ok_or_else(|| {
+            format!(
+                "{}: {TREND_FILTER_SECTION} is not a table",
+                config_path.display()
+            )
+        })?;
+    for (key, value) in [
+        ("lambda", config.lambda),
+        ("epsilon", config.epsilon),
+        ("huber_delta", config.huber_delta),
+        ("damping", config.damping),
+    ] {
+        // Присваивание на месте сохраняет комментарий над ключом, `insert` его бы сбросил.
+        match section.get_mut(key) {
+            Some(item) => *item = toml_edit::value(value),
+            None => {
+                section.insert(key, toml_edit::value(value));
+            }
+        }
+    }
+
+    fs::write(config_path, document.to_string())
+        .map_err(|err| format!("Failed to write {}: {err}", config_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TuneGrid, persist_trend_filter};
+    use crate::model::TrendFilterConfig;
+
+    #[test]
+    fn grid_expands_to_cartesian_product() {
+        let grid = TuneGrid {
+            lambda: vec![1.0, 5.0],
+            epsilon: vec![1e-3],
+            huber_delta: vec![0.0, 2.0],
+            damping: vec![0.9, 1.0],
+        };
+        let configs = grid.configs(TrendFilterConfig::default());
+        assert_eq!(configs.len(), 8);
+        let last = configs.last().expect("grid is not empty");
+        assert_eq!(
+            (last.lambda, last.huber_delta, last.damping),
+            (5.0, 2.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn persist_updates_tuned_keys_and_keeps_the_rest() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let path = temp.path().join("tune.toml");
+        std::fs::write(
+            &path,
+            "# Прогноз для страницы\nmode = \"forecast\"\nmodel = \"trend-filter\"\n\n[trend_filter]\n# Подобрано tune\nlambda = 5.0\nhuber = 1.0\n",
+        )
+        .expect("config should be written");
+        let config = TrendFilterConfig {
+            lambda: 20.0,
+            damping: 0.9,
+            ..TrendFilterConfig::default()
+        };
+        persist_trend_filter(&path, &config).expect("persist should succeed");
+        let raw = std::fs::read_to_string(&path).expect("config should be readable");
+        let document = raw
+            .parse::<toml::Table>()
+            .expect("config should stay valid TOML");
+
+        assert!(raw.starts_with("# Прогноз для страницы\nmode = \"forecast\"\n"));
+        assert!(raw.contains("# Подобрано tune\nlambda = 20.0\n"));
+        let section = document["trend_filter"]
+            .as_table()
+            .expect("trend_filter table");
+        assert_eq!(section["lambda"].as_float(), Some(20.0));
+        assert_eq!(section["damping"].as_float(), Some(0.9));
+        assert_eq!(section["huber"].as_float(), Some(1.0));
+    }
+}
@@ -0,0 +1,118 @@
+//! WASM-пакет расчётного ядра графика: страница пересчитывает сглаживание и прореживание
+//! линии «Ср. изменение» без пересборки HTML.
+//!
+//! Ядро — тот же крейт `rua-kernel`, что и в `rua-report`, поэтому график на сервере и
+//! пересчёт в браузере совпадают. Функции экспортируются с C ABI без `wasm-bindgen`: страница
+//! выделяет буферы `f64` через `rua_alloc`, пишет в них ряд и читает результат из памяти модуля.
+//!
+//! Сборка: `cargo build -p rua-wasm --release --target wasm32-unknown-unknown`, итог —
+//! `target/wasm32-unknown-unknown/release/rua_wasm.wasm`.
+
+pub use rua_kernel as kernel;
+
+use core::slice;
+
+/// Выделяет буфер на `len` значений `f64`; освобождается через [`rua_free`] с тем же `len`.
+#[unsafe(no_mangle)]
+pub extern "C" fn rua_alloc(len: usize) -> *mut f64 {
+    let mut buffer = Vec::<f64>::with_capacity(len);
+    let ptr = buffer.as_mut_ptr();
+    core::mem::forget(buffer);
+    ptr
+}
+
+/// Освобождает буфер из [`rua_alloc`].
+///
+/// # Safety
+///
+/// `ptr` получен из `rua_alloc(len)` и ещё не освобождён.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rua_free(ptr: *mut f64, len: usize) {
+    // SAFETY: буфер выделен `rua_alloc` с ёмкостью `len`; длина 0 — содержимое не трогается.
+    drop(unsafe { Vec::from_raw_parts(ptr, 0, len) });
+}
+
+/// Сглаживает `len` значений из `values` в `out`; `NaN` — окно недозаполнено.
+/// `method`: `0` — скользящее среднее, `1` — экспоненциальное, `2` — LOESS; иначе всё `NaN`.
+///
+/// # Safety
+///
+/// `values` и `out` указывают на буферы минимум из `len` значений `f64`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rua_smooth(
+    method: u32,
+    window: usize,
+    min_periods: usize,
+    values: *const f64,
+    len: usize,
+    out: *mut f64,
+) {
+    // SAFETY: размеры буферов гарантирует вызывающий.
+    let (values, out) = unsafe {
+        (
+            slice::from_raw_parts(values, len),
+            slice::from_raw_parts_mut(out, len),
+        )
+    };
+    let smoothed = match method {
+        0 => kernel::moving_average(values, window, min_periods),
+        1 => kernel::exponential_average(values, window, min_periods),
+        2 => kernel::local_linear(values, window, min_periods),
+        _ => vec![None; len],
+    };
+    for (slot, value) in out.iter_mut().zip(smoothed) {
+        *slot = value.unwrap_or(f64::NAN);
+    }
+}
+
+/// Пишет в `out` индексы точек, сохраняющих минимум и максимум по бакетам (как на сервере),
+/// и возвращает их число; `max_points < 3` оставляет все точки.
+///
+/// # Safety
+///
+/// `values` и `out` указывают на буферы минимум из `len` значений `f64`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rua_downsample(
+    values: *const f64,
+    len: usize,
+    max_points: usize,
+    out: *mut f64,
+) -> usize {
+    // SAFETY: размеры буферов гарантирует вызывающий; индексов не больше `len`.
+    let (values, out) = unsafe {
+        (
+            slice::from_raw_parts(values, len),
+            slice::from_raw_parts_mut(out, len),
+        )
+    };
+    let indices = kernel::downsample_min_max_indices(values, max_points);
+    for (slot, idx) in out.iter_mut().zip(&indices) {
+        *slot = u32::try_from(*idx).map_or(f64::NAN, f64::from);
+    }
+    indices.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rua_alloc, rua_downsample, rua_free, rua_smooth};
+
+    #[test]
+    fn exports_match_the_kernel_through_raw_buffers() {
+        let values = [0.0, 10.0, 20.0, 30.0, 40.0];
+        let input = rua_alloc(values.len());
+        let out = rua_alloc(values.len());
+        // SAFETY: оба буфера выделены на `values.len()` значений.
+        unsafe {
+            input.copy_from_nonoverlapping(values.as_ptr(), values.len());
+            rua_smooth(0, 3, 3, input, values.len(), out);
+            let smoothed = std::slice::from_raw_parts(out, values.len());
+            assert!(smoothed[0].is_nan() && smoothed[4].is_nan());
+            assert!((smoothed[2] - 20.0).abs() < 1e-12);
+
+            assert_eq!(rua_downsample(input, values.len(), 0, out), values.len());
+            assert!((*out.add(4) - 4.0).abs() < 1e-12);
+            rua_free(input, values.len());
+            rua_free(out, values.len());
+        }
+    }
+}